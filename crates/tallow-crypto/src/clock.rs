@@ -0,0 +1,235 @@
+//! Pluggable time source
+//!
+//! Expiry checks (signed pre-keys, relay tokens, idle rooms) read the time
+//! through [`Clock`] instead of calling `SystemTime::now()` directly, so
+//! tests can drive them with a [`MockClock`] rather than sleeping.
+//! [`TimeRange`] describes a daily window (e.g. working hours) for
+//! settings that vary with the time of day.
+
+use crate::error::CryptoError;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A source of wall-clock and monotonic time
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Wall-clock time in seconds since the Unix epoch
+    fn unix_secs(&self) -> u64;
+
+    /// Monotonic time, for measuring elapsed durations
+    fn instant(&self) -> Instant;
+}
+
+/// The real system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn unix_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Both readings advance together: [`advance`](Self::advance) by one
+/// second moves `unix_secs` forward by one and `instant` by one second.
+#[derive(Debug)]
+pub struct MockClock {
+    unix_start: u64,
+    base: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// Create a clock reading `unix_secs` seconds since the epoch
+    pub fn new(unix_secs: u64) -> Self {
+        Self {
+            unix_start: unix_secs,
+            base: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, by: Duration) {
+        let mut elapsed = self
+            .elapsed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *elapsed += by;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self
+            .elapsed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Clock for MockClock {
+    fn unix_secs(&self) -> u64 {
+        self.unix_start.saturating_add(self.elapsed().as_secs())
+    }
+
+    fn instant(&self) -> Instant {
+        self.base + self.elapsed()
+    }
+}
+
+/// Seconds in a day
+const DAY_SECS: u32 = 86_400;
+
+/// A daily time-of-day window, written `HH:MM-HH:MM`
+///
+/// The start is inclusive and the end exclusive. A window whose end is
+/// earlier than its start wraps past midnight, so `22:00-06:00` covers
+/// the night. `24:00` is accepted as an end time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeRange {
+    start: u32,
+    end: u32,
+}
+
+impl TimeRange {
+    /// Whether `secs_of_day` (seconds since midnight) falls in the window
+    pub fn contains(&self, secs_of_day: u32) -> bool {
+        let t = secs_of_day % DAY_SECS;
+        if self.start < self.end {
+            (self.start..self.end).contains(&t)
+        } else {
+            t >= self.start || t < self.end
+        }
+    }
+
+    /// Length of the window
+    pub fn duration(&self) -> Duration {
+        let secs = if self.start < self.end {
+            self.end - self.start
+        } else {
+            DAY_SECS - self.start + self.end
+        };
+        Duration::from_secs(secs.into())
+    }
+}
+
+impl std::str::FromStr for TimeRange {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, CryptoError> {
+        let invalid = || {
+            CryptoError::Serialization(format!("invalid time range '{}' (expected HH:MM-HH:MM)", s))
+        };
+        let (start, end) = s.trim().split_once('-').ok_or_else(invalid)?;
+        let start = parse_hh_mm(start).filter(|t| *t < DAY_SECS);
+        let end = parse_hh_mm(end);
+        match (start, end) {
+            (Some(start), Some(end)) if start != end => Ok(Self { start, end }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = CryptoError;
+
+    fn try_from(s: String) -> Result<Self, CryptoError> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 3600,
+            self.start % 3600 / 60,
+            self.end / 3600,
+            self.end % 3600 / 60
+        )
+    }
+}
+
+impl From<TimeRange> for String {
+    fn from(range: TimeRange) -> Self {
+        range.to_string()
+    }
+}
+
+/// Parse `HH:MM` into seconds since midnight, allowing `24:00`
+fn parse_hh_mm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    if h.is_empty() || h.len() > 2 || m.len() != 2 {
+        return None;
+    }
+    let h: u32 = h.parse().ok()?;
+    let m: u32 = m.parse().ok()?;
+    if m >= 60 || h > 24 || (h == 24 && m != 0) {
+        return None;
+    }
+    Some(h * 3600 + m * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advances_both_readings() {
+        let clock = MockClock::new(1_000);
+        let start = clock.instant();
+        assert_eq!(clock.unix_secs(), 1_000);
+
+        clock.advance(Duration::from_millis(1_500));
+        assert_eq!(clock.unix_secs(), 1_001);
+        assert_eq!(clock.instant() - start, Duration::from_millis(1_500));
+
+        // Nothing moves on its own
+        assert_eq!(clock.unix_secs(), 1_001);
+    }
+
+    #[test]
+    fn test_time_range_parse_and_contains() {
+        let work: TimeRange = "09:00-17:30".parse().unwrap();
+        assert!(work.contains(9 * 3600));
+        assert!(work.contains(17 * 3600 + 29 * 60));
+        assert!(!work.contains(17 * 3600 + 30 * 60));
+        assert!(!work.contains(8 * 3600));
+        assert_eq!(work.to_string(), "09:00-17:30");
+
+        // Wraps past midnight
+        let night: TimeRange = "22:00-06:00".parse().unwrap();
+        assert!(night.contains(23 * 3600));
+        assert!(night.contains(0));
+        assert!(!night.contains(12 * 3600));
+        assert_eq!(night.duration(), Duration::from_secs(8 * 3600));
+
+        let evening: TimeRange = "18:00-24:00".parse().unwrap();
+        assert!(evening.contains(DAY_SECS - 1));
+        assert!(!evening.contains(0));
+
+        let all_day: TimeRange = "00:00-24:00".parse().unwrap();
+        assert!(all_day.contains(12 * 3600));
+
+        for bad in [
+            "",
+            "9-17",
+            "09:00",
+            "25:00-26:00",
+            "09:60-10:00",
+            "10:00-10:00",
+        ] {
+            assert!(bad.parse::<TimeRange>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! RNG health checks before long-term key generation
+//!
+//! A freshly booted VM or embedded board can hand out randomness before the
+//! kernel pool is seeded, and a broken RNG shim can return constant output.
+//! Keys generated from either can be enumerated by an attacker, and unlike
+//! session keys an identity key lives for years. [`check_health`] draws a
+//! sample from the OS RNG and runs the FIPS 140-2 power-up statistics
+//! (monobit, poker and long-run) plus a repeated-block check on it.
+//!
+//! Statistical tests cannot prove output is random; they only catch
+//! sources that are badly broken. The FIPS bounds reject a healthy sample
+//! about once in ten thousand draws, so a failing sample is redrawn once
+//! before the check gives up.
+
+use crate::error::{CryptoError, Result};
+use rand_core::{OsRng, RngCore};
+
+/// Bits in one test sample (the FIPS 140-2 sample size)
+pub const SAMPLE_BITS: usize = 20_000;
+
+/// Bytes in one test sample
+pub const SAMPLE_BYTES: usize = SAMPLE_BITS / 8;
+
+/// Samples drawn before the check gives up
+const ATTEMPTS: usize = 2;
+
+/// Ones count must fall strictly inside this range
+const MONOBIT_BOUNDS: (u32, u32) = (9_725, 10_275);
+
+/// Poker statistic must fall strictly inside this range
+const POKER_BOUNDS: (f64, f64) = (2.16, 46.17);
+
+/// A run of identical bits this long fails the sample
+const LONG_RUN: usize = 26;
+
+/// Block size for the repeated-output check
+const BLOCK: usize = 16;
+
+/// Why a sample was rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthFailure {
+    /// Two consecutive blocks were identical (stuck or looping output)
+    Repetition,
+    /// Too many or too few one bits
+    Monobit(u32),
+    /// 4-bit patterns too skewed, or too evenly spread
+    Poker(f64),
+    /// Run of identical bits of this length
+    LongRun(usize),
+}
+
+impl std::fmt::Display for HealthFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Repetition => write!(f, "output repeats"),
+            Self::Monobit(ones) => write!(f, "{} of {} bits set", ones, SAMPLE_BITS),
+            Self::Poker(x) => write!(f, "poker statistic {:.2} out of range", x),
+            Self::LongRun(len) => write!(f, "run of {} identical bits", len),
+        }
+    }
+}
+
+/// Result of a passing health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntropyReport {
+    /// Whether the kernel pool was initialized before the check
+    /// (`None` where the platform can't tell)
+    pub kernel_pool_ready: Option<bool>,
+    /// Samples drawn before one passed
+    pub attempts: usize,
+}
+
+/// Run the statistical tests on one sample
+pub fn test_sample(sample: &[u8; SAMPLE_BYTES]) -> std::result::Result<(), HealthFailure> {
+    let mut blocks = sample.chunks_exact(BLOCK);
+    if let Some(first) = blocks.next() {
+        let mut prev = first;
+        for block in blocks {
+            if block == prev {
+                return Err(HealthFailure::Repetition);
+            }
+            prev = block;
+        }
+    }
+
+    let ones: u32 = sample.iter().map(|b| b.count_ones()).sum();
+    if ones <= MONOBIT_BOUNDS.0 || ones >= MONOBIT_BOUNDS.1 {
+        return Err(HealthFailure::Monobit(ones));
+    }
+
+    let mut nibbles = [0u64; 16];
+    for byte in sample {
+        nibbles[(byte >> 4) as usize] += 1;
+        nibbles[(byte & 0x0f) as usize] += 1;
+    }
+    let count = (SAMPLE_BITS / 4) as f64;
+    let squares: u64 = nibbles.iter().map(|f| f * f).sum();
+    let poker = 16.0 / count * squares as f64 - count;
+    if poker <= POKER_BOUNDS.0 || poker >= POKER_BOUNDS.1 {
+        return Err(HealthFailure::Poker(poker));
+    }
+
+    let mut run = 0;
+    let mut last = None;
+    for byte in sample {
+        for shift in (0..8).rev() {
+            let bit = (byte >> shift) & 1;
+            run = if last == Some(bit) { run + 1 } else { 1 };
+            last = Some(bit);
+            if run >= LONG_RUN {
+                return Err(HealthFailure::LongRun(run));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check the output of `rng`, redrawing once if the first sample fails
+///
+/// Returns the number of samples drawn.
+pub fn check_rng<R: RngCore + ?Sized>(rng: &mut R) -> Result<usize> {
+    let mut sample = [0u8; SAMPLE_BYTES];
+    let mut failure = None;
+    for attempt in 1..=ATTEMPTS {
+        rng.try_fill_bytes(&mut sample)
+            .map_err(|e| CryptoError::KeyGeneration(format!("RNG unavailable: {}", e)))?;
+        match test_sample(&sample) {
+            Ok(()) => return Ok(attempt),
+            Err(e) => failure = Some(e),
+        }
+    }
+    Err(CryptoError::KeyGeneration(format!(
+        "RNG health check failed: {}",
+        failure.map_or_else(String::new, |f| f.to_string())
+    )))
+}
+
+/// Check the OS RNG before generating long-term keys
+///
+/// Drawing from the OS RNG blocks until the kernel pool is seeded, so a
+/// passing check also means generation won't run on an unseeded pool.
+pub fn check_health() -> Result<EntropyReport> {
+    let kernel_pool_ready = kernel_pool_ready();
+    let attempts = check_rng(&mut OsRng)?;
+    Ok(EntropyReport {
+        kernel_pool_ready,
+        attempts,
+    })
+}
+
+/// Whether the kernel entropy pool is initialized, without blocking
+///
+/// `getrandom(2)` with `GRND_NONBLOCK` fails with `EAGAIN` until the pool
+/// has been seeded once.
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+fn kernel_pool_ready() -> Option<bool> {
+    let mut byte = 0u8;
+    // SAFETY: the buffer is a valid, writable local of exactly one byte.
+    let ret = unsafe {
+        libc::getrandom(
+            (&mut byte as *mut u8).cast::<libc::c_void>(),
+            1,
+            libc::GRND_NONBLOCK,
+        )
+    };
+    if ret >= 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EAGAIN) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kernel_pool_ready() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// RNG that returns the same byte forever
+    struct ConstantRng(u8);
+
+    impl RngCore for ConstantRng {
+        fn next_u32(&mut self) -> u32 {
+            u32::from_ne_bytes([self.0; 4])
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            u64::from_ne_bytes([self.0; 8])
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(self.0);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_constant_rng_is_rejected() {
+        for byte in [0x00, 0xff, 0x5a] {
+            let err = check_rng(&mut ConstantRng(byte)).unwrap_err();
+            assert!(err.to_string().contains("health check failed"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_degenerate_samples_are_caught() {
+        // A short cycle never repeats a whole block, but is far from random
+        let mut cycle = [0u8; SAMPLE_BYTES];
+        for (i, b) in cycle.iter_mut().enumerate() {
+            *b = b"tallow!"[i % 7];
+        }
+        assert!(matches!(
+            test_sample(&cycle),
+            Err(HealthFailure::Monobit(_) | HealthFailure::Poker(_))
+        ));
+
+        // Biased output: ANDing two random bytes leaves ~25% of bits set
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut biased = [0u8; SAMPLE_BYTES];
+        rng.fill_bytes(&mut biased);
+        for b in biased.iter_mut() {
+            *b &= rng.next_u32() as u8;
+        }
+        assert!(matches!(
+            test_sample(&biased),
+            Err(HealthFailure::Monobit(_))
+        ));
+
+        // A single long stuck stretch inside otherwise good output
+        let mut stuck = [0u8; SAMPLE_BYTES];
+        StdRng::seed_from_u64(8).fill_bytes(&mut stuck);
+        stuck[100..104].fill(0);
+        assert!(matches!(
+            test_sample(&stuck),
+            Err(HealthFailure::LongRun(_))
+        ));
+    }
+
+    #[test]
+    fn test_csprng_passes() {
+        for seed in 0..20 {
+            assert_eq!(
+                check_rng(&mut StdRng::seed_from_u64(seed)).map(|_| ()),
+                Ok(())
+            );
+        }
+        let report = check_health().unwrap();
+        assert!(report.attempts >= 1);
+        // A running test host has long since seeded its pool
+        assert_ne!(report.kernel_pool_ready, Some(false));
+    }
+}
@@ -0,0 +1,145 @@
+//! Selectable content hash for file identity
+//!
+//! BLAKE3 is the default everywhere. SHA3-256 exists for interop with
+//! systems that only accept FIPS 202 digests; both produce 32 bytes, so the
+//! algorithm has to travel alongside the digest for the other side to know
+//! which one to recompute.
+
+use serde::{Deserialize, Serialize};
+
+/// Hash algorithm used for file content digests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    /// BLAKE3 (default)
+    #[default]
+    Blake3,
+    /// SHA3-256 (FIPS 202)
+    Sha3_256,
+}
+
+impl HashAlgorithm {
+    /// Display name, also accepted by [`parse`](Self::parse)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blake3 => "blake3",
+            Self::Sha3_256 => "sha3-256",
+        }
+    }
+
+    /// Parse a name, ignoring case, `-` and `_`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "blake3" | "b3" => Some(Self::Blake3),
+            "sha3" | "sha3256" => Some(Self::Sha3_256),
+            _ => None,
+        }
+    }
+
+    /// Start a streaming hash
+    pub fn hasher(self) -> Hasher {
+        match self {
+            Self::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            Self::Sha3_256 => Hasher::Sha3_256(super::sha3::StreamHasher::new()),
+        }
+    }
+
+    /// Hash `data` in one call
+    pub fn digest(self, data: &[u8]) -> [u8; 32] {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Streaming hasher for a [`HashAlgorithm`]
+#[derive(Clone)]
+pub enum Hasher {
+    /// BLAKE3 state
+    Blake3(Box<blake3::Hasher>),
+    /// SHA3-256 state
+    Sha3_256(super::sha3::StreamHasher),
+}
+
+impl Hasher {
+    /// Algorithm this hasher computes
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Self::Blake3(_) => HashAlgorithm::Blake3,
+            Self::Sha3_256(_) => HashAlgorithm::Sha3_256,
+        }
+    }
+
+    /// Update with more data
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Self::Sha3_256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Finalize and return the 32-byte digest
+    pub fn finalize(self) -> [u8; 32] {
+        match self {
+            Self::Blake3(hasher) => hasher.finalize().into(),
+            Self::Sha3_256(hasher) => hasher.finalize(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Hasher").field(&self.algorithm()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_blake3() {
+        assert_eq!(HashAlgorithm::default(), HashAlgorithm::Blake3);
+        assert_eq!(
+            HashAlgorithm::default().digest(b"data"),
+            crate::hash::hash(b"data")
+        );
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        for algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha3_256] {
+            let mut hasher = algorithm.hasher();
+            hasher.update(b"hello ");
+            hasher.update(b"world");
+            assert_eq!(hasher.finalize(), algorithm.digest(b"hello world"));
+        }
+        assert_eq!(
+            HashAlgorithm::Sha3_256.digest(b"hello world"),
+            crate::hash::sha3_256(b"hello world")
+        );
+        assert_ne!(
+            HashAlgorithm::Sha3_256.digest(b"x"),
+            HashAlgorithm::Blake3.digest(b"x")
+        );
+    }
+
+    #[test]
+    fn test_parse_names() {
+        for algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha3_256] {
+            assert_eq!(HashAlgorithm::parse(algorithm.name()), Some(algorithm));
+        }
+        assert_eq!(
+            HashAlgorithm::parse("SHA3_256"),
+            Some(HashAlgorithm::Sha3_256)
+        );
+        assert_eq!(HashAlgorithm::parse("sha256"), None);
+    }
+}
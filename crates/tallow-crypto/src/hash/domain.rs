@@ -54,6 +54,9 @@ pub const DOMAIN_ROOM: &str = "tallow.room.v1";
 /// Domain separator for hybrid key combination
 pub const DOMAIN_HYBRID_COMBINE: &str = "tallow.hybrid.combine.v1";
 
+/// Domain separator for the stand-in secret of a failed hybrid KEM component
+pub const DOMAIN_HYBRID_REJECT: &str = "tallow.hybrid.reject.v1";
+
 /// Domain separator for password hashing
 pub const DOMAIN_PASSWORD: &str = "tallow.password.v1";
 
@@ -64,10 +67,48 @@ pub const DOMAIN_SAS: &str = "tallow.sas.v1";
 pub const DOMAIN_HANDSHAKE_TRANSCRIPT: &str = "tallow.handshake.transcript.v1";
 
 /// Domain separator for session key derivation from KEM + PAKE
-pub const DOMAIN_SESSION_KEY_KEM_PAKE: &str = "tallow.session_key.kem_pake.v3";
+///
+/// Used as the HKDF-Extract salt; v4 moved the expansion to
+/// HKDF-Expand-Label with [`LABEL_SESSION_KEY`].
+pub const DOMAIN_SESSION_KEY_KEM_PAKE: &str = "tallow.session_key.kem_pake.v4";
+
+/// Domain separator for session key derivation from KEM + pre-shared key
+///
+/// The PSK-mode counterpart of [`DOMAIN_SESSION_KEY_KEM_PAKE`]; a distinct
+/// salt keeps a PSK handshake from ever agreeing with a CPace one.
+pub const DOMAIN_SESSION_KEY_KEM_PSK: &str = "tallow.session_key.kem_psk.v1";
 
 /// Domain separator for sender key confirmation tag
 pub const DOMAIN_KEY_CONFIRM_SENDER: &str = "tallow.key_confirm.sender.v1";
 
 /// Domain separator for receiver key confirmation tag
 pub const DOMAIN_KEY_CONFIRM_RECEIVER: &str = "tallow.key_confirm.receiver.v1";
+
+/// Domain separator for airgap bundle signing transcripts
+pub const DOMAIN_BUNDLE: &str = "tallow.bundle.v1";
+
+/// Domain separator for stream transfer trailer signatures
+pub const DOMAIN_STREAM_TRAILER: &str = "tallow.stream.trailer.v1";
+
+/// Domain separator for the rolling hash of a streamed manifest
+pub const DOMAIN_MANIFEST_STREAM: &str = "tallow.manifest.stream.v1";
+
+/// Domain separator for relay bearer token signatures
+pub const DOMAIN_RELAY_TOKEN: &str = "tallow.relay.token.v1";
+
+/// Domain separator for the transfer history encryption key
+pub const DOMAIN_HISTORY: &str = "tallow.history.v1";
+
+/// Domain separator for peer channel keepalive frame authentication
+pub const DOMAIN_KEEPALIVE: &str = "tallow.keepalive.v1";
+
+// HKDF-Expand-Label labels (`kdf::hkdf::expand_label` prepends "tallow ")
+
+/// Label for the handshake session key (context: transcript hash)
+pub const LABEL_SESSION_KEY: &str = "session key";
+
+/// Label for directional multi-peer pair keys (context: sender ID, receiver ID)
+pub const LABEL_PAIR_KEY: &str = "pair key";
+
+/// Label for a transfer key bound to a caller's context label (context: that label)
+pub const LABEL_TRANSFER_KEY: &str = "transfer key";
@@ -137,6 +137,7 @@ impl MerkleTree {
             leaf_hash: self.leaves[index],
             proof_hashes,
             leaf_index: index,
+            leaf_count: self.leaves.len(),
         })
     }
 
@@ -156,22 +157,53 @@ impl MerkleTree {
             return false;
         }
 
+        if proof.leaf_count == 0 {
+            return Self::verify_without_count(proof, root, leaf);
+        }
+        if proof.leaf_index >= proof.leaf_count {
+            return false;
+        }
+
+        // Walk the same levels as `build`, skipping levels where the node
+        // was promoted without a sibling
+        let mut current_hash = *leaf;
+        let mut current_index = proof.leaf_index;
+        let mut width = proof.leaf_count;
+        let mut siblings = proof.proof_hashes.iter();
+
+        while width > 1 {
+            if (current_index ^ 1) < width {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                current_hash = if current_index.is_multiple_of(2) {
+                    hash_internal(&current_hash, sibling)
+                } else {
+                    hash_internal(sibling, &current_hash)
+                };
+            }
+            current_index /= 2;
+            width = width.div_ceil(2);
+        }
+
+        siblings.next().is_none() && constant_time::ct_eq(&current_hash, root)
+    }
+
+    /// Verify a proof that does not record the tree size
+    ///
+    /// Only correct for leaves whose path never passes a promoted node.
+    fn verify_without_count(proof: &MerkleProof, root: &[u8; 32], leaf: &[u8; 32]) -> bool {
         let mut current_hash = *leaf;
         let mut current_index = proof.leaf_index;
 
         for sibling in &proof.proof_hashes {
-            let mut data = [0u8; 65];
-            data[0] = 0x01; // internal node domain tag
-            if current_index.is_multiple_of(2) {
+            current_hash = if current_index.is_multiple_of(2) {
                 // Current is left child
-                data[1..33].copy_from_slice(&current_hash);
-                data[33..65].copy_from_slice(sibling);
+                hash_internal(&current_hash, sibling)
             } else {
                 // Current is right child
-                data[1..33].copy_from_slice(sibling);
-                data[33..65].copy_from_slice(&current_hash);
-            }
-            current_hash = hash(&data);
+                hash_internal(sibling, &current_hash)
+            };
             current_index /= 2;
         }
 
@@ -179,6 +211,142 @@ impl MerkleTree {
     }
 }
 
+/// Hash an internal node: `Hash(0x01 || left || right)`
+fn hash_internal(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 65];
+    data[0] = 0x01; // internal node domain tag
+    data[1..33].copy_from_slice(left);
+    data[33..65].copy_from_slice(right);
+    hash(&data)
+}
+
+/// Incremental Merkle tree builder
+///
+/// Accepts leaf hashes one at a time and produces the same root and proofs
+/// as [`MerkleTree::build`] over the leaves pushed so far. Every leaf hash
+/// and every completed internal node is kept, so that [`prove`](Self::prove)
+/// works for any leaf: memory is O(n) hashes (about 64 bytes per leaf), not
+/// O(log n). Chunk data itself is never buffered, and the root of the
+/// unfinished right edge is recomputed from the spine of perfect subtrees
+/// on demand.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleBuilder {
+    /// `levels[h]` holds every completed node of height `h`, left to right
+    levels: Vec<Vec<[u8; 32]>>,
+    leaf_count: usize,
+}
+
+impl MerkleBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the next leaf hash
+    pub fn push_leaf(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        let mut height = 0;
+
+        loop {
+            if self.levels.len() == height {
+                self.levels.push(Vec::new());
+            }
+            let level = &mut self.levels[height];
+            level.push(node);
+            if !level.len().is_multiple_of(2) {
+                break;
+            }
+            // A pair just completed: carry its parent up one level
+            let len = level.len();
+            node = hash_internal(&level[len - 2], &level[len - 1]);
+            height += 1;
+        }
+
+        self.leaf_count += 1;
+    }
+
+    /// Number of leaves pushed so far
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Whether no leaves have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Root over the leaves pushed so far
+    ///
+    /// # Returns
+    ///
+    /// The root hash, or a zero hash if no leaves have been pushed
+    pub fn root(&self) -> [u8; 32] {
+        self.spine_below(self.levels.len()).unwrap_or([0u8; 32])
+    }
+
+    /// Generate a proof of inclusion against the current root
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the leaf to prove
+    ///
+    /// # Returns
+    ///
+    /// A Merkle proof, or None if the index is out of bounds
+    pub fn prove(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count {
+            return None;
+        }
+
+        let mut proof_hashes = Vec::new();
+        let mut current_index = index;
+        let mut width = self.leaf_count;
+        let mut height = 0;
+
+        while width > 1 {
+            let sibling_index = current_index ^ 1;
+            if sibling_index < width {
+                proof_hashes.push(self.node(height, sibling_index));
+            }
+            current_index /= 2;
+            width = width.div_ceil(2);
+            height += 1;
+        }
+
+        Some(MerkleProof {
+            leaf_hash: self.levels[0][index],
+            proof_hashes,
+            leaf_index: index,
+            leaf_count: self.leaf_count,
+        })
+    }
+
+    /// Node at `height`, position `index` of the tree over the current leaves
+    fn node(&self, height: usize, index: usize) -> [u8; 32] {
+        if (index + 1) << height <= self.leaf_count {
+            self.levels[height][index]
+        } else {
+            // The unfinished right edge: fold the smaller perfect subtrees
+            self.spine_below(height).unwrap_or([0u8; 32])
+        }
+    }
+
+    /// Fold the perfect subtrees of height below `height`, right to left
+    fn spine_below(&self, height: usize) -> Option<[u8; 32]> {
+        let mut acc: Option<[u8; 32]> = None;
+        for h in 0..height.min(self.levels.len()) {
+            if (self.leaf_count >> h) & 1 == 1 {
+                let peak = *self.levels[h].last()?;
+                acc = Some(match acc {
+                    Some(right) => hash_internal(&peak, &right),
+                    None => peak,
+                });
+            }
+        }
+        acc
+    }
+}
+
 /// Proof of inclusion for a leaf in a Merkle tree
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MerkleProof {
@@ -188,6 +356,12 @@ pub struct MerkleProof {
     pub proof_hashes: Vec<[u8; 32]>,
     /// Index of the leaf in the tree
     pub leaf_index: usize,
+    /// Number of leaves in the tree the proof was generated from
+    ///
+    /// Needed to locate promoted nodes on the path. Zero for proofs
+    /// produced before this field existed.
+    #[serde(default)]
+    pub leaf_count: usize,
 }
 
 #[cfg(test)]
@@ -230,4 +404,68 @@ mod tests {
 
         assert!(!MerkleTree::verify(&proof, &root, &wrong_leaf));
     }
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n)
+            .map(|i| hash(format!("leaf{}", i).as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn test_merkle_proof_odd_leaf_count() {
+        // Paths through promoted nodes must verify too
+        let leaves = leaves(7);
+        let tree = MerkleTree::build(leaves.clone());
+        let root = tree.root();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.prove(i).unwrap();
+            assert!(MerkleTree::verify(&proof, &root, leaf), "leaf {}", i);
+        }
+    }
+
+    #[test]
+    fn test_builder_root_matches_batch() {
+        let all = leaves(40);
+        let mut builder = MerkleBuilder::new();
+        assert_eq!(builder.root(), MerkleTree::build(vec![]).root());
+
+        for n in 1..=all.len() {
+            builder.push_leaf(all[n - 1]);
+            let batch = MerkleTree::build(all[..n].to_vec());
+            assert_eq!(builder.root(), batch.root(), "root mismatch at {}", n);
+        }
+    }
+
+    #[test]
+    fn test_builder_proofs_verify_mid_stream() {
+        let all = leaves(21);
+        let mut builder = MerkleBuilder::new();
+
+        for n in 1..=all.len() {
+            builder.push_leaf(all[n - 1]);
+            let root = builder.root();
+            let batch = MerkleTree::build(all[..n].to_vec());
+
+            for (i, leaf) in all[..n].iter().enumerate() {
+                let proof = builder.prove(i).unwrap();
+                assert!(MerkleTree::verify(&proof, &root, leaf), "{} of {}", i, n);
+                assert_eq!(proof.proof_hashes, batch.prove(i).unwrap().proof_hashes);
+            }
+            assert!(builder.prove(n).is_none());
+        }
+    }
+
+    #[test]
+    fn test_builder_proof_rejected_after_growth() {
+        let all = leaves(5);
+        let mut builder = MerkleBuilder::new();
+        for leaf in &all[..4] {
+            builder.push_leaf(*leaf);
+        }
+        let proof = builder.prove(3).unwrap();
+
+        builder.push_leaf(all[4]);
+        assert!(!MerkleTree::verify(&proof, &builder.root(), &all[3]));
+    }
 }
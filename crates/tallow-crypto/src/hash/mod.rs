@@ -3,6 +3,7 @@
 //! This module provides hash functions including BLAKE3 and SHA3,
 //! domain separation constants, and Merkle tree implementations.
 
+pub mod algorithm;
 pub mod blake3;
 pub mod domain;
 pub mod merkle;
@@ -10,5 +11,6 @@ pub mod sha3;
 
 pub use self::blake3::{derive_key, hash, keyed_hash};
 pub use self::sha3::sha3_256;
+pub use algorithm::HashAlgorithm;
 pub use domain::*;
-pub use merkle::{MerkleProof, MerkleTree};
+pub use merkle::{MerkleBuilder, MerkleProof, MerkleTree};
@@ -18,6 +18,7 @@ pub fn sha3_256(data: &[u8]) -> [u8; 32] {
 }
 
 /// Streaming SHA3-256 hasher
+#[derive(Clone)]
 pub struct StreamHasher {
     hasher: Sha3_256,
 }
@@ -1,4 +1,8 @@
 //! HKDF (HMAC-based Key Derivation Function) implementation
+//!
+//! Besides generic [`derive`], this module provides TLS 1.3-style labeled
+//! expansion ([`expand_label`]) so structured derivations share one
+//! unambiguous info-field encoding.
 
 use crate::error::{CryptoError, Result};
 use hkdf::Hkdf;
@@ -62,6 +66,83 @@ pub fn derive_multiple(
     Ok(outputs)
 }
 
+/// Prefix prepended to every [`expand_label`] label
+pub const LABEL_PREFIX: &str = "tallow ";
+
+/// HKDF-Extract with SHA-256, returning the 32-byte pseudorandom key
+///
+/// Pair with [`expand_label`] when the input key material is not already
+/// uniformly random (e.g. a concatenation of shared secrets).
+pub fn extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), ikm);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&prk);
+    out
+}
+
+/// Encode the HKDF info field used by [`expand_label`]
+///
+/// Mirrors TLS 1.3's `HkdfLabel` structure:
+///
+/// ```text
+/// u16 BE   length                  (output length in bytes)
+/// u8       label length
+/// bytes    "tallow " || label
+/// u8       context length
+/// bytes    context
+/// ```
+///
+/// Every field is length-prefixed, so distinct (label, context, length)
+/// triples can never encode to the same info string.
+pub fn hkdf_label(label: &str, context: &[u8], len: usize) -> Result<Vec<u8>> {
+    let full_label_len = LABEL_PREFIX.len() + label.len();
+    let length = u16::try_from(len)
+        .map_err(|_| CryptoError::KeyGeneration(format!("HKDF label length too large: {}", len)))?;
+    let label_len = u8::try_from(full_label_len).map_err(|_| {
+        CryptoError::KeyGeneration(format!("HKDF label too long: {} bytes", full_label_len))
+    })?;
+    let context_len = u8::try_from(context.len()).map_err(|_| {
+        CryptoError::KeyGeneration(format!("HKDF context too long: {} bytes", context.len()))
+    })?;
+
+    let mut info = Vec::with_capacity(4 + full_label_len + context.len());
+    info.extend_from_slice(&length.to_be_bytes());
+    info.push(label_len);
+    info.extend_from_slice(LABEL_PREFIX.as_bytes());
+    info.extend_from_slice(label.as_bytes());
+    info.push(context_len);
+    info.extend_from_slice(context);
+    Ok(info)
+}
+
+/// HKDF-Expand-Label with SHA-256 (TLS 1.3 style)
+///
+/// # Arguments
+///
+/// * `secret` - Pseudorandom key of at least 32 bytes (from [`extract`] or
+///   an already-uniform key such as a session key)
+/// * `label` - Purpose label; `"tallow "` is prepended automatically
+/// * `context` - Optional context bound into the output (e.g. a transcript hash)
+/// * `len` - Length of output key material in bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let key = expand_label(&session_key, "pair key", &[id_a, id_b], 32)?;
+/// ```
+pub fn expand_label(secret: &[u8], label: &str, context: &[u8], len: usize) -> Result<Vec<u8>> {
+    let info = hkdf_label(label, context, len)?;
+    let hk = Hkdf::<Sha256>::from_prk(secret).map_err(|_| {
+        CryptoError::KeyGeneration("HKDF-Expand-Label secret must be at least 32 bytes".to_string())
+    })?;
+
+    let mut okm = vec![0u8; len];
+    hk.expand(&info, &mut okm)
+        .map_err(|e| CryptoError::KeyGeneration(format!("HKDF expansion failed: {}", e)))?;
+
+    Ok(okm)
+}
+
 /// Derive a key using BLAKE3 in KDF mode (alternative to HKDF)
 ///
 /// This is a simpler, faster alternative that uses BLAKE3's native KDF mode.
@@ -141,6 +222,55 @@ mod tests {
         assert_ne!(keys[0][..16], keys[1]);
     }
 
+    #[test]
+    fn test_hkdf_label_encoding() {
+        let info = hkdf_label("session key", b"ctx", 32).unwrap();
+        let mut expected = vec![0x00, 0x20, 18];
+        expected.extend_from_slice(b"tallow session key");
+        expected.push(3);
+        expected.extend_from_slice(b"ctx");
+        assert_eq!(info, expected);
+
+        // Empty context still carries its zero length byte
+        assert_eq!(
+            hex::encode(hkdf_label("key", b"", 16).unwrap()),
+            "00100a74616c6c6f77206b657900"
+        );
+    }
+
+    #[test]
+    fn test_expand_label_vector() {
+        let okm = expand_label(&[0x0b; 32], "key", b"", 16).unwrap();
+        assert_eq!(hex::encode(okm), "040253e50c0ceeea90754d738fe6cd5c");
+    }
+
+    #[test]
+    fn test_expand_label_separates_inputs() {
+        let secret = [0x42u8; 32];
+        let a = expand_label(&secret, "session key", b"", 32).unwrap();
+        let b = expand_label(&secret, "pair key", b"", 32).unwrap();
+        let c = expand_label(&secret, "session key", b"x", 32).unwrap();
+        let d = expand_label(&secret, "session key", b"", 16).unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        // Length is bound into the info field, so a shorter output is not a prefix
+        assert_ne!(a[..16], d[..]);
+
+        // Label/context boundaries are unambiguous
+        assert_ne!(
+            hkdf_label("ab", b"c", 32).unwrap(),
+            hkdf_label("a", b"bc", 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_expand_label_rejects_bad_input() {
+        assert!(expand_label(&[0u8; 16], "key", b"", 32).is_err());
+        assert!(expand_label(&[0u8; 32], &"x".repeat(249), b"", 32).is_err());
+        assert!(expand_label(&[0u8; 32], "key", &[0u8; 256], 32).is_err());
+        assert!(hkdf_label("key", b"", 70_000).is_err());
+    }
+
     #[test]
     fn test_derive_blake3() {
         let ikm = b"input key material";
@@ -8,5 +8,5 @@ pub mod hkdf;
 pub mod password;
 
 pub use self::argon2::{hash_password, verify_password};
-pub use self::hkdf::derive;
-pub use password::{estimate_entropy, generate_diceware};
+pub use self::hkdf::{derive, expand_label};
+pub use password::{estimate_entropy, estimate_strength, generate_diceware, StrengthReport};
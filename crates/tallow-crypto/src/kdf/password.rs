@@ -1,12 +1,20 @@
 //! Password strength estimation and generation
+//!
+//! [`estimate_strength`] is a zxcvbn-style estimator: it finds dictionary
+//! words, keyboard runs, sequences, repeats and years, then scores the
+//! cheapest way an attacker could guess the password by combining them.
+//! Everything runs offline against embedded lists.
 
 use crate::kdf::eff_wordlist::EFF_WORDLIST;
 use rand::{seq::SliceRandom, thread_rng};
+use std::collections::HashMap;
+use std::sync::LazyLock;
 
 /// Estimate the entropy of a password in bits
 ///
 /// This is a simple estimation based on character set diversity.
-/// It does NOT account for common patterns or dictionary words.
+/// It does NOT account for common patterns or dictionary words; use
+/// [`estimate_strength`] for that.
 ///
 /// # Arguments
 ///
@@ -103,8 +111,16 @@ pub fn is_strong_enough(password: &str, min_entropy: f64) -> bool {
 /// Generate a strength report for a password
 #[derive(Debug, Clone)]
 pub struct StrengthReport {
-    /// Estimated entropy in bits
+    /// Estimated entropy in bits (log2 of the guesses needed)
     pub entropy: f64,
+    /// Estimated guesses needed to crack, as a base-10 logarithm
+    pub guesses_log10: f64,
+    /// Score from 0 (trivially guessable) to 4 (very unguessable)
+    pub score: u8,
+    /// What makes the password weak, if anything
+    pub warning: Option<String>,
+    /// How to make the password stronger (empty for good passwords)
+    pub suggestions: Vec<String>,
     /// Password length
     pub length: usize,
     /// Has lowercase letters
@@ -135,27 +151,234 @@ pub enum StrengthCategory {
 }
 
 /// Analyze password strength
+///
+/// Equivalent to [`estimate_strength`].
 pub fn analyze(password: &str) -> StrengthReport {
-    let entropy = estimate_entropy(password);
-    let length = password.len();
+    estimate_strength(password)
+}
 
-    let mut has_lowercase = false;
-    let mut has_uppercase = false;
-    let mut has_digits = false;
-    let mut has_special = false;
+/// Passwords longer than this are only pattern-matched on their prefix;
+/// the remainder is costed as brute force
+const MAX_ANALYZED_CHARS: usize = 128;
 
-    for c in password.chars() {
-        if c.is_lowercase() {
-            has_lowercase = true;
-        } else if c.is_uppercase() {
-            has_uppercase = true;
-        } else if c.is_numeric() {
-            has_digits = true;
-        } else {
-            has_special = true;
+/// Shortest substring considered as a dictionary word
+const MIN_WORD_LEN: usize = 3;
+
+/// Longest substring considered as a dictionary word
+const MAX_WORD_LEN: usize = 16;
+
+/// Year that "recent year" guesses are centred on
+const REFERENCE_YEAR: i32 = 2025;
+
+/// Most frequently leaked passwords, most common first
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "welcome",
+    "admin",
+    "login",
+    "secret",
+    "hello",
+    "passw0rd",
+    "whatever",
+    "flower",
+    "qwerty123",
+    "solo",
+    "princess1",
+    "baby",
+    "monkey1",
+];
+
+/// Keyboard rows (unshifted, then shifted) for spatial pattern matching
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+    "~!@#$%^&*()_+",
+    "QWERTYUIOP{}|",
+    "ASDFGHJKL:\"",
+    "ZXCVBNM<>?",
+];
+
+/// Number of keys a keyboard pattern can start on
+const KEYBOARD_STARTS: f64 = 47.0;
+
+/// Lowercase dictionary word -> (kind, rank used as its guess count)
+static DICTIONARY: LazyLock<HashMap<&'static str, (PatternKind, f64)>> = LazyLock::new(|| {
+    let mut dict = HashMap::with_capacity(EFF_WORDLIST.len() + COMMON_PASSWORDS.len());
+    for word in EFF_WORDLIST.iter() {
+        dict.insert(*word, (PatternKind::Dictionary, EFF_WORDLIST.len() as f64));
+    }
+    for (rank, word) in COMMON_PASSWORDS.iter().enumerate() {
+        dict.insert(*word, (PatternKind::CommonPassword, (rank + 1) as f64));
+    }
+    dict
+});
+
+/// Kind of guessable pattern found in a password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternKind {
+    CommonPassword,
+    Dictionary,
+    Keyboard,
+    Sequence,
+    Repeat,
+    Year,
+    Bruteforce,
+}
+
+/// A pattern covering `chars[start..end]` and the guesses it costs
+#[derive(Debug, Clone)]
+struct PatternMatch {
+    start: usize,
+    end: usize,
+    kind: PatternKind,
+    guesses: f64,
+    /// Dictionary match with some uppercase letters
+    capitalized: bool,
+    /// Dictionary match only after undoing l33t substitutions
+    l33t: bool,
+    /// Dictionary match spelled backwards
+    reversed: bool,
+}
+
+impl PatternMatch {
+    fn new(start: usize, end: usize, kind: PatternKind, guesses: f64) -> Self {
+        Self {
+            start,
+            end,
+            kind,
+            guesses: guesses.max(1.0),
+            capitalized: false,
+            l33t: false,
+            reversed: false,
         }
     }
 
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Estimate how guessable a password is, zxcvbn-style
+///
+/// Unlike [`estimate_entropy`], this recognises common passwords,
+/// dictionary words (including capitalised, reversed and l33t forms),
+/// keyboard runs, sequences, repeats and years, so predictable passwords
+/// like `Password123!` score low despite mixing character classes.
+///
+/// The score maps estimated guesses to 0–4 (< 10^3, < 10^6, < 10^8,
+/// < 10^10, above). Weak passwords come with a warning and suggestions
+/// suitable for showing next to a password prompt.
+pub fn estimate_strength(password: &str) -> StrengthReport {
+    let chars: Vec<char> = password.chars().collect();
+    let analyzed = &chars[..chars.len().min(MAX_ANALYZED_CHARS)];
+
+    let (mut guesses_log10, sequence) = most_guessable_sequence(analyzed);
+    guesses_log10 += chars[analyzed.len()..]
+        .iter()
+        .map(|&c| char_cardinality(c).log10())
+        .sum::<f64>();
+
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    let entropy = guesses_log10 * std::f64::consts::LOG2_10;
+    let (warning, suggestions) = feedback(score, &sequence, chars.len());
+
     let category = if entropy < 28.0 {
         StrengthCategory::VeryWeak
     } else if entropy < 36.0 {
@@ -170,15 +393,392 @@ pub fn analyze(password: &str) -> StrengthReport {
 
     StrengthReport {
         entropy,
-        length,
-        has_lowercase,
-        has_uppercase,
-        has_digits,
-        has_special,
+        guesses_log10,
+        score,
+        warning,
+        suggestions,
+        length: password.len(),
+        has_lowercase: chars.iter().any(|c| c.is_lowercase()),
+        has_uppercase: chars.iter().any(|c| c.is_uppercase()),
+        has_digits: chars
+            .iter()
+            .any(|c| !c.is_lowercase() && !c.is_uppercase() && c.is_numeric()),
+        has_special: chars
+            .iter()
+            .any(|c| !c.is_lowercase() && !c.is_uppercase() && !c.is_numeric()),
         category,
     }
 }
 
+/// Find the segmentation of `chars` into patterns with the fewest total
+/// guesses, returning log10(guesses) and the chosen patterns in order
+fn most_guessable_sequence(chars: &[char]) -> (f64, Vec<PatternMatch>) {
+    let n = chars.len();
+    let mut matches = Vec::new();
+    dictionary_matches(chars, &mut matches);
+    sequence_matches(chars, &mut matches);
+    keyboard_matches(chars, &mut matches);
+    repeat_matches(chars, &mut matches);
+    year_matches(chars, &mut matches);
+    for (i, &c) in chars.iter().enumerate() {
+        matches.push(PatternMatch::new(
+            i,
+            i + 1,
+            PatternKind::Bruteforce,
+            char_cardinality(c),
+        ));
+    }
+
+    // best[i]: cheapest log10 guesses for chars[..i], via match index
+    let mut best: Vec<(f64, Option<usize>)> = vec![(f64::INFINITY, None); n + 1];
+    best[0] = (0.0, None);
+    for end in 1..=n {
+        for (idx, m) in matches.iter().enumerate() {
+            if m.end != end {
+                continue;
+            }
+            let cost = best[m.start].0 + m.guesses.log10();
+            if cost < best[end].0 {
+                best[end] = (cost, Some(idx));
+            }
+        }
+    }
+
+    let mut sequence = Vec::new();
+    let mut at = n;
+    while let Some(idx) = best[at].1 {
+        sequence.push(matches[idx].clone());
+        at = matches[idx].start;
+    }
+    sequence.reverse();
+    (best[n].0, sequence)
+}
+
+fn dictionary_matches(chars: &[char], out: &mut Vec<PatternMatch>) {
+    let lower: Vec<char> = chars.iter().map(|&c| to_lower(c)).collect();
+    let unleeted: Vec<char> = lower.iter().map(|&c| unleet(c)).collect();
+
+    for start in 0..chars.len() {
+        let max_end = chars.len().min(start + MAX_WORD_LEN);
+        for end in (start + MIN_WORD_LEN)..=max_end {
+            let original = &chars[start..end];
+            let plain: String = lower[start..end].iter().collect();
+            let substitutions = (start..end).filter(|&i| lower[i] != unleeted[i]).count();
+            let unleet_word: String = unleeted[start..end].iter().collect();
+            let reversed: String = plain.chars().rev().collect();
+
+            let candidates = [
+                (plain.as_str(), false, false),
+                (unleet_word.as_str(), substitutions > 0, false),
+                (reversed.as_str(), false, true),
+            ];
+            for (i, (word, l33t, reversed)) in candidates.into_iter().enumerate() {
+                // Alternate spellings identical to the plain one add nothing
+                if i > 0 && word == plain {
+                    continue;
+                }
+                let Some(&(kind, rank)) = DICTIONARY.get(word) else {
+                    continue;
+                };
+                let capitalized = original.iter().any(|c| c.is_uppercase());
+                let mut guesses = rank * uppercase_variations(original);
+                if l33t {
+                    guesses *= 2f64.powi(substitutions as i32);
+                }
+                if reversed {
+                    guesses *= 2.0;
+                }
+                let mut m = PatternMatch::new(start, end, kind, guesses);
+                m.capitalized = capitalized;
+                m.l33t = l33t;
+                m.reversed = reversed;
+                out.push(m);
+            }
+        }
+    }
+}
+
+/// Runs like `abc`, `6543` or `XYZ` (constant step of 1 within a class)
+fn sequence_matches(chars: &[char], out: &mut Vec<PatternMatch>) {
+    let step = |a: char, b: char| -> Option<i32> {
+        let same_class = (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+            || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+            || (a.is_ascii_digit() && b.is_ascii_digit());
+        let delta = b as i32 - a as i32;
+        (same_class && delta.abs() == 1).then_some(delta)
+    };
+
+    let mut start = 0;
+    while start + 1 < chars.len() {
+        let Some(delta) = step(chars[start], chars[start + 1]) else {
+            start += 1;
+            continue;
+        };
+        let mut end = start + 2;
+        while end < chars.len() && step(chars[end - 1], chars[end]) == Some(delta) {
+            end += 1;
+        }
+        if end - start >= 3 {
+            let first = chars[start];
+            let base = if "aAzZ019".contains(first) {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            let direction = if delta < 0 { 2.0 } else { 1.0 };
+            let guesses = base * (end - start) as f64 * direction;
+            out.push(PatternMatch::new(
+                start,
+                end,
+                PatternKind::Sequence,
+                guesses,
+            ));
+        }
+        start = end - 1;
+    }
+}
+
+/// Horizontal runs of adjacent keys, e.g. `qwerty` or `lkjh`
+fn keyboard_matches(chars: &[char], out: &mut Vec<PatternMatch>) {
+    let position = |c: char| -> Option<(usize, i32)> {
+        KEYBOARD_ROWS.iter().enumerate().find_map(|(row, keys)| {
+            keys.chars()
+                .position(|k| k == c)
+                .map(|col| (row, col as i32))
+        })
+    };
+
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        let mut turns = 0;
+        let mut last_dir = 0;
+        while end < chars.len() {
+            let (Some((r1, c1)), Some((r2, c2))) = (position(chars[end - 1]), position(chars[end]))
+            else {
+                break;
+            };
+            let dir = c2 - c1;
+            if r1 != r2 || dir.abs() != 1 {
+                break;
+            }
+            if dir != last_dir {
+                turns += 1;
+                last_dir = dir;
+            }
+            end += 1;
+        }
+        if end - start >= 3 {
+            let shifted = chars[start..end]
+                .iter()
+                .filter(|&&c| position(c).is_some_and(|(row, _)| row >= 4))
+                .count();
+            let mut guesses = KEYBOARD_STARTS * 2.0 * (end - start) as f64 * 2f64.powi(turns - 1);
+            if shifted > 0 {
+                guesses *= 2.0;
+            }
+            out.push(PatternMatch::new(
+                start,
+                end,
+                PatternKind::Keyboard,
+                guesses,
+            ));
+        }
+        start = end.max(start + 1);
+    }
+}
+
+/// Repeated characters (`aaaa`) and repeated blocks (`abcabc`)
+fn repeat_matches(chars: &[char], out: &mut Vec<PatternMatch>) {
+    let n = chars.len();
+    let mut block_guesses: HashMap<&[char], f64> = HashMap::new();
+    for start in 0..n {
+        for block in 1..=(n - start) / 2 {
+            let base = &chars[start..start + block];
+            let mut reps = 1;
+            while start + block * (reps + 1) <= n
+                && &chars[start + block * reps..start + block * (reps + 1)] == base
+            {
+                reps += 1;
+            }
+            if reps < 2 || (block == 1 && reps < 3) {
+                continue;
+            }
+            let base_guesses = *block_guesses
+                .entry(base)
+                .or_insert_with(|| most_guessable_sequence_bounded(base));
+            let guesses = base_guesses * reps as f64;
+            out.push(PatternMatch::new(
+                start,
+                start + block * reps,
+                PatternKind::Repeat,
+                guesses,
+            ));
+        }
+    }
+}
+
+/// Guesses for a repeated block, costed without further repeat matching
+fn most_guessable_sequence_bounded(block: &[char]) -> f64 {
+    if block.len() == 1 {
+        return char_cardinality(block[0]);
+    }
+    let brute: f64 = block.iter().map(|&c| char_cardinality(c)).product();
+    let mut matches = Vec::new();
+    dictionary_matches(block, &mut matches);
+    sequence_matches(block, &mut matches);
+    keyboard_matches(block, &mut matches);
+    matches
+        .iter()
+        .filter(|m| m.start == 0 && m.end == block.len())
+        .map(|m| m.guesses)
+        .fold(brute, f64::min)
+}
+
+/// Four-digit years from 1900 to 2049
+fn year_matches(chars: &[char], out: &mut Vec<PatternMatch>) {
+    for start in 0..chars.len().saturating_sub(3) {
+        let window = &chars[start..start + 4];
+        if !window.iter().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let year: i32 = window.iter().collect::<String>().parse().unwrap_or(0);
+        if (1900..=2049).contains(&year) {
+            let guesses = (year - REFERENCE_YEAR).abs().max(20) as f64;
+            out.push(PatternMatch::new(
+                start,
+                start + 4,
+                PatternKind::Year,
+                guesses,
+            ));
+        }
+    }
+}
+
+/// Size of the character class an attacker must search for `c`
+fn char_cardinality(c: char) -> f64 {
+    if c.is_ascii_digit() {
+        10.0
+    } else if c.is_ascii_lowercase() || c.is_ascii_uppercase() {
+        26.0
+    } else if c.is_ascii() {
+        33.0
+    } else {
+        100.0
+    }
+}
+
+/// Extra guesses for the capitalisation of a dictionary word
+fn uppercase_variations(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|c| c.is_uppercase()).count();
+    let lower = word.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    let first_only = upper == 1 && word.first().is_some_and(|c| c.is_uppercase());
+    let last_only = upper == 1 && word.last().is_some_and(|c| c.is_uppercase());
+    if first_only || last_only || lower == 0 {
+        return 2.0;
+    }
+    (1..=upper.min(lower))
+        .map(|k| binomial(upper + lower, k))
+        .sum()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn to_lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Undo common l33t-speak substitutions
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '(' => 'c',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' | '+' => 't',
+        '2' => 'z',
+        other => other,
+    }
+}
+
+/// Warning and suggestions for the pattern that makes the password weakest
+fn feedback(score: u8, sequence: &[PatternMatch], length: usize) -> (Option<String>, Vec<String>) {
+    if length == 0 {
+        return (
+            Some("A password is required".to_string()),
+            vec!["Use a few uncommon words, or a generated passphrase".to_string()],
+        );
+    }
+    if score >= 3 {
+        return (None, Vec::new());
+    }
+
+    let mut suggestions = vec!["Add another word or two; uncommon words are better".to_string()];
+    let weakest = sequence
+        .iter()
+        .filter(|m| m.kind != PatternKind::Bruteforce)
+        .max_by_key(|m| m.len());
+
+    let warning = match weakest {
+        None => Some("This password is too short".to_string()),
+        Some(m) => {
+            let whole = sequence.len() == 1;
+            let warning = match m.kind {
+                PatternKind::CommonPassword if whole => "This is a very common password",
+                PatternKind::CommonPassword => "This is similar to a commonly used password",
+                PatternKind::Dictionary if whole => "A word by itself is easy to guess",
+                PatternKind::Dictionary => "Common words are easy to guess",
+                PatternKind::Keyboard => "Straight rows of keys are easy to guess",
+                PatternKind::Sequence => "Sequences like abc or 6543 are easy to guess",
+                PatternKind::Repeat => "Repeats like \"aaa\" or \"abcabc\" are easy to guess",
+                PatternKind::Year => "Recent years are easy to guess",
+                PatternKind::Bruteforce => "This password is too short",
+            };
+
+            if m.capitalized {
+                suggestions.push("Capitalization doesn't help very much".to_string());
+            }
+            if m.l33t {
+                suggestions.push(
+                    "Predictable substitutions like '@' instead of 'a' don't help very much"
+                        .to_string(),
+                );
+            }
+            if m.reversed {
+                suggestions.push("Reversed words aren't much harder to guess".to_string());
+            }
+            match m.kind {
+                PatternKind::Keyboard => {
+                    suggestions.push("Avoid runs of neighbouring keys".to_string());
+                }
+                PatternKind::Sequence => suggestions.push("Avoid sequences".to_string()),
+                PatternKind::Repeat => {
+                    suggestions.push("Avoid repeated words and characters".to_string());
+                }
+                PatternKind::Year => {
+                    suggestions.push("Avoid years that are associated with you".to_string());
+                }
+                _ => {}
+            }
+            Some(warning.to_string())
+        }
+    };
+
+    suggestions.push("Consider a generated passphrase of 6 or more random words".to_string());
+    (warning, suggestions)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +820,77 @@ mod tests {
         assert!(report.has_special);
         assert_eq!(report.length, 9);
     }
+
+    #[test]
+    fn test_common_passwords_score_low() {
+        for password in [
+            "password",
+            "Password123!",
+            "P@ssw0rd",
+            "qwerty",
+            "qwertyuiop",
+            "123456789",
+            "iloveyou",
+            "aaaaaaaa",
+            "abcabcabc",
+            "letmein2024",
+            "drowssap",
+        ] {
+            let report = estimate_strength(password);
+            assert!(
+                report.score <= 1,
+                "{:?} scored {} ({:.1} log10 guesses)",
+                password,
+                report.score,
+                report.guesses_log10
+            );
+        }
+
+        // Raw charset entropy badly overestimates these
+        assert!(estimate_entropy("Password123!") > 70.0);
+        assert!(estimate_strength("Password123!").entropy < 20.0);
+    }
+
+    #[test]
+    fn test_diceware_scores_high() {
+        for _ in 0..5 {
+            let passphrase = generate_diceware(6);
+            let report = estimate_strength(&passphrase);
+            assert_eq!(report.score, 4, "{:?} scored {}", passphrase, report.score);
+            assert!(report.warning.is_none());
+            assert!(report.suggestions.is_empty());
+        }
+        assert_eq!(estimate_strength("k8#Qz!vR2m@Lp9^w").score, 4);
+    }
+
+    #[test]
+    fn test_weak_password_feedback() {
+        let report = estimate_strength("password");
+        assert_eq!(report.score, 0);
+        assert_eq!(
+            report.warning.as_deref(),
+            Some("This is a very common password")
+        );
+        assert!(!report.suggestions.is_empty());
+
+        let report = estimate_strength("P@ssw0rd");
+        assert!(report
+            .suggestions
+            .iter()
+            .any(|s| s.contains("substitutions")));
+
+        let report = estimate_strength("qwertyuiopasdfgh");
+        assert!(report.warning.is_some());
+        assert!(report.score < 3);
+
+        let report = estimate_strength("abcdefg");
+        assert_eq!(
+            report.warning.as_deref(),
+            Some("Sequences like abc or 6543 are easy to guess")
+        );
+
+        let report = estimate_strength("");
+        assert_eq!(report.score, 0);
+        assert!(report.warning.is_some());
+    }
 }
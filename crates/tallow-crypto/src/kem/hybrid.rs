@@ -1,6 +1,12 @@
 //! Hybrid KEM combining ML-KEM and X25519
-
-use crate::error::Result;
+//!
+//! Decapsulation never lets one broken component decide the outcome. A
+//! component that fails is replaced by a stand-in secret and the two slots
+//! are combined as usual, so the final key still rests on the intact
+//! component; it won't match the peer's key, which key confirmation then
+//! catches. [`HybridKem::decapsulate_with_report`] says which side failed.
+
+use crate::error::{CryptoError, Result};
 use crate::hash::{blake3, domain};
 use crate::kem::{mlkem, x25519};
 use serde::{Deserialize, Serialize};
@@ -71,6 +77,44 @@ impl SharedSecret {
     }
 }
 
+/// Which hybrid components failed during decapsulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComponentFailures {
+    /// The ML-KEM ciphertext was malformed (wrong length)
+    ///
+    /// A well-formed but tampered ciphertext cannot be detected here:
+    /// ML-KEM's implicit rejection turns it into an unrelated secret.
+    pub mlkem: bool,
+    /// The X25519 ephemeral key was a low-order point
+    pub x25519: bool,
+}
+
+impl ComponentFailures {
+    /// Whether any component failed
+    pub fn any(&self) -> bool {
+        self.mlkem || self.x25519
+    }
+}
+
+impl std::fmt::Display for ComponentFailures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.mlkem, self.x25519) {
+            (false, false) => write!(f, "none"),
+            (true, false) => write!(f, "ML-KEM"),
+            (false, true) => write!(f, "X25519"),
+            (true, true) => write!(f, "ML-KEM and X25519"),
+        }
+    }
+}
+
+/// Result of a decapsulation that reports per-component failures
+pub struct HybridDecapsulation {
+    /// The combined shared secret
+    pub secret: SharedSecret,
+    /// Components that failed and were replaced by a stand-in secret
+    pub failures: ComponentFailures,
+}
+
 /// Hybrid KEM operations
 pub struct HybridKem;
 
@@ -127,6 +171,9 @@ impl HybridKem {
 
     /// Decapsulate a shared secret from a hybrid ciphertext
     ///
+    /// A single failed component does not make this fail; see
+    /// [`decapsulate_with_report`](Self::decapsulate_with_report).
+    ///
     /// # Arguments
     ///
     /// * `sk` - The recipient's secret key
@@ -134,18 +181,57 @@ impl HybridKem {
     ///
     /// # Returns
     ///
-    /// The shared secret
+    /// The shared secret, or an error if both components failed
     pub fn decapsulate(sk: &SecretKey, ct: &Ciphertext) -> Result<SharedSecret> {
-        // Decapsulate ML-KEM
-        let mlkem_ss = mlkem::MlKem::decapsulate(&sk.mlkem, &ct.mlkem)?;
+        Self::decapsulate_with_report(sk, ct).map(|d| d.secret)
+    }
 
-        // Compute X25519 DH
-        let x25519_ss = sk.x25519.diffie_hellman(&ct.x25519_public)?;
+    /// Decapsulate, reporting which component(s) failed
+    ///
+    /// A failed component's slot is filled with a stand-in derived from its
+    /// ciphertext, so the combined key depends only on the intact side.
+    /// Fails only when neither component is usable.
+    pub fn decapsulate_with_report(sk: &SecretKey, ct: &Ciphertext) -> Result<HybridDecapsulation> {
+        let mut failures = ComponentFailures::default();
+
+        let mut mlkem_ss = match mlkem::MlKem::decapsulate(&sk.mlkem, &ct.mlkem) {
+            Ok(ss) => ss.0,
+            Err(_) => {
+                failures.mlkem = true;
+                Self::stand_in_secret(ct.mlkem.as_bytes())
+            }
+        };
 
-        // Combine both shared secrets
-        let combined = Self::combine_secrets(&mlkem_ss.0, &x25519_ss.0)?;
+        let mut x25519_ss = match sk.x25519.diffie_hellman(&ct.x25519_public) {
+            Ok(ss) => ss.0,
+            Err(_) => {
+                failures.x25519 = true;
+                Self::stand_in_secret(ct.x25519_public.as_bytes())
+            }
+        };
+
+        if failures.mlkem && failures.x25519 {
+            return Err(CryptoError::Decryption(
+                "both hybrid KEM components failed".to_string(),
+            ));
+        }
+
+        let combined = Self::combine_secrets(&mlkem_ss, &x25519_ss);
+        mlkem_ss.zeroize();
+        x25519_ss.zeroize();
 
-        Ok(SharedSecret(combined))
+        Ok(HybridDecapsulation {
+            secret: SharedSecret(combined?),
+            failures,
+        })
+    }
+
+    /// Stand-in for a failed component's shared secret
+    ///
+    /// Public by construction; it only keeps the slot layout fixed so the
+    /// intact component still feeds the combiner.
+    fn stand_in_secret(component_ciphertext: &[u8]) -> [u8; 32] {
+        blake3::derive_key(domain::DOMAIN_HYBRID_REJECT, component_ciphertext)
     }
 
     /// Combine two shared secrets using BLAKE3 KDF
@@ -194,4 +280,85 @@ mod tests {
 
         assert_eq!(ss1.0, ss2.0);
     }
+
+    #[test]
+    fn test_clean_decapsulation_reports_no_failures() {
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        let (ct, ss1) = HybridKem::encapsulate(&pk).unwrap();
+        let report = HybridKem::decapsulate_with_report(&sk, &ct).unwrap();
+
+        assert!(!report.failures.any());
+        assert_eq!(report.secret.0, ss1.0);
+    }
+
+    #[test]
+    fn test_corrupted_x25519_flagged_and_mlkem_still_used() {
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        let (mut ct, ss_sender) = HybridKem::encapsulate(&pk).unwrap();
+        // The identity point: DH with it is all zeros
+        ct.x25519_public = x25519::X25519PublicKey::from([0u8; 32]);
+
+        let report = HybridKem::decapsulate_with_report(&sk, &ct).unwrap();
+        assert_eq!(
+            report.failures,
+            ComponentFailures {
+                mlkem: false,
+                x25519: true
+            }
+        );
+
+        // Combination rule: intact ML-KEM secret in its slot, stand-in in
+        // the X25519 slot
+        let mlkem_ss = mlkem::MlKem::decapsulate(&sk.mlkem, &ct.mlkem).unwrap();
+        let expected = HybridKem::combine_secrets(
+            &mlkem_ss.0,
+            &HybridKem::stand_in_secret(ct.x25519_public.as_bytes()),
+        )
+        .unwrap();
+        assert_eq!(report.secret.0, expected);
+
+        // Differs from the sender's key, so key confirmation rejects it
+        assert_ne!(report.secret.0, ss_sender.0);
+    }
+
+    #[test]
+    fn test_malformed_mlkem_flagged() {
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        let (mut ct, _) = HybridKem::encapsulate(&pk).unwrap();
+
+        // Serde doesn't check lengths, so a peer can send a short ciphertext
+        let mut short = ct.mlkem.as_bytes().to_vec();
+        short.pop();
+        ct.mlkem = bincode::deserialize(&bincode::serialize(&short).unwrap()).unwrap();
+
+        let report = HybridKem::decapsulate_with_report(&sk, &ct).unwrap();
+        assert!(report.failures.mlkem);
+        assert!(!report.failures.x25519);
+
+        // With both sides broken there is nothing left to derive from
+        ct.x25519_public = x25519::X25519PublicKey::from([0u8; 32]);
+        assert!(HybridKem::decapsulate_with_report(&sk, &ct).is_err());
+    }
+
+    #[test]
+    fn test_combination_is_order_independent_between_peers() {
+        let (pk_a, sk_a) = HybridKem::keygen().unwrap();
+        let (pk_b, sk_b) = HybridKem::keygen().unwrap();
+
+        // A encapsulates to B, and B to A: each pair agrees on one key
+        // whichever peer took the encapsulating role
+        let (ct_ab, ss_a) = HybridKem::encapsulate(&pk_b).unwrap();
+        let (ct_ba, ss_b) = HybridKem::encapsulate(&pk_a).unwrap();
+        assert_eq!(HybridKem::decapsulate(&sk_b, &ct_ab).unwrap().0, ss_a.0);
+        assert_eq!(HybridKem::decapsulate(&sk_a, &ct_ba).unwrap().0, ss_b.0);
+
+        // Slots are fixed by component, not by role: the decapsulator's
+        // view of each component combines to the encapsulator's key
+        let mlkem_ss = mlkem::MlKem::decapsulate(&sk_b.mlkem, &ct_ab.mlkem).unwrap();
+        let x25519_ss = sk_b.x25519.diffie_hellman(&ct_ab.x25519_public).unwrap();
+        assert_eq!(
+            HybridKem::combine_secrets(&mlkem_ss.0, &x25519_ss.0).unwrap(),
+            ss_a.0
+        );
+    }
 }
@@ -1,9 +1,16 @@
 //! ML-KEM-1024 (FIPS 203) key encapsulation mechanism
+//!
+//! Decapsulation uses implicit rejection (FIPS 203, Algorithm 18): a
+//! ciphertext that fails the re-encryption check yields the pseudorandom
+//! key `J(z || c)` instead of an error. An attacker therefore can't tell a
+//! rejected ciphertext from an accepted one; the mismatch only shows up
+//! later, when key confirmation fails.
 
 use crate::error::{CryptoError, Result};
 use fips203::ml_kem_1024;
 use fips203::traits::{Decaps, Encaps, KeyGen, SerDes};
 use serde::{Deserialize, Serialize};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
 use zeroize::Zeroize;
 
 /// ML-KEM-1024 encapsulation key (public key) byte length
@@ -18,6 +25,9 @@ pub const CT_LEN: usize = 1568;
 /// ML-KEM-1024 shared secret byte length
 pub const SS_LEN: usize = 32;
 
+/// Length of the implicit-rejection seed `z` at the end of the secret key
+const Z_LEN: usize = 32;
+
 /// ML-KEM-1024 public key (encapsulation key)
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PublicKey(Vec<u8>);
@@ -152,6 +162,12 @@ impl MlKem {
 
     /// Decapsulate a shared secret from a ciphertext
     ///
+    /// A well-formed but invalid ciphertext does not produce an error: per
+    /// FIPS 203 implicit rejection the result is a deterministic
+    /// pseudorandom secret derived from the secret key and ciphertext.
+    /// Errors are only returned for malformed lengths or a corrupt secret
+    /// key, neither of which depends on the ciphertext's contents.
+    ///
     /// # Arguments
     ///
     /// * `sk` - The recipient's secret key
@@ -174,17 +190,31 @@ impl MlKem {
                 .try_into()
                 .map_err(|_| CryptoError::InvalidKey("Invalid ciphertext length".to_string()))?;
 
-        let ct_obj = ml_kem_1024::CipherText::try_from_bytes(ct_bytes)
-            .map_err(|_| CryptoError::Decryption("Invalid ML-KEM-1024 ciphertext".to_string()))?;
-
-        let ss = dk
-            .try_decaps(&ct_obj)
-            .map_err(|_| CryptoError::Decryption("ML-KEM-1024 decapsulation failed".to_string()))?;
-
-        Ok(SharedSecret(ss.into_bytes()))
+        // fips203 already applies implicit rejection inside `try_decaps`.
+        // Never turn a ciphertext-dependent failure into an error here
+        // either: fall back to the FIPS 203 rejection key instead.
+        let decapsulated = ml_kem_1024::CipherText::try_from_bytes(ct_bytes)
+            .ok()
+            .and_then(|ct_obj| dk.try_decaps(&ct_obj).ok());
+
+        Ok(match decapsulated {
+            Some(ss) => SharedSecret(ss.into_bytes()),
+            None => implicit_rejection(&sk.0, &ct.0),
+        })
     }
 }
 
+/// FIPS 203 implicit-rejection key `J(z || c)` = SHAKE256(z || c, 32)
+fn implicit_rejection(dk: &[u8], ct: &[u8]) -> SharedSecret {
+    let z = &dk[DK_LEN - Z_LEN..];
+    let mut xof = sha3::Shake256::default();
+    xof.update(z);
+    xof.update(ct);
+    let mut out = [0u8; SS_LEN];
+    xof.finalize_xof().read(&mut out);
+    SharedSecret(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +251,29 @@ mod tests {
         assert_eq!(sk.as_bytes().len(), DK_LEN);
     }
 
+    #[test]
+    fn test_mlkem_corrupted_ciphertext_implicit_rejection() {
+        let (pk, sk) = MlKem::keygen().unwrap();
+        let (ct, ss) = MlKem::encapsulate(&pk).unwrap();
+
+        let mut bytes = ct.as_bytes().to_vec();
+        bytes[CT_LEN / 2] ^= 0x01;
+        let bad_ct = Ciphertext::from_bytes(bytes).unwrap();
+
+        // No error, a different secret, and the same one every time
+        let rejected = MlKem::decapsulate(&sk, &bad_ct).unwrap();
+        let again = MlKem::decapsulate(&sk, &bad_ct).unwrap();
+        assert_ne!(rejected.0, ss.0);
+        assert_eq!(rejected.0, again.0);
+
+        // It is exactly the FIPS 203 rejection key J(z || c)
+        let expected = implicit_rejection(sk.as_bytes(), bad_ct.as_bytes());
+        assert_eq!(rejected.0, expected.0);
+
+        // The valid ciphertext still decapsulates correctly
+        assert_eq!(MlKem::decapsulate(&sk, &ct).unwrap().0, ss.0);
+    }
+
     #[test]
     fn test_mlkem_invalid_key_length() {
         assert!(PublicKey::from_bytes(vec![0u8; 32]).is_err());
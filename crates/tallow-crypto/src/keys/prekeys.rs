@@ -1,11 +1,18 @@
 //! Pre-keys for asynchronous key agreement
 
+use crate::clock::{Clock, SystemClock};
 use crate::error::{CryptoError, Result};
 use crate::kem::hybrid::{HybridKem, PublicKey};
 use crate::sig::hybrid::{HybridPublicKey, HybridSignature, HybridSigner};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+/// Domain separator for one-time pre-key signatures
+const ONETIME_PREKEY_DOMAIN: &[u8] = b"tallow-onetime-prekey-v1:";
+
+/// How long a signed pre-key stays valid after it is generated (30 days)
+pub const SIGNED_PREKEY_LIFETIME_SECS: u64 = 30 * 24 * 60 * 60;
+
 /// Signed pre-key
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SignedPreKey {
@@ -26,6 +33,8 @@ pub struct OneTimePreKey {
     pub id: u32,
     /// Hybrid public key used for a single key agreement exchange
     pub public_key: PublicKey,
+    /// Hybrid (ML-DSA-87 + Ed25519) signature over the pre-key id and public key
+    pub signature: HybridSignature,
 }
 
 impl Zeroize for OneTimePreKey {
@@ -48,8 +57,12 @@ pub struct PreKeyBundle {
     pub identity_key: HybridPublicKey,
     /// Medium-term signed pre-key for initiating key agreement
     pub signed_prekey: SignedPreKey,
-    /// Optional one-time pre-key for forward secrecy; absent when exhausted
-    pub onetime_prekey: Option<OneTimePreKey>,
+    /// Pool of unused one-time pre-keys for forward secrecy, oldest first
+    pub onetime_prekeys: Vec<OneTimePreKey>,
+    /// Id assigned to the next generated one-time pre-key
+    ///
+    /// Only ever increases, so an id handed out once is never reissued.
+    pub next_onetime_id: u32,
 }
 
 impl SignedPreKey {
@@ -58,11 +71,13 @@ impl SignedPreKey {
     /// Pre-keys MUST be signed with the hybrid identity key per security policy:
     /// "NEVER Ed25519 alone for identity"
     pub fn generate(id: u32, identity: &HybridSigner) -> Result<Self> {
+        Self::generate_at(id, identity, &SystemClock)
+    }
+
+    /// Generate a new signed pre-key timestamped by `clock`
+    pub fn generate_at(id: u32, identity: &HybridSigner, clock: &dyn Clock) -> Result<Self> {
         let (pk, _sk) = HybridKem::keygen()?;
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| CryptoError::KeyGeneration(format!("system clock error: {}", e)))?
-            .as_secs();
+        let timestamp = clock.unix_secs();
 
         let pk_bytes = bincode::serialize(&pk).map_err(|e| {
             CryptoError::Serialization(format!("Failed to serialize pre-key: {}", e))
@@ -101,12 +116,224 @@ impl SignedPreKey {
 
         crate::sig::hybrid::verify(identity_key, &message, &self.signature)
     }
+
+    /// Unix time (seconds) from which this pre-key is no longer valid
+    pub fn expires_at(&self) -> u64 {
+        self.timestamp.saturating_add(SIGNED_PREKEY_LIFETIME_SECS)
+    }
+
+    /// Whether the pre-key has reached its expiry according to `clock`
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        clock.unix_secs() >= self.expires_at()
+    }
+
+    /// Verify the signature and that the pre-key has not expired
+    pub fn verify_at(&self, identity_key: &HybridPublicKey, clock: &dyn Clock) -> Result<()> {
+        self.verify(identity_key)?;
+        if self.is_expired(clock) {
+            return Err(CryptoError::Verification(format!(
+                "signed pre-key {} expired at {}",
+                self.id,
+                self.expires_at()
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl OneTimePreKey {
-    /// Generate a new one-time pre-key
-    pub fn generate(id: u32) -> Result<Self> {
+    /// Generate a new one-time pre-key signed with the hybrid identity
+    pub fn generate(id: u32, identity: &HybridSigner) -> Result<Self> {
         let (pk, _sk) = HybridKem::keygen()?;
-        Ok(Self { id, public_key: pk })
+        let signature = identity.sign(&Self::signed_message(id, &pk)?)?;
+        Ok(Self {
+            id,
+            public_key: pk,
+            signature,
+        })
+    }
+
+    /// Verify the hybrid signature on this one-time pre-key
+    pub fn verify(&self, identity_key: &HybridPublicKey) -> Result<()> {
+        let message = Self::signed_message(self.id, &self.public_key)?;
+        crate::sig::hybrid::verify(identity_key, &message, &self.signature)
+    }
+
+    fn signed_message(id: u32, public_key: &PublicKey) -> Result<Vec<u8>> {
+        let pk_bytes = bincode::serialize(public_key).map_err(|e| {
+            CryptoError::Serialization(format!("Failed to serialize one-time pre-key: {}", e))
+        })?;
+
+        let mut message = Vec::with_capacity(ONETIME_PREKEY_DOMAIN.len() + 4 + pk_bytes.len());
+        message.extend_from_slice(ONETIME_PREKEY_DOMAIN);
+        message.extend_from_slice(&id.to_le_bytes());
+        message.extend_from_slice(&pk_bytes);
+        Ok(message)
+    }
+}
+
+impl PreKeyBundle {
+    /// Generate a bundle with a fresh signed pre-key and `onetime_count`
+    /// one-time pre-keys
+    pub fn generate(
+        identity: &HybridSigner,
+        signed_prekey_id: u32,
+        onetime_count: usize,
+    ) -> Result<Self> {
+        let mut bundle = Self {
+            identity_key: identity.public_key(),
+            signed_prekey: SignedPreKey::generate(signed_prekey_id, identity)?,
+            onetime_prekeys: Vec::with_capacity(onetime_count),
+            next_onetime_id: 0,
+        };
+        bundle.replenish(onetime_count, identity)?;
+        Ok(bundle)
+    }
+
+    /// Number of unused one-time pre-keys in the pool
+    pub fn onetime_count(&self) -> usize {
+        self.onetime_prekeys.len()
+    }
+
+    /// Remove and return the oldest unused one-time pre-key
+    ///
+    /// Each initiation by an offline peer consumes one key; `None` means the
+    /// pool is exhausted and the session falls back to the signed pre-key.
+    pub fn take_onetime_prekey(&mut self) -> Option<OneTimePreKey> {
+        if self.onetime_prekeys.is_empty() {
+            None
+        } else {
+            Some(self.onetime_prekeys.remove(0))
+        }
+    }
+
+    /// Top the one-time pre-key pool back up to `count` keys
+    ///
+    /// New keys get fresh ids and are signed with `signer`, which must be the
+    /// bundle's identity. Returns the number of keys generated.
+    pub fn replenish(&mut self, count: usize, signer: &HybridSigner) -> Result<usize> {
+        let signer_key = signer.public_key();
+        if signer_key.ed25519 != self.identity_key.ed25519
+            || signer_key.mldsa != self.identity_key.mldsa
+        {
+            return Err(CryptoError::InvalidKey(
+                "pre-key signer does not match bundle identity".to_string(),
+            ));
+        }
+
+        let missing = count.saturating_sub(self.onetime_prekeys.len());
+        for _ in 0..missing {
+            let id = self.next_onetime_id;
+            self.next_onetime_id = id.checked_add(1).ok_or_else(|| {
+                CryptoError::KeyGeneration("one-time pre-key ids exhausted".to_string())
+            })?;
+            self.onetime_prekeys
+                .push(OneTimePreKey::generate(id, signer)?);
+        }
+        Ok(missing)
+    }
+
+    /// Verify the signed pre-key and every one-time pre-key against the
+    /// bundle's identity key
+    pub fn verify(&self) -> Result<()> {
+        self.signed_prekey.verify(&self.identity_key)?;
+        for prekey in &self.onetime_prekeys {
+            prekey.verify(&self.identity_key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replenish_restores_target() {
+        let identity = HybridSigner::keygen().unwrap();
+        let mut bundle = PreKeyBundle::generate(&identity, 1, 4).unwrap();
+        assert_eq!(bundle.onetime_count(), 4);
+
+        bundle.take_onetime_prekey().unwrap();
+        bundle.take_onetime_prekey().unwrap();
+        assert_eq!(bundle.onetime_count(), 2);
+
+        assert_eq!(bundle.replenish(4, &identity).unwrap(), 2);
+        assert_eq!(bundle.onetime_count(), 4);
+
+        // Already at target: nothing to do
+        assert_eq!(bundle.replenish(4, &identity).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_consumed_prekeys_not_reused() {
+        let identity = HybridSigner::keygen().unwrap();
+        let mut bundle = PreKeyBundle::generate(&identity, 1, 3).unwrap();
+
+        let mut consumed = Vec::new();
+        for _ in 0..3 {
+            consumed.push(bundle.take_onetime_prekey().unwrap().id);
+        }
+        assert!(bundle.take_onetime_prekey().is_none());
+
+        bundle.replenish(3, &identity).unwrap();
+        for prekey in &bundle.onetime_prekeys {
+            assert!(!consumed.contains(&prekey.id), "id {} reissued", prekey.id);
+        }
+        assert_eq!(bundle.next_onetime_id, 6);
+    }
+
+    #[test]
+    fn test_replenished_prekeys_have_valid_signatures() {
+        let identity = HybridSigner::keygen().unwrap();
+        let mut bundle = PreKeyBundle::generate(&identity, 1, 1).unwrap();
+        bundle.take_onetime_prekey().unwrap();
+        bundle.replenish(2, &identity).unwrap();
+
+        bundle.verify().unwrap();
+        for prekey in &bundle.onetime_prekeys {
+            prekey.verify(&identity.public_key()).unwrap();
+        }
+
+        // A signature from a different identity must not verify
+        let other = HybridSigner::keygen().unwrap();
+        assert!(bundle.onetime_prekeys[0]
+            .verify(&other.public_key())
+            .is_err());
+    }
+
+    #[test]
+    fn test_replenish_rejects_foreign_signer() {
+        let identity = HybridSigner::keygen().unwrap();
+        let other = HybridSigner::keygen().unwrap();
+        let mut bundle = PreKeyBundle::generate(&identity, 1, 0).unwrap();
+        assert!(bundle.replenish(2, &other).is_err());
+        assert_eq!(bundle.onetime_count(), 0);
+    }
+
+    #[test]
+    fn test_signed_prekey_expires_exactly_at_expiry() {
+        use crate::clock::MockClock;
+        use std::time::Duration;
+
+        let identity = HybridSigner::keygen().unwrap();
+        let clock = MockClock::new(1_700_000_000);
+        let prekey = SignedPreKey::generate_at(7, &identity, &clock).unwrap();
+        assert_eq!(prekey.timestamp, 1_700_000_000);
+        assert_eq!(
+            prekey.expires_at(),
+            1_700_000_000 + SIGNED_PREKEY_LIFETIME_SECS
+        );
+
+        clock.advance(Duration::from_secs(SIGNED_PREKEY_LIFETIME_SECS - 1));
+        assert!(!prekey.is_expired(&clock));
+        prekey.verify_at(&identity.public_key(), &clock).unwrap();
+
+        clock.advance(Duration::from_secs(1));
+        assert!(prekey.is_expired(&clock));
+        assert!(prekey.verify_at(&identity.public_key(), &clock).is_err());
+
+        // The signature itself is still intact
+        prekey.verify(&identity.public_key()).unwrap();
     }
 }
@@ -14,6 +14,8 @@
 #![warn(missing_docs)]
 #![deny(unsafe_code)]
 
+pub mod clock;
+pub mod entropy;
 pub mod error;
 pub mod file;
 pub mod hash;
@@ -27,6 +29,7 @@ pub mod sig;
 pub mod symmetric;
 
 // Re-export commonly used types
+pub use clock::{Clock, MockClock, SystemClock, TimeRange};
 pub use error::{CryptoError, Result};
 pub use hash::{blake3, domain};
 pub use symmetric::CipherSuite;
@@ -41,3 +44,13 @@ pub fn init() -> Result<()> {
     mem::wipe::prevent_core_dumps()?;
     Ok(())
 }
+
+/// Initialize and verify the OS RNG before any long-term keys are generated
+///
+/// Runs [`init`], then [`entropy::check_health`]. Fails if the RNG output
+/// looks degenerate; the report says whether the kernel pool still had to
+/// be seeded, which callers may want to warn about.
+pub fn init_with_entropy_check() -> Result<entropy::EntropyReport> {
+    init()?;
+    entropy::check_health()
+}
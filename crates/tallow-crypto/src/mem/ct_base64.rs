@@ -0,0 +1,234 @@
+//! Constant-time base64 for secret key material
+//!
+//! A table-driven base64 codec indexes memory with the bytes being encoded,
+//! so the cache lines it touches depend on the secret. This codec maps
+//! between 6-bit values and characters with arithmetic masks instead: no
+//! lookup tables and no branches on the data, only on the (public) length.
+//!
+//! Use it when encoding or decoding secret keys (e.g. exported keyrings).
+//! It is slower than a table-driven codec, so public data need not use it.
+//! The alphabet is the standard one from RFC 4648 with `=` padding.
+
+use crate::error::{CryptoError, Result};
+use zeroize::Zeroizing;
+
+/// Encode `data` as padded standard base64
+pub fn ct_base64_encode(data: &[u8]) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::with_capacity(data.len().div_ceil(3) * 4));
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(encode_6bits(n >> 18));
+        out.push(encode_6bits(n >> 12));
+        // How much of the chunk is present depends only on the input length
+        out.push(if chunk.len() > 1 {
+            encode_6bits(n >> 6)
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            encode_6bits(n)
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Decode padded standard base64
+///
+/// Invalid characters are detected without branching on them: every
+/// character is decoded and the failure is reported only at the end, with
+/// no indication of where it was.
+pub fn ct_base64_decode(encoded: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes = encoded.as_bytes();
+    if bytes.len() % 4 != 0 {
+        return Err(CryptoError::Serialization(
+            "base64 length is not a multiple of 4".to_string(),
+        ));
+    }
+    let padding = bytes
+        .iter()
+        .rev()
+        .take(2)
+        .take_while(|&&c| c == b'=')
+        .count();
+    let data_chars = bytes.len() - padding;
+
+    let mut out = Zeroizing::new(Vec::with_capacity(data_chars * 3 / 4));
+    // Sign bit set if any character failed to decode
+    let mut err: i32 = 0;
+    for quad in bytes[..data_chars].chunks(4) {
+        let mut n: u32 = 0;
+        for &c in quad {
+            let v = decode_6bits(c);
+            err |= v;
+            n = (n << 6) | (v as u32 & 0x3f);
+        }
+        match quad.len() {
+            4 => out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8]),
+            3 => {
+                // 18 bits read; the low 2 must be zero for a canonical encoding
+                err |= -((n & 0x3) as i32);
+                out.extend_from_slice(&[(n >> 10) as u8, (n >> 2) as u8]);
+            }
+            2 => {
+                // 12 bits read; the low 4 must be zero
+                err |= -((n & 0xf) as i32);
+                out.push((n >> 4) as u8);
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if err < 0 {
+        return Err(invalid());
+    }
+    Ok(out)
+}
+
+fn invalid() -> CryptoError {
+    CryptoError::Serialization("invalid base64".to_string())
+}
+
+/// Map the low 6 bits of `x` to its base64 character
+///
+/// Starts from the offset for `A..=Z` and adjusts it with masks that are
+/// all-ones exactly when `x` is past each range boundary.
+fn encode_6bits(x: u32) -> char {
+    let x = (x & 0x3f) as i32;
+    let mut diff = b'A' as i32;
+    // 26..: 'a' - 26
+    diff += ((25 - x) >> 8) & 6;
+    // 52..: '0' - 52
+    diff -= ((51 - x) >> 8) & 75;
+    // 62: '+' - 62
+    diff -= ((61 - x) >> 8) & 15;
+    // 63: '/' - 63
+    diff += ((62 - x) >> 8) & 3;
+    char::from((x + diff) as u8)
+}
+
+/// Map a base64 character to its 6-bit value, or -1 if it is not one
+///
+/// Each range contributes `value + 1` under a mask that is all-ones only
+/// when `c` is inside it, on top of a starting value of -1.
+fn decode_6bits(c: u8) -> i32 {
+    let c = c as i32;
+    let mut ret = -1;
+    // 'A'..='Z' => 0..=25
+    ret += (((0x40 - c) & (c - 0x5b)) >> 8) & (c - 64);
+    // 'a'..='z' => 26..=51
+    ret += (((0x60 - c) & (c - 0x7b)) >> 8) & (c - 70);
+    // '0'..='9' => 52..=61
+    ret += (((0x2f - c) & (c - 0x3a)) >> 8) & (c + 5);
+    // '+' => 62
+    ret += (((0x2a - c) & (c - 0x2c)) >> 8) & 63;
+    // '/' => 63
+    ret += (((0x2e - c) & (c - 0x30)) >> 8) & 64;
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Conventional table-driven encoder to check against
+    fn reference_encode(data: &[u8]) -> String {
+        const TABLE: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in data.chunks(3) {
+            let n = ((chunk[0] as u32) << 16)
+                | ((*chunk.get(1).unwrap_or(&0) as u32) << 8)
+                | *chunk.get(2).unwrap_or(&0) as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(TABLE[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(*ct_base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(*ct_base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_matches_table_driven_codec() {
+        // Every 6-bit value maps to the standard alphabet
+        for x in 0..64u32 {
+            let c = encode_6bits(x);
+            assert_eq!(
+                c,
+                reference_encode(&[(x << 2) as u8]).chars().next().unwrap()
+            );
+            assert_eq!(decode_6bits(c as u8), x as i32);
+        }
+        // Every other byte is rejected
+        for c in 0..=255u8 {
+            if !c.is_ascii_alphanumeric() && c != b'+' && c != b'/' {
+                assert_eq!(decode_6bits(c), -1, "byte {:#04x}", c);
+            }
+        }
+
+        // Every length from empty to several blocks
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 151 + 7) as u8).collect();
+        for len in 0..=data.len() {
+            let encoded = ct_base64_encode(&data[..len]);
+            assert_eq!(*encoded, reference_encode(&data[..len]), "len {}", len);
+            assert_eq!(*ct_base64_decode(&encoded).unwrap(), &data[..len]);
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        for bad in [
+            "Zg=",
+            "Zm9v!A==",
+            "Zm9v Zg==",
+            "Zh==",
+            "Zm9=",
+            "=Zm9",
+            "Z===",
+            "Zg=A",
+            "Zm9vÿ",
+        ] {
+            assert!(ct_base64_decode(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn roundtrip(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+            let encoded = ct_base64_encode(&data);
+            prop_assert_eq!(encoded.len(), data.len().div_ceil(3) * 4);
+            prop_assert_eq!(&*ct_base64_decode(&encoded).unwrap(), &data);
+        }
+    }
+}
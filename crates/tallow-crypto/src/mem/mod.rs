@@ -4,9 +4,11 @@
 //! and protection against memory dumps.
 
 pub mod constant_time;
+pub mod ct_base64;
 pub mod secure_buf;
 pub mod wipe;
 
 pub use constant_time::{ct_eq, ct_select};
+pub use ct_base64::{ct_base64_decode, ct_base64_encode};
 pub use secure_buf::SecureBuf;
 pub use wipe::{lock_memory, prevent_core_dumps, wipe_on_drop};
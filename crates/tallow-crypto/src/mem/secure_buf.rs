@@ -41,6 +41,13 @@ impl<T: Zeroize> SecureBuf<T> {
     }
 }
 
+/// Wipe the contents early, without waiting for drop
+impl<T: Zeroize> Zeroize for SecureBuf<T> {
+    fn zeroize(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
 impl<T: Zeroize> From<T> for SecureBuf<T> {
     fn from(value: T) -> Self {
         Self::new(value)
@@ -174,6 +174,31 @@ impl DoubleRatchet {
         Ok(plaintext)
     }
 
+    /// Key-confirmation tag for the next outbound message
+    ///
+    /// Binds the current send chain key and counter under `domain`. The
+    /// peer recomputes it with [`recv_confirmation`](Self::recv_confirmation);
+    /// a mismatch means the chains have desynchronized.
+    pub fn send_confirmation(&self, domain: &str) -> [u8; 32] {
+        Self::confirmation_tag(domain, &self.send_chain_key, self.send_counter)
+    }
+
+    /// Expected key-confirmation tag for the next inbound message
+    pub fn recv_confirmation(&self, domain: &str) -> [u8; 32] {
+        Self::confirmation_tag(domain, &self.recv_chain_key, self.recv_counter)
+    }
+
+    /// Derive a confirmation tag from a chain key and message counter
+    fn confirmation_tag(domain: &str, chain_key: &[u8; 32], counter: u64) -> [u8; 32] {
+        let mut input = [0u8; 40];
+        input[..32].copy_from_slice(chain_key);
+        input[32..].copy_from_slice(&counter.to_le_bytes());
+
+        let tag = blake3::derive_key(domain, &input);
+        input.zeroize();
+        tag
+    }
+
     /// Perform DH ratchet step
     ///
     /// # Errors
@@ -306,6 +331,50 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_confirmation_tags_match_in_sync() {
+        let shared_secret = [42u8; 32];
+        let mut sender = DoubleRatchet::init(&shared_secret);
+        let mut receiver = DoubleRatchet::init_responder(&shared_secret);
+        let domain = crate::hash::domain::DOMAIN_KEY_CONFIRM_SENDER;
+
+        for i in 0..3 {
+            assert_eq!(
+                sender.send_confirmation(domain),
+                receiver.recv_confirmation(domain)
+            );
+            let ct = sender
+                .encrypt_message(format!("m{}", i).as_bytes())
+                .unwrap();
+            receiver.decrypt_message(&ct).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_confirmation_tags_detect_skipped_step() {
+        let shared_secret = [42u8; 32];
+        let mut sender = DoubleRatchet::init(&shared_secret);
+        let receiver = DoubleRatchet::init_responder(&shared_secret);
+        let domain = crate::hash::domain::DOMAIN_KEY_CONFIRM_SENDER;
+
+        // Message 0 is lost in transit
+        let _dropped = sender.encrypt_message(b"lost").unwrap();
+
+        assert_ne!(
+            sender.send_confirmation(domain),
+            receiver.recv_confirmation(domain)
+        );
+    }
+
+    #[test]
+    fn test_confirmation_tags_are_domain_separated() {
+        let ratchet = DoubleRatchet::init(&[7u8; 32]);
+        assert_ne!(
+            ratchet.send_confirmation(crate::hash::domain::DOMAIN_KEY_CONFIRM_SENDER),
+            ratchet.send_confirmation(crate::hash::domain::DOMAIN_KEY_CONFIRM_RECEIVER)
+        );
+    }
+
     #[test]
     fn test_pq_secret_mixing() {
         let shared_secret = [42u8; 32];
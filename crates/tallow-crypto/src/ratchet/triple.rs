@@ -55,6 +55,16 @@ impl TripleRatchet {
         self.double_ratchet.decrypt_message(ciphertext)
     }
 
+    /// Key-confirmation tag for the next outbound message
+    pub fn send_confirmation(&self, domain: &str) -> [u8; 32] {
+        self.double_ratchet.send_confirmation(domain)
+    }
+
+    /// Expected key-confirmation tag for the next inbound message
+    pub fn recv_confirmation(&self, domain: &str) -> [u8; 32] {
+        self.double_ratchet.recv_confirmation(domain)
+    }
+
     /// Perform ratchet step, potentially mixing PQ secret
     ///
     /// If the sparse PQ ratchet triggers a rekey, the new PQ shared secret
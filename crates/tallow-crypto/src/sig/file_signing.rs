@@ -1,11 +1,24 @@
 //! File chunk signing for integrity verification
+//!
+//! Also home to detached signatures: a standalone `.tallowsig` file that
+//! lets anyone holding the signer's fingerprint check a published file.
 
 use crate::error::{CryptoError, Result};
 use crate::hash::blake3;
 use crate::mem::constant_time;
-use crate::sig::Ed25519Signer;
+use crate::sig::hybrid::{self, HybridPublicKey, HybridSignature, HybridSigner};
+use crate::sig::{Ed25519Signer, SignatureAlgorithm};
 use serde::{Deserialize, Serialize};
 
+/// Magic bytes at the start of an encoded detached signature
+const DETACHED_MAGIC: &[u8; 4] = b"TSIG";
+
+/// Encoding version of detached signatures
+const DETACHED_VERSION: u8 = 1;
+
+/// Domain separator for detached file signatures
+const DETACHED_DOMAIN: &[u8] = b"tallow-detached-sig-v1:";
+
 /// Signature for a file chunk
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ChunkSignature {
@@ -67,6 +80,134 @@ pub fn sign_chunk(signer: &Ed25519Signer, chunk_data: &[u8], chunk_index: u64) -
     }
 }
 
+/// Background chunk signer that overlaps signing with transmission
+///
+/// Chunks are handed to a dedicated thread with [`submit`](Self::submit)
+/// and their signatures collected with [`recv`](Self::recv), so chunk N+1
+/// is hashed and signed while chunk N is on the wire. A single worker
+/// processes chunks in submission order, so signatures always come back in
+/// that order.
+///
+/// The job queue is bounded: at most `capacity` chunks wait for the worker
+/// and one more is being signed, after which `submit` blocks until the
+/// caller drains a signature with `recv`. Callers must therefore receive
+/// before more than `capacity + 1` chunks are outstanding.
+pub struct ChunkSigningWorker {
+    jobs: Option<std::sync::mpsc::SyncSender<(u64, Vec<u8>)>>,
+    results: std::sync::mpsc::Receiver<ChunkSignature>,
+    worker: Option<std::thread::JoinHandle<()>>,
+    in_flight: usize,
+}
+
+impl ChunkSigningWorker {
+    /// Start a worker thread signing with `signer`
+    ///
+    /// `capacity` is the number of chunks that may queue ahead of the one
+    /// being signed; zero is treated as one.
+    pub fn spawn(signer: Ed25519Signer, capacity: usize) -> Result<Self> {
+        let (jobs, job_rx) = std::sync::mpsc::sync_channel::<(u64, Vec<u8>)>(capacity.max(1));
+        // Rendezvous: the worker holds at most one finished signature
+        let (result_tx, results) = std::sync::mpsc::sync_channel(0);
+
+        let worker = std::thread::Builder::new()
+            .name("tallow-chunk-signer".to_string())
+            .spawn(move || {
+                for (index, data) in job_rx {
+                    if result_tx.send(sign_chunk(&signer, &data, index)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .map_err(|e| CryptoError::Signing(format!("Failed to start signing worker: {}", e)))?;
+
+        Ok(Self {
+            jobs: Some(jobs),
+            results,
+            worker: Some(worker),
+            in_flight: 0,
+        })
+    }
+
+    /// Queue a chunk for signing, blocking while the queue is full
+    pub fn submit(&mut self, chunk_index: u64, chunk_data: Vec<u8>) -> Result<()> {
+        let jobs = self
+            .jobs
+            .as_ref()
+            .ok_or_else(|| CryptoError::Signing("Signing worker is closed".to_string()))?;
+        jobs.send((chunk_index, chunk_data))
+            .map_err(|_| CryptoError::Signing("Signing worker stopped".to_string()))?;
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    /// Wait for the signature of the oldest outstanding chunk
+    ///
+    /// Returns `None` when no chunks are outstanding.
+    pub fn recv(&mut self) -> Result<Option<ChunkSignature>> {
+        if self.in_flight == 0 {
+            return Ok(None);
+        }
+        let signature = self
+            .results
+            .recv()
+            .map_err(|_| CryptoError::Signing("Signing worker stopped".to_string()))?;
+        self.in_flight -= 1;
+        Ok(Some(signature))
+    }
+
+    /// Queue a chunk without blocking
+    ///
+    /// Returns the chunk data back if the queue is full.
+    pub fn try_submit(&mut self, chunk_index: u64, chunk_data: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let jobs = self
+            .jobs
+            .as_ref()
+            .ok_or_else(|| CryptoError::Signing("Signing worker is closed".to_string()))?;
+        match jobs.try_send((chunk_index, chunk_data)) {
+            Ok(()) => {
+                self.in_flight += 1;
+                Ok(None)
+            }
+            Err(std::sync::mpsc::TrySendError::Full((_, data))) => Ok(Some(data)),
+            Err(std::sync::mpsc::TrySendError::Disconnected(_)) => {
+                Err(CryptoError::Signing("Signing worker stopped".to_string()))
+            }
+        }
+    }
+
+    /// Number of chunks submitted whose signatures have not been received
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Collect all outstanding signatures in order and stop the worker
+    pub fn finish(mut self) -> Result<Vec<ChunkSignature>> {
+        self.jobs = None;
+        let mut signatures = Vec::with_capacity(self.in_flight);
+        while let Some(signature) = self.recv()? {
+            signatures.push(signature);
+        }
+        if let Some(worker) = self.worker.take() {
+            worker
+                .join()
+                .map_err(|_| CryptoError::Signing("Signing worker panicked".to_string()))?;
+        }
+        Ok(signatures)
+    }
+}
+
+impl Drop for ChunkSigningWorker {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            // Closing both channels stops a worker that is waiting for a
+            // job or blocked handing over a signature nobody will read
+            self.jobs = None;
+            self.results = std::sync::mpsc::sync_channel(0).1;
+            let _ = worker.join();
+        }
+    }
+}
+
 /// Verify a chunk signature
 ///
 /// # Arguments
@@ -132,6 +273,123 @@ impl FileManifest {
     }
 }
 
+/// Detached signature over a file's BLAKE3 hash
+///
+/// The signer's hybrid public key travels with the signature, but it is
+/// only trusted once its fingerprint matches one the verifier already
+/// knows (a contact or a key they were given out of band).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    /// Algorithm of `signature`
+    pub algorithm: SignatureAlgorithm,
+    /// BLAKE3 hash of the serialized signer public key
+    pub signer_fingerprint: [u8; 32],
+    /// Signer's hybrid public key
+    pub public_key: HybridPublicKey,
+    /// BLAKE3 hash of the signed file
+    pub file_hash: [u8; 32],
+    /// Signature over the fingerprint and file hash
+    pub signature: HybridSignature,
+}
+
+impl DetachedSignature {
+    /// Sign a file, given its BLAKE3 hash
+    pub fn sign(signer: &HybridSigner, file_hash: [u8; 32]) -> Result<Self> {
+        let public_key = signer.public_key();
+        let signer_fingerprint = key_fingerprint(&public_key)?;
+        let signature = signer.sign(&detached_message(&signer_fingerprint, &file_hash))?;
+        Ok(Self {
+            algorithm: SignatureAlgorithm::Hybrid,
+            signer_fingerprint,
+            public_key,
+            file_hash,
+            signature,
+        })
+    }
+
+    /// Check the signature for a file hash against the expected signer
+    ///
+    /// `expected_signer` is the fingerprint of the key the caller trusts.
+    /// A signature from any other key fails, even if it is otherwise valid.
+    pub fn verify(&self, file_hash: &[u8; 32], expected_signer: &[u8; 32]) -> Result<()> {
+        if self.algorithm != SignatureAlgorithm::Hybrid {
+            return Err(CryptoError::Unsupported(format!(
+                "detached signature algorithm {:?}",
+                self.algorithm
+            )));
+        }
+        if !constant_time::ct_eq(
+            &key_fingerprint(&self.public_key)?,
+            &self.signer_fingerprint,
+        ) {
+            return Err(CryptoError::Verification(
+                "embedded public key does not match the signer fingerprint".to_string(),
+            ));
+        }
+        if !constant_time::ct_eq(&self.signer_fingerprint, expected_signer) {
+            return Err(CryptoError::Verification(format!(
+                "signed by key {}, not the expected key {}",
+                hex::encode(&self.signer_fingerprint[..8]),
+                hex::encode(&expected_signer[..8])
+            )));
+        }
+        if !constant_time::ct_eq(&self.file_hash, file_hash) {
+            return Err(CryptoError::Verification(
+                "file does not match the signature (modified or a different file)".to_string(),
+            ));
+        }
+        let message = detached_message(&self.signer_fingerprint, &self.file_hash);
+        hybrid::verify(&self.public_key, &message, &self.signature)
+    }
+
+    /// Encode as the contents of a `.tallowsig` file
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let body = bincode::serialize(self).map_err(|e| {
+            CryptoError::Serialization(format!("Failed to serialize signature: {}", e))
+        })?;
+        let mut bytes = Vec::with_capacity(DETACHED_MAGIC.len() + 1 + body.len());
+        bytes.extend_from_slice(DETACHED_MAGIC);
+        bytes.push(DETACHED_VERSION);
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Decode the contents of a `.tallowsig` file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let body = bytes
+            .strip_prefix(DETACHED_MAGIC.as_slice())
+            .ok_or_else(|| CryptoError::Serialization("Not a tallow signature file".to_string()))?;
+        match body.split_first() {
+            Some((&DETACHED_VERSION, body)) => bincode::deserialize(body).map_err(|e| {
+                CryptoError::Serialization(format!("Malformed signature file: {}", e))
+            }),
+            Some((version, _)) => Err(CryptoError::Unsupported(format!(
+                "signature file version {}",
+                version
+            ))),
+            None => Err(CryptoError::Serialization(
+                "Truncated signature file".to_string(),
+            )),
+        }
+    }
+}
+
+/// Fingerprint of a hybrid public key, as used for identities
+fn key_fingerprint(public_key: &HybridPublicKey) -> Result<[u8; 32]> {
+    let bytes = bincode::serialize(public_key).map_err(|e| {
+        CryptoError::Serialization(format!("Failed to serialize public key: {}", e))
+    })?;
+    Ok(blake3::hash(&bytes))
+}
+
+fn detached_message(fingerprint: &[u8; 32], file_hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(DETACHED_DOMAIN.len() + 64);
+    message.extend_from_slice(DETACHED_DOMAIN);
+    message.extend_from_slice(fingerprint);
+    message.extend_from_slice(file_hash);
+    message
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +418,141 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    fn test_chunks() -> Vec<Vec<u8>> {
+        (0..32u8).map(|i| vec![i; 1000 + i as usize]).collect()
+    }
+
+    #[test]
+    fn test_worker_signatures_in_chunk_order() {
+        let signer = Ed25519Signer::keygen();
+        let public_key = signer.verifying_key_bytes();
+        let mut worker = ChunkSigningWorker::spawn(signer, 4).unwrap();
+        let chunks = test_chunks();
+
+        let mut signatures = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            worker.submit(i as u64, chunk.clone()).unwrap();
+            // Keep a couple of chunks outstanding, as a sender would
+            if worker.in_flight() > 2 {
+                signatures.push(worker.recv().unwrap().unwrap());
+            }
+        }
+        signatures.extend(worker.finish().unwrap());
+
+        assert_eq!(signatures.len(), chunks.len());
+        for (i, (sig, chunk)) in signatures.iter().zip(&chunks).enumerate() {
+            assert_eq!(sig.index, i as u64);
+            verify_chunk(&public_key, chunk, sig).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_worker_backpressure_bounds_queue() {
+        let capacity = 2;
+        let mut worker = ChunkSigningWorker::spawn(Ed25519Signer::keygen(), capacity).unwrap();
+        let chunks = test_chunks();
+
+        // Without draining, the queue fills after `capacity` chunks plus
+        // the one the worker is holding
+        let mut accepted = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            if worker
+                .try_submit(i as u64, chunk.clone())
+                .unwrap()
+                .is_some()
+            {
+                break;
+            }
+            accepted += 1;
+        }
+        assert!(
+            accepted >= capacity && accepted <= capacity + 1,
+            "{}",
+            accepted
+        );
+        assert_eq!(worker.in_flight(), accepted);
+
+        // Draining one signature makes room again
+        assert_eq!(worker.recv().unwrap().unwrap().index, 0);
+        worker
+            .submit(accepted as u64, chunks[accepted].clone())
+            .unwrap();
+
+        let rest = worker.finish().unwrap();
+        let indices: Vec<u64> = rest.iter().map(|s| s.index).collect();
+        assert_eq!(indices, (1..=accepted as u64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_worker_matches_synchronous_signing() {
+        let signer = Ed25519Signer::keygen();
+        let chunks = test_chunks();
+        let expected: Vec<ChunkSignature> = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| sign_chunk(&signer, c, i as u64))
+            .collect();
+
+        let mut worker = ChunkSigningWorker::spawn(signer, 3).unwrap();
+        let mut signatures = Vec::new();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            worker.submit(i as u64, chunk).unwrap();
+            if worker.in_flight() > 3 {
+                signatures.push(worker.recv().unwrap().unwrap());
+            }
+        }
+        signatures.extend(worker.finish().unwrap());
+
+        // Ed25519 is deterministic, so the outputs are byte-identical
+        assert_eq!(signatures.len(), expected.len());
+        for (got, want) in signatures.iter().zip(&expected) {
+            assert_eq!(got.index, want.index);
+            assert_eq!(got.chunk_hash, want.chunk_hash);
+            assert_eq!(got.signature, want.signature);
+        }
+    }
+
+    #[test]
+    fn test_detached_signature_roundtrip() {
+        let identity = crate::keys::IdentityKeyPair::generate().unwrap();
+        let file_hash = blake3::hash(b"release tarball");
+
+        let sig = DetachedSignature::sign(identity.signer(), file_hash).unwrap();
+        assert_eq!(&sig.signer_fingerprint, identity.id());
+        assert_eq!(sig.algorithm, SignatureAlgorithm::Hybrid);
+
+        let decoded = DetachedSignature::from_bytes(&sig.to_bytes().unwrap()).unwrap();
+        decoded.verify(&file_hash, identity.id()).unwrap();
+    }
+
+    #[test]
+    fn test_detached_signature_modified_file() {
+        let identity = crate::keys::IdentityKeyPair::generate().unwrap();
+        let sig = DetachedSignature::sign(identity.signer(), blake3::hash(b"original")).unwrap();
+
+        let err = sig
+            .verify(&blake3::hash(b"0riginal"), identity.id())
+            .unwrap_err();
+        assert!(err.to_string().contains("file does not match"), "{}", err);
+    }
+
+    #[test]
+    fn test_detached_signature_wrong_key() {
+        let identity = crate::keys::IdentityKeyPair::generate().unwrap();
+        let other = crate::keys::IdentityKeyPair::generate().unwrap();
+        let file_hash = blake3::hash(b"data");
+        let sig = DetachedSignature::sign(identity.signer(), file_hash).unwrap();
+
+        let err = sig.verify(&file_hash, other.id()).unwrap_err();
+        assert!(err.to_string().contains("not the expected key"), "{}", err);
+
+        // Swapping in another key under the original fingerprint
+        let mut forged = sig.clone();
+        forged.public_key = other.signer().public_key();
+        assert!(forged.verify(&file_hash, identity.id()).is_err());
+
+        assert!(DetachedSignature::from_bytes(b"TSIG").is_err());
+        assert!(DetachedSignature::from_bytes(b"nope").is_err());
+    }
 }
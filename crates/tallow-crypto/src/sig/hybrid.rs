@@ -1,7 +1,10 @@
 //! Hybrid signature combining ML-DSA and Ed25519
+//!
+//! Verification is an AND of both components: a hybrid signature stays
+//! unforgeable as long as either algorithm remains unbroken.
 
-use crate::error::Result;
-use crate::sig::{ed25519, mldsa};
+use crate::error::{CryptoError, Result};
+use crate::sig::{ed25519, mldsa, SignatureAlgorithm};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
@@ -74,6 +77,23 @@ impl HybridSigner {
             ed25519: self.ed25519.verifying_key_bytes(),
         }
     }
+
+    /// Verify a signature against this signer's public key
+    ///
+    /// Returns `false` if either component fails.
+    pub fn verify(&self, message: &[u8], signature: &HybridSignature) -> bool {
+        self.verify_detailed(message, signature).is_valid()
+    }
+
+    /// Verify a signature against this signer's public key, reporting
+    /// each component separately
+    pub fn verify_detailed(
+        &self,
+        message: &[u8],
+        signature: &HybridSignature,
+    ) -> HybridVerification {
+        verify_detailed(&self.public_key(), message, signature)
+    }
 }
 
 /// Hybrid public key
@@ -85,6 +105,34 @@ pub struct HybridPublicKey {
     pub ed25519: [u8; 32],
 }
 
+/// Per-component result of verifying a hybrid signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HybridVerification {
+    /// Whether the ML-DSA-87 component verified
+    pub mldsa: bool,
+    /// Whether the Ed25519 component verified
+    pub ed25519: bool,
+}
+
+impl HybridVerification {
+    /// Whether the signature as a whole is valid (both components passed)
+    pub fn is_valid(&self) -> bool {
+        self.mldsa && self.ed25519
+    }
+
+    /// The components that failed verification, if any
+    pub fn failed_components(&self) -> Vec<SignatureAlgorithm> {
+        let mut failed = Vec::new();
+        if !self.mldsa {
+            failed.push(SignatureAlgorithm::MlDsa87);
+        }
+        if !self.ed25519 {
+            failed.push(SignatureAlgorithm::Ed25519);
+        }
+        failed
+    }
+}
+
 /// Verify a hybrid signature
 ///
 /// Both signatures must be valid for verification to succeed.
@@ -93,13 +141,31 @@ pub fn verify(
     message: &[u8],
     signature: &HybridSignature,
 ) -> Result<()> {
-    // Verify ML-DSA signature
-    mldsa::verify(&public_key.mldsa, message, &signature.mldsa)?;
+    let outcome = verify_detailed(public_key, message, signature);
+    if outcome.is_valid() {
+        return Ok(());
+    }
 
-    // Verify Ed25519 signature
-    ed25519::verify(&public_key.ed25519, message, &signature.ed25519)?;
+    Err(CryptoError::Verification(format!(
+        "hybrid signature verification failed ({:?})",
+        outcome.failed_components()
+    )))
+}
 
-    Ok(())
+/// Verify both components of a hybrid signature independently
+///
+/// Unlike [`verify`], both checks always run, so the result says exactly
+/// which component(s) failed. Use [`HybridVerification::is_valid`] for the
+/// accept/reject decision.
+pub fn verify_detailed(
+    public_key: &HybridPublicKey,
+    message: &[u8],
+    signature: &HybridSignature,
+) -> HybridVerification {
+    HybridVerification {
+        mldsa: mldsa::verify(&public_key.mldsa, message, &signature.mldsa).is_ok(),
+        ed25519: ed25519::verify(&public_key.ed25519, message, &signature.ed25519).is_ok(),
+    }
 }
 
 #[cfg(test)]
@@ -117,4 +183,67 @@ mod tests {
         let result = verify(&public_key, message, &signature);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_both_components_valid() {
+        let signer = HybridSigner::keygen().unwrap();
+        let signature = signer.sign(b"payload").unwrap();
+
+        assert!(signer.verify(b"payload", &signature));
+        let outcome = signer.verify_detailed(b"payload", &signature);
+        assert!(outcome.is_valid());
+        assert!(outcome.failed_components().is_empty());
+    }
+
+    #[test]
+    fn test_corrupted_mldsa_component_fails() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut signature = signer.sign(b"payload").unwrap();
+        signature.mldsa[100] ^= 0x01;
+
+        assert!(!signer.verify(b"payload", &signature));
+        assert!(verify(&signer.public_key(), b"payload", &signature).is_err());
+
+        let outcome = signer.verify_detailed(b"payload", &signature);
+        assert!(!outcome.mldsa);
+        assert!(outcome.ed25519);
+        assert_eq!(
+            outcome.failed_components(),
+            vec![SignatureAlgorithm::MlDsa87]
+        );
+    }
+
+    #[test]
+    fn test_corrupted_ed25519_component_fails() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut signature = signer.sign(b"payload").unwrap();
+        signature.ed25519[10] ^= 0x01;
+
+        assert!(!signer.verify(b"payload", &signature));
+        assert!(verify(&signer.public_key(), b"payload", &signature).is_err());
+
+        let outcome = signer.verify_detailed(b"payload", &signature);
+        assert!(outcome.mldsa);
+        assert!(!outcome.ed25519);
+        assert_eq!(
+            outcome.failed_components(),
+            vec![SignatureAlgorithm::Ed25519]
+        );
+    }
+
+    #[test]
+    fn test_components_from_different_keys_fail() {
+        // Splicing a valid Ed25519 signature from another key must not pass
+        let signer = HybridSigner::keygen().unwrap();
+        let other = HybridSigner::keygen().unwrap();
+        let mut signature = signer.sign(b"payload").unwrap();
+        signature.ed25519 = other.sign(b"payload").unwrap().ed25519;
+
+        let outcome = signer.verify_detailed(b"payload", &signature);
+        assert!(!outcome.is_valid());
+        assert_eq!(
+            outcome.failed_components(),
+            vec![SignatureAlgorithm::Ed25519]
+        );
+    }
 }
@@ -9,8 +9,10 @@ pub mod mldsa;
 pub mod slhdsa;
 
 pub use ed25519::Ed25519Signer;
-pub use file_signing::{sign_chunk, verify_chunk, ChunkSignature};
-pub use hybrid::{HybridPublicKey, HybridSignature, HybridSigner};
+pub use file_signing::{
+    sign_chunk, verify_chunk, ChunkSignature, ChunkSigningWorker, DetachedSignature,
+};
+pub use hybrid::{HybridPublicKey, HybridSignature, HybridSigner, HybridVerification};
 pub use mldsa::MlDsaSigner;
 pub use slhdsa::SlhDsaSigner;
 
@@ -14,7 +14,7 @@ pub mod aegis;
 pub use aes_gcm::{decrypt as aes_decrypt, encrypt as aes_encrypt};
 pub use chacha20::{decrypt as chacha_decrypt, encrypt as chacha_encrypt};
 pub use negotiation::{detect_aes_ni, select_cipher};
-pub use nonce::NonceGenerator;
+pub use nonce::{NonceGenerator, NonceStrategy};
 
 #[cfg(feature = "aegis")]
 pub use self::aegis::{decrypt as aegis_decrypt, encrypt as aegis_encrypt};
@@ -1,10 +1,62 @@
 //! Nonce generation and management
+//!
+//! A [`NonceGenerator`] follows one [`NonceStrategy`] per session. `Counter`
+//! nonces are a 64-bit position plus seed and direction bytes, so the nonce
+//! for any position can be recomputed and a resumed stream picks up at a
+//! chunk index. `Random` nonces are drawn fresh from the OS. Both signal
+//! [`needs_rekey`](NonceGenerator::needs_rekey) well before their limit:
+//! the counter wrapping, or the number of random 96-bit nonces one key may
+//! safely use.
+//!
+//! Debug builds also run a nonce-reuse detector: every nonce a generator
+//! issues is recorded in a bounded window shared with its clones, and issuing
+//! one that is still in the window is an error. This catches rewound counters
+//! and cloned generators in tests. Release builds compile the tracking out
+//! entirely.
 
 use crate::error::Result;
 use rand::RngCore;
 use rand_core::OsRng;
 use zeroize::Zeroize;
 
+#[cfg(debug_assertions)]
+use std::collections::{HashSet, VecDeque};
+#[cfg(debug_assertions)]
+use std::sync::{Arc, Mutex};
+
+/// Number of most recent nonces the debug reuse detector remembers
+///
+/// Reuse of a nonce older than this window goes undetected; the window is
+/// exact (no false positives), only its reach is limited.
+#[cfg(debug_assertions)]
+pub const REUSE_WINDOW: usize = 4096;
+
+/// Counter positions left when [`NonceGenerator::needs_rekey`] starts
+/// reporting `true` for a counter generator
+pub const COUNTER_REKEY_MARGIN: u64 = 1 << 32;
+
+/// Random nonces one key may encrypt with before a rekey is required
+///
+/// NIST SP 800-38D caps randomly generated 96-bit IVs at 2^32 per key to
+/// keep the collision probability negligible.
+pub const RANDOM_NONCE_LIMIT: u64 = 1 << 32;
+
+/// Fraction of [`RANDOM_NONCE_LIMIT`] after which a random generator asks
+/// for a rekey, leaving room to complete it
+const RANDOM_REKEY_AT: u64 = RANDOM_NONCE_LIMIT - RANDOM_NONCE_LIMIT / 16;
+
+/// How a [`NonceGenerator`] produces nonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonceStrategy {
+    /// Position counter plus seed and direction: deterministic, so a
+    /// stream with a fixed key can resume from a chunk index
+    #[default]
+    Counter,
+    /// Fresh OS randomness for every nonce, for ciphers and sessions where
+    /// no position is shared between the peers
+    Random,
+}
+
 /// Direction for bidirectional nonce generation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
@@ -17,12 +69,44 @@ pub enum Direction {
 /// Nonce generator with counter-based generation and direction encoding
 ///
 /// This generator ensures nonces are never reused by combining a random seed
-/// with a counter and direction bit.
+/// with a counter and direction bit. With [`NonceStrategy::Random`] the
+/// counter only tracks how many nonces the key has used.
+///
+/// Clones share the debug reuse detector, so a clone issuing the same nonce
+/// as its original is caught as well.
 #[derive(Clone)]
 pub struct NonceGenerator {
     counter: u64,
     seed: [u8; 32],
     direction: Direction,
+    strategy: NonceStrategy,
+    #[cfg(debug_assertions)]
+    issued: Arc<Mutex<IssuedNonces>>,
+}
+
+/// Bounded record of recently issued nonces (debug builds only)
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct IssuedNonces {
+    seen: HashSet<[u8; 12]>,
+    order: VecDeque<[u8; 12]>,
+}
+
+#[cfg(debug_assertions)]
+impl IssuedNonces {
+    /// Record `nonce`, returning `false` if it is already in the window
+    fn insert(&mut self, nonce: [u8; 12]) -> bool {
+        if !self.seen.insert(nonce) {
+            return false;
+        }
+        self.order.push_back(nonce);
+        if self.order.len() > REUSE_WINDOW {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
 }
 
 impl Zeroize for NonceGenerator {
@@ -53,6 +137,9 @@ impl NonceGenerator {
             counter: 0,
             seed,
             direction,
+            strategy: NonceStrategy::Counter,
+            #[cfg(debug_assertions)]
+            issued: Arc::default(),
         })
     }
 
@@ -67,23 +154,109 @@ impl NonceGenerator {
             counter: 0,
             seed,
             direction,
+            strategy: NonceStrategy::Counter,
+            #[cfg(debug_assertions)]
+            issued: Arc::default(),
+        }
+    }
+
+    /// Recreate a counter generator whose next nonce is the one for
+    /// `position`
+    ///
+    /// With the seed and direction of the original session, nonces match
+    /// the ones it issued from that position on, so a transfer resumed at
+    /// chunk `position` encrypts exactly as the first attempt would have.
+    pub fn resume_at(seed: [u8; 32], direction: Direction, position: u64) -> Self {
+        let mut gen = Self::from_seed(seed, direction);
+        gen.counter = position;
+        gen
+    }
+
+    /// Use `strategy` for every nonce from now on
+    pub fn with_strategy(mut self, strategy: NonceStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// The strategy in use
+    pub fn strategy(&self) -> NonceStrategy {
+        self.strategy
+    }
+
+    /// Counter nonce for `position`, without advancing the generator
+    ///
+    /// Fails for a random generator, whose nonces cannot be recomputed.
+    pub fn nonce_at(&self, position: u64) -> Result<[u8; 12]> {
+        match self.strategy {
+            NonceStrategy::Counter => Ok(self.counter_nonce(position)),
+            NonceStrategy::Random => Err(crate::error::CryptoError::InvalidNonce(
+                "Random nonces have no position to recompute".to_string(),
+            )),
+        }
+    }
+
+    /// Whether the key should be replaced before many more nonces are issued
+    ///
+    /// Counter generators report this within [`COUNTER_REKEY_MARGIN`] of
+    /// wrapping; random ones as they near [`RANDOM_NONCE_LIMIT`].
+    pub fn needs_rekey(&self) -> bool {
+        match self.strategy {
+            NonceStrategy::Counter => self.counter >= u64::MAX - COUNTER_REKEY_MARGIN,
+            NonceStrategy::Random => self.counter >= RANDOM_REKEY_AT,
         }
     }
 
     /// Generate the next nonce
     ///
-    /// The nonce is constructed as:
+    /// A counter nonce is constructed as:
     /// - First 8 bytes: counter (big-endian)
     /// - Next 4 bytes: XOR of seed bytes 0-3 with direction bit
     ///
+    /// A random nonce is 12 bytes from the OS RNG.
+    ///
     /// # Returns
     ///
     /// 12-byte nonce
+    ///
+    /// # Errors
+    ///
+    /// Fails if the counter is exhausted or a random generator has used up
+    /// [`RANDOM_NONCE_LIMIT`] (rekey first, see
+    /// [`needs_rekey`](Self::needs_rekey)), or (debug builds only) if the
+    /// nonce was already issued by this generator or one of its clones.
     pub fn next_nonce(&mut self) -> Result<[u8; 12]> {
+        let nonce = match self.strategy {
+            NonceStrategy::Counter => self.counter_nonce(self.counter),
+            NonceStrategy::Random => {
+                if self.counter >= RANDOM_NONCE_LIMIT {
+                    return Err(crate::error::CryptoError::InvalidNonce(
+                        "Random nonce limit reached (2^32 messages); rekey required".to_string(),
+                    ));
+                }
+                let mut nonce = [0u8; 12];
+                OsRng.fill_bytes(&mut nonce);
+                nonce
+            }
+        };
+
+        self.counter = self.counter.checked_add(1).ok_or_else(|| {
+            crate::error::CryptoError::InvalidNonce(
+                "Nonce counter exhausted (2^64 messages); rekey required".to_string(),
+            )
+        })?;
+
+        #[cfg(debug_assertions)]
+        self.check_reuse(nonce)?;
+
+        Ok(nonce)
+    }
+
+    /// Counter nonce for `counter` under this generator's seed and direction
+    fn counter_nonce(&self, counter: u64) -> [u8; 12] {
         let mut nonce = [0u8; 12];
 
         // Encode counter in first 8 bytes
-        nonce[..8].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[..8].copy_from_slice(&counter.to_be_bytes());
 
         // Encode seed and direction in last 4 bytes
         let direction_bit = match self.direction {
@@ -95,14 +268,31 @@ impl NonceGenerator {
         nonce[9] = self.seed[1];
         nonce[10] = self.seed[2];
         nonce[11] = self.seed[3];
+        nonce
+    }
 
-        self.counter = self.counter.checked_add(1).ok_or_else(|| {
-            crate::error::CryptoError::InvalidNonce(
-                "Nonce counter exhausted (2^64 messages)".to_string(),
-            )
-        })?;
+    /// Record an issued nonce, failing if it is a repeat (debug builds only)
+    #[cfg(debug_assertions)]
+    fn check_reuse(&self, nonce: [u8; 12]) -> Result<()> {
+        let mut issued = self
+            .issued
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !issued.insert(nonce) {
+            let mut counter_bytes = [0u8; 8];
+            counter_bytes.copy_from_slice(&nonce[..8]);
+            let counter = u64::from_be_bytes(counter_bytes);
+            return Err(crate::error::CryptoError::InvalidNonce(format!(
+                "Nonce reuse detected (counter {})",
+                counter
+            )));
+        }
+        Ok(())
+    }
 
-        Ok(nonce)
+    /// Whether this build checks for nonce reuse (true in debug builds)
+    pub const fn reuse_detection_enabled() -> bool {
+        cfg!(debug_assertions)
     }
 
     /// Get the current counter value
@@ -133,6 +323,7 @@ impl std::fmt::Debug for NonceGenerator {
             .field("counter", &self.counter)
             .field("seed", &"<REDACTED>")
             .field("direction", &self.direction)
+            .field("strategy", &self.strategy)
             .finish()
     }
 }
@@ -191,4 +382,127 @@ mod tests {
         let counter_bytes = u64::from_be_bytes(nonce[..8].try_into().unwrap());
         assert_eq!(counter_bytes, 100);
     }
+
+    #[test]
+    fn test_counter_nonces_deterministic_and_resumable() {
+        let seed = [9u8; 32];
+        let mut first = NonceGenerator::from_seed(seed, Direction::Send);
+        let issued: Vec<[u8; 12]> = (0..10).map(|_| first.next_nonce().unwrap()).collect();
+
+        // Same seed, same sequence
+        let mut again = NonceGenerator::from_seed(seed, Direction::Send);
+        assert_eq!(again.next_nonce().unwrap(), issued[0]);
+
+        // Resuming at chunk 6 continues the original sequence there
+        let mut resumed = NonceGenerator::resume_at(seed, Direction::Send, 6);
+        for expected in &issued[6..] {
+            assert_eq!(&resumed.next_nonce().unwrap(), expected);
+        }
+        assert_eq!(resumed.counter(), 10);
+        assert_eq!(first.nonce_at(3).unwrap(), issued[3]);
+    }
+
+    #[test]
+    fn test_random_nonces_do_not_repeat() {
+        let mut gen = NonceGenerator::new(Direction::Send)
+            .unwrap()
+            .with_strategy(NonceStrategy::Random);
+        assert_eq!(gen.strategy(), NonceStrategy::Random);
+        let nonces: std::collections::HashSet<[u8; 12]> =
+            (0..10_000).map(|_| gen.next_nonce().unwrap()).collect();
+        assert_eq!(nonces.len(), 10_000);
+        assert_eq!(gen.counter(), 10_000);
+        assert!(gen.nonce_at(0).is_err());
+    }
+
+    #[test]
+    fn test_counter_exhaustion_signals_rekey() {
+        let mut gen = NonceGenerator::resume_at([3u8; 32], Direction::Send, 0);
+        assert!(!gen.needs_rekey());
+
+        gen.set_counter(u64::MAX - COUNTER_REKEY_MARGIN - 1);
+        gen.next_nonce().unwrap();
+        assert!(gen.needs_rekey());
+
+        // The last position is refused rather than wrapping to 0
+        gen.set_counter(u64::MAX);
+        assert!(gen
+            .next_nonce()
+            .unwrap_err()
+            .to_string()
+            .contains("rekey required"));
+    }
+
+    #[test]
+    fn test_random_limit_signals_rekey() {
+        let mut gen = NonceGenerator::from_seed([3u8; 32], Direction::Send)
+            .with_strategy(NonceStrategy::Random);
+        gen.set_counter(RANDOM_REKEY_AT - 1);
+        assert!(!gen.needs_rekey());
+        gen.next_nonce().unwrap();
+        assert!(gen.needs_rekey());
+
+        gen.set_counter(RANDOM_NONCE_LIMIT);
+        assert!(gen.next_nonce().is_err());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_rewound_counter_trips_detector() {
+        assert!(NonceGenerator::reuse_detection_enabled());
+        let mut gen = NonceGenerator::from_seed([7u8; 32], Direction::Send);
+        gen.next_nonce().unwrap();
+        gen.next_nonce().unwrap();
+
+        gen.set_counter(1);
+        let err = gen.next_nonce().unwrap_err();
+        assert!(err.to_string().contains("Nonce reuse detected (counter 1)"));
+
+        // Moving past the issued range is fine again
+        gen.set_counter(2);
+        gen.next_nonce().unwrap();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_cloned_generator_trips_detector() {
+        let mut gen = NonceGenerator::from_seed([7u8; 32], Direction::Send);
+        let mut clone = gen.clone();
+        gen.next_nonce().unwrap();
+        assert!(clone.next_nonce().is_err());
+
+        // Independent generators with different contexts don't interfere
+        let mut other = NonceGenerator::from_seed([7u8; 32], Direction::Receive);
+        other.next_nonce().unwrap();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_detector_window_is_bounded() {
+        let mut gen = NonceGenerator::from_seed([7u8; 32], Direction::Send);
+        for _ in 0..REUSE_WINDOW + 1 {
+            gen.next_nonce().unwrap();
+        }
+        assert_eq!(gen.issued.lock().unwrap().seen.len(), REUSE_WINDOW);
+
+        // Counter 0 has aged out of the window, so its reuse goes unnoticed
+        gen.set_counter(0);
+        assert!(gen.next_nonce().is_ok());
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn test_release_build_has_no_detector() {
+        struct Bare {
+            _counter: u64,
+            _seed: [u8; 32],
+            _direction: Direction,
+            _strategy: NonceStrategy,
+        }
+        assert!(!NonceGenerator::reuse_detection_enabled());
+        assert_eq!(
+            std::mem::size_of::<NonceGenerator>(),
+            std::mem::size_of::<Bare>()
+        );
+    }
 }
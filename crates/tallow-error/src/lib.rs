@@ -0,0 +1,218 @@
+//! Error type shared by the Tallow crates
+//!
+//! Commands report failures as `io::Error`, and formatting a subsystem
+//! error into a message used to throw away what kind of failure it was.
+//! A [`TallowError`] keeps the original `CryptoError`, `NetworkError`,
+//! `ProtocolError` or `StoreError` inside the `io::Error`, so callers can
+//! classify it by [`ErrorKind`] instead of by the wording of its message.
+
+#![forbid(unsafe_code)]
+
+use std::io;
+use tallow_crypto::CryptoError;
+use tallow_net::NetworkError;
+use tallow_protocol::ProtocolError;
+use tallow_store::StoreError;
+
+/// Failure categories that callers report differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything without a more specific category
+    Other,
+    /// Wrong code phrase or password, failed handshake or bad signature
+    Auth,
+    /// The user cancelled the operation
+    Cancelled,
+    /// The network or relay failed
+    Network,
+    /// A file or other named resource does not exist
+    NotFound,
+    /// The OS refused access
+    PermissionDenied,
+    /// The configuration is invalid
+    Config,
+    /// The output disk filled up
+    DiskFull,
+    /// A transient network failure outlasted every retry
+    RetriesExhausted,
+    /// Nobody connected before the receiver stopped waiting
+    NoSender,
+    /// Some files were sent and others left out
+    PartialSuccess,
+}
+
+impl ErrorKind {
+    /// Category of an `io::Error`, looking inside for a [`TallowError`] first
+    pub fn of_io(err: &io::Error) -> Self {
+        if let Some(inner) = err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<TallowError>())
+        {
+            return inner.kind();
+        }
+        match err.kind() {
+            io::ErrorKind::NotFound => Self::NotFound,
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::TimedOut => Self::Network,
+            io::ErrorKind::Interrupted => Self::Cancelled,
+            io::ErrorKind::StorageFull => Self::DiskFull,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// An error from any Tallow crate
+#[derive(Debug, thiserror::Error)]
+pub enum TallowError {
+    /// Cryptographic failure
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    /// Network or relay failure
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+    /// Wire protocol or transfer failure
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    /// Configuration, identity or storage failure
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    /// Plain I/O failure
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// No sender connected within the receiver's wait window
+    #[error("no sender connected within {} seconds", .0.as_secs())]
+    NoSender(std::time::Duration),
+    /// The transfer completed without some of the files
+    #[error("{skipped} of {total} files could not be read and were not sent")]
+    PartialSuccess {
+        /// Files left out
+        skipped: usize,
+        /// Files the sender was asked to send
+        total: usize,
+    },
+    /// Another error, prefixed with what was being done
+    #[error("{context}: {source}")]
+    Context {
+        /// What was being done
+        context: String,
+        /// What went wrong
+        #[source]
+        source: Box<TallowError>,
+    },
+}
+
+impl TallowError {
+    /// Prefix the message with `context`, keeping the classification
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Category this error is reported under
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Crypto(e) => match e {
+                CryptoError::PakeFailure(_)
+                | CryptoError::Decryption(_)
+                | CryptoError::Verification(_) => ErrorKind::Auth,
+                _ => ErrorKind::Other,
+            },
+            Self::Network(e) => match e {
+                NetworkError::AuthenticationFailed => ErrorKind::Auth,
+                NetworkError::RetriesExhausted { .. } => ErrorKind::RetriesExhausted,
+                NetworkError::Io(e) => match ErrorKind::of_io(e) {
+                    ErrorKind::Other => ErrorKind::Network,
+                    kind => kind,
+                },
+                _ => ErrorKind::Network,
+            },
+            Self::Protocol(e) => match e {
+                ProtocolError::HandshakeFailed(_) | ProtocolError::KeyConfirmationFailed => {
+                    ErrorKind::Auth
+                }
+                ProtocolError::DiskFull(_) => ErrorKind::DiskFull,
+                ProtocolError::Io(e) => ErrorKind::of_io(e),
+                _ => ErrorKind::Other,
+            },
+            Self::Store(e) => match e {
+                StoreError::ConfigError(_) => ErrorKind::Config,
+                StoreError::Io(e) => ErrorKind::of_io(e),
+                _ => ErrorKind::Other,
+            },
+            Self::Io(e) => ErrorKind::of_io(e),
+            Self::NoSender(_) => ErrorKind::NoSender,
+            Self::PartialSuccess { .. } => ErrorKind::PartialSuccess,
+            Self::Context { source, .. } => source.kind(),
+        }
+    }
+
+    /// Attempts made, if this is a network operation that ran out of retries
+    pub fn attempts(&self) -> Option<u32> {
+        match self {
+            Self::Network(NetworkError::RetriesExhausted { attempts, .. }) => Some(*attempts),
+            Self::Context { source, .. } => source.attempts(),
+            _ => None,
+        }
+    }
+}
+
+impl From<TallowError> for io::Error {
+    fn from(err: TallowError) -> Self {
+        let kind = match err.kind() {
+            ErrorKind::NotFound => io::ErrorKind::NotFound,
+            ErrorKind::PermissionDenied => io::ErrorKind::PermissionDenied,
+            ErrorKind::Cancelled => io::ErrorKind::Interrupted,
+            ErrorKind::DiskFull => io::ErrorKind::StorageFull,
+            ErrorKind::NoSender => io::ErrorKind::TimedOut,
+            _ => io::ErrorKind::Other,
+        };
+        match err {
+            TallowError::Io(e) => e,
+            err => io::Error::new(kind, err),
+        }
+    }
+}
+
+/// Convert a subsystem error into a classified `io::Error`, message unchanged
+pub fn classified<E: Into<TallowError>>(err: E) -> io::Error {
+    err.into().into()
+}
+
+/// `map_err` adapter turning a subsystem error into a classified `io::Error`
+///
+/// ```ignore
+/// relay.connect(&room, pw).await.map_err(error::context("Connection failed"))?;
+/// ```
+pub fn context<E: Into<TallowError>>(context: impl Into<String>) -> impl FnOnce(E) -> io::Error {
+    let context = context.into();
+    move |err| err.into().context(context).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_keeps_kind_and_message() {
+        let err = context("Write failed")(ProtocolError::DiskFull("no space".into()));
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        assert_eq!(err.to_string(), "Write failed: Disk full: no space");
+        assert_eq!(ErrorKind::of_io(&err), ErrorKind::DiskFull);
+
+        let nested = context("Receive failed")(TallowError::from(err));
+        assert_eq!(ErrorKind::of_io(&nested), ErrorKind::DiskFull);
+    }
+
+    #[test]
+    fn test_plain_io_errors_keep_their_kind() {
+        let err = classified(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(ErrorKind::of_io(&err), ErrorKind::NotFound);
+        assert_eq!(ErrorKind::of_io(&io::Error::other("x")), ErrorKind::Other);
+    }
+}
@@ -10,19 +10,144 @@
 //! - `fp`: Truncated identity fingerprint (8 hex chars)
 //! - `rc`: Room code hash prefix (16 hex chars = 8 bytes)
 //! - `ts`: Unix timestamp (stale detection)
+//! - `ttl`: Seconds after `ts` the advertisement stays valid
+//!
+//! The advertiser re-announces on a fixed interval with a fresh `ts`.
+//! mdns-sd doesn't let callers set DNS record TTLs, so the advertised
+//! lifetime travels in the `ttl` TXT record and browsers drop expired
+//! advertisements themselves.
 
 use crate::discovery::DiscoveredPeer;
 use crate::error::NetworkError;
 use crate::Result;
-use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo, TxtProperties};
 use std::collections::{HashMap, HashSet};
 use std::net::{IpAddr, SocketAddr};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
 /// Tallow mDNS service type
 const SERVICE_TYPE: &str = "_tallow._tcp.local.";
 
+/// Default time between re-announcements
+pub const DEFAULT_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default lifetime of an announcement
+pub const DEFAULT_RECORD_TTL: Duration = Duration::from_secs(120);
+
+/// Shortest interval the advertiser will re-announce at
+const MIN_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Clock difference between sender and receiver tolerated on `ts`
+const CLOCK_SKEW_ALLOWANCE: u64 = 60;
+
+/// How often a [`LanAdvertiser`] announces itself and for how long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdvertiseOptions {
+    /// Time between re-announcements (at least one second)
+    pub interval: Duration,
+    /// How long browsers treat an announcement as current (at least `interval`)
+    pub ttl: Duration,
+}
+
+impl Default for AdvertiseOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_ANNOUNCE_INTERVAL,
+            ttl: DEFAULT_RECORD_TTL,
+        }
+    }
+}
+
+impl AdvertiseOptions {
+    /// Clamp the interval to the minimum and the TTL to the interval
+    ///
+    /// A TTL shorter than the interval would let the advertisement expire
+    /// between announcements.
+    fn normalized(self) -> Self {
+        let interval = self.interval.max(MIN_ANNOUNCE_INTERVAL);
+        Self {
+            interval,
+            ttl: self.ttl.max(interval),
+        }
+    }
+}
+
+/// Everything needed to rebuild the service record for each announcement
+#[derive(Debug, Clone)]
+struct Announcement {
+    /// `tallow-<hex(room_code_hash[..4])>`
+    instance_name: String,
+    /// Host name, without the trailing dot
+    hostname: String,
+    /// QUIC listener port
+    port: u16,
+    /// `fp` TXT value
+    fingerprint_prefix: String,
+    /// `rc` TXT value
+    room_code_prefix: String,
+    /// `ttl` TXT value
+    ttl: Duration,
+}
+
+impl Announcement {
+    /// Service record stamped with `ts`
+    fn service_info(&self, ts: u64) -> Result<ServiceInfo> {
+        let mut properties = HashMap::new();
+        properties.insert("v".to_string(), "1".to_string());
+        properties.insert("fp".to_string(), self.fingerprint_prefix.clone());
+        properties.insert("rc".to_string(), self.room_code_prefix.clone());
+        properties.insert("ts".to_string(), ts.to_string());
+        properties.insert("ttl".to_string(), self.ttl.as_secs().to_string());
+
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            &self.instance_name,
+            &format!("{}.", self.hostname),
+            "",
+            self.port,
+            properties,
+        )
+        .map_err(|e| NetworkError::DiscoveryError(format!("Failed to create service info: {}", e)))
+    }
+}
+
+/// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Whether an advertisement's `ts` plus `ttl` lies before `now`
+///
+/// Advertisements without both records (older senders) never expire.
+fn is_expired(properties: &TxtProperties, now: u64) -> bool {
+    let field = |key| {
+        properties
+            .get_property_val_str(key)
+            .and_then(|v| v.parse::<u64>().ok())
+    };
+    match (field("ts"), field("ttl")) {
+        (Some(ts), Some(ttl)) => now > ts.saturating_add(ttl).saturating_add(CLOCK_SKEW_ALLOWANCE),
+        _ => false,
+    }
+}
+
+/// Call `announce` every `interval` and whenever `wake` fires, until every
+/// sender for `wake` is gone
+fn run_announcer(wake: &mpsc::Receiver<()>, interval: Duration, mut announce: impl FnMut()) {
+    loop {
+        match wake.recv_timeout(interval) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => announce(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
 /// Advertises a Tallow sender on the LAN via mDNS.
 ///
 /// Registers a `_tallow._tcp.local.` service with TXT records containing:
@@ -30,6 +155,11 @@ const SERVICE_TYPE: &str = "_tallow._tcp.local.";
 /// - `fp`: Truncated identity fingerprint (8 hex chars)
 /// - `rc`: Room code hash prefix (16 hex chars = 8 bytes)
 /// - `ts`: Unix timestamp (stale detection)
+/// - `ttl`: Seconds after `ts` the advertisement stays valid
+///
+/// A background thread re-registers the service every
+/// [`AdvertiseOptions::interval`], and [`announce_now`](Self::announce_now)
+/// triggers one immediately.
 ///
 /// Implements `Drop` to unregister the service and shut down the mDNS daemon.
 pub struct LanAdvertiser {
@@ -37,6 +167,10 @@ pub struct LanAdvertiser {
     daemon: ServiceDaemon,
     /// Full name of the registered service (needed for unregister)
     service_fullname: String,
+    /// Wakes the announcer thread; dropping it stops the thread
+    wake: Option<mpsc::Sender<()>>,
+    /// Announcer thread handle
+    announcer: Option<JoinHandle<()>>,
 }
 
 impl LanAdvertiser {
@@ -48,40 +182,41 @@ impl LanAdvertiser {
     /// * `fingerprint_prefix` - First 8 hex chars of the identity fingerprint
     /// * `room_code_hash` - BLAKE3 hash of the room code (32 bytes)
     pub fn new(port: u16, fingerprint_prefix: &str, room_code_hash: &[u8; 32]) -> Result<Self> {
+        Self::with_options(
+            port,
+            fingerprint_prefix,
+            room_code_hash,
+            AdvertiseOptions::default(),
+        )
+    }
+
+    /// Create a LAN advertiser with a custom announce interval and TTL.
+    pub fn with_options(
+        port: u16,
+        fingerprint_prefix: &str,
+        room_code_hash: &[u8; 32],
+        options: AdvertiseOptions,
+    ) -> Result<Self> {
+        let options = options.normalized();
         let daemon = ServiceDaemon::new().map_err(|e| {
             NetworkError::DiscoveryError(format!("Failed to create mDNS daemon: {}", e))
         })?;
 
-        // Instance name: tallow-<hex(room_code_hash[..4])> (8 hex chars)
-        let instance_name = format!("tallow-{}", hex::encode(&room_code_hash[..4]));
-
         let hostname = std::env::var("HOSTNAME")
             .or_else(|_| std::env::var("COMPUTERNAME"))
             .unwrap_or_else(|_| "tallow-peer".to_string());
 
-        // Build TXT properties
-        let mut properties = HashMap::new();
-        properties.insert("v".to_string(), "1".to_string());
-        properties.insert("fp".to_string(), fingerprint_prefix.to_string());
-        properties.insert("rc".to_string(), hex::encode(&room_code_hash[..8]));
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        properties.insert("ts".to_string(), ts.to_string());
-
-        let service = ServiceInfo::new(
-            SERVICE_TYPE,
-            &instance_name,
-            &format!("{}.", hostname),
-            "",
+        let announcement = Announcement {
+            // Instance name: tallow-<hex(room_code_hash[..4])> (8 hex chars)
+            instance_name: format!("tallow-{}", hex::encode(&room_code_hash[..4])),
+            hostname,
             port,
-            properties,
-        )
-        .map_err(|e| {
-            NetworkError::DiscoveryError(format!("Failed to create service info: {}", e))
-        })?;
+            fingerprint_prefix: fingerprint_prefix.to_string(),
+            room_code_prefix: hex::encode(&room_code_hash[..8]),
+            ttl: options.ttl,
+        };
 
+        let service = announcement.service_info(unix_now())?;
         let fullname = service.get_fullname().to_string();
 
         daemon.register(service).map_err(|e| {
@@ -89,19 +224,58 @@ impl LanAdvertiser {
         })?;
 
         info!(
-            "LAN advertiser registered: {} on port {}",
-            instance_name, port
+            "LAN advertiser registered: {} on port {} (every {}s, ttl {}s)",
+            announcement.instance_name,
+            port,
+            options.interval.as_secs(),
+            options.ttl.as_secs()
         );
 
+        // Registering again re-announces with the fresh timestamp
+        let (wake, wake_rx) = mpsc::channel();
+        let announcer_daemon = daemon.clone();
+        let announcer = std::thread::Builder::new()
+            .name("tallow-mdns-announce".to_string())
+            .spawn(move || {
+                run_announcer(&wake_rx, options.interval, || {
+                    match announcement.service_info(unix_now()) {
+                        Ok(service) => {
+                            if let Err(e) = announcer_daemon.register(service) {
+                                warn!("mDNS re-announce failed: {}", e);
+                            }
+                        }
+                        Err(e) => warn!("mDNS re-announce failed: {}", e),
+                    }
+                })
+            })
+            .map_err(NetworkError::Io)?;
+
         Ok(Self {
             daemon,
             service_fullname: fullname,
+            wake: Some(wake),
+            announcer: Some(announcer),
         })
     }
+
+    /// Announce the service right away instead of waiting for the interval
+    ///
+    /// Useful when a user is actively looking for peers, e.g. when the
+    /// devices panel opens.
+    pub fn announce_now(&self) {
+        if let Some(wake) = &self.wake {
+            let _ = wake.send(());
+        }
+    }
 }
 
 impl Drop for LanAdvertiser {
     fn drop(&mut self) {
+        // Stop re-announcing before the service goes away
+        self.wake.take();
+        if let Some(announcer) = self.announcer.take() {
+            let _ = announcer.join();
+        }
         debug!("Unregistering mDNS service: {}", self.service_fullname);
         let _ = self.daemon.unregister(&self.service_fullname);
         let _ = self.daemon.shutdown();
@@ -146,6 +320,11 @@ pub async fn discover_sender(
         // Drain all available events
         while let Ok(event) = receiver.try_recv() {
             if let ServiceEvent::ServiceResolved(info) = event {
+                if is_expired(info.get_properties(), unix_now()) {
+                    debug!("Ignoring expired advertisement: {}", info.get_fullname());
+                    continue;
+                }
+
                 // Check room code match
                 let rc_match = info
                     .get_properties()
@@ -210,7 +389,7 @@ pub async fn discover_all_senders(timeout: Duration) -> Result<Vec<DiscoveredPee
         while let Ok(event) = receiver.try_recv() {
             if let ServiceEvent::ServiceResolved(info) = event {
                 let fullname = info.get_fullname().to_string();
-                if seen_names.contains(&fullname) {
+                if seen_names.contains(&fullname) || is_expired(info.get_properties(), unix_now()) {
                     continue;
                 }
 
@@ -317,6 +496,124 @@ mod tests {
         assert_ne!(name1, name2);
     }
 
+    fn announcement(ttl: Duration) -> Announcement {
+        Announcement {
+            instance_name: "tallow-abababab".to_string(),
+            hostname: "test-host".to_string(),
+            port: 12347,
+            fingerprint_prefix: "abcd1234".to_string(),
+            room_code_prefix: "abababababababab".to_string(),
+            ttl,
+        }
+    }
+
+    #[test]
+    fn test_ttl_in_advertised_record() {
+        let info = announcement(Duration::from_secs(300))
+            .service_info(1_000)
+            .unwrap();
+        let properties = info.get_properties();
+        assert_eq!(properties.get_property_val_str("ttl"), Some("300"));
+        assert_eq!(properties.get_property_val_str("ts"), Some("1000"));
+
+        let last_valid = 1_000 + 300 + CLOCK_SKEW_ALLOWANCE;
+        assert!(!is_expired(properties, last_valid));
+        assert!(is_expired(properties, last_valid + 1));
+    }
+
+    #[test]
+    fn test_record_without_ttl_never_expires() {
+        let mut properties = HashMap::new();
+        properties.insert("ts".to_string(), "1".to_string());
+        let info = ServiceInfo::new(SERVICE_TYPE, "tallow-old", "old.", "", 1, properties).unwrap();
+        assert!(!is_expired(info.get_properties(), u64::MAX));
+    }
+
+    #[test]
+    fn test_options_normalized() {
+        let options = AdvertiseOptions {
+            interval: Duration::ZERO,
+            ttl: Duration::ZERO,
+        }
+        .normalized();
+        assert_eq!(options.interval, MIN_ANNOUNCE_INTERVAL);
+        assert_eq!(options.ttl, MIN_ANNOUNCE_INTERVAL);
+
+        let options = AdvertiseOptions {
+            interval: Duration::from_secs(60),
+            ttl: Duration::from_secs(10),
+        }
+        .normalized();
+        assert_eq!(options.ttl, Duration::from_secs(60));
+        assert_eq!(
+            AdvertiseOptions::default().normalized(),
+            AdvertiseOptions::default()
+        );
+    }
+
+    #[test]
+    fn test_announcer_respects_interval() {
+        let interval = Duration::from_millis(50);
+        let (wake, wake_rx) = mpsc::channel();
+        let announcer = std::thread::spawn(move || {
+            let mut times = vec![std::time::Instant::now()];
+            run_announcer(&wake_rx, interval, || times.push(std::time::Instant::now()));
+            times
+        });
+
+        std::thread::sleep(Duration::from_millis(280));
+        drop(wake);
+        let times = announcer.join().unwrap();
+
+        let announcements = times.len() - 1;
+        assert!(
+            (3..=5).contains(&announcements),
+            "expected ~5 announcements, got {}",
+            announcements
+        );
+        for pair in times.windows(2) {
+            assert!(pair[1] - pair[0] >= interval);
+        }
+    }
+
+    #[test]
+    fn test_announce_now_emits_immediately() {
+        let (wake, wake_rx) = mpsc::channel();
+        let (announced, announced_rx) = mpsc::channel();
+        let announcer = std::thread::spawn(move || {
+            run_announcer(&wake_rx, Duration::from_secs(3600), || {
+                announced.send(()).unwrap()
+            })
+        });
+
+        // Nothing happens until the interval elapses...
+        assert!(announced_rx
+            .recv_timeout(Duration::from_millis(50))
+            .is_err());
+
+        // ...unless an announcement is requested
+        wake.send(()).unwrap();
+        assert!(announced_rx.recv_timeout(Duration::from_secs(1)).is_ok());
+
+        drop(wake);
+        announcer.join().unwrap();
+    }
+
+    #[test]
+    fn test_advertiser_announce_now() {
+        let room_hash = [0xEFu8; 32];
+        let options = AdvertiseOptions {
+            interval: Duration::from_secs(5),
+            ttl: Duration::from_secs(20),
+        };
+        // mDNS may be unavailable in CI; only check the handle works
+        if let Ok(advertiser) = LanAdvertiser::with_options(12348, "01234567", &room_hash, options)
+        {
+            advertiser.announce_now();
+            drop(advertiser);
+        }
+    }
+
     #[test]
     fn test_prefer_ipv4_with_both() {
         let mut addrs = HashSet::new();
@@ -23,6 +23,20 @@ pub enum NetworkError {
     DiscoveryError(String),
     /// Relay authentication failed
     AuthenticationFailed,
+    /// The peer did not finish the key exchange within the handshake timeout
+    HandshakeTimeout(std::time::Duration),
+    /// The server asked the client to back off and the wait budget ran out
+    RateLimited {
+        /// Delay the server asked for in its last refusal
+        retry_after: std::time::Duration,
+    },
+    /// A transient failure persisted through every allowed retry
+    RetriesExhausted {
+        /// Attempts made, including the first
+        attempts: u32,
+        /// Error from the final attempt
+        last: Box<NetworkError>,
+    },
     /// IO error
     Io(std::io::Error),
 }
@@ -39,6 +53,19 @@ impl fmt::Display for NetworkError {
             Self::TlsError(msg) => write!(f, "TLS error: {}", msg),
             Self::DiscoveryError(msg) => write!(f, "Discovery error: {}", msg),
             Self::AuthenticationFailed => write!(f, "Relay authentication failed"),
+            Self::HandshakeTimeout(limit) => write!(
+                f,
+                "Handshake timed out after {}s: the peer connected but did not complete the key exchange",
+                limit.as_secs_f64()
+            ),
+            Self::RateLimited { retry_after } => write!(
+                f,
+                "Rate limited by the server; try again in {}s",
+                retry_after.as_secs()
+            ),
+            Self::RetriesExhausted { attempts, last } => {
+                write!(f, "{} (gave up after {} attempts)", last, attempts)
+            }
             Self::Io(err) => write!(f, "IO error: {}", err),
         }
     }
@@ -5,7 +5,7 @@
 
 use crate::error::NetworkError;
 use crate::Result;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
 /// Candidate type for P2P connection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +29,182 @@ pub struct Candidate {
     pub priority: u32,
 }
 
+impl Candidate {
+    /// Routing scope of this candidate's address
+    pub fn scope(&self) -> AddressScope {
+        AddressScope::of(self.addr.ip())
+    }
+}
+
+/// Routing scope of a unicast address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressScope {
+    /// Loopback (127.0.0.0/8, ::1); only reachable from the same host
+    Loopback,
+    /// Link-local (169.254.0.0/16, fe80::/10); only reachable on the same segment
+    LinkLocal,
+    /// Site-local: IPv6 ULA (fc00::/7) or IPv4 private/CGNAT ranges
+    UniqueLocal,
+    /// Globally routable
+    Global,
+}
+
+impl AddressScope {
+    /// Classify an IP address. IPv4-mapped IPv6 addresses use the IPv4 rules.
+    pub fn of(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(v4) => Self::of_v4(v4),
+            IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+                Some(v4) => Self::of_v4(v4),
+                None => Self::of_v6(v6),
+            },
+        }
+    }
+
+    fn of_v4(ip: Ipv4Addr) -> Self {
+        let [a, b, ..] = ip.octets();
+        if ip.is_loopback() {
+            Self::Loopback
+        } else if ip.is_link_local() {
+            Self::LinkLocal
+        } else if ip.is_private() || (a == 100 && (b & 0xc0) == 64) {
+            // RFC 1918 and RFC 6598 shared address space (100.64.0.0/10)
+            Self::UniqueLocal
+        } else {
+            Self::Global
+        }
+    }
+
+    fn of_v6(ip: Ipv6Addr) -> Self {
+        let first = ip.segments()[0];
+        if ip.is_loopback() {
+            Self::Loopback
+        } else if first & 0xffc0 == 0xfe80 {
+            Self::LinkLocal
+        } else if first & 0xfe00 == 0xfc00 {
+            Self::UniqueLocal
+        } else {
+            Self::Global
+        }
+    }
+}
+
+/// Which address scopes to keep when filtering candidates
+///
+/// Global addresses always pass. The default drops loopback, keeps
+/// unique-local addresses, and keeps link-local addresses only when both
+/// peers are on the same LAN segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeFilter {
+    /// Keep loopback addresses (local testing only)
+    pub loopback: bool,
+    /// Keep link-local addresses when the peers share a LAN segment
+    pub link_local_same_lan: bool,
+    /// Keep unique-local and private addresses
+    pub unique_local: bool,
+}
+
+impl Default for ScopeFilter {
+    fn default() -> Self {
+        Self {
+            loopback: false,
+            link_local_same_lan: true,
+            unique_local: true,
+        }
+    }
+}
+
+impl ScopeFilter {
+    /// Whether an address of `scope` should be tried
+    ///
+    /// `same_lan` is whether both peers appear to share a LAN segment
+    /// (see [`share_lan`]).
+    pub fn allows(&self, scope: AddressScope, same_lan: bool) -> bool {
+        match scope {
+            AddressScope::Global => true,
+            AddressScope::UniqueLocal => self.unique_local,
+            AddressScope::LinkLocal => self.link_local_same_lan && same_lan,
+            AddressScope::Loopback => self.loopback,
+        }
+    }
+}
+
+/// Keep only candidates whose scope passes `filter`
+pub fn filter_candidates(
+    candidates: Vec<Candidate>,
+    filter: &ScopeFilter,
+    same_lan: bool,
+) -> Vec<Candidate> {
+    candidates
+        .into_iter()
+        .filter(|c| filter.allows(c.scope(), same_lan))
+        .collect()
+}
+
+/// Canonical form of a candidate address
+///
+/// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) become plain IPv4 so the
+/// same endpoint always compares equal, and so an IPv4-only socket can dial
+/// it. Every other address, including IPv4-compatible `::a.b.c.d`, is
+/// returned unchanged.
+pub fn normalize_addr(addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => SocketAddr::new(IpAddr::V4(v4), addr.port()),
+            None => addr,
+        },
+        IpAddr::V4(_) => addr,
+    }
+}
+
+/// Normalize every address and drop duplicate endpoints
+///
+/// When one endpoint appears more than once, the highest-priority entry is
+/// kept. The result is sorted by priority (highest first), ties keeping
+/// their original order, so connection attempts never dial the same
+/// endpoint twice.
+pub fn normalize_candidates(candidates: Vec<Candidate>) -> Vec<Candidate> {
+    let mut unique: Vec<Candidate> = Vec::with_capacity(candidates.len());
+    for mut candidate in candidates {
+        candidate.addr = normalize_addr(candidate.addr);
+        match unique.iter_mut().find(|c| c.addr == candidate.addr) {
+            Some(existing) if existing.priority < candidate.priority => *existing = candidate,
+            Some(_) => {}
+            None => unique.push(candidate),
+        }
+    }
+    unique.sort_by(|a, b| b.priority.cmp(&a.priority));
+    unique
+}
+
+/// Heuristic for whether two peers sit on the same LAN segment.
+///
+/// True when both report the same server-reflexive IP (same NAT) or have
+/// host candidates in the same /24 (IPv4) or /64 (IPv6).
+pub fn share_lan(local: &[Candidate], remote: &[Candidate]) -> bool {
+    local.iter().any(|l| {
+        remote.iter().any(|r| {
+            if l.candidate_type != r.candidate_type {
+                return false;
+            }
+            match l.candidate_type {
+                CandidateType::ServerReflexive => l.addr.ip() == r.addr.ip(),
+                CandidateType::Host => same_subnet(l.addr.ip(), r.addr.ip()),
+                CandidateType::UPnP => false,
+            }
+        })
+    })
+}
+
+/// Same /24 for IPv4 or same /64 for IPv6
+fn same_subnet(a: IpAddr, b: IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => a.octets()[..3] == b.octets()[..3],
+        (IpAddr::V6(a), IpAddr::V6(b)) => a.segments()[..4] == b.segments()[..4],
+        _ => false,
+    }
+}
+
 /// Gather all available candidates for P2P connection.
 ///
 /// Binds to `local_port` for STUN discovery so the discovered public address
@@ -66,13 +242,12 @@ pub async fn gather_candidates(local_port: u16) -> Vec<Candidate> {
         }
     }
 
-    // Sort by priority descending
-    candidates.sort_by(|a, b| b.priority.cmp(&a.priority));
-    candidates
+    // Canonical addresses, deduplicated, sorted by priority descending
+    normalize_candidates(candidates)
 }
 
 /// Get the primary local IP address (non-loopback, non-link-local).
-fn get_local_ip() -> Result<IpAddr> {
+pub fn get_local_ip() -> Result<IpAddr> {
     // Bind a UDP socket to an external address to discover the default route IP
     let socket = std::net::UdpSocket::bind("0.0.0.0:0")
         .map_err(|e| NetworkError::NatTraversal(format!("bind failed: {}", e)))?;
@@ -90,29 +265,28 @@ fn get_local_ip() -> Result<IpAddr> {
 /// Rejects loopback, link-local, broadcast, multicast, and unspecified addresses.
 /// Prevents an attacker from redirecting connections to unintended addresses.
 pub fn validate_candidate_addr(addr: &SocketAddr) -> bool {
+    validate_candidate_addr_scoped(addr, &ScopeFilter::default(), false)
+}
+
+/// Validate a candidate address, applying `filter` to its scope.
+///
+/// Broadcast, multicast, unspecified addresses and port 0 are always
+/// rejected; the filter decides loopback, link-local, and unique-local.
+pub fn validate_candidate_addr_scoped(
+    addr: &SocketAddr,
+    filter: &ScopeFilter,
+    same_lan: bool,
+) -> bool {
     let ip = addr.ip();
-    if ip.is_loopback() || ip.is_multicast() || ip.is_unspecified() {
+    if ip.is_multicast() || ip.is_unspecified() {
         return false;
     }
-    // Check link-local
-    match ip {
-        IpAddr::V4(v4) => {
-            // 169.254.0.0/16 link-local
-            if v4.octets()[0] == 169 && v4.octets()[1] == 254 {
-                return false;
-            }
-            // 255.255.255.255 broadcast
-            if v4 == Ipv4Addr::BROADCAST {
-                return false;
-            }
-        }
-        IpAddr::V6(v6) => {
-            // fe80::/10 link-local
-            let segments = v6.segments();
-            if segments[0] & 0xffc0 == 0xfe80 {
-                return false;
-            }
-        }
+    // 255.255.255.255 broadcast
+    if ip == IpAddr::V4(Ipv4Addr::BROADCAST) {
+        return false;
+    }
+    if !filter.allows(AddressScope::of(ip), same_lan) {
+        return false;
     }
     // Port must be valid (1-65535)
     addr.port() > 0
@@ -353,6 +527,169 @@ mod tests {
         assert!(decode_socket_addr(&[0u8; 19]).is_err()); // one over IPv6
     }
 
+    fn candidate(addr: &str, candidate_type: CandidateType) -> Candidate {
+        Candidate {
+            addr: addr.parse().unwrap(),
+            candidate_type,
+            priority: 100,
+        }
+    }
+
+    #[test]
+    fn test_scope_classification() {
+        let scope = |a: &str| candidate(a, CandidateType::Host).scope();
+        assert_eq!(scope("127.0.0.1:1"), AddressScope::Loopback);
+        assert_eq!(scope("[::1]:1"), AddressScope::Loopback);
+        assert_eq!(scope("169.254.3.4:1"), AddressScope::LinkLocal);
+        assert_eq!(scope("[fe80::1]:1"), AddressScope::LinkLocal);
+        assert_eq!(scope("[fd12:3456::1]:1"), AddressScope::UniqueLocal);
+        assert_eq!(scope("192.168.1.2:1"), AddressScope::UniqueLocal);
+        assert_eq!(scope("100.64.0.1:1"), AddressScope::UniqueLocal);
+        assert_eq!(scope("[::ffff:10.0.0.1]:1"), AddressScope::UniqueLocal);
+        assert_eq!(scope("8.8.8.8:1"), AddressScope::Global);
+        assert_eq!(scope("[2001:db8::1]:1"), AddressScope::Global);
+    }
+
+    #[test]
+    fn test_link_local_filtered_across_networks() {
+        let filter = ScopeFilter::default();
+        let link_local: SocketAddr = "[fe80::1234]:4433".parse().unwrap();
+        assert!(!validate_candidate_addr_scoped(&link_local, &filter, false));
+
+        let kept = filter_candidates(
+            vec![candidate("[fe80::1234]:4433", CandidateType::Host)],
+            &filter,
+            false,
+        );
+        assert!(kept.is_empty());
+    }
+
+    #[test]
+    fn test_link_local_retained_on_same_lan() {
+        let filter = ScopeFilter::default();
+        let link_local: SocketAddr = "[fe80::1234]:4433".parse().unwrap();
+        assert!(validate_candidate_addr_scoped(&link_local, &filter, true));
+
+        // Disabling same-LAN link-local drops it even then
+        let strict = ScopeFilter {
+            link_local_same_lan: false,
+            ..ScopeFilter::default()
+        };
+        assert!(!validate_candidate_addr_scoped(&link_local, &strict, true));
+    }
+
+    #[test]
+    fn test_global_always_passes() {
+        let closed = ScopeFilter {
+            loopback: false,
+            link_local_same_lan: false,
+            unique_local: false,
+        };
+        for addr in ["8.8.8.8:4433", "[2001:db8::1]:4433"] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            for same_lan in [false, true] {
+                assert!(validate_candidate_addr_scoped(&addr, &closed, same_lan));
+                assert!(validate_candidate_addr_scoped(
+                    &addr,
+                    &ScopeFilter::default(),
+                    same_lan
+                ));
+            }
+        }
+        assert!(!validate_candidate_addr_scoped(
+            &"10.0.0.1:4433".parse().unwrap(),
+            &closed,
+            true
+        ));
+    }
+
+    #[test]
+    fn test_share_lan() {
+        let local = vec![
+            candidate("192.168.1.10:4433", CandidateType::Host),
+            candidate("203.0.113.5:5000", CandidateType::ServerReflexive),
+        ];
+        assert!(share_lan(
+            &local,
+            &[candidate("192.168.1.20:4433", CandidateType::Host)]
+        ));
+        assert!(share_lan(
+            &local,
+            &[candidate(
+                "203.0.113.5:6000",
+                CandidateType::ServerReflexive
+            )]
+        ));
+        assert!(!share_lan(
+            &local,
+            &[
+                candidate("192.168.2.20:4433", CandidateType::Host),
+                candidate("198.51.100.7:5000", CandidateType::ServerReflexive),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_ipv4_mapped_deduplicates_with_ipv4() {
+        let mapped: SocketAddr = "[::ffff:203.0.113.5]:4433".parse().unwrap();
+        assert_eq!(normalize_addr(mapped), "203.0.113.5:4433".parse().unwrap());
+
+        let merged = normalize_candidates(vec![
+            Candidate {
+                addr: mapped,
+                candidate_type: CandidateType::ServerReflexive,
+                priority: 50,
+            },
+            candidate("203.0.113.5:4433", CandidateType::Host),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].addr, "203.0.113.5:4433".parse().unwrap());
+        // The higher-priority entry wins
+        assert_eq!(merged[0].candidate_type, CandidateType::Host);
+
+        // Same IP on another port is a different endpoint
+        let distinct = normalize_candidates(vec![
+            candidate("[::ffff:203.0.113.5]:4433", CandidateType::Host),
+            candidate("203.0.113.5:4434", CandidateType::Host),
+        ]);
+        assert_eq!(distinct.len(), 2);
+    }
+
+    #[test]
+    fn test_ipv6_preserved_by_normalization() {
+        for addr in [
+            "[2001:db8::1]:4433",
+            "[fe80::1234]:4433",
+            "[::1]:4433",
+            // IPv4-compatible (deprecated), not mapped
+            "[::203.0.113.5]:4433",
+        ] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            assert_eq!(normalize_addr(addr), addr);
+        }
+
+        let kept = normalize_candidates(vec![
+            candidate("[2001:db8::1]:4433", CandidateType::Host),
+            candidate("203.0.113.5:4433", CandidateType::Host),
+            candidate("[2001:db8::1]:4433", CandidateType::Host),
+        ]);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].addr, "[2001:db8::1]:4433".parse().unwrap());
+        assert_eq!(kept[1].addr, "203.0.113.5:4433".parse().unwrap());
+    }
+
+    #[test]
+    fn test_normalized_candidates_sorted_by_priority() {
+        let mut low = candidate("198.51.100.7:5000", CandidateType::UPnP);
+        low.priority = 30;
+        let sorted = normalize_candidates(vec![
+            low,
+            candidate("[::ffff:192.168.1.10]:4433", CandidateType::Host),
+        ]);
+        assert_eq!(sorted[0].addr, "192.168.1.10:4433".parse().unwrap());
+        assert_eq!(sorted[1].priority, 30);
+    }
+
     /// Port encoding is big-endian
     #[test]
     fn test_encode_port_big_endian() {
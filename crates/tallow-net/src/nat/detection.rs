@@ -1,10 +1,26 @@
 //! NAT type detection via STUN
 //!
 //! Uses two STUN servers to classify the NAT type by comparing
-//! mapped addresses from different servers.
+//! mapped addresses from different servers. Results are cached per local
+//! interface address so repeated connection attempts skip the probes.
 
+use super::candidates::get_local_ip;
 use super::stun::{StunClient, CLOUDFLARE_STUN, GOOGLE_STUN};
 use crate::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a detected NAT type is reused for the same interface
+pub const NAT_CACHE_TTL: Duration = Duration::from_secs(120);
+
+/// How often the network watcher checks the default-route address
+pub const NETWORK_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Process-wide cache used by [`detect_cached`]
+static NAT_CACHE: LazyLock<NatCache> = LazyLock::new(|| NatCache::new(NAT_CACHE_TTL));
 
 /// NAT type classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,3 +101,192 @@ pub async fn detect() -> Result<NatType> {
     // but for our purposes, cone NAT is traversable
     Ok(NatType::FullCone)
 }
+
+/// Detect the NAT type, reusing a recent result for the current interface
+///
+/// The cache key is the local address of the default route, so a route
+/// or address change misses the cache (and clears it) before any STUN
+/// probe is reused.
+pub async fn detect_cached() -> Result<NatType> {
+    match get_local_ip() {
+        Ok(local) => {
+            NAT_CACHE.observe_interface(local);
+            NAT_CACHE.get_or_detect(local, detect).await
+        }
+        Err(_) => detect().await,
+    }
+}
+
+/// Process-wide NAT cache used by [`detect_cached`]
+pub fn nat_cache() -> &'static NatCache {
+    &NAT_CACHE
+}
+
+/// Watch the default-route address and invalidate the NAT cache on change
+///
+/// Long-running processes (e.g. the drop box) should spawn this once so
+/// a network switch is noticed even between connection attempts.
+pub fn spawn_network_watcher() -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(NETWORK_WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Ok(local) = get_local_ip() {
+                if NAT_CACHE.observe_interface(local) {
+                    tracing::debug!("Local address changed to {}, NAT cache cleared", local);
+                }
+            }
+        }
+    })
+}
+
+/// Cache of detected NAT types keyed by local interface address
+#[derive(Debug)]
+pub struct NatCache {
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Last observed default-route address
+    interface: Option<IpAddr>,
+    entries: HashMap<IpAddr, (NatType, Instant)>,
+}
+
+impl NatCache {
+    /// Create an empty cache whose entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Cached NAT type for `local`, if still fresh
+    pub fn get(&self, local: IpAddr) -> Option<NatType> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .entries
+            .get(&local)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(nat, _)| *nat)
+    }
+
+    /// Record a detection result for `local`
+    ///
+    /// `Unknown` is not cached: it usually means STUN was unreachable and
+    /// the next attempt should probe again.
+    pub fn insert(&self, local: IpAddr, nat: NatType) {
+        if nat == NatType::Unknown {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.insert(local, (nat, Instant::now()));
+    }
+
+    /// Note the current default-route address
+    ///
+    /// Clears every entry if it differs from the last one observed and
+    /// returns whether that happened.
+    pub fn observe_interface(&self, local: IpAddr) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = state.interface.is_some_and(|prev| prev != local);
+        if changed {
+            state.entries.clear();
+        }
+        state.interface = Some(local);
+        changed
+    }
+
+    /// Drop all cached results
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.clear();
+    }
+
+    /// Return the cached type for `local`, or run `detect` and cache it
+    pub async fn get_or_detect<F, Fut>(&self, local: IpAddr, detect: F) -> Result<NatType>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<NatType>>,
+    {
+        if let Some(nat) = self.get(local) {
+            tracing::debug!("Using cached NAT type for {}: {}", local, nat);
+            return Ok(nat);
+        }
+        let nat = detect().await?;
+        self.insert(local, nat);
+        Ok(nat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const ADDR_A: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 1, 20));
+    const ADDR_B: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 7));
+
+    async fn counted(calls: &AtomicUsize, nat: NatType) -> Result<NatType> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(nat)
+    }
+
+    #[tokio::test]
+    async fn test_second_detection_reuses_cached_result() {
+        let cache = NatCache::new(NAT_CACHE_TTL);
+        let calls = AtomicUsize::new(0);
+
+        cache.observe_interface(ADDR_A);
+        let first = cache
+            .get_or_detect(ADDR_A, || counted(&calls, NatType::Symmetric))
+            .await
+            .unwrap();
+        cache.observe_interface(ADDR_A);
+        let second = cache
+            .get_or_detect(ADDR_A, || counted(&calls, NatType::FullCone))
+            .await
+            .unwrap();
+
+        assert_eq!(first, NatType::Symmetric);
+        assert_eq!(second, NatType::Symmetric);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_interface_change_invalidates_cache() {
+        let cache = NatCache::new(NAT_CACHE_TTL);
+        let calls = AtomicUsize::new(0);
+
+        assert!(!cache.observe_interface(ADDR_A));
+        cache
+            .get_or_detect(ADDR_A, || counted(&calls, NatType::FullCone))
+            .await
+            .unwrap();
+
+        // Switch networks and back: the old entry must not survive
+        assert!(cache.observe_interface(ADDR_B));
+        assert!(cache.observe_interface(ADDR_A));
+        assert_eq!(cache.get(ADDR_A), None);
+
+        let nat = cache
+            .get_or_detect(ADDR_A, || counted(&calls, NatType::Symmetric))
+            .await
+            .unwrap();
+        assert_eq!(nat, NatType::Symmetric);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_unknown_results_are_not_reused() {
+        let cache = NatCache::new(Duration::ZERO);
+        cache.insert(ADDR_A, NatType::FullCone);
+        assert_eq!(cache.get(ADDR_A), None);
+
+        let cache = NatCache::new(NAT_CACHE_TTL);
+        cache.insert(ADDR_A, NatType::Unknown);
+        assert_eq!(cache.get(ADDR_A), None);
+    }
+}
@@ -7,6 +7,6 @@ pub mod stun;
 pub mod turn;
 pub mod upnp;
 
-pub use candidates::{Candidate, CandidateType};
-pub use detection::NatType;
+pub use candidates::{AddressScope, Candidate, CandidateType, ScopeFilter};
+pub use detection::{detect_cached, NatCache, NatType};
 pub use stun::{StunClient, StunResult};
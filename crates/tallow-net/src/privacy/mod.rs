@@ -2,8 +2,10 @@
 
 pub mod doh;
 pub mod socks5;
+pub mod tor;
 pub mod traffic_analysis;
 
 pub use doh::DohResolver;
 pub use socks5::{ProxyAuth, ProxyConfig, Socks5Connector};
+pub use tor::{verify_tor_proxy, NetworkPolicy};
 pub use traffic_analysis::TrafficShaper;
@@ -0,0 +1,274 @@
+//! Tor mode: route everything through a local Tor SOCKS5 port
+//!
+//! `NetworkPolicy` is the single place that decides which network features
+//! are safe given the active proxy. With a proxy (and Tor in particular),
+//! anything that would reveal the local address outside the tunnel is
+//! disabled: mDNS/LAN discovery, STUN, UPnP, and direct P2P.
+
+use crate::privacy::ProxyConfig;
+use crate::relay::{resolve_relay_proxy, RelayClient, ResolvedRelay};
+use crate::{NetworkError, Result};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Time allowed for each step of the Tor proxy probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Marker Tor's SOCKS port returns when spoken to as an HTTP proxy
+const TOR_HTTP_MARKER: &str = "Tor is not an HTTP Proxy";
+
+/// Network feature policy derived from the proxy configuration
+#[derive(Debug, Clone, Default)]
+pub struct NetworkPolicy {
+    proxy: Option<ProxyConfig>,
+}
+
+impl NetworkPolicy {
+    /// Policy for the given proxy configuration (`None` = direct)
+    pub fn new(proxy: Option<ProxyConfig>) -> Self {
+        Self { proxy }
+    }
+
+    /// Tor policy using the given SOCKS5 proxy
+    ///
+    /// Forces `tor_mode` so relay hostnames are resolved inside Tor.
+    pub fn tor(mut proxy: ProxyConfig) -> Self {
+        proxy.tor_mode = true;
+        Self { proxy: Some(proxy) }
+    }
+
+    /// Whether connections are routed through Tor
+    pub fn is_tor(&self) -> bool {
+        self.proxy.as_ref().is_some_and(|p| p.tor_mode)
+    }
+
+    /// Whether connections are routed through any SOCKS5 proxy
+    pub fn is_proxied(&self) -> bool {
+        self.proxy.is_some()
+    }
+
+    /// The active proxy configuration, if any
+    pub fn proxy(&self) -> Option<&ProxyConfig> {
+        self.proxy.as_ref()
+    }
+
+    /// Whether mDNS / LAN discovery and advertising may run
+    ///
+    /// Multicast announcements broadcast the local IP outside the proxy.
+    pub fn allows_lan_discovery(&self) -> bool {
+        self.proxy.is_none()
+    }
+
+    /// Whether STUN, UPnP and hole punching may run
+    ///
+    /// These speak UDP directly and would reveal the public IP.
+    pub fn allows_nat_traversal(&self) -> bool {
+        self.proxy.is_none()
+    }
+
+    /// Whether a direct peer-to-peer upgrade may be attempted
+    pub fn allows_p2p(&self) -> bool {
+        self.proxy.is_none()
+    }
+
+    /// Build a relay client that honours this policy
+    ///
+    /// With a proxy, the client tunnels through SOCKS5 and the relay
+    /// address is resolved without touching the system resolver.
+    pub async fn relay_client(&self, relay: &str) -> Result<RelayClient> {
+        let resolved = resolve_relay_proxy(relay, self.proxy.as_ref()).await?;
+        let client = match (resolved, &self.proxy) {
+            (ResolvedRelay::Addr(addr), Some(proxy)) => {
+                let mut client = RelayClient::new(addr);
+                client.set_proxy(proxy.clone());
+                client
+            }
+            (ResolvedRelay::Addr(addr), None) => RelayClient::new(addr),
+            (ResolvedRelay::Hostname { host, port }, Some(proxy)) => {
+                RelayClient::new_with_proxy(&host, port, proxy.clone())
+            }
+            (ResolvedRelay::Hostname { host, .. }, None) => {
+                return Err(NetworkError::DnsResolution(format!(
+                    "relay '{}' requires a proxy to resolve",
+                    host
+                )))
+            }
+        };
+        Ok(client)
+    }
+}
+
+/// Verify that `addr` is reachable and behaves like a Tor SOCKS5 port.
+///
+/// Two probes are made:
+/// 1. A SOCKS5 greeting offering no authentication must be accepted.
+/// 2. A plain HTTP request must get Tor's "not an HTTP Proxy" response,
+///    which distinguishes Tor from an arbitrary SOCKS5 proxy.
+pub async fn verify_tor_proxy(addr: SocketAddr) -> Result<()> {
+    tokio::time::timeout(PROBE_TIMEOUT, probe_socks5_greeting(addr))
+        .await
+        .map_err(|_| {
+            NetworkError::ConnectionFailed(format!("Tor SOCKS5 port {} did not respond", addr))
+        })??;
+
+    tokio::time::timeout(PROBE_TIMEOUT, probe_tor_http_marker(addr))
+        .await
+        .map_err(|_| {
+            NetworkError::ConnectionFailed(format!(
+                "proxy at {} accepted SOCKS5 but did not answer like Tor",
+                addr
+            ))
+        })?
+}
+
+/// Check that the proxy speaks SOCKS5 and accepts unauthenticated clients
+async fn probe_socks5_greeting(addr: SocketAddr) -> Result<()> {
+    let mut stream = TcpStream::connect(addr).await.map_err(|e| {
+        NetworkError::ConnectionFailed(format!("Tor SOCKS5 port {} unreachable: {}", addr, e))
+    })?;
+
+    // VER=5, NMETHODS=1, METHOD=0 (no authentication)
+    stream.write_all(&[0x05, 0x01, 0x00]).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await.map_err(|e| {
+        NetworkError::ConnectionFailed(format!("{} closed during SOCKS5 greeting: {}", addr, e))
+    })?;
+
+    if reply != [0x05, 0x00] {
+        return Err(NetworkError::ConnectionFailed(format!(
+            "{} is not a SOCKS5 proxy accepting unauthenticated clients",
+            addr
+        )));
+    }
+    Ok(())
+}
+
+/// Check for Tor's fixed response to HTTP requests on its SOCKS port
+async fn probe_tor_http_marker(addr: SocketAddr) -> Result<()> {
+    let mut stream = TcpStream::connect(addr).await.map_err(|e| {
+        NetworkError::ConnectionFailed(format!("Tor SOCKS5 port {} unreachable: {}", addr, e))
+    })?;
+    stream.write_all(b"GET / HTTP/1.0\r\n\r\n").await?;
+
+    let mut response = Vec::with_capacity(512);
+    let _ = (&mut stream).take(512).read_to_end(&mut response).await;
+
+    if !String::from_utf8_lossy(&response).contains(TOR_HTTP_MARKER) {
+        return Err(NetworkError::ConnectionFailed(format!(
+            "proxy at {} speaks SOCKS5 but does not look like Tor",
+            addr
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Spawn a fake proxy that answers the SOCKS5 greeting and, if
+    /// `tor_like`, responds to HTTP the way Tor's SOCKS port does.
+    async fn spawn_fake_proxy(tor_like: bool) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                tokio::spawn(async move {
+                    let mut first = [0u8; 1];
+                    if stream.read_exact(&mut first).await.is_err() {
+                        return;
+                    }
+                    if first[0] == 0x05 {
+                        let mut rest = [0u8; 2];
+                        let _ = stream.read_exact(&mut rest).await;
+                        let _ = stream.write_all(&[0x05, 0x00]).await;
+                    } else if tor_like {
+                        let _ = stream
+                            .write_all(b"HTTP/1.0 501 Tor is not an HTTP Proxy\r\n\r\n")
+                            .await;
+                    } else {
+                        let _ = stream.write_all(b"HTTP/1.0 400 Bad Request\r\n\r\n").await;
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn test_tor_policy_disables_lan_discovery() {
+        let policy = NetworkPolicy::tor(ProxyConfig::tor_default());
+        assert!(policy.is_tor());
+        assert!(!policy.allows_lan_discovery());
+        assert!(!policy.allows_nat_traversal());
+        assert!(!policy.allows_p2p());
+    }
+
+    #[test]
+    fn test_direct_policy_allows_everything() {
+        let policy = NetworkPolicy::new(None);
+        assert!(!policy.is_proxied());
+        assert!(policy.allows_lan_discovery());
+        assert!(policy.allows_nat_traversal());
+        assert!(policy.allows_p2p());
+    }
+
+    #[test]
+    fn test_tor_policy_forces_tor_mode() {
+        let proxy = ProxyConfig::from_url("socks5://127.0.0.1:9150").unwrap();
+        assert!(!proxy.tor_mode);
+        let policy = NetworkPolicy::tor(proxy);
+        assert!(policy.is_tor());
+    }
+
+    #[tokio::test]
+    async fn test_tor_relay_client_uses_socks5_connector() {
+        let policy = NetworkPolicy::tor(ProxyConfig::tor_default());
+
+        // Hostname relays are handed to the proxy, never resolved locally
+        let client = policy.relay_client("relay.example.com:4433").await.unwrap();
+        let proxy = client.proxy_config().expect("relay must be proxied");
+        assert!(proxy.tor_mode);
+        assert_eq!(proxy.socks5_addr.port(), 9050);
+
+        let client = policy.relay_client("129.146.114.5:4433").await.unwrap();
+        assert!(client.proxy_config().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_direct_relay_client_has_no_proxy() {
+        let policy = NetworkPolicy::new(None);
+        let client = policy.relay_client("129.146.114.5:4433").await.unwrap();
+        assert!(client.proxy_config().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_accepts_tor_like_proxy() {
+        let addr = spawn_fake_proxy(true).await;
+        verify_tor_proxy(addr).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_plain_socks5_proxy() {
+        let addr = spawn_fake_proxy(false).await;
+        let err = verify_tor_proxy(addr).await.unwrap_err();
+        assert!(err.to_string().contains("does not look like Tor"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_unreachable_proxy() {
+        // Bind then drop to get a port with nothing listening
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        assert!(verify_tor_proxy(addr).await.is_err());
+    }
+}
@@ -5,6 +5,7 @@
 //! data to/from the paired peer.
 
 use crate::privacy::ProxyConfig;
+use crate::transport::tls_config::TlsPolicy;
 use crate::{NetworkError, Result};
 use std::net::SocketAddr;
 use tracing::info;
@@ -30,14 +31,24 @@ pub struct RelayClient {
     proxy_config: Option<ProxyConfig>,
     /// Relay hostname (for Tor DNS-via-proxy)
     relay_hostname: Option<String>,
+    /// Bearer token presented instead of the password hash, if set
+    auth_token: Option<Vec<u8>>,
+    /// Session token for resuming after a network change (2-peer rooms)
+    session_token: Option<[u8; SESSION_TOKEN_LEN]>,
+    /// TLS policy for the proxied TCP+TLS path
+    tls_policy: TlsPolicy,
 }
 
+/// Length of a relay session token
+const SESSION_TOKEN_LEN: usize = 32;
+
 impl std::fmt::Debug for RelayClient {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RelayClient")
             .field("relay_addr", &self.relay_addr)
             .field("peer_present", &self.peer_present)
             .field("has_proxy", &self.proxy_config.is_some())
+            .field("has_token", &self.auth_token.is_some())
             .finish()
     }
 }
@@ -51,6 +62,9 @@ impl RelayClient {
             peer_present: false,
             proxy_config: None,
             relay_hostname: None,
+            auth_token: None,
+            session_token: None,
+            tls_policy: TlsPolicy::default(),
         }
     }
 
@@ -68,6 +82,9 @@ impl RelayClient {
             peer_present: false,
             proxy_config: Some(proxy),
             relay_hostname: Some(relay_host.to_string()),
+            auth_token: None,
+            session_token: None,
+            tls_policy: TlsPolicy::default(),
         }
     }
 
@@ -79,12 +96,32 @@ impl RelayClient {
         self.proxy_config = Some(proxy);
     }
 
+    /// Restrict TCP+TLS connections (used when proxied) to `policy`
+    pub fn set_tls_policy(&mut self, policy: TlsPolicy) {
+        self.tls_policy = policy;
+    }
+
+    /// The proxy this client routes through, if any
+    pub fn proxy_config(&self) -> Option<&ProxyConfig> {
+        self.proxy_config.as_ref()
+    }
+
+    /// Authenticate with a relay bearer token (serialized, as minted by
+    /// `tallow-relay token mint`)
+    ///
+    /// When set, [`RelayClient::connect`] sends a token join instead of
+    /// the password hash.
+    pub fn set_auth_token(&mut self, token: Vec<u8>) {
+        self.auth_token = Some(token);
+    }
+
     /// Connect to the relay server and join a room
     ///
     /// # Arguments
     ///
     /// * `room_id` - BLAKE3 hash of the code phrase (32 bytes)
-    /// * `password_hash` - Optional BLAKE3 hash of relay password
+    /// * `password_hash` - Optional BLAKE3 hash of relay password (ignored
+    ///   when a bearer token is set)
     ///
     /// # Returns
     ///
@@ -92,7 +129,8 @@ impl RelayClient {
     ///
     /// # Errors
     ///
-    /// Returns `NetworkError::AuthenticationFailed` if the relay rejects the password.
+    /// Returns `NetworkError::AuthenticationFailed` if the relay rejects the
+    /// password or token.
     pub async fn connect(
         &mut self,
         room_id: &[u8; 32],
@@ -101,7 +139,10 @@ impl RelayClient {
         use crate::Transport;
 
         // Build the RoomJoin payload (shared between transport types)
-        let join_payload = build_room_join_payload(room_id, password_hash);
+        let join_payload = match self.auth_token {
+            Some(ref token) => build_room_join_token_payload(room_id, token),
+            None => build_room_join_payload(room_id, password_hash),
+        };
 
         if let Some(ref proxy) = self.proxy_config {
             // Proxy path: TCP+TLS through SOCKS5
@@ -109,7 +150,8 @@ impl RelayClient {
                 proxy,
                 self.relay_hostname.clone(),
                 self.relay_addr.port(),
-            );
+            )
+            .with_tls_policy(self.tls_policy.clone());
             transport.connect(self.relay_addr).await?;
             info!(
                 "connected to relay via SOCKS5 proxy at {}",
@@ -130,6 +172,7 @@ impl RelayClient {
             if n >= 1 {
                 self.peer_present = buf[0] == 1;
             }
+            self.session_token = parse_session_token(&buf[..n]);
 
             info!("joined room via proxy, peer_present={}", self.peer_present);
             self.transport = Some(RelayTransport::Proxied(Box::new(transport)));
@@ -155,6 +198,7 @@ impl RelayClient {
                 if n >= 1 {
                     self.peer_present = buf[0] == 1;
                 }
+                self.session_token = parse_session_token(&buf[..n]);
 
                 info!("joined room, peer_present={}", self.peer_present);
                 self.transport = Some(RelayTransport::Quic(transport));
@@ -187,7 +231,8 @@ impl RelayClient {
                 proxy,
                 self.relay_hostname.clone(),
                 self.relay_addr.port(),
-            );
+            )
+            .with_tls_policy(self.tls_policy.clone());
             transport.connect(self.relay_addr).await?;
             info!(
                 "connected to relay via SOCKS5 proxy at {}",
@@ -313,6 +358,44 @@ impl RelayClient {
         self.peer_present
     }
 
+    /// Session token issued by the relay for the current 2-peer room, if any
+    ///
+    /// Relays that predate session resumption do not issue one.
+    pub fn session_token(&self) -> Option<&[u8; SESSION_TOKEN_LEN]> {
+        self.session_token.as_ref()
+    }
+
+    /// Reconnect to a 2-peer room after a network change.
+    ///
+    /// Presents the session token from the previous connection; the relay
+    /// rebinds our room slot to the new connection and issues a replacement
+    /// token. The old token cannot be used again.
+    ///
+    /// Returns `Err(AuthenticationFailed)` if the token is unknown or expired.
+    pub async fn resume(&mut self, room_id: &[u8; 32]) -> Result<()> {
+        // Kept until the relay confirms, so a failed attempt can be retried
+        let token = self.session_token.ok_or_else(|| {
+            NetworkError::ConnectionFailed("no relay session to resume".to_string())
+        })?;
+        self.close().await;
+
+        let response = self
+            .connect_raw(&build_room_resume_payload(room_id, &token))
+            .await?;
+        match response.split_first() {
+            Some((3, new_token)) if new_token.len() == SESSION_TOKEN_LEN => {
+                let mut next = [0u8; SESSION_TOKEN_LEN];
+                next.copy_from_slice(new_token);
+                self.session_token = Some(next);
+                info!("resumed relay session");
+                Ok(())
+            }
+            _ => Err(NetworkError::ConnectionFailed(
+                "unexpected relay response to session resume".to_string(),
+            )),
+        }
+    }
+
     /// Get the relay address
     pub fn relay_addr(&self) -> SocketAddr {
         self.relay_addr
@@ -343,6 +426,54 @@ fn build_room_join_payload(room_id: &[u8; 32], password_hash: Option<&[u8; 32]>)
     join_payload
 }
 
+/// Build a RoomJoinToken payload (2-peer room)
+///
+/// Format: [discriminant(1)][varint_len(1)][room_id(32)][varint_len][token][option_disc(1)]
+fn build_room_join_token_payload(room_id: &[u8; 32], token: &[u8]) -> Vec<u8> {
+    let mut join_payload = Vec::with_capacity(40 + token.len());
+    join_payload.push(41); // RoomJoinToken discriminant (matches wire::Message enum position)
+    join_payload.push(32); // varint length of room_id
+    join_payload.extend_from_slice(room_id);
+    push_varint(&mut join_payload, token.len());
+    join_payload.extend_from_slice(token);
+    join_payload.push(0x00); // requested_capacity: None (2-peer room)
+    join_payload
+}
+
+/// Build a RoomResume payload
+///
+/// Format: [discriminant(1)][varint_len(1)][room_id(32)][varint_len(1)][token(32)]
+fn build_room_resume_payload(room_id: &[u8; 32], token: &[u8; SESSION_TOKEN_LEN]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(3 + 32 + SESSION_TOKEN_LEN);
+    payload.push(42); // RoomResume discriminant (matches wire::Message enum position)
+    payload.push(32); // varint length of room_id
+    payload.extend_from_slice(room_id);
+    payload.push(SESSION_TOKEN_LEN as u8); // varint length of token
+    payload.extend_from_slice(token);
+    payload
+}
+
+/// Extract the session token trailing a RoomJoined response, if present
+fn parse_session_token(response: &[u8]) -> Option<[u8; SESSION_TOKEN_LEN]> {
+    match response {
+        [0 | 1, token @ ..] if token.len() == SESSION_TOKEN_LEN => {
+            let mut out = [0u8; SESSION_TOKEN_LEN];
+            out.copy_from_slice(token);
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// Append `value` as a postcard (LEB128) varint
+fn push_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
 /// `PeerChannel` implementation for relay connections.
 ///
 /// Delegates to `forward()` and `receive()` methods, allowing relay connections
@@ -367,6 +498,15 @@ impl crate::transport::PeerChannel for RelayClient {
             format!("relay ({})", self.relay_addr)
         }
     }
+
+    /// Congestion state of the hop to the relay (QUIC only)
+    fn congestion(&self) -> Option<crate::transport::CongestionInfo> {
+        match self.transport.as_ref() {
+            #[cfg(feature = "quic")]
+            Some(RelayTransport::Quic(t)) => t.congestion(),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -423,4 +563,42 @@ mod tests {
         assert_eq!(payload[35], 32); // varint len
         assert_eq!(&payload[36..68], &pw_hash);
     }
+
+    #[test]
+    fn test_build_room_join_token_payload() {
+        let room_id = [0x11; 32];
+        let token = [0x22; 200];
+        let payload = build_room_join_token_payload(&room_id, &token);
+
+        // Must match postcard's encoding of Message::RoomJoinToken
+        let mut expected = vec![41, 32];
+        expected.extend_from_slice(&room_id);
+        expected.extend_from_slice(&[0xC8, 0x01]); // varint(200)
+        expected.extend_from_slice(&token);
+        expected.push(0x00);
+        assert_eq!(payload, expected);
+    }
+
+    #[test]
+    fn test_build_room_resume_payload() {
+        let payload = build_room_resume_payload(&[0x11; 32], &[0x33; 32]);
+
+        // Must match postcard's encoding of Message::RoomResume
+        let mut expected = vec![42, 32];
+        expected.extend_from_slice(&[0x11; 32]);
+        expected.push(32);
+        expected.extend_from_slice(&[0x33; 32]);
+        assert_eq!(payload, expected);
+    }
+
+    #[test]
+    fn test_parse_session_token() {
+        let mut joined = vec![1u8];
+        joined.extend_from_slice(&[0x44; 32]);
+        assert_eq!(parse_session_token(&joined), Some([0x44; 32]));
+
+        // Older relays reply with the peer_present flag only
+        assert_eq!(parse_session_token(&[0]), None);
+        assert_eq!(parse_session_token(&[0xFF]), None);
+    }
 }
@@ -104,6 +104,25 @@ impl RelayDirectory {
         self.relays.first()
     }
 
+    /// Up to `count` reachable relays for a [`RedundantChannel`]
+    ///
+    /// Picked by latency, then sorted by address: both peers must list the
+    /// relays in the same order, and their latency probes may disagree.
+    /// Call [`probe_latency`](Self::probe_latency) first.
+    ///
+    /// [`RedundantChannel`]: crate::transport::RedundantChannel
+    pub fn redundant_relays(&self, count: usize) -> Vec<SocketAddr> {
+        let mut picked: Vec<SocketAddr> = self
+            .relays
+            .iter()
+            .filter(|r| r.latency.is_some())
+            .take(count)
+            .map(|r| r.addr)
+            .collect();
+        picked.sort();
+        picked
+    }
+
     /// Get all known relays
     pub fn relays(&self) -> &[RelayInfo] {
         &self.relays
@@ -179,4 +198,30 @@ mod tests {
         let dir = RelayDirectory::with_relays(relays);
         assert_eq!(dir.reachable_count(), 1);
     }
+
+    #[test]
+    fn test_redundant_relays_same_order_for_both_peers() {
+        let relay = |addr: &str, ms: Option<u64>| RelayInfo {
+            addr: addr.parse().unwrap(),
+            region: "test".to_string(),
+            latency: ms.map(Duration::from_millis),
+        };
+        // Each peer's probe ranked the same relays differently
+        let ours = RelayDirectory::with_relays(vec![
+            relay("5.6.7.8:4433", Some(10)),
+            relay("1.2.3.4:4433", Some(40)),
+            relay("9.9.9.9:4433", None),
+        ]);
+        let theirs = RelayDirectory::with_relays(vec![
+            relay("1.2.3.4:4433", Some(15)),
+            relay("5.6.7.8:4433", Some(30)),
+        ]);
+        let expected: Vec<SocketAddr> = vec![
+            "1.2.3.4:4433".parse().unwrap(),
+            "5.6.7.8:4433".parse().unwrap(),
+        ];
+        assert_eq!(ours.redundant_relays(2), expected);
+        assert_eq!(theirs.redundant_relays(2), expected);
+        assert_eq!(ours.redundant_relays(5).len(), 2);
+    }
 }
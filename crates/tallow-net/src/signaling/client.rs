@@ -3,11 +3,51 @@
 //! Provides peer coordination via a signaling server. In v1, signaling
 //! is handled implicitly through the relay's room-join mechanism, so this
 //! client wraps that flow with a higher-level signaling API.
+//!
+//! A server that sees too many pairing attempts answers `Join` with
+//! [`SignalingMessage::RateLimited`]. [`SignalingClient::join`] waits out
+//! the delay it names before trying again, rather than retrying on its own
+//! schedule, and reports each wait so the user knows why nothing happens.
 
 use super::protocol::SignalingMessage;
 use crate::{NetworkError, Result};
+use std::fmt;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// Rate-limit refusals [`SignalingClient::join`] waits out before giving up
+pub const DEFAULT_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Longest single wait honored, whatever the server asks for
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Shortest wait between attempts, so a zero delay can't cause a busy loop
+const MIN_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Progress of a [`SignalingClient::join`] worth telling the user about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalingStatus {
+    /// The server refused the attempt; the client retries after `retry_after`
+    RateLimited {
+        /// How long the client waits before the next attempt
+        retry_after: Duration,
+        /// Retry this wait leads to, starting at 1
+        attempt: u32,
+        /// Server's explanation, if any
+        reason: Option<String>,
+    },
+}
+
+impl fmt::Display for SignalingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RateLimited { retry_after, .. } => {
+                write!(f, "rate limited, retrying in {}s", retry_after.as_secs())
+            }
+        }
+    }
+}
+
 /// Signaling client for coordinating with peers.
 ///
 /// In v1, the relay server acts as the signaling server — peers join rooms
@@ -23,6 +63,8 @@ pub struct SignalingClient {
     outbound_tx: Option<mpsc::Sender<SignalingMessage>>,
     /// Inbound message channel
     inbound_rx: Option<mpsc::Receiver<SignalingMessage>>,
+    /// Rate-limit refusals to wait out before giving up
+    rate_limit_retries: u32,
 }
 
 impl SignalingClient {
@@ -33,9 +75,34 @@ impl SignalingClient {
             connected: false,
             outbound_tx: None,
             inbound_rx: None,
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
         }
     }
 
+    /// Create a client already connected over the given channels
+    ///
+    /// The transport carrying messages to and from the server owns the
+    /// other ends.
+    pub fn with_channels(
+        server_url: String,
+        outbound_tx: mpsc::Sender<SignalingMessage>,
+        inbound_rx: mpsc::Receiver<SignalingMessage>,
+    ) -> Self {
+        Self {
+            server_url,
+            connected: true,
+            outbound_tx: Some(outbound_tx),
+            inbound_rx: Some(inbound_rx),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    /// Set how many rate-limit refusals [`join`](Self::join) waits out
+    pub fn with_rate_limit_retries(mut self, retries: u32) -> Self {
+        self.rate_limit_retries = retries;
+        self
+    }
+
     /// Connect to signaling server.
     ///
     /// Creates internal channels for message passing. The actual transport
@@ -80,6 +147,64 @@ impl SignalingClient {
             .ok_or_else(|| NetworkError::ConnectionFailed("Signaling channel closed".to_string()))
     }
 
+    /// Join `room_code`, waiting out any rate limiting
+    ///
+    /// Each [`SignalingMessage::RateLimited`] reply is reported through
+    /// `on_status` before sleeping for the server's `retry_after_secs`
+    /// (clamped to between 1s and 5 minutes). Once the retry budget is
+    /// spent, fails with [`NetworkError::RateLimited`]; any other refusal
+    /// fails with [`NetworkError::ConnectionFailed`].
+    pub async fn join(
+        &mut self,
+        room_code: &str,
+        peer_id: &str,
+        mut on_status: impl FnMut(&SignalingStatus),
+    ) -> Result<()> {
+        let mut retries: u32 = 0;
+        loop {
+            self.send(SignalingMessage::Join {
+                room_code: room_code.to_string(),
+                peer_id: peer_id.to_string(),
+            })
+            .await?;
+
+            match self.receive().await? {
+                SignalingMessage::Joined { .. } => return Ok(()),
+                SignalingMessage::RateLimited {
+                    retry_after_secs,
+                    reason,
+                } => {
+                    let retry_after = Duration::from_secs(retry_after_secs)
+                        .clamp(MIN_RETRY_AFTER, MAX_RETRY_AFTER);
+                    if retries >= self.rate_limit_retries {
+                        return Err(NetworkError::RateLimited { retry_after });
+                    }
+                    retries += 1;
+                    let status = SignalingStatus::RateLimited {
+                        retry_after,
+                        attempt: retries,
+                        reason,
+                    };
+                    tracing::warn!("Signaling server {}: {}", self.server_url, status);
+                    on_status(&status);
+                    tokio::time::sleep(retry_after).await;
+                }
+                SignalingMessage::Error { message } => {
+                    return Err(NetworkError::ConnectionFailed(format!(
+                        "Signaling server refused join: {}",
+                        message
+                    )))
+                }
+                other => {
+                    return Err(NetworkError::ProtocolNegotiation(format!(
+                        "unexpected reply to join: {:?}",
+                        other
+                    )))
+                }
+            }
+        }
+    }
+
     /// Check if the client is connected
     pub fn is_connected(&self) -> bool {
         self.connected
@@ -127,4 +252,90 @@ mod tests {
         let mut client = SignalingClient::new("ws://localhost:8080".to_string());
         assert!(client.receive().await.is_err());
     }
+
+    /// Start a mock server that answers the first `refusals` joins with a
+    /// rate-limit rejection asking for `retry_after_secs`, then accepts
+    ///
+    /// The returned receiver yields the time each join arrived.
+    fn mock_server(
+        refusals: usize,
+        retry_after_secs: u64,
+    ) -> (
+        SignalingClient,
+        mpsc::UnboundedReceiver<tokio::time::Instant>,
+    ) {
+        let (outbound_tx, mut server_rx) = mpsc::channel(8);
+        let (server_tx, inbound_rx) = mpsc::channel(8);
+        let (seen_tx, seen_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut joins = 0;
+            while let Some(msg) = server_rx.recv().await {
+                let SignalingMessage::Join { room_code, .. } = msg else {
+                    continue;
+                };
+                let _ = seen_tx.send(tokio::time::Instant::now());
+                joins += 1;
+                let reply = if joins <= refusals {
+                    SignalingMessage::RateLimited {
+                        retry_after_secs,
+                        reason: Some("too many pairing attempts".to_string()),
+                    }
+                } else {
+                    SignalingMessage::Joined { room_code }
+                };
+                if server_tx.send(reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let client = SignalingClient::with_channels("mock".to_string(), outbound_tx, inbound_rx);
+        (client, seen_rx)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_join_waits_retry_after() {
+        let (mut client, mut seen) = mock_server(2, 7);
+        let mut statuses = Vec::new();
+        client
+            .join("room", "peer1", |status| statuses.push(status.clone()))
+            .await
+            .unwrap();
+
+        let times: Vec<_> = std::iter::from_fn(|| seen.try_recv().ok()).collect();
+        assert_eq!(times.len(), 3);
+        for pair in times.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_secs(7));
+        }
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].to_string(), "rate limited, retrying in 7s");
+        assert!(matches!(
+            &statuses[1],
+            SignalingStatus::RateLimited { attempt: 2, retry_after, reason: Some(_) }
+                if *retry_after == Duration::from_secs(7)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_join_surfaces_rate_limit_when_budget_spent() {
+        let (client, mut seen) = mock_server(usize::MAX, 0);
+        let mut client = client.with_rate_limit_retries(1);
+        let mut statuses = 0;
+        let err = client
+            .join("room", "peer1", |_| statuses += 1)
+            .await
+            .unwrap_err();
+
+        // A zero delay is still waited as one second, then reported as a
+        // rate limit rather than a generic failure
+        assert_eq!(statuses, 1);
+        assert!(matches!(
+            err,
+            NetworkError::RateLimited { retry_after } if retry_after == Duration::from_secs(1)
+        ));
+        assert!(err.to_string().contains("Rate limited"), "{}", err);
+        let first = seen.try_recv().unwrap();
+        let second = seen.try_recv().unwrap();
+        assert!(second - first >= Duration::from_secs(1));
+    }
 }
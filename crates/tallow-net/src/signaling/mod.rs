@@ -3,5 +3,5 @@
 pub mod client;
 pub mod protocol;
 
-pub use client::SignalingClient;
+pub use client::{SignalingClient, SignalingStatus};
 pub use protocol::SignalingMessage;
@@ -39,4 +39,22 @@ pub enum SignalingMessage {
         /// Candidate SDP
         candidate: String,
     },
+    /// Server accepted a `Join`
+    Joined {
+        /// Room code
+        room_code: String,
+    },
+    /// Server refused a request because the client is sending too many
+    RateLimited {
+        /// Seconds to wait before trying again
+        retry_after_secs: u64,
+        /// Server's explanation, if any
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Server refused a request for any other reason
+    Error {
+        /// Server's explanation
+        message: String,
+    },
 }
@@ -1,9 +1,57 @@
 //! Bandwidth limiting and traffic shaping
 //!
 //! Implements a simple token-bucket rate limiter that sleeps when the
-//! send rate exceeds the configured maximum bytes per second.
+//! send rate exceeds the configured maximum bytes per second. The limit
+//! can be fixed or follow a [`BandwidthSchedule`] of time-of-day windows.
 
+use std::sync::Arc;
 use std::time::Duration;
+use tallow_crypto::clock::{Clock, TimeRange};
+
+/// Seconds in a day
+const DAY_SECS: i64 = 86_400;
+
+/// Rate caps that vary with the time of day
+///
+/// Each window maps a [`TimeRange`] to a cap in bytes per second, where
+/// zero means unlimited. When windows overlap the first one listed wins,
+/// and times no window covers are unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BandwidthSchedule {
+    windows: Vec<(TimeRange, u64)>,
+    utc_offset_secs: i32,
+}
+
+impl BandwidthSchedule {
+    /// Create a schedule from `(window, bytes per second)` pairs, in UTC
+    pub fn new(windows: Vec<(TimeRange, u64)>) -> Self {
+        Self {
+            windows,
+            utc_offset_secs: 0,
+        }
+    }
+
+    /// Interpret the windows in a local time zone `offset_secs` east of UTC
+    pub fn with_utc_offset(mut self, offset_secs: i32) -> Self {
+        self.utc_offset_secs = offset_secs;
+        self
+    }
+
+    /// Whether the schedule has no windows (always unlimited)
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Cap in effect at `unix_secs`, in bytes per second (0 = unlimited)
+    pub fn rate_at(&self, unix_secs: u64) -> u64 {
+        let local = unix_secs as i64 + i64::from(self.utc_offset_secs);
+        let secs_of_day = local.rem_euclid(DAY_SECS) as u32;
+        self.windows
+            .iter()
+            .find(|(range, _)| range.contains(secs_of_day))
+            .map_or(0, |(_, bps)| *bps)
+    }
+}
 
 /// Bandwidth limiter for rate control (token-bucket algorithm)
 #[derive(Debug)]
@@ -14,6 +62,8 @@ pub struct BandwidthLimiter {
     window_start: std::time::Instant,
     /// Bytes sent in current window
     bytes_sent: u64,
+    /// Schedule that sets `max_bps`, and the clock it is read against
+    schedule: Option<(BandwidthSchedule, Arc<dyn Clock>)>,
 }
 
 impl BandwidthLimiter {
@@ -23,6 +73,38 @@ impl BandwidthLimiter {
             max_bps,
             window_start: std::time::Instant::now(),
             bytes_sent: 0,
+            schedule: None,
+        }
+    }
+
+    /// Create a limiter whose rate follows `schedule`
+    ///
+    /// The active window is re-checked on every
+    /// [`wait_if_needed`](Self::wait_if_needed), so a transfer that
+    /// crosses a window boundary switches rate mid-way.
+    pub fn scheduled(schedule: BandwidthSchedule, clock: Arc<dyn Clock>) -> Self {
+        let max_bps = schedule.rate_at(clock.unix_secs());
+        Self {
+            schedule: Some((schedule, clock)),
+            ..Self::new(max_bps)
+        }
+    }
+
+    /// Current cap in bytes per second (0 = unlimited)
+    pub fn max_bps(&self) -> u64 {
+        self.max_bps
+    }
+
+    /// Pick up the schedule's rate, starting a fresh window if it changed
+    fn refresh_rate(&mut self) {
+        let Some((ref schedule, ref clock)) = self.schedule else {
+            return;
+        };
+        let rate = schedule.rate_at(clock.unix_secs());
+        if rate != self.max_bps {
+            tracing::debug!(from = self.max_bps, to = rate, "bandwidth window changed");
+            self.max_bps = rate;
+            self.reset();
         }
     }
 
@@ -32,6 +114,7 @@ impl BandwidthLimiter {
     /// the rate limit, sleep until enough time has passed to stay within budget.
     /// Returns the duration slept, or `Duration::ZERO` if no wait was needed.
     pub async fn wait_if_needed(&mut self, bytes: usize) -> Duration {
+        self.refresh_rate();
         if self.max_bps == 0 {
             return Duration::ZERO;
         }
@@ -109,4 +192,55 @@ mod tests {
         limiter.reset();
         assert_eq!(limiter.bytes_sent, 0);
     }
+
+    /// 16:59:50 UTC on some day
+    const BEFORE_FIVE_PM: u64 = 19_000 * 86_400 + 16 * 3600 + 59 * 60 + 50;
+
+    fn work_hours_schedule() -> BandwidthSchedule {
+        BandwidthSchedule::new(vec![
+            ("12:00-13:00".parse().unwrap(), 5_000_000),
+            ("09:00-17:00".parse().unwrap(), 1_000_000),
+        ])
+    }
+
+    #[test]
+    fn test_schedule_first_match_and_default() {
+        let schedule = work_hours_schedule();
+        let day = 19_000 * 86_400;
+        assert_eq!(schedule.rate_at(day + 10 * 3600), 1_000_000);
+        // Lunch overlaps work hours; the first window listed wins
+        assert_eq!(schedule.rate_at(day + 12 * 3600 + 30 * 60), 5_000_000);
+        // Uncovered times are unlimited
+        assert_eq!(schedule.rate_at(day + 20 * 3600), 0);
+
+        // Same windows, read in a zone two hours east of UTC
+        let local = work_hours_schedule().with_utc_offset(2 * 3600);
+        assert_eq!(local.rate_at(day + 8 * 3600), 1_000_000);
+        assert_eq!(local.rate_at(day + 16 * 3600), 0);
+    }
+
+    #[tokio::test]
+    async fn test_empty_schedule_is_unlimited() {
+        let clock = Arc::new(tallow_crypto::MockClock::new(BEFORE_FIVE_PM));
+        let mut limiter = BandwidthLimiter::scheduled(BandwidthSchedule::default(), clock);
+        assert_eq!(limiter.max_bps(), 0);
+        assert_eq!(limiter.wait_if_needed(100_000_000).await, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_rate_changes_across_window_boundary() {
+        let clock = Arc::new(tallow_crypto::MockClock::new(BEFORE_FIVE_PM));
+        let mut limiter = BandwidthLimiter::scheduled(work_hours_schedule(), clock.clone());
+        assert_eq!(limiter.max_bps(), 1_000_000);
+
+        limiter.wait_if_needed(1024).await;
+        assert_eq!(limiter.max_bps(), 1_000_000);
+        assert_eq!(limiter.bytes_sent, 1024);
+
+        // Cross 17:00 mid-transfer: the cap lifts and the window restarts
+        clock.advance(Duration::from_secs(15));
+        let waited = limiter.wait_if_needed(100_000_000).await;
+        assert_eq!(limiter.max_bps(), 0);
+        assert_eq!(waited, Duration::ZERO);
+    }
 }
@@ -4,9 +4,11 @@
 //! fall back to relay if mDNS discovery or direct connection fails.
 
 #[cfg(feature = "quic")]
-use crate::transport::direct::{connect_direct, DirectListener};
+use crate::discovery::AdvertiseOptions;
 #[cfg(feature = "quic")]
+use crate::transport::direct::{connect_direct, DirectListener};
 use crate::{NetworkError, Result};
+use std::future::Future;
 #[cfg(feature = "quic")]
 use std::net::SocketAddr;
 use std::time::Duration;
@@ -20,6 +22,42 @@ pub const DIRECT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 /// Timeout for sender waiting for receiver to connect
 pub const SENDER_ACCEPT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Default limit on the key exchange once a peer is connected
+///
+/// Separate from any overall transfer timeout: it only covers the
+/// handshake messages, so a peer that connects and then stalls is dropped
+/// instead of blocking the transfer indefinitely.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A single deadline shared by every step of a handshake
+///
+/// Steps run through [`step`](Self::step) fail with
+/// [`NetworkError::HandshakeTimeout`] once the deadline set at
+/// [`start`](Self::start) has passed, however the time was split between
+/// them.
+#[derive(Debug, Clone, Copy)]
+pub struct HandshakeDeadline {
+    deadline: tokio::time::Instant,
+    timeout: Duration,
+}
+
+impl HandshakeDeadline {
+    /// Start the clock on a handshake that must finish within `timeout`
+    pub fn start(timeout: Duration) -> Self {
+        Self {
+            deadline: tokio::time::Instant::now() + timeout,
+            timeout,
+        }
+    }
+
+    /// Run one handshake step, giving up at the deadline
+    pub async fn step<F: Future>(&self, step: F) -> Result<F::Output> {
+        tokio::time::timeout_at(self.deadline, step)
+            .await
+            .map_err(|_| NetworkError::HandshakeTimeout(self.timeout))
+    }
+}
+
 /// Result of a connection attempt, indicating which transport was used.
 ///
 /// Implements `PeerChannel` via enum dispatch, avoiding the need for
@@ -80,13 +118,21 @@ impl crate::transport::PeerChannel for ConnectionResult {
             ConnectionResult::Relay(r) => r.transport_description(),
         }
     }
+
+    fn congestion(&self) -> Option<crate::transport::CongestionInfo> {
+        match self {
+            ConnectionResult::Direct(d) => d.congestion(),
+            ConnectionResult::Relay(r) => r.congestion(),
+        }
+    }
 }
 
 /// Establish a connection as the sender.
 ///
 /// If `local_mode` is true:
 /// 1. Bind a QUIC listener on a random port
-/// 2. Advertise via mDNS with room code hash and fingerprint
+/// 2. Advertise via mDNS with room code hash and fingerprint, re-announcing
+///    as `advertise` specifies
 /// 3. Wait for receiver to connect (with timeout)
 /// 4. If timeout or error: unregister mDNS, fall back to relay
 ///
@@ -100,13 +146,15 @@ pub async fn establish_sender_connection(
     fingerprint_prefix: &str,
     relay_addr: SocketAddr,
     password_hash: Option<&[u8; 32]>,
+    auth_token: Option<&[u8]>,
     local_mode: bool,
+    advertise: AdvertiseOptions,
 ) -> Result<(ConnectionResult, bool)> {
     if local_mode {
         tracing::info!("Attempting direct LAN connection (sender mode)...");
 
         // Try direct connection first
-        match try_sender_direct(room_id, fingerprint_prefix).await {
+        match try_sender_direct(room_id, fingerprint_prefix, advertise).await {
             Ok(direct_conn) => {
                 tracing::info!("Direct LAN connection established (sender)");
                 return Ok((ConnectionResult::Direct(direct_conn), true));
@@ -119,6 +167,9 @@ pub async fn establish_sender_connection(
 
     // Fall back to relay
     let mut relay = crate::relay::RelayClient::new(relay_addr);
+    if let Some(token) = auth_token {
+        relay.set_auth_token(token.to_vec());
+    }
     relay.connect(room_id, password_hash).await?;
 
     if !relay.peer_present() {
@@ -135,6 +186,7 @@ pub async fn establish_sender_connection(
 async fn try_sender_direct(
     room_id: &[u8; 32],
     fingerprint_prefix: &str,
+    advertise: AdvertiseOptions,
 ) -> Result<crate::transport::DirectConnection> {
     let listener = DirectListener::bind()?;
     let port = listener.port();
@@ -142,7 +194,12 @@ async fn try_sender_direct(
     tracing::info!("Direct listener bound on port {}", port);
 
     // Advertise via mDNS (RAII -- drops and unregisters on scope exit)
-    let _advertiser = crate::discovery::lan::LanAdvertiser::new(port, fingerprint_prefix, room_id)?;
+    let _advertiser = crate::discovery::lan::LanAdvertiser::with_options(
+        port,
+        fingerprint_prefix,
+        room_id,
+        advertise,
+    )?;
 
     tracing::info!("mDNS advertisement active, waiting for receiver...");
 
@@ -168,6 +225,7 @@ pub async fn establish_receiver_connection(
     room_id: &[u8; 32],
     relay_addr: SocketAddr,
     password_hash: Option<&[u8; 32]>,
+    auth_token: Option<&[u8]>,
     local_mode: bool,
 ) -> Result<(ConnectionResult, bool)> {
     if local_mode {
@@ -189,6 +247,9 @@ pub async fn establish_receiver_connection(
 
     // Fall back to relay
     let mut relay = crate::relay::RelayClient::new(relay_addr);
+    if let Some(token) = auth_token {
+        relay.set_auth_token(token.to_vec());
+    }
     relay.connect(room_id, password_hash).await?;
 
     if !relay.peer_present() {
@@ -237,8 +298,16 @@ mod tests {
 
         // This should fail because no relay is running, but importantly
         // it should fail with a connection error, NOT a discovery error.
-        let result =
-            establish_sender_connection(&room_id, "abcd1234", relay_addr, None, false).await;
+        let result = establish_sender_connection(
+            &room_id,
+            "abcd1234",
+            relay_addr,
+            None,
+            None,
+            false,
+            AdvertiseOptions::default(),
+        )
+        .await;
 
         assert!(result.is_err());
         // Verify it's a connection failure, not a discovery error
@@ -249,4 +318,66 @@ mod tests {
             err_msg
         );
     }
+
+    /// A peer that connects but never answers
+    struct SilentPeer;
+
+    impl crate::transport::PeerChannel for SilentPeer {
+        async fn send_message(&mut self, _data: &[u8]) -> Result<()> {
+            Ok(())
+        }
+        async fn receive_message(&mut self, _buf: &mut [u8]) -> Result<usize> {
+            std::future::pending().await
+        }
+        async fn close(&mut self) {}
+        fn transport_description(&self) -> String {
+            "silent".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_timeout_fires_on_silent_peer() {
+        use crate::transport::PeerChannel;
+
+        let timeout = Duration::from_millis(200);
+        let mut peer = SilentPeer;
+        let mut buf = [0u8; 64];
+        let started = std::time::Instant::now();
+
+        let deadline = HandshakeDeadline::start(timeout);
+        deadline
+            .step(peer.send_message(b"init"))
+            .await
+            .unwrap()
+            .unwrap();
+        let err = deadline
+            .step(peer.receive_message(&mut buf))
+            .await
+            .unwrap_err();
+
+        let elapsed = started.elapsed();
+        assert!(
+            matches!(err, NetworkError::HandshakeTimeout(t) if t == timeout),
+            "{}",
+            err
+        );
+        assert!(elapsed >= timeout, "fired early: {:?}", elapsed);
+        assert!(elapsed < timeout * 5, "fired late: {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_deadline_spans_all_steps() {
+        let deadline = HandshakeDeadline::start(Duration::from_millis(300));
+
+        // Each step alone is within the limit, but together they are not
+        deadline
+            .step(tokio::time::sleep(Duration::from_millis(200)))
+            .await
+            .unwrap();
+        let err = deadline
+            .step(tokio::time::sleep(Duration::from_millis(200)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, NetworkError::HandshakeTimeout(_)));
+    }
 }
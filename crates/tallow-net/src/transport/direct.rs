@@ -111,6 +111,12 @@ impl crate::transport::PeerChannel for DirectConnection {
     fn transport_description(&self) -> String {
         format!("direct LAN ({})", self.remote_addr)
     }
+
+    fn congestion(&self) -> Option<crate::transport::CongestionInfo> {
+        Some(crate::transport::quic::connection_congestion(
+            &self.connection,
+        ))
+    }
 }
 
 /// Create a LAN-tuned QUIC transport configuration.
@@ -125,6 +131,8 @@ fn lan_transport_config() -> quinn::TransportConfig {
             .expect("30s fits in IdleTimeout"),
     ));
     transport.initial_rtt(Duration::from_millis(1));
+    // LANs are where jumbo frames are most likely
+    crate::transport::mtu::MtuConfig::default().apply(&mut transport);
     transport
 }
 
@@ -2,9 +2,23 @@
 //!
 //! Tries to connect via QUIC. If that fails (e.g., corporate firewall
 //! blocking UDP), falls back to TCP+TLS automatically.
+//!
+//! Falling back is not limited to connect time. A [`MigratingChannel`]
+//! carries a transfer over one peer channel (typically a direct connection)
+//! and, once [`DegradationDetector`] sees sustained loss on it, brings up an
+//! alternate channel (typically the relay) in the background and moves to
+//! it without the caller reconnecting. Messages in flight on the degraded
+//! path may be lost in the switch, so after
+//! [`take_migration`](MigratingChannel::take_migration) reports one the
+//! receiver sends its `ResumeState` as a `ResumeInfo` message and the
+//! sender continues from the chunks it lists as still missing.
 
+use super::{CongestionInfo, PeerChannel};
 use crate::{NetworkError, Result, Transport};
+use futures::future::BoxFuture;
+use std::future::Future;
 use std::net::SocketAddr;
+use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
 /// Active transport type
@@ -45,6 +59,15 @@ impl FallbackTransport {
         }
     }
 
+    /// Create a fallback transport whose TCP+TLS leg is restricted to
+    /// `policy`
+    pub fn with_tls_policy(policy: super::tls_config::TlsPolicy) -> Self {
+        Self {
+            tcp_tls: super::tcp_tls::TcpTlsTransport::with_tls_policy(policy),
+            ..Self::new()
+        }
+    }
+
     /// Get the currently active transport type
     pub fn active_transport(&self) -> ActiveTransport {
         self.active
@@ -101,3 +124,495 @@ impl Transport for FallbackTransport {
         }
     }
 }
+
+/// When a live path is degraded enough to migrate away from
+#[derive(Debug, Clone)]
+pub struct MigrationPolicy {
+    /// Loss (0.0-100.0) at or above which a check counts as degraded
+    pub loss_threshold_pct: f64,
+    /// Deliveries recorded between checks
+    pub check_interval: u32,
+    /// Consecutive degraded checks before migrating
+    pub sustained_checks: u32,
+}
+
+impl Default for MigrationPolicy {
+    fn default() -> Self {
+        Self {
+            loss_threshold_pct: 5.0,
+            check_interval: 20,
+            sustained_checks: 3,
+        }
+    }
+}
+
+/// Decides from delivery outcomes when a path has degraded for good
+///
+/// Each check looks at the loss over the deliveries since the previous
+/// one. A single burst is not enough: the loss must stay at or above the
+/// threshold for [`MigrationPolicy::sustained_checks`] checks in a row.
+#[derive(Debug)]
+pub struct DegradationDetector {
+    policy: MigrationPolicy,
+    since_check: u32,
+    lost_since_check: u32,
+    streak: u32,
+}
+
+impl DegradationDetector {
+    /// Create a detector with no samples
+    pub fn new(policy: MigrationPolicy) -> Self {
+        Self {
+            policy,
+            since_check: 0,
+            lost_since_check: 0,
+            streak: 0,
+        }
+    }
+
+    /// Record whether a message was delivered
+    ///
+    /// Returns `true` once degradation has been sustained, and on every
+    /// later call until a check finds the path healthy again.
+    pub fn record_delivery(&mut self, delivered: bool) -> bool {
+        self.since_check += 1;
+        if !delivered {
+            self.lost_since_check += 1;
+        }
+        if self.since_check >= self.policy.check_interval.max(1) {
+            let loss_pct = self.lost_since_check as f64 * 100.0 / self.since_check as f64;
+            if loss_pct >= self.policy.loss_threshold_pct {
+                self.streak += 1;
+            } else {
+                self.streak = 0;
+            }
+            self.since_check = 0;
+            self.lost_since_check = 0;
+        }
+        self.is_degraded()
+    }
+
+    /// Whether the last checks all found the path degraded
+    pub fn is_degraded(&self) -> bool {
+        self.streak >= self.policy.sustained_checks.max(1)
+    }
+}
+
+/// A peer channel that moves to an alternate path when the first degrades
+///
+/// Messages use `primary` until its loss, reported through
+/// [`record_delivery`](Self::record_delivery), is sustained or it fails
+/// outright. The alternate is then established in the background and takes
+/// over as soon as it is up; the primary is closed. Each side of a transfer
+/// wraps its own channel: the side with delivery feedback (the sender)
+/// decides, and the other uses [`follow_peer`](Self::follow_peer) so its
+/// alternate, e.g. a relay room join, completes once the peer arrives.
+pub struct MigratingChannel<P: PeerChannel, A: PeerChannel + 'static> {
+    primary: Option<P>,
+    alternate: Option<A>,
+    /// Connects the alternate; taken when the migration starts
+    connect: Option<BoxFuture<'static, Result<A>>>,
+    /// Alternate being established in the background
+    pending: Option<JoinHandle<Result<A>>>,
+    detector: DegradationDetector,
+    /// Set on switching, cleared by `take_migration`
+    migrated: bool,
+}
+
+impl<P: PeerChannel, A: PeerChannel + 'static> std::fmt::Debug for MigratingChannel<P, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MigratingChannel")
+            .field("on_alternate", &self.alternate.is_some())
+            .field("establishing", &self.pending.is_some())
+            .field("detector", &self.detector)
+            .finish()
+    }
+}
+
+impl<P: PeerChannel, A: PeerChannel + 'static> MigratingChannel<P, A> {
+    /// Use `primary`, with `connect_alternate` establishing the fallback
+    /// path if it is needed
+    pub fn new(
+        primary: P,
+        connect_alternate: impl Future<Output = Result<A>> + Send + 'static,
+    ) -> Self {
+        Self {
+            primary: Some(primary),
+            alternate: None,
+            connect: Some(Box::pin(connect_alternate)),
+            pending: None,
+            detector: DegradationDetector::new(MigrationPolicy::default()),
+            migrated: false,
+        }
+    }
+
+    /// Set when the primary counts as degraded
+    pub fn with_policy(mut self, policy: MigrationPolicy) -> Self {
+        self.detector = DegradationDetector::new(policy);
+        self
+    }
+
+    /// Start establishing the alternate now and switch once it is up
+    ///
+    /// For the peer without delivery feedback: its alternate connection
+    /// only completes when the other side has migrated too. Must be called
+    /// within a Tokio runtime.
+    pub fn follow_peer(mut self) -> Self {
+        self.start_migration();
+        self
+    }
+
+    /// Record whether a message sent on the primary was delivered
+    ///
+    /// Starts the migration once the loss is sustained. Ignored after the
+    /// switch.
+    pub fn record_delivery(&mut self, delivered: bool) {
+        if self.alternate.is_none() && self.detector.record_delivery(delivered) {
+            self.start_migration();
+        }
+    }
+
+    /// Begin establishing the alternate in the background, if not already
+    pub fn start_migration(&mut self) {
+        if let Some(connect) = self.connect.take() {
+            info!("Primary path degraded; establishing alternate in the background");
+            self.pending = Some(tokio::spawn(connect));
+        }
+    }
+
+    /// Wait for a migration in progress to finish
+    ///
+    /// Returns whether messages now travel over the alternate. Fails if
+    /// the alternate could not be established.
+    pub async fn finish_migration(&mut self) -> Result<bool> {
+        if let Some(pending) = self.pending.take() {
+            self.finish(joined(pending.await)).await?;
+        }
+        Ok(self.is_migrated())
+    }
+
+    /// Whether messages now travel over the alternate
+    pub fn is_migrated(&self) -> bool {
+        self.alternate.is_some()
+    }
+
+    /// Whether a switch happened since the last call
+    ///
+    /// Anything in flight on the old path may be lost, so a `true` is the
+    /// cue to resynchronise from the receiver's resume state.
+    pub fn take_migration(&mut self) -> bool {
+        std::mem::take(&mut self.migrated)
+    }
+
+    /// Switch if the alternate finished connecting in the background
+    async fn switch_if_ready(&mut self) {
+        if self.pending.as_ref().is_some_and(JoinHandle::is_finished) {
+            if let Some(pending) = self.pending.take() {
+                if let Err(e) = self.finish(joined(pending.await)).await {
+                    warn!("Alternate path failed, staying on primary: {}", e);
+                }
+            }
+        }
+    }
+
+    /// The primary failed with `err`: wait for the alternate or give up
+    async fn migrate_now(&mut self, err: NetworkError) -> Result<()> {
+        warn!("Primary path failed: {}", err);
+        self.start_migration();
+        let Some(pending) = self.pending.take() else {
+            return Err(err);
+        };
+        self.finish(joined(pending.await)).await
+    }
+
+    /// Move onto a newly connected alternate, closing the primary
+    async fn finish(&mut self, established: Result<A>) -> Result<()> {
+        let alternate = established?;
+        if let Some(mut primary) = self.primary.take() {
+            info!(
+                "Migrating from {} to {}",
+                primary.transport_description(),
+                alternate.transport_description()
+            );
+            primary.close().await;
+        }
+        self.alternate = Some(alternate);
+        self.migrated = true;
+        Ok(())
+    }
+
+    fn alternate_mut(&mut self) -> Result<&mut A> {
+        self.alternate
+            .as_mut()
+            .ok_or_else(|| NetworkError::ConnectionFailed("no path left".to_string()))
+    }
+}
+
+/// A finished connect task's result, with a panic or cancellation as an error
+fn joined<A>(result: std::result::Result<Result<A>, tokio::task::JoinError>) -> Result<A> {
+    result.unwrap_or_else(|e| {
+        Err(NetworkError::ConnectionFailed(format!(
+            "alternate connect task failed: {}",
+            e
+        )))
+    })
+}
+
+impl<P: PeerChannel, A: PeerChannel + 'static> PeerChannel for MigratingChannel<P, A> {
+    async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+        self.switch_if_ready().await;
+        if let Some(alternate) = self.alternate.as_mut() {
+            return alternate.send_message(data).await;
+        }
+        let Some(primary) = self.primary.as_mut() else {
+            return Err(NetworkError::ConnectionFailed("no path left".to_string()));
+        };
+        match primary.send_message(data).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.migrate_now(e).await?;
+                self.alternate_mut()?.send_message(data).await
+            }
+        }
+    }
+
+    async fn receive_message(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if let Some(alternate) = self.alternate.as_mut() {
+            return alternate.receive_message(buf).await;
+        }
+        let Some(primary) = self.primary.as_mut() else {
+            return Err(NetworkError::ConnectionFailed("no path left".to_string()));
+        };
+
+        // Drain whatever the primary still delivers, but stop waiting on it
+        // once the alternate is up: a degraded primary may deliver nothing
+        // more
+        let received = match self.pending.as_mut() {
+            Some(pending) => tokio::select! {
+                biased;
+                received = primary.receive_message(buf) => Ok(received),
+                established = pending => Err(joined(established)),
+            },
+            None => Ok(primary.receive_message(buf).await),
+        };
+        match received {
+            Ok(Ok(n)) => Ok(n),
+            Ok(Err(e)) => {
+                self.migrate_now(e).await?;
+                self.alternate_mut()?.receive_message(buf).await
+            }
+            Err(established) => {
+                self.pending = None;
+                match self.finish(established).await {
+                    Ok(()) => self.alternate_mut()?.receive_message(buf).await,
+                    Err(e) => {
+                        warn!("Alternate path failed, staying on primary: {}", e);
+                        match self.primary.as_mut() {
+                            Some(primary) => primary.receive_message(buf).await,
+                            None => Err(e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn close(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.abort();
+        }
+        if let Some(primary) = self.primary.as_mut() {
+            primary.close().await;
+        }
+        if let Some(alternate) = self.alternate.as_mut() {
+            alternate.close().await;
+        }
+    }
+
+    fn transport_description(&self) -> String {
+        match (&self.alternate, &self.primary) {
+            (Some(alternate), _) => alternate.transport_description(),
+            (None, Some(primary)) if self.pending.is_some() => {
+                format!("{} (migrating)", primary.transport_description())
+            }
+            (None, Some(primary)) => primary.transport_description(),
+            (None, None) => "closed".to_string(),
+        }
+    }
+
+    fn congestion(&self) -> Option<CongestionInfo> {
+        match (&self.alternate, &self.primary) {
+            (Some(alternate), _) => alternate.congestion(),
+            (None, Some(primary)) => primary.congestion(),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+    /// One end of an in-memory path that silently drops sends while `lossy`
+    struct MockPath {
+        name: &'static str,
+        tx: UnboundedSender<Vec<u8>>,
+        rx: UnboundedReceiver<Vec<u8>>,
+        lossy: Arc<AtomicBool>,
+    }
+
+    impl PeerChannel for MockPath {
+        async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+            if self.lossy.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            self.tx
+                .send(data.to_vec())
+                .map_err(|_| NetworkError::ConnectionFailed("path gone".to_string()))
+        }
+        async fn receive_message(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let frame = self
+                .rx
+                .recv()
+                .await
+                .ok_or_else(|| NetworkError::ConnectionFailed("path gone".to_string()))?;
+            buf[..frame.len()].copy_from_slice(&frame);
+            Ok(frame.len())
+        }
+        async fn close(&mut self) {}
+        fn transport_description(&self) -> String {
+            self.name.to_string()
+        }
+    }
+
+    fn mock_pair(name: &'static str) -> (MockPath, MockPath, Arc<AtomicBool>) {
+        let (a_tx, b_rx) = unbounded_channel();
+        let (b_tx, a_rx) = unbounded_channel();
+        let lossy = Arc::new(AtomicBool::new(false));
+        (
+            MockPath {
+                name,
+                tx: a_tx,
+                rx: a_rx,
+                lossy: lossy.clone(),
+            },
+            MockPath {
+                name,
+                tx: b_tx,
+                rx: b_rx,
+                lossy: lossy.clone(),
+            },
+            lossy,
+        )
+    }
+
+    fn test_policy() -> MigrationPolicy {
+        MigrationPolicy {
+            loss_threshold_pct: 20.0,
+            check_interval: 4,
+            sustained_checks: 2,
+        }
+    }
+
+    #[test]
+    fn test_detector_ignores_loss_burst() {
+        let mut detector = DegradationDetector::new(test_policy());
+        for _ in 0..4 {
+            assert!(!detector.record_delivery(false));
+        }
+        // Recovery resets the streak before it is sustained
+        for _ in 0..40 {
+            detector.record_delivery(true);
+        }
+        assert!(!detector.is_degraded());
+    }
+
+    #[test]
+    fn test_detector_flags_sustained_loss() {
+        let mut detector = DegradationDetector::new(test_policy());
+        let flagged: Vec<bool> = (0..8).map(|_| detector.record_delivery(false)).collect();
+        assert_eq!(flagged.iter().position(|&d| d), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_sustained_loss_migrates_to_alternate() {
+        let (direct_a, mut direct_b, lossy) = mock_pair("direct");
+        let (relay_a, mut relay_b, _) = mock_pair("relay");
+        let mut channel =
+            MigratingChannel::new(direct_a, async move { Ok(relay_a) }).with_policy(test_policy());
+        let mut buf = [0u8; 8];
+
+        channel.send_message(b"one").await.unwrap();
+        channel.record_delivery(true);
+        let n = direct_b.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"one");
+
+        lossy.store(true, Ordering::SeqCst);
+        for _ in 0..8 {
+            channel.send_message(b"lost").await.unwrap();
+            channel.record_delivery(false);
+        }
+        assert!(!channel.is_migrated());
+        assert!(channel.transport_description().ends_with("(migrating)"));
+
+        // The alternate comes up in the background and takes over
+        while !channel
+            .pending
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            tokio::task::yield_now().await;
+        }
+        channel.send_message(b"two").await.unwrap();
+        assert!(channel.is_migrated());
+        assert!(channel.take_migration());
+        assert!(!channel.take_migration());
+        assert_eq!(channel.transport_description(), "relay");
+        let n = relay_b.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"two");
+    }
+
+    #[tokio::test]
+    async fn test_failed_primary_migrates_immediately() {
+        let (direct_a, direct_b, _) = mock_pair("direct");
+        let (relay_a, mut relay_b, _) = mock_pair("relay");
+        let mut channel = MigratingChannel::new(direct_a, async move { Ok(relay_a) });
+        drop(direct_b);
+
+        channel.send_message(b"msg").await.unwrap();
+        assert!(channel.is_migrated());
+        let mut buf = [0u8; 8];
+        let n = relay_b.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"msg");
+    }
+
+    #[tokio::test]
+    async fn test_follower_switches_when_peer_arrives() {
+        let (direct_a, mut direct_b, _) = mock_pair("direct");
+        let (relay_a, mut relay_b, _) = mock_pair("relay");
+        let (arrived_tx, arrived_rx) = tokio::sync::oneshot::channel::<()>();
+        let mut follower = MigratingChannel::new(direct_a, async move {
+            arrived_rx
+                .await
+                .map_err(|_| NetworkError::ConnectionFailed("peer never came".to_string()))?;
+            Ok(relay_a)
+        })
+        .follow_peer();
+        let mut buf = [0u8; 8];
+
+        // Data already queued on the primary is drained first
+        direct_b.send_message(b"early").await.unwrap();
+        let n = follower.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"early");
+        assert!(!follower.is_migrated());
+
+        arrived_tx.send(()).unwrap();
+        relay_b.send_message(b"late").await.unwrap();
+        let n = follower.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"late");
+        assert!(follower.take_migration());
+    }
+}
@@ -5,18 +5,28 @@
 //!
 //! The `PeerChannel` trait provides a unified abstraction for both relay
 //! and direct LAN connections, allowing the transfer pipeline to be
-//! transport-agnostic.
+//! transport-agnostic. Channels over QUIC also report congestion state,
+//! which [`pacing::CongestionPacer`] uses to space out chunk submission.
+//! QUIC connections discover the path MTU as they go ([`mtu`]), and a
+//! [`RedundantChannel`] fails over between relays without losing messages.
+//! A [`MigratingChannel`] moves a transfer off a degraded direct path onto
+//! the relay mid-transfer.
 
 pub mod bandwidth;
 pub mod connection;
 pub mod direct;
 pub mod fallback;
+pub mod keepalive;
+pub mod mtu;
 pub mod negotiation;
 pub mod p2p;
+pub mod pacing;
 pub mod peer_channel;
 pub mod proxied;
+pub mod quality;
 pub mod quic;
 pub mod reconnect;
+pub mod redundant;
 pub mod tcp_tls;
 pub mod tls_config;
 
@@ -27,17 +37,27 @@ use std::net::SocketAddr;
 pub use connection::{
     establish_receiver_connection, establish_sender_connection, ConnectionResult,
 };
+pub use connection::{HandshakeDeadline, DEFAULT_HANDSHAKE_TIMEOUT};
 #[cfg(feature = "quic")]
 pub use direct::{connect_direct, DirectConnection, DirectListener};
-pub use fallback::{ActiveTransport, FallbackTransport};
+pub use fallback::{
+    ActiveTransport, DegradationDetector, FallbackTransport, MigratingChannel, MigrationPolicy,
+};
+pub use keepalive::{KeepaliveChannel, KeepaliveConfig};
+#[cfg(feature = "quic")]
+pub use mtu::MtuConfig;
 #[cfg(feature = "quic")]
 pub use p2p::{negotiate_p2p, NegotiationResult};
+pub use pacing::{CongestionInfo, CongestionPacer};
 pub use peer_channel::PeerChannel;
 pub use proxied::ProxiedTcpTlsTransport;
+pub use quality::{QualityMonitor, QualityRating, QualitySnapshot};
 #[cfg(feature = "quic")]
 pub use quic::QuicTransport;
 pub use reconnect::ReconnectConfig;
+pub use redundant::{connect_redundant_relays, RedundantChannel};
 pub use tcp_tls::TcpTlsTransport;
+pub use tls_config::{TlsMinVersion, TlsPolicy};
 
 /// Transport layer abstraction
 ///
@@ -0,0 +1,189 @@
+//! Path MTU discovery for QUIC connections
+//!
+//! Every QUIC path starts at 1200-byte datagrams, the size the protocol
+//! guarantees will get through. quinn implements Datagram Packetization
+//! Layer PMTU Discovery (RFC 8899) on top of that: once the handshake is
+//! confirmed it binary-searches upward with padded probe packets, treats a
+//! size as too big after three of its probes are lost, and drops back to
+//! the base size when full-size packets start disappearing (a black hole),
+//! searching again after a cooldown. [`MtuConfig`] sets how far up the
+//! search may go and how often it is repeated.
+//!
+//! quinn's own default stops at 1452 bytes, what fits an Ethernet frame.
+//! Tallow searches up to jumbo-frame size so LAN and datacenter links that
+//! carry 9000-byte frames move six times as much payload per packet; on an
+//! ordinary path the search simply settles lower.
+
+#[cfg(feature = "quic")]
+use std::time::Duration;
+
+/// Datagram size every QUIC path must carry (RFC 9000 §14)
+pub const BASE_MTU: u16 = 1200;
+
+/// Largest UDP payload searched for by default: a 9000-byte jumbo frame
+/// less the IPv6 and UDP headers
+pub const DEFAULT_MAX_MTU: u16 = 8952;
+
+/// Path MTU discovery settings for a QUIC endpoint
+#[cfg(feature = "quic")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MtuConfig {
+    /// Probe for a larger MTU at all (off = stay at [`BASE_MTU`])
+    pub enabled: bool,
+    /// Largest UDP payload size probed
+    pub max_mtu: u16,
+    /// Time between searches once one has finished
+    pub search_interval: Duration,
+    /// Time after a detected black hole before searching again
+    pub black_hole_cooldown: Duration,
+}
+
+#[cfg(feature = "quic")]
+impl Default for MtuConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_mtu: DEFAULT_MAX_MTU,
+            search_interval: Duration::from_secs(600),
+            black_hole_cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+#[cfg(feature = "quic")]
+impl MtuConfig {
+    /// Settings that keep every connection at [`BASE_MTU`]
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Apply these settings to a quinn transport configuration
+    pub fn apply(&self, transport: &mut quinn::TransportConfig) {
+        transport.initial_mtu(BASE_MTU);
+        transport.min_mtu(BASE_MTU);
+        if !self.enabled {
+            transport.mtu_discovery_config(None);
+            return;
+        }
+        let mut discovery = quinn::MtuDiscoveryConfig::default();
+        discovery
+            .upper_bound(self.max_mtu.max(BASE_MTU))
+            .interval(self.search_interval)
+            .black_hole_cooldown(self.black_hole_cooldown);
+        transport.mtu_discovery_config(Some(discovery));
+    }
+}
+
+/// Largest UDP payload the connection currently sends
+#[cfg(feature = "quic")]
+pub fn path_mtu(connection: &quinn::Connection) -> u16 {
+    connection.stats().path.current_mtu
+}
+
+#[cfg(test)]
+#[cfg(feature = "quic")]
+mod tests {
+    use super::*;
+    use crate::transport::tls_config;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::net::UdpSocket;
+
+    /// Forward datagrams between one client and `server`, silently
+    /// dropping any larger than `limit` the way a path with a smaller MTU
+    /// and no ICMP feedback would
+    async fn lossy_path(server: SocketAddr, limit: usize) -> SocketAddr {
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 65_536];
+            let mut client = None;
+            loop {
+                let Ok((n, from)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let to = if from == server {
+                    match client {
+                        Some(client) => client,
+                        None => continue,
+                    }
+                } else {
+                    client = Some(from);
+                    server
+                };
+                if n <= limit {
+                    let _ = socket.send_to(&buf[..n], to).await;
+                }
+            }
+        });
+        addr
+    }
+
+    /// Push stream data through `limit` until the client's MTU satisfies
+    /// `done` or ten seconds pass, and return the final MTU
+    async fn discover(limit: usize, done: impl Fn(u16) -> bool) -> u16 {
+        let identity = tls_config::generate_self_signed().unwrap();
+        let server = quinn::Endpoint::server(
+            tls_config::quinn_server_config(&identity).unwrap(),
+            "127.0.0.1:0".parse().unwrap(),
+        )
+        .unwrap();
+        let path = lossy_path(server.local_addr().unwrap(), limit).await;
+
+        tokio::spawn(async move {
+            let conn = server.accept().await.unwrap().await.unwrap();
+            let (_send, mut recv) = conn.accept_bi().await.unwrap();
+            let mut buf = vec![0u8; 65_536];
+            while let Ok(Some(_)) = recv.read(&mut buf).await {}
+        });
+
+        let mut client = quinn::Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        client.set_default_client_config(tls_config::quinn_client_config().unwrap());
+        let conn = client.connect(path, "localhost").unwrap().await.unwrap();
+        let (mut send, _recv) = conn.open_bi().await.unwrap();
+
+        let chunk = vec![0u8; 16 * 1024];
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while !done(path_mtu(&conn)) && tokio::time::Instant::now() < deadline {
+            send.write_all(&chunk).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let mtu = path_mtu(&conn);
+        conn.close(0u32.into(), b"done");
+        mtu
+    }
+
+    #[test]
+    fn test_apply_configs() {
+        let mut transport = quinn::TransportConfig::default();
+        MtuConfig::default().apply(&mut transport);
+        MtuConfig::disabled().apply(&mut transport);
+        MtuConfig {
+            max_mtu: 100,
+            ..MtuConfig::default()
+        }
+        .apply(&mut transport);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_settles_below_black_hole() {
+        const LIMIT: usize = 1400;
+        // Stop once the search has climbed within quinn's minimum step of
+        // the limit; more probing would only show it can't go further
+        let mtu = discover(LIMIT, |mtu| mtu as usize > LIMIT - 20).await;
+        assert!(mtu as usize <= LIMIT, "MTU {} above the black hole", mtu);
+        assert!(mtu > BASE_MTU, "MTU never rose above the base");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ramps_up_on_jumbo_path() {
+        // Loopback carries 64 KiB datagrams, so only the configured
+        // ceiling stops the search
+        let mtu = discover(65_536, |mtu| mtu >= 8_000).await;
+        assert!(mtu >= 8_000, "MTU only reached {}", mtu);
+        assert!(mtu <= DEFAULT_MAX_MTU);
+    }
+}
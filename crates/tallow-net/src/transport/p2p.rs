@@ -18,11 +18,11 @@
 
 #[cfg(feature = "quic")]
 use crate::nat::candidates::{
-    decode_socket_addr, encode_socket_addr, gather_candidates, validate_candidate_addr, Candidate,
-    CandidateType,
+    decode_socket_addr, encode_socket_addr, gather_candidates, normalize_candidates, share_lan,
+    validate_candidate_addr_scoped, Candidate, CandidateType, ScopeFilter,
 };
 #[cfg(feature = "quic")]
-use crate::nat::detection::{detect, NatType};
+use crate::nat::detection::{detect_cached, NatType};
 #[cfg(feature = "quic")]
 use crate::transport::direct::{DirectConnection, DirectListener};
 #[cfg(feature = "quic")]
@@ -92,7 +92,8 @@ impl std::fmt::Debug for NegotiationResult {
 /// The `is_initiator` flag determines QUIC roles: initiator=client, responder=server.
 /// Derive from peer ordering (e.g., sender=true, receiver=false).
 /// The `no_p2p` flag is a defense-in-depth guard: if true, returns FallbackToRelay
-/// immediately. Pass `proxy_config.is_some() || args.no_p2p` from the call site.
+/// immediately. Pass `true` when the caller's `NetworkPolicy` forbids P2P or
+/// NAT traversal, or the user passed `--no-p2p`.
 #[cfg(feature = "quic")]
 pub async fn negotiate_p2p(
     channel: &mut impl PeerChannel,
@@ -130,7 +131,7 @@ async fn negotiate_inner(
     is_initiator: bool,
 ) -> Result<NegotiationResult> {
     // Step 1: Detect NAT type
-    let nat_type = detect().await.unwrap_or(NatType::Unknown);
+    let nat_type = detect_cached().await.unwrap_or(NatType::Unknown);
     tracing::info!("NAT type detected: {}", nat_type);
 
     if nat_type == NatType::Symmetric {
@@ -165,8 +166,9 @@ async fn negotiate_inner(
     }
     send_candidates_done(channel).await?;
 
-    // Step 5: Receive remote candidates from peer
-    let remote_candidates = receive_remote_candidates(channel).await?;
+    // Step 5: Receive remote candidates from peer. A peer may list one
+    // endpoint as both IPv4 and IPv4-mapped IPv6; dial it only once.
+    let remote_candidates = normalize_candidates(receive_remote_candidates(channel).await?);
 
     if remote_candidates.is_empty() {
         tracing::info!("Peer sent no candidates (symmetric NAT or P2P disabled)");
@@ -176,10 +178,13 @@ async fn negotiate_inner(
     }
     tracing::info!("Received {} remote candidates", remote_candidates.len());
 
-    // Step 6: Filter and validate remote candidates
+    // Step 6: Filter and validate remote candidates. Link-local addresses
+    // are only worth trying when both peers appear to share a LAN segment.
+    let same_lan = share_lan(&local_candidates, &remote_candidates);
+    let scope_filter = ScopeFilter::default();
     let valid_candidates: Vec<_> = remote_candidates
         .into_iter()
-        .filter(|c| validate_candidate_addr(&c.addr))
+        .filter(|c| validate_candidate_addr_scoped(&c.addr, &scope_filter, same_lan))
         .collect();
 
     if valid_candidates.is_empty() {
@@ -0,0 +1,189 @@
+//! Congestion-window pacing for chunk submission
+//!
+//! QUIC exposes its congestion window and RTT estimate, which together give
+//! the rate the path is currently believed to sustain. [`CongestionPacer`]
+//! spaces chunk submissions at that rate instead of writing as fast as the
+//! stream buffer accepts, so progress reflects what is actually leaving and
+//! the transfer yields sooner to other flows when the window shrinks.
+//!
+//! Transports without congestion feedback (TCP+TLS) report nothing, and the
+//! pacer steps aside: backpressure from the socket buffer is all there is.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Smallest RTT used for rate calculations, so a near-zero LAN estimate
+/// doesn't turn into an unbounded rate
+const MIN_RTT: Duration = Duration::from_millis(1);
+
+/// Congestion state reported by a transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CongestionInfo {
+    /// Current congestion window in bytes
+    pub cwnd_bytes: u64,
+    /// Smoothed round-trip time
+    pub rtt: Duration,
+}
+
+impl CongestionInfo {
+    /// Rate the window allows, in bytes per second (one window per RTT)
+    pub fn pacing_rate(&self) -> u64 {
+        let rtt = self.rtt.max(MIN_RTT);
+        (self.cwnd_bytes as f64 / rtt.as_secs_f64()) as u64
+    }
+}
+
+/// Spaces submissions at the transport's reported pacing rate
+#[derive(Debug, Default)]
+pub struct CongestionPacer {
+    /// Earliest time the next submission may start
+    next_send: Option<Instant>,
+    /// Rate used for the most recent submission (0 = not pacing)
+    rate: u64,
+}
+
+impl CongestionPacer {
+    /// Create a pacer with no submission history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rate applied to the last submission in bytes per second (0 = none)
+    pub fn current_rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Wait until `bytes` may be submitted under the reported congestion state
+    ///
+    /// The rate is re-read on every call, so a shrinking window stretches the
+    /// gap before the very next chunk. Idle time earns no burst credit.
+    /// Returns the duration slept.
+    pub async fn pace(&mut self, bytes: usize, congestion: Option<CongestionInfo>) -> Duration {
+        let rate = congestion.map_or(0, |c| c.pacing_rate());
+        self.rate = rate;
+        if rate == 0 {
+            self.next_send = None;
+            return Duration::ZERO;
+        }
+
+        let now = Instant::now();
+        let start = self.next_send.map_or(now, |next| next.max(now));
+        let waited = start - now;
+        if !waited.is_zero() {
+            tokio::time::sleep_until(start).await;
+        }
+
+        self.next_send = Some(start + Duration::from_secs_f64(bytes as f64 / rate as f64));
+        waited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::PeerChannel;
+    use crate::Result;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    const RTT: Duration = Duration::from_millis(100);
+    const CHUNK: usize = 10_000;
+
+    /// Channel reporting a congestion window the test can change
+    struct SyntheticCwnd {
+        cwnd: Arc<AtomicU64>,
+        sent_bytes: u64,
+    }
+
+    impl PeerChannel for SyntheticCwnd {
+        async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+            self.sent_bytes += data.len() as u64;
+            Ok(())
+        }
+
+        async fn receive_message(&mut self, _buf: &mut [u8]) -> Result<usize> {
+            Ok(0)
+        }
+
+        async fn close(&mut self) {}
+
+        fn transport_description(&self) -> String {
+            "synthetic".to_string()
+        }
+
+        fn congestion(&self) -> Option<CongestionInfo> {
+            Some(CongestionInfo {
+                cwnd_bytes: self.cwnd.load(Ordering::SeqCst),
+                rtt: RTT,
+            })
+        }
+    }
+
+    /// Submit `count` chunks through the pacer; returns the achieved rate
+    ///
+    /// Timed from the first submission to the end of the last chunk's slot,
+    /// so a slot left over from an earlier window isn't counted.
+    async fn submit(pacer: &mut CongestionPacer, channel: &mut SyntheticCwnd, count: usize) -> f64 {
+        let before = channel.sent_bytes;
+        let mut start = None;
+        for _ in 0..count {
+            pacer.pace(CHUNK, channel.congestion()).await;
+            start.get_or_insert_with(Instant::now);
+            channel.send_message(&[0u8; CHUNK]).await.unwrap();
+        }
+        let last_slot = Duration::from_secs_f64(CHUNK as f64 / pacer.current_rate() as f64);
+        let elapsed = start.unwrap().elapsed() + last_slot;
+        (channel.sent_bytes - before) as f64 / elapsed.as_secs_f64()
+    }
+
+    #[test]
+    fn test_pacing_rate_is_window_per_rtt() {
+        let info = CongestionInfo {
+            cwnd_bytes: 100_000,
+            rtt: RTT,
+        };
+        assert_eq!(info.pacing_rate(), 1_000_000);
+
+        // A zero RTT estimate is clamped instead of dividing by zero
+        let lan = CongestionInfo {
+            cwnd_bytes: 1_000,
+            rtt: Duration::ZERO,
+        };
+        assert_eq!(lan.pacing_rate(), 1_000_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_submission_rate_tracks_window_and_backs_off() {
+        let cwnd = Arc::new(AtomicU64::new(100_000));
+        let mut channel = SyntheticCwnd {
+            cwnd: cwnd.clone(),
+            sent_bytes: 0,
+        };
+        let mut pacer = CongestionPacer::new();
+
+        let full = submit(&mut pacer, &mut channel, 50).await;
+        assert!((full - 1_000_000.0).abs() < 10_000.0, "rate {full}");
+
+        // The window collapses to a quarter: the sender slows to match
+        cwnd.store(25_000, Ordering::SeqCst);
+        let shrunk = submit(&mut pacer, &mut channel, 20).await;
+        assert!((shrunk - 250_000.0).abs() < 2_500.0, "rate {shrunk}");
+        assert!(shrunk < full / 3.0);
+
+        // And speeds back up when the window recovers
+        cwnd.store(200_000, Ordering::SeqCst);
+        let grown = submit(&mut pacer, &mut channel, 50).await;
+        assert!((grown - 2_000_000.0).abs() < 20_000.0, "rate {grown}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_feedback_means_no_pacing() {
+        let mut pacer = CongestionPacer::new();
+        let start = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(pacer.pace(CHUNK, None).await, Duration::ZERO);
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(pacer.current_rate(), 0);
+    }
+}
@@ -5,6 +5,7 @@
 //! (`Message` enum, `TallowCodec`, postcard encoding) is identical regardless
 //! of transport -- only the underlying connection differs.
 
+use crate::transport::CongestionInfo;
 use crate::Result;
 
 /// Unified channel for communicating with a peer, regardless of transport.
@@ -33,6 +34,14 @@ pub trait PeerChannel: Send {
     ///
     /// Examples: `"relay (129.146.114.5:4433)"`, `"direct LAN (192.168.1.42:52341)"`
     fn transport_description(&self) -> String;
+
+    /// Congestion window and RTT of the path, if the transport exposes them
+    ///
+    /// QUIC-backed channels report these; TCP+TLS returns `None` and relies
+    /// on socket-buffer backpressure instead.
+    fn congestion(&self) -> Option<CongestionInfo> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@
 //! traverse SOCKS5, so this TCP+TLS transport is the only option
 //! when a proxy is configured.
 
+use super::tls_config::TlsPolicy;
 use crate::privacy::{ProxyConfig, Socks5Connector};
 use crate::{NetworkError, Result, Transport};
 use std::net::SocketAddr;
@@ -25,6 +26,8 @@ pub struct ProxiedTcpTlsTransport {
     relay_port: u16,
     /// Whether to send hostname to proxy instead of resolved IP (Tor mode)
     use_hostname: bool,
+    /// Versions and cipher suites the TLS handshake may negotiate
+    tls_policy: TlsPolicy,
 }
 
 impl std::fmt::Debug for ProxiedTcpTlsTransport {
@@ -53,9 +56,16 @@ impl ProxiedTcpTlsTransport {
             relay_host,
             relay_port,
             use_hostname: proxy_config.tor_mode,
+            tls_policy: TlsPolicy::default(),
         }
     }
 
+    /// Restrict the TLS handshake to `policy`
+    pub fn with_tls_policy(mut self, policy: TlsPolicy) -> Self {
+        self.tls_policy = policy;
+        self
+    }
+
     /// Connect to the relay through the SOCKS5 proxy
     ///
     /// For Tor mode: sends hostname to proxy (DNS resolved inside Tor network).
@@ -74,7 +84,7 @@ impl ProxiedTcpTlsTransport {
         })??;
 
         // Wrap in TLS
-        let tls_config = super::tls_config::rustls_client_config()?;
+        let tls_config = super::tls_config::rustls_client_config(&self.tls_policy)?;
         let tls_connector = tokio_rustls::TlsConnector::from(tls_config);
 
         // SNI: use relay hostname when available, fall back to "localhost"
@@ -87,7 +97,8 @@ impl ProxiedTcpTlsTransport {
             .connect(server_name, tcp_stream)
             .await
             .map_err(|e| {
-                NetworkError::TlsError(format!("TLS handshake via proxy failed: {}", e))
+                self.tls_policy
+                    .handshake_error("TLS handshake via proxy failed", e)
             })?;
 
         info!(
@@ -0,0 +1,385 @@
+//! Connection quality scoring
+//!
+//! [`QualityMonitor`] keeps rolling statistics for a live connection and
+//! folds them into a 0-100 score and a [`QualityRating`].
+//!
+//! Scoring:
+//!
+//! | Input      | Full marks              | Zero                   | Weight |
+//! |------------|-------------------------|------------------------|--------|
+//! | RTT        | <= 50 ms                | >= 400 ms              | 50     |
+//! | Jitter     | <= 5 ms                 | >= 80 ms               | 25     |
+//! | Throughput | >= 5 MB/s               | <= 64 KB/s (log scale) | 25     |
+//!
+//! Components are interpolated linearly between the bounds so small metric
+//! changes move the score by small amounts. Throughput is left out while the
+//! link is idle. Loss scales the weighted result: 0% keeps it, 8% or more
+//! zeroes it, because a lossy link is bad however fast it is.
+//!
+//! Ratings use the score bands 90/70/50/30. A rating only changes once the
+//! score is [`RATING_HYSTERESIS`] points past a band edge, so a connection
+//! sitting on a boundary doesn't flicker.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// RTT at or below which the RTT component is 100
+pub const RTT_GOOD_MS: f64 = 50.0;
+/// RTT at or above which the RTT component is 0
+pub const RTT_BAD_MS: f64 = 400.0;
+/// Jitter at or below which the jitter component is 100
+pub const JITTER_GOOD_MS: f64 = 5.0;
+/// Jitter at or above which the jitter component is 0
+pub const JITTER_BAD_MS: f64 = 80.0;
+/// Throughput at or above which the throughput component is 100
+pub const THROUGHPUT_GOOD_BPS: f64 = 5_000_000.0;
+/// Throughput at or below which the throughput component is 0
+pub const THROUGHPUT_BAD_BPS: f64 = 64_000.0;
+/// Loss at or above which the score is 0
+pub const LOSS_BAD_PCT: f64 = 8.0;
+/// Points a score must move past a band edge before the rating changes
+pub const RATING_HYSTERESIS: u8 = 3;
+
+const RTT_WEIGHT: f64 = 50.0;
+const JITTER_WEIGHT: f64 = 25.0;
+const THROUGHPUT_WEIGHT: f64 = 25.0;
+
+/// Number of delivery outcomes kept for the loss estimate
+const LOSS_WINDOW: usize = 200;
+/// Time span kept for the throughput estimate
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+/// Smoothing factor for RTT (RFC 6298 uses 1/8)
+const RTT_ALPHA: f64 = 0.125;
+/// Smoothing factor for jitter (RFC 3550 uses 1/16)
+const JITTER_ALPHA: f64 = 0.0625;
+
+/// Overall connection quality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityRating {
+    /// Score 90-100
+    Excellent,
+    /// Score 70-89
+    Good,
+    /// Score 50-69
+    Fair,
+    /// Score 30-49
+    Poor,
+    /// Score 0-29
+    Critical,
+}
+
+impl QualityRating {
+    /// Rating for a score without hysteresis
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            90.. => Self::Excellent,
+            70..=89 => Self::Good,
+            50..=69 => Self::Fair,
+            30..=49 => Self::Poor,
+            _ => Self::Critical,
+        }
+    }
+
+    /// Lowest score in this rating's band
+    pub fn min_score(self) -> u8 {
+        match self {
+            Self::Excellent => 90,
+            Self::Good => 70,
+            Self::Fair => 50,
+            Self::Poor => 30,
+            Self::Critical => 0,
+        }
+    }
+}
+
+/// Point-in-time view of a connection's quality
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualitySnapshot {
+    /// Smoothed round-trip time in milliseconds
+    pub rtt_ms: f64,
+    /// Smoothed RTT variation in milliseconds
+    pub jitter_ms: f64,
+    /// Loss over the recent delivery window (0.0-100.0)
+    pub loss_pct: f64,
+    /// Recent throughput in bytes per second
+    pub throughput_bps: u64,
+    /// Combined score (0-100); `None` until an RTT sample arrives
+    pub score: Option<u8>,
+    /// Rating derived from the score; `None` until an RTT sample arrives
+    pub rating: Option<QualityRating>,
+}
+
+/// Rolling quality estimator for one connection
+#[derive(Debug)]
+pub struct QualityMonitor {
+    srtt_ms: Option<f64>,
+    last_rtt_ms: Option<f64>,
+    jitter_ms: f64,
+    deliveries: VecDeque<bool>,
+    transfers: VecDeque<(Instant, u64)>,
+    first_transfer: Option<Instant>,
+    score: Option<u8>,
+    rating: Option<QualityRating>,
+}
+
+impl Default for QualityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QualityMonitor {
+    /// Create a monitor with no samples
+    pub fn new() -> Self {
+        Self {
+            srtt_ms: None,
+            last_rtt_ms: None,
+            jitter_ms: 0.0,
+            deliveries: VecDeque::with_capacity(LOSS_WINDOW),
+            transfers: VecDeque::new(),
+            first_transfer: None,
+            score: None,
+            rating: None,
+        }
+    }
+
+    /// Record a round-trip time measurement
+    pub fn record_rtt(&mut self, rtt: Duration) {
+        let sample = rtt.as_secs_f64() * 1000.0;
+        self.srtt_ms = Some(match self.srtt_ms {
+            Some(srtt) => srtt + RTT_ALPHA * (sample - srtt),
+            None => sample,
+        });
+        if let Some(last) = self.last_rtt_ms {
+            let delta = (sample - last).abs();
+            self.jitter_ms += JITTER_ALPHA * (delta - self.jitter_ms);
+        }
+        self.last_rtt_ms = Some(sample);
+        self.refresh();
+    }
+
+    /// Record whether a packet or message was delivered
+    pub fn record_delivery(&mut self, delivered: bool) {
+        if self.deliveries.len() == LOSS_WINDOW {
+            self.deliveries.pop_front();
+        }
+        self.deliveries.push_back(delivered);
+        self.refresh();
+    }
+
+    /// Record bytes moved over the connection now
+    pub fn record_bytes(&mut self, bytes: u64) {
+        self.record_bytes_at(bytes, Instant::now());
+    }
+
+    /// Record bytes moved over the connection at `at`
+    pub fn record_bytes_at(&mut self, bytes: u64, at: Instant) {
+        self.first_transfer.get_or_insert(at);
+        self.transfers.push_back((at, bytes));
+        while let Some(&(t, _)) = self.transfers.front() {
+            if at.duration_since(t) > THROUGHPUT_WINDOW {
+                self.transfers.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.refresh();
+    }
+
+    /// Current view of the connection's quality
+    pub fn snapshot(&self) -> QualitySnapshot {
+        QualitySnapshot {
+            rtt_ms: self.srtt_ms.unwrap_or(0.0),
+            jitter_ms: self.jitter_ms,
+            loss_pct: self.loss_pct(),
+            throughput_bps: self.throughput_bps(),
+            score: self.score,
+            rating: self.rating,
+        }
+    }
+
+    fn loss_pct(&self) -> f64 {
+        if self.deliveries.is_empty() {
+            return 0.0;
+        }
+        let lost = self.deliveries.iter().filter(|d| !**d).count();
+        lost as f64 * 100.0 / self.deliveries.len() as f64
+    }
+
+    fn throughput_bps(&self) -> u64 {
+        let (Some(first), Some(&(last, _))) = (self.first_transfer, self.transfers.back()) else {
+            return 0;
+        };
+        // Span the window once it has filled; before that, time since the
+        // first sample (at least 100 ms so one burst doesn't read as huge)
+        let span = last
+            .duration_since(first)
+            .clamp(Duration::from_millis(100), THROUGHPUT_WINDOW);
+        let bytes: u64 = self.transfers.iter().map(|(_, b)| b).sum();
+        (bytes as f64 / span.as_secs_f64()) as u64
+    }
+
+    fn refresh(&mut self) {
+        let Some(rtt_ms) = self.srtt_ms else {
+            return;
+        };
+        let score = compute_score(
+            rtt_ms,
+            self.jitter_ms,
+            self.loss_pct(),
+            self.throughput_bps(),
+        );
+        self.score = Some(score);
+        self.rating = Some(match self.rating {
+            Some(current) => apply_hysteresis(current, score),
+            None => QualityRating::from_score(score),
+        });
+    }
+}
+
+/// Combine metrics into a 0-100 score (see module docs for thresholds)
+///
+/// A `throughput_bps` of 0 means the link is idle and is left out.
+pub fn compute_score(rtt_ms: f64, jitter_ms: f64, loss_pct: f64, throughput_bps: u64) -> u8 {
+    let rtt = falloff(rtt_ms, RTT_GOOD_MS, RTT_BAD_MS);
+    let jitter = falloff(jitter_ms, JITTER_GOOD_MS, JITTER_BAD_MS);
+
+    let mut weighted = rtt * RTT_WEIGHT + jitter * JITTER_WEIGHT;
+    let mut weights = RTT_WEIGHT + JITTER_WEIGHT;
+    if throughput_bps > 0 {
+        let bps = throughput_bps as f64;
+        let throughput = if bps <= THROUGHPUT_BAD_BPS {
+            0.0
+        } else {
+            ((bps / THROUGHPUT_BAD_BPS).ln() / (THROUGHPUT_GOOD_BPS / THROUGHPUT_BAD_BPS).ln())
+                .min(1.0)
+        };
+        weighted += throughput * THROUGHPUT_WEIGHT;
+        weights += THROUGHPUT_WEIGHT;
+    }
+
+    let loss_factor = 1.0 - (loss_pct / LOSS_BAD_PCT).clamp(0.0, 1.0);
+    (weighted / weights * loss_factor * 100.0).round() as u8
+}
+
+/// 1.0 at or below `good`, 0.0 at or above `bad`, linear in between
+fn falloff(value: f64, good: f64, bad: f64) -> f64 {
+    (1.0 - (value - good) / (bad - good)).clamp(0.0, 1.0)
+}
+
+/// Move `current` only when `score` is clearly inside another band
+fn apply_hysteresis(current: QualityRating, score: u8) -> QualityRating {
+    let upgraded = QualityRating::from_score(score.saturating_sub(RATING_HYSTERESIS));
+    let downgraded = QualityRating::from_score(score.saturating_add(RATING_HYSTERESIS).min(100));
+    if upgraded.min_score() > current.min_score() {
+        upgraded
+    } else if downgraded.min_score() < current.min_score() {
+        downgraded
+    } else {
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(v: u64) -> Duration {
+        Duration::from_millis(v)
+    }
+
+    #[test]
+    fn test_synthetic_metrics_map_to_ratings() {
+        // Fast, clean link
+        assert_eq!(
+            QualityRating::from_score(compute_score(20.0, 2.0, 0.0, 10_000_000)),
+            QualityRating::Excellent
+        );
+        // Fine latency but 5% loss
+        assert_eq!(
+            QualityRating::from_score(compute_score(30.0, 3.0, 5.0, 0)),
+            QualityRating::Poor
+        );
+        // Loss past the ceiling zeroes the score
+        assert_eq!(compute_score(10.0, 1.0, 12.0, 0), 0);
+        // Satellite-like latency
+        assert_eq!(
+            QualityRating::from_score(compute_score(600.0, 40.0, 0.0, 0)),
+            QualityRating::Critical
+        );
+        // Moderate latency and jitter
+        assert_eq!(
+            QualityRating::from_score(compute_score(200.0, 30.0, 0.0, 0)),
+            QualityRating::Fair
+        );
+    }
+
+    #[test]
+    fn test_monitor_reports_loss_and_rating() {
+        let mut monitor = QualityMonitor::new();
+        assert_eq!(monitor.snapshot().rating, None);
+
+        for i in 0..100 {
+            monitor.record_rtt(ms(30));
+            monitor.record_delivery(i % 10 != 0); // 10% loss
+        }
+        let snap = monitor.snapshot();
+        assert!((snap.loss_pct - 10.0).abs() < f64::EPSILON);
+        assert_eq!(snap.rating, Some(QualityRating::Critical));
+    }
+
+    #[test]
+    fn test_score_stable_under_minor_jitter() {
+        let mut monitor = QualityMonitor::new();
+        for i in 0..200 {
+            monitor.record_rtt(ms(if i % 2 == 0 { 40 } else { 44 }));
+        }
+        let settled = monitor.snapshot().score.unwrap();
+
+        for i in 0..50 {
+            monitor.record_rtt(ms(if i % 2 == 0 { 38 } else { 46 }));
+            let snap = monitor.snapshot();
+            assert_eq!(snap.rating, Some(QualityRating::Excellent));
+            assert!(snap.score.unwrap().abs_diff(settled) <= 3);
+        }
+    }
+
+    #[test]
+    fn test_rating_hysteresis_at_band_edge() {
+        // Just inside Good must not drop an Excellent rating ...
+        assert_eq!(
+            apply_hysteresis(QualityRating::Excellent, 88),
+            QualityRating::Excellent
+        );
+        // ... but a clear drop does
+        assert_eq!(
+            apply_hysteresis(QualityRating::Excellent, 86),
+            QualityRating::Good
+        );
+        // Upgrades need the same margin
+        assert_eq!(
+            apply_hysteresis(QualityRating::Good, 91),
+            QualityRating::Good
+        );
+        assert_eq!(
+            apply_hysteresis(QualityRating::Good, 93),
+            QualityRating::Excellent
+        );
+    }
+
+    #[test]
+    fn test_throughput_window() {
+        let mut monitor = QualityMonitor::new();
+        let start = Instant::now();
+        for i in 0..=10u32 {
+            monitor.record_bytes_at(1_000_000, start + ms(100) * i);
+        }
+        // 11 MB over 1 s
+        let bps = monitor.snapshot().throughput_bps;
+        assert!((10_000_000..=12_000_000).contains(&bps), "{}", bps);
+
+        // Samples older than the window are dropped
+        monitor.record_bytes_at(1_000_000, start + Duration::from_secs(20));
+        assert_eq!(monitor.snapshot().throughput_bps, 200_000);
+    }
+}
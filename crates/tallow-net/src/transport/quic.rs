@@ -104,6 +104,19 @@ impl QuicTransport {
         self.endpoint.as_ref()
     }
 
+    /// Current congestion window and RTT, once connected
+    pub fn congestion(&self) -> Option<super::CongestionInfo> {
+        self.connection.as_ref().map(connection_congestion)
+    }
+
+    /// Largest datagram the connection currently sends, once connected
+    ///
+    /// Starts at [`super::mtu::BASE_MTU`] and grows as path MTU discovery
+    /// finds the path can carry more.
+    pub fn path_mtu(&self) -> Option<u16> {
+        self.connection.as_ref().map(super::mtu::path_mtu)
+    }
+
     /// Close the transport gracefully
     pub async fn close(&mut self) {
         if let Some(conn) = self.connection.take() {
@@ -117,6 +130,16 @@ impl QuicTransport {
     }
 }
 
+/// Read the congestion controller's view of a QUIC connection's path
+#[cfg(feature = "quic")]
+pub(crate) fn connection_congestion(connection: &quinn::Connection) -> super::CongestionInfo {
+    let path = connection.stats().path;
+    super::CongestionInfo {
+        cwnd_bytes: path.cwnd,
+        rtt: path.rtt,
+    }
+}
+
 #[cfg(feature = "quic")]
 impl Default for QuicTransport {
     fn default() -> Self {
@@ -96,12 +96,17 @@ impl ReconnectConfig {
             NetworkError::Io(io_err) => Self::is_transient_io(io_err),
             // Protocol, auth, DNS, TLS errors are not transient
             NetworkError::AuthenticationFailed
+            | NetworkError::HandshakeTimeout(_)
             | NetworkError::ProtocolNegotiation(_)
             | NetworkError::DnsResolution(_)
             | NetworkError::TlsError(_)
             | NetworkError::NatTraversal(_)
             | NetworkError::DiscoveryError(_)
-            | NetworkError::RelayError(_) => false,
+            | NetworkError::RelayError(_)
+            // The server set the pace; backing off faster would defeat it
+            | NetworkError::RateLimited { .. }
+            // Already retried; retrying again would multiply the budget
+            | NetworkError::RetriesExhausted { .. } => false,
         }
     }
 
@@ -121,8 +126,9 @@ impl ReconnectConfig {
 /// Send a message through a `PeerChannel` with automatic retry on transient failures.
 ///
 /// On each transient error, sleeps for an exponentially increasing backoff duration
-/// before retrying. Returns the first non-transient error or the final transient
-/// error after all retries are exhausted.
+/// before retrying. Returns the first non-transient error as-is, or
+/// [`NetworkError::RetriesExhausted`] wrapping the final transient error once
+/// the retry budget is spent.
 pub async fn send_with_retry(
     channel: &mut impl PeerChannel,
     data: &[u8],
@@ -144,7 +150,7 @@ pub async fn send_with_retry(
                 );
                 tokio::time::sleep(backoff).await;
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(give_up(e, attempts)),
         }
     }
 }
@@ -152,8 +158,9 @@ pub async fn send_with_retry(
 /// Receive a message through a `PeerChannel` with automatic retry on transient failures.
 ///
 /// On each transient error, sleeps for an exponentially increasing backoff duration
-/// before retrying. Returns the first non-transient error or the final transient
-/// error after all retries are exhausted.
+/// before retrying. Returns the first non-transient error as-is, or
+/// [`NetworkError::RetriesExhausted`] wrapping the final transient error once
+/// the retry budget is spent.
 pub async fn receive_with_retry(
     channel: &mut impl PeerChannel,
     buf: &mut [u8],
@@ -175,11 +182,23 @@ pub async fn receive_with_retry(
                 );
                 tokio::time::sleep(backoff).await;
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(give_up(e, attempts)),
         }
     }
 }
 
+/// The error to report after `retries` retries ended with `err`
+fn give_up(err: NetworkError, retries: u32) -> NetworkError {
+    if ReconnectConfig::is_transient(&err) {
+        NetworkError::RetriesExhausted {
+            attempts: retries.saturating_add(1),
+            last: Box::new(err),
+        }
+    } else {
+        err
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,7 +387,7 @@ mod tests {
         let mut channel = FailChannel { call_count: 0 };
 
         let result = send_with_retry(&mut channel, b"hello", &config).await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(NetworkError::AuthenticationFailed)));
         // Should have only been called once (no retries for non-transient)
         assert_eq!(channel.call_count, 1);
     }
@@ -499,9 +518,15 @@ mod tests {
         let mut channel = AlwaysFail { call_count: 0 };
 
         let result = send_with_retry(&mut channel, b"hello", &config).await;
-        assert!(result.is_err());
         // 1 initial + 3 retries = 4 calls total
         assert_eq!(channel.call_count, 4);
+        match result {
+            Err(NetworkError::RetriesExhausted { attempts, last }) => {
+                assert_eq!(attempts, 4);
+                assert!(matches!(*last, NetworkError::Io(_)));
+            }
+            other => panic!("expected RetriesExhausted, got {:?}", other),
+        }
     }
 
     /// Test with max_retries=0: no retries, transient errors fail immediately
@@ -537,8 +562,11 @@ mod tests {
         let mut channel = TransientFail { call_count: 0 };
 
         let result = send_with_retry(&mut channel, b"hello", &config).await;
-        assert!(result.is_err());
         // Only 1 call — no retries
         assert_eq!(channel.call_count, 1);
+        assert!(matches!(
+            result,
+            Err(NetworkError::RetriesExhausted { attempts: 1, .. })
+        ));
     }
 }
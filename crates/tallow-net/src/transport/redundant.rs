@@ -0,0 +1,521 @@
+//! Redundant peer channels over several relays
+//!
+//! With a single relay, a relay outage mid-transfer ends the transfer. A
+//! [`RedundantChannel`] holds connections to the same peer through two or
+//! more relays. Messages travel over one active path; the others stay
+//! connected as hot standbys. When the active path fails, both peers move to
+//! the next path and resend everything the other side has not acknowledged,
+//! so the transfer continues without the application noticing.
+//!
+//! # Frame format
+//!
+//! ```text
+//! [8-byte BE seq][8-byte BE ack][payload]
+//! ```
+//!
+//! `seq` numbers the sender's messages from zero, and `ack` is how many of
+//! the peer's messages the sender has received. Acknowledged messages are
+//! dropped from the replay buffer; anything with a `seq` already delivered
+//! (a resend after failover) is discarded, so each message, and with it
+//! each chunk, reaches the application exactly once.
+//!
+//! Both peers must wrap their paths in a `RedundantChannel`, in the same
+//! order: a path failure is seen by both ends of it, and each moves to the
+//! first path it still has.
+
+use crate::relay::RelayClient;
+use crate::transport::{CongestionInfo, PeerChannel};
+use crate::{NetworkError, Result};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Bytes of sequence and ack prefixed to every message
+pub const FRAME_HEADER_LEN: usize = 16;
+
+/// Default cap on unacknowledged bytes kept for resending after a failover
+pub const DEFAULT_REPLAY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// How long a standby relay may take to pair once the first one has
+const STANDBY_PEER_WAIT: Duration = Duration::from_secs(10);
+
+/// A peer channel carried over several paths, failing over between them
+pub struct RedundantChannel<C: PeerChannel> {
+    /// Paths in failover order; `None` once a path has failed
+    paths: Vec<Option<C>>,
+    /// Index of the path messages currently use
+    active: usize,
+    /// Sequence number of the next message sent
+    send_seq: u64,
+    /// Sequence number of the next message expected from the peer
+    recv_seq: u64,
+    /// Sent frames the peer has not acknowledged, oldest first
+    replay: VecDeque<(u64, Vec<u8>)>,
+    /// Total length of the frames in `replay`
+    replay_bytes: usize,
+    /// Cap on `replay_bytes`
+    replay_limit: usize,
+    /// Frames before this sequence number may have been dropped from
+    /// `replay` to stay under the cap
+    dropped_until: u64,
+    /// How many of our messages the peer has acknowledged
+    peer_acked: u64,
+    /// Receive buffer with room for the frame header
+    scratch: Vec<u8>,
+}
+
+impl<C: PeerChannel> std::fmt::Debug for RedundantChannel<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedundantChannel")
+            .field("paths", &self.paths.len())
+            .field("live", &self.live_paths())
+            .field("active", &self.active)
+            .field("send_seq", &self.send_seq)
+            .field("recv_seq", &self.recv_seq)
+            .field("unacked", &self.replay.len())
+            .finish()
+    }
+}
+
+impl<C: PeerChannel> RedundantChannel<C> {
+    /// Carry messages over `paths`, using the first until it fails
+    pub fn new(paths: Vec<C>) -> Result<Self> {
+        if paths.is_empty() {
+            return Err(NetworkError::ConnectionFailed(
+                "redundant channel needs at least one path".to_string(),
+            ));
+        }
+        Ok(Self {
+            paths: paths.into_iter().map(Some).collect(),
+            active: 0,
+            send_seq: 0,
+            recv_seq: 0,
+            replay: VecDeque::new(),
+            replay_bytes: 0,
+            replay_limit: DEFAULT_REPLAY_LIMIT,
+            dropped_until: 0,
+            peer_acked: 0,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Cap the unacknowledged bytes kept for resending
+    ///
+    /// Past the cap the oldest frames are dropped, and a failover before
+    /// the peer has acknowledged them fails instead of resuming with a gap.
+    pub fn with_replay_limit(mut self, bytes: usize) -> Self {
+        self.replay_limit = bytes;
+        self
+    }
+
+    /// Paths still connected
+    pub fn live_paths(&self) -> usize {
+        self.paths.iter().filter(|p| p.is_some()).count()
+    }
+
+    /// Index (in the order given to [`new`](Self::new)) of the path in use
+    pub fn active_path(&self) -> usize {
+        self.active
+    }
+
+    /// Messages sent that the peer has not acknowledged yet
+    pub fn unacked(&self) -> usize {
+        self.replay.len()
+    }
+
+    /// Drop frames the peer says it has received
+    fn acknowledge(&mut self, ack: u64) {
+        self.peer_acked = self.peer_acked.max(ack);
+        while let Some((seq, frame)) = self.replay.front() {
+            if *seq >= ack {
+                break;
+            }
+            self.replay_bytes -= frame.len();
+            self.replay.pop_front();
+        }
+    }
+
+    /// Abandon the active path after `err` and resume on the next one
+    ///
+    /// Every unacknowledged frame is resent on the new path; the peer
+    /// discards the ones it already has. Fails with the last path error
+    /// once no path is left.
+    async fn fail_over(&mut self, mut err: NetworkError) -> Result<()> {
+        loop {
+            if let Some(mut dead) = self.paths[self.active].take() {
+                tracing::warn!(
+                    "Redundant path {} ({}) failed: {}",
+                    self.active,
+                    dead.transport_description(),
+                    err
+                );
+                dead.close().await;
+            }
+            let Some(next) = self.paths.iter().position(Option::is_some) else {
+                return Err(err);
+            };
+            if self.peer_acked < self.dropped_until {
+                return Err(NetworkError::ConnectionFailed(format!(
+                    "cannot fail over to path {}: unacknowledged data exceeded the {} byte replay buffer",
+                    next, self.replay_limit
+                )));
+            }
+            self.active = next;
+            let path = self.paths[next]
+                .as_mut()
+                .expect("position() found a live path");
+            tracing::info!(
+                "Failing over to path {} ({}), resending {} message(s)",
+                next,
+                path.transport_description(),
+                self.replay.len()
+            );
+
+            let mut resend = Ok(());
+            for (_, frame) in &self.replay {
+                resend = path.send_message(frame).await;
+                if resend.is_err() {
+                    break;
+                }
+            }
+            match resend {
+                Ok(()) => return Ok(()),
+                Err(e) => err = e,
+            }
+        }
+    }
+}
+
+impl<C: PeerChannel> PeerChannel for RedundantChannel<C> {
+    async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+        let seq = self.send_seq;
+        self.send_seq += 1;
+        let frame = encode_frame(seq, self.recv_seq, data);
+
+        self.replay_bytes += frame.len();
+        self.replay.push_back((seq, frame.clone()));
+        while self.replay_bytes > self.replay_limit && self.replay.len() > 1 {
+            if let Some((old_seq, old)) = self.replay.pop_front() {
+                self.replay_bytes -= old.len();
+                self.dropped_until = old_seq + 1;
+            }
+        }
+
+        let Some(path) = self.paths[self.active].as_mut() else {
+            return Err(NetworkError::ConnectionFailed(
+                "every redundant path has failed".to_string(),
+            ));
+        };
+        match path.send_message(&frame).await {
+            Ok(()) => Ok(()),
+            // The failover resends this frame along with the rest
+            Err(e) => self.fail_over(e).await,
+        }
+    }
+
+    async fn receive_message(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let need = buf.len() + FRAME_HEADER_LEN;
+        if self.scratch.len() < need {
+            self.scratch.resize(need, 0);
+        }
+        loop {
+            let Some(path) = self.paths[self.active].as_mut() else {
+                return Err(NetworkError::ConnectionFailed(
+                    "every redundant path has failed".to_string(),
+                ));
+            };
+            let n = match path.receive_message(&mut self.scratch[..need]).await {
+                Ok(n) => n,
+                Err(e) => {
+                    self.fail_over(e).await?;
+                    continue;
+                }
+            };
+
+            let (seq, ack) = decode_header(&self.scratch[..n])?;
+            self.acknowledge(ack);
+            if seq < self.recv_seq {
+                tracing::trace!("Dropping duplicate message {}", seq);
+                continue;
+            }
+            if seq > self.recv_seq {
+                return Err(NetworkError::ConnectionFailed(format!(
+                    "redundant channel lost messages {}..{}",
+                    self.recv_seq, seq
+                )));
+            }
+            self.recv_seq += 1;
+            let len = n - FRAME_HEADER_LEN;
+            buf[..len].copy_from_slice(&self.scratch[FRAME_HEADER_LEN..n]);
+            return Ok(len);
+        }
+    }
+
+    async fn close(&mut self) {
+        for path in self.paths.iter_mut().flatten() {
+            path.close().await;
+        }
+    }
+
+    fn transport_description(&self) -> String {
+        let paths: Vec<String> = self
+            .paths
+            .iter()
+            .flatten()
+            .map(PeerChannel::transport_description)
+            .collect();
+        format!("redundant [{}]", paths.join(", "))
+    }
+
+    fn congestion(&self) -> Option<CongestionInfo> {
+        self.paths[self.active]
+            .as_ref()
+            .and_then(PeerChannel::congestion)
+    }
+}
+
+/// Join `room_id` on every relay in `relays` and wrap the paired
+/// connections in a [`RedundantChannel`]
+///
+/// The first relay is required and is waited on for as long as the peer
+/// takes to arrive. The rest are standbys: one that can't be reached or
+/// whose peer doesn't show up within a few seconds of the first is left
+/// out. Both peers must pass the relays in the same order, e.g. from
+/// [`RelayDirectory::redundant_relays`](crate::relay::RelayDirectory::redundant_relays).
+pub async fn connect_redundant_relays(
+    relays: &[SocketAddr],
+    room_id: &[u8; 32],
+    password_hash: Option<&[u8; 32]>,
+) -> Result<RedundantChannel<RelayClient>> {
+    let (&primary, standbys) = relays
+        .split_first()
+        .ok_or_else(|| NetworkError::ConnectionFailed("no relays given".to_string()))?;
+
+    let mut client = RelayClient::new(primary);
+    client.connect(room_id, password_hash).await?;
+    client.wait_for_peer().await?;
+    let mut paths = vec![client];
+
+    for &addr in standbys {
+        let mut client = RelayClient::new(addr);
+        let joined = tokio::time::timeout(STANDBY_PEER_WAIT, async {
+            client.connect(room_id, password_hash).await?;
+            client.wait_for_peer().await
+        })
+        .await
+        .unwrap_or(Err(NetworkError::Timeout));
+        match joined {
+            Ok(()) => paths.push(client),
+            Err(e) => {
+                tracing::warn!("Standby relay {} unavailable: {}", addr, e);
+                client.close().await;
+            }
+        }
+    }
+
+    RedundantChannel::new(paths)
+}
+
+fn encode_frame(seq: u64, ack: u64, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + data.len());
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&ack.to_be_bytes());
+    frame.extend_from_slice(data);
+    frame
+}
+
+fn decode_header(frame: &[u8]) -> Result<(u64, u64)> {
+    if frame.len() < FRAME_HEADER_LEN {
+        return Err(NetworkError::ConnectionFailed(format!(
+            "redundant frame too short: {} bytes",
+            frame.len()
+        )));
+    }
+    let seq = u64::from_be_bytes(frame[..8].try_into().expect("8-byte slice"));
+    let ack = u64::from_be_bytes(frame[8..16].try_into().expect("8-byte slice"));
+    Ok((seq, ack))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+    /// One peer's connection to a mock relay
+    struct MockPath {
+        tx: UnboundedSender<Vec<u8>>,
+        rx: UnboundedReceiver<Vec<u8>>,
+    }
+
+    impl PeerChannel for MockPath {
+        async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+            self.tx
+                .send(data.to_vec())
+                .map_err(|_| NetworkError::ConnectionFailed("relay gone".to_string()))
+        }
+        async fn receive_message(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let frame = self
+                .rx
+                .recv()
+                .await
+                .ok_or_else(|| NetworkError::ConnectionFailed("relay gone".to_string()))?;
+            buf[..frame.len()].copy_from_slice(&frame);
+            Ok(frame.len())
+        }
+        async fn close(&mut self) {}
+        fn transport_description(&self) -> String {
+            "mock relay".to_string()
+        }
+    }
+
+    /// A relay forwarding between two peers until its task is aborted,
+    /// which drops whatever it had queued and disconnects both
+    fn mock_relay() -> (MockPath, MockPath, tokio::task::JoinHandle<()>) {
+        let (a_tx, mut relay_from_a) = unbounded_channel::<Vec<u8>>();
+        let (b_tx, mut relay_from_b) = unbounded_channel::<Vec<u8>>();
+        let (relay_to_a, a_rx) = unbounded_channel();
+        let (relay_to_b, b_rx) = unbounded_channel();
+        let relay = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(frame) = relay_from_a.recv() => { let _ = relay_to_b.send(frame); }
+                    Some(frame) = relay_from_b.recv() => { let _ = relay_to_a.send(frame); }
+                    else => return,
+                }
+            }
+        });
+        (
+            MockPath { tx: a_tx, rx: a_rx },
+            MockPath { tx: b_tx, rx: b_rx },
+            relay,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_relay_outage_mid_transfer() {
+        const MESSAGES: u32 = 1_000;
+        let (a0, b0, relay0) = mock_relay();
+        let (a1, b1, _relay1) = mock_relay();
+        let mut sender = RedundantChannel::new(vec![a0, a1]).unwrap();
+        let mut receiver = RedundantChannel::new(vec![b0, b1]).unwrap();
+
+        let receiving = tokio::spawn(async move {
+            let mut relay0 = Some(relay0);
+            let mut buf = vec![0u8; 64];
+            let mut got = Vec::new();
+            while got.len() < MESSAGES as usize {
+                let n = receiver.receive_message(&mut buf).await.unwrap();
+                got.push(u32::from_be_bytes(buf[..n].try_into().unwrap()));
+                if got.len() == 300 {
+                    relay0.take().unwrap().abort();
+                }
+            }
+            receiver.send_message(b"done").await.unwrap();
+            (got, receiver)
+        });
+
+        for i in 0..MESSAGES {
+            sender.send_message(&i.to_be_bytes()).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        let mut buf = vec![0u8; 64];
+        let n = sender.receive_message(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"done");
+
+        let (got, receiver) = receiving.await.unwrap();
+        assert_eq!(got, (0..MESSAGES).collect::<Vec<_>>());
+        assert_eq!(sender.active_path(), 1);
+        assert_eq!(receiver.active_path(), 1);
+        assert_eq!(sender.live_paths(), 1);
+        // The receiver's "done" acknowledged everything
+        assert_eq!(sender.unacked(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_from_both_relays_dropped() {
+        let (mut peer0, b0, relay0) = mock_relay();
+        let (mut peer1, b1, _relay1) = mock_relay();
+        let mut receiver = RedundantChannel::new(vec![b0, b1]).unwrap();
+
+        // Messages 0-2 reach the receiver over relay 0 before it dies; the
+        // peer then resends 1-4 over relay 1
+        for seq in 0..3u64 {
+            peer0
+                .send_message(&encode_frame(seq, 0, &[seq as u8]))
+                .await
+                .unwrap();
+        }
+        for seq in 1..5u64 {
+            peer1
+                .send_message(&encode_frame(seq, 0, &[seq as u8]))
+                .await
+                .unwrap();
+        }
+
+        let mut buf = [0u8; 8];
+        let mut got = Vec::new();
+        for _ in 0..3 {
+            let n = receiver.receive_message(&mut buf).await.unwrap();
+            got.extend_from_slice(&buf[..n]);
+        }
+        relay0.abort();
+        for _ in 0..2 {
+            let n = receiver.receive_message(&mut buf).await.unwrap();
+            got.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(got, vec![0, 1, 2, 3, 4]);
+        assert_eq!(receiver.active_path(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_acks_trim_replay_buffer() {
+        let (a, b, _relay) = mock_relay();
+        let mut left = RedundantChannel::new(vec![a]).unwrap();
+        let mut right = RedundantChannel::new(vec![b]).unwrap();
+        let mut buf = [0u8; 8];
+
+        for i in 0..5u8 {
+            left.send_message(&[i]).await.unwrap();
+        }
+        assert_eq!(left.unacked(), 5);
+        for _ in 0..5 {
+            right.receive_message(&mut buf).await.unwrap();
+        }
+        right.send_message(b"ack").await.unwrap();
+        left.receive_message(&mut buf).await.unwrap();
+        assert_eq!(left.unacked(), 0);
+        assert_eq!(right.unacked(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_past_truncated_replay() {
+        let (a0, _b0, relay0) = mock_relay();
+        let (a1, _b1, _relay1) = mock_relay();
+        let mut sender = RedundantChannel::new(vec![a0, a1])
+            .unwrap()
+            .with_replay_limit(64);
+        for _ in 0..4 {
+            sender.send_message(&[0u8; 32]).await.unwrap();
+        }
+        relay0.abort();
+        relay0_gone(&sender).await;
+        assert!(sender.send_message(&[0u8; 32]).await.is_err());
+    }
+
+    /// Wait until the aborted relay has actually dropped its end
+    async fn relay0_gone(sender: &RedundantChannel<MockPath>) {
+        while let Some(path) = sender.paths[0].as_ref() {
+            if path.tx.is_closed() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn test_empty_and_short_frames_rejected() {
+        assert!(RedundantChannel::<MockPath>::new(Vec::new()).is_err());
+        assert!(decode_header(&[0u8; FRAME_HEADER_LEN - 1]).is_err());
+        assert_eq!(decode_header(&encode_frame(7, 3, b"x")).unwrap(), (7, 3));
+    }
+}
@@ -3,6 +3,7 @@
 //! Fallback transport for networks where QUIC is blocked.
 //! Uses tokio_rustls for TLS over TCP with the same framing as QUIC.
 
+use super::tls_config::TlsPolicy;
 use crate::{NetworkError, Result, Transport};
 use std::net::SocketAddr;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -13,12 +14,15 @@ use tokio_rustls::client::TlsStream;
 pub struct TcpTlsTransport {
     /// TLS-wrapped TCP stream
     stream: Option<TlsStream<TcpStream>>,
+    /// Versions and cipher suites the handshake may negotiate
+    policy: TlsPolicy,
 }
 
 impl std::fmt::Debug for TcpTlsTransport {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TcpTlsTransport")
             .field("connected", &self.stream.is_some())
+            .field("min_version", &self.policy.min_version)
             .finish()
     }
 }
@@ -26,7 +30,15 @@ impl std::fmt::Debug for TcpTlsTransport {
 impl TcpTlsTransport {
     /// Create a new TCP+TLS transport
     pub fn new() -> Self {
-        Self { stream: None }
+        Self::with_tls_policy(TlsPolicy::default())
+    }
+
+    /// Create a TCP+TLS transport restricted to `policy`
+    pub fn with_tls_policy(policy: TlsPolicy) -> Self {
+        Self {
+            stream: None,
+            policy,
+        }
     }
 
     /// Close the transport gracefully
@@ -50,7 +62,7 @@ impl Transport for TcpTlsTransport {
             .await
             .map_err(|e| NetworkError::ConnectionFailed(format!("TCP connect failed: {}", e)))?;
 
-        let tls_config = super::tls_config::rustls_client_config()?;
+        let tls_config = super::tls_config::rustls_client_config(&self.policy)?;
         let connector = tokio_rustls::TlsConnector::from(tls_config);
 
         let server_name = rustls::pki_types::ServerName::try_from("localhost")
@@ -60,7 +72,7 @@ impl Transport for TcpTlsTransport {
         let tls_stream = connector
             .connect(server_name, tcp_stream)
             .await
-            .map_err(|e| NetworkError::TlsError(format!("TLS handshake failed: {}", e)))?;
+            .map_err(|e| self.policy.handshake_error("TLS handshake failed", e))?;
 
         self.stream = Some(tls_stream);
         Ok(())
@@ -124,3 +136,79 @@ impl Transport for TcpTlsTransport {
         Ok(len)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tls_config::{generate_self_signed, TlsMinVersion};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    /// Accept one TLS connection offering only `versions`; returns the
+    /// negotiated version, or `None` if the handshake failed
+    async fn serve_once(
+        versions: &'static [&'static rustls::SupportedProtocolVersion],
+    ) -> (
+        SocketAddr,
+        tokio::task::JoinHandle<Option<rustls::ProtocolVersion>>,
+    ) {
+        let identity = generate_self_signed().unwrap();
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_protocol_versions(versions)
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![identity.cert_der], identity.key_der.into())
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let tls = acceptor.accept(tcp).await.ok()?;
+            tls.get_ref().1.protocol_version()
+        });
+        (addr, server)
+    }
+
+    #[tokio::test]
+    async fn test_strict_policy_rejects_tls12_only_peer() {
+        let (addr, server) = serve_once(&[&rustls::version::TLS12]).await;
+
+        let mut transport = TcpTlsTransport::new();
+        let err = transport.connect(addr).await.unwrap_err().to_string();
+        assert!(
+            err.contains("peer does not support the required TLS policy (TLS 1.3 minimum"),
+            "{err}"
+        );
+        assert!(server.await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_relaxed_policy_accepts_tls12_peer() {
+        let (addr, server) = serve_once(&[&rustls::version::TLS12]).await;
+
+        let mut transport = TcpTlsTransport::with_tls_policy(TlsPolicy {
+            min_version: TlsMinVersion::Tls12,
+            cipher_suites: Vec::new(),
+        });
+        transport.connect(addr).await.unwrap();
+        assert_eq!(
+            server.await.unwrap(),
+            Some(rustls::ProtocolVersion::TLSv1_2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_policy_negotiates_tls13() {
+        let (addr, server) = serve_once(rustls::ALL_VERSIONS).await;
+
+        let mut transport = TcpTlsTransport::new();
+        transport.connect(addr).await.unwrap();
+        assert_eq!(
+            server.await.unwrap(),
+            Some(rustls::ProtocolVersion::TLSv1_3)
+        );
+    }
+}
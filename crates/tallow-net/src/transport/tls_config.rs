@@ -7,6 +7,160 @@
 use crate::{NetworkError, Result};
 use std::sync::Arc;
 
+/// Lowest TLS version the TCP+TLS fallback will negotiate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsMinVersion {
+    /// Allow TLS 1.2 and 1.3
+    Tls12,
+    /// TLS 1.3 only
+    #[default]
+    Tls13,
+}
+
+impl std::str::FromStr for TlsMinVersion {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s
+            .trim()
+            .to_ascii_lowercase()
+            .trim_start_matches("tls")
+            .trim()
+        {
+            "1.2" | "12" => Ok(Self::Tls12),
+            "1.3" | "13" => Ok(Self::Tls13),
+            other => Err(NetworkError::TlsError(format!(
+                "unsupported minimum TLS version '{}' (expected 1.2 or 1.3)",
+                other
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for TlsMinVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tls12 => write!(f, "TLS 1.2"),
+            Self::Tls13 => write!(f, "TLS 1.3"),
+        }
+    }
+}
+
+/// Protocol versions and cipher suites allowed on TCP+TLS connections
+///
+/// The default is TLS 1.3 with every suite the provider offers. QUIC is
+/// unaffected: it always runs TLS 1.3.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsPolicy {
+    /// Lowest version negotiated
+    pub min_version: TlsMinVersion,
+    /// Allowed cipher suite names, e.g. `TLS13_AES_256_GCM_SHA384`
+    /// (empty = all suites of the allowed versions)
+    pub cipher_suites: Vec<String>,
+}
+
+impl TlsPolicy {
+    /// Build a policy from configuration strings
+    ///
+    /// Fails on an unknown version or suite name, or when the suites left
+    /// cover none of the allowed versions.
+    pub fn from_config(min_version: &str, cipher_suites: &[String]) -> Result<Self> {
+        let policy = Self {
+            min_version: min_version.parse()?,
+            cipher_suites: cipher_suites.to_vec(),
+        };
+        policy.provider()?;
+        Ok(policy)
+    }
+
+    /// Protocol versions enabled by this policy
+    pub fn versions(&self) -> &'static [&'static rustls::SupportedProtocolVersion] {
+        match self.min_version {
+            TlsMinVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+            TlsMinVersion::Tls13 => &[&rustls::version::TLS13],
+        }
+    }
+
+    /// Crypto provider restricted to the allowed cipher suites
+    pub fn provider(&self) -> Result<rustls::crypto::CryptoProvider> {
+        let mut provider = rustls::crypto::aws_lc_rs::default_provider();
+        let versions = self.versions();
+        provider.cipher_suites.retain(|suite| {
+            versions
+                .iter()
+                .any(|v| v.version == suite.version().version)
+        });
+
+        if !self.cipher_suites.is_empty() {
+            let wanted: Vec<String> = self
+                .cipher_suites
+                .iter()
+                .map(|name| name.trim().to_ascii_uppercase())
+                .collect();
+            let known: Vec<String> = rustls::crypto::aws_lc_rs::default_provider()
+                .cipher_suites
+                .iter()
+                .map(suite_name)
+                .collect();
+            if let Some(unknown) = wanted.iter().find(|name| !known.contains(name)) {
+                return Err(NetworkError::TlsError(format!(
+                    "unknown cipher suite '{}'",
+                    unknown
+                )));
+            }
+            provider
+                .cipher_suites
+                .retain(|suite| wanted.contains(&suite_name(suite)));
+        }
+
+        if provider.cipher_suites.is_empty() {
+            return Err(NetworkError::TlsError(format!(
+                "no allowed cipher suite supports {} or later",
+                self.min_version
+            )));
+        }
+        Ok(provider)
+    }
+
+    /// Turn a failed handshake into an error that names the policy when
+    /// the peer could not meet it
+    pub fn handshake_error(&self, context: &str, err: std::io::Error) -> NetworkError {
+        let incompatible = err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+            .is_some_and(|e| {
+                matches!(
+                    e,
+                    rustls::Error::PeerIncompatible(_)
+                        | rustls::Error::AlertReceived(
+                            rustls::AlertDescription::ProtocolVersion
+                                | rustls::AlertDescription::HandshakeFailure
+                                | rustls::AlertDescription::InsufficientSecurity
+                        )
+                )
+            });
+        if incompatible {
+            NetworkError::TlsError(format!(
+                "{}: peer does not support the required TLS policy ({} minimum{}): {}",
+                context,
+                self.min_version,
+                if self.cipher_suites.is_empty() {
+                    String::new()
+                } else {
+                    format!(", suites {}", self.cipher_suites.join(", "))
+                },
+                err
+            ))
+        } else {
+            NetworkError::TlsError(format!("{}: {}", context, err))
+        }
+    }
+}
+
+fn suite_name(suite: &rustls::SupportedCipherSuite) -> String {
+    format!("{:?}", suite.suite())
+}
+
 /// Generated TLS certificate and key pair
 pub struct TlsIdentity {
     /// DER-encoded certificate
@@ -33,7 +187,7 @@ pub fn generate_self_signed() -> Result<TlsIdentity> {
 /// Build a quinn ServerConfig from a TLS identity
 ///
 /// Configures a 5-minute idle timeout to keep connections alive while
-/// peers wait for each other in relay rooms.
+/// peers wait for each other in relay rooms, and path MTU discovery.
 #[cfg(feature = "quic")]
 pub fn quinn_server_config(identity: &TlsIdentity) -> Result<quinn::ServerConfig> {
     let mut transport_config = quinn::TransportConfig::default();
@@ -42,6 +196,7 @@ pub fn quinn_server_config(identity: &TlsIdentity) -> Result<quinn::ServerConfig
             .try_into()
             .expect("300s fits in IdleTimeout"),
     ));
+    super::mtu::MtuConfig::default().apply(&mut transport_config);
 
     let mut server_config = quinn::ServerConfig::with_single_cert(
         vec![identity.cert_der.clone()],
@@ -61,7 +216,7 @@ pub fn quinn_server_config(identity: &TlsIdentity) -> Result<quinn::ServerConfig
 /// encryption against passive observers only.
 ///
 /// Configures a 5-minute idle timeout and 15-second keep-alive interval
-/// so connections survive while waiting for peers.
+/// so connections survive while waiting for peers, and path MTU discovery.
 #[cfg(feature = "quic")]
 pub fn quinn_client_config() -> Result<quinn::ClientConfig> {
     let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
@@ -79,6 +234,7 @@ pub fn quinn_client_config() -> Result<quinn::ClientConfig> {
             .expect("300s fits in IdleTimeout"),
     ));
     transport_config.keep_alive_interval(Some(std::time::Duration::from_secs(15)));
+    super::mtu::MtuConfig::default().apply(&mut transport_config);
 
     let mut client_config = quinn::ClientConfig::new(Arc::new(
         quinn::crypto::rustls::QuicClientConfig::try_from(crypto)
@@ -91,10 +247,13 @@ pub fn quinn_client_config() -> Result<quinn::ClientConfig> {
 }
 
 /// Build a rustls ServerConfig from a TLS identity (for TCP+TLS)
-pub fn rustls_server_config(identity: &TlsIdentity) -> Result<Arc<rustls::ServerConfig>> {
-    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+pub fn rustls_server_config(
+    identity: &TlsIdentity,
+    policy: &TlsPolicy,
+) -> Result<Arc<rustls::ServerConfig>> {
+    let provider = Arc::new(policy.provider()?);
     let config = rustls::ServerConfig::builder_with_provider(provider)
-        .with_safe_default_protocol_versions()
+        .with_protocol_versions(policy.versions())
         .map_err(|e| NetworkError::TlsError(format!("TLS protocol versions: {}", e)))?
         .with_no_client_auth()
         .with_single_cert(
@@ -107,10 +266,10 @@ pub fn rustls_server_config(identity: &TlsIdentity) -> Result<Arc<rustls::Server
 }
 
 /// Build a rustls ClientConfig that accepts any server certificate (for TCP+TLS)
-pub fn rustls_client_config() -> Result<Arc<rustls::ClientConfig>> {
-    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+pub fn rustls_client_config(policy: &TlsPolicy) -> Result<Arc<rustls::ClientConfig>> {
+    let provider = Arc::new(policy.provider()?);
     let config = rustls::ClientConfig::builder_with_provider(provider)
-        .with_safe_default_protocol_versions()
+        .with_protocol_versions(policy.versions())
         .map_err(|e| NetworkError::TlsError(format!("TLS protocol versions: {}", e)))?
         .dangerous()
         .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
@@ -194,4 +353,49 @@ mod tests {
         let config = quinn_client_config();
         assert!(config.is_ok());
     }
+
+    #[test]
+    fn test_default_policy_is_tls13_only() {
+        let policy = TlsPolicy::default();
+        assert_eq!(policy.min_version, TlsMinVersion::Tls13);
+        assert_eq!(policy.versions().len(), 1);
+        assert_eq!(
+            policy.versions()[0].version,
+            rustls::ProtocolVersion::TLSv1_3
+        );
+
+        let provider = policy.provider().unwrap();
+        assert!(!provider.cipher_suites.is_empty());
+        assert!(provider
+            .cipher_suites
+            .iter()
+            .all(|s| s.version().version == rustls::ProtocolVersion::TLSv1_3));
+
+        let identity = generate_self_signed().unwrap();
+        assert!(rustls_server_config(&identity, &policy).is_ok());
+        assert!(rustls_client_config(&policy).is_ok());
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let relaxed = TlsPolicy::from_config("1.2", &[]).unwrap();
+        assert_eq!(relaxed.min_version, TlsMinVersion::Tls12);
+        assert_eq!(relaxed.versions().len(), 2);
+
+        let suites = vec!["tls13_aes_256_gcm_sha384".to_string()];
+        let narrow = TlsPolicy::from_config("TLS1.3", &suites).unwrap();
+        let provider = narrow.provider().unwrap();
+        assert_eq!(provider.cipher_suites.len(), 1);
+        assert_eq!(
+            suite_name(&provider.cipher_suites[0]),
+            "TLS13_AES_256_GCM_SHA384"
+        );
+
+        assert!(TlsPolicy::from_config("1.1", &[]).is_err());
+        assert!(TlsPolicy::from_config("1.3", &["TLS_NULL_WITH_NULL".to_string()]).is_err());
+        // Only TLS 1.2 suites left under a TLS 1.3 floor
+        let tls12_only = vec!["TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384".to_string()];
+        let err = TlsPolicy::from_config("1.3", &tls12_only).unwrap_err();
+        assert!(err.to_string().contains("TLS 1.3"), "{err}");
+    }
 }
@@ -15,6 +15,30 @@ pub struct ChatMessage {
     pub timestamp: u64,
     /// Encrypted flag
     pub encrypted: bool,
+    /// Per-session sequence number assigned by the sender, starting at 1
+    ///
+    /// Zero for messages that did not come from a [`ChatSession`](super::ChatSession).
+    #[serde(default)]
+    pub seq: u64,
+    /// Set on a marker standing in for messages that can no longer be replayed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gap: Option<SeqGap>,
+}
+
+/// A run of sequence numbers the sender no longer holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeqGap {
+    /// First missing sequence number
+    pub first: u64,
+    /// Last missing sequence number (inclusive)
+    pub last: u64,
+}
+
+impl SeqGap {
+    /// Number of messages lost
+    pub fn count(&self) -> u64 {
+        self.last - self.first + 1
+    }
 }
 
 impl ChatMessage {
@@ -29,6 +53,8 @@ impl ChatMessage {
                 .unwrap_or_default()
                 .as_secs(),
             encrypted: false,
+            seq: 0,
+            gap: None,
         }
     }
 
@@ -45,8 +71,23 @@ impl ChatMessage {
                 .unwrap_or_default()
                 .as_secs(),
             encrypted: true,
+            seq: 0,
+            gap: None,
         }
     }
+
+    /// Create a marker telling the peer that `gap` will not be replayed
+    pub fn gap(sender: String, gap: SeqGap) -> Self {
+        let mut msg = Self::new(sender, String::new());
+        msg.seq = gap.last;
+        msg.gap = Some(gap);
+        msg
+    }
+
+    /// Whether this is a gap marker rather than a real message
+    pub fn is_gap(&self) -> bool {
+        self.gap.is_some()
+    }
 }
 
 /// Generate a random message ID (hex-encoded 16 random bytes)
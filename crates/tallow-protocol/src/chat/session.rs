@@ -4,12 +4,33 @@
 //! message passing architecture. Optionally encrypts/decrypts messages
 //! using a Triple Ratchet (Double Ratchet + Sparse PQ Ratchet) for
 //! post-quantum forward secrecy.
+//!
+//! Every encrypted message carries a key-confirmation tag derived from the
+//! sender's chain state. The receiver checks it against its own chain before
+//! decrypting, so a dropped ratchet step surfaces as
+//! [`ProtocolError::RatchetDesync`] instead of an opaque decryption failure.
+//!
+//! Outgoing messages are numbered per session. After a reconnect, both
+//! sides re-key with [`ChatSession::enable_encryption`], the receiver
+//! reports its [`last_received_seq`](ChatSession::last_received_seq), and
+//! the sender calls [`replay_since`](ChatSession::replay_since) to resend
+//! what was missed under the new ratchet.
 
+use super::message::SeqGap;
 use super::ChatMessage;
 use crate::{ProtocolError, Result};
+use std::collections::VecDeque;
+use subtle::ConstantTimeEq;
+use tallow_crypto::hash::domain;
 use tallow_crypto::ratchet::TripleRatchet;
 use tokio::sync::mpsc;
 
+/// Length of the key-confirmation tag prefixed to each ciphertext
+const CONFIRMATION_TAG_LEN: usize = 32;
+
+/// Default number of sent messages kept for replay after a reconnect
+pub const DEFAULT_REPLAY_RETENTION: usize = 256;
+
 /// Chat session with a peer
 pub struct ChatSession {
     /// Session ID
@@ -26,6 +47,16 @@ pub struct ChatSession {
     inbound_rx: mpsc::Receiver<ChatMessage>,
     /// Optional Triple Ratchet for end-to-end encryption
     ratchet: Option<TripleRatchet>,
+    /// Whether this side initiated the session (selects confirmation domains)
+    is_initiator: bool,
+    /// Sequence number for the next outbound message
+    next_seq: u64,
+    /// Highest sequence number received from the peer
+    last_received_seq: u64,
+    /// Plaintext of the most recent outbound messages, for replay
+    replay: VecDeque<ChatMessage>,
+    /// Maximum length of `replay`
+    replay_retention: usize,
 }
 
 impl std::fmt::Debug for ChatSession {
@@ -36,6 +67,8 @@ impl std::fmt::Debug for ChatSession {
             .field("local_id", &self.local_id)
             .field("messages", &self.messages)
             .field("encrypted", &self.ratchet.is_some())
+            .field("next_seq", &self.next_seq)
+            .field("last_received_seq", &self.last_received_seq)
             .finish_non_exhaustive()
     }
 }
@@ -60,6 +93,11 @@ impl ChatSession {
             outbound_tx,
             inbound_rx,
             ratchet: None,
+            is_initiator: false,
+            next_seq: 1,
+            last_received_seq: 0,
+            replay: VecDeque::new(),
+            replay_retention: DEFAULT_REPLAY_RETENTION,
         };
 
         (session, outbound_rx, inbound_tx)
@@ -70,11 +108,18 @@ impl ChatSession {
         self.local_id = id;
     }
 
+    /// Set how many sent messages are kept for [`replay_since`](Self::replay_since)
+    pub fn set_replay_retention(&mut self, messages: usize) {
+        self.replay_retention = messages;
+        self.trim_replay();
+    }
+
     /// Enable end-to-end encryption with a shared secret.
     ///
     /// Initializes the Triple Ratchet (Double Ratchet + Sparse PQ Ratchet)
     /// for post-quantum forward secrecy. The `is_initiator` flag determines
-    /// which side of the ratchet this session takes.
+    /// which side of the ratchet this session takes. Calling it again
+    /// (after a reconnect) replaces the ratchet; sequence numbers carry on.
     ///
     /// # Arguments
     /// * `shared_secret` - 32-byte shared secret from key exchange
@@ -86,6 +131,22 @@ impl ChatSession {
             TripleRatchet::init_responder(shared_secret, 10)
         };
         self.ratchet = Some(ratchet);
+        self.is_initiator = is_initiator;
+    }
+
+    /// Confirmation domains as (outbound, inbound) for this side
+    fn confirmation_domains(&self) -> (&'static str, &'static str) {
+        if self.is_initiator {
+            (
+                domain::DOMAIN_KEY_CONFIRM_SENDER,
+                domain::DOMAIN_KEY_CONFIRM_RECEIVER,
+            )
+        } else {
+            (
+                domain::DOMAIN_KEY_CONFIRM_RECEIVER,
+                domain::DOMAIN_KEY_CONFIRM_SENDER,
+            )
+        }
     }
 
     /// Check whether encryption is enabled for this session
@@ -96,32 +157,18 @@ impl ChatSession {
     /// Send a message to the peer.
     ///
     /// If encryption is enabled, the message text is encrypted via the
-    /// Triple Ratchet and sent as a hex-encoded ciphertext. Otherwise,
-    /// the message is sent as plaintext.
+    /// Triple Ratchet and sent as a hex-encoded `tag || ciphertext`, where
+    /// `tag` confirms the sender's chain state. Otherwise, the message is
+    /// sent as plaintext.
     pub async fn send(&mut self, text: String) -> Result<()> {
-        let msg = if let Some(ref mut ratchet) = self.ratchet {
-            // Encrypt the message text
-            let ciphertext = ratchet.encrypt_message(text.as_bytes()).map_err(|e| {
-                ProtocolError::TransferFailed(format!("Chat encrypt failed: {}", e))
-            })?;
-            ratchet.step().map_err(|e| {
-                ProtocolError::TransferFailed(format!("Ratchet step failed: {}", e))
-            })?;
-            // Hex-encode the ciphertext for wire transport
-            let hex_ct =
-                ciphertext
-                    .iter()
-                    .fold(String::with_capacity(ciphertext.len() * 2), |mut s, b| {
-                        use std::fmt::Write;
-                        let _ = write!(s, "{b:02x}");
-                        s
-                    });
-            ChatMessage::new_encrypted(self.local_id.clone(), hex_ct)
-        } else {
-            ChatMessage::new(self.local_id.clone(), text)
-        };
+        let mut plain = ChatMessage::new(self.local_id.clone(), text);
+        plain.seq = self.next_seq;
+        self.next_seq += 1;
 
+        let msg = self.seal(&plain)?;
         self.messages.push(msg.clone());
+        self.replay.push_back(plain);
+        self.trim_replay();
 
         self.outbound_tx
             .send(msg)
@@ -131,18 +178,118 @@ impl ChatSession {
         Ok(())
     }
 
+    /// Resend every message after `seq` that the peer missed
+    ///
+    /// `seq` is the peer's [`last_received_seq`](Self::last_received_seq).
+    /// Messages are re-encrypted under the current ratchet and keep their
+    /// original ID, timestamp and sequence number. If some of them have
+    /// already fallen out of the replay window, a single gap marker (see
+    /// [`ChatMessage::gap`]) is sent in their place first.
+    ///
+    /// Returns the number of messages replayed, not counting a gap marker.
+    pub async fn replay_since(&mut self, seq: u64) -> Result<usize> {
+        let last_sent = self.next_seq - 1;
+        if seq >= last_sent {
+            return Ok(0);
+        }
+
+        let oldest_kept = self.replay.front().map_or(self.next_seq, |m| m.seq);
+        let mut outgoing = Vec::new();
+        if oldest_kept > seq + 1 {
+            let gap = SeqGap {
+                first: seq + 1,
+                last: oldest_kept - 1,
+            };
+            outgoing.push(ChatMessage::gap(self.local_id.clone(), gap));
+        }
+        let missed: Vec<ChatMessage> = self
+            .replay
+            .iter()
+            .filter(|m| m.seq > seq)
+            .cloned()
+            .collect();
+        for plain in &missed {
+            outgoing.push(self.seal(plain)?);
+        }
+
+        for msg in outgoing {
+            self.outbound_tx
+                .send(msg)
+                .await
+                .map_err(|e| ProtocolError::TransferFailed(format!("Chat send failed: {}", e)))?;
+        }
+        Ok(missed.len())
+    }
+
+    /// Encrypt `plain` for the wire if encryption is enabled
+    fn seal(&mut self, plain: &ChatMessage) -> Result<ChatMessage> {
+        let (send_domain, _) = self.confirmation_domains();
+        let Some(ref mut ratchet) = self.ratchet else {
+            return Ok(plain.clone());
+        };
+
+        // Confirmation tag for the chain step this message is encrypted under
+        let mut ciphertext = ratchet.send_confirmation(send_domain).to_vec();
+
+        // Encrypt the message text
+        let encrypted = ratchet
+            .encrypt_message(plain.text.as_bytes())
+            .map_err(|e| ProtocolError::TransferFailed(format!("Chat encrypt failed: {}", e)))?;
+        ciphertext.extend_from_slice(&encrypted);
+        ratchet
+            .step()
+            .map_err(|e| ProtocolError::TransferFailed(format!("Ratchet step failed: {}", e)))?;
+        // Hex-encode the ciphertext for wire transport
+        let hex_ct =
+            ciphertext
+                .iter()
+                .fold(String::with_capacity(ciphertext.len() * 2), |mut s, b| {
+                    use std::fmt::Write;
+                    let _ = write!(s, "{b:02x}");
+                    s
+                });
+
+        let mut msg = ChatMessage::new_encrypted(plain.sender.clone(), hex_ct);
+        msg.id = plain.id.clone();
+        msg.timestamp = plain.timestamp;
+        msg.seq = plain.seq;
+        Ok(msg)
+    }
+
+    fn trim_replay(&mut self) {
+        while self.replay.len() > self.replay_retention {
+            self.replay.pop_front();
+        }
+    }
+
     /// Receive a message from the peer.
     ///
     /// If encryption is enabled and the incoming message is marked encrypted,
-    /// the hex-encoded ciphertext is decoded and decrypted via the Triple
-    /// Ratchet before being returned as plaintext.
+    /// the hex-encoded ciphertext is decoded, its confirmation tag checked,
+    /// and decrypted via the Triple Ratchet before being returned as plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::RatchetDesync`] if the confirmation tag does
+    /// not match this side's receive chain. The ratchet is left untouched so
+    /// the caller can prompt for a re-handshake.
+    ///
+    /// A gap marker from [`replay_since`](Self::replay_since) is returned
+    /// as-is for the caller to display, and is not added to the history.
     pub async fn receive(&mut self) -> Result<ChatMessage> {
+        let (_, recv_domain) = self.confirmation_domains();
         let mut msg = self
             .inbound_rx
             .recv()
             .await
             .ok_or_else(|| ProtocolError::TransferFailed("Chat channel closed".to_string()))?;
 
+        if msg.is_gap() {
+            self.last_received_seq = self.last_received_seq.max(msg.seq);
+            msg.text.clear();
+            return Ok(msg);
+        }
+
         if msg.encrypted {
             if let Some(ref mut ratchet) = self.ratchet {
                 // Validate hex string length before decoding
@@ -165,7 +312,20 @@ impl ChatSession {
                     })
                     .collect::<std::result::Result<Vec<u8>, _>>()?;
 
-                let plaintext = ratchet.decrypt_message(&ct_bytes).map_err(|e| {
+                if ct_bytes.len() < CONFIRMATION_TAG_LEN {
+                    return Err(ProtocolError::TransferFailed(
+                        "Invalid ciphertext: missing confirmation tag".to_string(),
+                    ));
+                }
+                let (tag, ct_bytes) = ct_bytes.split_at(CONFIRMATION_TAG_LEN);
+
+                // Constant-time check that both chains are at the same step
+                let expected = ratchet.recv_confirmation(recv_domain);
+                if !bool::from(expected.as_slice().ct_eq(tag)) {
+                    return Err(ProtocolError::RatchetDesync);
+                }
+
+                let plaintext = ratchet.decrypt_message(ct_bytes).map_err(|e| {
                     ProtocolError::TransferFailed(format!("Chat decrypt failed: {}", e))
                 })?;
                 ratchet.step().map_err(|e| {
@@ -187,10 +347,18 @@ impl ChatSession {
         // to strip ANSI escape sequences and control characters
         msg.text = crate::transfer::sanitize::sanitize_display(&msg.text);
 
+        self.last_received_seq = self.last_received_seq.max(msg.seq);
         self.messages.push(msg.clone());
         Ok(msg)
     }
 
+    /// Highest sequence number received from the peer, or 0 if none
+    ///
+    /// Send this to the peer after reconnecting so it can replay the rest.
+    pub fn last_received_seq(&self) -> u64 {
+        self.last_received_seq
+    }
+
     /// Get message history
     pub fn messages(&self) -> &[ChatMessage] {
         &self.messages
@@ -311,6 +479,171 @@ mod tests {
         assert_eq!(receiver.message_count(), 3);
     }
 
+    #[tokio::test]
+    async fn test_encrypted_messages_confirm_in_both_directions() {
+        let shared_secret = [7u8; 32];
+
+        let (mut alice, mut alice_rx, alice_tx) =
+            ChatSession::new("s1".to_string(), "bob".to_string());
+        alice.enable_encryption(&shared_secret, true);
+
+        let (mut bob, mut bob_rx, bob_tx) = ChatSession::new("s2".to_string(), "alice".to_string());
+        bob.enable_encryption(&shared_secret, false);
+
+        alice.send("ping".to_string()).await.unwrap();
+        bob_tx.send(alice_rx.recv().await.unwrap()).await.unwrap();
+        assert_eq!(bob.receive().await.unwrap().text, "ping");
+
+        bob.send("pong".to_string()).await.unwrap();
+        alice_tx.send(bob_rx.recv().await.unwrap()).await.unwrap();
+        assert_eq!(alice.receive().await.unwrap().text, "pong");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_ratchet_step_reports_desync() {
+        let shared_secret = [11u8; 32];
+
+        let (mut sender, mut sender_rx, _sender_tx) =
+            ChatSession::new("s1".to_string(), "p1".to_string());
+        sender.enable_encryption(&shared_secret, true);
+
+        let (mut receiver, _receiver_rx, receiver_tx) =
+            ChatSession::new("s2".to_string(), "p2".to_string());
+        receiver.enable_encryption(&shared_secret, false);
+
+        // First message is lost in transit
+        sender.send("lost".to_string()).await.unwrap();
+        let _dropped = sender_rx.recv().await.unwrap();
+
+        sender.send("arrives".to_string()).await.unwrap();
+        receiver_tx
+            .send(sender_rx.recv().await.unwrap())
+            .await
+            .unwrap();
+
+        let err = receiver.receive().await.unwrap_err();
+        assert!(matches!(err, ProtocolError::RatchetDesync));
+        assert_eq!(receiver.message_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reflected_message_reports_desync() {
+        let shared_secret = [13u8; 32];
+
+        let (mut alice, mut alice_rx, alice_tx) =
+            ChatSession::new("s1".to_string(), "bob".to_string());
+        alice.enable_encryption(&shared_secret, true);
+
+        // Alice's own message echoed back to her must not confirm
+        alice.send("echo".to_string()).await.unwrap();
+        alice_tx.send(alice_rx.recv().await.unwrap()).await.unwrap();
+
+        let err = alice.receive().await.unwrap_err();
+        assert!(matches!(err, ProtocolError::RatchetDesync));
+    }
+
+    /// Initiator and responder sessions wired up for encryption
+    fn encrypted_pair(
+        secret: &[u8; 32],
+    ) -> (
+        ChatSession,
+        mpsc::Receiver<ChatMessage>,
+        ChatSession,
+        mpsc::Sender<ChatMessage>,
+    ) {
+        let (mut sender, sender_rx, _) = ChatSession::new("s1".to_string(), "p1".to_string());
+        sender.enable_encryption(secret, true);
+        let (mut receiver, _, receiver_tx) = ChatSession::new("s2".to_string(), "p2".to_string());
+        receiver.enable_encryption(secret, false);
+        (sender, sender_rx, receiver, receiver_tx)
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_missed_messages() {
+        let (mut sender, mut sender_rx, mut receiver, receiver_tx) = encrypted_pair(&[21u8; 32]);
+
+        for text in ["one", "two"] {
+            sender.send(text.to_string()).await.unwrap();
+            receiver_tx
+                .send(sender_rx.recv().await.unwrap())
+                .await
+                .unwrap();
+            receiver.receive().await.unwrap();
+        }
+        assert_eq!(receiver.last_received_seq(), 2);
+
+        // The connection drops while three more messages are in flight
+        for text in ["three", "four", "five"] {
+            sender.send(text.to_string()).await.unwrap();
+            let _lost = sender_rx.recv().await.unwrap();
+        }
+
+        // Reconnect with a fresh key, then replay from the receiver's position
+        let secret = [22u8; 32];
+        sender.enable_encryption(&secret, true);
+        receiver.enable_encryption(&secret, false);
+        let replayed = sender
+            .replay_since(receiver.last_received_seq())
+            .await
+            .unwrap();
+        assert_eq!(replayed, 3);
+
+        let mut received = Vec::new();
+        while let Ok(msg) = sender_rx.try_recv() {
+            receiver_tx.send(msg).await.unwrap();
+            let msg = receiver.receive().await.unwrap();
+            received.push((msg.seq, msg.text));
+        }
+        assert_eq!(
+            received,
+            vec![
+                (3, "three".to_string()),
+                (4, "four".to_string()),
+                (5, "five".to_string()),
+            ]
+        );
+        assert_eq!(receiver.last_received_seq(), 5);
+
+        // Already caught up: nothing more to send
+        assert_eq!(sender.replay_since(5).await.unwrap(), 0);
+        assert!(sender_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_beyond_retention_sends_gap() {
+        let (mut sender, mut sender_rx, mut receiver, receiver_tx) = encrypted_pair(&[23u8; 32]);
+        sender.set_replay_retention(2);
+
+        for i in 1..=5 {
+            sender.send(format!("msg{}", i)).await.unwrap();
+            let _lost = sender_rx.recv().await.unwrap();
+        }
+
+        let secret = [24u8; 32];
+        sender.enable_encryption(&secret, true);
+        receiver.enable_encryption(&secret, false);
+        assert_eq!(sender.replay_since(0).await.unwrap(), 2);
+
+        receiver_tx
+            .send(sender_rx.recv().await.unwrap())
+            .await
+            .unwrap();
+        let marker = receiver.receive().await.unwrap();
+        assert_eq!(marker.gap, Some(SeqGap { first: 1, last: 3 }));
+        assert_eq!(marker.gap.unwrap().count(), 3);
+        assert_eq!(receiver.last_received_seq(), 3);
+
+        for expected in ["msg4", "msg5"] {
+            receiver_tx
+                .send(sender_rx.recv().await.unwrap())
+                .await
+                .unwrap();
+            assert_eq!(receiver.receive().await.unwrap().text, expected);
+        }
+        // Gap markers are not chat history
+        assert_eq!(receiver.message_count(), 2);
+    }
+
     #[tokio::test]
     async fn test_unencrypted_session_unchanged() {
         // Verify that sessions without encryption still work identically
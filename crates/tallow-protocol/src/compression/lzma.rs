@@ -1,7 +1,36 @@
 //! LZMA/XZ compression (highest ratio)
+//!
+//! [`compress`] produces a single raw LZMA stream. [`LzmaCompressor`] can
+//! instead split large inputs into independently compressed blocks and
+//! compress them on several threads, trading a little ratio for speed.
+//! [`decompress`] accepts both layouts.
+//!
+//! Multi-block layout:
+//! `[magic(4)][version(1)][block_count: u32 LE]`
+//! `[raw_len: u64 LE][packed_len: u64 LE]` per block, then the packed blocks.
 
+use crate::compression::Compressor;
 use crate::{ProtocolError, Result};
 
+/// Default LZMA preset
+const DEFAULT_PRESET: u32 = 6;
+
+/// Default uncompressed size of each block in multi-block mode (4 MiB)
+pub const DEFAULT_BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Marks a multi-block stream. A raw LZMA stream starts with its
+/// properties byte, which is always below 225, so 0xFF is unambiguous.
+const MULTI_BLOCK_MAGIC: [u8; 4] = [0xFF, b'T', b'L', b'Z'];
+
+/// Multi-block layout version
+const MULTI_BLOCK_VERSION: u8 = 1;
+
+/// Size of the fixed multi-block header (magic + version + block count)
+const MULTI_BLOCK_HEADER_LEN: usize = 4 + 1 + 4;
+
+/// Size of each per-block index entry (raw length + packed length)
+const BLOCK_ENTRY_LEN: usize = 16;
+
 /// Compress data with LZMA
 ///
 /// # Arguments
@@ -24,10 +53,194 @@ pub fn compress(data: &[u8], _preset: u32) -> Result<Vec<u8>> {
 /// Maximum decompressed output size (256 MiB) to prevent decompression bombs
 const MAX_DECOMPRESS_SIZE: usize = 256 * 1024 * 1024;
 
-/// Decompress LZMA data
+/// Decompress LZMA data (single stream or multi-block)
 ///
 /// Limits output to [`MAX_DECOMPRESS_SIZE`] to prevent decompression bombs.
 pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    if data.starts_with(&MULTI_BLOCK_MAGIC) {
+        decompress_blocks(data)
+    } else {
+        decompress_single(data)
+    }
+}
+
+/// Number of independently compressed blocks in `data`
+///
+/// A plain single-stream payload counts as one block.
+pub fn block_count(data: &[u8]) -> Result<usize> {
+    if data.starts_with(&MULTI_BLOCK_MAGIC) {
+        Ok(parse_block_index(data)?.0.len())
+    } else {
+        Ok(1)
+    }
+}
+
+/// LZMA compressor with optional multithreaded block mode
+///
+/// With one thread (the default), or when the input fits in a single
+/// block, output is a plain LZMA stream identical to [`compress`], so any
+/// peer can decode it. Otherwise the input is split into blocks of
+/// `block_size` bytes that are compressed in parallel; only peers that
+/// understand the multi-block layout can decode that output.
+#[derive(Debug, Clone)]
+pub struct LzmaCompressor {
+    preset: u32,
+    threads: usize,
+    block_size: usize,
+}
+
+impl Default for LzmaCompressor {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRESET)
+    }
+}
+
+impl LzmaCompressor {
+    /// Create a single-threaded compressor with the given preset (0-9)
+    pub fn new(preset: u32) -> Self {
+        Self {
+            preset,
+            threads: 1,
+            block_size: DEFAULT_BLOCK_SIZE,
+        }
+    }
+
+    /// Compress with up to `threads` worker threads (minimum 1)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Set the uncompressed size of each block (minimum 1 byte)
+    ///
+    /// Smaller blocks parallelise better but compress worse.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Number of worker threads
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+}
+
+impl Compressor for LzmaCompressor {
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.threads <= 1 || data.len() <= self.block_size {
+            return compress(data, self.preset);
+        }
+        compress_blocks(data, self.preset, self.block_size, self.threads)
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        decompress(data)
+    }
+}
+
+/// Compress `data` as independent blocks spread over `threads` workers
+fn compress_blocks(data: &[u8], preset: u32, block_size: usize, threads: usize) -> Result<Vec<u8>> {
+    let blocks: Vec<&[u8]> = data.chunks(block_size).collect();
+    // Contiguous runs of blocks per worker keep the output in order
+    let per_worker = blocks.len().div_ceil(threads);
+
+    let packed: Vec<Vec<u8>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = blocks
+            .chunks(per_worker)
+            .map(|run| {
+                scope.spawn(move || {
+                    run.iter()
+                        .map(|block| compress(block, preset))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+
+        let mut packed = Vec::with_capacity(blocks.len());
+        for worker in workers {
+            let run = worker.join().map_err(|_| {
+                ProtocolError::CompressionError("lzma worker thread panicked".to_string())
+            })??;
+            packed.extend(run);
+        }
+        Ok::<_, ProtocolError>(packed)
+    })?;
+
+    let body_len: usize = packed.iter().map(Vec::len).sum();
+    let mut output =
+        Vec::with_capacity(MULTI_BLOCK_HEADER_LEN + BLOCK_ENTRY_LEN * blocks.len() + body_len);
+    output.extend_from_slice(&MULTI_BLOCK_MAGIC);
+    output.push(MULTI_BLOCK_VERSION);
+    output.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+    for (raw, block) in blocks.iter().zip(&packed) {
+        output.extend_from_slice(&(raw.len() as u64).to_le_bytes());
+        output.extend_from_slice(&(block.len() as u64).to_le_bytes());
+    }
+    for block in &packed {
+        output.extend_from_slice(block);
+    }
+    Ok(output)
+}
+
+/// Parse the multi-block index into `(raw_len, packed_len)` entries and the
+/// offset where block data begins
+fn parse_block_index(data: &[u8]) -> Result<(Vec<(usize, usize)>, usize)> {
+    let corrupt = |what: &str| ProtocolError::CompressionError(format!("lzma: {}", what));
+
+    if data.len() < MULTI_BLOCK_HEADER_LEN {
+        return Err(corrupt("truncated multi-block header"));
+    }
+    if data[4] != MULTI_BLOCK_VERSION {
+        return Err(corrupt("unsupported multi-block version"));
+    }
+    let count = u32::from_le_bytes([data[5], data[6], data[7], data[8]]) as usize;
+    let index_end = count
+        .checked_mul(BLOCK_ENTRY_LEN)
+        .and_then(|n| n.checked_add(MULTI_BLOCK_HEADER_LEN))
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| corrupt("truncated block index"))?;
+
+    let mut entries = Vec::with_capacity(count);
+    let mut total_raw: u64 = 0;
+    let mut total_packed: u64 = 0;
+    for entry in data[MULTI_BLOCK_HEADER_LEN..index_end].chunks_exact(BLOCK_ENTRY_LEN) {
+        let raw = u64::from_le_bytes(entry[..8].try_into().unwrap_or_default());
+        let packed = u64::from_le_bytes(entry[8..].try_into().unwrap_or_default());
+        total_raw = total_raw.saturating_add(raw);
+        total_packed = total_packed.saturating_add(packed);
+        if total_raw > MAX_DECOMPRESS_SIZE as u64 {
+            return Err(ProtocolError::CompressionError(format!(
+                "decompressed size exceeds limit of {} bytes",
+                MAX_DECOMPRESS_SIZE
+            )));
+        }
+        entries.push((raw as usize, packed as usize));
+    }
+    if total_packed != (data.len() - index_end) as u64 {
+        return Err(corrupt("block lengths do not match stream size"));
+    }
+    Ok((entries, index_end))
+}
+
+fn decompress_blocks(data: &[u8]) -> Result<Vec<u8>> {
+    let (entries, mut offset) = parse_block_index(data)?;
+    let total: usize = entries.iter().map(|(raw, _)| raw).sum();
+
+    let mut output = Vec::with_capacity(total);
+    for (raw, packed) in entries {
+        let block = decompress_single(&data[offset..offset + packed])?;
+        if block.len() != raw {
+            return Err(ProtocolError::CompressionError(
+                "lzma: block size does not match index".to_string(),
+            ));
+        }
+        output.extend_from_slice(&block);
+        offset += packed;
+    }
+    Ok(output)
+}
+
+fn decompress_single(data: &[u8]) -> Result<Vec<u8>> {
     let mut output = Vec::new();
     lzma_rs::lzma_decompress(&mut &data[..], &mut output)
         .map_err(|e| ProtocolError::CompressionError(format!("lzma decompress failed: {}", e)))?;
@@ -52,4 +265,62 @@ mod tests {
         let decompressed = decompress(&compressed).unwrap();
         assert_eq!(&decompressed, data);
     }
+
+    /// Compressible but not trivially repetitive test data
+    fn sample(len: usize) -> Vec<u8> {
+        let words: [&[u8]; 5] = [b"tallow ", b"transfer ", b"chunk ", b"relay ", b"peer "];
+        let mut out = Vec::with_capacity(len + 16);
+        let mut state: u32 = 0x1234_5678;
+        while out.len() < len {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            out.extend_from_slice(words[(state >> 24) as usize % words.len()]);
+        }
+        out.truncate(len);
+        out
+    }
+
+    #[test]
+    fn test_multithreaded_roundtrip_uses_blocks() {
+        let data = sample(600_000);
+        let compressor = LzmaCompressor::default()
+            .with_threads(4)
+            .with_block_size(100_000);
+
+        let compressed = compressor.compress(&data).unwrap();
+        assert!(compressed.starts_with(&MULTI_BLOCK_MAGIC));
+        assert_eq!(block_count(&compressed).unwrap(), 6);
+        assert!(compressed.len() < data.len());
+
+        assert_eq!(compressor.decompress(&compressed).unwrap(), data);
+        assert_eq!(decompress(&compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn test_small_input_stays_single_stream() {
+        let data = sample(10_000);
+        let compressor = LzmaCompressor::default().with_threads(4);
+
+        let compressed = compressor.compress(&data).unwrap();
+        assert_eq!(block_count(&compressed).unwrap(), 1);
+        assert_eq!(compressed, compress(&data, 6).unwrap());
+        assert_eq!(decompress(&compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn test_corrupt_block_index_rejected() {
+        let data = sample(50_000);
+        let compressed = LzmaCompressor::default()
+            .with_threads(2)
+            .with_block_size(20_000)
+            .compress(&data)
+            .unwrap();
+
+        assert!(decompress(&compressed[..compressed.len() - 1]).is_err());
+        assert!(decompress(&compressed[..MULTI_BLOCK_HEADER_LEN + 3]).is_err());
+
+        // Claim a block decodes to more bytes than it does
+        let mut tampered = compressed.clone();
+        tampered[MULTI_BLOCK_HEADER_LEN] ^= 0x01;
+        assert!(decompress(&tampered).is_err());
+    }
 }
@@ -5,8 +5,11 @@ pub mod brotli;
 pub mod lz4;
 pub mod lzma;
 pub mod pipeline;
+pub mod skiplist;
 pub mod zstd;
 
+pub use skiplist::SkipList;
+
 use crate::Result;
 
 /// Compression algorithms
@@ -1,10 +1,22 @@
 //! Compression pipeline with automatic algorithm selection
 //!
-//! Analyzes the first 64KB of data to determine compressibility,
-//! then selects the best algorithm based on content type and size.
+//! Known-incompressible file types are recognized by extension or magic
+//! bytes ([`SkipList`]); anything else has its first 64KB sampled to
+//! determine compressibility.
+//!
+//! Upfront sampling can be wrong for mixed content, so
+//! [`AdaptiveCompressor`] also watches the ratio achieved on each chunk and
+//! stops compressing once the data turns incompressible. Each adaptive chunk
+//! carries a one-byte tag saying whether its payload is compressed.
+//!
+//! When the peer allows it, the compressor can instead pick an algorithm for
+//! every chunk from its entropy ([`choose_chunk_algorithm`]); the tag then
+//! names that algorithm, so the receiver never consults the transfer-wide
+//! setting for those frames.
 
-use super::{analysis, CompressionAlgorithm};
-use crate::Result;
+use super::{analysis, CompressionAlgorithm, SkipList};
+use crate::{ProtocolError, Result};
+use std::path::Path;
 
 /// Compression pipeline
 #[derive(Debug)]
@@ -40,13 +52,288 @@ impl CompressionPipeline {
     }
 }
 
+/// Chunk frame tag: payload is stored as-is
+const FRAME_STORED: u8 = 0;
+
+/// Chunk frame tag: payload is compressed with the transfer's algorithm
+const FRAME_COMPRESSED: u8 = 1;
+
+/// Chunk frame tag: payload is zstd, whatever the transfer's algorithm
+const FRAME_ZSTD: u8 = 2;
+
+/// Chunk frame tag: payload is LZ4
+const FRAME_LZ4: u8 = 3;
+
+/// Chunk frame tag: payload is Brotli
+const FRAME_BROTLI: u8 = 4;
+
+/// Chunk frame tag: payload is LZMA
+const FRAME_LZMA: u8 = 5;
+
+/// Entropy (bits per byte) above which a chunk is sent uncompressed
+const CHUNK_STORE_ENTROPY: f64 = 7.5;
+
+/// Entropy above which the faster LZ4 is preferred over zstd
+const CHUNK_FAST_ENTROPY: f64 = 6.0;
+
+/// Compressed/original size ratio above which a chunk counts as incompressible
+pub const DEFAULT_POOR_RATIO: f64 = 0.95;
+
+/// Consecutive incompressible chunks before compression is switched off
+pub const DEFAULT_POOR_CHUNK_LIMIT: u32 = 4;
+
+/// Per-chunk compressor that gives up on incompressible data
+///
+/// Chunks are compressed with the configured algorithm until
+/// [`DEFAULT_POOR_CHUNK_LIMIT`] consecutive chunks fail to reach
+/// [`DEFAULT_POOR_RATIO`]; every later chunk is stored uncompressed.
+/// Output is framed (see [`decompress_chunk`]), so the receiver needs no
+/// knowledge of when the switch happened.
+#[derive(Debug, Clone)]
+pub struct AdaptiveCompressor {
+    algorithm: CompressionAlgorithm,
+    poor_ratio: f64,
+    poor_limit: u32,
+    poor_streak: u32,
+    chunks_seen: u64,
+    disabled_at: Option<u64>,
+    /// Algorithms to pick from per chunk; empty keeps the single algorithm
+    per_chunk: Vec<CompressionAlgorithm>,
+}
+
+impl AdaptiveCompressor {
+    /// Create an adaptive compressor with the default thresholds
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            poor_ratio: DEFAULT_POOR_RATIO,
+            poor_limit: DEFAULT_POOR_CHUNK_LIMIT,
+            poor_streak: 0,
+            chunks_seen: 0,
+            disabled_at: None,
+            per_chunk: Vec::new(),
+        }
+    }
+
+    /// Set the incompressible ratio and how many poor chunks in a row disable compression
+    ///
+    /// A `poor_limit` of zero is treated as one.
+    pub fn with_thresholds(mut self, poor_ratio: f64, poor_limit: u32) -> Self {
+        self.poor_ratio = poor_ratio;
+        self.poor_limit = poor_limit.max(1);
+        self
+    }
+
+    /// Choose each chunk's algorithm from `allowed` instead of using one throughout
+    ///
+    /// Frames then name their algorithm (see [`choose_chunk_algorithm`]) and
+    /// compression is never switched off, since a later chunk may compress
+    /// well again. Only use this once [`FeatureSet::PER_CHUNK_ALGORITHM`] is
+    /// negotiated. An empty `allowed` keeps the single-algorithm behaviour.
+    ///
+    /// [`FeatureSet::PER_CHUNK_ALGORITHM`]: crate::wire::FeatureSet::PER_CHUNK_ALGORITHM
+    pub fn with_chunk_choice(mut self, allowed: &[CompressionAlgorithm]) -> Self {
+        self.per_chunk = allowed
+            .iter()
+            .copied()
+            .filter(|a| *a != CompressionAlgorithm::None)
+            .collect();
+        self
+    }
+
+    /// Whether each chunk's algorithm is chosen separately
+    pub fn chooses_per_chunk(&self) -> bool {
+        !self.per_chunk.is_empty()
+    }
+
+    /// The configured algorithm
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.algorithm
+    }
+
+    /// Whether compression has been switched off for the rest of the stream
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some() || self.algorithm == CompressionAlgorithm::None
+    }
+
+    /// Number of chunks compressed before compression was switched off
+    pub fn disabled_at(&self) -> Option<u64> {
+        self.disabled_at
+    }
+
+    /// Frame one chunk uncompressed, leaving the ratio tracking alone
+    ///
+    /// For files the [`SkipList`] already knows won't compress.
+    pub fn store_chunk(&self, data: &[u8]) -> Vec<u8> {
+        frame(FRAME_STORED, data)
+    }
+
+    /// Compress one chunk into a tagged frame
+    ///
+    /// Chunks that did not shrink are stored as-is even before the switch,
+    /// so a frame is never larger than the input plus the tag byte.
+    pub fn compress_chunk(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.chooses_per_chunk() {
+            return self.compress_chosen(data);
+        }
+        if self.is_disabled() {
+            return Ok(frame(FRAME_STORED, data));
+        }
+
+        let compressed = compress(data, self.algorithm)?;
+        self.chunks_seen += 1;
+
+        let poor = compressed.len() as f64 > data.len() as f64 * self.poor_ratio;
+        if poor && !data.is_empty() {
+            self.poor_streak += 1;
+            if self.poor_streak >= self.poor_limit {
+                tracing::debug!(
+                    "Disabling {:?} compression after {} incompressible chunks",
+                    self.algorithm,
+                    self.poor_streak
+                );
+                self.disabled_at = Some(self.chunks_seen);
+            }
+        } else {
+            self.poor_streak = 0;
+        }
+
+        if compressed.len() < data.len() {
+            Ok(frame(FRAME_COMPRESSED, &compressed))
+        } else {
+            Ok(frame(FRAME_STORED, data))
+        }
+    }
+
+    /// Compress one chunk with the algorithm its entropy calls for
+    fn compress_chosen(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        self.chunks_seen += 1;
+        let algorithm = choose_chunk_algorithm(data, &self.per_chunk);
+        let Some(tag) = algorithm_tag(algorithm) else {
+            return Ok(frame(FRAME_STORED, data));
+        };
+        let compressed = compress(data, algorithm)?;
+        if compressed.len() < data.len() {
+            Ok(frame(tag, &compressed))
+        } else {
+            Ok(frame(FRAME_STORED, data))
+        }
+    }
+}
+
+/// Pick an algorithm for one chunk from `allowed`, by byte entropy
+///
+/// - Near-random (≥ 7.5 bits/byte) → None
+/// - Moderately compressible (≥ 6.0) → LZ4, whose speed matters more than
+///   the few percent zstd would gain
+/// - Otherwise → zstd
+///
+/// Falls back to whichever of the two is allowed, then to None.
+pub fn choose_chunk_algorithm(
+    data: &[u8],
+    allowed: &[CompressionAlgorithm],
+) -> CompressionAlgorithm {
+    let entropy = analysis::shannon_entropy(data);
+    let preference: &[CompressionAlgorithm] = if entropy >= CHUNK_STORE_ENTROPY {
+        &[]
+    } else if entropy >= CHUNK_FAST_ENTROPY {
+        &[CompressionAlgorithm::Lz4, CompressionAlgorithm::Zstd]
+    } else {
+        &[CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4]
+    };
+    preference
+        .iter()
+        .copied()
+        .find(|a| allowed.contains(a))
+        .unwrap_or(CompressionAlgorithm::None)
+}
+
+/// Frame tag naming `algorithm` explicitly, or `None` for no compression
+fn algorithm_tag(algorithm: CompressionAlgorithm) -> Option<u8> {
+    match algorithm {
+        CompressionAlgorithm::Zstd => Some(FRAME_ZSTD),
+        CompressionAlgorithm::Lz4 => Some(FRAME_LZ4),
+        CompressionAlgorithm::Brotli => Some(FRAME_BROTLI),
+        CompressionAlgorithm::Lzma => Some(FRAME_LZMA),
+        CompressionAlgorithm::None => None,
+    }
+}
+
+/// Algorithm a frame tag names, if it names one
+fn tag_algorithm(tag: u8) -> Option<CompressionAlgorithm> {
+    match tag {
+        FRAME_ZSTD => Some(CompressionAlgorithm::Zstd),
+        FRAME_LZ4 => Some(CompressionAlgorithm::Lz4),
+        FRAME_BROTLI => Some(CompressionAlgorithm::Brotli),
+        FRAME_LZMA => Some(CompressionAlgorithm::Lzma),
+        _ => None,
+    }
+}
+
+/// Algorithm recorded in a chunk frame's tag
+///
+/// `Some(CompressionAlgorithm::None)` for stored frames, `None` for frames
+/// compressed with the transfer's algorithm or an unknown tag.
+pub fn chunk_frame_algorithm(framed: &[u8]) -> Option<CompressionAlgorithm> {
+    match framed.first() {
+        Some(&FRAME_STORED) => Some(CompressionAlgorithm::None),
+        Some(&tag) => tag_algorithm(tag),
+        None => None,
+    }
+}
+
+/// Prefix `payload` with a frame tag
+fn frame(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 1);
+    out.push(tag);
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Decode a chunk produced by [`AdaptiveCompressor::compress_chunk`]
+///
+/// Only frames tagged as compressed are passed through `algorithm`; frames
+/// naming their own algorithm are decoded with that one.
+pub fn decompress_chunk(framed: &[u8], algorithm: CompressionAlgorithm) -> Result<Vec<u8>> {
+    match framed.split_first() {
+        Some((&FRAME_STORED, payload)) => Ok(payload.to_vec()),
+        Some((&FRAME_COMPRESSED, payload)) => decompress(payload, algorithm),
+        Some((&tag, payload)) => match tag_algorithm(tag) {
+            Some(named) => decompress(payload, named),
+            None => Err(ProtocolError::CompressionError(format!(
+                "unknown chunk frame tag {}",
+                tag
+            ))),
+        },
+        None => Err(ProtocolError::CompressionError(
+            "empty chunk frame".to_string(),
+        )),
+    }
+}
+
 /// Select the best compression algorithm based on data analysis
 ///
-/// Examines the first 64KB to determine:
-/// - If data is already compressed (high entropy) → None
-/// - File type from magic bytes → algorithm choice
-/// - Otherwise → Zstd (best general-purpose default)
+/// Same as [`select_algorithm_for`] with no file name and the default
+/// [`SkipList`].
 pub fn select_algorithm(data: &[u8]) -> CompressionAlgorithm {
+    select_algorithm_for(None, data, &SkipList::default())
+}
+
+/// Select a compression algorithm for `data`, read from `path` if known
+///
+/// - Type in `skiplist` by extension or magic bytes → None, unsampled
+/// - Already compressed (high entropy in the first 64KB) → None
+/// - Otherwise → Zstd (best general-purpose default)
+pub fn select_algorithm_for(
+    path: Option<&Path>,
+    data: &[u8],
+    skiplist: &SkipList,
+) -> CompressionAlgorithm {
+    if let Some(kind) = skiplist.check(path, data) {
+        tracing::trace!("Not compressing {} data", kind);
+        return CompressionAlgorithm::None;
+    }
+
     let sample = if data.len() > 65536 {
         &data[..65536]
     } else {
@@ -58,11 +345,6 @@ pub fn select_algorithm(data: &[u8]) -> CompressionAlgorithm {
         return CompressionAlgorithm::None;
     }
 
-    // Skip compression for known-compressed file types
-    if let Some("zip" | "gzip" | "png" | "jpeg") = analysis::detect_file_type(sample) {
-        return CompressionAlgorithm::None;
-    }
-
     // Default: Zstd (best speed/ratio tradeoff)
     CompressionAlgorithm::Zstd
 }
@@ -108,6 +390,49 @@ mod tests {
         assert_eq!(pipeline.algorithm(), CompressionAlgorithm::None);
     }
 
+    #[test]
+    fn test_skiplist_decides_before_sampling() {
+        let list = SkipList::default();
+        // Compressible content, so only the skip-list can say None
+        let text = "frame ".repeat(4000).into_bytes();
+        for name in ["movie.mp4", "photo.png", "bundle.zip"] {
+            assert_eq!(
+                select_algorithm_for(Some(Path::new(name)), &text, &list),
+                CompressionAlgorithm::None,
+                "{}",
+                name
+            );
+        }
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&text);
+        assert_eq!(
+            select_algorithm_for(None, &png, &list),
+            CompressionAlgorithm::None
+        );
+
+        // Unknown types fall through to sampling
+        assert_eq!(
+            select_algorithm_for(Some(Path::new("notes.txt")), &text, &list),
+            CompressionAlgorithm::Zstd
+        );
+        let random: Vec<u8> = (0..65536).map(|_| rand::random::<u8>()).collect();
+        assert_eq!(
+            select_algorithm_for(Some(Path::new("blob.bin")), &random, &list),
+            CompressionAlgorithm::None
+        );
+
+        // Config can take a type off the list
+        let list = list.with_overrides(&["log".to_string()], &["zip".to_string()]);
+        assert_eq!(
+            select_algorithm_for(Some(Path::new("bundle.zip")), &text, &list),
+            CompressionAlgorithm::Zstd
+        );
+        assert_eq!(
+            select_algorithm_for(Some(Path::new("app.log")), &text, &list),
+            CompressionAlgorithm::None
+        );
+    }
+
     #[test]
     fn test_pipeline_roundtrip() {
         let data = b"compressible data repeated compressible data repeated";
@@ -123,4 +448,134 @@ mod tests {
             assert_eq!(&decompressed, data, "roundtrip failed for {:?}", algo);
         }
     }
+
+    /// Highly compressible chunks followed by random ones
+    fn mixed_chunks() -> Vec<Vec<u8>> {
+        let mut chunks: Vec<Vec<u8>> = (0..3)
+            .map(|i| format!("header line {} ", i).repeat(2000).into_bytes())
+            .collect();
+        chunks.extend((0..8).map(|_| (0..16_384).map(|_| rand::random::<u8>()).collect()));
+        chunks
+    }
+
+    #[test]
+    fn test_adaptive_stops_compressing_after_threshold() {
+        let mut compressor = AdaptiveCompressor::new(CompressionAlgorithm::Zstd);
+        let frames: Vec<Vec<u8>> = mixed_chunks()
+            .iter()
+            .map(|c| compressor.compress_chunk(c).unwrap())
+            .collect();
+
+        // 3 text chunks, then DEFAULT_POOR_CHUNK_LIMIT random chunks tried
+        assert!(compressor.is_disabled());
+        assert_eq!(
+            compressor.disabled_at(),
+            Some(3 + DEFAULT_POOR_CHUNK_LIMIT as u64)
+        );
+        assert!(frames[..3].iter().all(|f| f[0] == FRAME_COMPRESSED));
+        assert!(frames[3..].iter().all(|f| f[0] == FRAME_STORED));
+
+        // A compressible chunk after the switch stays uncompressed
+        let late = compressor.compress_chunk(&[0u8; 4096]).unwrap();
+        assert_eq!(late[0], FRAME_STORED);
+        assert_eq!(late.len(), 4097);
+    }
+
+    #[test]
+    fn test_adaptive_poor_streak_resets() {
+        let mut compressor =
+            AdaptiveCompressor::new(CompressionAlgorithm::Lz4).with_thresholds(0.95, 2);
+        let random: Vec<u8> = (0..8192).map(|_| rand::random::<u8>()).collect();
+        let text = "abc".repeat(3000).into_bytes();
+
+        compressor.compress_chunk(&random).unwrap();
+        compressor.compress_chunk(&text).unwrap();
+        compressor.compress_chunk(&random).unwrap();
+        assert!(!compressor.is_disabled());
+        compressor.compress_chunk(&random).unwrap();
+        assert!(compressor.is_disabled());
+    }
+
+    #[test]
+    fn test_adaptive_mixed_stream_roundtrip() {
+        for algo in [
+            CompressionAlgorithm::Zstd,
+            CompressionAlgorithm::Brotli,
+            CompressionAlgorithm::None,
+        ] {
+            let chunks = mixed_chunks();
+            let mut compressor = AdaptiveCompressor::new(algo);
+            for chunk in &chunks {
+                let framed = compressor.compress_chunk(chunk).unwrap();
+                assert_eq!(&decompress_chunk(&framed, algo).unwrap(), chunk);
+            }
+        }
+    }
+
+    #[test]
+    fn test_per_chunk_algorithm_alternating_stream() {
+        let text = "per-chunk header line ".repeat(800).into_bytes();
+        let random: Vec<u8> = (0..16_384).map(|_| rand::random::<u8>()).collect();
+        let chunks: Vec<&[u8]> = (0..6)
+            .map(|i| if i % 2 == 0 { &text[..] } else { &random[..] })
+            .collect();
+
+        let mut compressor = AdaptiveCompressor::new(CompressionAlgorithm::Zstd)
+            .with_chunk_choice(&[CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4]);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let framed = compressor.compress_chunk(chunk).unwrap();
+            let expected = if i % 2 == 0 {
+                CompressionAlgorithm::Zstd
+            } else {
+                CompressionAlgorithm::None
+            };
+            assert_eq!(
+                chunk_frame_algorithm(&framed),
+                Some(expected),
+                "chunk {}",
+                i
+            );
+            // The transfer algorithm is irrelevant to self-describing frames
+            let plain = decompress_chunk(&framed, CompressionAlgorithm::Brotli).unwrap();
+            assert_eq!(&plain[..], *chunk, "chunk {}", i);
+        }
+        // Random chunks never switch compression off in this mode
+        assert!(!compressor.is_disabled());
+    }
+
+    #[test]
+    fn test_choose_chunk_algorithm() {
+        let both = [CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4];
+        let text = "aaaaaaaabbbb".repeat(1000).into_bytes();
+        // Uniform over 100 byte values: ~6.6 bits/byte
+        let moderate: Vec<u8> = (0..100u8).cycle().take(10_000).collect();
+        let random: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+
+        assert_eq!(
+            choose_chunk_algorithm(&text, &both),
+            CompressionAlgorithm::Zstd
+        );
+        assert_eq!(
+            choose_chunk_algorithm(&moderate, &both),
+            CompressionAlgorithm::Lz4
+        );
+        assert_eq!(
+            choose_chunk_algorithm(&random, &both),
+            CompressionAlgorithm::None
+        );
+        assert_eq!(
+            choose_chunk_algorithm(&text, &[CompressionAlgorithm::Lz4]),
+            CompressionAlgorithm::Lz4
+        );
+        assert_eq!(
+            choose_chunk_algorithm(&text, &[]),
+            CompressionAlgorithm::None
+        );
+    }
+
+    #[test]
+    fn test_decompress_chunk_rejects_bad_frames() {
+        assert!(decompress_chunk(&[], CompressionAlgorithm::Zstd).is_err());
+        assert!(decompress_chunk(&[7, 1, 2], CompressionAlgorithm::Zstd).is_err());
+    }
 }
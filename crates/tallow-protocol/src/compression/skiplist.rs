@@ -0,0 +1,208 @@
+//! File types not worth compressing
+//!
+//! Archives, images, audio and video are already compressed; running them
+//! through zstd burns CPU for a ratio of ~1.0. [`SkipList`] recognizes
+//! them up front from the file extension or the magic bytes at the start
+//! of the file, before any content is sampled.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// An already-compressed format
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SkipType {
+    /// Name used in logs and to remove the whole type
+    name: String,
+    /// Lowercase extensions, without the dot
+    extensions: BTreeSet<String>,
+    /// Signature bytes and the offset they appear at
+    magic: Option<(usize, &'static [u8])>,
+}
+
+/// Built-in types: name, extensions, and magic signature
+const DEFAULT_TYPES: &[(&str, &[&str], Option<(usize, &[u8])>)] = &[
+    (
+        "zip",
+        &["zip", "jar", "apk", "docx", "xlsx", "pptx", "odt", "epub"],
+        Some((0, b"PK\x03\x04")),
+    ),
+    ("gzip", &["gz", "tgz"], Some((0, b"\x1f\x8b"))),
+    ("bzip2", &["bz2", "tbz2"], Some((0, b"BZh"))),
+    ("xz", &["xz", "txz"], Some((0, b"\xfd7zXZ\x00"))),
+    ("zstd", &["zst"], Some((0, b"\x28\xb5\x2f\xfd"))),
+    ("7z", &["7z"], Some((0, b"7z\xbc\xaf\x27\x1c"))),
+    ("rar", &["rar"], Some((0, b"Rar!\x1a\x07"))),
+    ("png", &["png"], Some((0, b"\x89PNG"))),
+    ("jpeg", &["jpg", "jpeg"], Some((0, b"\xff\xd8\xff"))),
+    ("gif", &["gif"], Some((0, b"GIF8"))),
+    ("webp", &["webp"], Some((8, b"WEBP"))),
+    (
+        "mp4",
+        &["mp4", "m4v", "m4a", "mov", "heic", "avif"],
+        Some((4, b"ftyp")),
+    ),
+    ("matroska", &["mkv", "webm"], Some((0, b"\x1a\x45\xdf\xa3"))),
+    ("mp3", &["mp3"], Some((0, b"ID3"))),
+    ("ogg", &["ogg", "opus"], Some((0, b"OggS"))),
+    ("flac", &["flac"], Some((0, b"fLaC"))),
+    ("woff2", &["woff2"], Some((0, b"wOF2"))),
+];
+
+/// Bytes of a file's start needed to check every built-in signature
+pub const MAGIC_HEADER_LEN: usize = 16;
+
+/// Name of the type user-added extensions are grouped under
+const CUSTOM_TYPE: &str = "custom";
+
+/// Known-incompressible file types, by extension and magic bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipList {
+    types: Vec<SkipType>,
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        Self {
+            types: DEFAULT_TYPES
+                .iter()
+                .map(|&(name, extensions, magic)| SkipType {
+                    name: name.to_string(),
+                    extensions: extensions.iter().map(|e| e.to_string()).collect(),
+                    magic,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SkipList {
+    /// A list that skips nothing
+    pub fn empty() -> Self {
+        Self { types: Vec::new() }
+    }
+
+    /// Apply config overrides to this list
+    ///
+    /// `add` holds extensions to skip as well. Each `remove` entry is a
+    /// type name ("zip" drops every zip-based extension and the zip
+    /// signature) or, failing that, a single extension ("docx"). A leading
+    /// dot and letter case are ignored.
+    pub fn with_overrides(mut self, add: &[String], remove: &[String]) -> Self {
+        for entry in remove {
+            let entry = normalize(entry);
+            let before = self.types.len();
+            self.types.retain(|t| t.name != entry);
+            if self.types.len() == before {
+                for t in &mut self.types {
+                    t.extensions.remove(&entry);
+                }
+            }
+        }
+
+        let added: BTreeSet<String> = add
+            .iter()
+            .map(|e| normalize(e))
+            .filter(|e| !e.is_empty())
+            .collect();
+        if !added.is_empty() {
+            match self.types.iter_mut().find(|t| t.name == CUSTOM_TYPE) {
+                Some(custom) => custom.extensions.extend(added),
+                None => self.types.push(SkipType {
+                    name: CUSTOM_TYPE.to_string(),
+                    extensions: added,
+                    magic: None,
+                }),
+            }
+        }
+        self
+    }
+
+    /// Type whose extension `path` has, if any
+    pub fn match_extension(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.types
+            .iter()
+            .find(|t| t.extensions.contains(&ext))
+            .map(|t| t.name.as_str())
+    }
+
+    /// Type whose signature `header` (the start of a file) carries, if any
+    pub fn match_magic(&self, header: &[u8]) -> Option<&str> {
+        self.types
+            .iter()
+            .find(|t| {
+                t.magic.is_some_and(|(offset, sig)| {
+                    header.get(offset..offset + sig.len()) == Some(sig)
+                })
+            })
+            .map(|t| t.name.as_str())
+    }
+
+    /// Type that makes the file not worth compressing, by extension first
+    ///
+    /// `None` means the list doesn't know and the content should be
+    /// sampled.
+    pub fn check(&self, path: Option<&Path>, header: &[u8]) -> Option<&str> {
+        path.and_then(|p| self.match_extension(p))
+            .or_else(|| self.match_magic(header))
+    }
+}
+
+fn normalize(entry: &str) -> String {
+    entry.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_extensions_and_magic() {
+        let list = SkipList::default();
+        assert_eq!(list.match_extension(Path::new("clip.MP4")), Some("mp4"));
+        assert_eq!(
+            list.match_extension(Path::new("a/b/photo.png")),
+            Some("png")
+        );
+        assert_eq!(list.match_extension(Path::new("notes.txt")), None);
+        assert_eq!(list.match_extension(Path::new("Makefile")), None);
+
+        assert_eq!(list.match_magic(b"\x89PNG\r\n\x1a\n...."), Some("png"));
+        assert_eq!(list.match_magic(b"\0\0\0\x20ftypisom"), Some("mp4"));
+        assert_eq!(list.match_magic(b"RIFF\0\0\0\0WEBPVP8 "), Some("webp"));
+        assert_eq!(list.match_magic(b"PK\x03\x04"), Some("zip"));
+        assert_eq!(list.match_magic(b"plain text"), None);
+        assert_eq!(list.match_magic(b"PK"), None);
+
+        // A renamed archive is still caught by its signature
+        assert_eq!(
+            list.check(Some(Path::new("backup.dat")), b"\x1f\x8b\x08\0"),
+            Some("gzip")
+        );
+        assert_eq!(list.check(Some(Path::new("main.rs")), b"fn main"), None);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let list = SkipList::default().with_overrides(
+            &[".Parquet".to_string(), "npz".to_string()],
+            &["zip".to_string(), "mov".to_string()],
+        );
+        assert_eq!(list.match_extension(Path::new("t.parquet")), Some("custom"));
+        assert_eq!(list.match_extension(Path::new("a.npz")), Some("custom"));
+
+        // Removing a type drops its extensions and its signature
+        assert_eq!(list.match_extension(Path::new("a.docx")), None);
+        assert_eq!(list.match_magic(b"PK\x03\x04"), None);
+
+        // Removing an extension leaves the rest of its type
+        assert_eq!(list.match_extension(Path::new("a.mov")), None);
+        assert_eq!(list.match_extension(Path::new("a.mp4")), Some("mp4"));
+        assert_eq!(list.match_magic(b"\0\0\0\x20ftypqt  "), Some("mp4"));
+
+        assert_eq!(
+            SkipList::empty().check(Some(Path::new("a.zip")), b"PK\x03\x04"),
+            None
+        );
+    }
+}
@@ -13,6 +13,8 @@ pub enum ProtocolError {
     EncodingError(String),
     /// Decoding error
     DecodingError(String),
+    /// Frame length exceeds the codec's limit for its message type
+    FrameTooLarge { len: usize, max: usize },
     /// Transfer failed
     TransferFailed(String),
     /// Compression error
@@ -25,6 +27,12 @@ pub enum ProtocolError {
     HandshakeFailed(String),
     /// Key confirmation mismatch
     KeyConfirmationFailed,
+    /// Chat ratchet chains no longer agree; the session must re-handshake
+    RatchetDesync,
+    /// The output disk filled up; the transfer can resume once space is freed
+    DiskFull(String),
+    /// A chunk's CRC32C did not match: it was corrupted before decryption
+    ChunkChecksumMismatch { index: u64 },
 }
 
 impl fmt::Display for ProtocolError {
@@ -36,6 +44,9 @@ impl fmt::Display for ProtocolError {
             }
             Self::EncodingError(msg) => write!(f, "Encoding error: {}", msg),
             Self::DecodingError(msg) => write!(f, "Decoding error: {}", msg),
+            Self::FrameTooLarge { len, max } => {
+                write!(f, "Frame too large: {} bytes (max {})", len, max)
+            }
             Self::TransferFailed(msg) => write!(f, "Transfer failed: {}", msg),
             Self::CompressionError(msg) => write!(f, "Compression error: {}", msg),
             Self::InvalidStateTransition { from, to } => {
@@ -46,6 +57,15 @@ impl fmt::Display for ProtocolError {
             Self::KeyConfirmationFailed => {
                 write!(f, "Handshake failed: key confirmation mismatch")
             }
+            Self::RatchetDesync => {
+                write!(f, "Chat ratchet desynchronized: re-handshake required")
+            }
+            Self::DiskFull(msg) => write!(f, "Disk full: {}", msg),
+            Self::ChunkChecksumMismatch { index } => write!(
+                f,
+                "Chunk {} checksum mismatch: data was corrupted before decryption",
+                index
+            ),
         }
     }
 }
@@ -6,7 +6,8 @@
 //! # Feature flags
 //!
 //! - **`full`** (default): All modules, native dependencies (tokio, zstd, etc.)
-//! - **`wasm`**: Minimal subset for browser compilation (wire messages + sanitize only)
+//! - **`wasm`**: Minimal subset for browser compilation (wire messages,
+//!   chunk nonce/AAD construction, and sanitize)
 
 #![forbid(unsafe_code)]
 
@@ -23,6 +24,8 @@ pub mod metadata;
 pub mod multi;
 #[cfg(feature = "full")]
 pub mod room;
+#[cfg(feature = "full")]
+pub mod sessions;
 pub mod transfer;
 pub mod wire;
 
@@ -0,0 +1,318 @@
+//! Per-receiver flow control for sending to several peers at once
+//!
+//! In a multi-peer room the sender produces each chunk once and delivers it
+//! to every receiver. A chunk stays buffered until the last receiver has
+//! taken it, so a single stalled receiver would otherwise make the sender
+//! hold the rest of the transfer in memory. A [`FanoutQueue`] tracks where
+//! each receiver is and applies one of two policies:
+//!
+//! - [`FairnessMode::Lockstep`]: the sender runs at most `max_lag` chunks
+//!   ahead of the slowest receiver, so everyone moves at its pace.
+//! - [`FairnessMode::Independent`]: the sender is paced by the fastest
+//!   receiver, and a receiver more than `max_lag` chunks behind is dropped
+//!   rather than holding the others back.
+//!
+//! Either way no more than `max_lag` chunks are ever buffered.
+
+use crate::{ProtocolError, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+/// Default bound on chunks buffered for the slowest receiver
+pub const DEFAULT_MAX_LAG: usize = 64;
+
+/// How a multi-peer send treats receivers that progress at different rates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FairnessMode {
+    /// Receivers progress on their own; stragglers past the limit are dropped
+    #[default]
+    Independent,
+    /// The sender waits for the slowest receiver
+    Lockstep,
+}
+
+impl std::str::FromStr for FairnessMode {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "independent" => Ok(Self::Independent),
+            "lockstep" => Ok(Self::Lockstep),
+            other => Err(ProtocolError::InvalidMessage(format!(
+                "unknown fairness mode '{}' (expected independent or lockstep)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Flow-control settings for a [`FanoutQueue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FanoutConfig {
+    /// Policy for receivers that fall behind
+    pub mode: FairnessMode,
+    /// Most chunks buffered for the slowest receiver still being served
+    pub max_lag: usize,
+}
+
+impl FanoutConfig {
+    /// Settings with `max_lag` clamped to at least one chunk
+    pub fn new(mode: FairnessMode, max_lag: usize) -> Self {
+        Self {
+            mode,
+            max_lag: max_lag.max(1),
+        }
+    }
+}
+
+impl Default for FanoutConfig {
+    fn default() -> Self {
+        Self::new(FairnessMode::default(), DEFAULT_MAX_LAG)
+    }
+}
+
+/// Chunks shared between several receivers, each with its own position
+///
+/// Sequence numbers count chunks pushed since the queue was created.
+#[derive(Debug)]
+pub struct FanoutQueue<T> {
+    config: FanoutConfig,
+    /// Chunks some receiver has not taken yet
+    buffer: VecDeque<Arc<T>>,
+    /// Sequence number of `buffer[0]`
+    base: u64,
+    /// Sequence number of each receiver's next chunk
+    cursors: BTreeMap<u8, u64>,
+}
+
+impl<T> FanoutQueue<T> {
+    /// Create an empty queue
+    pub fn new(config: FanoutConfig) -> Self {
+        Self {
+            config,
+            buffer: VecDeque::new(),
+            base: 0,
+            cursors: BTreeMap::new(),
+        }
+    }
+
+    /// Flow-control settings
+    pub fn config(&self) -> &FanoutConfig {
+        &self.config
+    }
+
+    /// Start delivering to `peer_id` from the next chunk pushed
+    pub fn add_receiver(&mut self, peer_id: u8) {
+        let head = self.head();
+        self.cursors.entry(peer_id).or_insert(head);
+    }
+
+    /// Stop delivering to `peer_id`, releasing chunks only it was holding
+    pub fn remove_receiver(&mut self, peer_id: u8) -> bool {
+        let removed = self.cursors.remove(&peer_id).is_some();
+        self.trim();
+        removed
+    }
+
+    /// Receivers still being delivered to
+    pub fn receivers(&self) -> impl Iterator<Item = u8> + '_ {
+        self.cursors.keys().copied()
+    }
+
+    /// Chunks currently held in memory
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// How many pushed chunks `peer_id` has not taken yet
+    pub fn lag(&self, peer_id: u8) -> Option<u64> {
+        self.cursors
+            .get(&peer_id)
+            .map(|&cursor| self.head() - cursor)
+    }
+
+    /// Whether [`push`](Self::push) would accept another chunk now
+    ///
+    /// In lockstep mode this waits on the slowest receiver, in independent
+    /// mode on the fastest.
+    pub fn can_push(&self) -> bool {
+        let pace = match self.config.mode {
+            FairnessMode::Lockstep => self.cursors.values().min(),
+            FairnessMode::Independent => self.cursors.values().max(),
+        };
+        match pace {
+            Some(&cursor) => self.head() - cursor < self.config.max_lag as u64,
+            None => true,
+        }
+    }
+
+    /// Queue the next chunk for every receiver
+    ///
+    /// Returns the receivers dropped for falling too far behind (only ever
+    /// non-empty in independent mode). Fails without queueing when
+    /// [`can_push`](Self::can_push) is false.
+    pub fn push(&mut self, chunk: T) -> Result<Vec<u8>> {
+        if !self.can_push() {
+            return Err(ProtocolError::TransferFailed(format!(
+                "fan-out buffer full ({} chunks); wait for receivers to catch up",
+                self.buffer.len()
+            )));
+        }
+        self.buffer.push_back(Arc::new(chunk));
+
+        let mut dropped = Vec::new();
+        if self.config.mode == FairnessMode::Independent {
+            let head = self.head();
+            let limit = self.config.max_lag as u64;
+            self.cursors.retain(|&peer_id, &mut cursor| {
+                let keep = head - cursor <= limit;
+                if !keep {
+                    dropped.push(peer_id);
+                }
+                keep
+            });
+            for peer_id in &dropped {
+                tracing::warn!(
+                    "Dropping receiver {} from fan-out: more than {} chunks behind",
+                    peer_id,
+                    limit
+                );
+            }
+        }
+        self.trim();
+        Ok(dropped)
+    }
+
+    /// Next chunk for `peer_id` with its sequence number, if one is queued
+    pub fn next_for(&mut self, peer_id: u8) -> Option<(u64, Arc<T>)> {
+        let head = self.head();
+        let cursor = self.cursors.get_mut(&peer_id)?;
+        if *cursor >= head {
+            return None;
+        }
+        let seq = *cursor;
+        *cursor += 1;
+        let chunk = Arc::clone(&self.buffer[(seq - self.base) as usize]);
+        self.trim();
+        Some((seq, chunk))
+    }
+
+    /// Sequence number the next pushed chunk gets
+    fn head(&self) -> u64 {
+        self.base + self.buffer.len() as u64
+    }
+
+    /// Release chunks every receiver has taken
+    fn trim(&mut self) {
+        let oldest = self.cursors.values().min().copied().unwrap_or(self.head());
+        while self.base < oldest {
+            self.buffer.pop_front();
+            self.base += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNKS: u64 = 1_000;
+
+    #[test]
+    fn test_fairness_mode_from_str() {
+        assert_eq!(
+            "lockstep".parse::<FairnessMode>().unwrap(),
+            FairnessMode::Lockstep
+        );
+        assert_eq!(
+            "independent".parse::<FairnessMode>().unwrap(),
+            FairnessMode::Independent
+        );
+        assert!("fastest".parse::<FairnessMode>().is_err());
+        assert_eq!(FanoutConfig::new(FairnessMode::Lockstep, 0).max_lag, 1);
+    }
+
+    #[test]
+    fn test_independent_drops_stalled_receiver() {
+        let mut queue = FanoutQueue::new(FanoutConfig::new(FairnessMode::Independent, 8));
+        for peer in 1..=3 {
+            queue.add_receiver(peer);
+        }
+
+        let mut received = [Vec::new(), Vec::new()];
+        let mut dropped = Vec::new();
+        for seq in 0..CHUNKS {
+            assert!(queue.can_push());
+            dropped.extend(queue.push(seq).unwrap());
+            assert!(queue.buffered() <= 8, "{} buffered", queue.buffered());
+            // Peers 1 and 2 keep up; peer 3 never reads
+            for (peer, got) in [1, 2].into_iter().zip(received.iter_mut()) {
+                while let Some((_, chunk)) = queue.next_for(peer) {
+                    got.push(*chunk);
+                }
+            }
+        }
+
+        assert_eq!(dropped, vec![3]);
+        assert_eq!(queue.lag(3), None);
+        assert_eq!(queue.receivers().collect::<Vec<_>>(), vec![1, 2]);
+        for got in received {
+            assert_eq!(got, (0..CHUNKS).collect::<Vec<_>>());
+        }
+        assert_eq!(queue.buffered(), 0);
+    }
+
+    #[test]
+    fn test_lockstep_paces_to_slowest() {
+        let mut queue = FanoutQueue::new(FanoutConfig::new(FairnessMode::Lockstep, 4));
+        queue.add_receiver(1);
+        queue.add_receiver(2);
+
+        let mut next = 0u64;
+        let mut slow = Vec::new();
+        let mut fast = Vec::new();
+        let mut round = 0;
+        while slow.len() < CHUNKS as usize {
+            while next < CHUNKS && queue.can_push() {
+                assert!(queue.push(next).unwrap().is_empty());
+                next += 1;
+            }
+            assert!(queue.buffered() <= 4);
+            while let Some((_, chunk)) = queue.next_for(1) {
+                fast.push(*chunk);
+            }
+            // The slow receiver takes one chunk every other round
+            if round % 2 == 0 {
+                if let Some((_, chunk)) = queue.next_for(2) {
+                    slow.push(*chunk);
+                }
+            }
+            // The fast receiver is never more than the window ahead
+            assert!(fast.len() - slow.len() <= 4);
+            round += 1;
+        }
+
+        assert_eq!(fast, slow);
+        assert_eq!(queue.receivers().count(), 2);
+        assert!(round >= 2 * CHUNKS as usize - 1);
+    }
+
+    #[test]
+    fn test_lockstep_refuses_push_when_full() {
+        let mut queue = FanoutQueue::new(FanoutConfig::new(FairnessMode::Lockstep, 2));
+        queue.add_receiver(1);
+        queue.add_receiver(2);
+        queue.push("a").unwrap();
+        queue.push("b").unwrap();
+        assert!(!queue.can_push());
+        assert!(queue.push("c").is_err());
+        assert_eq!(queue.lag(2), Some(2));
+
+        // Removing the straggler frees its chunks
+        queue.next_for(1);
+        queue.next_for(1);
+        assert!(queue.remove_receiver(2));
+        assert_eq!(queue.buffered(), 0);
+        assert!(queue.can_push());
+    }
+}
@@ -2,6 +2,11 @@
 //!
 //! Manages pairwise KEM sessions between N peers in a multi-peer room.
 //! Each peer pair derives independent directional encryption keys via HKDF.
+//! [`fanout`] paces a send to several receivers at once.
+
+pub mod fanout;
+
+pub use fanout::{FairnessMode, FanoutConfig, FanoutQueue};
 
 use std::collections::HashMap;
 use zeroize::Zeroize;
@@ -49,13 +54,11 @@ impl Drop for PeerSession {
     }
 }
 
-/// Domain separation prefix for multi-peer key derivation
-const MULTI_PEER_KEY_DOMAIN: &str = "tallow.multipeer.pairkey.v1";
-
 /// Derive directional encryption keys from a pairwise session key.
 ///
-/// Uses HKDF-SHA256 with peer IDs in the info field to derive two
-/// distinct keys: one for each direction of communication.
+/// Uses HKDF-Expand-Label ("pair key") with the sending and receiving peer
+/// IDs as context to derive two distinct keys: one for each direction of
+/// communication.
 ///
 /// The lower peer ID's "send_key" is the higher peer ID's "recv_key",
 /// ensuring both sides derive the same key pair.
@@ -77,22 +80,17 @@ pub fn derive_peer_keys(
         (their_peer_id, my_peer_id)
     };
 
-    let info_a_to_b = format!("{}-{}-to-{}", MULTI_PEER_KEY_DOMAIN, id_a, id_b);
-    let info_b_to_a = format!("{}-{}-to-{}", MULTI_PEER_KEY_DOMAIN, id_b, id_a);
-
-    let key_a_to_b = tallow_crypto::kdf::hkdf::derive(
-        &[0u8; 32], // salt
-        session_key,
-        info_a_to_b.as_bytes(),
-        32,
-    )
-    .map_err(|e| crate::ProtocolError::HandshakeFailed(format!("HKDF derive failed: {}", e)))?;
-
-    let key_b_to_a =
-        tallow_crypto::kdf::hkdf::derive(&[0u8; 32], session_key, info_b_to_a.as_bytes(), 32)
-            .map_err(|e| {
-                crate::ProtocolError::HandshakeFailed(format!("HKDF derive failed: {}", e))
-            })?;
+    let derive = |from: u8, to: u8| {
+        tallow_crypto::kdf::hkdf::expand_label(
+            session_key,
+            tallow_crypto::hash::LABEL_PAIR_KEY,
+            &[from, to],
+            32,
+        )
+        .map_err(|e| crate::ProtocolError::HandshakeFailed(format!("HKDF derive failed: {}", e)))
+    };
+    let key_a_to_b = derive(id_a, id_b)?;
+    let key_b_to_a = derive(id_b, id_a)?;
 
     let mut send_key = [0u8; 32];
     let mut recv_key = [0u8; 32];
@@ -2,9 +2,14 @@
 //!
 //! Code phrases use the EFF Diceware wordlist for human-readable codes.
 //! Room IDs are BLAKE3 hashes of the code phrase for relay routing.
+//!
+//! Rendezvous room IDs skip the code phrase entirely: peers that already
+//! share a secret derive the same room ID from it on their own.
 
+use crate::{ProtocolError, Result};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use tallow_crypto::hash::domain;
 
 /// Default number of words in a code phrase.
 ///
@@ -13,6 +18,12 @@ use rand::thread_rng;
 /// is prevented by the relay rate-limiting and session expiry.
 pub const DEFAULT_WORD_COUNT: usize = 4;
 
+/// Minimum length of a rendezvous secret in bytes.
+///
+/// Anyone who can guess the secret can compute the room ID and squat on
+/// the room, so short secrets are refused outright.
+pub const MIN_RENDEZVOUS_SECRET_LEN: usize = 16;
+
 /// Generate a memorable room code phrase using the EFF Diceware wordlist
 ///
 /// # Arguments
@@ -40,6 +51,27 @@ pub fn derive_room_id(code_phrase: &str) -> [u8; 32] {
     blake3::hash(code_phrase.as_bytes()).into()
 }
 
+/// Derive a rendezvous room ID from a secret both peers already share
+///
+/// Uses BLAKE3 key derivation under `DOMAIN_ROOM`, so the ID can never
+/// equal a code-phrase room ID or any other hash of the same secret. Both
+/// peers compute it independently; no code is generated or exchanged.
+/// Surrounding whitespace is ignored. Secrets shorter than
+/// [`MIN_RENDEZVOUS_SECRET_LEN`] are rejected.
+pub fn derive_rendezvous_room_id(secret: &str) -> Result<[u8; 32]> {
+    let secret = secret.trim();
+    if secret.len() < MIN_RENDEZVOUS_SECRET_LEN {
+        return Err(ProtocolError::InvalidMessage(format!(
+            "rendezvous secret must be at least {} bytes",
+            MIN_RENDEZVOUS_SECRET_LEN
+        )));
+    }
+    Ok(tallow_crypto::hash::derive_key(
+        domain::DOMAIN_ROOM,
+        secret.as_bytes(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +132,41 @@ mod tests {
         let id2 = derive_room_id("abce");
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn test_rendezvous_same_secret_same_room() {
+        let secret = "correct-horse-battery-staple-42";
+        let alice = derive_rendezvous_room_id(secret).unwrap();
+        let bob = derive_rendezvous_room_id(&format!("  {}\n", secret)).unwrap();
+        assert_eq!(alice, bob);
+    }
+
+    #[test]
+    fn test_rendezvous_different_secrets_differ() {
+        let id1 = derive_rendezvous_room_id("correct-horse-battery-staple-42").unwrap();
+        let id2 = derive_rendezvous_room_id("correct-horse-battery-staple-43").unwrap();
+        assert_ne!(id1, id2);
+    }
+
+    #[test]
+    fn test_rendezvous_domain_separated() {
+        let secret = "correct-horse-battery-staple-42";
+        let id = derive_rendezvous_room_id(secret).unwrap();
+        assert_eq!(
+            id,
+            tallow_crypto::hash::derive_key(domain::DOMAIN_ROOM, secret.as_bytes())
+        );
+        // Never collides with the plain-hash code phrase room for the same text
+        assert_ne!(id, derive_room_id(secret));
+        assert_ne!(
+            id,
+            tallow_crypto::hash::derive_key(domain::DOMAIN_SAS, secret.as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_rendezvous_rejects_short_secret() {
+        assert!(derive_rendezvous_room_id("short").is_err());
+        assert!(derive_rendezvous_room_id("   padded-short   ").is_err());
+    }
 }
@@ -1,13 +1,22 @@
 //! Room management
 //!
 //! Manages room participants and their roles for a transfer session.
+//!
+//! Each room has a connection cap set by its creator (within the relay's
+//! global maximum). Joins beyond the cap go to a FIFO waiting list when one
+//! is enabled, and waiters are admitted as participants leave or the host
+//! raises the cap.
 
 use super::RoomRole;
 use crate::{ProtocolError, Result};
+use std::collections::VecDeque;
 
 /// Maximum participants per room (sender + receiver)
 const MAX_PARTICIPANTS: usize = 2;
 
+/// Hard upper bound on any room's cap, matching the relay's multi-peer limit
+pub const MAX_ROOM_CAPACITY: usize = 20;
+
 /// Room participant
 #[derive(Debug, Clone)]
 pub struct Participant {
@@ -19,6 +28,18 @@ pub struct Participant {
     pub role: RoomRole,
 }
 
+/// Result of a join attempt that was not rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinOutcome {
+    /// Admitted to the room with the given role
+    Admitted(RoomRole),
+    /// Room is full; placed on the waiting list at this 1-based position
+    Queued {
+        /// Position in the waiting list (1 = next to be admitted)
+        position: usize,
+    },
+}
+
 /// Room manager
 #[derive(Debug)]
 pub struct RoomManager {
@@ -26,56 +47,111 @@ pub struct RoomManager {
     pub code: String,
     /// Participants
     participants: Vec<Participant>,
+    /// Peers waiting for a free slot, in arrival order
+    waiting: VecDeque<Participant>,
+    /// Connection cap set by the room creator
+    capacity: usize,
+    /// Relay-wide cap that `capacity` may never exceed
+    global_max: usize,
+    /// Maximum waiting list length (0 disables queueing)
+    waitlist_limit: usize,
 }
 
 impl RoomManager {
-    /// Create a new room
+    /// Create a new two-party room with no waiting list
     pub fn new(code: String) -> Self {
         Self {
             code,
             participants: Vec::new(),
+            waiting: VecDeque::new(),
+            capacity: MAX_PARTICIPANTS,
+            global_max: MAX_ROOM_CAPACITY,
+            waitlist_limit: 0,
         }
     }
 
+    /// Create a room with a creator-chosen connection cap.
+    ///
+    /// `global_max` is the relay's per-room limit (itself clamped to
+    /// [`MAX_ROOM_CAPACITY`]); a `capacity` of zero or above it is rejected.
+    pub fn with_capacity(code: String, capacity: usize, global_max: usize) -> Result<Self> {
+        let global_max = global_max.min(MAX_ROOM_CAPACITY);
+        check_capacity(capacity, global_max)?;
+        Ok(Self {
+            capacity,
+            global_max,
+            ..Self::new(code)
+        })
+    }
+
+    /// Enable a waiting list holding up to `limit` peers when the room is full
+    pub fn with_waitlist(mut self, limit: usize) -> Self {
+        self.waitlist_limit = limit;
+        self
+    }
+
     /// Join a room.
     ///
     /// Adds the participant to the room if there is space and they are
-    /// not already present. The first participant becomes the host.
-    pub async fn join(&mut self, mut participant: Participant) -> Result<()> {
-        // Check if already in room
-        if self.participants.iter().any(|p| p.id == participant.id) {
+    /// not already present. The first participant becomes the host; later
+    /// participants join as guests and cannot claim the host role. When the
+    /// room is at its cap the participant is queued if the waiting list has
+    /// room, and rejected otherwise.
+    pub async fn join(&mut self, mut participant: Participant) -> Result<JoinOutcome> {
+        // Check if already in room or waiting
+        if self.contains(&participant.id) {
             return Err(ProtocolError::InvalidMessage(format!(
                 "Participant {} already in room",
                 participant.id
             )));
         }
 
-        // Check room capacity
-        if self.participants.len() >= MAX_PARTICIPANTS {
-            return Err(ProtocolError::InvalidMessage("Room is full".to_string()));
+        if participant.role == RoomRole::Host && !self.participants.is_empty() {
+            participant.role = RoomRole::Member;
         }
 
-        // First participant becomes host
-        if self.participants.is_empty() {
-            participant.role = RoomRole::Host;
-        }
+        // Check room capacity
+        if self.participants.len() >= self.capacity {
+            if self.waiting.len() >= self.waitlist_limit {
+                return Err(ProtocolError::InvalidMessage(format!(
+                    "Room is full ({}/{} participants, {} waiting)",
+                    self.participants.len(),
+                    self.capacity,
+                    self.waiting.len()
+                )));
+            }
 
-        tracing::info!(
-            "Participant {} joined room {} as {:?}",
-            participant.id,
-            self.code,
-            participant.role
-        );
+            tracing::info!(
+                "Participant {} queued for room {} (position {})",
+                participant.id,
+                self.code,
+                self.waiting.len() + 1
+            );
+            self.waiting.push_back(participant);
+            return Ok(JoinOutcome::Queued {
+                position: self.waiting.len(),
+            });
+        }
 
-        self.participants.push(participant);
-        Ok(())
+        Ok(JoinOutcome::Admitted(self.admit(participant)))
     }
 
     /// Leave a room.
     ///
-    /// Removes the participant from the room. If the host leaves and
-    /// there are remaining participants, the next participant is promoted.
-    pub async fn leave(&mut self, participant_id: &str) -> Result<()> {
+    /// Removes the participant from the room or its waiting list. If the host
+    /// leaves and there are remaining participants, the next participant is
+    /// promoted. A freed slot admits the next waiter, whose ID is returned.
+    pub async fn leave(&mut self, participant_id: &str) -> Result<Option<String>> {
+        if let Some(pos) = self.waiting.iter().position(|p| p.id == participant_id) {
+            self.waiting.remove(pos);
+            tracing::info!(
+                "Participant {} left the waiting list for room {}",
+                participant_id,
+                self.code
+            );
+            return Ok(None);
+        }
+
         let pos = self
             .participants
             .iter()
@@ -96,7 +172,39 @@ impl RoomManager {
         }
 
         tracing::info!("Participant {} left room {}", participant_id, self.code);
-        Ok(())
+        Ok(self.admit_waiters().into_iter().next())
+    }
+
+    /// Change the room's connection cap.
+    ///
+    /// Only the host may do this, and the new cap must stay within the
+    /// relay's global maximum. Lowering the cap never removes current
+    /// participants; raising it admits waiters, whose IDs are returned.
+    pub fn set_capacity(&mut self, requester_id: &str, capacity: usize) -> Result<Vec<String>> {
+        let role = self
+            .participants
+            .iter()
+            .find(|p| p.id == requester_id)
+            .map(|p| p.role)
+            .ok_or_else(|| {
+                ProtocolError::InvalidMessage(format!("Participant {} not in room", requester_id))
+            })?;
+        if !role.can_manage_room() {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "Only the host can change the room cap ({} is a guest)",
+                requester_id
+            )));
+        }
+        check_capacity(capacity, self.global_max)?;
+
+        tracing::info!(
+            "Room {} cap changed from {} to {}",
+            self.code,
+            self.capacity,
+            capacity
+        );
+        self.capacity = capacity;
+        Ok(self.admit_waiters())
     }
 
     /// Get participants
@@ -104,6 +212,24 @@ impl RoomManager {
         &self.participants
     }
 
+    /// Peers waiting for a slot, next to be admitted first
+    pub fn waiting(&self) -> impl Iterator<Item = &Participant> {
+        self.waiting.iter()
+    }
+
+    /// 1-based waiting list position of a participant, if queued
+    pub fn waiting_position(&self, participant_id: &str) -> Option<usize> {
+        self.waiting
+            .iter()
+            .position(|p| p.id == participant_id)
+            .map(|i| i + 1)
+    }
+
+    /// Current connection cap
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     /// Check if room is empty
     pub fn is_empty(&self) -> bool {
         self.participants.is_empty()
@@ -111,13 +237,59 @@ impl RoomManager {
 
     /// Check if room is full
     pub fn is_full(&self) -> bool {
-        self.participants.len() >= MAX_PARTICIPANTS
+        self.participants.len() >= self.capacity
     }
 
     /// Check if room is paired (both sender and receiver present)
     pub fn is_paired(&self) -> bool {
         self.participants.len() == MAX_PARTICIPANTS
     }
+
+    fn contains(&self, participant_id: &str) -> bool {
+        self.participants.iter().any(|p| p.id == participant_id)
+            || self.waiting.iter().any(|p| p.id == participant_id)
+    }
+
+    /// Add a participant to the room, making them host if it is empty
+    fn admit(&mut self, mut participant: Participant) -> RoomRole {
+        if self.participants.is_empty() {
+            participant.role = RoomRole::Host;
+        }
+
+        tracing::info!(
+            "Participant {} joined room {} as {:?}",
+            participant.id,
+            self.code,
+            participant.role
+        );
+
+        let role = participant.role;
+        self.participants.push(participant);
+        role
+    }
+
+    /// Move waiters into free slots in arrival order
+    fn admit_waiters(&mut self) -> Vec<String> {
+        let mut admitted = Vec::new();
+        while self.participants.len() < self.capacity {
+            let Some(next) = self.waiting.pop_front() else {
+                break;
+            };
+            admitted.push(next.id.clone());
+            self.admit(next);
+        }
+        admitted
+    }
+}
+
+fn check_capacity(capacity: usize, global_max: usize) -> Result<()> {
+    if capacity == 0 || capacity > global_max {
+        return Err(ProtocolError::InvalidMessage(format!(
+            "Room cap must be between 1 and {} (got {})",
+            global_max, capacity
+        )));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -188,4 +360,102 @@ mod tests {
         mgr.join(make_participant("bob")).await.unwrap();
         assert!(mgr.is_paired());
     }
+
+    #[test]
+    fn test_capacity_within_global_max() {
+        assert!(RoomManager::with_capacity("r".to_string(), 5, 10).is_ok());
+        assert!(RoomManager::with_capacity("r".to_string(), 0, 10).is_err());
+        assert!(RoomManager::with_capacity("r".to_string(), 11, 10).is_err());
+        // The relay's global max is itself capped
+        assert!(RoomManager::with_capacity("r".to_string(), 21, 50).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_join_beyond_cap_is_queued() {
+        let mut mgr = RoomManager::with_capacity("r".to_string(), 3, 20)
+            .unwrap()
+            .with_waitlist(2);
+        for id in ["alice", "bob", "carol"] {
+            assert!(matches!(
+                mgr.join(make_participant(id)).await.unwrap(),
+                JoinOutcome::Admitted(_)
+            ));
+        }
+
+        assert_eq!(
+            mgr.join(make_participant("dave")).await.unwrap(),
+            JoinOutcome::Queued { position: 1 }
+        );
+        assert_eq!(
+            mgr.join(make_participant("erin")).await.unwrap(),
+            JoinOutcome::Queued { position: 2 }
+        );
+        assert_eq!(mgr.participants().len(), 3);
+        assert_eq!(mgr.waiting_position("erin"), Some(2));
+
+        // Waiting list full: rejected with a reason
+        let err = mgr.join(make_participant("frank")).await.unwrap_err();
+        assert!(err.to_string().contains("Room is full (3/3"));
+        // Queued peers count as present
+        assert!(mgr.join(make_participant("dave")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_departure_admits_next_waiter() {
+        let mut mgr = RoomManager::with_capacity("r".to_string(), 2, 20)
+            .unwrap()
+            .with_waitlist(4);
+        mgr.join(make_participant("alice")).await.unwrap();
+        mgr.join(make_participant("bob")).await.unwrap();
+        mgr.join(make_participant("carol")).await.unwrap();
+        mgr.join(make_participant("dave")).await.unwrap();
+
+        // A waiter leaving the queue frees no room slot
+        assert_eq!(mgr.leave("carol").await.unwrap(), None);
+        assert_eq!(mgr.waiting_position("dave"), Some(1));
+
+        assert_eq!(mgr.leave("alice").await.unwrap(), Some("dave".to_string()));
+        let ids: Vec<_> = mgr.participants().iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["bob", "dave"]);
+        assert_eq!(mgr.participants()[0].role, RoomRole::Host);
+        assert_eq!(mgr.participants()[1].role, RoomRole::Member);
+        assert_eq!(mgr.waiting().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_host_adjusts_cap() {
+        let mut mgr = RoomManager::with_capacity("r".to_string(), 2, 10)
+            .unwrap()
+            .with_waitlist(4);
+        mgr.join(make_participant("alice")).await.unwrap();
+        mgr.join(make_participant("bob")).await.unwrap();
+        mgr.join(make_participant("carol")).await.unwrap();
+        mgr.join(make_participant("dave")).await.unwrap();
+
+        // Guests cannot change the cap, and it can't exceed the global max
+        assert!(mgr.set_capacity("bob", 4).is_err());
+        assert!(mgr.set_capacity("alice", 11).is_err());
+
+        assert_eq!(mgr.set_capacity("alice", 3).unwrap(), ["carol"]);
+        assert_eq!(mgr.capacity(), 3);
+        assert_eq!(mgr.participants().len(), 3);
+
+        // Lowering keeps everyone but blocks new admissions
+        assert!(mgr.set_capacity("alice", 2).unwrap().is_empty());
+        assert_eq!(mgr.participants().len(), 3);
+        assert_eq!(mgr.leave("carol").await.unwrap(), None);
+        assert_eq!(mgr.leave("bob").await.unwrap(), Some("dave".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_late_joiner_cannot_claim_host() {
+        let mut mgr = RoomManager::with_capacity("r".to_string(), 3, 20).unwrap();
+        mgr.join(make_participant("alice")).await.unwrap();
+        let mut mallory = make_participant("mallory");
+        mallory.role = RoomRole::Host;
+        assert_eq!(
+            mgr.join(mallory).await.unwrap(),
+            JoinOutcome::Admitted(RoomRole::Member)
+        );
+    }
 }
@@ -5,5 +5,5 @@ pub mod manager;
 pub mod roles;
 
 pub use code::generate_code_phrase;
-pub use manager::RoomManager;
+pub use manager::{JoinOutcome, RoomManager, MAX_ROOM_CAPACITY};
 pub use roles::RoomRole;
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 pub enum RoomRole {
     /// Room host (creator)
     Host,
-    /// Regular member
+    /// Regular member (a guest admitted by the host's room)
     Member,
     /// Read-only observer
     ReadOnly,
@@ -28,4 +28,9 @@ impl RoomRole {
     pub fn can_kick(&self) -> bool {
         matches!(self, RoomRole::Host)
     }
+
+    /// Check if role can change room settings such as the connection cap
+    pub fn can_manage_room(&self) -> bool {
+        matches!(self, RoomRole::Host)
+    }
 }
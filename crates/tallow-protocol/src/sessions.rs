@@ -0,0 +1,371 @@
+//! Registry of active sessions
+//!
+//! A long-running process (daemon, TUI) registers each transfer or chat
+//! session here so the user can list what is open and revoke any of it.
+//! Revoking a session cancels its [`SessionHandle`] and zeroizes the session
+//! key the registry holds, so nothing keyed by it can be resumed.
+
+use crate::kex::SessionKey;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tallow_crypto::clock::{Clock, SystemClock};
+use tokio_util::sync::CancellationToken;
+use zeroize::Zeroize;
+
+/// Registry-assigned session identifier
+pub type SessionId = u64;
+
+/// What a session is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionKind {
+    /// Sending files
+    Send,
+    /// Receiving files
+    Receive,
+    /// Directory sync
+    Sync,
+    /// Encrypted chat
+    Chat,
+}
+
+impl std::fmt::Display for SessionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Send => "send",
+            Self::Receive => "receive",
+            Self::Sync => "sync",
+            Self::Chat => "chat",
+        })
+    }
+}
+
+/// Snapshot of a registered session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Registry-assigned ID
+    pub id: SessionId,
+    /// Peer the session is with (name, fingerprint or address)
+    pub peer: String,
+    /// What the session is doing
+    pub kind: SessionKind,
+    /// Unix timestamp (seconds) when the session was registered
+    pub started_at: u64,
+    /// Bytes moved so far
+    pub bytes: u64,
+}
+
+/// Called after a revoked session's key has been zeroized
+pub type RevokeHook = Box<dyn Fn(&SessionInfo, &SessionKey) + Send + Sync>;
+
+/// The session's side of a registration
+///
+/// The task running the session keeps this to report progress and to
+/// notice revocation.
+#[derive(Debug, Clone)]
+pub struct SessionHandle {
+    id: SessionId,
+    bytes: Arc<AtomicU64>,
+    cancel: CancellationToken,
+}
+
+impl SessionHandle {
+    /// The session's registry ID
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// Record `n` more bytes moved
+    pub fn add_bytes(&self, n: u64) {
+        self.bytes.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Whether the session has been revoked
+    pub fn is_revoked(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Wait until the session is revoked
+    ///
+    /// Select on this alongside the session's I/O and stop when it fires.
+    pub async fn revoked(&self) {
+        self.cancel.cancelled().await
+    }
+}
+
+struct Entry {
+    peer: String,
+    kind: SessionKind,
+    started_at: u64,
+    bytes: Arc<AtomicU64>,
+    key: SessionKey,
+    cancel: CancellationToken,
+}
+
+impl Entry {
+    fn info(&self, id: SessionId) -> SessionInfo {
+        SessionInfo {
+            id,
+            peer: self.peer.clone(),
+            kind: self.kind,
+            started_at: self.started_at,
+            bytes: self.bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tracks active sessions and tears them down on request
+///
+/// All methods take `&self`; share the registry behind an `Arc`.
+pub struct SessionRegistry {
+    sessions: Mutex<HashMap<SessionId, Entry>>,
+    next_id: AtomicU64,
+    clock: Arc<dyn Clock>,
+    on_revoke: Option<RevokeHook>,
+}
+
+impl std::fmt::Debug for SessionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionRegistry")
+            .field("sessions", &self.lock().len())
+            .field("clock", &self.clock)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            clock: Arc::new(SystemClock),
+            on_revoke: None,
+        }
+    }
+
+    /// Use `clock` for session start times
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Run `hook` for every revoked session, after its key is zeroized
+    pub fn with_revoke_hook(mut self, hook: RevokeHook) -> Self {
+        self.on_revoke = Some(hook);
+        self
+    }
+
+    /// Register a session, handing its key to the registry
+    pub fn register(
+        &self,
+        peer: impl Into<String>,
+        kind: SessionKind,
+        key: SessionKey,
+    ) -> SessionHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let handle = SessionHandle {
+            id,
+            bytes: Arc::new(AtomicU64::new(0)),
+            cancel: CancellationToken::new(),
+        };
+        let entry = Entry {
+            peer: peer.into(),
+            kind,
+            started_at: self.clock.unix_secs(),
+            bytes: Arc::clone(&handle.bytes),
+            key,
+            cancel: handle.cancel.clone(),
+        };
+        self.lock().insert(id, entry);
+        handle
+    }
+
+    /// Active sessions, oldest first
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .lock()
+            .iter()
+            .map(|(id, entry)| entry.info(*id))
+            .collect();
+        sessions.sort_by_key(|s| s.id);
+        sessions
+    }
+
+    /// Look up one session
+    pub fn get(&self, id: SessionId) -> Option<SessionInfo> {
+        self.lock().get(&id).map(|entry| entry.info(id))
+    }
+
+    /// Tear down a session: cancel its handle and zeroize its key
+    ///
+    /// Returns the session as it was when revoked, or `None` if no such
+    /// session is registered (including one already revoked).
+    pub fn revoke(&self, id: SessionId) -> Option<SessionInfo> {
+        let mut entry = self.lock().remove(&id)?;
+        entry.cancel.cancel();
+        entry.key.zeroize();
+
+        let info = entry.info(id);
+        tracing::info!(id, peer = %info.peer, kind = %info.kind, "session revoked");
+        if let Some(ref hook) = self.on_revoke {
+            hook(&info, &entry.key);
+        }
+        Some(info)
+    }
+
+    /// Drop a session that ended on its own, without running the revoke hook
+    ///
+    /// The key is still zeroized when the entry is dropped.
+    pub fn finish(&self, id: SessionId) {
+        self.lock().remove(&id);
+    }
+
+    /// Number of active sessions
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no sessions are active
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<SessionId, Entry>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Default for SessionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tallow_crypto::clock::MockClock;
+
+    #[test]
+    fn test_registered_session_listed() {
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let registry = SessionRegistry::new().with_clock(clock.clone());
+
+        let first = registry.register("alice", SessionKind::Send, SessionKey::from_bytes([1; 32]));
+        clock.advance(Duration::from_secs(5));
+        let second = registry.register("bob", SessionKind::Chat, SessionKey::from_bytes([2; 32]));
+        first.add_bytes(4096);
+
+        let sessions = registry.list();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(
+            sessions[0],
+            SessionInfo {
+                id: first.id(),
+                peer: "alice".to_string(),
+                kind: SessionKind::Send,
+                started_at: 1_700_000_000,
+                bytes: 4096,
+            }
+        );
+        assert_eq!(sessions[1].id, second.id());
+        assert_eq!(sessions[1].started_at, 1_700_000_005);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_tears_down_and_zeroizes() {
+        let zeroized = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&zeroized);
+        let registry = SessionRegistry::new().with_revoke_hook(Box::new(move |info, key| {
+            seen.lock()
+                .unwrap()
+                .push((info.id, key.as_bytes().iter().all(|b| *b == 0)));
+        }));
+
+        let handle = registry.register(
+            "alice",
+            SessionKind::Receive,
+            SessionKey::from_bytes([7; 32]),
+        );
+        let other = registry.register("bob", SessionKind::Send, SessionKey::from_bytes([8; 32]));
+
+        let task = tokio::spawn({
+            let handle = handle.clone();
+            async move { handle.revoked().await }
+        });
+
+        let revoked = registry.revoke(handle.id()).unwrap();
+        assert_eq!(revoked.peer, "alice");
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("session task not cancelled")
+            .unwrap();
+        assert!(handle.is_revoked());
+        assert!(!other.is_revoked());
+
+        assert_eq!(*zeroized.lock().unwrap(), vec![(handle.id(), true)]);
+        assert_eq!(registry.list().len(), 1);
+        assert!(registry.get(handle.id()).is_none());
+
+        // Revoking twice is a no-op
+        assert!(registry.revoke(handle.id()).is_none());
+        assert_eq!(zeroized.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_register_and_revoke() {
+        let revocations = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&revocations);
+        let registry = Arc::new(
+            SessionRegistry::new().with_revoke_hook(Box::new(move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+        );
+
+        // Each thread registers sessions and two threads race to revoke each
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let registry = Arc::clone(&registry);
+                std::thread::spawn(move || {
+                    let mut revoked = 0;
+                    for i in 0..100 {
+                        let handle = registry.register(
+                            format!("peer-{}-{}", t, i),
+                            SessionKind::Sync,
+                            SessionKey::from_bytes([t as u8; 32]),
+                        );
+                        let id = handle.id();
+                        let racer = {
+                            let registry = Arc::clone(&registry);
+                            std::thread::spawn(move || registry.revoke(id).is_some())
+                        };
+                        let mine = registry.revoke(id).is_some();
+                        let theirs = racer.join().unwrap();
+                        assert!(
+                            mine ^ theirs,
+                            "session {} revoked {} times",
+                            id,
+                            mine as u8 + theirs as u8
+                        );
+                        assert!(handle.is_revoked());
+                        revoked += 1;
+                    }
+                    revoked
+                })
+            })
+            .collect();
+
+        let total: u64 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+        assert_eq!(total, 800);
+        assert_eq!(revocations.load(Ordering::SeqCst), 800);
+        assert!(registry.is_empty());
+
+        // IDs were never handed out twice
+        let next = registry.register("last", SessionKind::Chat, SessionKey::from_bytes([0; 32]));
+        assert_eq!(next.id(), 801);
+    }
+}
@@ -0,0 +1,1033 @@
+//! Airgap export bundles
+//!
+//! A bundle is a single self-describing file for sneakernet transfers:
+//! the encrypted manifest, every encrypted chunk, and a hybrid signature
+//! over the whole thing. Bundles are encrypted either to a recipient's
+//! hybrid KEM public key or to a password (Argon2id).
+//!
+//! ## Layout
+//!
+//! ```text
+//! magic (8) | version u16 | header_len u32 | header
+//!           | manifest_len u32 | encrypted manifest
+//!           | total_chunks × (chunk_len u32 | encrypted chunk)
+//!           | sig_len u32 | signature
+//! ```
+//!
+//! All integers are big-endian. The signature covers a BLAKE3 transcript
+//! of every byte preceding it, and is verified before anything is decrypted.
+//!
+//! ## Stream bundles
+//!
+//! [`seal_stream`] and [`open_stream`] use the same layout for an
+//! unknown-length source such as stdin. The manifest is a
+//! `TransferType::Stream` manifest and the last chunk frame is the encrypted
+//! [`StreamTrailer`]. Because the input is never buffered, `open_stream`
+//! writes decrypted data before the signature is reached; the output is
+//! only trustworthy once it returns `Ok`.
+
+use crate::transfer::chunking;
+use crate::transfer::manifest::{FileManifest, TransferType};
+use crate::transfer::receive::ReceivePipeline;
+use crate::transfer::send::SendPipeline;
+use crate::transfer::stream::{StreamReceiver, StreamTrailer};
+use crate::wire::Message;
+use crate::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tallow_crypto::hash::domain;
+use tallow_crypto::kem::hybrid::{self, HybridKem};
+use tallow_crypto::sig::{HybridPublicKey, HybridSignature, HybridSigner};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use zeroize::Zeroize;
+
+/// Magic bytes identifying a Tallow bundle file
+pub const BUNDLE_MAGIC: [u8; 8] = *b"TALLOWBX";
+
+/// Current bundle format version
+pub const BUNDLE_VERSION: u16 = 1;
+
+/// Maximum encoded header size (KEM ciphertext + signer public key)
+const MAX_HEADER_LEN: usize = 64 * 1024;
+
+/// Maximum encrypted manifest size
+const MAX_MANIFEST_LEN: usize = 64 * 1024 * 1024;
+
+/// Maximum encrypted chunk size (max chunk + compression expansion + AEAD tag)
+const MAX_CHUNK_LEN: usize = chunking::MAX_CHUNK_SIZE + 64 * 1024;
+
+/// Maximum encoded signature size
+const MAX_SIGNATURE_LEN: usize = 16 * 1024;
+
+/// Nonce for the manifest. Chunk nonces always have a zero prefix,
+/// so the non-zero first byte keeps this nonce out of their range.
+const MANIFEST_NONCE: [u8; 12] = [0xFF, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+/// Who a bundle is encrypted to
+pub enum BundleRecipient<'a> {
+    /// Recipient's hybrid (ML-KEM-1024 + X25519) public key
+    PublicKey(&'a hybrid::PublicKey),
+    /// Shared password, stretched with Argon2id
+    Password(&'a str),
+}
+
+/// Credential used to open a bundle
+pub enum BundleUnlock<'a> {
+    /// Recipient's hybrid secret key
+    SecretKey(&'a hybrid::SecretKey),
+    /// Shared password
+    Password(&'a str),
+}
+
+/// How the bundle key is wrapped
+#[derive(Clone, Serialize, Deserialize)]
+pub enum KeyWrap {
+    /// Key derived from a hybrid KEM encapsulation
+    Kem(hybrid::Ciphertext),
+    /// Key derived from a password with Argon2id
+    Password {
+        /// Argon2id salt
+        salt: [u8; 16],
+    },
+}
+
+/// Plaintext bundle header
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BundleHeader {
+    /// Transfer ID bound into chunk AAD
+    pub transfer_id: [u8; 16],
+    /// Key wrapping method
+    pub key_wrap: KeyWrap,
+    /// Public key of the exporter, used to verify the bundle signature
+    pub signer: HybridPublicKey,
+}
+
+/// Export files into an encrypted, signed bundle at `output`.
+///
+/// The written file is marked read-only.
+///
+/// # Returns
+///
+/// The manifest that was sealed into the bundle
+pub async fn export(
+    paths: &[PathBuf],
+    recipient: BundleRecipient<'_>,
+    signer: &HybridSigner,
+    output: &Path,
+) -> Result<FileManifest> {
+    let transfer_id: [u8; 16] = rand::random();
+    let (key_wrap, mut key) = wrap_key(recipient, &transfer_id)?;
+
+    let mut pipeline = SendPipeline::new(transfer_id, key);
+    pipeline.prepare(paths).await?;
+    let manifest = pipeline.manifest().clone();
+
+    let file = tokio::fs::File::create(output).await.map_err(|e| {
+        ProtocolError::TransferFailed(format!("create {}: {}", output.display(), e))
+    })?;
+    let mut writer = TranscriptWriter::new(file);
+    let header = BundleHeader {
+        transfer_id,
+        key_wrap,
+        signer: signer.public_key(),
+    };
+    let written = writer.write_preamble(&header, &manifest, &key).await;
+    key.zeroize();
+    written?;
+
+    let total_chunks = manifest.total_chunks;
+    let mut index: u64 = 0;
+    for source in pipeline.source_paths() {
+        let mut reader = pipeline.open_file_reader(source).await?;
+        while let Some(raw) = reader.next_chunk().await? {
+            let is_last = index + 1 == total_chunks;
+            if let Message::Chunk { data, .. } =
+                pipeline.encrypt_chunk(&raw, index, total_chunks, is_last)?
+            {
+                writer.write_frame(&data).await?;
+            }
+            index += 1;
+        }
+    }
+    if index != total_chunks {
+        return Err(ProtocolError::TransferFailed(format!(
+            "source files changed during export: wrote {} of {} chunks",
+            index, total_chunks
+        )));
+    }
+
+    writer.sign_and_finish(signer).await?;
+
+    let mut permissions = tokio::fs::metadata(output).await?.permissions();
+    permissions.set_readonly(true);
+    tokio::fs::set_permissions(output, permissions).await?;
+
+    Ok(manifest)
+}
+
+/// Verify a bundle's signature and unpack its files into `output_dir`.
+///
+/// The bundle must be signed by `expected_signer`; the key embedded in the
+/// header only proves the file is intact, not who made it. The file is
+/// read into memory once, and the signature is checked over that buffer
+/// before the same bytes are decrypted, so the file cannot be swapped
+/// between the two steps.
+///
+/// # Returns
+///
+/// Paths of the files written
+pub async fn import(
+    bundle: &Path,
+    unlock: BundleUnlock<'_>,
+    expected_signer: &HybridPublicKey,
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let bytes = tokio::fs::read(bundle)
+        .await
+        .map_err(|e| ProtocolError::TransferFailed(format!("read {}: {}", bundle.display(), e)))?;
+    let header = verify_frames(&mut BundleReader::new(&bytes[..]), Some(expected_signer)).await?;
+    let mut key = unwrap_key(&header, unlock)?;
+
+    let mut reader = BundleReader::new(&bytes[..]);
+    reader.read_preamble().await?;
+    let _header_bytes = reader.read_frame(MAX_HEADER_LEN).await?;
+    let encrypted_manifest = reader.read_frame(MAX_MANIFEST_LEN).await?;
+
+    let manifest_bytes = match decrypt_manifest(&key, &header, &encrypted_manifest) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            key.zeroize();
+            return Err(e);
+        }
+    };
+    if FileManifest::from_bytes(&manifest_bytes)?.transfer_type == TransferType::Stream {
+        key.zeroize();
+        return Err(ProtocolError::TransferFailed(
+            "bundle holds a stream; open it with open_stream".to_string(),
+        ));
+    }
+
+    let mut pipeline = ReceivePipeline::new(header.transfer_id, output_dir, key);
+    key.zeroize();
+    let total_chunks = pipeline.process_offer(&manifest_bytes)?.total_chunks;
+
+    for index in 0..total_chunks {
+        let data = reader.read_frame(MAX_CHUNK_LEN).await?;
+        let total = (index + 1 == total_chunks).then_some(total_chunks);
+        pipeline.process_chunk(index, &data, total)?;
+    }
+
+    pipeline.finalize().await
+}
+
+/// Verify a bundle's structure and signature without decrypting it.
+///
+/// Without `expected_signer` this only shows the bundle is intact: anyone
+/// can re-sign a bundle with their own key.
+///
+/// # Returns
+///
+/// The authenticated bundle header
+pub async fn verify(
+    bundle: &Path,
+    expected_signer: Option<&HybridPublicKey>,
+) -> Result<BundleHeader> {
+    verify_frames(&mut BundleReader::open(bundle).await?, expected_signer).await
+}
+
+/// Read every frame from `reader` and check the trailing signature
+async fn verify_frames<R: AsyncRead + Unpin>(
+    reader: &mut BundleReader<R>,
+    expected_signer: Option<&HybridPublicKey>,
+) -> Result<BundleHeader> {
+    let header = reader.read_header(expected_signer).await?;
+
+    // Hash every frame up to the signature. The chunk count is inside the
+    // encrypted manifest, so frames are consumed until only the signature
+    // frame remains.
+    let _manifest = reader.read_frame(MAX_MANIFEST_LEN).await?;
+    loop {
+        let frame = reader.read_frame(MAX_CHUNK_LEN).await?;
+        if reader.at_eof().await? {
+            reader.verify_signature(&header, &frame)?;
+            return Ok(header);
+        }
+    }
+}
+
+/// Encrypt an unknown-length stream into a signed bundle written to `writer`.
+///
+/// Reads `reader` one chunk at a time, so memory use does not grow with the
+/// input. Suitable for pipes such as `tallow encrypt < plain > sealed`.
+///
+/// # Returns
+///
+/// The number of plaintext bytes read from `reader`
+pub async fn seal_stream<R, W>(
+    reader: R,
+    recipient: BundleRecipient<'_>,
+    signer: &HybridSigner,
+    writer: W,
+) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let transfer_id: [u8; 16] = rand::random();
+    let (key_wrap, mut key) = wrap_key(recipient, &transfer_id)?;
+
+    let mut pipeline = SendPipeline::new(transfer_id, key);
+    pipeline.prepare_stream("stdin")?;
+
+    let mut writer = TranscriptWriter::new(writer);
+    let header = BundleHeader {
+        transfer_id,
+        key_wrap,
+        signer: signer.public_key(),
+    };
+    let written = writer
+        .write_preamble(&header, pipeline.manifest(), &key)
+        .await;
+    key.zeroize();
+    written?;
+
+    let mut chunker = pipeline.stream_chunks(reader, signer);
+    while let Some(msg) = chunker.next_message().await? {
+        match msg {
+            Message::Chunk { data, .. } => writer.write_frame(&data).await?,
+            Message::StreamEnd { trailer, .. } => writer.write_frame(&trailer).await?,
+            _ => {}
+        }
+    }
+    let total_bytes = chunker.bytes_read();
+
+    writer.sign_and_finish(signer).await?;
+    Ok(total_bytes)
+}
+
+/// Decrypt a stream bundle from `reader`, writing the plaintext to `writer`.
+///
+/// Chunks are decrypted and written as they are read, holding back only the
+/// most recent frame. The bundle signature and stream trailer are checked at
+/// the end; on error the caller must discard whatever was written.
+///
+/// Without `expected_signer` the signature is checked against the key in
+/// the bundle's own header, which proves integrity but not origin. A
+/// warning is logged in that case.
+///
+/// # Returns
+///
+/// The verified stream trailer
+pub async fn open_stream<R, W>(
+    reader: R,
+    unlock: BundleUnlock<'_>,
+    expected_signer: Option<&HybridPublicKey>,
+    writer: W,
+) -> Result<StreamTrailer>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    if expected_signer.is_none() {
+        tracing::warn!(
+            "opening stream bundle without an expected signer; its origin is not authenticated"
+        );
+    }
+    let mut reader = BundleReader::new(reader);
+    let header = reader.read_header(expected_signer).await?;
+    let mut key = unwrap_key(&header, unlock)?;
+
+    let encrypted_manifest = reader.read_frame(MAX_MANIFEST_LEN).await?;
+    let manifest = decrypt_manifest(&key, &header, &encrypted_manifest)
+        .and_then(|bytes| FileManifest::from_bytes(&bytes));
+    let receiver = manifest
+        .and_then(|manifest| StreamReceiver::new(header.transfer_id, key, &manifest, writer));
+    key.zeroize();
+    let mut receiver = receiver?;
+
+    // The final two frames are the trailer and the signature, so each frame
+    // is only processed once the next one has been read.
+    let mut pending: Option<Vec<u8>> = None;
+    let mut index: u64 = 0;
+    loop {
+        let frame = reader.read_frame(MAX_CHUNK_LEN).await?;
+        if reader.at_eof().await? {
+            let trailer = pending.ok_or_else(|| {
+                ProtocolError::DecodingError("stream bundle has no trailer".to_string())
+            })?;
+            reader.verify_signature(&header, &frame)?;
+            return receiver.finish(&trailer, Some(&header.signer)).await;
+        }
+        if let Some(chunk) = pending.replace(frame) {
+            receiver.process_chunk(index, &chunk).await?;
+            index += 1;
+        }
+    }
+}
+
+/// Wrap a fresh bundle key for `recipient`
+fn wrap_key(recipient: BundleRecipient<'_>, transfer_id: &[u8; 16]) -> Result<(KeyWrap, [u8; 32])> {
+    match recipient {
+        BundleRecipient::PublicKey(pk) => {
+            let (ct, ss) = HybridKem::encapsulate(pk)
+                .map_err(|e| ProtocolError::TransferFailed(format!("bundle KEM failed: {}", e)))?;
+            Ok((
+                KeyWrap::Kem(ct),
+                kem_bundle_key(ss.expose_secret(), transfer_id),
+            ))
+        }
+        BundleRecipient::Password(password) => {
+            let salt: [u8; 16] = rand::random();
+            let key = password_bundle_key(password, &salt)?;
+            Ok((KeyWrap::Password { salt }, key))
+        }
+    }
+}
+
+/// Recover the bundle key from the header with `unlock`
+fn unwrap_key(header: &BundleHeader, unlock: BundleUnlock<'_>) -> Result<[u8; 32]> {
+    match (&header.key_wrap, unlock) {
+        (KeyWrap::Kem(ct), BundleUnlock::SecretKey(sk)) => {
+            let ss = HybridKem::decapsulate(sk, ct)
+                .map_err(|e| ProtocolError::TransferFailed(format!("bundle KEM failed: {}", e)))?;
+            Ok(kem_bundle_key(ss.expose_secret(), &header.transfer_id))
+        }
+        (KeyWrap::Password { salt }, BundleUnlock::Password(password)) => {
+            password_bundle_key(password, salt)
+        }
+        _ => Err(ProtocolError::TransferFailed(
+            "bundle key type does not match the supplied credential".to_string(),
+        )),
+    }
+}
+
+/// Decrypt the manifest frame
+fn decrypt_manifest(
+    key: &[u8; 32],
+    header: &BundleHeader,
+    encrypted_manifest: &[u8],
+) -> Result<Vec<u8>> {
+    tallow_crypto::symmetric::aes_decrypt(
+        key,
+        &MANIFEST_NONCE,
+        encrypted_manifest,
+        &manifest_aad(&header.transfer_id),
+    )
+    .map_err(|_| {
+        ProtocolError::TransferFailed("bundle decryption failed: wrong key or password".to_string())
+    })
+}
+
+/// Derive the bundle key from a KEM shared secret
+fn kem_bundle_key(shared_secret: &[u8; 32], transfer_id: &[u8; 16]) -> [u8; 32] {
+    let mut input = [0u8; 48];
+    input[..32].copy_from_slice(shared_secret);
+    input[32..].copy_from_slice(transfer_id);
+    let key = tallow_crypto::hash::blake3::derive_key(domain::DOMAIN_BUNDLE, &input);
+    input.zeroize();
+    key
+}
+
+/// Derive the bundle key from a password with Argon2id
+fn password_bundle_key(password: &str, salt: &[u8; 16]) -> Result<[u8; 32]> {
+    let mut derived = tallow_crypto::kdf::argon2::derive_key(password.as_bytes(), salt, 32)
+        .map_err(|e| ProtocolError::TransferFailed(format!("bundle key derivation: {}", e)))?;
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&derived);
+    derived.zeroize();
+    Ok(key)
+}
+
+/// AAD for the encrypted manifest
+fn manifest_aad(transfer_id: &[u8; 16]) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(domain::DOMAIN_BUNDLE.len() + 16);
+    aad.extend_from_slice(domain::DOMAIN_BUNDLE.as_bytes());
+    aad.extend_from_slice(transfer_id);
+    aad
+}
+
+/// Buffered writer that hashes everything it writes into the signing transcript
+struct TranscriptWriter<W> {
+    inner: tokio::io::BufWriter<W>,
+    hasher: blake3::Hasher,
+}
+
+impl<W: AsyncWrite + Unpin> TranscriptWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner: tokio::io::BufWriter::new(inner),
+            hasher: blake3::Hasher::new_derive_key(domain::DOMAIN_BUNDLE),
+        }
+    }
+
+    /// Write magic, version, header and the encrypted manifest
+    async fn write_preamble(
+        &mut self,
+        header: &BundleHeader,
+        manifest: &FileManifest,
+        key: &[u8; 32],
+    ) -> Result<()> {
+        let manifest_bytes = manifest.to_bytes()?;
+        let encrypted_manifest = tallow_crypto::symmetric::aes_encrypt(
+            key,
+            &MANIFEST_NONCE,
+            &manifest_bytes,
+            &manifest_aad(&header.transfer_id),
+        )
+        .map_err(|e| ProtocolError::TransferFailed(format!("bundle manifest encryption: {}", e)))?;
+        let header_bytes = postcard::to_stdvec(header)
+            .map_err(|e| ProtocolError::EncodingError(format!("bundle header: {}", e)))?;
+
+        self.write(&BUNDLE_MAGIC).await?;
+        self.write(&BUNDLE_VERSION.to_be_bytes()).await?;
+        self.write_frame(&header_bytes).await?;
+        self.write_frame(&encrypted_manifest).await
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.hasher.update(bytes);
+        self.inner
+            .write_all(bytes)
+            .await
+            .map_err(|e| ProtocolError::TransferFailed(format!("bundle write: {}", e)))
+    }
+
+    async fn write_frame(&mut self, bytes: &[u8]) -> Result<()> {
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| ProtocolError::EncodingError("bundle frame too large".to_string()))?;
+        self.write(&len.to_be_bytes()).await?;
+        self.write(bytes).await
+    }
+
+    /// Sign the transcript, append the signature frame and flush
+    async fn sign_and_finish(mut self, signer: &HybridSigner) -> Result<()> {
+        let digest: [u8; 32] = self.hasher.finalize().into();
+        let signature = signer
+            .sign(&digest)
+            .map_err(|e| ProtocolError::TransferFailed(format!("bundle signing failed: {}", e)))?;
+        let sig_bytes = postcard::to_stdvec(&signature)
+            .map_err(|e| ProtocolError::EncodingError(format!("bundle signature: {}", e)))?;
+        self.write_frame(&sig_bytes).await?;
+
+        self.inner
+            .flush()
+            .await
+            .map_err(|e| ProtocolError::TransferFailed(format!("bundle flush: {}", e)))
+    }
+}
+
+/// Buffered reader for bundle frames that tracks the signing transcript
+struct BundleReader<R> {
+    inner: tokio::io::BufReader<R>,
+    hasher: blake3::Hasher,
+    /// Transcript state before the most recently read frame
+    before_last: blake3::Hasher,
+}
+
+impl BundleReader<tokio::fs::File> {
+    async fn open(path: &Path) -> Result<Self> {
+        let file = tokio::fs::File::open(path).await.map_err(|e| {
+            ProtocolError::TransferFailed(format!("open {}: {}", path.display(), e))
+        })?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<R: AsyncRead + Unpin> BundleReader<R> {
+    fn new(inner: R) -> Self {
+        let hasher = blake3::Hasher::new_derive_key(domain::DOMAIN_BUNDLE);
+        Self {
+            inner: tokio::io::BufReader::new(inner),
+            before_last: hasher.clone(),
+            hasher,
+        }
+    }
+
+    /// Read the preamble and header, checking the signer if one is expected
+    async fn read_header(
+        &mut self,
+        expected_signer: Option<&HybridPublicKey>,
+    ) -> Result<BundleHeader> {
+        self.read_preamble().await?;
+        let header_bytes = self.read_frame(MAX_HEADER_LEN).await?;
+        let header: BundleHeader = postcard::from_bytes(&header_bytes)
+            .map_err(|e| ProtocolError::DecodingError(format!("bundle header: {}", e)))?;
+
+        if let Some(expected) = expected_signer {
+            let same_mldsa =
+                tallow_crypto::mem::constant_time::ct_eq(&expected.mldsa, &header.signer.mldsa);
+            let same_ed25519 =
+                tallow_crypto::mem::constant_time::ct_eq(&expected.ed25519, &header.signer.ed25519);
+            if !(same_mldsa && same_ed25519) {
+                return Err(ProtocolError::TransferFailed(
+                    "bundle signed by an unexpected key".to_string(),
+                ));
+            }
+        }
+        Ok(header)
+    }
+
+    /// Verify `frame`, the last frame read, as the signature over everything
+    /// before it
+    fn verify_signature(&self, header: &BundleHeader, frame: &[u8]) -> Result<()> {
+        if frame.len() > MAX_SIGNATURE_LEN {
+            return Err(ProtocolError::TransferFailed(
+                "bundle signature is malformed".to_string(),
+            ));
+        }
+        let signature: HybridSignature = postcard::from_bytes(frame).map_err(|_| {
+            ProtocolError::TransferFailed("bundle signature is malformed".to_string())
+        })?;
+        let digest = self.transcript_before_last_frame();
+        tallow_crypto::sig::hybrid::verify(&header.signer, &digest, &signature).map_err(|_| {
+            ProtocolError::TransferFailed("bundle signature verification failed".to_string())
+        })
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_exact(buf).await.map_err(|e| {
+            ProtocolError::DecodingError(format!("bundle truncated or unreadable: {}", e))
+        })?;
+        self.hasher.update(buf);
+        Ok(())
+    }
+
+    async fn read_preamble(&mut self) -> Result<()> {
+        let mut magic = [0u8; 8];
+        self.read_exact(&mut magic).await?;
+        if magic != BUNDLE_MAGIC {
+            return Err(ProtocolError::DecodingError(
+                "not a Tallow bundle".to_string(),
+            ));
+        }
+
+        let mut version = [0u8; 2];
+        self.read_exact(&mut version).await?;
+        let version = u16::from_be_bytes(version);
+        if version != BUNDLE_VERSION {
+            return Err(ProtocolError::VersionMismatch {
+                local: BUNDLE_VERSION as u32,
+                remote: version as u32,
+            });
+        }
+        Ok(())
+    }
+
+    async fn read_frame(&mut self, max_len: usize) -> Result<Vec<u8>> {
+        self.before_last = self.hasher.clone();
+
+        let mut len = [0u8; 4];
+        self.read_exact(&mut len).await?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > max_len {
+            return Err(ProtocolError::DecodingError(format!(
+                "bundle frame of {} bytes exceeds limit {}",
+                len, max_len
+            )));
+        }
+
+        let mut frame = vec![0u8; len];
+        self.read_exact(&mut frame).await?;
+        Ok(frame)
+    }
+
+    async fn at_eof(&mut self) -> Result<bool> {
+        use tokio::io::AsyncBufReadExt;
+        let buf = self
+            .inner
+            .fill_buf()
+            .await
+            .map_err(|e| ProtocolError::DecodingError(format!("bundle read: {}", e)))?;
+        Ok(buf.is_empty())
+    }
+
+    fn transcript_before_last_frame(&self) -> [u8; 32] {
+        self.before_last.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn write_sources(dir: &Path) -> Vec<PathBuf> {
+        let a = dir.join("notes.txt");
+        let b = dir.join("data.bin");
+        tokio::fs::write(&a, b"sneakernet payload").await.unwrap();
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        tokio::fs::write(&b, &data).await.unwrap();
+        vec![a, b]
+    }
+
+    #[tokio::test]
+    async fn test_kem_bundle_opens_only_with_matching_key() {
+        let src = tempfile::tempdir().unwrap();
+        let sources = write_sources(src.path()).await;
+        let bundle_path = src.path().join("out.tallow");
+
+        let signer = HybridSigner::keygen().unwrap();
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        let (_other_pk, other_sk) = HybridKem::keygen().unwrap();
+
+        export(
+            &sources,
+            BundleRecipient::PublicKey(&pk),
+            &signer,
+            &bundle_path,
+        )
+        .await
+        .unwrap();
+        assert!(tokio::fs::metadata(&bundle_path)
+            .await
+            .unwrap()
+            .permissions()
+            .readonly());
+
+        let wrong = tempfile::tempdir().unwrap();
+        let result = import(
+            &bundle_path,
+            BundleUnlock::SecretKey(&other_sk),
+            &signer.public_key(),
+            wrong.path(),
+        )
+        .await;
+        assert!(result.is_err(), "wrong private key must not open bundle");
+
+        let dst = tempfile::tempdir().unwrap();
+        let written = import(
+            &bundle_path,
+            BundleUnlock::SecretKey(&sk),
+            &signer.public_key(),
+            dst.path(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.len(), 2);
+        for source in &sources {
+            let name = source.file_name().unwrap();
+            let expected = tokio::fs::read(source).await.unwrap();
+            let actual = tokio::fs::read(dst.path().join(name)).await.unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampered_bundle_fails_signature() {
+        let src = tempfile::tempdir().unwrap();
+        let sources = write_sources(src.path()).await;
+        let bundle_path = src.path().join("out.tallow");
+
+        let signer = HybridSigner::keygen().unwrap();
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        export(
+            &sources,
+            BundleRecipient::PublicKey(&pk),
+            &signer,
+            &bundle_path,
+        )
+        .await
+        .unwrap();
+
+        let mut bytes = tokio::fs::read(&bundle_path).await.unwrap();
+        let mid = bytes.len() / 2;
+        bytes[mid] ^= 0x01;
+        let tampered = src.path().join("tampered.tallow");
+        tokio::fs::write(&tampered, &bytes).await.unwrap();
+
+        let err = verify(&tampered, None).await.err().unwrap();
+        assert!(err.to_string().contains("signature"));
+
+        let dst = tempfile::tempdir().unwrap();
+        let result = import(
+            &tampered,
+            BundleUnlock::SecretKey(&sk),
+            &signer.public_key(),
+            dst.path(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!dst.path().join("notes.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_signer_rejected() {
+        let src = tempfile::tempdir().unwrap();
+        let sources = write_sources(src.path()).await;
+        let bundle_path = src.path().join("out.tallow");
+
+        let signer = HybridSigner::keygen().unwrap();
+        let impostor = HybridSigner::keygen().unwrap();
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        export(
+            &sources,
+            BundleRecipient::PublicKey(&pk),
+            &signer,
+            &bundle_path,
+        )
+        .await
+        .unwrap();
+
+        assert!(verify(&bundle_path, Some(&impostor.public_key()))
+            .await
+            .is_err());
+
+        let dst = tempfile::tempdir().unwrap();
+        let result = import(
+            &bundle_path,
+            BundleUnlock::SecretKey(&sk),
+            &impostor.public_key(),
+            dst.path(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!dst.path().join("notes.txt").exists());
+    }
+
+    /// Endless-looking source that records how much has been read
+    struct CountingSource {
+        remaining: u64,
+        read: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    }
+
+    impl AsyncRead for CountingSource {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let n = (buf.remaining() as u64).min(self.remaining).min(8192) as usize;
+            let offset = self.read.load(std::sync::atomic::Ordering::SeqCst);
+            let bytes: Vec<u8> = (0..n as u64).map(|i| ((offset + i) % 251) as u8).collect();
+            buf.put_slice(&bytes);
+            self.remaining -= n as u64;
+            self.read
+                .fetch_add(n as u64, std::sync::atomic::Ordering::SeqCst);
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_roundtrip() {
+        let signer = HybridSigner::keygen().unwrap();
+        let (pk, sk) = HybridKem::keygen().unwrap();
+        let plaintext: Vec<u8> = (0..700_000u32).map(|i| (i % 97) as u8).collect();
+
+        let mut sealed = Vec::new();
+        let n = seal_stream(
+            &plaintext[..],
+            BundleRecipient::PublicKey(&pk),
+            &signer,
+            &mut sealed,
+        )
+        .await
+        .unwrap();
+        assert_eq!(n, plaintext.len() as u64);
+
+        let mut opened = Vec::new();
+        let trailer = open_stream(
+            &sealed[..],
+            BundleUnlock::SecretKey(&sk),
+            &signer.public_key(),
+            &mut opened,
+        )
+        .await
+        .unwrap();
+        assert_eq!(opened, plaintext);
+        assert_eq!(trailer.total_bytes, plaintext.len() as u64);
+
+        // Password-wrapped empty input round-trips too
+        let mut sealed = Vec::new();
+        seal_stream(
+            &b""[..],
+            BundleRecipient::Password("pipe"),
+            &signer,
+            &mut sealed,
+        )
+        .await
+        .unwrap();
+        let mut opened = Vec::new();
+        open_stream(
+            &sealed[..],
+            BundleUnlock::Password("pipe"),
+            None,
+            &mut opened,
+        )
+        .await
+        .unwrap();
+        assert!(opened.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_wrong_recipient() {
+        let signer = HybridSigner::keygen().unwrap();
+        let (pk, _sk) = HybridKem::keygen().unwrap();
+        let (_other_pk, other_sk) = HybridKem::keygen().unwrap();
+
+        let mut sealed = Vec::new();
+        seal_stream(
+            &b"for your eyes only"[..],
+            BundleRecipient::PublicKey(&pk),
+            &signer,
+            &mut sealed,
+        )
+        .await
+        .unwrap();
+
+        let mut opened = Vec::new();
+        let err = open_stream(
+            &sealed[..],
+            BundleUnlock::SecretKey(&other_sk),
+            &signer.public_key(),
+            &mut opened,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("wrong key"));
+        assert!(opened.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_truncated_fails() {
+        let signer = HybridSigner::keygen().unwrap();
+        let plaintext = vec![7u8; 600_000];
+        let mut sealed = Vec::new();
+        seal_stream(
+            &plaintext[..],
+            BundleRecipient::Password("pipe"),
+            &signer,
+            &mut sealed,
+        )
+        .await
+        .unwrap();
+
+        sealed.truncate(sealed.len() - 10);
+        let result = open_stream(
+            &sealed[..],
+            BundleUnlock::Password("pipe"),
+            None,
+            tokio::io::sink(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_does_not_buffer_input() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        const TOTAL: u64 = 16 * 1024 * 1024;
+        let signer = HybridSigner::keygen().unwrap();
+        let read = Arc::new(AtomicU64::new(0));
+        let source = CountingSource {
+            remaining: TOTAL,
+            read: read.clone(),
+        };
+
+        // A small pipe between sealer and opener: neither side can run ahead
+        // of the other by more than its buffer.
+        let (pipe_writer, pipe_reader) = tokio::io::duplex(64 * 1024);
+        let first_output_at = Arc::new(AtomicU64::new(u64::MAX));
+
+        struct Sink {
+            written: u64,
+            read: Arc<AtomicU64>,
+            first_output_at: Arc<AtomicU64>,
+        }
+        impl AsyncWrite for Sink {
+            fn poll_write(
+                mut self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                if self.written == 0 && !buf.is_empty() {
+                    self.first_output_at
+                        .store(self.read.load(Ordering::SeqCst), Ordering::SeqCst);
+                }
+                self.written += buf.len() as u64;
+                std::task::Poll::Ready(Ok(buf.len()))
+            }
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                std::task::Poll::Ready(Ok(()))
+            }
+            fn poll_shutdown(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                std::task::Poll::Ready(Ok(()))
+            }
+        }
+        let mut sink = Sink {
+            written: 0,
+            read: read.clone(),
+            first_output_at: first_output_at.clone(),
+        };
+
+        let seal = async {
+            let mut pipe_writer = pipe_writer;
+            let n = seal_stream(
+                source,
+                BundleRecipient::Password("pipe"),
+                &signer,
+                &mut pipe_writer,
+            )
+            .await;
+            pipe_writer.shutdown().await.unwrap();
+            n
+        };
+        let open = open_stream(pipe_reader, BundleUnlock::Password("pipe"), None, &mut sink);
+        let (sealed, opened) = tokio::join!(seal, open);
+
+        assert_eq!(sealed.unwrap(), TOTAL);
+        assert_eq!(opened.unwrap().total_bytes, TOTAL);
+        assert_eq!(sink.written, TOTAL);
+        assert!(
+            first_output_at.load(Ordering::SeqCst) < TOTAL / 2,
+            "plaintext must start flowing before most of the input is read"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_password_bundle_roundtrip() {
+        let src = tempfile::tempdir().unwrap();
+        let sources = write_sources(src.path()).await;
+        let bundle_path = src.path().join("out.tallow");
+
+        let signer = HybridSigner::keygen().unwrap();
+        export(
+            &sources,
+            BundleRecipient::Password("correct horse battery staple"),
+            &signer,
+            &bundle_path,
+        )
+        .await
+        .unwrap();
+
+        let dst = tempfile::tempdir().unwrap();
+        let written = import(
+            &bundle_path,
+            BundleUnlock::Password("correct horse battery staple"),
+            &signer.public_key(),
+            dst.path(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.len(), 2);
+        let actual = tokio::fs::read(dst.path().join("notes.txt")).await.unwrap();
+        assert_eq!(actual, b"sneakernet payload");
+
+        let wrong = tempfile::tempdir().unwrap();
+        assert!(import(
+            &bundle_path,
+            BundleUnlock::Password("wrong password"),
+            &signer.public_key(),
+            wrong.path(),
+        )
+        .await
+        .is_err());
+    }
+}
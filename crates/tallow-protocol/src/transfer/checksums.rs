@@ -0,0 +1,383 @@
+//! Checksum files (`SHA256SUMS`, `SHA512SUMS`, `B3SUMS`)
+//!
+//! Parses the output of `sha256sum`, `sha512sum` and `b3sum`, plus the
+//! BSD tagged form (`SHA256 (name) = digest`), and verifies files against
+//! it. The algorithm is detected from the digest length. BLAKE3 and
+//! SHA-256 digests are both 32 bytes, so an untagged 64-digit digest is
+//! read as SHA-256 unless the checksum file's name says BLAKE3 (`B3SUMS`,
+//! `*.b3`, `*blake3*`).
+
+use crate::{ProtocolError, Result};
+use sha2::Digest;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Read buffer size for hashing files
+const READ_BUF_SIZE: usize = 64 * 1024;
+
+/// Digest algorithm of a checksum entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    /// BLAKE3 (32-byte digest)
+    Blake3,
+    /// SHA-256 (32-byte digest)
+    Sha256,
+    /// SHA-512 (64-byte digest)
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Digest length in bytes
+    pub fn digest_len(self) -> usize {
+        match self {
+            Self::Blake3 | Self::Sha256 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+
+    /// Name used in BSD-style tagged lines
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blake3 => "BLAKE3",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag.to_ascii_uppercase().replace('-', "").as_str() {
+            "BLAKE3" | "B3" => Some(Self::Blake3),
+            "SHA256" => Some(Self::Sha256),
+            "SHA512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Hash everything `reader` yields
+    pub fn digest_reader(self, mut reader: impl Read) -> Result<Vec<u8>> {
+        let mut buf = vec![0u8; READ_BUF_SIZE];
+        match self {
+            Self::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                loop {
+                    let n = reader.read(&mut buf)?;
+                    if n == 0 {
+                        break;
+                    }
+                    hasher.update(&buf[..n]);
+                }
+                Ok(hasher.finalize().as_bytes().to_vec())
+            }
+            Self::Sha256 => digest_with(sha2::Sha256::new(), reader, &mut buf),
+            Self::Sha512 => digest_with(sha2::Sha512::new(), reader, &mut buf),
+        }
+    }
+}
+
+fn digest_with<D: Digest>(mut hasher: D, mut reader: impl Read, buf: &mut [u8]) -> Result<Vec<u8>> {
+    loop {
+        let n = reader.read(buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// One line of a checksum file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// File name as listed, with any leading `./` removed
+    pub path: String,
+    /// Digest algorithm
+    pub algorithm: ChecksumAlgorithm,
+    /// Expected digest
+    pub digest: Vec<u8>,
+}
+
+/// A parsed checksum file
+#[derive(Debug, Clone, Default)]
+pub struct ChecksumList {
+    entries: Vec<ChecksumEntry>,
+}
+
+impl ChecksumList {
+    /// Load a checksum file, using its name to resolve 32-byte digests
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ProtocolError::Io(std::io::Error::new(
+                e.kind(),
+                format!("{}: {}", path.display(), e),
+            ))
+        })?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let short = if name.starts_with("b3sum") || name.ends_with(".b3") || name.contains("blake3")
+        {
+            ChecksumAlgorithm::Blake3
+        } else {
+            ChecksumAlgorithm::Sha256
+        };
+        Self::parse(&content, short)
+    }
+
+    /// Parse checksum file contents
+    ///
+    /// `short` is the algorithm assumed for untagged 32-byte digests.
+    pub fn parse(content: &str, short: ChecksumAlgorithm) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_line(line, short).ok_or_else(|| {
+                ProtocolError::DecodingError(format!(
+                    "checksum file line {}: not a recognised checksum line",
+                    i + 1
+                ))
+            })?;
+            entries.push(entry);
+        }
+        if entries.is_empty() {
+            return Err(ProtocolError::DecodingError(
+                "checksum file lists no files".to_string(),
+            ));
+        }
+        Ok(Self { entries })
+    }
+
+    /// All entries, in file order
+    pub fn entries(&self) -> &[ChecksumEntry] {
+        &self.entries
+    }
+
+    /// Entry for a file at `relative` (a path relative to the listing)
+    ///
+    /// Falls back to matching the bare file name when exactly one entry
+    /// has it, so a listing made in the sender's directory still matches.
+    pub fn find(&self, relative: &Path) -> Option<&ChecksumEntry> {
+        let wanted = normalize(&relative.to_string_lossy().replace('\\', "/"));
+        if let Some(entry) = self.entries.iter().find(|e| e.path == wanted) {
+            return Some(entry);
+        }
+        let file_name = relative.file_name()?.to_string_lossy();
+        let mut by_name = self
+            .entries
+            .iter()
+            .filter(|e| e.path.rsplit('/').next() == Some(file_name.as_ref()));
+        match (by_name.next(), by_name.next()) {
+            (Some(entry), None) => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Verify `files` (under `base`) against the listing
+    ///
+    /// Files that are not listed are skipped. Fails if any listed file
+    /// differs, naming every mismatching file, or if none of `files` is
+    /// listed at all. Returns the number of files verified.
+    pub fn verify_files(&self, base: &Path, files: &[PathBuf]) -> Result<usize> {
+        let mut verified = 0;
+        let mut mismatched = Vec::new();
+        for file in files {
+            let relative = file.strip_prefix(base).unwrap_or(file);
+            let Some(entry) = self.find(relative) else {
+                continue;
+            };
+            let actual = entry.algorithm.digest_reader(std::fs::File::open(file)?)?;
+            if actual == entry.digest {
+                verified += 1;
+            } else {
+                mismatched.push(relative.display().to_string());
+            }
+        }
+
+        if !mismatched.is_empty() {
+            return Err(ProtocolError::TransferFailed(format!(
+                "checksum mismatch for {}",
+                mismatched.join(", ")
+            )));
+        }
+        if verified == 0 {
+            return Err(ProtocolError::TransferFailed(
+                "none of the received files are listed in the checksum file".to_string(),
+            ));
+        }
+        Ok(verified)
+    }
+}
+
+/// Parse `digest  name`, `digest *name` or `ALGO (name) = digest`
+fn parse_line(line: &str, short: ChecksumAlgorithm) -> Option<ChecksumEntry> {
+    // GNU tools prefix a line with '\' when the name contains escapes
+    let (line, escaped) = match line.strip_prefix('\\') {
+        Some(rest) => (rest, true),
+        None => (line, false),
+    };
+
+    if let Some((tag, rest)) = line.split_once(" (") {
+        if let Some(algorithm) = ChecksumAlgorithm::from_tag(tag) {
+            let (name, digest) = rest.rsplit_once(") = ")?;
+            let digest = decode_digest(digest.trim())?;
+            if digest.len() != algorithm.digest_len() {
+                return None;
+            }
+            return Some(ChecksumEntry {
+                path: normalize(&unescape(name, escaped)),
+                algorithm,
+                digest,
+            });
+        }
+    }
+
+    let (digest, name) = line.split_once(' ')?;
+    let name = name.strip_prefix([' ', '*'])?;
+    let digest = decode_digest(digest)?;
+    let algorithm = match digest.len() {
+        32 => short,
+        64 => ChecksumAlgorithm::Sha512,
+        _ => return None,
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some(ChecksumEntry {
+        path: normalize(&unescape(name, escaped)),
+        algorithm,
+        digest,
+    })
+}
+
+fn decode_digest(digest: &str) -> Option<Vec<u8>> {
+    hex::decode(digest).ok().filter(|d| !d.is_empty())
+}
+
+fn unescape(name: &str, escaped: bool) -> String {
+    if escaped {
+        name.replace("\\n", "\n").replace("\\\\", "\\")
+    } else {
+        name.to_string()
+    }
+}
+
+fn normalize(path: &str) -> String {
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"hello checksum\n";
+
+    fn digest(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+        hex::encode(algorithm.digest_reader(data).unwrap())
+    }
+
+    #[test]
+    fn test_parse_each_format() {
+        let sha256 = digest(ChecksumAlgorithm::Sha256, DATA);
+        let sha512 = digest(ChecksumAlgorithm::Sha512, DATA);
+        let b3 = digest(ChecksumAlgorithm::Blake3, DATA);
+        assert_eq!(sha256.len(), 64);
+        assert_eq!(sha512.len(), 128);
+
+        let list = ChecksumList::parse(
+            &format!("{}  ./a.txt\n{} *dir/b.bin\n", sha256, sha512),
+            ChecksumAlgorithm::Sha256,
+        )
+        .unwrap();
+        assert_eq!(list.entries()[0].algorithm, ChecksumAlgorithm::Sha256);
+        assert_eq!(list.entries()[0].path, "a.txt");
+        assert_eq!(list.entries()[1].algorithm, ChecksumAlgorithm::Sha512);
+        assert_eq!(list.entries()[1].path, "dir/b.bin");
+
+        // b3sum output is only distinguishable by the listing's name or tag
+        let list =
+            ChecksumList::parse(&format!("{}  a.txt\n", b3), ChecksumAlgorithm::Blake3).unwrap();
+        assert_eq!(list.entries()[0].algorithm, ChecksumAlgorithm::Blake3);
+
+        let list = ChecksumList::parse(
+            &format!("# comment\nBLAKE3 (a (1).txt) = {}\n", b3),
+            ChecksumAlgorithm::Sha256,
+        )
+        .unwrap();
+        assert_eq!(list.entries()[0].algorithm, ChecksumAlgorithm::Blake3);
+        assert_eq!(list.entries()[0].path, "a (1).txt");
+        assert_eq!(list.entries()[0].digest, hex::decode(&b3).unwrap());
+
+        assert!(ChecksumList::parse("abcd  a.txt\n", ChecksumAlgorithm::Sha256).is_err());
+        assert!(ChecksumList::parse("not a checksum\n", ChecksumAlgorithm::Sha256).is_err());
+        assert!(ChecksumList::parse("", ChecksumAlgorithm::Sha256).is_err());
+    }
+
+    #[test]
+    fn test_load_detects_blake3_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = format!("{}  a.txt\n", digest(ChecksumAlgorithm::Blake3, DATA));
+        std::fs::write(dir.path().join("B3SUMS"), &line).unwrap();
+        std::fs::write(dir.path().join("SHA256SUMS"), &line).unwrap();
+
+        let b3 = ChecksumList::load(&dir.path().join("B3SUMS")).unwrap();
+        assert_eq!(b3.entries()[0].algorithm, ChecksumAlgorithm::Blake3);
+        let sha = ChecksumList::load(&dir.path().join("SHA256SUMS")).unwrap();
+        assert_eq!(sha.entries()[0].algorithm, ChecksumAlgorithm::Sha256);
+    }
+
+    #[test]
+    fn test_verify_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("dir")).unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("dir").join("b.bin");
+        let c = dir.path().join("unlisted");
+        std::fs::write(&a, DATA).unwrap();
+        std::fs::write(&b, b"other").unwrap();
+        std::fs::write(&c, b"x").unwrap();
+
+        let list = ChecksumList::parse(
+            &format!(
+                "{}  a.txt\n{}  dir/b.bin\n",
+                digest(ChecksumAlgorithm::Sha256, DATA),
+                digest(ChecksumAlgorithm::Sha512, b"other")
+            ),
+            ChecksumAlgorithm::Sha256,
+        )
+        .unwrap();
+        assert_eq!(
+            list.verify_files(dir.path(), &[a, b, c.clone()]).unwrap(),
+            2
+        );
+
+        // Nothing listed is not a pass
+        assert!(list.verify_files(dir.path(), &[c]).is_err());
+    }
+
+    #[test]
+    fn test_mismatch_names_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.txt");
+        let bad = dir.path().join("bad.txt");
+        std::fs::write(&good, DATA).unwrap();
+        std::fs::write(&bad, b"tampered").unwrap();
+
+        let sum = digest(ChecksumAlgorithm::Sha256, DATA);
+        let list = ChecksumList::parse(
+            &format!("{}  good.txt\n{}  bad.txt\n", sum, sum),
+            ChecksumAlgorithm::Sha256,
+        )
+        .unwrap();
+        let err = list.verify_files(dir.path(), &[good, bad]).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("bad.txt"), "{}", message);
+        assert!(!message.contains("good.txt"), "{}", message);
+    }
+}
@@ -3,8 +3,12 @@
 //! Each chunk is encrypted with a counter-based nonce and AAD
 //! binding the chunk index to prevent reordering attacks.
 
+use crate::{ProtocolError, Result};
 use serde::{Deserialize, Serialize};
 
+/// AEAD protecting every chunk, as reported in transfer history
+pub const CHUNK_CIPHER: &str = "AES-256-GCM";
+
 /// Default chunk size (256 KB)
 ///
 /// Larger chunks reduce per-chunk overhead (AAD, nonce, AES-GCM tag) and
@@ -98,6 +102,40 @@ pub fn build_chunk_nonce(chunk_index: u64) -> [u8; 12] {
     nonce
 }
 
+/// Length of the CRC32C trailer on a checksummed chunk
+pub const CHUNK_CHECKSUM_LEN: usize = 4;
+
+/// Append the CRC32C of `sealed`, a chunk's AEAD output, to it
+///
+/// The checksum is a debugging aid, not a security control: the AEAD tag
+/// alone authenticates the chunk. Checking the CRC first lets a receiver
+/// report corruption as such, at a known chunk, instead of as a failed
+/// decryption. CRC32C uses the SSE4.2/ARMv8 instruction where available.
+pub fn append_chunk_checksum(mut sealed: Vec<u8>) -> Vec<u8> {
+    let crc = crc32c::crc32c(&sealed);
+    sealed.extend_from_slice(&crc.to_be_bytes());
+    sealed
+}
+
+/// Check and strip the CRC32C trailer of chunk `index`
+///
+/// Returns the AEAD output to decrypt, or
+/// [`ProtocolError::ChunkChecksumMismatch`] if the trailer is missing or
+/// does not match.
+pub fn verify_chunk_checksum(data: &[u8], index: u64) -> Result<&[u8]> {
+    let split = data
+        .len()
+        .checked_sub(CHUNK_CHECKSUM_LEN)
+        .ok_or(ProtocolError::ChunkChecksumMismatch { index })?;
+    let (sealed, trailer) = data.split_at(split);
+    let mut expected = [0u8; CHUNK_CHECKSUM_LEN];
+    expected.copy_from_slice(trailer);
+    if crc32c::crc32c(sealed) != u32::from_be_bytes(expected) {
+        return Err(ProtocolError::ChunkChecksumMismatch { index });
+    }
+    Ok(sealed)
+}
+
 /// Split data into chunks
 pub fn split_into_chunks(data: &[u8], chunk_size: usize) -> Vec<Chunk> {
     data.chunks(chunk_size)
@@ -151,4 +189,25 @@ mod tests {
         assert_eq!(chunks[1].data.len(), 64);
         assert_eq!(chunks[2].data.len(), 22);
     }
+
+    #[test]
+    fn test_chunk_checksum_roundtrip() {
+        let sealed = b"ciphertext and tag".to_vec();
+        let framed = append_chunk_checksum(sealed.clone());
+        assert_eq!(framed.len(), sealed.len() + CHUNK_CHECKSUM_LEN);
+        // Known CRC32C check value ("123456789" -> 0xE3069283)
+        assert_eq!(
+            &append_chunk_checksum(b"123456789".to_vec())[9..],
+            &[0xE3, 0x06, 0x92, 0x83]
+        );
+        assert_eq!(verify_chunk_checksum(&framed, 7).unwrap(), &sealed[..]);
+
+        let mut corrupted = framed.clone();
+        corrupted[3] ^= 0x40;
+        assert!(matches!(
+            verify_chunk_checksum(&corrupted, 7),
+            Err(ProtocolError::ChunkChecksumMismatch { index: 7 })
+        ));
+        assert!(verify_chunk_checksum(&framed[..3], 7).is_err());
+    }
 }
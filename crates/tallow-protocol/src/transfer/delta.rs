@@ -0,0 +1,596 @@
+//! rsync-style differential transfer of a single file
+//!
+//! [`sync`](super::sync) decides *which* files changed; this module shrinks
+//! what is sent for each of them. The receiver splits its existing copy
+//! into fixed-size blocks and sends a [`FileSignature`]: a weak rolling
+//! checksum and a BLAKE3 hash per block. The sender slides a window over
+//! the new version, looking each position's rolling checksum up in the
+//! signature, and produces a [`Delta`] of block copies and literal bytes.
+//! A region that moved or survived an insertion still matches, since the
+//! window is tried at every byte offset rather than only on block
+//! boundaries.
+//!
+//! When most of the file is literal data anyway, [`compute_delta`] gives up
+//! early and the caller sends the file in full instead.
+
+use crate::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Smallest block size accepted in a signature
+pub const MIN_BLOCK_SIZE: u32 = 512;
+
+/// Largest block size accepted in a signature
+pub const MAX_BLOCK_SIZE: u32 = 1024 * 1024;
+
+/// Longest literal run carried by one [`DeltaOp::Literal`]
+const MAX_LITERAL_RUN: usize = 64 * 1024;
+
+/// Give up on a delta once literals exceed this share of the new file (percent)
+const FALLBACK_LITERAL_PERCENT: u64 = 75;
+
+/// Read granularity when scanning the new file
+const READ_SIZE: usize = 64 * 1024;
+
+/// rsync's weak checksum: two 16-bit sums that can slide one byte at a time
+#[derive(Debug, Clone, Copy)]
+struct RollingChecksum {
+    a: u32,
+    b: u32,
+    len: u32,
+}
+
+impl RollingChecksum {
+    fn new(block: &[u8]) -> Self {
+        let len = block.len() as u32;
+        let (mut a, mut b) = (0u32, 0u32);
+        for (i, &byte) in block.iter().enumerate() {
+            a = a.wrapping_add(byte as u32);
+            b = b.wrapping_add((len - i as u32).wrapping_mul(byte as u32));
+        }
+        Self { a, b, len }
+    }
+
+    /// Slide the window: drop `out` from the front, append `incoming`
+    fn roll(&mut self, out: u8, incoming: u8) {
+        self.a = self
+            .a
+            .wrapping_sub(out as u32)
+            .wrapping_add(incoming as u32);
+        self.b = self
+            .b
+            .wrapping_sub(self.len.wrapping_mul(out as u32))
+            .wrapping_add(self.a);
+    }
+
+    fn digest(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+/// Checksums of one block of the receiver's copy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSignature {
+    /// Rolling checksum
+    pub weak: u32,
+    /// BLAKE3 hash of the block
+    pub strong: [u8; 32],
+}
+
+/// Block checksums of the receiver's existing copy of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSignature {
+    /// Block size in bytes; every block but the last is this long
+    pub block_size: u32,
+    /// Length of the file the signature was computed over
+    pub file_len: u64,
+    /// One entry per block, in file order
+    pub blocks: Vec<BlockSignature>,
+}
+
+impl FileSignature {
+    /// Block size for a file of `len` bytes: about √len, within bounds
+    pub fn block_size_for(len: u64) -> u32 {
+        let root = (len as f64).sqrt() as u64;
+        let rounded = root.div_ceil(1024) * 1024;
+        rounded.clamp(MIN_BLOCK_SIZE as u64, 128 * 1024) as u32
+    }
+
+    /// Checksum every block read from `reader`
+    pub fn compute<R: Read>(mut reader: R, block_size: u32) -> Result<Self> {
+        check_block_size(block_size)?;
+        let mut block = vec![0u8; block_size as usize];
+        let mut blocks = Vec::new();
+        let mut file_len = 0u64;
+        loop {
+            let n = read_full(&mut reader, &mut block)?;
+            if n == 0 {
+                break;
+            }
+            blocks.push(BlockSignature {
+                weak: RollingChecksum::new(&block[..n]).digest(),
+                strong: blake3::hash(&block[..n]).into(),
+            });
+            file_len += n as u64;
+            if n < block.len() {
+                break;
+            }
+        }
+        Ok(Self {
+            block_size,
+            file_len,
+            blocks,
+        })
+    }
+
+    /// Check a signature received from a peer before using it
+    pub fn validate(&self) -> Result<()> {
+        check_block_size(self.block_size)?;
+        if self.blocks.len() as u64 != self.file_len.div_ceil(self.block_size as u64) {
+            return Err(ProtocolError::DecodingError(format!(
+                "signature has {} blocks for a {}-byte file",
+                self.blocks.len(),
+                self.file_len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Length of block `index`
+    fn block_len(&self, index: usize) -> usize {
+        let start = index as u64 * self.block_size as u64;
+        (self.file_len - start).min(self.block_size as u64) as usize
+    }
+}
+
+/// One instruction for rebuilding the new file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeltaOp {
+    /// Copy `count` consecutive blocks of the receiver's copy, from `start`
+    Copy {
+        /// First block index
+        start: u32,
+        /// Number of blocks
+        count: u32,
+    },
+    /// Bytes the receiver does not have
+    Literal(Vec<u8>),
+}
+
+/// Instructions that turn the receiver's copy into the sender's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delta {
+    /// Block size of the signature the delta was computed against
+    pub block_size: u32,
+    /// Length of the rebuilt file
+    pub target_len: u64,
+    /// BLAKE3 hash of the rebuilt file
+    pub target_hash: [u8; 32],
+    /// Copy and literal instructions, in output order
+    pub ops: Vec<DeltaOp>,
+}
+
+impl Delta {
+    /// Bytes carried as literals
+    pub fn literal_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                DeltaOp::Literal(data) => data.len() as u64,
+                DeltaOp::Copy { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Serialize the delta to bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        postcard::to_stdvec(self)
+            .map_err(|e| ProtocolError::EncodingError(format!("delta serialize failed: {}", e)))
+    }
+
+    /// Deserialize a delta from bytes
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        postcard::from_bytes(data)
+            .map_err(|e| ProtocolError::DecodingError(format!("delta decode failed: {}", e)))
+    }
+}
+
+/// Compute the delta from `signature`'s file to the `new_len` bytes in `reader`
+///
+/// Returns `None` when more than 75% of the new file would go over as
+/// literals; it is then better sent whole. Scanning stops as soon as that
+/// is clear, so a completely rewritten file is never buffered in full.
+pub fn compute_delta<R: Read>(
+    signature: &FileSignature,
+    mut reader: R,
+    new_len: u64,
+) -> Result<Option<Delta>> {
+    signature.validate()?;
+    let block_size = signature.block_size as usize;
+    let budget = new_len.saturating_mul(FALLBACK_LITERAL_PERCENT) / 100;
+
+    // Only full-size blocks can match mid-file; a short last block is
+    // tried once the remaining input is exactly its length
+    let full_blocks = (signature.file_len / signature.block_size as u64) as usize;
+    let mut by_weak: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (index, block) in signature.blocks.iter().take(full_blocks).enumerate() {
+        by_weak.entry(block.weak).or_default().push(index as u32);
+    }
+    let tail = (full_blocks < signature.blocks.len()).then(|| {
+        let index = signature.blocks.len() - 1;
+        (index, signature.block_len(index))
+    });
+
+    let mut scanner = Scanner {
+        reader: &mut reader,
+        buf: Vec::new(),
+        eof: false,
+        hasher: blake3::Hasher::new(),
+        read_len: 0,
+    };
+    let mut ops: Vec<DeltaOp> = Vec::new();
+    let mut literal_total = 0u64;
+    // Window start, and start of the pending literal run, within `buf`
+    let mut pos = 0usize;
+    let mut literal_start = 0usize;
+    let mut rolling: Option<RollingChecksum> = None;
+
+    loop {
+        scanner.fill(pos + block_size + 1)?;
+        let available = scanner.buf.len() - pos;
+        if available == 0 {
+            break;
+        }
+
+        let matched = if available >= block_size {
+            let window = &scanner.buf[pos..pos + block_size];
+            let weak = rolling
+                .get_or_insert_with(|| RollingChecksum::new(window))
+                .digest();
+            by_weak.get(&weak).and_then(|candidates| {
+                let strong: [u8; 32] = blake3::hash(window).into();
+                candidates
+                    .iter()
+                    .find(|&&i| signature.blocks[i as usize].strong == strong)
+                    .map(|&i| (i, block_size))
+            })
+        } else {
+            tail.filter(|&(_, len)| len == available && scanner.eof)
+                .filter(|&(i, _)| {
+                    let strong: [u8; 32] = blake3::hash(&scanner.buf[pos..]).into();
+                    signature.blocks[i].strong == strong
+                })
+                .map(|(i, len)| (i as u32, len))
+        };
+
+        if let Some((index, len)) = matched {
+            literal_total += flush_literal(&mut ops, &scanner.buf[literal_start..pos]);
+            push_copy(&mut ops, index);
+            pos += len;
+            literal_start = pos;
+            rolling = None;
+        } else if available > block_size {
+            if let Some(r) = rolling.as_mut() {
+                r.roll(scanner.buf[pos], scanner.buf[pos + block_size]);
+            }
+            pos += 1;
+        } else if let Some((_, len)) = tail.filter(|&(_, len)| len < available) {
+            // Only the last block can still match, and only at the very end
+            pos = scanner.buf.len() - len;
+            rolling = None;
+        } else {
+            // Fewer than a block left and no tail match: the rest is literal
+            pos = scanner.buf.len();
+            rolling = None;
+        }
+
+        if pos - literal_start >= MAX_LITERAL_RUN {
+            literal_total += flush_literal(&mut ops, &scanner.buf[literal_start..pos]);
+            literal_start = pos;
+        }
+        if literal_total + (pos - literal_start) as u64 > budget {
+            return Ok(None);
+        }
+        // Drop bytes already turned into ops
+        if literal_start >= READ_SIZE {
+            scanner.buf.drain(..literal_start);
+            pos -= literal_start;
+            literal_start = 0;
+        }
+    }
+    flush_literal(&mut ops, &scanner.buf[literal_start..pos]);
+
+    if scanner.read_len != new_len {
+        return Err(ProtocolError::TransferFailed(format!(
+            "file changed while computing delta: expected {} bytes, read {}",
+            new_len, scanner.read_len
+        )));
+    }
+    Ok(Some(Delta {
+        block_size: signature.block_size,
+        target_len: scanner.read_len,
+        target_hash: scanner.hasher.finalize().into(),
+        ops,
+    }))
+}
+
+/// Rebuild the new file from `basis` (the receiver's copy) and `delta`
+///
+/// The output is checked against the delta's length and hash, so a
+/// corrupted delta or a basis that changed since its signature was taken
+/// is reported rather than silently producing a wrong file.
+pub fn apply_delta<B: Read + Seek, W: Write>(
+    delta: &Delta,
+    basis: &mut B,
+    output: &mut W,
+) -> Result<u64> {
+    check_block_size(delta.block_size)?;
+    let block_size = delta.block_size as u64;
+    let basis_len = basis.seek(SeekFrom::End(0))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut written = 0u64;
+    let mut buf = vec![0u8; READ_SIZE];
+
+    for op in &delta.ops {
+        match op {
+            DeltaOp::Literal(data) => {
+                output.write_all(data)?;
+                hasher.update(data);
+                written += data.len() as u64;
+            }
+            DeltaOp::Copy { start, count } => {
+                let offset = *start as u64 * block_size;
+                let end = (*start as u64 + *count as u64) * block_size;
+                if *count == 0 || offset >= basis_len {
+                    return Err(ProtocolError::TransferFailed(format!(
+                        "delta copies blocks {}+{} past the end of the basis file",
+                        start, count
+                    )));
+                }
+                let mut remaining = end.min(basis_len) - offset;
+                basis.seek(SeekFrom::Start(offset))?;
+                while remaining > 0 {
+                    let n = remaining.min(buf.len() as u64) as usize;
+                    basis.read_exact(&mut buf[..n])?;
+                    output.write_all(&buf[..n])?;
+                    hasher.update(&buf[..n]);
+                    remaining -= n as u64;
+                }
+                written += end.min(basis_len) - offset;
+            }
+        }
+        if written > delta.target_len {
+            return Err(ProtocolError::TransferFailed(
+                "delta output exceeds the declared length".to_string(),
+            ));
+        }
+    }
+
+    let hash: [u8; 32] = hasher.finalize().into();
+    if written != delta.target_len || hash != delta.target_hash {
+        return Err(ProtocolError::TransferFailed(
+            "file rebuilt from delta does not match the sender's".to_string(),
+        ));
+    }
+    Ok(written)
+}
+
+/// Buffered view of the new file, hashing everything it reads
+struct Scanner<'a, R> {
+    reader: &'a mut R,
+    buf: Vec<u8>,
+    eof: bool,
+    hasher: blake3::Hasher,
+    read_len: u64,
+}
+
+impl<R: Read> Scanner<'_, R> {
+    /// Read until `buf` holds at least `len` bytes or the input ends
+    fn fill(&mut self, len: usize) -> Result<()> {
+        let mut chunk = [0u8; 8 * 1024];
+        while self.buf.len() < len && !self.eof {
+            let n = self.reader.read(&mut chunk)?;
+            if n == 0 {
+                self.eof = true;
+            } else {
+                self.buf.extend_from_slice(&chunk[..n]);
+                self.hasher.update(&chunk[..n]);
+                self.read_len += n as u64;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Append `data` as literal ops, returning its length
+fn flush_literal(ops: &mut Vec<DeltaOp>, data: &[u8]) -> u64 {
+    for run in data.chunks(MAX_LITERAL_RUN) {
+        ops.push(DeltaOp::Literal(run.to_vec()));
+    }
+    data.len() as u64
+}
+
+/// Append a copy of block `index`, extending the previous copy if adjacent
+fn push_copy(ops: &mut Vec<DeltaOp>, index: u32) {
+    if let Some(DeltaOp::Copy { start, count }) = ops.last_mut() {
+        if *start + *count == index {
+            *count += 1;
+            return;
+        }
+    }
+    ops.push(DeltaOp::Copy {
+        start: index,
+        count: 1,
+    });
+}
+
+fn check_block_size(block_size: u32) -> Result<()> {
+    if !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size) {
+        return Err(ProtocolError::DecodingError(format!(
+            "block size {} outside {}..={}",
+            block_size, MIN_BLOCK_SIZE, MAX_BLOCK_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Fill `buf` from `reader`, stopping early only at end of input
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const BLOCK: u32 = 4096;
+
+    /// Deterministic incompressible bytes
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Delta from `old` to `new`, its encoded size, and the rebuilt file
+    fn roundtrip(old: &[u8], new: &[u8]) -> Option<(Delta, usize, Vec<u8>)> {
+        let signature = FileSignature::compute(old, BLOCK).unwrap();
+        let delta = compute_delta(&signature, new, new.len() as u64).unwrap()?;
+        let wire = delta.to_bytes().unwrap();
+        let decoded = Delta::from_bytes(&wire).unwrap();
+        let mut rebuilt = Vec::new();
+        apply_delta(&decoded, &mut Cursor::new(old), &mut rebuilt).unwrap();
+        Some((delta, wire.len(), rebuilt))
+    }
+
+    #[test]
+    fn test_rolling_checksum_matches_fresh() {
+        let data = noise(10_000, 3);
+        let mut rolling = RollingChecksum::new(&data[..1000]);
+        for start in 1..=9000 {
+            rolling.roll(data[start - 1], data[start + 999]);
+            assert_eq!(
+                rolling.digest(),
+                RollingChecksum::new(&data[start..start + 1000]).digest()
+            );
+        }
+    }
+
+    #[test]
+    fn test_small_edit_sends_only_changed_blocks() {
+        let old = noise(4 * 1024 * 1024 + 123, 1);
+        let mut new = old.clone();
+        // Overwrite 100 bytes in one place and insert 10 in another
+        new[1_000_000..1_000_100].copy_from_slice(&noise(100, 2));
+        new.splice(3_000_000..3_000_000, noise(10, 4));
+
+        let (delta, wire_len, rebuilt) = roundtrip(&old, &new).unwrap();
+        assert_eq!(rebuilt, new);
+        // At most two blocks per edit go over as literals
+        assert!(
+            delta.literal_bytes() <= 4 * BLOCK as u64,
+            "{} literal bytes",
+            delta.literal_bytes()
+        );
+        let signature_len = postcard::to_stdvec(&FileSignature::compute(&old[..], BLOCK).unwrap())
+            .unwrap()
+            .len();
+        assert!(
+            wire_len + signature_len < new.len() / 50,
+            "{} + {} bytes for a {}-byte file",
+            wire_len,
+            signature_len,
+            new.len()
+        );
+    }
+
+    #[test]
+    fn test_rewritten_file_falls_back_to_full() {
+        let old = noise(256 * 1024, 5);
+        let new = noise(256 * 1024, 6);
+        assert!(roundtrip(&old, &new).is_none());
+
+        // With nothing to compare against, everything is literal
+        assert!(roundtrip(&[], &new).is_none());
+    }
+
+    #[test]
+    fn test_appended_and_truncated_files_rebuild_exactly() {
+        let base = noise(100_000, 7);
+
+        let mut appended = base.clone();
+        appended.extend(noise(5_000, 8));
+        let (delta, _, rebuilt) = roundtrip(&base, &appended).unwrap();
+        assert_eq!(rebuilt, appended);
+        assert!(delta.literal_bytes() < 2 * BLOCK as u64 + 5_000);
+
+        // The short last block matches at the end of the new file
+        let (delta, _, rebuilt) = roundtrip(&base, &base).unwrap();
+        assert_eq!(rebuilt, base);
+        assert_eq!(delta.literal_bytes(), 0);
+        assert_eq!(
+            delta.ops,
+            vec![DeltaOp::Copy {
+                start: 0,
+                count: 25
+            }]
+        );
+
+        let truncated = &base[..60_000];
+        let (_, _, rebuilt) = roundtrip(&base, truncated).unwrap();
+        assert_eq!(rebuilt, truncated);
+    }
+
+    #[test]
+    fn test_bad_delta_or_basis_rejected() {
+        let old = noise(50_000, 9);
+        let mut new = old.clone();
+        new[10] ^= 0xff;
+        let signature = FileSignature::compute(&old[..], BLOCK).unwrap();
+        let delta = compute_delta(&signature, &new[..], new.len() as u64)
+            .unwrap()
+            .unwrap();
+
+        // Basis changed since the signature was taken
+        let mut other = old.clone();
+        other[20_000] ^= 0xff;
+        let err = apply_delta(&delta, &mut Cursor::new(other), &mut Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+
+        // Copy past the end of the basis
+        let mut bogus = delta.clone();
+        bogus.ops.push(DeltaOp::Copy {
+            start: 1000,
+            count: 1,
+        });
+        assert!(apply_delta(&bogus, &mut Cursor::new(&old), &mut Vec::new()).is_err());
+
+        // Inconsistent signature from a peer
+        let mut forged = signature.clone();
+        forged.blocks.pop();
+        assert!(forged.validate().is_err());
+        assert!(FileSignature::compute(&old[..], 16).is_err());
+    }
+
+    #[test]
+    fn test_block_size_for() {
+        assert_eq!(FileSignature::block_size_for(0), MIN_BLOCK_SIZE);
+        assert_eq!(FileSignature::block_size_for(100 * 1024 * 1024), 10 * 1024);
+        assert_eq!(FileSignature::block_size_for(u64::MAX / 2), 128 * 1024);
+    }
+}
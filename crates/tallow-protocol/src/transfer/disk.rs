@@ -0,0 +1,392 @@
+//! Disk write strategy for received files
+//!
+//! Output files are preallocated to their manifest size before any chunk is
+//! written, and can optionally be opened with `O_DIRECT` to bypass the page
+//! cache. Both are best effort: platforms or filesystems without support
+//! fall back to ordinary buffered writes.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Alignment required for `O_DIRECT` buffers, offsets and lengths
+const DIRECT_IO_ALIGN: usize = 4096;
+
+/// Size of the aligned staging buffer used for direct writes
+const DIRECT_IO_BUFFER: usize = 1024 * 1024;
+
+/// How received files are written to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteConfig {
+    /// Reserve the full file size (from the manifest) before writing
+    pub preallocate: bool,
+    /// Open output files with `O_DIRECT` where supported
+    pub direct_io: bool,
+}
+
+impl Default for WriteConfig {
+    fn default() -> Self {
+        Self {
+            preallocate: true,
+            direct_io: false,
+        }
+    }
+}
+
+/// Whether an I/O error means the destination ran out of space
+///
+/// `WriteZero` covers writers that stop accepting bytes without an OS
+/// error (e.g. a full fixed-size device).
+pub fn is_disk_full(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::StorageFull | io::ErrorKind::WriteZero
+    )
+}
+
+/// Create `path` and reserve `size` bytes for it
+///
+/// Only a file that does not exist yet is touched: an existing file is left
+/// as it is and `Ok(false)` is returned, so an offer that is never
+/// authenticated cannot resize the user's data. On Linux the space is
+/// reserved with `fallocate`; elsewhere, or on filesystems without it, the
+/// file is only extended with `set_len`, which may leave it sparse.
+/// Returns whether the file was created.
+pub fn preallocate(path: &Path, size: u64) -> io::Result<bool> {
+    let file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if let Err(e) = reserve(&file, size) {
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(true)
+}
+
+#[cfg(target_os = "linux")]
+fn reserve(file: &File, size: u64) -> io::Result<()> {
+    if size == 0 {
+        return Ok(());
+    }
+    match rustix::fs::fallocate(file, rustix::fs::FallocateFlags::empty(), 0, size) {
+        Ok(()) => Ok(()),
+        // tmpfs on old kernels, some FUSE and network filesystems
+        Err(rustix::io::Errno::OPNOTSUPP) => file.set_len(size),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reserve(file: &File, size: u64) -> io::Result<()> {
+    file.set_len(size)
+}
+
+/// Sequential writer for a single received file
+pub struct OutputFile {
+    path: PathBuf,
+    mode: WriteMode,
+    /// Bytes of file data accepted so far
+    written: u64,
+}
+
+enum WriteMode {
+    Buffered(BufWriter<File>),
+    Direct {
+        file: File,
+        buf: AlignedBuffer,
+        /// Bytes already written to the file (always block aligned)
+        flushed: u64,
+    },
+}
+
+impl std::fmt::Debug for OutputFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputFile")
+            .field("path", &self.path)
+            .field("direct", &self.is_direct())
+            .field("written", &self.written)
+            .finish()
+    }
+}
+
+impl OutputFile {
+    /// Open `path` for writing `size_hint` bytes according to `config`
+    ///
+    /// An existing (e.g. preallocated) file is reused rather than truncated;
+    /// the final length is set by [`OutputFile::finish`].
+    pub fn open(path: &Path, size_hint: u64, config: &WriteConfig) -> io::Result<Self> {
+        let direct_file = if config.direct_io {
+            match open_direct(path) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    tracing::debug!(
+                        "O_DIRECT unavailable for {}, using buffered I/O: {}",
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mode = match direct_file {
+            Some(file) => WriteMode::Direct {
+                file,
+                buf: AlignedBuffer::new(),
+                flushed: 0,
+            },
+            None => WriteMode::Buffered(BufWriter::new(open_buffered(path)?)),
+        };
+
+        let output = Self {
+            path: path.to_path_buf(),
+            mode,
+            written: 0,
+        };
+
+        if config.preallocate {
+            if let Err(e) = output.file().set_len(size_hint) {
+                tracing::debug!("preallocation failed for {}: {}", path.display(), e);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Whether writes currently bypass the page cache
+    pub fn is_direct(&self) -> bool {
+        matches!(self.mode, WriteMode::Direct { .. })
+    }
+
+    /// Append `data` to the file
+    pub fn write_all(&mut self, mut data: &[u8]) -> io::Result<()> {
+        self.written += data.len() as u64;
+
+        while !data.is_empty() {
+            let full = match &mut self.mode {
+                WriteMode::Buffered(writer) => return writer.write_all(data),
+                WriteMode::Direct { buf, .. } => {
+                    let taken = buf.fill(data);
+                    data = &data[taken..];
+                    buf.is_full()
+                }
+            };
+            if full {
+                self.flush_direct(false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush remaining data and truncate the file to the bytes written
+    ///
+    /// Truncation drops any preallocated tail when the manifest overstated
+    /// the size, and any block padding from direct writes.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.is_direct() {
+            self.flush_direct(true)?;
+        }
+        if let WriteMode::Buffered(writer) = &mut self.mode {
+            writer.flush()?;
+        }
+
+        let file = self.file();
+        file.set_len(self.written)?;
+        file.sync_all()
+    }
+
+    fn file(&self) -> &File {
+        match &self.mode {
+            WriteMode::Buffered(writer) => writer.get_ref(),
+            WriteMode::Direct { file, .. } => file,
+        }
+    }
+
+    /// Write the staged block(s); `pad` writes a partial final block
+    ///
+    /// If the filesystem rejects the direct write, the staged data is
+    /// replayed through a buffered handle and the file stays buffered.
+    fn flush_direct(&mut self, pad: bool) -> io::Result<()> {
+        let WriteMode::Direct { file, buf, flushed } = &mut self.mode else {
+            return Ok(());
+        };
+        if buf.len == 0 {
+            return Ok(());
+        }
+
+        let block = if pad {
+            buf.len.next_multiple_of(DIRECT_IO_ALIGN)
+        } else {
+            buf.len
+        };
+        buf.zero_tail(block);
+
+        match file.write_all(&buf.as_slice()[..block]) {
+            Ok(()) => {
+                *flushed += block as u64;
+                buf.len = 0;
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                tracing::debug!(
+                    "direct write rejected for {}, falling back to buffered I/O",
+                    self.path.display()
+                );
+                let pending = buf.as_slice()[..buf.len].to_vec();
+                let offset = *flushed;
+
+                let mut file = open_buffered(&self.path)?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut writer = BufWriter::new(file);
+                writer.write_all(&pending)?;
+                self.mode = WriteMode::Buffered(writer);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Heap buffer whose usable region starts on a `DIRECT_IO_ALIGN` boundary
+struct AlignedBuffer {
+    storage: Vec<u8>,
+    start: usize,
+    len: usize,
+}
+
+impl AlignedBuffer {
+    fn new() -> Self {
+        let storage = vec![0u8; DIRECT_IO_BUFFER + DIRECT_IO_ALIGN];
+        // The Vec is never grown, so its allocation (and alignment) is stable
+        let start = storage.as_ptr().align_offset(DIRECT_IO_ALIGN);
+        Self {
+            storage,
+            start,
+            len: 0,
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.storage[self.start..self.start + DIRECT_IO_BUFFER]
+    }
+
+    fn fill(&mut self, data: &[u8]) -> usize {
+        let n = data.len().min(DIRECT_IO_BUFFER - self.len);
+        let at = self.start + self.len;
+        self.storage[at..at + n].copy_from_slice(&data[..n]);
+        self.len += n;
+        n
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == DIRECT_IO_BUFFER
+    }
+
+    fn zero_tail(&mut self, end: usize) {
+        let from = self.start + self.len;
+        self.storage[from..self.start + end].fill(0);
+    }
+}
+
+fn open_buffered(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+#[cfg(target_os = "linux")]
+fn open_direct(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .custom_flags(libc::O_DIRECT)
+        .open(path)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_direct(_path: &Path) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "O_DIRECT is not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_disk_full() {
+        assert!(is_disk_full(&io::Error::from(io::ErrorKind::StorageFull)));
+        assert!(is_disk_full(&io::Error::from(io::ErrorKind::WriteZero)));
+        assert!(!is_disk_full(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+        #[cfg(target_os = "linux")]
+        assert!(is_disk_full(&io::Error::from_raw_os_error(libc::ENOSPC)));
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn write_chunks(path: &Path, size_hint: u64, data: &[u8], config: &WriteConfig) {
+        let mut out = OutputFile::open(path, size_hint, config).unwrap();
+        for chunk in data.chunks(64 * 1024 + 7) {
+            out.write_all(chunk).unwrap();
+        }
+        out.finish().unwrap();
+    }
+
+    #[test]
+    fn test_preallocate_sets_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.bin");
+        assert!(preallocate(&path, 5_000_000).unwrap());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 5_000_000);
+    }
+
+    #[test]
+    fn test_preallocate_leaves_existing_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("existing.txt");
+        std::fs::write(&path, b"user data").unwrap();
+        assert!(!preallocate(&path, 10).unwrap());
+        assert!(!preallocate(&path, 1_000_000).unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), b"user data");
+    }
+
+    #[test]
+    fn test_buffered_write_truncates_to_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.bin");
+        let data = pattern(300_000);
+
+        write_chunks(&path, 1_000_000, &data, &WriteConfig::default());
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_direct_write_roundtrip_or_fallback() {
+        // Works with real O_DIRECT or degrades (e.g. tmpfs) to buffered
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.bin");
+        let data = pattern(DIRECT_IO_BUFFER * 2 + 12_345);
+        let config = WriteConfig {
+            preallocate: true,
+            direct_io: true,
+        };
+
+        write_chunks(&path, data.len() as u64, &data, &config);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+    }
+}
@@ -0,0 +1,288 @@
+//! Transfer time estimates from a short bandwidth probe
+//!
+//! When both peers advertise `FeatureSet::BANDWIDTH_PROBE`, the sender
+//! sends [`PROBE_BYTES`] of random filler as `ProbeData` frames ahead of
+//! the `FileOffer` and times the receiver's `ProbeAck`. The measured
+//! throughput, the manifest's total size and an entropy-based compression
+//! ratio give a [`TransferEstimate`] on both sides. Probe frames carry no
+//! file data and are shown to the user as a probe, never as progress.
+
+use crate::compression::analysis::shannon_entropy;
+use crate::wire::Message;
+use rand::RngCore;
+use std::time::{Duration, Instant};
+
+/// Filler bytes sent by one probe
+pub const PROBE_BYTES: usize = 256 * 1024;
+
+/// Filler bytes per `ProbeData` frame
+pub const PROBE_FRAME_BYTES: usize = 16 * 1024;
+
+/// Transfers smaller than this are sent without probing, since the probe
+/// would be a large share of the transfer itself
+pub const MIN_PROBED_SIZE: u64 = 4 * PROBE_BYTES as u64;
+
+/// Lowest compression ratio an entropy sample may predict
+///
+/// Very repetitive samples rarely hold for a whole transfer.
+const MIN_RATIO_HINT: f64 = 0.05;
+
+/// Bytes moved over a measured interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThroughputSample {
+    bytes: u64,
+    elapsed: Duration,
+}
+
+impl ThroughputSample {
+    /// A sample of `bytes` moved in `elapsed`
+    pub fn new(bytes: u64, elapsed: Duration) -> Self {
+        Self { bytes, elapsed }
+    }
+
+    /// Bytes moved
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Measured throughput in bytes per second
+    ///
+    /// `None` when nothing arrived or the interval was too short to time,
+    /// so a failed probe never turns into a zero or infinite rate.
+    pub fn bytes_per_sec(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        if self.bytes == 0 || secs <= 0.0 {
+            return None;
+        }
+        Some(self.bytes as f64 / secs)
+    }
+}
+
+/// Expected size on the wire and duration of a transfer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransferEstimate {
+    /// Manifest size scaled by the compression ratio
+    pub wire_bytes: u64,
+    /// Measured throughput, if the probe produced one
+    pub bytes_per_sec: Option<f64>,
+    /// Estimated duration; `None` means unknown
+    pub duration: Option<Duration>,
+}
+
+/// Estimate how long sending `total_size` bytes will take
+///
+/// `compression_ratio` is the expected compressed/original size, clamped
+/// to `0.0..=1.0` (anything invalid counts as incompressible).
+pub fn estimate(
+    total_size: u64,
+    compression_ratio: f64,
+    sample: &ThroughputSample,
+) -> TransferEstimate {
+    let ratio = if compression_ratio.is_finite() {
+        compression_ratio.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let wire_bytes = (total_size as f64 * ratio).ceil() as u64;
+    let bytes_per_sec = sample.bytes_per_sec();
+    let duration =
+        bytes_per_sec.and_then(|rate| Duration::try_from_secs_f64(wire_bytes as f64 / rate).ok());
+    TransferEstimate {
+        wire_bytes,
+        bytes_per_sec,
+        duration,
+    }
+}
+
+/// Expected compressed/original size for data like `sample`
+///
+/// Uses the sample's byte entropy, so it is a rough guide rather than a
+/// measurement. Returns 1.0 when the transfer is not compressed.
+pub fn compression_ratio_hint(sample: &[u8], compression: Option<&str>) -> f64 {
+    match compression {
+        None | Some("none") => 1.0,
+        Some(_) if sample.is_empty() => 1.0,
+        Some(_) => (shannon_entropy(sample) / 8.0).clamp(MIN_RATIO_HINT, 1.0),
+    }
+}
+
+/// The `ProbeData` frames for one probe, filled with random bytes
+///
+/// Random filler keeps transport-level compression from inflating the
+/// measurement. `ratio` is passed along so the receiver can estimate too.
+pub fn probe_frames(ratio: f64) -> Vec<Message> {
+    let ratio_permille = (ratio.clamp(0.0, 1.0) * 1000.0).round() as u16;
+    let count = PROBE_BYTES.div_ceil(PROBE_FRAME_BYTES);
+    (0..count)
+        .map(|seq| {
+            let mut payload = vec![0u8; PROBE_FRAME_BYTES];
+            rand::thread_rng().fill_bytes(&mut payload);
+            Message::ProbeData {
+                seq: seq as u32,
+                last: seq + 1 == count,
+                ratio_permille,
+                payload,
+            }
+        })
+        .collect()
+}
+
+/// Receiver side of a probe
+///
+/// Times the span from the first to the last `ProbeData` frame. The first
+/// frame only starts the clock, so its bytes are not counted.
+#[derive(Debug, Default)]
+pub struct ProbeReceiver {
+    first_at: Option<Instant>,
+    last_at: Option<Instant>,
+    bytes: u64,
+    timed_bytes: u64,
+    ratio_permille: u16,
+}
+
+impl ProbeReceiver {
+    /// A receiver that has seen no probe frames
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one message; returns the `ProbeAck` to send once the final
+    /// frame arrives
+    ///
+    /// Messages other than `ProbeData` are ignored.
+    pub fn record(&mut self, msg: &Message) -> Option<Message> {
+        self.record_at(msg, Instant::now())
+    }
+
+    fn record_at(&mut self, msg: &Message, now: Instant) -> Option<Message> {
+        let Message::ProbeData {
+            last,
+            ratio_permille,
+            payload,
+            ..
+        } = msg
+        else {
+            return None;
+        };
+        let len = payload.len() as u64;
+        self.bytes += len;
+        if self.first_at.is_none() {
+            self.first_at = Some(now);
+        } else {
+            self.timed_bytes += len;
+        }
+        self.last_at = Some(now);
+        self.ratio_permille = *ratio_permille;
+        last.then_some(Message::ProbeAck { bytes: self.bytes })
+    }
+
+    /// Throughput measured so far
+    pub fn sample(&self) -> ThroughputSample {
+        let elapsed = match (self.first_at, self.last_at) {
+            (Some(first), Some(last)) => last.duration_since(first),
+            _ => Duration::ZERO,
+        };
+        ThroughputSample::new(self.timed_bytes, elapsed)
+    }
+
+    /// The sender's compression ratio hint
+    pub fn ratio_hint(&self) -> f64 {
+        f64::from(self.ratio_permille.min(1000)) / 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_estimate_scales_with_size() {
+        let sample = ThroughputSample::new(MIB, Duration::from_secs(1));
+        let small = estimate(10 * MIB, 1.0, &sample);
+        let large = estimate(100 * MIB, 1.0, &sample);
+        assert_eq!(small.duration, Some(Duration::from_secs(10)));
+        assert_eq!(large.duration, Some(Duration::from_secs(100)));
+
+        // Compression shrinks what has to cross the wire
+        let compressed = estimate(100 * MIB, 0.5, &sample);
+        assert_eq!(compressed.wire_bytes, 50 * MIB);
+        assert_eq!(compressed.duration, Some(Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn test_estimate_scales_with_throughput() {
+        let slow = ThroughputSample::new(MIB, Duration::from_secs(2));
+        let fast = ThroughputSample::new(4 * MIB, Duration::from_secs(1));
+        let slow = estimate(64 * MIB, 1.0, &slow);
+        let fast = estimate(64 * MIB, 1.0, &fast);
+        assert_eq!(slow.duration, Some(Duration::from_secs(128)));
+        assert_eq!(fast.duration, Some(Duration::from_secs(16)));
+    }
+
+    #[test]
+    fn test_zero_throughput_is_unknown() {
+        let nothing = ThroughputSample::new(0, Duration::from_secs(5));
+        let est = estimate(MIB, 1.0, &nothing);
+        assert_eq!(est.bytes_per_sec, None);
+        assert_eq!(est.duration, None);
+
+        // An untimed interval is not an infinite rate either
+        let instant = ThroughputSample::new(MIB, Duration::ZERO);
+        assert_eq!(estimate(MIB, 1.0, &instant).duration, None);
+    }
+
+    #[test]
+    fn test_invalid_ratio_counts_as_incompressible() {
+        let sample = ThroughputSample::new(MIB, Duration::from_secs(1));
+        assert_eq!(estimate(MIB, f64::NAN, &sample).wire_bytes, MIB);
+        assert_eq!(estimate(MIB, 3.0, &sample).wire_bytes, MIB);
+    }
+
+    #[test]
+    fn test_compression_ratio_hint() {
+        assert_eq!(compression_ratio_hint(&[0u8; 1024], None), 1.0);
+        assert_eq!(compression_ratio_hint(&[0u8; 1024], Some("none")), 1.0);
+        assert_eq!(
+            compression_ratio_hint(&[0u8; 1024], Some("zstd")),
+            MIN_RATIO_HINT
+        );
+        let text = b"the quick brown fox jumps over the lazy dog ".repeat(32);
+        let hint = compression_ratio_hint(&text, Some("zstd"));
+        assert!(hint > MIN_RATIO_HINT && hint < 1.0, "{}", hint);
+    }
+
+    #[test]
+    fn test_probe_roundtrip_measures_receiver_side() {
+        let frames = probe_frames(0.5);
+        let total: usize = frames
+            .iter()
+            .map(|m| match m {
+                Message::ProbeData { payload, .. } => payload.len(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(total, PROBE_BYTES);
+
+        let mut receiver = ProbeReceiver::new();
+        let start = Instant::now();
+        let mut ack = None;
+        for (i, frame) in frames.iter().enumerate() {
+            ack = receiver.record_at(frame, start + Duration::from_millis(10 * i as u64));
+        }
+        assert_eq!(
+            ack,
+            Some(Message::ProbeAck {
+                bytes: PROBE_BYTES as u64
+            })
+        );
+        assert_eq!(receiver.ratio_hint(), 0.5);
+
+        // 15 timed frames of 16 KiB over 150 ms
+        let sample = receiver.sample();
+        assert_eq!(sample.bytes(), (PROBE_BYTES - PROBE_FRAME_BYTES) as u64);
+        let rate = sample.bytes_per_sec().unwrap();
+        assert!((rate - 245_760.0 / 0.15).abs() < 1.0, "{}", rate);
+    }
+}
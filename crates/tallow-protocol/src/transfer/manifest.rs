@@ -2,9 +2,16 @@
 //!
 //! Contains the list of files, their sizes and hashes.
 //! Signed by the sender before transfer begins.
+//!
+//! Directory enumeration order differs between platforms and filesystems,
+//! so entries are sorted by [`path_sort_key`] before the manifest is hashed:
+//! the same tree always produces the same manifest bytes.
 
+use crate::transfer::sparse::{self, Hole};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use tallow_crypto::hash::HashAlgorithm;
 
 /// Transfer content type
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -20,6 +27,8 @@ pub enum TransferType {
     ClipboardImage,
     /// URL/link sharing
     Url,
+    /// Unknown-length stream (e.g. stdin); sizes arrive in the stream trailer
+    Stream,
 }
 
 /// File entry in manifest
@@ -35,6 +44,21 @@ pub struct FileEntry {
     pub chunk_count: u64,
 }
 
+/// A file the sender left out because it couldn't be read
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkippedFile {
+    /// Path as it would have appeared in the manifest
+    pub path: PathBuf,
+    /// Why it couldn't be read
+    pub reason: String,
+}
+
+impl std::fmt::Display for SkippedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.reason)
+    }
+}
+
 /// File manifest containing transfer metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileManifest {
@@ -57,6 +81,27 @@ pub struct FileManifest {
     /// Per-chunk compression enables streaming I/O for large files.
     #[serde(default)]
     pub per_chunk_compression: bool,
+    /// Whether each chunk carries a tag saying if it is compressed, letting
+    /// the sender stop compressing mid-stream. Only set when negotiated.
+    #[serde(default)]
+    pub adaptive_compression: bool,
+    /// Holes in sparse files, by index into `files`
+    ///
+    /// A sparse entry's chunks carry only the bytes outside its holes. Only
+    /// filled in when both peers support sparse files.
+    #[serde(default)]
+    pub holes: BTreeMap<u32, Vec<Hole>>,
+    /// Algorithm of every entry's `hash`
+    ///
+    /// Receivers verify with the same one. Anything but BLAKE3 is only
+    /// used when both peers support it.
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Files the sender meant to include but couldn't read
+    ///
+    /// Informational only: they have no entry in `files` and no chunks.
+    #[serde(default)]
+    pub skipped: Vec<SkippedFile>,
 }
 
 impl FileManifest {
@@ -71,6 +116,10 @@ impl FileManifest {
             manifest_hash: None,
             transfer_type: TransferType::default(),
             per_chunk_compression: true,
+            adaptive_compression: false,
+            holes: BTreeMap::new(),
+            hash_algorithm: HashAlgorithm::default(),
+            skipped: Vec::new(),
         }
     }
 
@@ -87,9 +136,75 @@ impl FileManifest {
         });
     }
 
+    /// Sort entries by [`path_sort_key`]
+    ///
+    /// The sort is stable, so entries with identical keys keep their
+    /// relative order. Returns the original index of each entry in its new
+    /// position, for reordering data kept alongside the manifest.
+    pub fn sort_files(&mut self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.files.len()).collect();
+        order.sort_by_cached_key(|&i| path_sort_key(&self.files[i].path));
+        let mut files: Vec<Option<FileEntry>> = self.files.drain(..).map(Some).collect();
+        self.files = order.iter().filter_map(|&i| files[i].take()).collect();
+        let mut holes = std::mem::take(&mut self.holes);
+        for (new, &old) in order.iter().enumerate() {
+            if let Some(list) = holes.remove(&(old as u32)) {
+                self.holes.insert(new as u32, list);
+            }
+        }
+        order
+    }
+
+    /// Mark file `index` as sparse, sending only the bytes outside `holes`
+    ///
+    /// Its chunk count drops to cover just those bytes. Call
+    /// [`finalize`](Self::finalize) again afterwards.
+    pub fn set_holes(&mut self, index: usize, holes: Vec<Hole>) -> crate::Result<()> {
+        let entry = self.files.get_mut(index).ok_or_else(|| {
+            crate::ProtocolError::TransferFailed(format!("no file {} in manifest", index))
+        })?;
+        sparse::validate(&holes, entry.size)?;
+        let chunk_count = sparse::data_len(entry.size, &holes).div_ceil(self.chunk_size as u64);
+        self.total_chunks = self.total_chunks - entry.chunk_count + chunk_count;
+        entry.chunk_count = chunk_count;
+        if holes.is_empty() {
+            self.holes.remove(&(index as u32));
+        } else {
+            self.holes.insert(index as u32, holes);
+        }
+        Ok(())
+    }
+
+    /// Holes in file `index`; empty unless it is sent sparse
+    pub fn holes(&self, index: usize) -> &[Hole] {
+        self.holes
+            .get(&(index as u32))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Bytes of file `index` that are actually sent, i.e. outside its holes
+    pub fn data_len(&self, index: usize) -> u64 {
+        self.files
+            .get(index)
+            .map_or(0, |entry| sparse::data_len(entry.size, self.holes(index)))
+    }
+
+    /// Bytes sent for the whole transfer, i.e. outside any holes
+    pub fn data_size(&self) -> u64 {
+        (0..self.files.len()).map(|i| self.data_len(i)).sum()
+    }
+
     /// Compute and store the manifest hash
+    ///
+    /// Covers the hole lists too once any file is sparse.
     pub fn finalize(&mut self) -> crate::Result<()> {
-        let bytes = postcard::to_stdvec(&self.files).map_err(|e| {
+        let bytes = if self.holes.is_empty() && self.hash_algorithm == HashAlgorithm::Blake3 {
+            postcard::to_stdvec(&self.files)
+        } else {
+            postcard::to_stdvec(&(&self.files, &self.holes, self.hash_algorithm))
+        }
+        .map_err(|e| {
             crate::ProtocolError::EncodingError(format!("manifest finalize failed: {}", e))
         })?;
         self.manifest_hash = Some(blake3::hash(&bytes).into());
@@ -108,13 +223,13 @@ impl FileManifest {
     const MAX_TOTAL_CHUNKS: u64 = 1_000_000;
 
     /// Maximum total transfer size in bytes (DoS protection): 10 TB
-    const MAX_TOTAL_SIZE: u64 = 10_000_000_000_000;
+    pub(crate) const MAX_TOTAL_SIZE: u64 = 10_000_000_000_000;
 
     /// Maximum number of files in a single manifest
     const MAX_FILE_COUNT: usize = 1_000_000;
 
     /// Maximum individual file size in bytes: 1 TB
-    const MAX_FILE_SIZE: u64 = 1_000_000_000_000;
+    pub(crate) const MAX_FILE_SIZE: u64 = 1_000_000_000_000;
 
     /// Deserialize a manifest from bytes, with validation against DoS limits
     pub fn from_bytes(data: &[u8]) -> crate::Result<Self> {
@@ -175,6 +290,23 @@ impl FileManifest {
                 })?;
         }
 
+        // A sparse entry's chunks must cover exactly its bytes outside the holes
+        for (&index, holes) in &self.holes {
+            let entry = self.files.get(index as usize).ok_or_else(|| {
+                crate::ProtocolError::TransferFailed(format!("holes for unknown file {}", index))
+            })?;
+            sparse::validate(holes, entry.size)?;
+            let data_len = sparse::data_len(entry.size, holes);
+            if self.chunk_size == 0
+                || entry.chunk_count != data_len.div_ceil(self.chunk_size as u64)
+            {
+                return Err(crate::ProtocolError::TransferFailed(format!(
+                    "sparse file {} has an inconsistent chunk count",
+                    index
+                )));
+            }
+        }
+
         Ok(())
     }
 
@@ -183,30 +315,68 @@ impl FileManifest {
         self.files.len()
     }
 
+    /// Transfer-wide index of chunk `index` of file `file_id`
+    ///
+    /// Files occupy consecutive index ranges in manifest order. Returns
+    /// `None` if the file does not exist or has fewer chunks.
+    pub fn global_chunk_index(&self, file_id: u32, index: u64) -> Option<u64> {
+        let entry = self.files.get(file_id as usize)?;
+        if index >= entry.chunk_count {
+            return None;
+        }
+        let start: u64 = self.files[..file_id as usize]
+            .iter()
+            .map(|f| f.chunk_count)
+            .sum();
+        start.checked_add(index)
+    }
+
     /// Sanitize file paths to prevent directory traversal
     ///
     /// Removes parent directory components (`..`), root prefixes (`/`, `C:\`),
     /// and prefix components to ensure paths are strictly relative.
     pub fn sanitize_paths(&mut self) {
         for entry in &mut self.files {
-            let sanitized: PathBuf = entry
-                .path
-                .components()
-                .filter(|c| {
-                    matches!(
-                        c,
-                        std::path::Component::Normal(_) | std::path::Component::CurDir
-                    )
-                })
-                .collect();
-            // If sanitization results in an empty path, use a safe fallback
-            entry.path = if sanitized.as_os_str().is_empty() {
-                PathBuf::from("unnamed")
-            } else {
-                sanitized
-            };
+            entry.path = sanitize_entry_path(&entry.path);
+        }
+    }
+}
+
+/// Strictly relative form of a manifest path; see [`FileManifest::sanitize_paths`]
+pub(crate) fn sanitize_entry_path(path: &Path) -> PathBuf {
+    let sanitized: PathBuf = path
+        .components()
+        .filter(|c| {
+            matches!(
+                c,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        })
+        .collect();
+    // If sanitization results in an empty path, use a safe fallback
+    if sanitized.as_os_str().is_empty() {
+        PathBuf::from("unnamed")
+    } else {
+        sanitized
+    }
+}
+
+/// Byte string manifest entries are ordered by
+///
+/// Normal components joined with `/`, whatever the platform separator, and
+/// compared byte-wise: no case folding or Unicode normalization, so `B.txt`
+/// sorts before `a.txt` and names differing only in case stay distinct.
+pub fn path_sort_key(path: &Path) -> Vec<u8> {
+    let mut key = Vec::new();
+    for component in path.components() {
+        if let Component::Normal(part) = component {
+            if !key.is_empty() {
+                key.push(b'/');
+            }
+            key.extend_from_slice(part.as_encoded_bytes());
         }
     }
+    key
 }
 
 impl Default for FileManifest {
@@ -253,6 +423,105 @@ mod tests {
         assert_eq!(decoded.total_size, 300);
     }
 
+    #[test]
+    fn test_hash_algorithm_recorded() {
+        let mut manifest = FileManifest::new(64 * 1024);
+        manifest.add_file(PathBuf::from("a.txt"), 100, [1u8; 32]);
+        manifest.finalize().unwrap();
+        let blake3_hash = manifest.manifest_hash;
+        assert_eq!(manifest.hash_algorithm, HashAlgorithm::Blake3);
+
+        // The algorithm survives encoding and is covered by the manifest hash
+        manifest.hash_algorithm = HashAlgorithm::Sha3_256;
+        manifest.finalize().unwrap();
+        assert_ne!(manifest.manifest_hash, blake3_hash);
+        let decoded = FileManifest::from_bytes(&manifest.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.hash_algorithm, HashAlgorithm::Sha3_256);
+    }
+
+    #[test]
+    fn test_sparse_entries() {
+        let mut manifest = FileManifest::new(64 * 1024);
+        manifest.add_file(PathBuf::from("b.img"), 1 << 20, [1u8; 32]);
+        manifest.add_file(PathBuf::from("a.txt"), 100, [2u8; 32]);
+        manifest.add_file(PathBuf::from("empty"), 0, [3u8; 32]);
+        let holes = vec![Hole {
+            offset: 64 * 1024,
+            len: 896 * 1024,
+        }];
+        manifest.set_holes(0, holes.clone()).unwrap();
+        assert_eq!(manifest.files[0].chunk_count, 2);
+        assert_eq!(manifest.total_chunks, 3);
+        assert_eq!(manifest.total_size, (1 << 20) + 100);
+        assert_eq!(manifest.data_size(), 128 * 1024 + 100);
+
+        // Hole lists follow their file when entries are sorted
+        manifest.sort_files();
+        assert_eq!(manifest.holes(2), holes.as_slice());
+        assert!(manifest.holes(0).is_empty());
+        manifest.finalize().unwrap();
+
+        let decoded = FileManifest::from_bytes(&manifest.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.holes, manifest.holes);
+        assert_eq!(decoded.files[1].chunk_count, 0);
+
+        // Holes that don't match the chunk count, or lie outside the file
+        let mut tampered = manifest.clone();
+        tampered.holes.insert(
+            2,
+            vec![Hole {
+                offset: 0,
+                len: 4096,
+            }],
+        );
+        assert!(FileManifest::from_bytes(&tampered.to_bytes().unwrap()).is_err());
+        tampered.holes.insert(
+            2,
+            vec![Hole {
+                offset: 1 << 20,
+                len: 1,
+            }],
+        );
+        assert!(FileManifest::from_bytes(&tampered.to_bytes().unwrap()).is_err());
+        let mut unknown = manifest.clone();
+        unknown.holes.insert(9, holes);
+        assert!(FileManifest::from_bytes(&unknown.to_bytes().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_sort_is_bytewise_and_stable() {
+        let mut manifest = FileManifest::new(64 * 1024);
+        for (name, size) in [
+            ("b.txt", 1),
+            ("a/z.txt", 2),
+            ("a.txt", 3),
+            ("A.txt", 4),
+            ("./a.txt", 5),
+            ("a-b.txt", 6),
+        ] {
+            manifest.add_file(PathBuf::from(name), size, [0u8; 32]);
+        }
+
+        let order = manifest.sort_files();
+        let sizes: Vec<u64> = manifest.files.iter().map(|f| f.size).collect();
+        // Uppercase first; "a.txt" and "./a.txt" share a key and keep
+        // insertion order; '-' and '.' sort before '/'
+        assert_eq!(sizes, vec![4, 6, 3, 5, 2, 1]);
+        assert_eq!(order, vec![3, 5, 2, 4, 1, 0]);
+
+        // Case-only differences sort the same whatever the insertion order
+        let mut reversed = FileManifest::new(64 * 1024);
+        reversed.add_file(PathBuf::from("readme"), 1, [0u8; 32]);
+        reversed.add_file(PathBuf::from("README"), 2, [0u8; 32]);
+        reversed.sort_files();
+        let mut forward = FileManifest::new(64 * 1024);
+        forward.add_file(PathBuf::from("README"), 2, [0u8; 32]);
+        forward.add_file(PathBuf::from("readme"), 1, [0u8; 32]);
+        forward.sort_files();
+        assert_eq!(reversed.files, forward.files);
+        assert_eq!(forward.files[0].path, PathBuf::from("README"));
+    }
+
     #[test]
     fn test_sanitize_paths() {
         let mut manifest = FileManifest::new(64 * 1024);
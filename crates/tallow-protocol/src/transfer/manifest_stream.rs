@@ -0,0 +1,678 @@
+//! Streamed manifests for very large directories
+//!
+//! A `FileOffer` carries the whole manifest in one message, which stops
+//! scaling somewhere around a million files. A streamed manifest is sent as
+//! a `ManifestStart` header, `ManifestChunk` batches of entries and a
+//! `ManifestEnd` summary. The sender pulls entries from an iterator as it
+//! goes and the receiver hands each entry to a callback as it is decoded,
+//! so neither side holds more than one batch at a time.
+//!
+//! Because nobody sees the whole list at once, the summary is signed over
+//! a rolling BLAKE3 hash fed every entry in order. An entry dropped,
+//! reordered or altered anywhere in the stream changes that hash, and the
+//! manifest fails verification at `ManifestEnd`.
+
+use crate::transfer::manifest::{
+    sanitize_entry_path, FileEntry, FileManifest, SkippedFile, TransferType,
+};
+use crate::wire::Message;
+use crate::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use tallow_crypto::hash::domain;
+use tallow_crypto::hash::HashAlgorithm;
+use tallow_crypto::sig::{HybridPublicKey, HybridSignature, HybridSigner};
+
+/// Target encoded size of one `ManifestChunk` batch
+pub const MANIFEST_BATCH_BYTES: usize = 64 * 1024;
+
+/// Largest `ManifestChunk` payload accepted from the wire
+const MAX_BATCH_LEN: usize = 4 * MANIFEST_BATCH_BYTES;
+
+/// Largest serialized header or summary accepted from the wire
+const MAX_SUMMARY_LEN: usize = 16 * 1024;
+
+/// Most entries a streamed manifest may carry
+const MAX_STREAMED_ENTRIES: u64 = 100_000_000;
+
+/// Every manifest field except the file list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestHeader {
+    /// Chunk size used for splitting
+    pub chunk_size: usize,
+    /// Compression algorithm used (as string identifier)
+    pub compression: Option<String>,
+    /// Type of transfer
+    pub transfer_type: TransferType,
+    /// Whether compression is applied per chunk
+    pub per_chunk_compression: bool,
+    /// Whether chunks carry a compressed/uncompressed tag
+    pub adaptive_compression: bool,
+    /// Algorithm of every entry's hash
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Files the sender couldn't read and left out
+    #[serde(default)]
+    pub skipped: Vec<SkippedFile>,
+}
+
+impl ManifestHeader {
+    /// Header with the same settings as `manifest`
+    pub fn of(manifest: &FileManifest) -> Self {
+        Self {
+            chunk_size: manifest.chunk_size,
+            compression: manifest.compression.clone(),
+            transfer_type: manifest.transfer_type.clone(),
+            per_chunk_compression: manifest.per_chunk_compression,
+            adaptive_compression: manifest.adaptive_compression,
+            hash_algorithm: manifest.hash_algorithm,
+            skipped: manifest.skipped.clone(),
+        }
+    }
+}
+
+/// Signed totals sent in `ManifestEnd`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ManifestSummary {
+    /// Number of entries sent
+    pub entry_count: u64,
+    /// Sum of entry sizes in bytes
+    pub total_size: u64,
+    /// Sum of entry chunk counts
+    pub total_chunks: u64,
+    /// Rolling hash over the header and every entry
+    pub rolling_hash: [u8; 32],
+    /// Sender's signing key
+    pub signer: HybridPublicKey,
+    /// Signature over the summary transcript
+    pub signature: HybridSignature,
+}
+
+impl std::fmt::Debug for ManifestSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManifestSummary")
+            .field("entry_count", &self.entry_count)
+            .field("total_size", &self.total_size)
+            .field("total_chunks", &self.total_chunks)
+            .finish()
+    }
+}
+
+/// Running hash over a manifest's header and entries, in order
+#[derive(Debug, Clone)]
+pub struct RollingManifestHash {
+    hasher: blake3::Hasher,
+    entries: u64,
+}
+
+impl RollingManifestHash {
+    /// Start a hash for `transfer_id` with the serialized header
+    pub fn new(transfer_id: &[u8; 16], header: &[u8]) -> Self {
+        let mut hasher = blake3::Hasher::new_derive_key(domain::DOMAIN_MANIFEST_STREAM);
+        hasher.update(b"entries");
+        hasher.update(transfer_id);
+        hasher.update(&(header.len() as u64).to_be_bytes());
+        hasher.update(header);
+        Self { hasher, entries: 0 }
+    }
+
+    /// Feed the next serialized entry
+    pub fn push(&mut self, entry: &[u8]) {
+        self.hasher.update(&self.entries.to_be_bytes());
+        self.hasher.update(&(entry.len() as u64).to_be_bytes());
+        self.hasher.update(entry);
+        self.entries += 1;
+    }
+
+    /// Hash of everything pushed so far, including the entry count
+    pub fn finalize(&self) -> [u8; 32] {
+        let mut hasher = self.hasher.clone();
+        hasher.update(&self.entries.to_be_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Running entry count and totals, checked against DoS limits
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    entries: u64,
+    size: u64,
+    chunks: u64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &FileEntry, chunk_size: usize) -> Result<()> {
+        let fail = |msg: String| Err(ProtocolError::TransferFailed(msg));
+        if entry.size > FileManifest::MAX_FILE_SIZE {
+            return fail(format!(
+                "file size {} exceeds limit {}",
+                entry.size,
+                FileManifest::MAX_FILE_SIZE
+            ));
+        }
+        if entry.chunk_count != entry.size.div_ceil(chunk_size as u64) {
+            return fail(format!(
+                "manifest entry {} has an inconsistent chunk count",
+                self.entries
+            ));
+        }
+        self.entries += 1;
+        if self.entries > MAX_STREAMED_ENTRIES {
+            return fail(format!(
+                "manifest file count exceeds limit {}",
+                MAX_STREAMED_ENTRIES
+            ));
+        }
+        self.size = self
+            .size
+            .checked_add(entry.size)
+            .filter(|&size| size <= FileManifest::MAX_TOTAL_SIZE)
+            .ok_or_else(|| {
+                ProtocolError::TransferFailed(format!(
+                    "manifest total_size exceeds limit {}",
+                    FileManifest::MAX_TOTAL_SIZE
+                ))
+            })?;
+        self.chunks = self.chunks.checked_add(entry.chunk_count).ok_or_else(|| {
+            ProtocolError::TransferFailed("manifest total_chunks overflow".to_string())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamerState {
+    Start,
+    Entries,
+    Done,
+}
+
+/// Produces the messages of a streamed manifest from an entry iterator
+///
+/// Entries are pulled one batch at a time, so the iterator can walk a
+/// directory lazily.
+pub struct ManifestStreamer<'a, I> {
+    transfer_id: [u8; 16],
+    header: ManifestHeader,
+    entries: I,
+    signer: &'a HybridSigner,
+    batch_bytes: usize,
+    hash: Option<RollingManifestHash>,
+    totals: Totals,
+    seq: u64,
+    state: StreamerState,
+}
+
+impl<I> std::fmt::Debug for ManifestStreamer<'_, I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManifestStreamer")
+            .field("seq", &self.seq)
+            .field("entries", &self.totals.entries)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<'a, I: Iterator<Item = FileEntry>> ManifestStreamer<'a, I> {
+    /// Stream `entries` under `header`, signing the summary with `signer`
+    pub fn new(
+        transfer_id: [u8; 16],
+        header: ManifestHeader,
+        entries: I,
+        signer: &'a HybridSigner,
+    ) -> Self {
+        Self {
+            transfer_id,
+            header,
+            entries,
+            signer,
+            batch_bytes: MANIFEST_BATCH_BYTES,
+            hash: None,
+            totals: Totals::default(),
+            seq: 0,
+            state: StreamerState::Start,
+        }
+    }
+
+    /// Override the target batch size (clamped to what receivers accept)
+    pub fn with_batch_bytes(mut self, bytes: usize) -> Self {
+        self.batch_bytes = bytes.clamp(1, MANIFEST_BATCH_BYTES);
+        self
+    }
+
+    /// Next message to send
+    ///
+    /// Returns `ManifestStart`, then `ManifestChunk` batches while entries
+    /// remain, then a single `ManifestEnd`, then `None`.
+    pub fn next_message(&mut self) -> Result<Option<Message>> {
+        match self.state {
+            StreamerState::Done => Ok(None),
+            StreamerState::Start => {
+                let header = encode(&self.header, "manifest header")?;
+                self.hash = Some(RollingManifestHash::new(&self.transfer_id, &header));
+                self.state = StreamerState::Entries;
+                Ok(Some(Message::ManifestStart {
+                    transfer_id: self.transfer_id,
+                    header,
+                }))
+            }
+            StreamerState::Entries => {
+                let hash = self.hash.as_mut().ok_or_else(|| {
+                    ProtocolError::TransferFailed("manifest stream not started".to_string())
+                })?;
+                let mut batch = Vec::new();
+                let mut batch_len = 0;
+                while batch_len < self.batch_bytes {
+                    let Some(entry) = self.entries.next() else {
+                        break;
+                    };
+                    self.totals.add(&entry, self.header.chunk_size)?;
+                    let bytes = encode(&entry, "manifest entry")?;
+                    hash.push(&bytes);
+                    batch_len += bytes.len();
+                    batch.push(entry);
+                }
+
+                if batch.is_empty() {
+                    self.state = StreamerState::Done;
+                    return self.end_message().map(Some);
+                }
+                let msg = Message::ManifestChunk {
+                    transfer_id: self.transfer_id,
+                    seq: self.seq,
+                    entries: encode(&batch, "manifest batch")?,
+                };
+                self.seq += 1;
+                Ok(Some(msg))
+            }
+        }
+    }
+
+    /// Entries sent so far
+    pub fn entries_sent(&self) -> u64 {
+        self.totals.entries
+    }
+
+    fn end_message(&self) -> Result<Message> {
+        let rolling_hash = self
+            .hash
+            .as_ref()
+            .map(RollingManifestHash::finalize)
+            .ok_or_else(|| {
+                ProtocolError::TransferFailed("manifest stream not started".to_string())
+            })?;
+        let digest = summary_transcript(&self.transfer_id, &self.totals, &rolling_hash);
+        let signature = self.signer.sign(&digest).map_err(|e| {
+            ProtocolError::TransferFailed(format!("manifest signing failed: {}", e))
+        })?;
+        let summary = ManifestSummary {
+            entry_count: self.totals.entries,
+            total_size: self.totals.size,
+            total_chunks: self.totals.chunks,
+            rolling_hash,
+            signer: self.signer.public_key(),
+            signature,
+        };
+        Ok(Message::ManifestEnd {
+            transfer_id: self.transfer_id,
+            summary: encode(&summary, "manifest summary")?,
+        })
+    }
+}
+
+/// Verifies a streamed manifest while handing entries on as they arrive
+///
+/// Entries reach the caller before the summary is checked, so anything
+/// done with them (creating placeholders, building a selection) must be
+/// undone if [`finish`](Self::finish) fails.
+#[derive(Debug)]
+pub struct ManifestStreamReceiver {
+    transfer_id: [u8; 16],
+    header: ManifestHeader,
+    hash: RollingManifestHash,
+    totals: Totals,
+    next_seq: u64,
+}
+
+impl ManifestStreamReceiver {
+    /// Begin receiving from a `ManifestStart` header
+    pub fn start(transfer_id: [u8; 16], header: &[u8]) -> Result<Self> {
+        if header.len() > MAX_SUMMARY_LEN {
+            return Err(ProtocolError::DecodingError(format!(
+                "manifest header of {} bytes exceeds limit",
+                header.len()
+            )));
+        }
+        let decoded: ManifestHeader = decode(header, "manifest header")?;
+        if decoded.chunk_size == 0 {
+            return Err(ProtocolError::TransferFailed(
+                "manifest chunk size is zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            transfer_id,
+            header: decoded,
+            hash: RollingManifestHash::new(&transfer_id, header),
+            totals: Totals::default(),
+            next_seq: 0,
+        })
+    }
+
+    /// Manifest settings from the header
+    pub fn header(&self) -> &ManifestHeader {
+        &self.header
+    }
+
+    /// Entries received so far
+    pub fn entries_received(&self) -> u64 {
+        self.totals.entries
+    }
+
+    /// Decode a `ManifestChunk` batch, passing each entry to `on_entry`
+    ///
+    /// Paths are sanitized before they are handed on. Batches must arrive
+    /// in sequence; a gap means entries were lost.
+    pub fn process_chunk(
+        &mut self,
+        seq: u64,
+        entries: &[u8],
+        mut on_entry: impl FnMut(FileEntry) -> Result<()>,
+    ) -> Result<()> {
+        if seq != self.next_seq {
+            return Err(ProtocolError::TransferFailed(format!(
+                "manifest batch {} arrived, expected {}",
+                seq, self.next_seq
+            )));
+        }
+        if entries.len() > MAX_BATCH_LEN {
+            return Err(ProtocolError::DecodingError(format!(
+                "manifest batch of {} bytes exceeds limit",
+                entries.len()
+            )));
+        }
+        let batch: Vec<FileEntry> = decode(entries, "manifest batch")?;
+        for mut entry in batch {
+            self.totals.add(&entry, self.header.chunk_size)?;
+            self.hash.push(&encode(&entry, "manifest entry")?);
+            entry.path = sanitize_entry_path(&entry.path);
+            on_entry(entry)?;
+        }
+        self.next_seq += 1;
+        Ok(())
+    }
+
+    /// Check the `ManifestEnd` summary against everything received
+    ///
+    /// With `expected_signer`, the summary must also be signed by that key.
+    pub fn finish(
+        self,
+        summary: &[u8],
+        expected_signer: Option<&HybridPublicKey>,
+    ) -> Result<ManifestSummary> {
+        if summary.len() > MAX_SUMMARY_LEN {
+            return Err(ProtocolError::DecodingError(format!(
+                "manifest summary of {} bytes exceeds limit",
+                summary.len()
+            )));
+        }
+        let summary: ManifestSummary = decode(summary, "manifest summary")?;
+
+        if let Some(expected) = expected_signer {
+            if expected.ed25519 != summary.signer.ed25519 || expected.mldsa != summary.signer.mldsa
+            {
+                return Err(ProtocolError::TransferFailed(
+                    "manifest signed by an unexpected key".to_string(),
+                ));
+            }
+        }
+        let declared = Totals {
+            entries: summary.entry_count,
+            size: summary.total_size,
+            chunks: summary.total_chunks,
+        };
+        let digest = summary_transcript(&self.transfer_id, &declared, &summary.rolling_hash);
+        tallow_crypto::sig::hybrid::verify(&summary.signer, &digest, &summary.signature).map_err(
+            |_| {
+                ProtocolError::TransferFailed(
+                    "manifest summary signature verification failed".to_string(),
+                )
+            },
+        )?;
+
+        if summary.entry_count != self.totals.entries {
+            return Err(ProtocolError::TransferFailed(format!(
+                "manifest has {} entries, summary declares {}",
+                self.totals.entries, summary.entry_count
+            )));
+        }
+        if summary.total_size != self.totals.size || summary.total_chunks != self.totals.chunks {
+            return Err(ProtocolError::TransferFailed(
+                "manifest totals do not match the summary".to_string(),
+            ));
+        }
+        if summary.rolling_hash != self.hash.finalize() {
+            return Err(ProtocolError::TransferFailed(
+                "manifest entries do not match the signed rolling hash".to_string(),
+            ));
+        }
+        Ok(summary)
+    }
+}
+
+fn summary_transcript(
+    transfer_id: &[u8; 16],
+    totals: &Totals,
+    rolling_hash: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new_derive_key(domain::DOMAIN_MANIFEST_STREAM);
+    hasher.update(b"summary");
+    hasher.update(transfer_id);
+    hasher.update(&totals.entries.to_be_bytes());
+    hasher.update(&totals.size.to_be_bytes());
+    hasher.update(&totals.chunks.to_be_bytes());
+    hasher.update(rolling_hash);
+    hasher.finalize().into()
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T, what: &str) -> Result<Vec<u8>> {
+    postcard::to_stdvec(value)
+        .map_err(|e| ProtocolError::EncodingError(format!("{} serialize failed: {}", what, e)))
+}
+
+fn decode<'de, T: Deserialize<'de>>(bytes: &'de [u8], what: &str) -> Result<T> {
+    postcard::from_bytes(bytes)
+        .map_err(|e| ProtocolError::DecodingError(format!("{} decode failed: {}", what, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    fn header() -> ManifestHeader {
+        ManifestHeader::of(&FileManifest::new(CHUNK_SIZE))
+    }
+
+    fn entry(i: u64) -> FileEntry {
+        let size = i % 500_000;
+        FileEntry {
+            path: PathBuf::from(format!("tree/{:03}/file-{:07}.bin", i % 997, i)),
+            size,
+            hash: blake3::hash(&i.to_le_bytes()).into(),
+            chunk_count: size.div_ceil(CHUNK_SIZE as u64),
+        }
+    }
+
+    /// Collect every message of a small manifest
+    fn messages(count: u64, signer: &HybridSigner) -> Vec<Message> {
+        let mut streamer = ManifestStreamer::new([7; 16], header(), (0..count).map(entry), signer)
+            .with_batch_bytes(1024);
+        std::iter::from_fn(|| streamer.next_message().unwrap()).collect()
+    }
+
+    /// Feed `messages` to a fresh receiver and return its verdict
+    fn receive(messages: &[Message]) -> Result<ManifestSummary> {
+        let Message::ManifestStart {
+            transfer_id,
+            ref header,
+        } = messages[0]
+        else {
+            panic!("expected ManifestStart");
+        };
+        let mut receiver = ManifestStreamReceiver::start(transfer_id, header)?;
+        for msg in &messages[1..] {
+            match msg {
+                Message::ManifestChunk { seq, entries, .. } => {
+                    receiver.process_chunk(*seq, entries, |_| Ok(()))?
+                }
+                Message::ManifestEnd { summary, .. } => return receiver.finish(summary, None),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        panic!("no ManifestEnd");
+    }
+
+    #[test]
+    fn test_large_manifest_streams_in_bounded_batches() {
+        const COUNT: u64 = 150_000;
+        let signer = HybridSigner::keygen().unwrap();
+        let generated = Rc::new(Cell::new(0u64));
+        let source = {
+            let generated = Rc::clone(&generated);
+            (0..COUNT).map(move |i| {
+                generated.set(generated.get() + 1);
+                entry(i)
+            })
+        };
+        let mut streamer = ManifestStreamer::new([9; 16], header(), source, &signer);
+
+        let mut receiver = None;
+        let mut delivered = 0u64;
+        let mut most_outstanding = 0u64;
+        let mut batches = 0;
+        let mut expected_size = 0u64;
+        let mut summary = None;
+        // Each message is handed over as soon as it is produced
+        while let Some(msg) = streamer.next_message().unwrap() {
+            match msg {
+                Message::ManifestStart {
+                    transfer_id,
+                    header,
+                } => receiver = Some(ManifestStreamReceiver::start(transfer_id, &header).unwrap()),
+                Message::ManifestChunk { seq, entries, .. } => {
+                    assert!(entries.len() <= MAX_BATCH_LEN);
+                    batches += 1;
+                    let receiver = receiver.as_mut().unwrap();
+                    receiver
+                        .process_chunk(seq, &entries, |e| {
+                            assert_eq!(e, entry(delivered));
+                            expected_size += e.size;
+                            delivered += 1;
+                            most_outstanding = most_outstanding.max(generated.get() - delivered);
+                            Ok(())
+                        })
+                        .unwrap();
+                }
+                Message::ManifestEnd { summary: bytes, .. } => {
+                    summary = Some(receiver.take().unwrap().finish(&bytes, None).unwrap());
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        let summary = summary.unwrap();
+        assert_eq!(summary.entry_count, COUNT);
+        assert_eq!(summary.total_size, expected_size);
+        assert_eq!(delivered, COUNT);
+        assert!(batches > 50, "{} batches", batches);
+        // The source is only ever one batch ahead of the receiver
+        assert!(
+            most_outstanding < 2_000,
+            "{} entries outstanding",
+            most_outstanding
+        );
+    }
+
+    #[test]
+    fn test_dropped_entry_fails_rolling_signature() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut msgs = messages(200, &signer);
+        assert!(receive(&msgs).is_ok());
+
+        // Drop one entry from the middle batch
+        let Message::ManifestChunk { entries, .. } = &mut msgs[2] else {
+            panic!("expected ManifestChunk");
+        };
+        let mut batch: Vec<FileEntry> = postcard::from_bytes(entries).unwrap();
+        batch.remove(1);
+        *entries = postcard::to_stdvec(&batch).unwrap();
+        let err = receive(&msgs).unwrap_err().to_string();
+        assert!(err.contains("summary declares"), "{}", err);
+
+        // Lowering the declared count to match breaks the signature
+        let Message::ManifestEnd { summary, .. } = msgs.last_mut().unwrap() else {
+            panic!("expected ManifestEnd");
+        };
+        let mut forged: ManifestSummary = postcard::from_bytes(summary).unwrap();
+        forged.entry_count -= 1;
+        *summary = postcard::to_stdvec(&forged).unwrap();
+        let err = receive(&msgs).unwrap_err().to_string();
+        assert!(err.contains("signature verification failed"), "{}", err);
+    }
+
+    #[test]
+    fn test_replaced_entry_and_missing_batch_detected() {
+        let signer = HybridSigner::keygen().unwrap();
+        let original = messages(200, &signer);
+
+        // Same count and totals, different path
+        let mut msgs = original.clone();
+        let Message::ManifestChunk { entries, .. } = &mut msgs[1] else {
+            panic!("expected ManifestChunk");
+        };
+        let mut batch: Vec<FileEntry> = postcard::from_bytes(entries).unwrap();
+        batch[0].path = PathBuf::from("tree/evil.bin");
+        *entries = postcard::to_stdvec(&batch).unwrap();
+        let err = receive(&msgs).unwrap_err().to_string();
+        assert!(err.contains("rolling hash"), "{}", err);
+
+        let mut msgs = original;
+        msgs.remove(2);
+        let err = receive(&msgs).unwrap_err().to_string();
+        assert!(err.contains("expected 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_receiver_sanitizes_and_validates_entries() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut bad = entry(1);
+        bad.path = PathBuf::from("../../etc/passwd");
+        let mut streamer = ManifestStreamer::new([1; 16], header(), std::iter::once(bad), &signer);
+        let Some(Message::ManifestStart { header, .. }) = streamer.next_message().unwrap() else {
+            panic!("expected ManifestStart");
+        };
+        let Some(Message::ManifestChunk { entries, .. }) = streamer.next_message().unwrap() else {
+            panic!("expected ManifestChunk");
+        };
+        let mut receiver = ManifestStreamReceiver::start([1; 16], &header).unwrap();
+        let mut paths = Vec::new();
+        receiver
+            .process_chunk(0, &entries, |e| {
+                paths.push(e.path);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(paths, vec![PathBuf::from("etc/passwd")]);
+
+        // A chunk count that disagrees with the size is refused
+        let mut inconsistent = entry(2);
+        inconsistent.chunk_count += 1;
+        let mut streamer =
+            ManifestStreamer::new([1; 16], header(), std::iter::once(inconsistent), &signer);
+        streamer.next_message().unwrap();
+        assert!(streamer.next_message().is_err());
+    }
+}
@@ -4,12 +4,27 @@
 //! encryption, progress tracking, and resume.
 
 #[cfg(feature = "full")]
+pub mod bundle;
+#[cfg(feature = "full")]
+pub mod checksums;
 pub mod chunking;
 #[cfg(feature = "full")]
+pub mod delta;
+#[cfg(feature = "full")]
+pub mod disk;
+#[cfg(feature = "full")]
+pub mod estimate;
+#[cfg(feature = "full")]
 pub mod exclusion;
 #[cfg(feature = "full")]
 pub mod manifest;
 #[cfg(feature = "full")]
+pub mod manifest_stream;
+#[cfg(feature = "full")]
+pub mod multiplex;
+#[cfg(feature = "full")]
+pub mod naming;
+#[cfg(feature = "full")]
 pub mod progress;
 #[cfg(feature = "full")]
 pub mod queue;
@@ -17,24 +32,49 @@ pub mod queue;
 pub mod receive;
 #[cfg(feature = "full")]
 pub mod resume;
+#[cfg(feature = "full")]
+pub mod retransmit;
 pub mod sanitize;
 #[cfg(feature = "full")]
+pub mod selection;
+#[cfg(feature = "full")]
 pub mod send;
 #[cfg(feature = "full")]
+pub mod sparse;
+#[cfg(feature = "full")]
 pub mod state_machine;
 #[cfg(feature = "full")]
+pub mod stream;
+#[cfg(feature = "full")]
 pub mod sync;
 #[cfg(feature = "full")]
+pub mod volumes;
+#[cfg(feature = "full")]
 pub mod watch;
+#[cfg(feature = "full")]
+pub mod window;
 
 #[cfg(feature = "full")]
+pub use checksums::{ChecksumAlgorithm, ChecksumList};
 pub use chunking::{ChunkConfig, DEFAULT_CHUNK_SIZE};
 #[cfg(feature = "full")]
+pub use delta::{apply_delta, compute_delta, Delta, FileSignature};
+#[cfg(feature = "full")]
+pub use disk::WriteConfig;
+#[cfg(feature = "full")]
+pub use estimate::{ProbeReceiver, ThroughputSample, TransferEstimate};
+#[cfg(feature = "full")]
 pub use exclusion::ExclusionConfig;
 #[cfg(feature = "full")]
-pub use manifest::FileManifest;
+pub use manifest::{FileManifest, SkippedFile};
+#[cfg(feature = "full")]
+pub use manifest_stream::{ManifestStreamReceiver, ManifestStreamer};
+#[cfg(feature = "full")]
+pub use multiplex::InterleavedChunker;
 #[cfg(feature = "full")]
-pub use progress::TransferProgress;
+pub use naming::{ConflictPolicy, DirectoryLayout, OutputName, OutputTemplate, TemplateContext};
+#[cfg(feature = "full")]
+pub use progress::{CompressionStats, TransferProgress};
 #[cfg(feature = "full")]
 pub use queue::{QueueHandle, TransferQueue};
 #[cfg(feature = "full")]
@@ -42,8 +82,18 @@ pub use receive::ReceivePipeline;
 #[cfg(feature = "full")]
 pub use resume::ResumeState;
 #[cfg(feature = "full")]
+pub use retransmit::SentChunks;
+#[cfg(feature = "full")]
+pub use selection::AcceptanceMask;
+#[cfg(feature = "full")]
 pub use send::SendPipeline;
 #[cfg(feature = "full")]
 pub use state_machine::{TransferState, TransferStateMachine};
 #[cfg(feature = "full")]
+pub use stream::{StreamChunker, StreamReceiver, StreamTrailer};
+#[cfg(feature = "full")]
+pub use volumes::{VolumeManifest, VolumeWriter};
+#[cfg(feature = "full")]
 pub use watch::{WatchConfig, WatchEvent, WatchHandle};
+#[cfg(feature = "full")]
+pub use window::{send_window, InFlight, WindowReceiver, WindowSender};
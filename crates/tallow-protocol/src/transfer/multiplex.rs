@@ -0,0 +1,250 @@
+//! Concurrent multi-file chunking over one connection
+//!
+//! Sending many small files one after another pays per-file latency (open,
+//! first read, last ack) serially. [`InterleavedChunker`] keeps several
+//! files open and emits their chunks round-robin as `FileChunk` messages,
+//! which carry the file's manifest index so the receiver can route each
+//! chunk with [`ReceivePipeline::process_file_chunk`].
+//!
+//! Only use this when both peers negotiated
+//! [`FeatureSet::MULTIPLEXED_FILES`](crate::wire::FeatureSet::MULTIPLEXED_FILES).
+//!
+//! [`ReceivePipeline::process_file_chunk`]: crate::transfer::ReceivePipeline::process_file_chunk
+
+use crate::transfer::send::{FileChunkReader, SendPipeline};
+use crate::wire::Message;
+use crate::Result;
+use std::collections::VecDeque;
+
+/// Default number of files read concurrently
+pub const DEFAULT_MAX_CONCURRENT_FILES: usize = 4;
+
+/// A file currently being chunked
+struct ActiveFile {
+    file_id: u32,
+    reader: FileChunkReader,
+    next_index: u64,
+}
+
+/// Produces `FileChunk` messages for several files at once
+///
+/// Files are opened in manifest order, at most `max_concurrent` at a time,
+/// and each call to [`next_message`](Self::next_message) takes the next
+/// chunk from the next open file in turn. Compression totals are recorded
+/// for the transfer as a whole but not per file.
+pub struct InterleavedChunker<'a> {
+    pipeline: &'a SendPipeline,
+    pending: VecDeque<u32>,
+    active: VecDeque<ActiveFile>,
+    max_concurrent: usize,
+}
+
+impl std::fmt::Debug for InterleavedChunker<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InterleavedChunker")
+            .field("pending", &self.pending.len())
+            .field("active", &self.active.len())
+            .field("max_concurrent", &self.max_concurrent)
+            .finish()
+    }
+}
+
+impl<'a> InterleavedChunker<'a> {
+    /// Chunk every file the receiver accepted, `max_concurrent` at a time
+    ///
+    /// A `max_concurrent` of zero is treated as one.
+    pub fn new(pipeline: &'a SendPipeline, max_concurrent: usize) -> Self {
+        Self {
+            pipeline,
+            pending: pipeline.acceptance().indices().into(),
+            active: VecDeque::new(),
+            max_concurrent: max_concurrent.max(1),
+        }
+    }
+
+    /// Chunk only the given manifest files (e.g. after a `FileSelection`)
+    pub fn with_files(mut self, file_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.pending = file_ids.into_iter().collect();
+        self
+    }
+
+    /// Produce the next `FileChunk`, or `None` once every file is sent
+    pub async fn next_message(&mut self) -> Result<Option<Message>> {
+        loop {
+            while self.active.len() < self.max_concurrent {
+                let Some(file_id) = self.pending.pop_front() else {
+                    break;
+                };
+                self.open(file_id).await?;
+            }
+
+            let Some(mut file) = self.active.pop_front() else {
+                return Ok(None);
+            };
+            let Some(raw) = file.reader.next_chunk().await? else {
+                // File finished; its slot goes to the next pending file
+                continue;
+            };
+
+            let msg = self
+                .pipeline
+                .encrypt_file_chunk(&raw, file.file_id, file.next_index)?;
+            file.next_index += 1;
+            self.active.push_back(file);
+            return Ok(Some(msg));
+        }
+    }
+
+    async fn open(&mut self, file_id: u32) -> Result<()> {
+        let path = self
+            .pipeline
+            .source_paths()
+            .get(file_id as usize)
+            .ok_or_else(|| {
+                crate::ProtocolError::TransferFailed(format!("file {} has no source path", file_id))
+            })?;
+        let reader = FileChunkReader::open(path, self.pipeline.chunk_size())
+            .await?
+            .skip_holes(self.pipeline.manifest().holes(file_id as usize));
+        self.active.push_back(ActiveFile {
+            file_id,
+            reader,
+            next_index: 0,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::chunking::{self, ChunkConfig};
+    use crate::transfer::ReceivePipeline;
+
+    const TRANSFER_ID: [u8; 16] = [0x31; 16];
+    const KEY: [u8; 32] = [0x42; 32];
+
+    /// Sender with two multi-chunk files
+    async fn two_file_sender(dir: &std::path::Path) -> (SendPipeline, Vec<Vec<u8>>) {
+        let size = chunking::MIN_CHUNK_SIZE;
+        let contents = vec![
+            (0..3 * size + 100)
+                .map(|i| (i % 251) as u8)
+                .collect::<Vec<u8>>(),
+            (0..2 * size + 7)
+                .map(|i| (i % 13) as u8)
+                .collect::<Vec<u8>>(),
+        ];
+        let src = dir.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        let mut paths = Vec::new();
+        for (i, data) in contents.iter().enumerate() {
+            let path = src.join(format!("file{}.bin", i));
+            std::fs::write(&path, data).unwrap();
+            paths.push(path);
+        }
+
+        let mut config = ChunkConfig::new();
+        config.size = size;
+        let mut pipeline = SendPipeline::new(TRANSFER_ID, KEY).with_chunk_config(config);
+        pipeline.prepare(&paths).await.unwrap();
+        (pipeline, contents)
+    }
+
+    fn receiver(out: &std::path::Path, pipeline: &SendPipeline) -> ReceivePipeline {
+        let mut receiver = ReceivePipeline::new(TRANSFER_ID, out, KEY);
+        let manifest = pipeline.manifest().to_bytes().unwrap();
+        receiver.process_offer(&manifest).unwrap();
+        receiver
+    }
+
+    #[tokio::test]
+    async fn test_two_files_concurrently_over_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pipeline, contents) = two_file_sender(dir.path()).await;
+        let out = dir.path().join("out");
+        let mut receiver = receiver(&out, &pipeline);
+
+        // Encoded messages cross an in-memory channel, as over a connection
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<u8>>(4);
+        let send = async {
+            let mut chunker = InterleavedChunker::new(&pipeline, 2);
+            while let Some(msg) = chunker.next_message().await.unwrap() {
+                tx.send(postcard::to_stdvec(&msg).unwrap()).await.unwrap();
+            }
+            drop(tx);
+        };
+        let recv = async {
+            let mut order = Vec::new();
+            while let Some(bytes) = rx.recv().await {
+                match postcard::from_bytes::<Message>(&bytes).unwrap() {
+                    Message::FileChunk {
+                        file_id,
+                        index,
+                        data,
+                        ..
+                    } => {
+                        order.push(file_id);
+                        let ack = receiver.process_file_chunk(file_id, index, &data).unwrap();
+                        assert!(matches!(ack, Some(Message::Ack { .. })));
+                    }
+                    other => panic!("unexpected {:?}", other),
+                }
+            }
+            order
+        };
+        let ((), order) = tokio::join!(send, recv);
+
+        // Both files were in flight at once
+        assert_eq!(&order[..4], &[0, 1, 0, 1]);
+        assert!(receiver.is_complete());
+
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(written.len(), 2);
+        for (path, data) in written.iter().zip(&contents) {
+            assert_eq!(&std::fs::read(path).unwrap(), data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_chunks_routed_to_right_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pipeline, contents) = two_file_sender(dir.path()).await;
+
+        let mut chunker = InterleavedChunker::new(&pipeline, 2);
+        let mut messages = Vec::new();
+        while let Some(msg) = chunker.next_message().await.unwrap() {
+            messages.push(msg);
+        }
+        let fields = |m: &Message| match m {
+            Message::FileChunk {
+                file_id,
+                index,
+                data,
+                ..
+            } => (*file_id, *index, data.clone()),
+            other => panic!("unexpected {:?}", other),
+        };
+
+        // A chunk claiming the wrong file fails authentication
+        let (file_id, index, data) = fields(&messages[1]);
+        assert_eq!((file_id, index), (1, 0));
+        let mut receiver_a = receiver(&dir.path().join("a"), &pipeline);
+        assert!(receiver_a.process_file_chunk(0, 0, &data).is_err());
+        assert!(receiver_a.process_file_chunk(1, 9, &data).is_err());
+        assert!(receiver_a.process_file_chunk(5, 0, &data).is_err());
+
+        // Delivered in reverse, every chunk still lands in its own file
+        let mut receiver_b = receiver(&dir.path().join("b"), &pipeline);
+        for msg in messages.iter().rev() {
+            let (file_id, index, data) = fields(msg);
+            receiver_b
+                .process_file_chunk(file_id, index, &data)
+                .unwrap();
+        }
+        let written = receiver_b.finalize().await.unwrap();
+        for (path, data) in written.iter().zip(&contents) {
+            assert_eq!(&std::fs::read(path).unwrap(), data);
+        }
+    }
+}
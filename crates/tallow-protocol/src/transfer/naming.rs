@@ -0,0 +1,441 @@
+//! Output naming for received files
+//!
+//! An [`OutputTemplate`] renames each received file using placeholders:
+//!
+//! | Placeholder | Expands to                                         |
+//! |-------------|----------------------------------------------------|
+//! | `{name}`    | original file name without extension               |
+//! | `{ext}`     | original extension without the dot (may be empty)  |
+//! | `{date}`    | receive date, `YYYY-MM-DD`                         |
+//! | `{sender}`  | identifier of the sending peer                     |
+//! | `{n}`       | counter, incremented until the name is unused      |
+//!
+//! The template applies to the file name only. Whether the directories
+//! from the manifest are kept is a [`DirectoryLayout`] choice made first;
+//! [`resolve_output_names`] then settles collisions according to a
+//! [`ConflictPolicy`].
+
+use crate::{ProtocolError, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Upper bound on counter values tried when looking for a free name
+const MAX_COUNTER: u32 = 10_000;
+
+/// What to do when a received file's target name already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Don't receive the file
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Pick a new, unused name
+    Rename,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" => Ok(Self::Rename),
+            other => Err(ProtocolError::InvalidMessage(format!(
+                "unknown conflict policy '{}' (expected skip, overwrite or rename)",
+                other
+            ))),
+        }
+    }
+}
+
+/// How the sender's directory structure is mapped under the output directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectoryLayout {
+    /// Write every file directly into the output directory
+    #[default]
+    Flatten,
+    /// Recreate the manifest's relative subdirectories
+    Preserve,
+}
+
+impl DirectoryLayout {
+    /// Relative path `original` is written to, before templates and conflicts
+    pub fn apply(self, original: &Path) -> PathBuf {
+        match self {
+            Self::Preserve => original.to_path_buf(),
+            Self::Flatten => original
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("unnamed")),
+        }
+    }
+}
+
+/// Values substituted for the non-file placeholders
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    /// `{date}` value
+    pub date: String,
+    /// `{sender}` value
+    pub sender: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Name,
+    Ext,
+    Date,
+    Sender,
+    Counter,
+}
+
+/// Parsed `--output-template`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, rejecting unknown or unterminated placeholders
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |msg: String| ProtocolError::InvalidMessage(msg);
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(open) = rest.find('{') {
+            literal.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let close = after
+                .find('}')
+                .ok_or_else(|| invalid(format!("unterminated placeholder in '{}'", template)))?;
+            let segment = match &after[..close] {
+                "name" => Segment::Name,
+                "ext" => Segment::Ext,
+                "date" => Segment::Date,
+                "sender" => Segment::Sender,
+                "n" => Segment::Counter,
+                other => {
+                    return Err(invalid(format!(
+                        "unknown placeholder '{{{}}}' in output template",
+                        other
+                    )))
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(segment);
+            rest = &after[close + 1..];
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if segments.is_empty() {
+            return Err(invalid("output template is empty".to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Whether the template contains `{n}`
+    pub fn has_counter(&self) -> bool {
+        self.segments.contains(&Segment::Counter)
+    }
+
+    /// Expand the template for `original`, keeping its parent directories
+    ///
+    /// A `.` directly before an empty `{ext}` is dropped, so `{name}.{ext}`
+    /// yields `README` rather than `README.`.
+    pub fn expand(&self, original: &Path, ctx: &TemplateContext, n: u32) -> PathBuf {
+        let stem = original
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = original
+            .extension()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut name = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => name.push_str(text),
+                Segment::Name => name.push_str(&stem),
+                Segment::Ext if ext.is_empty() => {
+                    if name.ends_with('.') {
+                        name.pop();
+                    }
+                }
+                Segment::Ext => name.push_str(&ext),
+                Segment::Date => name.push_str(&path_safe(&ctx.date)),
+                Segment::Sender => name.push_str(&path_safe(&ctx.sender)),
+                Segment::Counter => name.push_str(&n.to_string()),
+            }
+        }
+
+        match original.parent() {
+            Some(parent) => parent.join(name),
+            None => PathBuf::from(name),
+        }
+    }
+}
+
+/// Where one received file should be written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputName {
+    /// Write to this path (relative to the output directory)
+    Write(PathBuf),
+    /// Don't receive this file
+    Skip,
+}
+
+/// Decide the output name for each manifest entry
+///
+/// `exists` reports whether a relative path is already taken on disk.
+/// Files are expanded with `template` (or keep their name) and then:
+///
+/// - templates with `{n}` count up from 1 until the name is free;
+/// - otherwise a name taken on disk is handled by `policy`, with
+///   [`ConflictPolicy::Rename`] inserting `-1`, `-2`, ... before the
+///   extension.
+///
+/// Two files of the same transfer never share a name: later ones are
+/// renamed regardless of policy, so nothing received is silently lost.
+pub fn resolve_output_names(
+    files: &[PathBuf],
+    template: Option<&OutputTemplate>,
+    ctx: &TemplateContext,
+    policy: ConflictPolicy,
+    exists: impl Fn(&Path) -> bool,
+) -> Result<Vec<OutputName>> {
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    let mut names = Vec::with_capacity(files.len());
+
+    for original in files {
+        let base = match template {
+            Some(t) if t.has_counter() => {
+                let path = first_free(
+                    |n| t.expand(original, ctx, n),
+                    |p| taken.contains(p) || exists(p),
+                )?;
+                taken.insert(path.clone());
+                names.push(OutputName::Write(path));
+                continue;
+            }
+            Some(t) => t.expand(original, ctx, 0),
+            None => original.clone(),
+        };
+
+        let on_disk = exists(&base);
+        let name = if taken.contains(&base) || (on_disk && policy == ConflictPolicy::Rename) {
+            let path = first_free(
+                |n| with_suffix(&base, n),
+                |p| taken.contains(p) || exists(p),
+            )?;
+            OutputName::Write(path)
+        } else if on_disk && policy == ConflictPolicy::Skip {
+            OutputName::Skip
+        } else {
+            OutputName::Write(base)
+        };
+
+        if let OutputName::Write(ref path) = name {
+            taken.insert(path.clone());
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// First candidate (counter from 1) that is not `used`
+fn first_free(candidate: impl Fn(u32) -> PathBuf, used: impl Fn(&Path) -> bool) -> Result<PathBuf> {
+    (1..=MAX_COUNTER)
+        .map(candidate)
+        .find(|p| !used(p))
+        .ok_or_else(|| {
+            ProtocolError::TransferFailed(format!(
+                "no free output name after {} attempts",
+                MAX_COUNTER
+            ))
+        })
+}
+
+/// `dir/report.pdf` -> `dir/report-<n>.pdf`
+fn with_suffix(path: &Path, n: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, n),
+    };
+    path.with_file_name(name)
+}
+
+/// Keep context values from introducing path separators
+fn path_safe(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c == '/' || c == '\\' { '_' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> TemplateContext {
+        TemplateContext {
+            date: "2026-03-14".to_string(),
+            sender: "alice/laptop".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_template_placeholders_expand() {
+        let t = OutputTemplate::parse("{date}_{sender}_{name}-{n}.{ext}").unwrap();
+        assert!(t.has_counter());
+        assert_eq!(
+            t.expand(Path::new("docs/report.pdf"), &ctx(), 3),
+            PathBuf::from("docs/2026-03-14_alice_laptop_report-3.pdf")
+        );
+
+        let t = OutputTemplate::parse("{name}.{ext}").unwrap();
+        assert_eq!(
+            t.expand(Path::new("README"), &ctx(), 0),
+            PathBuf::from("README")
+        );
+        assert_eq!(
+            t.expand(Path::new("archive.tar.gz"), &ctx(), 0),
+            PathBuf::from("archive.tar.gz")
+        );
+    }
+
+    #[test]
+    fn test_template_rejects_bad_placeholders() {
+        assert!(OutputTemplate::parse("{name}-{bogus}").is_err());
+        assert!(OutputTemplate::parse("{name").is_err());
+        assert!(OutputTemplate::parse("").is_err());
+    }
+
+    #[test]
+    fn test_rename_policy_produces_unique_names() {
+        let existing: HashSet<PathBuf> = ["a.txt", "a-1.txt", "b.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let files: Vec<PathBuf> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let t = OutputTemplate::parse("{name}.{ext}").unwrap();
+
+        let names = resolve_output_names(&files, Some(&t), &ctx(), ConflictPolicy::Rename, |p| {
+            existing.contains(p)
+        })
+        .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                OutputName::Write(PathBuf::from("a-2.txt")),
+                OutputName::Write(PathBuf::from("b-1.txt")),
+                OutputName::Write(PathBuf::from("c.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_collisions_within_transfer_are_renamed() {
+        // Every file maps to the same name
+        let files: Vec<PathBuf> = ["x.bin", "y.bin", "z.bin"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let t = OutputTemplate::parse("{sender}.{ext}").unwrap();
+
+        let names =
+            resolve_output_names(&files, Some(&t), &ctx(), ConflictPolicy::Overwrite, |_| {
+                false
+            })
+            .unwrap();
+        let unique: HashSet<_> = names.iter().collect();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(
+            names[0],
+            OutputName::Write(PathBuf::from("alice_laptop.bin"))
+        );
+    }
+
+    #[test]
+    fn test_counter_template_skips_taken_names() {
+        let existing: HashSet<PathBuf> = ["photo-1.jpg"].iter().map(PathBuf::from).collect();
+        let files = vec![PathBuf::from("photo.jpg"), PathBuf::from("photo.jpg")];
+        let t = OutputTemplate::parse("{name}-{n}.{ext}").unwrap();
+
+        let names = resolve_output_names(&files, Some(&t), &ctx(), ConflictPolicy::Skip, |p| {
+            existing.contains(p)
+        })
+        .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                OutputName::Write(PathBuf::from("photo-2.jpg")),
+                OutputName::Write(PathBuf::from("photo-3.jpg")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_and_overwrite_policies() {
+        let files = vec![PathBuf::from("keep.txt"), PathBuf::from("new.txt")];
+        let exists = |p: &Path| p == Path::new("keep.txt");
+
+        let skip =
+            resolve_output_names(&files, None, &ctx(), ConflictPolicy::Skip, exists).unwrap();
+        assert_eq!(skip[0], OutputName::Skip);
+        assert_eq!(skip[1], OutputName::Write(PathBuf::from("new.txt")));
+
+        let overwrite =
+            resolve_output_names(&files, None, &ctx(), ConflictPolicy::Overwrite, exists).unwrap();
+        assert_eq!(overwrite[0], OutputName::Write(PathBuf::from("keep.txt")));
+    }
+
+    #[test]
+    fn test_flatten_layout_renames_colliding_names() {
+        let files: Vec<PathBuf> = ["docs/readme.md", "src/readme.md", "src/lib.rs"]
+            .iter()
+            .map(|p| DirectoryLayout::Flatten.apply(Path::new(p)))
+            .collect();
+
+        let names =
+            resolve_output_names(&files, None, &ctx(), ConflictPolicy::Overwrite, |_| false)
+                .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                OutputName::Write(PathBuf::from("readme.md")),
+                OutputName::Write(PathBuf::from("readme-1.md")),
+                OutputName::Write(PathBuf::from("lib.rs")),
+            ]
+        );
+
+        assert_eq!(
+            DirectoryLayout::Preserve.apply(Path::new("src/readme.md")),
+            PathBuf::from("src/readme.md")
+        );
+    }
+
+    #[test]
+    fn test_conflict_policy_from_str() {
+        assert_eq!(
+            "rename".parse::<ConflictPolicy>().unwrap(),
+            ConflictPolicy::Rename
+        );
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+}
@@ -2,19 +2,74 @@
 
 use std::time::{Duration, Instant};
 
+/// Pre- and post-compression byte counts for a transfer or a single file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// Bytes before compression
+    pub original_bytes: u64,
+    /// Bytes after compression (before encryption)
+    pub compressed_bytes: u64,
+}
+
+impl CompressionStats {
+    /// Account for one chunk
+    pub fn record(&mut self, original: u64, compressed: u64) {
+        self.original_bytes += original;
+        self.compressed_bytes += compressed;
+    }
+
+    /// Compression ratio as original / compressed (2.0 = half the size)
+    ///
+    /// Incompressible data reports roughly 1.0. Empty input reports exactly 1.0.
+    pub fn ratio(&self) -> f64 {
+        if self.original_bytes == 0 || self.compressed_bytes == 0 {
+            return 1.0;
+        }
+        self.original_bytes as f64 / self.compressed_bytes as f64
+    }
+
+    /// Bytes saved by compression, never negative
+    ///
+    /// Incompressible data can grow slightly from framing overhead; that
+    /// reports as zero savings.
+    pub fn bytes_saved(&self) -> u64 {
+        self.original_bytes.saturating_sub(self.compressed_bytes)
+    }
+
+    /// Savings as a percentage of the original size (0.0-100.0)
+    pub fn percent_saved(&self) -> f64 {
+        if self.original_bytes == 0 {
+            return 0.0;
+        }
+        self.bytes_saved() as f64 / self.original_bytes as f64 * 100.0
+    }
+}
+
 /// Transfer progress information
+///
+/// A sender tracks two figures: bytes handed to the transport
+/// (`bytes_transferred`) and bytes the receiver has confirmed holding
+/// (`confirmed_bytes`). Once the first confirmation arrives, speed and ETA
+/// are based on confirmed bytes, since data buffered in the network or the
+/// peer's socket has not really arrived yet.
 #[derive(Debug, Clone)]
 pub struct TransferProgress {
     /// Bytes transferred
     pub bytes_transferred: u64,
+    /// Bytes the receiver confirmed (cumulative acks)
+    pub confirmed_bytes: u64,
     /// Total bytes
     pub total_bytes: u64,
     /// Current speed in bytes/second
     pub speed_bps: u64,
     /// Estimated time remaining in seconds
     pub eta_seconds: u64,
+    /// Compression accounting; `None` when compression is disabled
+    pub compression: Option<CompressionStats>,
     /// Transfer start time
     start_time: Instant,
+    /// Whether any confirmation has been recorded
+    confirming: bool,
 }
 
 impl TransferProgress {
@@ -22,23 +77,56 @@ impl TransferProgress {
     pub fn new(total_bytes: u64) -> Self {
         Self {
             bytes_transferred: 0,
+            confirmed_bytes: 0,
             total_bytes,
             speed_bps: 0,
             eta_seconds: 0,
+            compression: None,
             start_time: Instant::now(),
+            confirming: false,
         }
     }
 
     /// Update progress
     pub fn update(&mut self, bytes_transferred: u64) {
         self.bytes_transferred = bytes_transferred;
+        if !self.confirming {
+            self.update_rate(bytes_transferred);
+        }
+    }
+
+    /// Record that the receiver holds the first `confirmed_bytes` bytes
+    ///
+    /// Confirmations only move forward; a stale (lower) one is ignored.
+    pub fn confirm(&mut self, confirmed_bytes: u64) {
+        self.confirming = true;
+        self.confirmed_bytes = self
+            .confirmed_bytes
+            .max(confirmed_bytes.min(self.total_bytes));
+        self.update_rate(self.confirmed_bytes);
+    }
+
+    /// Bytes sent but not yet confirmed by the receiver
+    pub fn in_flight(&self) -> u64 {
+        self.bytes_transferred.saturating_sub(self.confirmed_bytes)
+    }
+
+    /// Get confirmed completion percentage
+    pub fn confirmed_percentage(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.confirmed_bytes as f64 / self.total_bytes as f64) * 100.0
+    }
+
+    fn update_rate(&mut self, bytes: u64) {
         let elapsed = self.start_time.elapsed();
 
         if elapsed.as_secs() > 0 {
-            self.speed_bps = bytes_transferred / elapsed.as_secs();
+            self.speed_bps = bytes / elapsed.as_secs();
 
             if self.speed_bps > 0 {
-                let remaining = self.total_bytes.saturating_sub(bytes_transferred);
+                let remaining = self.total_bytes.saturating_sub(bytes);
                 self.eta_seconds = remaining / self.speed_bps;
             }
         }
@@ -57,3 +145,38 @@ impl TransferProgress {
         self.start_time.elapsed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_ratio() {
+        let mut stats = CompressionStats::default();
+        stats.record(600, 150);
+        stats.record(400, 100);
+        assert_eq!(stats.original_bytes, 1000);
+        assert_eq!(stats.compressed_bytes, 250);
+        assert!((stats.ratio() - 4.0).abs() < 1e-9);
+        assert_eq!(stats.bytes_saved(), 750);
+        assert!((stats.percent_saved() - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_incompressible_reports_no_negative_savings() {
+        // Framing overhead makes incompressible data slightly larger
+        let mut stats = CompressionStats::default();
+        stats.record(1_000_000, 1_000_013);
+        assert!((stats.ratio() - 1.0).abs() < 0.001);
+        assert_eq!(stats.bytes_saved(), 0);
+        assert_eq!(stats.percent_saved(), 0.0);
+    }
+
+    #[test]
+    fn test_empty_stats() {
+        let stats = CompressionStats::default();
+        assert_eq!(stats.ratio(), 1.0);
+        assert_eq!(stats.bytes_saved(), 0);
+        assert_eq!(stats.percent_saved(), 0.0);
+    }
+}
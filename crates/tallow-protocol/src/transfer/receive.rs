@@ -9,14 +9,20 @@
 
 use crate::compression::{self, CompressionAlgorithm};
 use crate::transfer::chunking;
-use crate::transfer::manifest::FileManifest;
+use crate::transfer::disk::{self, OutputFile, WriteConfig};
+use crate::transfer::manifest::{FileEntry, FileManifest};
+use crate::transfer::naming::OutputName;
 use crate::transfer::progress::TransferProgress;
 use crate::transfer::resume::ResumeState;
+use crate::transfer::selection::AcceptanceMask;
+use crate::transfer::sparse::{ContentHasher, Hole, SparseFile, ZeroFill};
+use crate::transfer::volumes::{self, VolumeWriter};
 use crate::wire::Message;
 use crate::{ProtocolError, Result};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use tokio::io::AsyncWriteExt;
+use tallow_crypto::hash::HashAlgorithm;
 
 /// Maximum number of chunks to buffer in memory (for non-streaming mode)
 const MAX_BUFFERED_CHUNKS: usize = 65_536;
@@ -25,6 +31,9 @@ const MAX_BUFFERED_CHUNKS: usize = 65_536;
 /// Files larger than this are streamed to disk as chunks arrive.
 const STREAMING_THRESHOLD: u64 = 10 * 1024 * 1024; // 10 MB
 
+/// Opens the temp file a streamed chunk is written to
+type ChunkWriterFn = dyn Fn(&Path) -> std::io::Result<Box<dyn Write>> + Send + Sync;
+
 /// Receive pipeline for file transfers
 pub struct ReceivePipeline {
     /// Transfer ID
@@ -47,12 +56,48 @@ pub struct ReceivePipeline {
     expected_total_chunks: Option<u64>,
     /// Whether sender uses per-chunk compression
     per_chunk_compression: bool,
+    /// Whether each chunk is tagged as compressed or stored
+    adaptive_compression: bool,
     /// Temp directory for streaming large transfers (chunks written to disk)
     temp_dir: Option<PathBuf>,
     /// Whether we're using streaming mode (temp file storage)
     streaming_mode: bool,
     /// BLAKE3 hashes of received chunks (for Merkle tree verification)
     chunk_hashes: Vec<Option<[u8; 32]>>,
+    /// Disk write strategy (preallocation, direct I/O)
+    write_config: WriteConfig,
+    /// Whether output files have been preallocated
+    outputs_prepared: bool,
+    /// Output files created by preallocation (removed if never finalized)
+    preallocated: Vec<PathBuf>,
+    /// Whether finalize completed successfully
+    finalized: bool,
+    /// Per-file output names chosen by the receiver, in manifest order
+    output_names: Option<Vec<OutputName>>,
+    /// Where to write the resume state if the disk fills up
+    checkpoint_path: Option<PathBuf>,
+    /// Keep temp chunks on drop (they back a saved resume state)
+    keep_temp: bool,
+    /// Opens temp chunk files (overridable so tests can simulate a full disk)
+    open_chunk_writer: Box<ChunkWriterFn>,
+    /// Resend requests allowed per chunk before failing (0 = never request)
+    max_chunk_retries: u32,
+    /// Resend requests made so far, by chunk index
+    chunk_retries: HashMap<u64, u32>,
+    /// Files accepted from the offer (`None` = all of them)
+    accepted: Option<AcceptanceMask>,
+    /// Files larger than this are written as numbered volumes
+    split_size: Option<u64>,
+    /// Send a `CumulativeAck` every this many chunks (`None` = never)
+    cumulative_ack_interval: Option<u64>,
+    /// Chunks below this index are all stored
+    contiguous: u64,
+    /// `received` value of the last `CumulativeAck` produced
+    acked_through: u64,
+    /// Chunk payloads carry a CRC32C trailer to check before decrypting
+    chunk_checksum: bool,
+    /// Manifest paths that tried to leave the output directory, as sent
+    unsafe_paths: Vec<PathBuf>,
 }
 
 impl Drop for ReceivePipeline {
@@ -62,7 +107,16 @@ impl Drop for ReceivePipeline {
 
         // Clean up temp directory on drop (best effort)
         if let Some(ref temp_dir) = self.temp_dir {
-            let _ = std::fs::remove_dir_all(temp_dir);
+            if !self.keep_temp {
+                let _ = std::fs::remove_dir_all(temp_dir);
+            }
+        }
+
+        // Don't leave zero-filled placeholders behind for aborted transfers
+        if !self.finalized {
+            for path in &self.preallocated {
+                let _ = std::fs::remove_file(path);
+            }
         }
     }
 }
@@ -91,27 +145,108 @@ impl ReceivePipeline {
             compression: CompressionAlgorithm::Zstd,
             expected_total_chunks: None,
             per_chunk_compression: true,
+            adaptive_compression: false,
             temp_dir: None,
             streaming_mode: false,
             chunk_hashes: Vec::new(),
+            write_config: WriteConfig::default(),
+            outputs_prepared: false,
+            preallocated: Vec::new(),
+            finalized: false,
+            output_names: None,
+            checkpoint_path: None,
+            keep_temp: false,
+            open_chunk_writer: Box::new(create_chunk_file),
+            max_chunk_retries: 0,
+            chunk_retries: HashMap::new(),
+            accepted: None,
+            split_size: None,
+            cumulative_ack_interval: None,
+            contiguous: 0,
+            acked_through: 0,
+            chunk_checksum: false,
+            unsafe_paths: Vec::new(),
         }
     }
 
+    /// Answer chunks that fail authentication with `ResendChunks`
+    ///
+    /// Each chunk may be re-requested up to `max_retries` times before the
+    /// transfer fails. Only enable this when the sender negotiated
+    /// [`FeatureSet::CHUNK_RETRANSMIT`](crate::wire::FeatureSet::CHUNK_RETRANSMIT).
+    pub fn with_chunk_retransmit(mut self, max_retries: u32) -> Self {
+        self.max_chunk_retries = max_retries;
+        self
+    }
+
+    /// Produce a `CumulativeAck` every `interval` chunks (see
+    /// [`cumulative_ack`](Self::cumulative_ack))
+    ///
+    /// Only enable this when the sender negotiated
+    /// [`FeatureSet::CUMULATIVE_ACK`](crate::wire::FeatureSet::CUMULATIVE_ACK).
+    pub fn with_cumulative_ack(mut self, interval: u64) -> Self {
+        self.cumulative_ack_interval = Some(interval.max(1));
+        self
+    }
+
+    /// Expect a CRC32C trailer on every chunk and check it before decrypting
+    ///
+    /// A mismatch is reported as
+    /// [`ProtocolError::ChunkChecksumMismatch`] (or re-requested, with
+    /// chunk retransmit). Only enable this when
+    /// [`FeatureSet::CHUNK_CHECKSUM`](crate::wire::FeatureSet::CHUNK_CHECKSUM)
+    /// was negotiated.
+    pub fn with_chunk_checksum(mut self, enabled: bool) -> Self {
+        self.chunk_checksum = enabled;
+        self
+    }
+
     /// Set a resume state for continuing an interrupted transfer
     pub fn with_resume(mut self, resume: ResumeState) -> Self {
         self.resume = Some(resume);
         self
     }
 
+    /// Set the disk write strategy (preallocation, direct I/O)
+    pub fn with_write_config(mut self, config: WriteConfig) -> Self {
+        self.write_config = config;
+        self
+    }
+
+    /// Write files larger than `volume_size` bytes as numbered parts
+    ///
+    /// See [`volumes`](crate::transfer::volumes). Such files are never
+    /// written whole, so they fit on media with a file size limit; the path
+    /// `finalize` reports for them is the volume manifest.
+    pub fn with_split_size(mut self, volume_size: u64) -> Self {
+        self.split_size = Some(volume_size);
+        self
+    }
+
+    /// Set where the resume state is saved if the disk fills up mid-transfer
+    pub fn with_checkpoint_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint_path = Some(path.into());
+        self
+    }
+
     /// Process a FileOffer message — parse manifest and prepare for reception
     ///
     /// Returns the manifest for user confirmation before accepting.
     pub fn process_offer(&mut self, manifest_bytes: &[u8]) -> Result<&FileManifest> {
         let mut manifest = FileManifest::from_bytes(manifest_bytes)?;
+        self.unsafe_paths = manifest
+            .files
+            .iter()
+            .filter(|f| {
+                crate::transfer::sanitize::check_relative_path(&f.path.to_string_lossy()).is_err()
+            })
+            .map(|f| f.path.clone())
+            .collect();
         manifest.sanitize_paths();
 
-        self.progress = Some(TransferProgress::new(manifest.total_size));
+        self.progress = Some(TransferProgress::new(manifest.data_size()));
         self.per_chunk_compression = manifest.per_chunk_compression;
+        self.adaptive_compression = manifest.adaptive_compression;
 
         if self.resume.is_none() {
             self.resume = Some(ResumeState::new(
@@ -131,8 +266,9 @@ impl ReceivePipeline {
             };
         }
 
-        // Use streaming mode for large transfers to avoid OOM
-        self.streaming_mode = manifest.total_size > STREAMING_THRESHOLD;
+        // Use streaming mode for large transfers to avoid OOM; holes in
+        // sparse files are never held in memory
+        self.streaming_mode = manifest.data_size() > STREAMING_THRESHOLD;
         if self.streaming_mode {
             let temp_dir = self.output_dir.join(".tallow_temp");
             let _ = std::fs::create_dir_all(&temp_dir);
@@ -157,6 +293,157 @@ impl ReceivePipeline {
             .ok_or_else(|| ProtocolError::TransferFailed("manifest not set".to_string()))
     }
 
+    /// Manifest paths with `..` or absolute components, before sanitizing
+    ///
+    /// Such paths are neutralized before anything is written, but a
+    /// well-behaved sender never produces them; receivers should refuse
+    /// the offer rather than guess where the file was meant to go.
+    pub fn unsafe_paths(&self) -> &[PathBuf] {
+        &self.unsafe_paths
+    }
+
+    /// Override where each manifest entry is written
+    ///
+    /// `names` follows manifest order. Paths are relative to the output
+    /// directory and still sanitized. Entries marked [`OutputName::Skip`]
+    /// are never touched on disk; the sender doesn't send them (they were
+    /// left out of the `FileSelection`), so they consume no chunks.
+    pub fn set_output_names(&mut self, names: Vec<OutputName>) -> Result<()> {
+        let expected = self
+            .manifest
+            .as_ref()
+            .ok_or_else(|| ProtocolError::TransferFailed("no manifest".to_string()))?
+            .files
+            .len();
+        if names.len() != expected {
+            return Err(ProtocolError::TransferFailed(format!(
+                "expected {} output names, got {}",
+                expected,
+                names.len()
+            )));
+        }
+        self.output_names = Some(names);
+        Ok(())
+    }
+
+    /// Receive only the files in `mask`, as sent to the peer in `FileSelection`
+    ///
+    /// Must follow [`process_offer`](Self::process_offer). The sender
+    /// numbers chunks over the accepted files alone, so the expected chunk
+    /// total, progress and completion are narrowed to match; the other
+    /// files are never written.
+    pub fn accept_files(&mut self, mask: AcceptanceMask) -> Result<()> {
+        let manifest = self
+            .manifest
+            .as_ref()
+            .ok_or_else(|| ProtocolError::TransferFailed("no manifest".to_string()))?;
+        let total = mask.total_chunks(manifest);
+
+        self.progress = Some(TransferProgress::new(mask.total_size(manifest)));
+        self.expected_total_chunks = Some(total);
+        self.chunk_hashes = vec![None; total as usize];
+        if let Some(ref mut resume) = self.resume {
+            resume.total_chunks = total;
+        }
+        self.accepted = Some(mask);
+        Ok(())
+    }
+
+    /// Create the output files and size them from the manifest
+    ///
+    /// Called automatically before the first chunk is stored, i.e. after the
+    /// offer was accepted. Preallocation is best effort and only applies to
+    /// files that do not exist yet; existing files are not touched until
+    /// their data is written. Files created here are removed again if the
+    /// transfer is dropped before `finalize`.
+    pub fn prepare_outputs(&mut self) -> Result<()> {
+        let Some(manifest) = self.manifest.as_ref() else {
+            return Ok(());
+        };
+        if self.outputs_prepared || !self.write_config.preallocate {
+            return Ok(());
+        }
+        self.outputs_prepared = true;
+
+        for (index, entry) in manifest.files.iter().enumerate() {
+            let Some(output_path) = self.output_path(index, entry)? else {
+                continue;
+            };
+            if self.volume_size(entry).is_some() {
+                continue;
+            }
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| ProtocolError::TransferFailed(format!("mkdir failed: {}", e)))?;
+            }
+
+            match disk::preallocate(&output_path, entry.size) {
+                Ok(true) => self.preallocated.push(output_path),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::debug!("preallocation failed for {}: {}", output_path.display(), e)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the sanitized output path for a manifest entry
+    ///
+    /// Returns `None` for entries the receiver chose to skip.
+    fn output_path(&self, index: usize, entry: &FileEntry) -> Result<Option<PathBuf>> {
+        if let Some(ref mask) = self.accepted {
+            if !mask.is_accepted(index as u32) {
+                return Ok(None);
+            }
+        }
+        let name = match self.output_names.as_ref().and_then(|n| n.get(index)) {
+            Some(OutputName::Skip) => return Ok(None),
+            Some(OutputName::Write(name)) => name,
+            None => &entry.path,
+        };
+        crate::transfer::sanitize::sanitize_filename(&name.to_string_lossy(), &self.output_dir)
+            .map(Some)
+            .map_err(|e| {
+                ProtocolError::TransferFailed(format!(
+                    "filename sanitization failed for {}: {}",
+                    entry.path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Process a FileChunk message — route it to its file, then as `process_chunk`
+    ///
+    /// Chunks of different files may arrive interleaved in any order.
+    pub fn process_file_chunk(
+        &mut self,
+        file_id: u32,
+        index: u64,
+        data: &[u8],
+    ) -> Result<Option<Message>> {
+        let global = self.file_chunk_index(file_id, index)?;
+        self.process_chunk(global, data, None)
+    }
+
+    /// Map a `FileChunk` (file id, index within file) to its transfer-wide index
+    pub fn file_chunk_index(&self, file_id: u32, index: u64) -> Result<u64> {
+        let manifest = self
+            .manifest
+            .as_ref()
+            .ok_or_else(|| ProtocolError::TransferFailed("no manifest".to_string()))?;
+        match self.accepted {
+            Some(ref mask) => mask.chunk_index(manifest, file_id, index),
+            None => manifest.global_chunk_index(file_id, index),
+        }
+        .ok_or_else(|| {
+            ProtocolError::TransferFailed(format!(
+                "chunk {} of file {} is not part of this transfer",
+                index, file_id
+            ))
+        })
+    }
+
     /// Process a Chunk message — decrypt, decompress, store
     pub fn process_chunk(
         &mut self,
@@ -203,29 +490,45 @@ impl ReceivePipeline {
             }
         }
 
-        // Record the hash of the encrypted chunk data for Merkle verification
-        if (index as usize) < self.chunk_hashes.len() {
-            let chunk_hash: [u8; 32] = blake3::hash(data).into();
-            self.chunk_hashes[index as usize] = Some(chunk_hash);
-        }
+        self.prepare_outputs()?;
+
+        // The cheap checksum localizes corruption before the AEAD check
+        let sealed = if self.chunk_checksum {
+            match chunking::verify_chunk_checksum(data, index) {
+                Ok(sealed) => sealed,
+                Err(e) => return self.request_resend(index, e),
+            }
+        } else {
+            data
+        };
 
         // Build AAD and nonce
         let aad = chunking::build_chunk_aad(&self.transfer_id, index);
         let nonce = chunking::build_chunk_nonce(index);
 
         // Decrypt
-        let decrypted = tallow_crypto::symmetric::aes_decrypt(
-            &self.session_key,
-            &nonce,
-            data,
-            &aad,
-        )
-        .map_err(|e| {
-            ProtocolError::TransferFailed(format!("chunk {} decryption failed: {}", index, e))
-        })?;
+        let decrypted =
+            match tallow_crypto::symmetric::aes_decrypt(&self.session_key, &nonce, sealed, &aad) {
+                Ok(decrypted) => decrypted,
+                Err(e) => {
+                    let failure = ProtocolError::TransferFailed(format!(
+                        "chunk {} decryption failed: {}",
+                        index, e
+                    ));
+                    return self.request_resend(index, failure);
+                }
+            };
+
+        // Record the hash of the encrypted chunk data for Merkle verification
+        if (index as usize) < self.chunk_hashes.len() {
+            let chunk_hash: [u8; 32] = blake3::hash(data).into();
+            self.chunk_hashes[index as usize] = Some(chunk_hash);
+        }
 
         // Per-chunk decompression (new streaming mode)
-        let chunk_data = if self.per_chunk_compression {
+        let chunk_data = if self.adaptive_compression {
+            compression::pipeline::decompress_chunk(&decrypted, self.compression)?
+        } else if self.per_chunk_compression {
             compression::pipeline::decompress(&decrypted, self.compression)?
         } else {
             decrypted
@@ -237,9 +540,20 @@ impl ReceivePipeline {
         if self.streaming_mode {
             if let Some(ref temp_dir) = self.temp_dir {
                 let chunk_path = temp_dir.join(format!("{}.chunk", index));
-                std::fs::write(&chunk_path, &chunk_data).map_err(|e| {
-                    ProtocolError::TransferFailed(format!("write temp chunk {}: {}", index, e))
-                })?;
+                if let Err(e) = self.write_temp_chunk(&chunk_path, &chunk_data) {
+                    // Never leave a truncated chunk behind
+                    let _ = std::fs::remove_file(&chunk_path);
+                    if let Some(hash) = self.chunk_hashes.get_mut(index as usize) {
+                        *hash = None;
+                    }
+                    if disk::is_disk_full(&e) {
+                        return Err(self.disk_full(format!("writing chunk {}", index)));
+                    }
+                    return Err(ProtocolError::TransferFailed(format!(
+                        "write temp chunk {}: {}",
+                        index, e
+                    )));
+                }
             }
         } else {
             self.received_chunks.insert(index, chunk_data);
@@ -267,6 +581,92 @@ impl ReceivePipeline {
         }))
     }
 
+    /// A `CumulativeAck` to send, if one is due
+    ///
+    /// Call after each processed chunk. One is due once `interval` more
+    /// chunks are held contiguously from chunk 0 than last reported, and
+    /// always when the last chunk arrives. Returns `None` unless enabled with
+    /// [`with_cumulative_ack`](Self::with_cumulative_ack).
+    pub fn cumulative_ack(&mut self) -> Option<Message> {
+        let interval = self.cumulative_ack_interval?;
+        while self
+            .expected_total_chunks
+            .is_none_or(|total| self.contiguous < total)
+            && self.is_stored(self.contiguous)
+        {
+            self.contiguous += 1;
+        }
+        let complete = self.expected_total_chunks == Some(self.contiguous);
+        let advanced = self.contiguous - self.acked_through;
+        if advanced == 0 || (advanced < interval && !complete) {
+            return None;
+        }
+        self.acked_through = self.contiguous;
+        Some(Message::CumulativeAck {
+            transfer_id: self.transfer_id,
+            received: self.contiguous,
+        })
+    }
+
+    /// Whether chunk `index` has been received and verified
+    fn is_stored(&self, index: u64) -> bool {
+        matches!(self.chunk_hashes.get(index as usize), Some(Some(_)))
+            || self
+                .resume
+                .as_ref()
+                .is_some_and(|resume| resume.is_verified(index))
+    }
+
+    /// Ask for a chunk that failed its checks again, if retries remain
+    ///
+    /// Fails with `failure` once the chunk's retries are used up.
+    fn request_resend(&mut self, index: u64, failure: ProtocolError) -> Result<Option<Message>> {
+        let retries = self.chunk_retries.entry(index).or_insert(0);
+        if *retries >= self.max_chunk_retries {
+            return Err(failure);
+        }
+        *retries += 1;
+        tracing::warn!(
+            "{}, requesting resend ({}/{})",
+            failure,
+            retries,
+            self.max_chunk_retries
+        );
+        Ok(Some(Message::ResendChunks {
+            transfer_id: self.transfer_id,
+            indices: vec![index],
+        }))
+    }
+
+    /// Write one decompressed chunk to its temp file
+    fn write_temp_chunk(&self, path: &Path, data: &[u8]) -> std::io::Result<()> {
+        let mut file = (self.open_chunk_writer)(path)?;
+        file.write_all(data)?;
+        file.flush()
+    }
+
+    /// Handle a full disk: keep received chunks and save the resume state
+    ///
+    /// The resume state only covers chunks fully on disk, so the transfer
+    /// can continue from where it stopped once space is freed.
+    fn disk_full(&mut self, context: String) -> ProtocolError {
+        self.keep_temp = true;
+
+        if let (Some(path), Some(resume)) = (&self.checkpoint_path, &self.resume) {
+            let saved = resume.checkpoint().and_then(|data| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, data).map_err(ProtocolError::from)
+            });
+            if let Err(e) = saved {
+                tracing::warn!("could not save resume state after disk full: {}", e);
+            }
+        }
+
+        ProtocolError::DiskFull(context)
+    }
+
     /// Check if all chunks have been received
     pub fn is_complete(&self) -> bool {
         self.resume
@@ -300,13 +700,16 @@ impl ReceivePipeline {
             .as_ref()
             .ok_or_else(|| ProtocolError::TransferFailed("no manifest".to_string()))?;
 
-        if self.streaming_mode {
+        let written = if self.streaming_mode {
             self.finalize_streaming().await
         } else if self.per_chunk_compression {
             self.finalize_per_chunk().await
         } else {
             self.finalize_whole_file().await
-        }
+        }?;
+
+        self.finalized = true;
+        Ok(written)
     }
 
     /// Finalize with streaming mode — read chunks from temp files
@@ -317,18 +720,10 @@ impl ReceivePipeline {
         let mut written_paths = Vec::new();
         let mut chunk_index: u64 = 0;
 
-        for entry in &manifest.files {
-            let output_path = crate::transfer::sanitize::sanitize_filename(
-                &entry.path.to_string_lossy(),
-                &self.output_dir,
-            )
-            .map_err(|e| {
-                ProtocolError::TransferFailed(format!(
-                    "filename sanitization failed for {}: {}",
-                    entry.path.display(),
-                    e
-                ))
-            })?;
+        for (index, entry) in manifest.files.iter().enumerate() {
+            let Some(output_path) = self.output_path(index, entry)? else {
+                continue;
+            };
 
             if let Some(parent) = output_path.parent() {
                 tokio::fs::create_dir_all(parent)
@@ -336,12 +731,12 @@ impl ReceivePipeline {
                     .map_err(|e| ProtocolError::TransferFailed(format!("mkdir failed: {}", e)))?;
             }
 
-            // Create output file and write chunks sequentially
-            let file = tokio::fs::File::create(&output_path).await.map_err(|e| {
+            // Open (preallocated) output file and write chunks sequentially
+            let holes = manifest.holes(index);
+            let mut writer = FileSink::create(&output_path, entry, holes, self).map_err(|e| {
                 ProtocolError::TransferFailed(format!("create {}: {}", output_path.display(), e))
             })?;
-            let mut writer = tokio::io::BufWriter::new(file);
-            let mut hasher = blake3::Hasher::new();
+            let mut hasher = ContentHasher::new(holes, manifest.hash_algorithm);
 
             for _ in 0..entry.chunk_count {
                 let chunk_path = temp_dir.join(format!("{}.chunk", chunk_index));
@@ -350,21 +745,24 @@ impl ReceivePipeline {
                 })?;
 
                 hasher.update(&chunk_data);
-                writer.write_all(&chunk_data).await.map_err(|e| {
-                    ProtocolError::TransferFailed(format!("write {}: {}", output_path.display(), e))
-                })?;
+                if let Err(e) = writer.write_all(&chunk_data) {
+                    drop(writer);
+                    return Err(output_write_failed(&output_path, e));
+                }
 
                 // Remove temp chunk after writing
                 let _ = tokio::fs::remove_file(&chunk_path).await;
                 chunk_index += 1;
             }
 
-            writer.flush().await.map_err(|e| {
-                ProtocolError::TransferFailed(format!("flush {}: {}", output_path.display(), e))
-            })?;
+            let written_path =
+                match writer.finish(&output_path, &entry.hash, manifest.hash_algorithm) {
+                    Ok(path) => path,
+                    Err(e) => return Err(output_write_failed(&output_path, e)),
+                };
 
-            // Verify BLAKE3 hash
-            let actual_hash: [u8; 32] = hasher.finalize().into();
+            // Verify against the manifest hash
+            let actual_hash = hasher.finalize();
             if !tallow_crypto::mem::constant_time::ct_eq(&actual_hash, &entry.hash) {
                 return Err(ProtocolError::TransferFailed(format!(
                     "hash mismatch for {}",
@@ -372,7 +770,7 @@ impl ReceivePipeline {
                 )));
             }
 
-            written_paths.push(output_path);
+            written_paths.push(written_path);
         }
 
         // Clean up temp directory
@@ -391,18 +789,10 @@ impl ReceivePipeline {
         let mut written_paths = Vec::new();
         let mut chunk_index: u64 = 0;
 
-        for entry in &manifest.files {
-            let output_path = crate::transfer::sanitize::sanitize_filename(
-                &entry.path.to_string_lossy(),
-                &self.output_dir,
-            )
-            .map_err(|e| {
-                ProtocolError::TransferFailed(format!(
-                    "filename sanitization failed for {}: {}",
-                    entry.path.display(),
-                    e
-                ))
-            })?;
+        for (index, entry) in manifest.files.iter().enumerate() {
+            let Some(output_path) = self.output_path(index, entry)? else {
+                continue;
+            };
 
             if let Some(parent) = output_path.parent() {
                 tokio::fs::create_dir_all(parent)
@@ -422,8 +812,11 @@ impl ReceivePipeline {
                 chunk_index += 1;
             }
 
-            // Verify BLAKE3 hash
-            let actual_hash: [u8; 32] = blake3::hash(&file_data).into();
+            // Verify with the algorithm the manifest names
+            let holes = manifest.holes(index);
+            let mut hasher = ContentHasher::new(holes, manifest.hash_algorithm);
+            hasher.update(&file_data);
+            let actual_hash = hasher.finalize();
             if !tallow_crypto::mem::constant_time::ct_eq(&actual_hash, &entry.hash) {
                 return Err(ProtocolError::TransferFailed(format!(
                     "hash mismatch for {}",
@@ -431,17 +824,10 @@ impl ReceivePipeline {
                 )));
             }
 
-            tokio::fs::write(&output_path, &file_data)
-                .await
-                .map_err(|e| {
-                    ProtocolError::TransferFailed(format!(
-                        "write {} failed: {}",
-                        output_path.display(),
-                        e
-                    ))
-                })?;
-
-            written_paths.push(output_path);
+            written_paths.push(
+                self.write_file(&output_path, entry, holes, &file_data)
+                    .await?,
+            );
         }
 
         Ok(written_paths)
@@ -470,9 +856,12 @@ impl ReceivePipeline {
         let mut written_paths = Vec::new();
         let mut offset = 0usize;
 
-        for entry in &manifest.files {
+        for (index, entry) in manifest.files.iter().enumerate() {
+            let Some(output_path) = self.output_path(index, entry)? else {
+                continue;
+            };
             let end = offset
-                .checked_add(entry.size as usize)
+                .checked_add(manifest.data_len(index) as usize)
                 .ok_or_else(|| ProtocolError::TransferFailed("file offset overflow".to_string()))?;
             if end > decompressed.len() {
                 return Err(ProtocolError::TransferFailed(format!(
@@ -483,8 +872,11 @@ impl ReceivePipeline {
 
             let file_data = &decompressed[offset..end];
 
-            // Verify BLAKE3 hash using constant-time comparison
-            let actual_hash: [u8; 32] = blake3::hash(file_data).into();
+            // Verify the hash using constant-time comparison
+            let holes = manifest.holes(index);
+            let mut hasher = ContentHasher::new(holes, manifest.hash_algorithm);
+            hasher.update(file_data);
+            let actual_hash = hasher.finalize();
             if !tallow_crypto::mem::constant_time::ct_eq(&actual_hash, &entry.hash) {
                 return Err(ProtocolError::TransferFailed(format!(
                     "hash mismatch for {}",
@@ -493,40 +885,61 @@ impl ReceivePipeline {
             }
 
             // Write to output directory (sanitized path prevents traversal attacks)
-            let output_path = crate::transfer::sanitize::sanitize_filename(
-                &entry.path.to_string_lossy(),
-                &self.output_dir,
-            )
-            .map_err(|e| {
-                ProtocolError::TransferFailed(format!(
-                    "filename sanitization failed for {}: {}",
-                    entry.path.display(),
-                    e
-                ))
-            })?;
             if let Some(parent) = output_path.parent() {
                 tokio::fs::create_dir_all(parent)
                     .await
                     .map_err(|e| ProtocolError::TransferFailed(format!("mkdir failed: {}", e)))?;
             }
 
-            tokio::fs::write(&output_path, file_data)
-                .await
-                .map_err(|e| {
-                    ProtocolError::TransferFailed(format!(
-                        "write {} failed: {}",
-                        output_path.display(),
-                        e
-                    ))
-                })?;
-
-            written_paths.push(output_path);
+            written_paths.push(
+                self.write_file(&output_path, entry, holes, file_data)
+                    .await?,
+            );
             offset = end;
         }
 
         Ok(written_paths)
     }
 
+    /// Volume size to split `entry` into, if it exceeds the split size
+    fn volume_size(&self, entry: &FileEntry) -> Option<u64> {
+        self.split_size.filter(|&size| entry.size > size)
+    }
+
+    /// Write an assembled file, as volumes if it exceeds the split size
+    ///
+    /// `data` is the file's bytes outside `holes`. Returns the path to
+    /// report: the file, or its volume manifest.
+    async fn write_file(
+        &self,
+        output_path: &Path,
+        entry: &FileEntry,
+        holes: &[Hole],
+        data: &[u8],
+    ) -> Result<PathBuf> {
+        let write_failed = |e: std::io::Error| {
+            ProtocolError::TransferFailed(format!("write {} failed: {}", output_path.display(), e))
+        };
+        if self.volume_size(entry).is_some() || !holes.is_empty() {
+            let mut sink =
+                FileSink::create(output_path, entry, holes, self).map_err(write_failed)?;
+            sink.write_all(data).map_err(write_failed)?;
+            let algorithm = self
+                .manifest
+                .as_ref()
+                .map(|m| m.hash_algorithm)
+                .unwrap_or_default();
+            return sink
+                .finish(output_path, &entry.hash, algorithm)
+                .map_err(write_failed);
+        }
+
+        tokio::fs::write(output_path, data)
+            .await
+            .map_err(write_failed)?;
+        Ok(output_path.to_path_buf())
+    }
+
     /// Get the manifest
     pub fn manifest(&self) -> Option<&FileManifest> {
         self.manifest.as_ref()
@@ -567,6 +980,73 @@ impl ReceivePipeline {
     }
 }
 
+/// Where a streamed file is written
+enum FileSink {
+    Whole(OutputFile),
+    /// A file recreated with holes
+    Sparse(SparseFile),
+    /// Numbered volumes, which hold holes as zeros
+    Volumes(ZeroFill<VolumeWriter>),
+}
+
+impl FileSink {
+    /// Open the output for `entry`, whose data stream skips `holes`
+    fn create(
+        path: &Path,
+        entry: &FileEntry,
+        holes: &[Hole],
+        pipeline: &ReceivePipeline,
+    ) -> std::io::Result<Self> {
+        match pipeline.volume_size(entry) {
+            Some(volume_size) => VolumeWriter::create(path, volume_size)
+                .map(|volumes| Self::Volumes(ZeroFill::new(volumes, holes))),
+            None if !holes.is_empty() => {
+                SparseFile::create(path, entry.size, holes).map(Self::Sparse)
+            }
+            None => OutputFile::open(path, entry.size, &pipeline.write_config).map(Self::Whole),
+        }
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Whole(file) => file.write_all(data),
+            Self::Sparse(file) => file.write_all(data),
+            Self::Volumes(volumes) => volumes.write_all(data),
+        }
+    }
+
+    /// Finish writing; returns the file's path or its volume manifest's
+    ///
+    /// Volumes record `hash` and its `algorithm` for [`volumes::join`].
+    fn finish(
+        self,
+        path: &Path,
+        hash: &[u8; 32],
+        algorithm: HashAlgorithm,
+    ) -> std::io::Result<PathBuf> {
+        match self {
+            Self::Whole(file) => file.finish().map(|()| path.to_path_buf()),
+            Self::Sparse(file) => file.finish().map(|()| path.to_path_buf()),
+            Self::Volumes(volumes) => volumes.finish()?.finish(hash, algorithm),
+        }
+    }
+}
+
+/// Default [`ReceivePipeline`] chunk writer: a plain file
+fn create_chunk_file(path: &Path) -> std::io::Result<Box<dyn Write>> {
+    Ok(Box::new(std::fs::File::create(path)?))
+}
+
+/// Map a failed write to a final output file, removing the partial file
+fn output_write_failed(path: &Path, e: std::io::Error) -> ProtocolError {
+    let _ = std::fs::remove_file(path);
+    if disk::is_disk_full(&e) {
+        ProtocolError::DiskFull(format!("writing {}", path.display()))
+    } else {
+        ProtocolError::TransferFailed(format!("write {}: {}", path.display(), e))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -713,6 +1193,82 @@ mod tests {
         assert_eq!(received, file_data);
     }
 
+    #[tokio::test]
+    async fn test_e2e_per_chunk_algorithm_roundtrip() {
+        // Chunks alternate between compressible text and random bytes
+        let size = chunking::DEFAULT_CHUNK_SIZE;
+        let mut file_data = Vec::new();
+        for i in 0..4 {
+            if i % 2 == 0 {
+                let text = format!("mixed chunk {} ", i).repeat(size);
+                file_data.extend_from_slice(&text.as_bytes()[..size]);
+            } else {
+                file_data.extend((0..size).map(|_| rand::random::<u8>()));
+            }
+        }
+        let src_dir = tempfile::tempdir().unwrap();
+        let file_path = src_dir.path().join("mixed.dat");
+        tokio::fs::write(&file_path, &file_data).await.unwrap();
+
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key());
+        sender.prepare(&[file_path.clone()]).await.unwrap();
+        sender.restrict_compression(crate::wire::FeatureSet::local());
+        let manifest_bytes = match sender.file_offer().unwrap() {
+            Message::FileOffer { manifest, .. } => manifest,
+            _ => panic!("Expected FileOffer"),
+        };
+
+        let total_chunks = sender.manifest().total_chunks;
+        assert_eq!(total_chunks, 4);
+        let mut chunks = Vec::new();
+        let mut reader = sender.open_file_reader(&file_path).await.unwrap();
+        let mut index = 0;
+        while let Some(raw) = reader.next_chunk().await.unwrap() {
+            let msg = sender
+                .encrypt_chunk(&raw, index, total_chunks, index + 1 == total_chunks)
+                .unwrap();
+            chunks.push(msg);
+            index += 1;
+        }
+
+        // Each chunk's frame records its own algorithm
+        for msg in &chunks {
+            let Message::Chunk { index, data, .. } = msg else {
+                panic!("Expected Chunk");
+            };
+            let framed = tallow_crypto::symmetric::aes_decrypt(
+                &test_key(),
+                &chunking::build_chunk_nonce(*index),
+                data,
+                &chunking::build_chunk_aad(&test_transfer_id(), *index),
+            )
+            .unwrap();
+            let expected = if index % 2 == 0 {
+                CompressionAlgorithm::Zstd
+            } else {
+                CompressionAlgorithm::None
+            };
+            assert_eq!(
+                compression::pipeline::chunk_frame_algorithm(&framed),
+                Some(expected)
+            );
+        }
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+        for msg in &chunks {
+            if let Message::Chunk {
+                index, data, total, ..
+            } = msg
+            {
+                receiver.process_chunk(*index, data, *total).unwrap();
+            }
+        }
+        let paths = receiver.finalize().await.unwrap();
+        assert_eq!(tokio::fs::read(&paths[0]).await.unwrap(), file_data);
+    }
+
     // ── E2E: multi-file directory transfer ────────────────────────
 
     #[tokio::test]
@@ -749,9 +1305,9 @@ mod tests {
         let mut chunk_hashes: Vec<[u8; 32]> = Vec::new();
         let mut global_idx: u64 = 0;
 
-        for (name, _) in &files {
-            let fpath = src_dir.path().join(name);
-            let mut reader = sender.open_file_reader(&fpath).await.unwrap();
+        // Chunks follow manifest order, which is sorted by path
+        for fpath in sender.source_paths() {
+            let mut reader = sender.open_file_reader(fpath).await.unwrap();
             while let Some(raw) = reader.next_chunk().await.unwrap() {
                 let is_last = global_idx + 1 == total_chunks;
                 let msg = sender
@@ -1046,6 +1602,291 @@ mod tests {
         }
     }
 
+    // ── Disk write strategy: preallocation ───────────────────────
+
+    /// Helper: encrypt `file_data` as a single-file transfer.
+    ///
+    /// Returns the manifest bytes and the chunk messages.
+    async fn send_file(name: &str, file_data: &[u8]) -> (Vec<u8>, Vec<Message>) {
+        let src_dir = tempfile::tempdir().unwrap();
+        let file_path = src_dir.path().join(name);
+        tokio::fs::write(&file_path, file_data).await.unwrap();
+
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key());
+        let offer_msgs = sender.prepare(&[file_path.clone()]).await.unwrap();
+        let manifest_bytes = match &offer_msgs[0] {
+            Message::FileOffer { manifest, .. } => manifest.clone(),
+            _ => panic!("Expected FileOffer"),
+        };
+
+        let total_chunks = sender.manifest().total_chunks;
+        let mut chunks = Vec::new();
+        let mut idx: u64 = 0;
+        let mut reader = sender.open_file_reader(&file_path).await.unwrap();
+        while let Some(raw) = reader.next_chunk().await.unwrap() {
+            let is_last = idx + 1 == total_chunks;
+            chunks.push(
+                sender
+                    .encrypt_chunk(&raw, idx, total_chunks, is_last)
+                    .unwrap(),
+            );
+            idx += 1;
+        }
+        (manifest_bytes, chunks)
+    }
+
+    /// Helper: rewrite the manifest so the file claims `claimed_size` bytes.
+    fn with_claimed_size(manifest_bytes: &[u8], claimed_size: u64) -> Vec<u8> {
+        let mut manifest = FileManifest::from_bytes(manifest_bytes).unwrap();
+        manifest.total_size = claimed_size;
+        manifest.files[0].size = claimed_size;
+        manifest.to_bytes().unwrap()
+    }
+
+    fn feed_chunks(receiver: &mut ReceivePipeline, chunks: &[Message]) {
+        for msg in chunks {
+            if let Message::Chunk {
+                index, data, total, ..
+            } = msg
+            {
+                receiver.process_chunk(*index, data, *total).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_output_preallocated_before_chunks_written() {
+        let file_data: Vec<u8> = (0..3 * 1024 * 1024u32).map(|i| (i % 253) as u8).collect();
+        let (manifest_bytes, chunks) = send_file("prealloc.bin", &file_data).await;
+        assert!(chunks.len() > 1);
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let output = dst_dir.path().join("prealloc.bin");
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+
+        // Nothing is created before the offer is accepted
+        assert!(!output.exists());
+
+        // First chunk arrives: the file already has its full size
+        feed_chunks(&mut receiver, &chunks[..1]);
+        let len = std::fs::metadata(&output).unwrap().len();
+        assert_eq!(len, file_data.len() as u64);
+
+        feed_chunks(&mut receiver, &chunks[1..]);
+        let paths = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), file_data);
+    }
+
+    #[tokio::test]
+    async fn test_existing_file_not_resized_before_data() {
+        let file_data: Vec<u8> = (0..3 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let (manifest_bytes, _chunks) = send_file("existing.bin", &file_data).await;
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let output = dst_dir.path().join("existing.bin");
+        std::fs::write(&output, b"keep me").unwrap();
+        {
+            let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+            receiver.process_offer(&manifest_bytes).unwrap();
+            receiver.prepare_outputs().unwrap();
+            assert_eq!(std::fs::read(&output).unwrap(), b"keep me");
+        }
+        // Aborted: still the user's file, not truncated or padded
+        assert_eq!(std::fs::read(&output).unwrap(), b"keep me");
+    }
+
+    #[tokio::test]
+    async fn test_preallocation_disabled() {
+        let (manifest_bytes, chunks) = send_file("plain.bin", b"no preallocation").await;
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key())
+            .with_write_config(WriteConfig {
+                preallocate: false,
+                direct_io: false,
+            });
+        receiver.process_offer(&manifest_bytes).unwrap();
+        feed_chunks(&mut receiver, &chunks);
+        assert!(!dst_dir.path().join("plain.bin").exists());
+
+        let paths = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), b"no preallocation");
+    }
+
+    #[tokio::test]
+    async fn test_output_names_rename_and_skip() {
+        let (manifest_bytes, chunks) = send_file("notes.txt", b"fresh contents").await;
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let existing = dst_dir.path().join("notes.txt");
+        std::fs::write(&existing, b"keep me").unwrap();
+
+        // Renamed: written next to the existing file
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+        receiver
+            .set_output_names(vec![OutputName::Write(PathBuf::from("notes-1.txt"))])
+            .unwrap();
+        feed_chunks(&mut receiver, &chunks);
+        let paths = receiver.finalize().await.unwrap();
+        assert_eq!(paths, vec![dst_dir.path().join("notes-1.txt")]);
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), b"fresh contents");
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep me");
+
+        // Skipped: nothing is preallocated or written
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+        receiver.set_output_names(vec![OutputName::Skip]).unwrap();
+        receiver.prepare_outputs().unwrap();
+        assert!(receiver.finalize().await.unwrap().is_empty());
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep me");
+
+        // Name count must match the manifest
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+        assert!(receiver.set_output_names(Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preserve_layout_recreates_subdirectories() {
+        use crate::transfer::naming::DirectoryLayout;
+
+        let src_dir = tempfile::tempdir().unwrap();
+        let root = src_dir.path().join("project");
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::write(root.join("src/nested/deep.rs"), b"deep").unwrap();
+        std::fs::write(root.join("top.txt"), b"top").unwrap();
+
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key());
+        let offer_msgs = sender.prepare(&[root.clone()]).await.unwrap();
+        let manifest_bytes = match &offer_msgs[0] {
+            Message::FileOffer { manifest, .. } => manifest.clone(),
+            _ => panic!("Expected FileOffer"),
+        };
+        let total_chunks = sender.manifest().total_chunks;
+        let mut chunks = Vec::new();
+        for path in sender.source_paths().to_vec() {
+            let mut reader = sender.open_file_reader(&path).await.unwrap();
+            while let Some(raw) = reader.next_chunk().await.unwrap() {
+                let idx = chunks.len() as u64;
+                let is_last = idx + 1 == total_chunks;
+                chunks.push(
+                    sender
+                        .encrypt_chunk(&raw, idx, total_chunks, is_last)
+                        .unwrap(),
+                );
+            }
+        }
+
+        for layout in [DirectoryLayout::Preserve, DirectoryLayout::Flatten] {
+            let dst_dir = tempfile::tempdir().unwrap();
+            let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+            let manifest = receiver.process_offer(&manifest_bytes).unwrap();
+            let names = manifest
+                .files
+                .iter()
+                .map(|f| OutputName::Write(layout.apply(&f.path)))
+                .collect();
+            assert!(receiver.unsafe_paths().is_empty());
+            receiver.set_output_names(names).unwrap();
+            feed_chunks(&mut receiver, &chunks);
+            receiver.finalize().await.unwrap();
+
+            let (deep, top) = match layout {
+                DirectoryLayout::Preserve => (
+                    dst_dir.path().join("src/nested/deep.rs"),
+                    dst_dir.path().join("top.txt"),
+                ),
+                DirectoryLayout::Flatten => {
+                    assert!(!dst_dir.path().join("src").exists());
+                    (
+                        dst_dir.path().join("deep.rs"),
+                        dst_dir.path().join("top.txt"),
+                    )
+                }
+            };
+            assert_eq!(std::fs::read(deep).unwrap(), b"deep");
+            assert_eq!(std::fs::read(top).unwrap(), b"top");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_traversal_paths_reported_before_writing() {
+        let (manifest_bytes, _) = send_file("innocent.txt", b"payload").await;
+        let mut manifest = FileManifest::from_bytes(&manifest_bytes).unwrap();
+        manifest.files[0].path = PathBuf::from("../../outside.txt");
+        let malicious = manifest.to_bytes().unwrap();
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&malicious).unwrap();
+        assert_eq!(
+            receiver.unsafe_paths(),
+            &[PathBuf::from("../../outside.txt")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aborted_transfer_removes_preallocated_file() {
+        let file_data = vec![7u8; 600 * 1024];
+        let (manifest_bytes, chunks) = send_file("aborted.bin", &file_data).await;
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let output = dst_dir.path().join("aborted.bin");
+        {
+            let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+            receiver.process_offer(&manifest_bytes).unwrap();
+            feed_chunks(&mut receiver, &chunks[..1]);
+            assert!(output.exists());
+        }
+        assert!(!output.exists());
+    }
+
+    #[tokio::test]
+    async fn test_overstated_manifest_size_truncated() {
+        // Streaming path: the file is preallocated past the real data
+        let file_data: Vec<u8> = (0..11 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let (manifest_bytes, chunks) = send_file("long.bin", &file_data).await;
+        let manifest_bytes = with_claimed_size(&manifest_bytes, file_data.len() as u64 + 4096);
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key())
+            .with_write_config(WriteConfig {
+                preallocate: true,
+                direct_io: true,
+            });
+        receiver.process_offer(&manifest_bytes).unwrap();
+        feed_chunks(&mut receiver, &chunks);
+
+        let paths = receiver.finalize().await.unwrap();
+        let received = std::fs::read(&paths[0]).unwrap();
+        assert_eq!(received.len(), file_data.len());
+        assert_eq!(received, file_data);
+    }
+
+    #[tokio::test]
+    async fn test_understated_manifest_size_extended() {
+        let file_data: Vec<u8> = (0..700 * 1024u32).map(|i| (i % 249) as u8).collect();
+        let (manifest_bytes, chunks) = send_file("short.bin", &file_data).await;
+        let manifest_bytes = with_claimed_size(&manifest_bytes, 1000);
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key());
+        receiver.process_offer(&manifest_bytes).unwrap();
+        feed_chunks(&mut receiver, &chunks[..1]);
+        assert_eq!(
+            std::fs::metadata(dst_dir.path().join("short.bin"))
+                .unwrap()
+                .len(),
+            1000
+        );
+
+        feed_chunks(&mut receiver, &chunks[1..]);
+        let paths = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), file_data);
+    }
+
     // ── Wave 5: Stress tests for massive files ────────────────────
     //
     // These tests are #[ignore]'d by default because they create large
@@ -1145,4 +1986,500 @@ mod tests {
     async fn stress_1tb() {
         stress_transfer(1024 * 1024 * 1024 * 1024).await;
     }
+
+    // ── Disk full ─────────────────────────────────────────────────
+
+    /// Writer that accepts `remaining` bytes in total, then reports a full disk
+    struct LimitedWriter {
+        file: std::fs::File,
+        remaining: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            use std::sync::atomic::Ordering;
+            let remaining = self.remaining.load(Ordering::SeqCst);
+            if remaining == 0 {
+                return Err(std::io::ErrorKind::StorageFull.into());
+            }
+            let n = self.file.write(&buf[..buf.len().min(remaining)])?;
+            self.remaining.fetch_sub(n, Ordering::SeqCst);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    /// Make every temp chunk write share a budget of `capacity` bytes
+    fn limit_disk_capacity(receiver: &mut ReceivePipeline, capacity: usize) {
+        let remaining = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(capacity));
+        receiver.open_chunk_writer =
+            Box::new(move |path: &Path| -> std::io::Result<Box<dyn Write>> {
+                Ok(Box::new(LimitedWriter {
+                    file: std::fs::File::create(path)?,
+                    remaining: std::sync::Arc::clone(&remaining),
+                }))
+            });
+    }
+
+    #[tokio::test]
+    async fn test_disk_full_during_receive() {
+        let chunk = chunking::DEFAULT_CHUNK_SIZE;
+        let file_data: Vec<u8> = (0..4 * chunk).map(|i| (i % 241) as u8).collect();
+        let (manifest_bytes, chunks) = send_file("full.bin", &file_data).await;
+        assert_eq!(chunks.len(), 4);
+        // Claim a large size so the receiver streams chunks to temp files
+        let manifest_bytes = with_claimed_size(&manifest_bytes, STREAMING_THRESHOLD + 1);
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let checkpoint = dst_dir.path().join("state/transfer.checkpoint");
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst_dir.path(), test_key())
+            .with_checkpoint_path(&checkpoint);
+        // Room for two whole chunks and part of the third
+        limit_disk_capacity(&mut receiver, 2 * chunk + 1000);
+        receiver.process_offer(&manifest_bytes).unwrap();
+
+        let mut outcome = Vec::new();
+        for msg in &chunks {
+            if let Message::Chunk {
+                index, data, total, ..
+            } = msg
+            {
+                outcome.push(receiver.process_chunk(*index, data, *total));
+            }
+        }
+        assert!(outcome[0].is_ok() && outcome[1].is_ok());
+        assert!(matches!(outcome[2], Err(ProtocolError::DiskFull(_))));
+
+        // Resume state covers exactly the chunks fully on disk
+        let resume = receiver.resume_state().unwrap();
+        assert!(resume.is_verified(0) && resume.is_verified(1));
+        assert!(!resume.is_verified(2));
+        assert_eq!(resume.next_needed_chunk(), Some(2));
+        assert_eq!(resume.bytes_transferred, 2 * chunk as u64);
+
+        // ...and was saved for the next attempt
+        let saved = ResumeState::restore(&std::fs::read(&checkpoint).unwrap()).unwrap();
+        assert_eq!(saved.verified_chunks, resume.verified_chunks);
+
+        // No truncated chunk was left behind; complete chunks survive drop
+        let temp_dir = dst_dir.path().join(".tallow_temp");
+        assert!(!temp_dir.join("2.chunk").exists());
+        drop(receiver);
+        assert_eq!(
+            std::fs::read(temp_dir.join("1.chunk")).unwrap().len(),
+            chunk
+        );
+    }
+
+    #[test]
+    fn test_output_write_failed_removes_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("partial.bin");
+        std::fs::write(&path, b"half").unwrap();
+
+        let err = output_write_failed(&path, std::io::ErrorKind::StorageFull.into());
+        assert!(matches!(err, ProtocolError::DiskFull(_)));
+        assert!(!path.exists());
+
+        std::fs::write(&path, b"half").unwrap();
+        let err = output_write_failed(&path, std::io::ErrorKind::PermissionDenied.into());
+        assert!(matches!(err, ProtocolError::TransferFailed(_)));
+    }
+
+    /// Sender with a ten-chunk file, its chunks and the file's length
+    async fn ten_chunk_sender(dir: &Path, checksum: bool) -> (SendPipeline, Vec<Message>, usize) {
+        let size = chunking::MIN_CHUNK_SIZE;
+        let data: Vec<u8> = (0..10 * size - 100).map(|i| (i % 239) as u8).collect();
+        let path = dir.join("ten.bin");
+        std::fs::write(&path, &data).unwrap();
+
+        let mut config = chunking::ChunkConfig::new();
+        config.size = size;
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key())
+            .with_chunk_config(config)
+            .with_chunk_checksum(checksum);
+        sender.prepare(&[path.clone()]).await.unwrap();
+        let chunks = sender.chunk_file(&path, 0).await.unwrap();
+        assert_eq!(chunks.len(), 10);
+        (sender, chunks, data.len())
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_progress_lags_sent_by_window() {
+        use tokio::sync::mpsc;
+        const WINDOW: u64 = 3;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let (mut sender, chunks, len) = ten_chunk_sender(src.path(), false).await;
+        let chunk = chunking::MIN_CHUNK_SIZE as u64;
+        let mut receiver =
+            ReceivePipeline::new(test_transfer_id(), dst.path(), test_key()).with_cumulative_ack(1);
+        receiver
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+
+        // Mock channel: chunks one way, acks the other
+        let (to_receiver, mut from_sender) = mpsc::unbounded_channel::<Message>();
+        let (to_sender, mut from_receiver) = mpsc::unbounded_channel::<Message>();
+        let recv = async move {
+            while let Some(Message::Chunk {
+                index, data, total, ..
+            }) = from_sender.recv().await
+            {
+                let ack = receiver.process_chunk(index, &data, total).unwrap();
+                to_sender.send(ack.unwrap()).unwrap();
+                if let Some(cumulative) = receiver.cumulative_ack() {
+                    to_sender.send(cumulative).unwrap();
+                }
+            }
+            receiver
+        };
+
+        let send = async {
+            let total = chunks.len() as u64;
+            let mut sent = 0u64;
+            let mut confirmed = 0u64;
+            let mut max_in_flight = 0u64;
+            while confirmed < total {
+                if sent < total && sent - confirmed < WINDOW {
+                    to_receiver.send(chunks[sent as usize].clone()).unwrap();
+                    sent += 1;
+                    sender.update_progress((sent * chunk).min(len as u64));
+                } else {
+                    match from_receiver.recv().await.unwrap() {
+                        Message::CumulativeAck { received, .. } => {
+                            confirmed = received;
+                            sender.process_cumulative_ack(received).unwrap();
+                        }
+                        Message::Ack { .. } => continue,
+                        other => panic!("unexpected {:?}", other),
+                    }
+                }
+                let progress = sender.progress().unwrap();
+                assert!(progress.confirmed_bytes <= progress.bytes_transferred);
+                assert!(progress.in_flight() <= WINDOW * chunk);
+                max_in_flight = max_in_flight.max(progress.in_flight());
+            }
+            drop(to_receiver);
+            max_in_flight
+        };
+
+        let (max_in_flight, mut receiver) = tokio::join!(send, recv);
+        // Confirmation trailed by a full window while chunks were in flight...
+        assert_eq!(max_in_flight, WINDOW * chunk);
+        // ...and caught up exactly at completion
+        let progress = sender.progress().unwrap();
+        assert_eq!(progress.confirmed_bytes, len as u64);
+        assert_eq!(progress.bytes_transferred, len as u64);
+        assert_eq!(progress.confirmed_percentage(), 100.0);
+        assert!(receiver.is_complete());
+        receiver.finalize().await.unwrap();
+
+        // The receiver cannot confirm more than the transfer holds
+        assert!(sender.process_cumulative_ack(11).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cumulative_ack_waits_for_gap() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let (sender, chunks, _) = ten_chunk_sender(src.path(), false).await;
+        let mut receiver =
+            ReceivePipeline::new(test_transfer_id(), dst.path(), test_key()).with_cumulative_ack(4);
+        receiver
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+
+        let mut acks = Vec::new();
+        // Chunk 0 arrives last among the first five
+        for i in [1, 2, 3, 4, 0, 5, 6, 7, 8, 9] {
+            feed_chunks(&mut receiver, &chunks[i..=i]);
+            if let Some(Message::CumulativeAck { received, .. }) = receiver.cumulative_ack() {
+                acks.push(received);
+            }
+        }
+        // Nothing until the gap at 0 fills, then every 4 chunks, then the end
+        assert_eq!(acks, vec![5, 9, 10]);
+
+        // Off unless enabled
+        let mut quiet = ReceivePipeline::new(test_transfer_id(), dst.path(), test_key());
+        quiet
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+        feed_chunks(&mut quiet, &chunks);
+        assert!(quiet.cumulative_ack().is_none());
+    }
+
+    fn chunk_data(msg: &Message) -> &[u8] {
+        match msg {
+            Message::Chunk { data, .. } => data,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunk_checksum_localizes_corruption() {
+        let src = tempfile::tempdir().unwrap();
+        let dst: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        let (sender, mut chunks, len) = ten_chunk_sender(src.path(), true).await;
+        let manifest = sender.manifest().to_bytes().unwrap();
+
+        // Flip one ciphertext bit in chunk 6
+        if let Message::Chunk { data, .. } = &mut chunks[6] {
+            data[0] ^= 0x08;
+        }
+
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst[0].path(), test_key())
+            .with_chunk_checksum(true);
+        receiver.process_offer(&manifest).unwrap();
+        feed_chunks(&mut receiver, &chunks[..6]);
+        let Message::Chunk {
+            index, data, total, ..
+        } = &chunks[6]
+        else {
+            unreachable!();
+        };
+        let err = receiver.process_chunk(*index, data, *total).unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::ChunkChecksumMismatch { index: 6 }
+        ));
+        assert!(err.to_string().contains("Chunk 6 checksum mismatch"));
+
+        // With retransmit the chunk is re-requested instead
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst[1].path(), test_key())
+            .with_chunk_checksum(true)
+            .with_chunk_retransmit(1);
+        receiver.process_offer(&manifest).unwrap();
+        assert!(matches!(
+            receiver.process_chunk(6, chunk_data(&chunks[6]), None),
+            Ok(Some(Message::ResendChunks { ref indices, .. })) if indices == &[6]
+        ));
+
+        // Intact chunks pass both checks
+        let (_, intact, _) = ten_chunk_sender(src.path(), true).await;
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst[2].path(), test_key())
+            .with_chunk_checksum(true);
+        receiver.process_offer(&manifest).unwrap();
+        feed_chunks(&mut receiver, &intact);
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::metadata(&written[0]).unwrap().len(), len as u64);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_checksum_off_keeps_wire_format() {
+        let src = tempfile::tempdir().unwrap();
+        let (_, plain, _) = ten_chunk_sender(src.path(), false).await;
+        let (_, checked, _) = ten_chunk_sender(src.path(), true).await;
+
+        // Disabled, payloads are exactly what a build without checksums sends;
+        // enabled, they gain only the 4-byte trailer
+        for (plain, checked) in plain.iter().zip(&checked) {
+            let (plain, checked) = (chunk_data(plain), chunk_data(checked));
+            assert_eq!(checked.len(), plain.len() + chunking::CHUNK_CHECKSUM_LEN);
+            assert_eq!(&checked[..plain.len()], plain);
+        }
+
+        // A receiver that does not expect the trailer decrypts plain chunks
+        let (sender, _, _) = ten_chunk_sender(src.path(), false).await;
+        let dst = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst.path(), test_key());
+        receiver
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+        feed_chunks(&mut receiver, &plain);
+        assert!(receiver.is_complete());
+    }
+
+    /// Send `paths` end to end uncompressed, as sparse files if `sparse` is
+    /// negotiated
+    ///
+    /// Returns the receiver's output directory, the paths it wrote, and the
+    /// bytes of chunk payload that went over the wire.
+    async fn transfer(paths: &[PathBuf], sparse: bool) -> (tempfile::TempDir, Vec<PathBuf>, usize) {
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key())
+            .with_compression(CompressionAlgorithm::None);
+        sender.prepare(paths).await.unwrap();
+        let negotiated = if sparse {
+            crate::wire::FeatureSet::local()
+        } else {
+            crate::wire::FeatureSet::legacy()
+        };
+        sender.enable_sparse(negotiated).unwrap();
+        let Message::FileOffer { manifest, .. } = sender.file_offer().unwrap() else {
+            unreachable!();
+        };
+        let chunks = sender.chunk_accepted().await.unwrap();
+        let sent = chunks.iter().map(|c| chunk_data(c).len()).sum();
+
+        let dst = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst.path(), test_key());
+        receiver.process_offer(&manifest).unwrap();
+        feed_chunks(&mut receiver, &chunks);
+        assert!(receiver.is_complete());
+        let written = receiver.finalize().await.unwrap();
+        (dst, written, sent)
+    }
+
+    #[tokio::test]
+    async fn test_zero_length_files_roundtrip() {
+        let src = tempfile::tempdir().unwrap();
+        let empty = src.path().join("empty.txt");
+        std::fs::write(&empty, b"").unwrap();
+
+        // A transfer of nothing but an empty file has no chunks at all
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key());
+        sender.prepare(std::slice::from_ref(&empty)).await.unwrap();
+        let entry = &sender.manifest().files[0];
+        assert_eq!((entry.size, entry.chunk_count), (0, 0));
+        assert_eq!(entry.hash, *blake3::hash(b"").as_bytes());
+        assert_eq!(sender.manifest().total_chunks, 0);
+
+        let (_dst, written, sent) = transfer(std::slice::from_ref(&empty), true).await;
+        assert_eq!(sent, 0);
+        assert_eq!(written.len(), 1);
+        assert_eq!(std::fs::read(&written[0]).unwrap(), b"");
+
+        // Between other files it takes no chunk index of its own
+        let before = src.path().join("a.txt");
+        let after = src.path().join("z.txt");
+        std::fs::write(&before, b"before").unwrap();
+        std::fs::write(&after, vec![0x5Au8; 70_000]).unwrap();
+        let (dst, written, _) = transfer(&[after, empty, before], true).await;
+        assert_eq!(written.len(), 3);
+        assert_eq!(std::fs::read(dst.path().join("a.txt")).unwrap(), b"before");
+        assert_eq!(std::fs::read(dst.path().join("empty.txt")).unwrap(), b"");
+        assert_eq!(
+            std::fs::read(dst.path().join("z.txt")).unwrap(),
+            vec![0x5Au8; 70_000]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sha3_manifest_verifies_under_sha3() {
+        let src = tempfile::tempdir().unwrap();
+        let path = src.path().join("report.pdf");
+        let data: Vec<u8> = (0..150_000u32).map(|i| (i % 199) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        // Without the option the sender hashes with BLAKE3
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key());
+        sender.prepare(std::slice::from_ref(&path)).await.unwrap();
+        assert_eq!(sender.manifest().hash_algorithm, HashAlgorithm::Blake3);
+        assert_eq!(
+            sender.manifest().files[0].hash,
+            *blake3::hash(&data).as_bytes()
+        );
+
+        let mut sender = SendPipeline::new(test_transfer_id(), test_key())
+            .with_hash_algorithm(HashAlgorithm::Sha3_256);
+        sender.prepare(std::slice::from_ref(&path)).await.unwrap();
+        assert_eq!(
+            sender.manifest().files[0].hash,
+            tallow_crypto::hash::sha3_256(&data)
+        );
+        assert!(sender
+            .check_hash_algorithm(crate::wire::FeatureSet::local())
+            .is_ok());
+        assert!(sender
+            .check_hash_algorithm(crate::wire::FeatureSet::legacy())
+            .is_err());
+        let chunks = sender.chunk_accepted().await.unwrap();
+
+        let dst = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst.path(), test_key());
+        let manifest = receiver
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+        assert_eq!(manifest.hash_algorithm, HashAlgorithm::Sha3_256);
+        feed_chunks(&mut receiver, &chunks);
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::read(&written[0]).unwrap(), data);
+
+        // The same digest checked as BLAKE3 does not verify
+        let mut relabeled = sender.manifest().clone();
+        relabeled.hash_algorithm = HashAlgorithm::Blake3;
+        let dst = tempfile::tempdir().unwrap();
+        let mut receiver = ReceivePipeline::new(test_transfer_id(), dst.path(), test_key());
+        receiver
+            .process_offer(&relabeled.to_bytes().unwrap())
+            .unwrap();
+        feed_chunks(&mut receiver, &chunks);
+        let err = receiver.finalize().await.unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"), "{}", err);
+    }
+
+    /// Blocks a file actually occupies on disk, in bytes
+    #[cfg(target_os = "linux")]
+    fn allocated(path: &Path) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).unwrap().blocks() * 512
+    }
+
+    /// Whether the filesystem under `dir` reports holes
+    #[cfg(target_os = "linux")]
+    fn reports_holes(dir: &Path) -> bool {
+        let probe = dir.join("probe");
+        let file = std::fs::File::create(&probe).unwrap();
+        file.set_len(1 << 20).unwrap();
+        let holes = crate::transfer::sparse::find_holes(&file, 1 << 20).unwrap();
+        std::fs::remove_file(&probe).unwrap();
+        !holes.is_empty()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_sparse_file_keeps_holes() {
+        use std::io::{Seek, SeekFrom};
+
+        let src = tempfile::tempdir().unwrap();
+        if !reports_holes(src.path()) {
+            return;
+        }
+        const SIZE: u64 = 8 * 1024 * 1024;
+        let path = src.path().join("disk.img");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.set_len(SIZE).unwrap();
+        for (offset, byte) in [(1024 * 1024, 0x11u8), (5 * 1024 * 1024 + 4096, 0x22)] {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(&vec![byte; 200_000]).unwrap();
+        }
+        file.sync_all().unwrap();
+        let original = std::fs::read(&path).unwrap();
+
+        let (_dst, written, sent) = transfer(std::slice::from_ref(&path), true).await;
+        assert_eq!(std::fs::read(&written[0]).unwrap(), original);
+        // Only the written regions (and their block padding) went out...
+        assert!(sent < 1024 * 1024, "sent {} bytes", sent);
+        // ...and the output is as sparse as the source
+        assert!(allocated(&written[0]) < SIZE / 4);
+
+        // Without the feature the holes go out as zeros, and the file
+        // still arrives intact
+        let (_dst, written, _) = transfer(std::slice::from_ref(&path), false).await;
+        assert_eq!(std::fs::read(&written[0]).unwrap(), original);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_hole_only_file_sends_no_chunks() {
+        let src = tempfile::tempdir().unwrap();
+        if !reports_holes(src.path()) {
+            return;
+        }
+        const SIZE: u64 = 16 * 1024 * 1024;
+        let path = src.path().join("blank.img");
+        std::fs::File::create(&path).unwrap().set_len(SIZE).unwrap();
+
+        let (_dst, written, sent) = transfer(std::slice::from_ref(&path), true).await;
+        assert_eq!(sent, 0);
+        assert_eq!(std::fs::metadata(&written[0]).unwrap().len(), SIZE);
+        assert_eq!(allocated(&written[0]), 0);
+        let contents = std::fs::read(&written[0]).unwrap();
+        assert!(contents.iter().all(|&b| b == 0));
+    }
 }
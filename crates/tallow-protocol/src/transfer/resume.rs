@@ -1,10 +1,120 @@
 //! Transfer resume state
 //!
 //! Saves and restores transfer progress for resuming interrupted transfers.
+//!
+//! The state also fingerprints each source file (size, mtime and a hash of
+//! samples spread across its content) so a resumed send can detect that a
+//! source changed on disk and restart instead of producing a corrupt
+//! transfer.
 
+use crate::wire::Message;
 use crate::{ProtocolError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Bytes hashed at each sample point
+const SAMPLE_LEN: u64 = 64 * 1024;
+
+/// Sample points per file; files up to `SAMPLES * SAMPLE_LEN` bytes are
+/// hashed whole
+const SAMPLES: u64 = 16;
+
+/// Identity of a source file when its transfer started
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceFingerprint {
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time (seconds since the Unix epoch), if available
+    pub modified: Option<u64>,
+    /// BLAKE3 hash of the sampled content
+    pub content_hash: [u8; 32],
+}
+
+impl SourceFingerprint {
+    /// Fingerprint `path`
+    ///
+    /// Small files are hashed whole. Larger ones are sampled at
+    /// [`SAMPLES`] evenly spaced points from the first byte to the last,
+    /// so an edit anywhere but between samples is caught without
+    /// rereading the whole file.
+    pub fn capture(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path).map_err(|e| {
+            ProtocolError::TransferFailed(format!("stat {}: {}", path.display(), e))
+        })?;
+        Ok(Self {
+            size: metadata.len(),
+            modified: modified_secs(&metadata),
+            content_hash: hash_samples(path, metadata.len())?,
+        })
+    }
+
+    /// Whether `path` still has the content this fingerprint describes
+    ///
+    /// Size and the content hash decide; a changed mtime alone (e.g.
+    /// after `touch` or a copy) does not invalidate the fingerprint.
+    pub fn matches(&self, path: &Path) -> Result<bool> {
+        let metadata = match std::fs::metadata(path) {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => {
+                return Err(ProtocolError::TransferFailed(format!(
+                    "stat {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        if metadata.len() != self.size {
+            return Ok(false);
+        }
+
+        let content_hash = hash_samples(path, self.size)?;
+        let same = tallow_crypto::mem::constant_time::ct_eq(&content_hash, &self.content_hash);
+        if same && modified_secs(&metadata) != self.modified {
+            tracing::debug!("{} has a new mtime but unchanged content", path.display());
+        }
+        Ok(same)
+    }
+}
+
+fn modified_secs(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// Offsets of the sample windows for a file of `size` bytes
+fn sample_offsets(size: u64) -> Vec<u64> {
+    if size <= SAMPLES * SAMPLE_LEN {
+        return vec![0];
+    }
+    let last = size - SAMPLE_LEN;
+    (0..SAMPLES).map(|i| last * i / (SAMPLES - 1)).collect()
+}
+
+fn hash_samples(path: &Path, size: u64) -> Result<[u8; 32]> {
+    let read_err = |e: std::io::Error| {
+        ProtocolError::TransferFailed(format!("read {}: {}", path.display(), e))
+    };
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| ProtocolError::TransferFailed(format!("open {}: {}", path.display(), e)))?;
+    let window = if size <= SAMPLES * SAMPLE_LEN {
+        size
+    } else {
+        SAMPLE_LEN
+    };
+    let mut hasher = blake3::Hasher::new();
+    for offset in sample_offsets(size) {
+        file.seek(SeekFrom::Start(offset)).map_err(read_err)?;
+        std::io::copy(&mut (&mut file).take(window), &mut hasher).map_err(read_err)?;
+    }
+    Ok(hasher.finalize().into())
+}
 
 /// Resume state for interrupted transfers
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +129,8 @@ pub struct ResumeState {
     pub bytes_transferred: u64,
     /// BLAKE3 hash of the manifest (to verify same transfer)
     pub manifest_hash: [u8; 32],
+    /// Fingerprints of the sender's source files, in manifest order
+    pub sources: Vec<SourceFingerprint>,
 }
 
 impl ResumeState {
@@ -30,9 +142,48 @@ impl ResumeState {
             total_chunks,
             bytes_transferred: 0,
             manifest_hash,
+            sources: Vec::new(),
         }
     }
 
+    /// Record fingerprints of the source files being sent
+    pub fn with_sources(mut self, paths: &[PathBuf]) -> Result<Self> {
+        self.sources = paths
+            .iter()
+            .map(|p| SourceFingerprint::capture(p))
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Whether every source file still matches its recorded fingerprint
+    pub fn sources_match(&self, paths: &[PathBuf]) -> Result<bool> {
+        if paths.len() != self.sources.len() {
+            return Ok(false);
+        }
+        for (fingerprint, path) in self.sources.iter().zip(paths) {
+            if !fingerprint.matches(path)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Validate a restored state against the current source files
+    ///
+    /// Returns `self` if the sources are unchanged. Otherwise warns and
+    /// returns a fresh state (no verified chunks, new fingerprints) so the
+    /// transfer restarts from the beginning.
+    pub fn revalidate(self, paths: &[PathBuf]) -> Result<Self> {
+        if self.sources_match(paths)? {
+            return Ok(self);
+        }
+
+        tracing::warn!(
+            "source files changed since the transfer was interrupted; restarting from the beginning"
+        );
+        Self::new(self.transfer_id, self.total_chunks, self.manifest_hash).with_sources(paths)
+    }
+
     /// Mark a chunk as verified
     pub fn mark_verified(&mut self, chunk_index: u64, chunk_size: u64) {
         self.verified_chunks.insert(chunk_index);
@@ -49,6 +200,26 @@ impl ResumeState {
         (0..self.total_chunks).find(|i| !self.verified_chunks.contains(i))
     }
 
+    /// Chunks not yet verified, in index order
+    pub fn pending_chunks(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.total_chunks).filter(|i| !self.verified_chunks.contains(i))
+    }
+
+    /// `ResumeInfo` telling the sender which chunks are already held
+    ///
+    /// Sent when a transfer restarts from a checkpoint, and again when the
+    /// connection migrates to another transport mid-transfer, since chunks
+    /// in flight on the old path may never have arrived.
+    pub fn resume_info(&self) -> Message {
+        let mut verified_chunks: Vec<u64> = self.verified_chunks.iter().copied().collect();
+        verified_chunks.sort_unstable();
+        Message::ResumeInfo {
+            transfer_id: self.transfer_id,
+            manifest_hash: self.manifest_hash,
+            verified_chunks,
+        }
+    }
+
     /// Get completion percentage
     pub fn completion_percentage(&self) -> f64 {
         if self.total_chunks == 0 {
@@ -115,4 +286,94 @@ mod tests {
         assert!(restored.is_verified(3));
         assert!(!restored.is_verified(1));
     }
+
+    fn write_source(dir: &Path, name: &str, data: &[u8]) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn interrupted_state(paths: &[PathBuf]) -> ResumeState {
+        let mut state = ResumeState::new([7u8; 16], 4, [9u8; 32])
+            .with_sources(paths)
+            .unwrap();
+        state.mark_verified(0, 1024);
+        state.mark_verified(1, 1024);
+        // Round-trip as a real resume would
+        ResumeState::restore(&state.checkpoint().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_source_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = vec![write_source(dir.path(), "a.bin", &[1u8; 300_000])];
+        let state = interrupted_state(&paths);
+
+        let resumed = state.revalidate(&paths).unwrap();
+        assert!(resumed.is_verified(0));
+        assert!(resumed.is_verified(1));
+    }
+
+    #[test]
+    fn test_modified_source_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = vec![write_source(dir.path(), "a.bin", &[1u8; 300_000])];
+        let state = interrupted_state(&paths);
+
+        // Same size, different first chunk
+        let mut changed = vec![1u8; 300_000];
+        changed[10] = 2;
+        std::fs::write(&paths[0], &changed).unwrap();
+
+        let restarted = state.revalidate(&paths).unwrap();
+        assert_eq!(restarted.completion_percentage(), 0.0);
+        assert_eq!(restarted.next_needed_chunk(), Some(0));
+        assert!(restarted.sources_match(&paths).unwrap());
+
+        // Size change is caught too
+        let state = interrupted_state(&paths);
+        std::fs::write(&paths[0], [1u8; 10]).unwrap();
+        assert!(!state.sources_match(&paths).unwrap());
+    }
+
+    #[test]
+    fn test_change_past_first_chunk_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let size = (SAMPLES * SAMPLE_LEN * 4) as usize;
+        let original: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        let paths = vec![write_source(dir.path(), "big.bin", &original)];
+
+        // The tail and a point in the middle are both checked
+        let middle = sample_offsets(size as u64)[SAMPLES as usize / 2] as usize + 10;
+        for offset in [size - 1, middle] {
+            let state = interrupted_state(&paths);
+            let mut changed = original.clone();
+            changed[offset] ^= 0xFF;
+            std::fs::write(&paths[0], &changed).unwrap();
+            assert!(!state.sources_match(&paths).unwrap(), "offset {}", offset);
+            std::fs::write(&paths[0], &original).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_touched_but_identical_source_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = vec![write_source(dir.path(), "a.bin", &[3u8; 50_000])];
+        let state = interrupted_state(&paths);
+
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&paths[0])
+            .unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        file.set_modified(later).unwrap();
+        drop(file);
+        assert_ne!(
+            modified_secs(&std::fs::metadata(&paths[0]).unwrap()),
+            state.sources[0].modified
+        );
+
+        let resumed = state.revalidate(&paths).unwrap();
+        assert!(resumed.is_verified(1));
+    }
 }
@@ -0,0 +1,253 @@
+//! Chunk-level retransmission
+//!
+//! A chunk that fails authentication at the receiver is answered with
+//! `ResendChunks` instead of an `Ack` (see
+//! [`ReceivePipeline::with_chunk_retransmit`]), and the sender repeats just
+//! that chunk rather than restarting the transfer.
+//!
+//! The sender resends the ciphertext it already produced, never a fresh
+//! encryption: chunk nonces are derived from the chunk index, so encrypting
+//! again after the source file changed would reuse a nonce with different
+//! plaintext.
+//!
+//! [`ReceivePipeline::with_chunk_retransmit`]: crate::transfer::ReceivePipeline::with_chunk_retransmit
+
+use crate::wire::Message;
+use crate::{ProtocolError, Result};
+use std::collections::BTreeMap;
+
+/// Default number of times the receiver re-requests one chunk
+pub const DEFAULT_MAX_CHUNK_RETRIES: u32 = 3;
+
+/// Chunks sent but not yet acknowledged, kept so they can be resent
+#[derive(Debug, Default)]
+pub struct SentChunks<'a> {
+    chunks: BTreeMap<u64, &'a Message>,
+}
+
+impl<'a> SentChunks<'a> {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `msg`, sent as transfer-wide chunk `index`, until it is acked
+    pub fn record(&mut self, index: u64, msg: &'a Message) {
+        self.chunks.insert(index, msg);
+    }
+
+    /// Forget chunk `index` once acked; `false` if it was not outstanding
+    pub fn ack(&mut self, index: u64) -> bool {
+        self.chunks.remove(&index).is_some()
+    }
+
+    /// The messages to send again for a `ResendChunks` request
+    ///
+    /// Fails if the receiver asks for a chunk that is not awaiting an ack,
+    /// which only a confused or malicious peer would do.
+    pub fn resend(&self, indices: &[u64]) -> Result<Vec<&'a Message>> {
+        indices
+            .iter()
+            .map(|index| {
+                self.chunks.get(index).copied().ok_or_else(|| {
+                    ProtocolError::TransferFailed(format!(
+                        "peer asked to resend chunk {} which is not awaiting an ack",
+                        index
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Number of chunks awaiting an ack
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Whether every recorded chunk has been acked
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::chunking::{self, ChunkConfig};
+    use crate::transfer::{ReceivePipeline, SendPipeline};
+    use tokio::sync::mpsc;
+
+    const TRANSFER_ID: [u8; 16] = [0x52; 16];
+    const KEY: [u8; 32] = [0x17; 32];
+
+    /// A five-chunk file, its sender and the encrypted chunks
+    async fn sender(dir: &std::path::Path) -> (SendPipeline, Vec<u8>, Vec<Message>) {
+        let data: Vec<u8> = (0..5 * chunking::MIN_CHUNK_SIZE - 10)
+            .map(|i| (i % 241) as u8)
+            .collect();
+        let path = dir.join("data.bin");
+        std::fs::write(&path, &data).unwrap();
+
+        let mut config = ChunkConfig::new();
+        config.size = chunking::MIN_CHUNK_SIZE;
+        let mut pipeline = SendPipeline::new(TRANSFER_ID, KEY).with_chunk_config(config);
+        pipeline.prepare(&[path.clone()]).await.unwrap();
+        let chunks = pipeline.chunk_file(&path, 0).await.unwrap();
+        assert_eq!(chunks.len(), 5);
+        (pipeline, data, chunks)
+    }
+
+    fn receiver(out: &std::path::Path, pipeline: &SendPipeline) -> ReceivePipeline {
+        let mut receiver = ReceivePipeline::new(TRANSFER_ID, out, KEY)
+            .with_chunk_retransmit(DEFAULT_MAX_CHUNK_RETRIES);
+        receiver
+            .process_offer(&pipeline.manifest().to_bytes().unwrap())
+            .unwrap();
+        receiver
+    }
+
+    fn chunk_index(msg: &Message) -> u64 {
+        match msg {
+            Message::Chunk { index, .. } => *index,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// Run a transfer over in-memory channels, flipping a bit in the first
+    /// `corrupt_times` transmissions of chunk `corrupt`
+    ///
+    /// Returns the receiver, the index of every chunk transmission in order,
+    /// and every `ResendChunks` request, or the receiver's error.
+    async fn transfer(
+        chunks: &[Message],
+        mut receiver: ReceivePipeline,
+        corrupt: u64,
+        corrupt_times: usize,
+    ) -> Result<(ReceivePipeline, Vec<u64>, Vec<Vec<u64>>)> {
+        let (to_receiver, mut from_sender) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (to_sender, mut from_receiver) = mpsc::unbounded_channel::<Message>();
+
+        let send = async {
+            let mut transmitted = Vec::new();
+            let mut requests = Vec::new();
+            let mut corrupted = 0;
+            let mut transmit = |msg: &Message| {
+                let index = chunk_index(msg);
+                transmitted.push(index);
+                let mut bytes = postcard::to_stdvec(msg).unwrap();
+                if index == corrupt && corrupted < corrupt_times {
+                    corrupted += 1;
+                    let last = bytes.len() - 1;
+                    bytes[last] ^= 0x01;
+                }
+                let _ = to_receiver.send(bytes);
+            };
+
+            let mut sent = SentChunks::new();
+            for msg in chunks {
+                sent.record(chunk_index(msg), msg);
+                transmit(msg);
+            }
+            while !sent.is_empty() {
+                match from_receiver.recv().await {
+                    Some(Message::Ack { index, .. }) => assert!(sent.ack(index)),
+                    Some(Message::ResendChunks { indices, .. }) => {
+                        for msg in sent.resend(&indices).unwrap() {
+                            transmit(msg);
+                        }
+                        requests.push(indices);
+                    }
+                    Some(other) => panic!("unexpected {:?}", other),
+                    // Receiver gave up
+                    None => break,
+                }
+            }
+            drop(to_receiver);
+            (transmitted, requests)
+        };
+        // Owns the receiver's channel ends, so they close when it stops
+        let recv = async move {
+            while let Some(bytes) = from_sender.recv().await {
+                let Message::Chunk {
+                    index, total, data, ..
+                } = postcard::from_bytes(&bytes).unwrap()
+                else {
+                    panic!("expected a chunk");
+                };
+                let reply = receiver.process_chunk(index, &data, total)?;
+                to_sender.send(reply.unwrap()).unwrap();
+                if receiver.is_complete() {
+                    break;
+                }
+            }
+            Ok::<_, ProtocolError>(receiver)
+        };
+
+        let ((transmitted, requests), receiver) = tokio::join!(send, recv);
+        Ok((receiver?, transmitted, requests))
+    }
+
+    #[tokio::test]
+    async fn test_only_corrupted_chunk_is_resent() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pipeline, data, chunks) = sender(dir.path()).await;
+        let receiver = receiver(&dir.path().join("out"), &pipeline);
+
+        let (mut receiver, transmitted, requests) =
+            transfer(&chunks, receiver, 2, 1).await.unwrap();
+
+        assert_eq!(requests, vec![vec![2]]);
+        assert_eq!(transmitted, vec![0, 1, 2, 3, 4, 2]);
+        assert!(receiver.is_complete());
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(std::fs::read(&written[0]).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_corruption_fails_after_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pipeline, _, chunks) = sender(dir.path()).await;
+        let receiver = receiver(&dir.path().join("out"), &pipeline);
+
+        let err = transfer(&chunks, receiver, 3, usize::MAX)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("chunk 3"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_retransmit_off_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pipeline, _, chunks) = sender(dir.path()).await;
+        let mut receiver = ReceivePipeline::new(TRANSFER_ID, dir.path().join("out"), KEY);
+        receiver
+            .process_offer(&pipeline.manifest().to_bytes().unwrap())
+            .unwrap();
+
+        let Message::Chunk {
+            index,
+            total,
+            mut data,
+            ..
+        } = chunks[1].clone()
+        else {
+            unreachable!();
+        };
+        data[0] ^= 0x80;
+        assert!(receiver.process_chunk(index, &data, total).is_err());
+    }
+
+    #[test]
+    fn test_resend_of_unsent_chunk_rejected() {
+        let msg = Message::Ack {
+            transfer_id: TRANSFER_ID,
+            index: 0,
+        };
+        let mut sent = SentChunks::new();
+        sent.record(0, &msg);
+        assert_eq!(sent.resend(&[0]).unwrap().len(), 1);
+        assert!(sent.ack(0));
+        assert!(sent.resend(&[0]).is_err());
+        assert!(!sent.ack(0));
+    }
+}
@@ -123,7 +123,9 @@ pub fn sanitize_filename(name: &str, output_dir: &Path) -> Result<PathBuf, Sanit
     let parts: Vec<&str> = trimmed.split('/').collect();
     let mut safe_components: Vec<String> = Vec::new();
 
-    for part in parts {
+    let first = parts.iter().position(|p| !p.trim().is_empty());
+    let last = parts.iter().rposition(|p| !p.trim().is_empty());
+    for (i, part) in parts.iter().enumerate() {
         let part = part.trim();
 
         // Skip empty components (from double slashes or leading slash)
@@ -136,8 +138,8 @@ pub fn sanitize_filename(name: &str, output_dir: &Path) -> Result<PathBuf, Sanit
             continue;
         }
 
-        // Skip tilde-prefixed components (prevent shell expansion)
-        if part.starts_with('~') {
+        // Skip home directory references (prevent shell expansion)
+        if is_home_reference(part, Some(i) == first, Some(i) == last) {
             continue;
         }
 
@@ -213,6 +215,66 @@ pub fn sanitize_filename(name: &str, output_dir: &Path) -> Result<PathBuf, Sanit
     Ok(joined)
 }
 
+/// Verify that a path from a manifest is a plain relative path.
+///
+/// [`sanitize_filename`] quietly drops dangerous components; this instead
+/// refuses the whole path if any component climbs out (`..`) or anchors it
+/// (leading separator, drive letter, `~` or `~user`). Separators are normalized the
+/// same way first, so `..\` and fullwidth slashes are caught too.
+///
+/// # Errors
+///
+/// Returns [`SanitizeError::PathEscape`] for traversal or absolute paths,
+/// and the same empty/null-byte errors as [`sanitize_filename`].
+pub fn check_relative_path(name: &str) -> Result<(), SanitizeError> {
+    if name.is_empty() {
+        return Err(SanitizeError::EmptyFilename);
+    }
+    if name.contains('\0') {
+        return Err(SanitizeError::NullByte);
+    }
+
+    let normalized = strip_ansi(name)
+        .replace(['\u{FF0F}', '\u{FF3C}'], "/")
+        .replace('\\', "/");
+    let trimmed = normalized.trim();
+    let escape = || SanitizeError::PathEscape(trimmed.to_string());
+
+    if trimmed.starts_with('/') {
+        return Err(escape());
+    }
+    let parts: Vec<&str> = trimmed.split('/').map(str::trim).collect();
+    for (i, part) in parts.iter().enumerate() {
+        let drive = part.len() >= 2
+            && part.as_bytes()[0].is_ascii_alphabetic()
+            && part.as_bytes()[1] == b':';
+        let home = i == 0 && is_home_reference(part, true, parts.len() == 1);
+        if *part == ".." || home || (i == 0 && drive) {
+            return Err(escape());
+        }
+    }
+    Ok(())
+}
+
+/// Whether a path component names a home directory (`~` or `~user`)
+///
+/// `~user` only counts as the leading component of a longer path, so
+/// ordinary names that start with a tilde, like Office lock files
+/// (`~$report.docx`) or a lone `~backup.txt`, are left alone.
+fn is_home_reference(part: &str, leading: bool, last: bool) -> bool {
+    let Some(user) = part.strip_prefix('~') else {
+        return false;
+    };
+    if user.is_empty() {
+        return true;
+    }
+    leading
+        && !last
+        && user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
 /// Truncate a string to at most `max_bytes` bytes, respecting UTF-8 boundaries.
 fn truncate_to_byte_len(s: &str, max_bytes: usize) -> &str {
     if s.len() <= max_bytes {
@@ -601,6 +663,53 @@ mod tests {
         assert!(result.to_string_lossy().contains("_CON.txt"));
     }
 
+    #[test]
+    fn test_check_relative_path() {
+        assert!(check_relative_path("docs/sub/report.pdf").is_ok());
+        assert!(check_relative_path("./notes.txt").is_ok());
+        for bad in [
+            "../escape.txt",
+            "docs/../../etc/passwd",
+            "..\\windows\\evil.dll",
+            "docs\u{FF0F}..\u{FF0F}x",
+            "/etc/passwd",
+            "\\\\server\\share",
+            "C:\\Windows\\evil.dll",
+            "~/.ssh/authorized_keys",
+            "~",
+            "~root/.bashrc",
+        ] {
+            assert!(
+                matches!(check_relative_path(bad), Err(SanitizeError::PathEscape(_))),
+                "{bad:?} accepted"
+            );
+        }
+        assert!(matches!(
+            check_relative_path(""),
+            Err(SanitizeError::EmptyFilename)
+        ));
+    }
+
+    #[test]
+    fn test_tilde_prefixed_names_allowed() {
+        for ok in [
+            "~$report.docx",
+            "~backup.txt",
+            "docs/~$report.docx",
+            "docs/~old/notes.txt",
+            "~$drafts/report.docx",
+        ] {
+            assert!(check_relative_path(ok).is_ok(), "{ok:?} rejected");
+        }
+        let result = sanitize_filename("~$report.docx", &output_dir()).unwrap();
+        assert_eq!(result, output_dir().join("~$report.docx"));
+        let result = sanitize_filename("~backup.txt", &output_dir()).unwrap();
+        assert_eq!(result, output_dir().join("~backup.txt"));
+        // A real home reference is still stripped
+        let result = sanitize_filename("~alice/notes.txt", &output_dir()).unwrap();
+        assert_eq!(result, output_dir().join("notes.txt"));
+    }
+
     #[test]
     fn test_sanitize_display_strips_ansi() {
         let input = "\x1b[31mred text\x1b[0m";
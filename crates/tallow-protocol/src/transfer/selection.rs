@@ -0,0 +1,312 @@
+//! Receiver-side file selection
+//!
+//! A receiver can accept only part of a multi-file offer. The accepted set
+//! is an [`AcceptanceMask`] over the manifest, sent back as a
+//! `FileSelection` before `FileAccept`. Both pipelines then number chunks
+//! over the accepted files only, so the sender never reads or sends a
+//! deselected file and the receiver does not wait for one.
+
+use crate::transfer::manifest::FileManifest;
+use crate::wire::Message;
+use crate::{ProtocolError, Result};
+use ignore::overrides::OverrideBuilder;
+
+/// Which manifest files the receiver accepted, in manifest order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptanceMask {
+    accepted: Vec<bool>,
+}
+
+impl AcceptanceMask {
+    /// Accept every file in `manifest`
+    pub fn all(manifest: &FileManifest) -> Self {
+        Self {
+            accepted: vec![true; manifest.files.len()],
+        }
+    }
+
+    /// Accept the files at `indices`, as carried by `FileSelection`
+    ///
+    /// Fails if the selection is empty or names a file not in the manifest.
+    pub fn from_indices(manifest: &FileManifest, indices: &[u32]) -> Result<Self> {
+        if indices.is_empty() {
+            return Err(ProtocolError::TransferFailed(
+                "empty file selection".to_string(),
+            ));
+        }
+        let mut accepted = vec![false; manifest.files.len()];
+        for &index in indices {
+            let slot = accepted.get_mut(index as usize).ok_or_else(|| {
+                ProtocolError::TransferFailed(format!(
+                    "file selection index {} out of range ({} files)",
+                    index,
+                    manifest.files.len()
+                ))
+            })?;
+            *slot = true;
+        }
+        Ok(Self { accepted })
+    }
+
+    /// Accept the files whose manifest path matches any of `patterns`
+    ///
+    /// Patterns use gitignore syntax, as `--exclude` does: `*.jpg` matches
+    /// at any depth, `photos/**` everything under `photos`. The result may
+    /// accept nothing; check [`is_empty`](Self::is_empty).
+    pub fn from_globs(manifest: &FileManifest, patterns: &[String]) -> Result<Self> {
+        let mut builder = OverrideBuilder::new("");
+        for pattern in patterns {
+            builder.add(pattern).map_err(|e| {
+                ProtocolError::TransferFailed(format!("invalid pattern '{}': {}", pattern, e))
+            })?;
+        }
+        let matcher = builder
+            .build()
+            .map_err(|e| ProtocolError::TransferFailed(format!("invalid patterns: {}", e)))?;
+
+        let accepted = manifest
+            .files
+            .iter()
+            .map(|f| matcher.matched(&f.path, false).is_whitelist())
+            .collect();
+        Ok(Self { accepted })
+    }
+
+    /// Whether manifest file `file_id` was accepted
+    pub fn is_accepted(&self, file_id: u32) -> bool {
+        self.accepted
+            .get(file_id as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Whether every file was accepted
+    pub fn is_all(&self) -> bool {
+        self.accepted.iter().all(|a| *a)
+    }
+
+    /// Whether no file was accepted
+    pub fn is_empty(&self) -> bool {
+        !self.accepted.iter().any(|a| *a)
+    }
+
+    /// Number of accepted files
+    pub fn len(&self) -> usize {
+        self.accepted.iter().filter(|a| **a).count()
+    }
+
+    /// Manifest indices of the accepted files
+    pub fn indices(&self) -> Vec<u32> {
+        (0..self.accepted.len() as u32)
+            .filter(|&i| self.is_accepted(i))
+            .collect()
+    }
+
+    /// Chunks the sender will transmit
+    pub fn total_chunks(&self, manifest: &FileManifest) -> u64 {
+        self.accepted_entries(manifest).map(|f| f.chunk_count).sum()
+    }
+
+    /// Bytes the sender will transmit (before compression)
+    ///
+    /// Holes in sparse files are not transmitted and don't count.
+    pub fn total_size(&self, manifest: &FileManifest) -> u64 {
+        self.indices()
+            .into_iter()
+            .map(|i| manifest.data_len(i as usize))
+            .sum()
+    }
+
+    /// Transfer-wide index of chunk `index` of file `file_id`
+    ///
+    /// Chunks are numbered over accepted files only. `None` if the file was
+    /// not accepted or has no such chunk.
+    pub fn chunk_index(&self, manifest: &FileManifest, file_id: u32, index: u64) -> Option<u64> {
+        let entry = manifest.files.get(file_id as usize)?;
+        if !self.is_accepted(file_id) || index >= entry.chunk_count {
+            return None;
+        }
+        let start: u64 = manifest.files[..file_id as usize]
+            .iter()
+            .zip(&self.accepted)
+            .filter(|(_, accepted)| **accepted)
+            .map(|(f, _)| f.chunk_count)
+            .sum();
+        start.checked_add(index)
+    }
+
+    /// Plaintext bytes in the first `chunks` transfer-wide chunks
+    ///
+    /// Every chunk but a file's last is a full `manifest.chunk_size`.
+    pub fn bytes_through(&self, manifest: &FileManifest, chunks: u64) -> u64 {
+        let mut remaining = chunks;
+        let mut bytes = 0u64;
+        for file_id in self.indices() {
+            let Some(entry) = manifest.files.get(file_id as usize) else {
+                break;
+            };
+            if remaining >= entry.chunk_count {
+                bytes += manifest.data_len(file_id as usize);
+                remaining -= entry.chunk_count;
+            } else {
+                bytes += remaining * manifest.chunk_size as u64;
+                break;
+            }
+        }
+        bytes
+    }
+
+    /// The `FileSelection` message announcing this mask
+    pub fn to_message(&self, transfer_id: [u8; 16]) -> Message {
+        Message::FileSelection {
+            transfer_id,
+            selected_indices: self.indices(),
+        }
+    }
+
+    fn accepted_entries<'a>(
+        &'a self,
+        manifest: &'a FileManifest,
+    ) -> impl Iterator<Item = &'a crate::transfer::manifest::FileEntry> {
+        manifest
+            .files
+            .iter()
+            .zip(&self.accepted)
+            .filter(|(_, accepted)| **accepted)
+            .map(|(f, _)| f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::chunking::{self, ChunkConfig};
+    use crate::transfer::{ReceivePipeline, SendPipeline};
+
+    const TRANSFER_ID: [u8; 16] = [0x66; 16];
+    const KEY: [u8; 32] = [0x19; 32];
+
+    /// Sender offering three multi-chunk files, and their contents
+    async fn three_file_sender(dir: &std::path::Path) -> (SendPipeline, Vec<Vec<u8>>) {
+        let size = chunking::MIN_CHUNK_SIZE;
+        let contents: Vec<Vec<u8>> = [2 * size + 5, 3 * size, size + 1]
+            .iter()
+            .enumerate()
+            .map(|(n, len)| (0..*len).map(|i| (i * (n + 3) % 251) as u8).collect())
+            .collect();
+        let src = dir.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        let names = ["notes.txt", "photo.jpg", "report.txt"];
+        let paths: Vec<_> = names
+            .iter()
+            .zip(&contents)
+            .map(|(name, data)| {
+                let path = src.join(name);
+                std::fs::write(&path, data).unwrap();
+                path
+            })
+            .collect();
+
+        let mut config = ChunkConfig::new();
+        config.size = size;
+        let mut pipeline = SendPipeline::new(TRANSFER_ID, KEY).with_chunk_config(config);
+        pipeline.prepare(&paths).await.unwrap();
+        (pipeline, contents)
+    }
+
+    #[tokio::test]
+    async fn test_only_accepted_files_transferred() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut sender, contents) = three_file_sender(dir.path()).await;
+        let manifest = sender.manifest().clone();
+
+        let out = dir.path().join("out");
+        let mut receiver = ReceivePipeline::new(TRANSFER_ID, &out, KEY);
+        receiver
+            .process_offer(&manifest.to_bytes().unwrap())
+            .unwrap();
+
+        // Receiver picks the text files and sends its selection
+        let mask = AcceptanceMask::from_globs(&manifest, &["*.txt".to_string()]).unwrap();
+        assert_eq!(mask.indices(), vec![0, 2]);
+        let Message::FileSelection {
+            selected_indices, ..
+        } = mask.to_message(TRANSFER_ID)
+        else {
+            unreachable!();
+        };
+        receiver.accept_files(mask.clone()).unwrap();
+
+        // Sender applies the selection it received
+        sender.accept_files(AcceptanceMask::from_indices(&manifest, &selected_indices).unwrap());
+        let chunks = sender.chunk_accepted().await.unwrap();
+        assert_eq!(chunks.len() as u64, mask.total_chunks(&manifest));
+
+        for msg in &chunks {
+            let Message::Chunk {
+                index, total, data, ..
+            } = msg
+            else {
+                panic!("unexpected {:?}", msg);
+            };
+            receiver.process_chunk(*index, data, *total).unwrap();
+        }
+        assert!(receiver.is_complete());
+
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(std::fs::read(&written[0]).unwrap(), contents[0]);
+        assert_eq!(std::fs::read(&written[1]).unwrap(), contents[2]);
+        assert!(!out.join("photo.jpg").exists());
+    }
+
+    #[tokio::test]
+    async fn test_sender_skips_deselected_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut sender, _) = three_file_sender(dir.path()).await;
+        let manifest = sender.manifest().clone();
+        let mask = AcceptanceMask::from_indices(&manifest, &[2]).unwrap();
+        sender.accept_files(mask.clone());
+
+        // The deselected files are never read
+        std::fs::remove_file(dir.path().join("src/notes.txt")).unwrap();
+        std::fs::remove_file(dir.path().join("src/photo.jpg")).unwrap();
+
+        let chunks = sender.chunk_accepted().await.unwrap();
+        let indices: Vec<u64> = chunks
+            .iter()
+            .map(|m| match m {
+                Message::Chunk { index, .. } => *index,
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(indices, vec![0, 1]);
+        assert_eq!(mask.total_chunks(&manifest), manifest.files[2].chunk_count);
+        assert!(matches!(
+            chunks.last(),
+            Some(Message::Chunk { total: Some(2), .. })
+        ));
+
+        // Interleaved chunks of an accepted file use the same numbering
+        assert_eq!(mask.chunk_index(&manifest, 2, 1), Some(1));
+        assert_eq!(mask.chunk_index(&manifest, 1, 0), None);
+        assert!(sender.encrypt_file_chunk(b"x", 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_selection_indices_validated() {
+        let mut manifest = FileManifest::new(chunking::MIN_CHUNK_SIZE);
+        for name in ["a", "b"] {
+            manifest.add_file(name.into(), 10, [0; 32]);
+        }
+        assert!(AcceptanceMask::from_indices(&manifest, &[]).is_err());
+        assert!(AcceptanceMask::from_indices(&manifest, &[2]).is_err());
+
+        let both = AcceptanceMask::from_indices(&manifest, &[1, 0, 1]).unwrap();
+        assert!(both.is_all());
+        assert_eq!(both, AcceptanceMask::all(&manifest));
+
+        let none = AcceptanceMask::from_globs(&manifest, &["*.zip".to_string()]).unwrap();
+        assert!(none.is_empty());
+    }
+}
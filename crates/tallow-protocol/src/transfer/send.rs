@@ -6,15 +6,25 @@
 //! Supports streaming I/O for large files — files are read, compressed,
 //! and encrypted one chunk at a time to avoid loading entire files into memory.
 
-use crate::compression::{self, CompressionAlgorithm};
+use crate::compression::pipeline::AdaptiveCompressor;
+use crate::compression::skiplist::MAGIC_HEADER_LEN;
+use crate::compression::{self, CompressionAlgorithm, SkipList};
 use crate::transfer::chunking::{self, ChunkConfig};
 use crate::transfer::exclusion::ExclusionConfig;
-use crate::transfer::manifest::{FileManifest, TransferType};
-use crate::transfer::progress::TransferProgress;
-use crate::wire::Message;
+use crate::transfer::manifest::{FileEntry, FileManifest, SkippedFile, TransferType};
+use crate::transfer::progress::{CompressionStats, TransferProgress};
+use crate::transfer::resume::{ResumeState, SourceFingerprint};
+use crate::transfer::selection::AcceptanceMask;
+use crate::transfer::sparse::{self, Hole};
+use crate::transfer::stream::StreamChunker;
+use crate::wire::{FeatureSet, Message};
 use crate::{ProtocolError, Result};
 use std::path::{Path, PathBuf};
-use tokio::io::AsyncReadExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use tallow_crypto::hash::HashAlgorithm;
+use tallow_crypto::sig::HybridSigner;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncSeekExt};
 
 /// Send pipeline for file transfers
 pub struct SendPipeline {
@@ -32,6 +42,34 @@ pub struct SendPipeline {
     session_key: [u8; 32],
     /// File exclusion configuration for directory scanning
     exclusion: ExclusionConfig,
+    /// On-disk source path for each manifest entry (parallel to `manifest.files`)
+    source_paths: Vec<PathBuf>,
+    /// Fingerprint of each source taken when the manifest was built
+    /// (parallel to `source_paths`)
+    source_fingerprints: Vec<SourceFingerprint>,
+    /// Holes found in each source file (parallel to `source_paths`)
+    source_holes: Vec<Vec<Hole>>,
+    /// Pre/post-compression sizes, updated as chunks are encrypted
+    compression_log: Mutex<CompressionLog>,
+    /// Per-chunk ratio tracking, used when the manifest enables adaptive compression
+    adaptive: Mutex<AdaptiveCompressor>,
+    /// File types stored without trying to compress them
+    skiplist: SkipList,
+    /// The file being read is on `skiplist`
+    skip_current_file: AtomicBool,
+    /// Files the receiver accepted (`None` = all of them)
+    accepted: Option<AcceptanceMask>,
+    /// Append a CRC32C trailer to every sealed chunk
+    chunk_checksum: bool,
+}
+
+/// Running compression totals for a send
+#[derive(Debug, Default)]
+struct CompressionLog {
+    /// Whole transfer
+    total: CompressionStats,
+    /// One entry per file reader opened, in order
+    files: Vec<(PathBuf, CompressionStats)>,
 }
 
 impl Drop for SendPipeline {
@@ -70,11 +108,17 @@ pub struct FileChunkReader {
     chunk_size: usize,
     buffer: Vec<u8>,
     done: bool,
+    /// Holes skipped over rather than read, in file order
+    holes: Vec<Hole>,
+    /// First hole not yet skipped
+    next_hole: usize,
+    /// File offset of the next byte read
+    pos: u64,
 }
 
 impl FileChunkReader {
     /// Open a file for streaming chunk reads
-    async fn open(path: &Path, chunk_size: usize) -> Result<Self> {
+    pub(crate) async fn open(path: &Path, chunk_size: usize) -> Result<Self> {
         let file = tokio::fs::File::open(path).await.map_err(|e| {
             ProtocolError::TransferFailed(format!("open {}: {}", path.display(), e))
         })?;
@@ -83,9 +127,28 @@ impl FileChunkReader {
             chunk_size,
             buffer: vec![0u8; chunk_size],
             done: false,
+            holes: Vec::new(),
+            next_hole: 0,
+            pos: 0,
         })
     }
 
+    /// Read only the bytes outside `holes`, as chunks of a sparse file
+    pub(crate) fn skip_holes(mut self, holes: &[Hole]) -> Self {
+        self.holes = holes.to_vec();
+        self
+    }
+
+    /// Up to `len` bytes from the start of what's left, without consuming them
+    pub(crate) async fn peek(&mut self, len: usize) -> Result<Vec<u8>> {
+        let buf = self
+            .file
+            .fill_buf()
+            .await
+            .map_err(|e| ProtocolError::TransferFailed(format!("read chunk: {}", e)))?;
+        Ok(buf[..buf.len().min(len)].to_vec())
+    }
+
     /// Read the next chunk of raw data from the file.
     ///
     /// Returns `None` when the file is fully read.
@@ -97,9 +160,26 @@ impl FileChunkReader {
         let mut total_read = 0;
         // Read exactly chunk_size bytes (or less at EOF)
         while total_read < self.chunk_size {
+            let mut end = self.chunk_size;
+            if let Some(hole) = self.holes.get(self.next_hole) {
+                if self.pos >= hole.offset {
+                    self.pos = hole.end();
+                    self.next_hole += 1;
+                    self.file
+                        .seek(std::io::SeekFrom::Start(self.pos))
+                        .await
+                        .map_err(|e| ProtocolError::TransferFailed(format!("seek: {}", e)))?;
+                    continue;
+                }
+                // Stop at the start of the hole
+                let before_hole = hole.offset - self.pos;
+                if before_hole < (end - total_read) as u64 {
+                    end = total_read + before_hole as usize;
+                }
+            }
             let n = self
                 .file
-                .read(&mut self.buffer[total_read..])
+                .read(&mut self.buffer[total_read..end])
                 .await
                 .map_err(|e| ProtocolError::TransferFailed(format!("read chunk: {}", e)))?;
             if n == 0 {
@@ -107,6 +187,7 @@ impl FileChunkReader {
                 break;
             }
             total_read += n;
+            self.pos += n as u64;
         }
 
         if total_read == 0 {
@@ -128,6 +209,15 @@ impl SendPipeline {
             progress: None,
             session_key,
             exclusion: ExclusionConfig::default(),
+            source_paths: Vec::new(),
+            source_fingerprints: Vec::new(),
+            source_holes: Vec::new(),
+            compression_log: Mutex::new(CompressionLog::default()),
+            adaptive: Mutex::new(AdaptiveCompressor::new(CompressionAlgorithm::Zstd)),
+            skiplist: SkipList::default(),
+            skip_current_file: AtomicBool::new(false),
+            accepted: None,
+            chunk_checksum: false,
         }
     }
 
@@ -140,6 +230,35 @@ impl SendPipeline {
     /// Set compression algorithm
     pub fn with_compression(mut self, algo: CompressionAlgorithm) -> Self {
         self.compression = algo;
+        self.adaptive = Mutex::new(AdaptiveCompressor::new(algo));
+        self
+    }
+
+    /// Set the file types whose chunks are stored without compression
+    ///
+    /// Only applies once adaptive compression is negotiated: its framing is
+    /// what lets a chunk go out uncompressed in a compressed transfer.
+    pub fn with_compression_skiplist(mut self, skiplist: SkipList) -> Self {
+        self.skiplist = skiplist;
+        self
+    }
+
+    /// Append a CRC32C of each sealed chunk to its payload
+    ///
+    /// See [`chunking::append_chunk_checksum`]. Only enable this when
+    /// [`FeatureSet::CHUNK_CHECKSUM`] was negotiated.
+    pub fn with_chunk_checksum(mut self, enabled: bool) -> Self {
+        self.chunk_checksum = enabled;
+        self
+    }
+
+    /// Hash file contents with `algorithm` instead of BLAKE3
+    ///
+    /// Set before any `prepare*` call, since files are hashed as they are
+    /// scanned. Stream transfers keep BLAKE3 in their trailer. Check the
+    /// peer supports it with [`check_hash_algorithm`](Self::check_hash_algorithm).
+    pub fn with_hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.manifest.hash_algorithm = algorithm;
         self
     }
 
@@ -160,9 +279,95 @@ impl SendPipeline {
         self.session_key = key;
     }
 
+    /// Restrict compression to what both peers support.
+    ///
+    /// Falls back to zstd, then to no compression, when the configured
+    /// algorithm is not in `negotiated`, and enables adaptive compression
+    /// when both peers support it. With [`FeatureSet::PER_CHUNK_ALGORITHM`]
+    /// each adaptive chunk also gets its own algorithm, which needs no
+    /// manifest change. Returns `true` if the algorithm or adaptive mode
+    /// changed, in which case offers built by a `prepare*` call are stale
+    /// and must be rebuilt with [`SendPipeline::file_offer`].
+    pub fn restrict_compression(&mut self, negotiated: FeatureSet) -> bool {
+        let selected = negotiated.select_compression(self.compression);
+        let adaptive = selected != CompressionAlgorithm::None
+            && negotiated.contains(FeatureSet::ADAPTIVE_COMPRESSION);
+
+        let mut compressor = AdaptiveCompressor::new(selected);
+        if adaptive && negotiated.contains(FeatureSet::PER_CHUNK_ALGORITHM) {
+            let choices: Vec<CompressionAlgorithm> =
+                [CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4]
+                    .into_iter()
+                    .filter(|a| negotiated.supports_compression(*a))
+                    .collect();
+            compressor = compressor.with_chunk_choice(&choices);
+        }
+        self.adaptive = Mutex::new(compressor);
+
+        if selected == self.compression && adaptive == self.manifest.adaptive_compression {
+            return false;
+        }
+        self.compression = selected;
+        self.manifest.adaptive_compression = adaptive;
+        if self.manifest.compression.is_some() {
+            self.manifest.compression = Some(self.compression_name());
+        }
+        true
+    }
+
+    /// Send sparse files as their data plus a hole list, if the peer can
+    ///
+    /// Without [`FeatureSet::SPARSE_FILES`] holes go out as ordinary zero
+    /// bytes. Returns `true` if any file became sparse, in which case
+    /// offers built by [`prepare`](Self::prepare) are stale and must be
+    /// rebuilt with [`SendPipeline::file_offer`].
+    pub fn enable_sparse(&mut self, negotiated: FeatureSet) -> Result<bool> {
+        if !negotiated.contains(FeatureSet::SPARSE_FILES)
+            || self.manifest.transfer_type != TransferType::Files
+            || !self.manifest.holes.is_empty()
+        {
+            return Ok(false);
+        }
+        let mut changed = false;
+        for (index, holes) in self.source_holes.iter().enumerate() {
+            if !holes.is_empty() {
+                self.manifest.set_holes(index, holes.clone())?;
+                changed = true;
+            }
+        }
+        if changed {
+            self.manifest.finalize()?;
+            self.progress = Some(TransferProgress::new(self.manifest.data_size()));
+        }
+        Ok(changed)
+    }
+
+    /// Fail unless the peer can verify the manifest's hash algorithm
+    ///
+    /// There is no fallback: the files were hashed when prepared, and a
+    /// non-default algorithm is usually required by whoever receives them.
+    pub fn check_hash_algorithm(&self, negotiated: FeatureSet) -> Result<()> {
+        match self.manifest.hash_algorithm {
+            HashAlgorithm::Blake3 => Ok(()),
+            HashAlgorithm::Sha3_256 if negotiated.contains(FeatureSet::HASH_SHA3) => Ok(()),
+            algorithm => Err(ProtocolError::TransferFailed(format!(
+                "peer does not support {} file hashes",
+                algorithm
+            ))),
+        }
+    }
+
+    /// Build the FileOffer message for the current manifest
+    pub fn file_offer(&self) -> Result<Message> {
+        Ok(Message::FileOffer {
+            transfer_id: self.transfer_id,
+            manifest: self.manifest.to_bytes()?,
+        })
+    }
+
     /// Prepare files for transfer — scan, hash, build manifest
     ///
-    /// Uses streaming BLAKE3 hashing so large files are not loaded into memory.
+    /// Hashes are streamed so large files are not loaded into memory.
     ///
     /// # Arguments
     ///
@@ -177,16 +382,36 @@ impl SendPipeline {
         for path in paths {
             self.scan_path(path).await?;
         }
+        if self.manifest.files.is_empty() {
+            if let Some(first) = self.manifest.skipped.first() {
+                return Err(ProtocolError::TransferFailed(format!(
+                    "no files could be read ({})",
+                    first
+                )));
+            }
+        }
+        self.manifest.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Sort for a reproducible manifest, keeping source paths in step
+        let order = self.manifest.sort_files();
+        self.source_paths = order
+            .iter()
+            .map(|&i| std::mem::take(&mut self.source_paths[i]))
+            .collect();
+        self.source_holes = order
+            .iter()
+            .map(|&i| std::mem::take(&mut self.source_holes[i]))
+            .collect();
+
+        self.source_fingerprints = self
+            .source_paths
+            .iter()
+            .map(|p| SourceFingerprint::capture(p))
+            .collect::<Result<_>>()?;
 
         self.manifest.finalize()?;
         self.manifest.per_chunk_compression = true;
-        self.manifest.compression = Some(match self.compression {
-            CompressionAlgorithm::Zstd => "zstd".to_string(),
-            CompressionAlgorithm::Lz4 => "lz4".to_string(),
-            CompressionAlgorithm::Brotli => "brotli".to_string(),
-            CompressionAlgorithm::Lzma => "lzma".to_string(),
-            CompressionAlgorithm::None => "none".to_string(),
-        });
+        self.manifest.compression = Some(self.compression_name());
         self.progress = Some(TransferProgress::new(self.manifest.total_size));
 
         // Create FileOffer message
@@ -199,42 +424,49 @@ impl SendPipeline {
         Ok(messages)
     }
 
-    /// Compute BLAKE3 hash of a file using streaming reads (O(chunk_size) memory)
-    async fn hash_file_streaming(path: &Path, chunk_size: usize) -> Result<[u8; 32]> {
-        let file = tokio::fs::File::open(path).await.map_err(|e| {
-            ProtocolError::TransferFailed(format!("open for hash {}: {}", path.display(), e))
-        })?;
+    /// Hash a file with `algorithm` using streaming reads (O(chunk_size) memory)
+    async fn hash_file_streaming(
+        path: &Path,
+        chunk_size: usize,
+        algorithm: HashAlgorithm,
+    ) -> std::io::Result<[u8; 32]> {
+        let file = tokio::fs::File::open(path).await?;
         let mut reader = tokio::io::BufReader::with_capacity(chunk_size, file);
-        let mut hasher = blake3::Hasher::new();
+        let mut hasher = algorithm.hasher();
         let mut buf = vec![0u8; chunk_size];
 
         loop {
-            let n = reader.read(&mut buf).await.map_err(|e| {
-                ProtocolError::TransferFailed(format!("read for hash {}: {}", path.display(), e))
-            })?;
+            let n = reader.read(&mut buf).await?;
             if n == 0 {
                 break;
             }
             hasher.update(&buf[..n]);
         }
 
-        Ok(hasher.finalize().into())
+        Ok(hasher.finalize())
     }
 
     /// Scan a path and add it to the manifest (streaming hash — no full file load)
+    ///
+    /// A file that can't be read is recorded in the manifest's `skipped`
+    /// list instead of failing the transfer.
     async fn scan_path(&mut self, path: &Path) -> Result<()> {
-        let metadata = tokio::fs::metadata(path).await.map_err(|e| {
-            ProtocolError::TransferFailed(format!("cannot read {}: {}", path.display(), e))
-        })?;
-
-        if metadata.is_file() {
-            let hash = Self::hash_file_streaming(path, self.chunk_config.size).await?;
-            let relative_path = path
-                .file_name()
+        let name = || {
+            path.file_name()
                 .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("unnamed"));
+                .unwrap_or_else(|| PathBuf::from("unnamed"))
+        };
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.skip(name(), &e);
+                return Ok(());
+            }
+        };
 
-            self.manifest.add_file(relative_path, metadata.len(), hash);
+        if metadata.is_file() {
+            self.scan_file(name(), path.to_path_buf(), metadata.len())
+                .await;
         } else if metadata.is_dir() {
             self.scan_directory(path, path).await?;
         }
@@ -242,21 +474,56 @@ impl SendPipeline {
         Ok(())
     }
 
+    /// Hash a file and add it to the manifest, or skip it if it can't be read
+    async fn scan_file(&mut self, relative: PathBuf, path: PathBuf, size: u64) {
+        match Self::hash_file_streaming(&path, self.chunk_config.size, self.manifest.hash_algorithm)
+            .await
+        {
+            Ok(hash) => self.add_source(relative, path, size, hash),
+            Err(e) => self.skip(relative, &e),
+        }
+    }
+
+    /// Record a file left out of the transfer
+    fn skip(&mut self, relative: PathBuf, err: &std::io::Error) {
+        tracing::warn!("skipping {}: {}", relative.display(), err);
+        self.manifest.skipped.push(SkippedFile {
+            path: relative,
+            reason: err.to_string(),
+        });
+    }
+
+    /// Add a scanned file to the manifest, noting any holes in it
+    fn add_source(&mut self, relative: PathBuf, path: PathBuf, size: u64, hash: [u8; 32]) {
+        self.manifest.add_file(relative, size, hash);
+        self.source_holes.push(Self::find_holes(&path, size));
+        self.source_paths.push(path);
+    }
+
+    /// Holes in a source file; none if they can't be determined
+    fn find_holes(path: &Path, size: u64) -> Vec<Hole> {
+        std::fs::File::open(path)
+            .and_then(|file| sparse::find_holes(&file, size))
+            .unwrap_or_else(|e| {
+                tracing::debug!("hole detection failed for {}: {}", path.display(), e);
+                Vec::new()
+            })
+    }
+
     /// Recursively scan a directory, respecting exclusion rules if configured
     async fn scan_directory(&mut self, base: &Path, dir: &Path) -> Result<()> {
         // Use exclusion-aware walker for the root directory scan
         if self.exclusion.is_active() && dir == base {
             let files = self.exclusion.walk_directory(base)?;
             for file_path in files {
-                let metadata = tokio::fs::metadata(&file_path).await.map_err(|e| {
-                    ProtocolError::TransferFailed(format!("stat {}: {}", file_path.display(), e))
-                })?;
-                let hash = Self::hash_file_streaming(&file_path, self.chunk_config.size).await?;
                 let relative = file_path
                     .strip_prefix(base)
                     .unwrap_or(&file_path)
                     .to_path_buf();
-                self.manifest.add_file(relative, metadata.len(), hash);
+                match tokio::fs::metadata(&file_path).await {
+                    Ok(metadata) => self.scan_file(relative, file_path, metadata.len()).await,
+                    Err(e) => self.skip(relative, &e),
+                }
             }
             return Ok(());
         }
@@ -278,14 +545,11 @@ impl SendPipeline {
                 .map_err(|e| ProtocolError::TransferFailed(format!("file_type: {}", e)))?;
 
             if file_type.is_file() {
-                let metadata = entry
-                    .metadata()
-                    .await
-                    .map_err(|e| ProtocolError::TransferFailed(format!("stat: {}", e)))?;
-                let hash = Self::hash_file_streaming(&path, self.chunk_config.size).await?;
                 let relative = path.strip_prefix(base).unwrap_or(&path).to_path_buf();
-
-                self.manifest.add_file(relative, metadata.len(), hash);
+                match entry.metadata().await {
+                    Ok(metadata) => self.scan_file(relative, path, metadata.len()).await,
+                    Err(e) => self.skip(relative, &e),
+                }
             } else if file_type.is_dir() {
                 Box::pin(self.scan_directory(base, &path)).await?;
             }
@@ -297,9 +561,37 @@ impl SendPipeline {
     /// Open a file for streaming chunk reads.
     ///
     /// Use with `encrypt_chunk()` to process files without loading them
-    /// entirely into memory.
+    /// entirely into memory. Chunks encrypted until the next reader is
+    /// opened count towards this file in [`SendPipeline::file_compression_stats`],
+    /// and are stored uncompressed if the file is on the compression skip-list.
+    /// A source file sent sparse is read around its holes.
     pub async fn open_file_reader(&self, file_path: &Path) -> Result<FileChunkReader> {
-        FileChunkReader::open(file_path, self.chunk_config.size).await
+        let holes = match self.source_paths.iter().position(|p| p == file_path) {
+            Some(index) => self.manifest.holes(index),
+            None => &[],
+        };
+        let mut reader = FileChunkReader::open(file_path, self.chunk_config.size)
+            .await?
+            .skip_holes(holes);
+        let skip = if self.manifest.adaptive_compression {
+            let header = reader.peek(MAGIC_HEADER_LEN).await?;
+            let kind = self.skiplist.check(Some(file_path), &header);
+            if let Some(kind) = kind {
+                tracing::debug!(
+                    "Storing {} uncompressed ({} data)",
+                    file_path.display(),
+                    kind
+                );
+            }
+            kind.is_some()
+        } else {
+            false
+        };
+        self.skip_current_file.store(skip, Ordering::Relaxed);
+        self.log()
+            .files
+            .push((file_path.to_path_buf(), CompressionStats::default()));
+        Ok(reader)
     }
 
     /// Compress and encrypt a single raw chunk of file data.
@@ -320,25 +612,131 @@ impl SendPipeline {
         total_chunks: u64,
         is_last: bool,
     ) -> Result<Message> {
+        Ok(Message::Chunk {
+            transfer_id: self.transfer_id,
+            index: global_index,
+            total: if is_last { Some(total_chunks) } else { None },
+            data: self.seal_chunk(raw_data, global_index)?,
+        })
+    }
+
+    /// Compress and encrypt chunk `index` of manifest file `file_id`.
+    ///
+    /// Produces a `FileChunk` so chunks of several files can interleave on
+    /// one connection; see [`crate::transfer::multiplex`]. The chunk is
+    /// encrypted under its transfer-wide index, exactly as
+    /// [`SendPipeline::encrypt_chunk`] would.
+    pub fn encrypt_file_chunk(&self, raw_data: &[u8], file_id: u32, index: u64) -> Result<Message> {
+        let global_index = self.chunk_index(file_id, index).ok_or_else(|| {
+            ProtocolError::TransferFailed(format!(
+                "chunk {} of file {} is not part of this transfer",
+                index, file_id
+            ))
+        })?;
+        Ok(Message::FileChunk {
+            transfer_id: self.transfer_id,
+            file_id,
+            index,
+            data: self.seal_chunk(raw_data, global_index)?,
+        })
+    }
+
+    /// Send only the files the receiver accepted in its `FileSelection`
+    ///
+    /// Chunks are then numbered over the accepted files alone, for both
+    /// [`chunk_accepted`](Self::chunk_accepted) and
+    /// [`encrypt_file_chunk`](Self::encrypt_file_chunk).
+    pub fn accept_files(&mut self, mask: AcceptanceMask) {
+        self.accepted = Some(mask);
+    }
+
+    /// The files the receiver accepted, all of them unless narrowed by
+    /// [`accept_files`](Self::accept_files)
+    pub fn acceptance(&self) -> AcceptanceMask {
+        self.accepted
+            .clone()
+            .unwrap_or_else(|| AcceptanceMask::all(&self.manifest))
+    }
+
+    /// Transfer-wide index of chunk `index` of manifest file `file_id`
+    fn chunk_index(&self, file_id: u32, index: u64) -> Option<u64> {
+        match self.accepted {
+            Some(ref mask) => mask.chunk_index(&self.manifest, file_id, index),
+            None => self.manifest.global_chunk_index(file_id, index),
+        }
+    }
+
+    /// Record a `CumulativeAck`: the receiver holds the first `received` chunks
+    ///
+    /// Moves the progress tracker's confirmed bytes forward; see
+    /// [`TransferProgress::confirm`]. Fails if the receiver claims more
+    /// chunks than the transfer has.
+    pub fn process_cumulative_ack(&mut self, received: u64) -> Result<Option<&TransferProgress>> {
+        let mask = self.acceptance();
+        let total = mask.total_chunks(&self.manifest);
+        if received > total {
+            return Err(ProtocolError::TransferFailed(format!(
+                "peer acknowledged {} chunks of {}",
+                received, total
+            )));
+        }
+        let confirmed = mask.bytes_through(&self.manifest, received);
+        if let Some(ref mut progress) = self.progress {
+            progress.confirm(confirmed);
+        }
+        Ok(self.progress.as_ref())
+    }
+
+    /// Chunk every accepted file in order, reading nothing else
+    ///
+    /// Loads all chunks into memory; a streaming sender walks
+    /// [`acceptance`](Self::acceptance) with `open_file_reader()` instead.
+    pub async fn chunk_accepted(&self) -> Result<Vec<Message>> {
+        let mask = self.acceptance();
+        let total = mask.total_chunks(&self.manifest);
+        let mut messages = Vec::new();
+        for file_id in mask.indices() {
+            let path = self.source_paths.get(file_id as usize).ok_or_else(|| {
+                ProtocolError::TransferFailed(format!("file {} has no source path", file_id))
+            })?;
+            let mut reader = self.open_file_reader(path).await?;
+            while let Some(raw) = reader.next_chunk().await? {
+                let index = messages.len() as u64;
+                messages.push(self.encrypt_chunk(&raw, index, total, index + 1 == total)?);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Compress and encrypt one chunk under its transfer-wide index
+    fn seal_chunk(&self, raw_data: &[u8], global_index: u64) -> Result<Vec<u8>> {
         // Compress this chunk independently
-        let compressed = compression::pipeline::compress(raw_data, self.compression)?;
+        let compressed = if self.manifest.adaptive_compression {
+            let mut adaptive = self.adaptive.lock().unwrap_or_else(PoisonError::into_inner);
+            if self.skip_current_file.load(Ordering::Relaxed) {
+                adaptive.store_chunk(raw_data)
+            } else {
+                adaptive.compress_chunk(raw_data)?
+            }
+        } else {
+            compression::pipeline::compress(raw_data, self.compression)?
+        };
+        self.record_compression(raw_data.len() as u64, compressed.len() as u64);
 
         // Build AAD and nonce
         let aad = chunking::build_chunk_aad(&self.transfer_id, global_index);
         let nonce = chunking::build_chunk_nonce(global_index);
 
         // Encrypt with AES-256-GCM
-        let encrypted =
+        let sealed =
             tallow_crypto::symmetric::aes_encrypt(&self.session_key, &nonce, &compressed, &aad)
                 .map_err(|e| {
                     ProtocolError::TransferFailed(format!("chunk encryption failed: {}", e))
                 })?;
-
-        Ok(Message::Chunk {
-            transfer_id: self.transfer_id,
-            index: global_index,
-            total: if is_last { Some(total_chunks) } else { None },
-            data: encrypted,
+        Ok(if self.chunk_checksum {
+            chunking::append_chunk_checksum(sealed)
+        } else {
+            sealed
         })
     }
 
@@ -362,6 +760,65 @@ impl SendPipeline {
         &self.manifest
     }
 
+    /// Get the on-disk source path of each manifest entry, in manifest order
+    ///
+    /// Empty for text transfers, which have no backing file.
+    pub fn source_paths(&self) -> &[PathBuf] {
+        &self.source_paths
+    }
+
+    /// Check a saved resume state against the current source files
+    ///
+    /// Returns the state unchanged if every source still matches its
+    /// fingerprint, or a fresh state (restart from chunk 0) if any changed.
+    pub fn validate_resume(&self, state: ResumeState) -> Result<ResumeState> {
+        state.revalidate(&self.source_paths)
+    }
+
+    /// Resume state reported by the receiver in a `ResumeInfo` message
+    ///
+    /// After a reconnect or a mid-transfer transport migration, the sender
+    /// continues with [`ResumeState::pending_chunks`] instead of where it
+    /// stopped writing. Fails if the message belongs to another transfer
+    /// or manifest, or names a chunk past the end. If a source file changed
+    /// since [`prepare`](Self::prepare), the chunks the receiver holds are
+    /// stale and the returned state starts over from chunk 0.
+    pub fn resume_from(&self, info: &Message) -> Result<ResumeState> {
+        let Message::ResumeInfo {
+            transfer_id,
+            manifest_hash,
+            verified_chunks,
+        } = info
+        else {
+            return Err(ProtocolError::InvalidMessage(
+                "expected ResumeInfo".to_string(),
+            ));
+        };
+        let expected_hash = self.manifest.manifest_hash.unwrap_or([0u8; 32]);
+        if *transfer_id != self.transfer_id || *manifest_hash != expected_hash {
+            return Err(ProtocolError::TransferFailed(
+                "resume info is for a different transfer".to_string(),
+            ));
+        }
+
+        let total = self.manifest.total_chunks;
+        let chunk_size = self.chunk_config.size as u64;
+        let data_size = self.manifest.data_size();
+        let mut state = ResumeState::new(self.transfer_id, total, expected_hash);
+        for &index in verified_chunks {
+            if index >= total {
+                return Err(ProtocolError::TransferFailed(format!(
+                    "resume info names chunk {} of {}",
+                    index, total
+                )));
+            }
+            let len = data_size.saturating_sub(index * chunk_size).min(chunk_size);
+            state.mark_verified(index, len);
+        }
+        state.sources = self.source_fingerprints.clone();
+        self.validate_resume(state)
+    }
+
     /// Get the transfer ID
     pub fn transfer_id(&self) -> &[u8; 16] {
         &self.transfer_id
@@ -372,12 +829,17 @@ impl SendPipeline {
         self.chunk_config.size
     }
 
+    /// Session key used for chunk encryption
+    pub(crate) fn session_key(&self) -> &[u8; 32] {
+        &self.session_key
+    }
+
     /// Prepare a text payload for transfer as a virtual file.
     ///
     /// The text is treated as a single file named `_tallow_text_` in the manifest.
     /// The receiver detects this special name and prints to stdout instead of disk.
     pub async fn prepare_text(&mut self, text: &[u8]) -> Result<Vec<Message>> {
-        let hash: [u8; 32] = blake3::hash(text).into();
+        let hash = self.manifest.hash_algorithm.digest(text);
 
         self.manifest.transfer_type = TransferType::Text;
         self.manifest
@@ -385,13 +847,7 @@ impl SendPipeline {
 
         self.manifest.finalize()?;
         self.manifest.per_chunk_compression = true;
-        self.manifest.compression = Some(match self.compression {
-            CompressionAlgorithm::Zstd => "zstd".to_string(),
-            CompressionAlgorithm::Lz4 => "lz4".to_string(),
-            CompressionAlgorithm::Brotli => "brotli".to_string(),
-            CompressionAlgorithm::Lzma => "lzma".to_string(),
-            CompressionAlgorithm::None => "none".to_string(),
-        });
+        self.manifest.compression = Some(self.compression_name());
         self.progress = Some(TransferProgress::new(self.manifest.total_size));
 
         let manifest_bytes = self.manifest.to_bytes()?;
@@ -401,6 +857,90 @@ impl SendPipeline {
         }])
     }
 
+    /// Prepare an unknown-length stream (e.g. stdin) for transfer.
+    ///
+    /// The manifest carries a single entry named `name` with no size or
+    /// hash; those are sent in the signed trailer once the stream ends.
+    /// Use [`SendPipeline::stream_chunks`] to produce the chunks.
+    pub fn prepare_stream(&mut self, name: &str) -> Result<Vec<Message>> {
+        self.manifest.transfer_type = TransferType::Stream;
+        self.manifest.hash_algorithm = HashAlgorithm::Blake3;
+        self.manifest.files.push(FileEntry {
+            path: PathBuf::from(name),
+            size: 0,
+            hash: [0u8; 32],
+            chunk_count: 0,
+        });
+
+        self.manifest.finalize()?;
+        self.manifest.per_chunk_compression = true;
+        self.manifest.compression = Some(self.compression_name());
+
+        let manifest_bytes = self.manifest.to_bytes()?;
+        Ok(vec![Message::FileOffer {
+            transfer_id: self.transfer_id,
+            manifest: manifest_bytes,
+        }])
+    }
+
+    /// Chunk an `AsyncRead` source of unknown length as data arrives.
+    ///
+    /// The returned chunker yields `Chunk` messages followed by a single
+    /// `StreamEnd` whose trailer is signed with `signer`.
+    pub fn stream_chunks<'a, R: AsyncRead + Unpin>(
+        &'a self,
+        reader: R,
+        signer: &'a HybridSigner,
+    ) -> StreamChunker<'a, R> {
+        StreamChunker::new(self, reader, signer)
+    }
+
+    /// Compression totals for the chunks encrypted so far
+    ///
+    /// `None` when compression is disabled for this transfer.
+    pub fn compression_stats(&self) -> Option<CompressionStats> {
+        if self.compression == CompressionAlgorithm::None {
+            return None;
+        }
+        Some(self.log().total)
+    }
+
+    /// Per-file compression totals, in the order the files were read
+    ///
+    /// Empty when compression is disabled or for text and stream transfers.
+    pub fn file_compression_stats(&self) -> Vec<(PathBuf, CompressionStats)> {
+        if self.compression == CompressionAlgorithm::None {
+            return Vec::new();
+        }
+        self.log().files.clone()
+    }
+
+    fn record_compression(&self, original: u64, compressed: u64) {
+        let mut log = self.log();
+        log.total.record(original, compressed);
+        if let Some((_, stats)) = log.files.last_mut() {
+            stats.record(original, compressed);
+        }
+    }
+
+    fn log(&self) -> std::sync::MutexGuard<'_, CompressionLog> {
+        // The log holds plain counters, so a poisoned lock is still usable
+        self.compression_log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wire identifier of the configured compression algorithm
+    fn compression_name(&self) -> String {
+        match self.compression {
+            CompressionAlgorithm::Zstd => "zstd".to_string(),
+            CompressionAlgorithm::Lz4 => "lz4".to_string(),
+            CompressionAlgorithm::Brotli => "brotli".to_string(),
+            CompressionAlgorithm::Lzma => "lzma".to_string(),
+            CompressionAlgorithm::None => "none".to_string(),
+        }
+    }
+
     /// Generate chunk messages for in-memory data (text or stdin).
     ///
     /// Uses per-chunk compression: each chunk is independently compressed
@@ -426,9 +966,511 @@ impl SendPipeline {
 
     /// Update progress and return current state
     pub fn update_progress(&mut self, bytes: u64) -> Option<&TransferProgress> {
+        let compression = self.compression_stats();
         if let Some(ref mut progress) = self.progress {
             progress.update(bytes);
+            progress.compression = compression;
         }
         self.progress.as_ref()
     }
+
+    /// Current progress, if a transfer has been prepared
+    pub fn progress(&self) -> Option<&TransferProgress> {
+        self.progress.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_compression_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("text.txt");
+        let noise = dir.path().join("noise.bin");
+        std::fs::write(&text, "tallow ".repeat(10_000)).unwrap();
+        let random: Vec<u8> = (0..70_000).map(|_| rand::random::<u8>()).collect();
+        std::fs::write(&noise, &random).unwrap();
+
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+        pipeline
+            .prepare(&[text.clone(), noise.clone()])
+            .await
+            .unwrap();
+
+        let mut index = 0;
+        for path in [&text, &noise] {
+            let mut reader = pipeline.open_file_reader(path).await.unwrap();
+            while let Some(chunk) = reader.next_chunk().await.unwrap() {
+                pipeline.encrypt_chunk(&chunk, index, 0, false).unwrap();
+                index += 1;
+            }
+        }
+
+        let files = pipeline.file_compression_stats();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, text);
+        assert_eq!(files[0].1.original_bytes, 70_000);
+        assert!(files[0].1.ratio() > 10.0);
+        assert_eq!(files[1].1.original_bytes, 70_000);
+        assert_eq!(files[1].1.bytes_saved(), 0);
+
+        let total = pipeline.compression_stats().unwrap();
+        assert_eq!(total.original_bytes, 140_000);
+        assert_eq!(
+            total.compressed_bytes,
+            files[0].1.compressed_bytes + files[1].1.compressed_bytes
+        );
+
+        let progress = pipeline.update_progress(140_000).unwrap();
+        assert_eq!(progress.compression, Some(total));
+    }
+
+    /// Write `files` under a fresh directory `root`, in the order given
+    fn write_tree(root: &Path, files: &[(&str, &[u8])]) {
+        for (name, data) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, data).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_same_tree_gives_identical_manifest_bytes() {
+        let files: [(&str, &[u8]); 5] = [
+            ("zeta.txt", b"z"),
+            ("docs/b.md", b"bb"),
+            ("docs/a.md", b"a"),
+            ("alpha/nested/deep.bin", &[7u8; 300]),
+            ("beta.txt", b"beta"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first/tree");
+        let second = dir.path().join("second/tree");
+        write_tree(&first, &files);
+        // Created in the opposite order, so enumeration order may differ
+        let reversed: Vec<_> = files.iter().rev().copied().collect();
+        write_tree(&second, &reversed);
+
+        let mut manifests = Vec::new();
+        for root in [&first, &second] {
+            let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+            pipeline.prepare(&[root.clone()]).await.unwrap();
+            let sources: Vec<_> = pipeline
+                .source_paths()
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_path_buf())
+                .collect();
+            let paths: Vec<_> = pipeline
+                .manifest()
+                .files
+                .iter()
+                .map(|f| f.path.clone())
+                .collect();
+            // Source paths are reordered together with the manifest
+            assert_eq!(sources, paths);
+            manifests.push(pipeline.manifest().to_bytes().unwrap());
+        }
+        assert_eq!(manifests[0], manifests[1]);
+
+        let decoded = FileManifest::from_bytes(&manifests[0]).unwrap();
+        let names: Vec<_> = decoded.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            names,
+            [
+                "alpha/nested/deep.bin",
+                "beta.txt",
+                "docs/a.md",
+                "docs/b.md",
+                "zeta.txt"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_case_only_differences_sort_stably() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tree");
+        write_tree(
+            &root,
+            &[
+                ("notes.txt", b"lower"),
+                ("Notes.txt", b"upper"),
+                ("NOTES.txt", b"caps"),
+            ],
+        );
+
+        let mut orders = Vec::new();
+        for _ in 0..2 {
+            let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+            pipeline.prepare(&[root.clone()]).await.unwrap();
+            let names: Vec<_> = pipeline
+                .manifest()
+                .files
+                .iter()
+                .map(|f| f.path.clone())
+                .collect();
+            orders.push(names);
+        }
+        assert_eq!(orders[0], orders[1]);
+        assert_eq!(
+            orders[0],
+            ["NOTES.txt", "Notes.txt", "notes.txt"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_compression_disabled_reports_none() {
+        let pipeline =
+            SendPipeline::new([1u8; 16], [2u8; 32]).with_compression(CompressionAlgorithm::None);
+        pipeline.encrypt_chunk(b"hello", 0, 1, true).unwrap();
+        assert_eq!(pipeline.compression_stats(), None);
+        assert!(pipeline.file_compression_stats().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restrict_compression_to_negotiated() {
+        let mut pipeline =
+            SendPipeline::new([1u8; 16], [2u8; 32]).with_compression(CompressionAlgorithm::Brotli);
+        pipeline.prepare_text(b"hello").await.unwrap();
+
+        let negotiated = FeatureSet::local().difference(FeatureSet::COMPRESS_BROTLI);
+        assert!(pipeline.restrict_compression(negotiated));
+        assert_eq!(pipeline.manifest().compression.as_deref(), Some("zstd"));
+
+        match pipeline.file_offer().unwrap() {
+            Message::FileOffer { manifest, .. } => {
+                let decoded = FileManifest::from_bytes(&manifest).unwrap();
+                assert_eq!(decoded.compression.as_deref(), Some("zstd"));
+            }
+            other => panic!("Expected FileOffer, got {:?}", other),
+        }
+
+        // Already within the negotiated set
+        assert!(!pipeline.restrict_compression(negotiated));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_compression_only_when_negotiated() {
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+        pipeline.prepare_text(b"hello").await.unwrap();
+
+        assert!(!pipeline.restrict_compression(FeatureSet::legacy()));
+        assert!(!pipeline.manifest().adaptive_compression);
+
+        assert!(pipeline.restrict_compression(FeatureSet::local()));
+        assert!(pipeline.manifest().adaptive_compression);
+
+        // Adaptive chunks carry a frame tag the receiver strips
+        let text = "tallow ".repeat(1000);
+        match pipeline.encrypt_chunk(text.as_bytes(), 0, 1, true).unwrap() {
+            Message::Chunk { data, .. } => {
+                let nonce = chunking::build_chunk_nonce(0);
+                let aad = chunking::build_chunk_aad(&[1u8; 16], 0);
+                let framed =
+                    tallow_crypto::symmetric::aes_decrypt(&[2u8; 32], &nonce, &data, &aad).unwrap();
+                let plain =
+                    compression::pipeline::decompress_chunk(&framed, CompressionAlgorithm::Zstd)
+                        .unwrap();
+                assert_eq!(plain, text.as_bytes());
+            }
+            other => panic!("Expected Chunk, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skiplisted_files_stored_uncompressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = "tallow ".repeat(10_000);
+        let movie = dir.path().join("movie.mp4");
+        let notes = dir.path().join("notes.txt");
+        let parquet = dir.path().join("table.parquet");
+        for path in [&movie, &notes, &parquet] {
+            std::fs::write(path, &text).unwrap();
+        }
+
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]).with_compression_skiplist(
+            SkipList::default().with_overrides(&["parquet".to_string()], &[]),
+        );
+        pipeline
+            .prepare(&[movie.clone(), notes.clone(), parquet.clone()])
+            .await
+            .unwrap();
+        pipeline.restrict_compression(FeatureSet::local());
+
+        let mut index = 0;
+        for path in [&movie, &notes, &parquet] {
+            let mut reader = pipeline.open_file_reader(path).await.unwrap();
+            while let Some(chunk) = reader.next_chunk().await.unwrap() {
+                pipeline.encrypt_chunk(&chunk, index, 0, false).unwrap();
+                index += 1;
+            }
+        }
+
+        // Stored chunks only carry the frame tag; the peek consumed nothing
+        let files = pipeline.file_compression_stats();
+        assert_eq!(files[0].1.original_bytes, 70_000);
+        assert_eq!(files[0].1.bytes_saved(), 0);
+        assert!(files[1].1.ratio() > 10.0);
+        assert_eq!(files[2].1.original_bytes, 70_000);
+        assert_eq!(files[2].1.bytes_saved(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_file_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        write_tree(dir.path(), &[("a.txt", b"aaa"), ("c.txt", b"ccc")]);
+        let paths = [
+            dir.path().join("a.txt"),
+            dir.path().join("b.txt"),
+            dir.path().join("c.txt"),
+        ];
+
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+        pipeline.prepare(&paths).await.unwrap();
+
+        let manifest = pipeline.manifest();
+        let sent: Vec<_> = manifest.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(sent, [PathBuf::from("a.txt"), PathBuf::from("c.txt")]);
+        assert_eq!(manifest.total_size, 6);
+        assert_eq!(manifest.skipped.len(), 1);
+        assert_eq!(manifest.skipped[0].path, PathBuf::from("b.txt"));
+        let reason = std::fs::metadata(&paths[1]).unwrap_err().to_string();
+        assert_eq!(manifest.skipped[0].reason, reason);
+
+        // The receiver learns about it from the manifest it is offered
+        let decoded = FileManifest::from_bytes(&manifest.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.skipped, manifest.skipped);
+    }
+
+    #[tokio::test]
+    async fn test_nothing_readable_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+        assert!(pipeline
+            .prepare(&[dir.path().join("missing")])
+            .await
+            .is_err());
+    }
+
+    /// In-memory path end that drops every send while `lossy` is set
+    struct LossyPath {
+        tx: tokio::sync::mpsc::UnboundedSender<Vec<u8>>,
+        rx: tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>,
+        lossy: std::sync::Arc<AtomicBool>,
+    }
+
+    impl tallow_net::PeerChannel for LossyPath {
+        async fn send_message(&mut self, data: &[u8]) -> tallow_net::Result<()> {
+            if !self.lossy.load(Ordering::SeqCst) {
+                let _ = self.tx.send(data.to_vec());
+            }
+            Ok(())
+        }
+        async fn receive_message(&mut self, buf: &mut [u8]) -> tallow_net::Result<usize> {
+            let frame = self.rx.recv().await.ok_or_else(|| {
+                tallow_net::NetworkError::ConnectionFailed("path gone".to_string())
+            })?;
+            buf[..frame.len()].copy_from_slice(&frame);
+            Ok(frame.len())
+        }
+        async fn close(&mut self) {}
+        fn transport_description(&self) -> String {
+            "mock path".to_string()
+        }
+    }
+
+    fn lossy_pair() -> (LossyPath, LossyPath, std::sync::Arc<AtomicBool>) {
+        let (a_tx, b_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (b_tx, a_rx) = tokio::sync::mpsc::unbounded_channel();
+        let lossy = std::sync::Arc::new(AtomicBool::new(false));
+        let a = LossyPath {
+            tx: a_tx,
+            rx: a_rx,
+            lossy: lossy.clone(),
+        };
+        let b = LossyPath {
+            tx: b_tx,
+            rx: b_rx,
+            lossy: lossy.clone(),
+        };
+        (a, b, lossy)
+    }
+
+    async fn send_msg(channel: &mut impl tallow_net::PeerChannel, msg: &Message) {
+        let bytes = postcard::to_stdvec(msg).unwrap();
+        channel.send_message(&bytes).await.unwrap();
+    }
+
+    async fn recv_msg(channel: &mut impl tallow_net::PeerChannel) -> Message {
+        let mut buf = vec![0u8; 1 << 20];
+        let n = channel.receive_message(&mut buf).await.unwrap();
+        postcard::from_bytes(&buf[..n]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migration_continues_from_resume_point() {
+        use crate::transfer::ReceivePipeline;
+        use tallow_net::transport::{MigratingChannel, MigrationPolicy};
+
+        const CHUNKS: u64 = 12;
+        const LOSS_FROM: u64 = 4;
+        let size = chunking::MIN_CHUNK_SIZE;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("payload.bin");
+        let data: Vec<u8> = (0..CHUNKS as usize * size)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut pipeline =
+            SendPipeline::new([5u8; 16], [6u8; 32]).with_chunk_config(ChunkConfig::with_size(size));
+        let manifest = match &pipeline.prepare(&[path.clone()]).await.unwrap()[0] {
+            Message::FileOffer { manifest, .. } => manifest.clone(),
+            other => panic!("Expected FileOffer, got {:?}", other),
+        };
+        assert_eq!(pipeline.manifest().total_chunks, CHUNKS);
+        let mut chunks = Vec::new();
+        let mut reader = pipeline.open_file_reader(&path).await.unwrap();
+        while let Some(raw) = reader.next_chunk().await.unwrap() {
+            let index = chunks.len() as u64;
+            chunks.push(
+                pipeline
+                    .encrypt_chunk(&raw, index, CHUNKS, index + 1 == CHUNKS)
+                    .unwrap(),
+            );
+        }
+
+        // Direct path that turns lossy mid-transfer; the relay joins once
+        // the sender migrates
+        let (direct_tx, direct_rx, lossy) = lossy_pair();
+        let (relay_tx, relay_rx, _) = lossy_pair();
+        let (joined_tx, joined_rx) = tokio::sync::oneshot::channel::<()>();
+        let mut sender = MigratingChannel::new(direct_tx, async move {
+            let _ = joined_tx.send(());
+            Ok(relay_tx)
+        })
+        .with_policy(MigrationPolicy {
+            loss_threshold_pct: 50.0,
+            check_interval: 2,
+            sustained_checks: 2,
+        });
+        let mut receiver_channel = MigratingChannel::new(direct_rx, async move {
+            joined_rx.await.map_err(|_| {
+                tallow_net::NetworkError::ConnectionFailed("sender never migrated".to_string())
+            })?;
+            Ok(relay_rx)
+        })
+        .follow_peer();
+
+        let out = tempfile::tempdir().unwrap();
+        let receiving = async {
+            let mut receiver = ReceivePipeline::new([5u8; 16], out.path(), [6u8; 32]);
+            receiver.process_offer(&manifest).unwrap();
+            let mut arrivals = Vec::new();
+            while !receiver.resume_state().unwrap().is_complete() {
+                let msg = recv_msg(&mut receiver_channel).await;
+                if receiver_channel.take_migration() {
+                    let info = receiver.resume_state().unwrap().resume_info();
+                    send_msg(&mut receiver_channel, &info).await;
+                }
+                if let Message::Chunk {
+                    index, data, total, ..
+                } = msg
+                {
+                    arrivals.push(index);
+                    receiver.process_chunk(index, &data, total).unwrap();
+                }
+            }
+            let paths = receiver.finalize().await.unwrap();
+            (arrivals, std::fs::read(&paths[0]).unwrap())
+        };
+
+        let sending = async {
+            for (index, chunk) in chunks.iter().enumerate() {
+                if index as u64 == LOSS_FROM {
+                    lossy.store(true, Ordering::SeqCst);
+                }
+                let delivered = !lossy.load(Ordering::SeqCst);
+                send_msg(&mut sender, chunk).await;
+                // Stand-in for the ack feedback a real transfer gets
+                sender.record_delivery(delivered);
+            }
+            assert!(sender.finish_migration().await.unwrap());
+            assert!(sender.take_migration());
+
+            // Prompt the receiver for its resume state over the new path
+            send_msg(&mut sender, &Message::Ping).await;
+            let resume = pipeline.resume_from(&recv_msg(&mut sender).await).unwrap();
+            assert_eq!(resume.next_needed_chunk(), Some(LOSS_FROM));
+            let resent: Vec<u64> = resume.pending_chunks().collect();
+            for &index in &resent {
+                send_msg(&mut sender, &chunks[index as usize]).await;
+            }
+            resent
+        };
+
+        let ((arrivals, received), resent) = tokio::join!(receiving, sending);
+        assert_eq!(resent, (LOSS_FROM..CHUNKS).collect::<Vec<_>>());
+        // Every chunk arrived exactly once, and the file is intact
+        assert_eq!(arrivals, (0..CHUNKS).collect::<Vec<_>>());
+        assert_eq!(received, data);
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_restarts_when_source_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![1u8; 3 * chunking::MIN_CHUNK_SIZE]).unwrap();
+
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32])
+            .with_chunk_config(ChunkConfig::with_size(chunking::MIN_CHUNK_SIZE));
+        pipeline.prepare(&[path.clone()]).await.unwrap();
+        let mut state = ResumeState::new(
+            [1u8; 16],
+            3,
+            pipeline.manifest().manifest_hash.unwrap_or([0u8; 32]),
+        );
+        state.mark_verified(0, chunking::MIN_CHUNK_SIZE as u64);
+        let info = state.resume_info();
+
+        assert_eq!(
+            pipeline.resume_from(&info).unwrap().next_needed_chunk(),
+            Some(1)
+        );
+
+        let mut changed = vec![1u8; 3 * chunking::MIN_CHUNK_SIZE];
+        changed[chunking::MIN_CHUNK_SIZE * 2] = 9;
+        std::fs::write(&path, &changed).unwrap();
+        assert_eq!(
+            pipeline.resume_from(&info).unwrap().next_needed_chunk(),
+            Some(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_rejects_other_transfer() {
+        let mut pipeline = SendPipeline::new([1u8; 16], [2u8; 32]);
+        pipeline.prepare_text(b"hello").await.unwrap();
+
+        let mut state = ResumeState::new(
+            [9u8; 16],
+            1,
+            pipeline.manifest().manifest_hash.unwrap_or([0u8; 32]),
+        );
+        assert!(pipeline.resume_from(&state.resume_info()).is_err());
+        state.transfer_id = [1u8; 16];
+        state.mark_verified(0, 5);
+        assert!(pipeline
+            .resume_from(&state.resume_info())
+            .unwrap()
+            .is_complete());
+
+        state.mark_verified(7, 5);
+        assert!(pipeline.resume_from(&state.resume_info()).is_err());
+        assert!(pipeline.resume_from(&Message::Ping).is_err());
+    }
 }
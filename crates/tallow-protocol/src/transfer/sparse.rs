@@ -0,0 +1,449 @@
+//! Sparse file support
+//!
+//! A sparse file's holes read back as zeros but occupy no disk space. The
+//! sender finds them with `SEEK_DATA`/`SEEK_HOLE` and lists them in the
+//! manifest; only the bytes outside the holes (the file's *data stream*)
+//! are chunked and sent. The receiver writes each run of data at its real
+//! offset and punches the holes back out with `fallocate`, so the output
+//! is as sparse as the source.
+//!
+//! A file's BLAKE3 hash always covers its full contents, holes included,
+//! so a hole list that doesn't match the data fails verification like any
+//! other corruption.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use tallow_crypto::hash::algorithm::{HashAlgorithm, Hasher};
+
+/// A range of a file that reads as zeros and is not stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hole {
+    /// Offset of the first byte
+    pub offset: u64,
+    /// Length in bytes (never zero)
+    pub len: u64,
+}
+
+impl Hole {
+    /// Offset just past the hole
+    pub fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+}
+
+/// Zeros handed out for holes that must be hashed or written out
+static ZEROS: [u8; 64 * 1024] = [0u8; 64 * 1024];
+
+/// Bytes of a `size`-byte file that lie outside `holes`
+pub fn data_len(size: u64, holes: &[Hole]) -> u64 {
+    size - holes.iter().map(|h| h.len).sum::<u64>()
+}
+
+/// Check that `holes` are non-empty, sorted, disjoint and inside `size`
+pub fn validate(holes: &[Hole], size: u64) -> crate::Result<()> {
+    let mut end = 0u64;
+    for hole in holes {
+        let hole_end = hole.offset.checked_add(hole.len);
+        if hole.len == 0 || hole.offset < end || hole_end.is_none_or(|e| e > size) {
+            return Err(crate::ProtocolError::TransferFailed(format!(
+                "invalid hole at offset {} (length {})",
+                hole.offset, hole.len
+            )));
+        }
+        end = hole.end();
+    }
+    Ok(())
+}
+
+/// Holes in the first `size` bytes of `file`
+///
+/// Empty on filesystems that don't report holes, or where the whole file
+/// is data.
+#[cfg(target_os = "linux")]
+pub fn find_holes(file: &File, size: u64) -> io::Result<Vec<Hole>> {
+    use rustix::fs::{seek, SeekFrom as SparseSeek};
+    use rustix::io::Errno;
+
+    let mut holes = Vec::new();
+    let mut pos = 0;
+    while pos < size {
+        let data = match seek(file, SparseSeek::Data(pos)) {
+            Ok(data) => data.min(size),
+            // No data after `pos`: the rest of the file is one hole
+            Err(Errno::NXIO) => size,
+            Err(Errno::INVAL | Errno::OPNOTSUPP) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        if data > pos {
+            holes.push(Hole {
+                offset: pos,
+                len: data - pos,
+            });
+        }
+        if data >= size {
+            break;
+        }
+        pos = match seek(file, SparseSeek::Hole(data)) {
+            Ok(hole) => hole.min(size),
+            Err(Errno::NXIO) => size,
+            Err(e) => return Err(e.into()),
+        };
+    }
+    Ok(holes)
+}
+
+/// Holes in the first `size` bytes of `file`
+///
+/// Hole detection is only implemented on Linux; elsewhere every file is
+/// sent as plain data.
+#[cfg(not(target_os = "linux"))]
+pub fn find_holes(_file: &File, _size: u64) -> io::Result<Vec<Hole>> {
+    Ok(Vec::new())
+}
+
+/// Deallocate `hole` in `file`, leaving its size unchanged
+#[cfg(target_os = "linux")]
+fn punch_hole(file: &File, hole: &Hole) -> io::Result<()> {
+    use rustix::fs::{fallocate, FallocateFlags};
+
+    fallocate(
+        file,
+        FallocateFlags::PUNCH_HOLE | FallocateFlags::KEEP_SIZE,
+        hole.offset,
+        hole.len,
+    )
+    .map_err(io::Error::from)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn punch_hole(_file: &File, _hole: &Hole) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "hole punching is not supported on this platform",
+    ))
+}
+
+/// Where a piece of the data stream lands in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// A hole the stream skips over
+    Hole(Hole),
+    /// `range` of the bytes being placed, starting at file `offset`
+    Data { offset: u64, range: Range<usize> },
+}
+
+/// Position in a sparse file's data stream, mapped to file offsets
+#[derive(Debug, Clone)]
+struct DataCursor {
+    holes: Vec<Hole>,
+    /// File offset of the next data byte
+    pos: u64,
+    /// First hole not yet passed
+    next: usize,
+}
+
+impl DataCursor {
+    fn new(holes: &[Hole]) -> Self {
+        Self {
+            holes: holes.to_vec(),
+            pos: 0,
+            next: 0,
+        }
+    }
+
+    /// Lay the next `len` bytes of the data stream out in the file
+    ///
+    /// Holes reached along the way come out in order between the data
+    /// runs. Bytes past the end of the file land after it, so an overlong
+    /// stream shows up as a hash mismatch rather than being dropped.
+    fn place(&mut self, len: usize) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut placed = 0;
+        while placed < len {
+            if let Some(hole) = self.take_hole() {
+                segments.push(Segment::Hole(hole));
+                continue;
+            }
+            let room = match self.holes.get(self.next) {
+                Some(hole) => hole.offset - self.pos,
+                None => u64::MAX,
+            };
+            let run = room.min((len - placed) as u64) as usize;
+            segments.push(Segment::Data {
+                offset: self.pos,
+                range: placed..placed + run,
+            });
+            placed += run;
+            self.pos += run as u64;
+        }
+        segments
+    }
+
+    /// Holes between the last data byte and the end of the file
+    fn finish(&mut self) -> Vec<Hole> {
+        std::iter::from_fn(|| self.take_hole()).collect()
+    }
+
+    /// Pass the next hole if the cursor is at its start
+    fn take_hole(&mut self) -> Option<Hole> {
+        let hole = *self.holes.get(self.next)?;
+        if hole.offset != self.pos {
+            return None;
+        }
+        self.next += 1;
+        self.pos = hole.end();
+        Some(hole)
+    }
+}
+
+/// Hash of a file's full contents, fed only its data stream
+///
+/// Holes count as the zeros they read as. With no holes this is a plain
+/// hasher for the manifest's algorithm.
+#[derive(Debug, Clone)]
+pub struct ContentHasher {
+    hasher: Hasher,
+    cursor: DataCursor,
+}
+
+impl ContentHasher {
+    /// Hasher for a file with `holes`, hashed with `algorithm`
+    pub fn new(holes: &[Hole], algorithm: HashAlgorithm) -> Self {
+        Self {
+            hasher: algorithm.hasher(),
+            cursor: DataCursor::new(holes),
+        }
+    }
+
+    /// Add the next bytes of the data stream
+    pub fn update(&mut self, data: &[u8]) {
+        for segment in self.cursor.place(data.len()) {
+            match segment {
+                Segment::Hole(hole) => hash_zeros(&mut self.hasher, hole.len),
+                Segment::Data { range, .. } => {
+                    self.hasher.update(&data[range]);
+                }
+            }
+        }
+    }
+
+    /// Hash of the whole file, including any trailing holes
+    pub fn finalize(mut self) -> [u8; 32] {
+        for hole in self.cursor.finish() {
+            hash_zeros(&mut self.hasher, hole.len);
+        }
+        self.hasher.finalize().into()
+    }
+}
+
+fn hash_zeros(hasher: &mut Hasher, mut len: u64) {
+    while len > 0 {
+        let n = len.min(ZEROS.len() as u64) as usize;
+        hasher.update(&ZEROS[..n]);
+        len -= n as u64;
+    }
+}
+
+/// Sequential writer that places a data stream around a file's holes
+///
+/// Data runs are written at their file offsets and the holes are punched
+/// out when the file is finished. Where punching isn't supported the holes
+/// are filled with zeros, so the contents are right even if the file ends
+/// up dense.
+#[derive(Debug)]
+pub struct SparseFile {
+    file: File,
+    cursor: DataCursor,
+    holes: Vec<Hole>,
+}
+
+impl SparseFile {
+    /// Create (or reuse) `path` for a `size`-byte file with `holes`
+    pub fn create(path: &Path, size: u64, holes: &[Hole]) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.set_len(size)?;
+        Ok(Self {
+            file,
+            cursor: DataCursor::new(holes),
+            holes: holes.to_vec(),
+        })
+    }
+
+    /// Write the next bytes of the data stream
+    pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        for segment in self.cursor.place(data.len()) {
+            if let Segment::Data { offset, range } = segment {
+                self.file.seek(SeekFrom::Start(offset))?;
+                self.file.write_all(&data[range])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Punch out the holes and flush the file to disk
+    ///
+    /// Every hole is punched, not just the ones skipped over, so a reused
+    /// file keeps none of its old contents.
+    pub fn finish(mut self) -> io::Result<()> {
+        for hole in &self.holes {
+            if let Err(e) = punch_hole(&self.file, hole) {
+                tracing::debug!("punching hole at {} failed: {}", hole.offset, e);
+                self.file.seek(SeekFrom::Start(hole.offset))?;
+                write_zeros(&mut self.file, hole.len)?;
+            }
+        }
+        self.file.sync_all()
+    }
+}
+
+/// Write a data stream to `out` with its holes expanded to zeros
+///
+/// For outputs that can't be sparse, such as split volumes.
+#[derive(Debug)]
+pub struct ZeroFill<W> {
+    out: W,
+    cursor: DataCursor,
+}
+
+impl<W: Write> ZeroFill<W> {
+    /// Wrap `out` for a file with `holes`
+    pub fn new(out: W, holes: &[Hole]) -> Self {
+        Self {
+            out,
+            cursor: DataCursor::new(holes),
+        }
+    }
+
+    /// Write the next bytes of the data stream
+    pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        for segment in self.cursor.place(data.len()) {
+            match segment {
+                Segment::Hole(hole) => write_zeros(&mut self.out, hole.len)?,
+                Segment::Data { range, .. } => self.out.write_all(&data[range])?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write any trailing holes and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        for hole in self.cursor.finish() {
+            write_zeros(&mut self.out, hole.len)?;
+        }
+        Ok(self.out)
+    }
+}
+
+fn write_zeros(out: &mut impl Write, mut len: u64) -> io::Result<()> {
+    while len > 0 {
+        let n = len.min(ZEROS.len() as u64) as usize;
+        out.write_all(&ZEROS[..n])?;
+        len -= n as u64;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hole(offset: u64, len: u64) -> Hole {
+        Hole { offset, len }
+    }
+
+    /// Full contents of a file with `holes` whose data stream is `data`
+    fn expand(holes: &[Hole], data: &[u8]) -> Vec<u8> {
+        let mut fill = ZeroFill::new(Vec::new(), holes);
+        // Odd pieces, so runs and holes straddle write boundaries
+        for piece in data.chunks(7) {
+            fill.write_all(piece).unwrap();
+        }
+        fill.finish().unwrap()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&[hole(0, 10), hole(20, 5)], 25).is_ok());
+        assert!(validate(&[], 0).is_ok());
+        assert!(validate(&[hole(0, 0)], 10).is_err());
+        assert!(validate(&[hole(5, 10)], 10).is_err());
+        assert!(validate(&[hole(0, 10), hole(5, 10)], 30).is_err());
+        assert!(validate(&[hole(20, 5), hole(0, 10)], 30).is_err());
+        assert!(validate(&[hole(u64::MAX, 2)], u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_data_stream_layout() {
+        let holes = [hole(0, 4), hole(10, 3), hole(20, 5)];
+        let data: Vec<u8> = (1..=13).collect();
+        assert_eq!(data_len(25, &holes), 13);
+
+        let mut expected = vec![0u8; 25];
+        expected[4..10].copy_from_slice(&data[..6]);
+        expected[13..20].copy_from_slice(&data[6..]);
+        assert_eq!(expand(&holes, &data), expected);
+
+        let mut hasher = ContentHasher::new(&holes, HashAlgorithm::Blake3);
+        for piece in data.chunks(5) {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finalize(), *blake3::hash(&expected).as_bytes());
+
+        // A file that is all hole has an empty data stream
+        assert_eq!(expand(&[hole(0, 8)], &[]), vec![0u8; 8]);
+        let empty = ContentHasher::new(&[], HashAlgorithm::Blake3).finalize();
+        assert_eq!(empty, *blake3::hash(b"").as_bytes());
+    }
+
+    #[test]
+    fn test_sparse_file_written_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.bin");
+        // Stale contents must not survive in the holes
+        std::fs::write(&path, vec![0xAAu8; 40]).unwrap();
+
+        let holes = [hole(0, 8), hole(16, 8)];
+        let mut file = SparseFile::create(&path, 32, &holes).unwrap();
+        file.write_all(&[1; 5]).unwrap();
+        file.write_all(&[2; 11]).unwrap();
+        file.finish().unwrap();
+
+        let mut expected = vec![0u8; 32];
+        expected[8..13].fill(1);
+        expected[13..16].fill(2);
+        expected[24..].fill(2);
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_holes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sparse.bin");
+        let file = File::create(&path).unwrap();
+        let size = 8 * 1024 * 1024;
+        file.set_len(size).unwrap();
+        let mut writer = &file;
+        writer.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+        writer.write_all(&[7u8; 4096]).unwrap();
+        file.sync_all().unwrap();
+
+        let holes = find_holes(&file, size).unwrap();
+        if holes.is_empty() {
+            // The temp directory's filesystem doesn't report holes
+            return;
+        }
+        validate(&holes, size).unwrap();
+        assert_eq!(holes.first().unwrap().offset, 0);
+        assert_eq!(holes.last().unwrap().end(), size);
+        let data = data_len(size, &holes);
+        assert!(data >= 4096 && data < 1024 * 1024, "{} bytes of data", data);
+    }
+}
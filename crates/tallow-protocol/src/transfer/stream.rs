@@ -0,0 +1,645 @@
+//! Unknown-length stream transfers
+//!
+//! A stream transfer (e.g. `tar c . | tallow stream`) is offered with a
+//! `TransferType::Stream` manifest that has no sizes. Chunks are produced as
+//! data arrives from an `AsyncRead`, and the end of the stream is signalled
+//! by `Message::StreamEnd`. Because the Merkle root is only known once the
+//! input is exhausted, it travels in that trailer together with the final
+//! counts, the content hash and the sender's signature over all of them.
+//!
+//! The trailer is encrypted with the session key under a reserved chunk
+//! index, so a relay can neither read nor truncate-and-forge it.
+
+use crate::compression::{self, CompressionAlgorithm};
+use crate::transfer::chunking;
+use crate::transfer::manifest::{FileManifest, TransferType};
+use crate::transfer::send::SendPipeline;
+use crate::wire::Message;
+use crate::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use tallow_crypto::hash::{domain, MerkleBuilder};
+use tallow_crypto::sig::{HybridPublicKey, HybridSignature, HybridSigner};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Chunk index reserved for the trailer's nonce and AAD
+///
+/// Never reached by data chunks, so the nonce is unique per session key.
+const TRAILER_INDEX: u64 = u64::MAX;
+
+/// Maximum encrypted trailer size accepted from the wire
+const MAX_TRAILER_LEN: usize = 16 * 1024;
+
+/// Signed end-of-stream summary, sent encrypted in `Message::StreamEnd`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StreamTrailer {
+    /// Number of data chunks sent
+    pub total_chunks: u64,
+    /// Number of plaintext bytes read from the source
+    pub total_bytes: u64,
+    /// BLAKE3 hash of the plaintext stream
+    pub content_hash: [u8; 32],
+    /// Merkle root over the BLAKE3 hashes of the encrypted chunks
+    pub merkle_root: [u8; 32],
+    /// Sender's signing key
+    pub signer: HybridPublicKey,
+    /// Signature over the trailer transcript
+    pub signature: HybridSignature,
+}
+
+impl std::fmt::Debug for StreamTrailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamTrailer")
+            .field("total_chunks", &self.total_chunks)
+            .field("total_bytes", &self.total_bytes)
+            .field("merkle_root", &self.merkle_root)
+            .finish()
+    }
+}
+
+impl StreamTrailer {
+    /// Verify the trailer signature for `transfer_id`
+    pub fn verify_signature(&self, transfer_id: &[u8; 16]) -> Result<()> {
+        let digest = trailer_transcript(
+            transfer_id,
+            self.total_chunks,
+            self.total_bytes,
+            &self.content_hash,
+            &self.merkle_root,
+        );
+        tallow_crypto::sig::hybrid::verify(&self.signer, &digest, &self.signature).map_err(|_| {
+            ProtocolError::TransferFailed(
+                "stream trailer signature verification failed".to_string(),
+            )
+        })
+    }
+}
+
+/// Produces `Chunk` messages from an `AsyncRead`, then one `StreamEnd`
+///
+/// Created by [`SendPipeline::stream_chunks`].
+pub struct StreamChunker<'a, R> {
+    pipeline: &'a SendPipeline,
+    reader: R,
+    signer: &'a HybridSigner,
+    buffer: Vec<u8>,
+    next_index: u64,
+    total_bytes: u64,
+    content_hasher: blake3::Hasher,
+    merkle: MerkleBuilder,
+    state: ChunkerState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkerState {
+    Reading,
+    TrailerPending,
+    Done,
+}
+
+impl<R> std::fmt::Debug for StreamChunker<'_, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamChunker")
+            .field("next_index", &self.next_index)
+            .field("total_bytes", &self.total_bytes)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin> StreamChunker<'a, R> {
+    pub(crate) fn new(pipeline: &'a SendPipeline, reader: R, signer: &'a HybridSigner) -> Self {
+        Self {
+            pipeline,
+            reader,
+            signer,
+            buffer: vec![0u8; pipeline.chunk_size()],
+            next_index: 0,
+            total_bytes: 0,
+            content_hasher: blake3::Hasher::new(),
+            merkle: MerkleBuilder::new(),
+            state: ChunkerState::Reading,
+        }
+    }
+
+    /// Next message to send
+    ///
+    /// Returns `Chunk` messages while the source has data, then a single
+    /// `StreamEnd`, then `None`.
+    pub async fn next_message(&mut self) -> Result<Option<Message>> {
+        match self.state {
+            ChunkerState::Done => Ok(None),
+            ChunkerState::TrailerPending => {
+                self.state = ChunkerState::Done;
+                self.trailer_message().map(Some)
+            }
+            ChunkerState::Reading => {
+                let len = self.fill_buffer().await?;
+                if len == 0 {
+                    self.state = ChunkerState::Done;
+                    return self.trailer_message().map(Some);
+                }
+                if len < self.buffer.len() {
+                    // Short read means EOF was hit; no need to poll again
+                    self.state = ChunkerState::TrailerPending;
+                }
+
+                let raw = &self.buffer[..len];
+                self.content_hasher.update(raw);
+                self.total_bytes += len as u64;
+
+                // `total` stays unset: the count is only known at the trailer
+                let msg = self
+                    .pipeline
+                    .encrypt_chunk(raw, self.next_index, 0, false)?;
+                if let Message::Chunk { ref data, .. } = msg {
+                    self.merkle.push_leaf(blake3::hash(data).into());
+                }
+                self.next_index += 1;
+                Ok(Some(msg))
+            }
+        }
+    }
+
+    /// Plaintext bytes read from the source so far
+    pub fn bytes_read(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Chunks produced so far
+    pub fn chunks_sent(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Merkle root over the encrypted chunks produced so far
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.merkle.root()
+    }
+
+    /// Read until the buffer is full or the source reaches EOF
+    async fn fill_buffer(&mut self) -> Result<usize> {
+        let mut filled = 0;
+        while filled < self.buffer.len() {
+            let n = self
+                .reader
+                .read(&mut self.buffer[filled..])
+                .await
+                .map_err(|e| ProtocolError::TransferFailed(format!("read stream: {}", e)))?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        Ok(filled)
+    }
+
+    fn trailer_message(&self) -> Result<Message> {
+        let transfer_id = *self.pipeline.transfer_id();
+        let content_hash: [u8; 32] = self.content_hasher.finalize().into();
+        let merkle_root = self.merkle.root();
+
+        let digest = trailer_transcript(
+            &transfer_id,
+            self.next_index,
+            self.total_bytes,
+            &content_hash,
+            &merkle_root,
+        );
+        let signature = self
+            .signer
+            .sign(&digest)
+            .map_err(|e| ProtocolError::TransferFailed(format!("sign stream trailer: {}", e)))?;
+
+        let trailer = StreamTrailer {
+            total_chunks: self.next_index,
+            total_bytes: self.total_bytes,
+            content_hash,
+            merkle_root,
+            signer: self.signer.public_key(),
+            signature,
+        };
+        let plaintext = postcard::to_stdvec(&trailer)
+            .map_err(|e| ProtocolError::EncodingError(format!("stream trailer: {}", e)))?;
+
+        let encrypted = tallow_crypto::symmetric::aes_encrypt(
+            self.pipeline.session_key(),
+            &chunking::build_chunk_nonce(TRAILER_INDEX),
+            &plaintext,
+            &chunking::build_chunk_aad(&transfer_id, TRAILER_INDEX),
+        )
+        .map_err(|e| ProtocolError::TransferFailed(format!("stream trailer encryption: {}", e)))?;
+
+        Ok(Message::StreamEnd {
+            transfer_id,
+            trailer: encrypted,
+        })
+    }
+}
+
+/// Receives a stream transfer and writes it to `W` as chunks arrive
+///
+/// Data is written before the trailer is checked, so callers must treat the
+/// output as unverified until [`StreamReceiver::finish`] succeeds (e.g. by
+/// deleting a partially written file on error).
+pub struct StreamReceiver<W> {
+    transfer_id: [u8; 16],
+    session_key: [u8; 32],
+    compression: CompressionAlgorithm,
+    adaptive_compression: bool,
+    writer: W,
+    next_index: u64,
+    total_bytes: u64,
+    content_hasher: blake3::Hasher,
+    merkle: MerkleBuilder,
+}
+
+impl<W> Drop for StreamReceiver<W> {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.session_key.zeroize();
+    }
+}
+
+impl<W> std::fmt::Debug for StreamReceiver<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamReceiver")
+            .field("next_index", &self.next_index)
+            .field("total_bytes", &self.total_bytes)
+            .finish()
+    }
+}
+
+impl<W: AsyncWrite + Unpin> StreamReceiver<W> {
+    /// Create a receiver for a stream offered with `manifest`
+    pub fn new(
+        transfer_id: [u8; 16],
+        session_key: [u8; 32],
+        manifest: &FileManifest,
+        writer: W,
+    ) -> Result<Self> {
+        if manifest.transfer_type != TransferType::Stream {
+            return Err(ProtocolError::TransferFailed(
+                "manifest is not a stream transfer".to_string(),
+            ));
+        }
+
+        let compression = match manifest.compression.as_deref() {
+            Some("zstd") => CompressionAlgorithm::Zstd,
+            Some("lz4") => CompressionAlgorithm::Lz4,
+            Some("brotli") => CompressionAlgorithm::Brotli,
+            Some("lzma") => CompressionAlgorithm::Lzma,
+            _ => CompressionAlgorithm::None,
+        };
+
+        Ok(Self {
+            transfer_id,
+            session_key,
+            compression,
+            adaptive_compression: manifest.adaptive_compression,
+            writer,
+            next_index: 0,
+            total_bytes: 0,
+            content_hasher: blake3::Hasher::new(),
+            merkle: MerkleBuilder::new(),
+        })
+    }
+
+    /// Decrypt, decompress and write the next chunk
+    ///
+    /// Stream chunks must arrive in order. Returns the `Ack` to send back.
+    pub async fn process_chunk(&mut self, index: u64, data: &[u8]) -> Result<Message> {
+        if index != self.next_index {
+            return Err(ProtocolError::TransferFailed(format!(
+                "stream chunk {} out of order (expected {})",
+                index, self.next_index
+            )));
+        }
+
+        let decrypted = tallow_crypto::symmetric::aes_decrypt(
+            &self.session_key,
+            &chunking::build_chunk_nonce(index),
+            data,
+            &chunking::build_chunk_aad(&self.transfer_id, index),
+        )
+        .map_err(|e| {
+            ProtocolError::TransferFailed(format!("chunk {} decryption failed: {}", index, e))
+        })?;
+        let plaintext = if self.adaptive_compression {
+            compression::pipeline::decompress_chunk(&decrypted, self.compression)?
+        } else {
+            compression::pipeline::decompress(&decrypted, self.compression)?
+        };
+
+        self.writer
+            .write_all(&plaintext)
+            .await
+            .map_err(|e| ProtocolError::TransferFailed(format!("write stream: {}", e)))?;
+
+        self.merkle.push_leaf(blake3::hash(data).into());
+        self.content_hasher.update(&plaintext);
+        self.total_bytes += plaintext.len() as u64;
+        self.next_index += 1;
+
+        Ok(Message::Ack {
+            transfer_id: self.transfer_id,
+            index,
+        })
+    }
+
+    /// Plaintext bytes written so far
+    pub fn bytes_written(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Verify the `StreamEnd` trailer against what was received and flush
+    ///
+    /// Checks the chunk and byte counts, content hash, Merkle root and
+    /// signature. If `expected_signer` is given, the trailer must also be
+    /// signed by that key.
+    pub async fn finish(
+        &mut self,
+        trailer: &[u8],
+        expected_signer: Option<&HybridPublicKey>,
+    ) -> Result<StreamTrailer> {
+        if trailer.len() > MAX_TRAILER_LEN {
+            return Err(ProtocolError::TransferFailed(
+                "stream trailer too large".to_string(),
+            ));
+        }
+
+        let plaintext = tallow_crypto::symmetric::aes_decrypt(
+            &self.session_key,
+            &chunking::build_chunk_nonce(TRAILER_INDEX),
+            trailer,
+            &chunking::build_chunk_aad(&self.transfer_id, TRAILER_INDEX),
+        )
+        .map_err(|_| {
+            ProtocolError::TransferFailed("stream trailer decryption failed".to_string())
+        })?;
+        let trailer: StreamTrailer = postcard::from_bytes(&plaintext)
+            .map_err(|e| ProtocolError::DecodingError(format!("stream trailer: {}", e)))?;
+
+        if trailer.total_chunks != self.next_index || trailer.total_bytes != self.total_bytes {
+            return Err(ProtocolError::TransferFailed(format!(
+                "stream truncated: trailer says {} chunks / {} bytes, received {} / {}",
+                trailer.total_chunks, trailer.total_bytes, self.next_index, self.total_bytes
+            )));
+        }
+
+        let content_hash: [u8; 32] = self.content_hasher.finalize().into();
+        if !tallow_crypto::mem::constant_time::ct_eq(&content_hash, &trailer.content_hash) {
+            return Err(ProtocolError::TransferFailed(
+                "stream content hash mismatch".to_string(),
+            ));
+        }
+        if !tallow_crypto::mem::constant_time::ct_eq(&self.merkle.root(), &trailer.merkle_root) {
+            return Err(ProtocolError::TransferFailed(
+                "stream Merkle root mismatch".to_string(),
+            ));
+        }
+
+        if let Some(expected) = expected_signer {
+            let same_mldsa =
+                tallow_crypto::mem::constant_time::ct_eq(&expected.mldsa, &trailer.signer.mldsa);
+            let same_ed25519 = tallow_crypto::mem::constant_time::ct_eq(
+                &expected.ed25519,
+                &trailer.signer.ed25519,
+            );
+            if !(same_mldsa && same_ed25519) {
+                return Err(ProtocolError::TransferFailed(
+                    "stream signed by an unexpected key".to_string(),
+                ));
+            }
+        }
+        trailer.verify_signature(&self.transfer_id)?;
+
+        self.writer
+            .flush()
+            .await
+            .map_err(|e| ProtocolError::TransferFailed(format!("flush stream: {}", e)))?;
+
+        Ok(trailer)
+    }
+}
+
+/// Digest signed by the sender at the end of a stream
+fn trailer_transcript(
+    transfer_id: &[u8; 16],
+    total_chunks: u64,
+    total_bytes: u64,
+    content_hash: &[u8; 32],
+    merkle_root: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new_derive_key(domain::DOMAIN_STREAM_TRAILER);
+    hasher.update(transfer_id);
+    hasher.update(&total_chunks.to_be_bytes());
+    hasher.update(&total_bytes.to_be_bytes());
+    hasher.update(content_hash);
+    hasher.update(merkle_root);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::chunking::ChunkConfig;
+
+    fn test_key() -> [u8; 32] {
+        [0x5A; 32]
+    }
+
+    fn test_transfer_id() -> [u8; 16] {
+        [0x07; 16]
+    }
+
+    /// Sender pipeline with small chunks so tests span many chunks
+    fn sender() -> SendPipeline {
+        let mut config = ChunkConfig::new();
+        config.size = chunking::MIN_CHUNK_SIZE;
+        SendPipeline::new(test_transfer_id(), test_key()).with_chunk_config(config)
+    }
+
+    /// Run the sender over `data`, returning the offer manifest and messages
+    async fn stream_out(
+        pipeline: &mut SendPipeline,
+        signer: &HybridSigner,
+        data: &[u8],
+    ) -> (FileManifest, Vec<Message>) {
+        let offer = pipeline.prepare_stream("stdin").unwrap();
+        let manifest = match &offer[0] {
+            Message::FileOffer { manifest, .. } => FileManifest::from_bytes(manifest).unwrap(),
+            _ => panic!("Expected FileOffer"),
+        };
+
+        // A duplex pipe delivers data in small, uneven pieces like stdin
+        let (mut tx, rx) = tokio::io::duplex(1000);
+        let input = data.to_vec();
+        let feeder = tokio::spawn(async move {
+            for piece in input.chunks(777) {
+                tx.write_all(piece).await.unwrap();
+            }
+        });
+
+        let mut chunker = pipeline.stream_chunks(rx, signer);
+        let mut messages = Vec::new();
+        while let Some(msg) = chunker.next_message().await.unwrap() {
+            messages.push(msg);
+        }
+        feeder.await.unwrap();
+        (manifest, messages)
+    }
+
+    async fn stream_in(
+        manifest: &FileManifest,
+        messages: &[Message],
+        expected_signer: Option<&HybridPublicKey>,
+    ) -> (Vec<u8>, Result<StreamTrailer>) {
+        let mut out = Vec::new();
+        let result = {
+            let mut receiver =
+                StreamReceiver::new(test_transfer_id(), test_key(), manifest, &mut out).unwrap();
+            let mut result = Err(ProtocolError::TransferFailed("no StreamEnd".to_string()));
+            for msg in messages {
+                match msg {
+                    Message::Chunk { index, data, .. } => {
+                        receiver.process_chunk(*index, data).await.unwrap();
+                    }
+                    Message::StreamEnd { trailer, .. } => {
+                        result = receiver.finish(trailer, expected_signer).await;
+                    }
+                    other => panic!("unexpected {:?}", other),
+                }
+            }
+            result
+        };
+        (out, result)
+    }
+
+    #[tokio::test]
+    async fn test_unknown_length_stream_roundtrip() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i * 31 % 256) as u8).collect();
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (manifest, messages) = stream_out(&mut pipeline, &signer, &data).await;
+
+        assert_eq!(manifest.transfer_type, TransferType::Stream);
+        assert_eq!(manifest.total_size, 0);
+        assert!(matches!(messages.last(), Some(Message::StreamEnd { .. })));
+        assert!(messages
+            .iter()
+            .all(|m| !matches!(m, Message::Chunk { total: Some(_), .. })));
+
+        let (out, trailer) = stream_in(&manifest, &messages, Some(&signer.public_key())).await;
+        let trailer = trailer.unwrap();
+        assert_eq!(out, data);
+        assert_eq!(trailer.total_bytes, data.len() as u64);
+        assert_eq!(trailer.total_chunks, messages.len() as u64 - 1);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_mixed_stream_roundtrip() {
+        let size = chunking::MIN_CHUNK_SIZE;
+        let mut data = "compressible header ".repeat(4 * size / 20).into_bytes();
+        data.extend((0..10 * size).map(|_| rand::random::<u8>()));
+
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        assert!(pipeline.restrict_compression(crate::wire::FeatureSet::local()));
+        let (manifest, messages) = stream_out(&mut pipeline, &signer, &data).await;
+        assert!(manifest.adaptive_compression);
+
+        let (out, trailer) = stream_in(&manifest, &messages, Some(&signer.public_key())).await;
+        trailer.unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[tokio::test]
+    async fn test_empty_stream_roundtrip() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (manifest, messages) = stream_out(&mut pipeline, &signer, &[]).await;
+        assert_eq!(messages.len(), 1);
+
+        let (out, trailer) = stream_in(&manifest, &messages, None).await;
+        assert!(out.is_empty());
+        assert_eq!(trailer.unwrap().total_chunks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_trailer_root_and_signature_verify() {
+        let data = vec![0xC3u8; 20_000];
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (_, messages) = stream_out(&mut pipeline, &signer, &data).await;
+
+        let Some(Message::StreamEnd { trailer, .. }) = messages.last() else {
+            panic!("Expected StreamEnd");
+        };
+        let plaintext = tallow_crypto::symmetric::aes_decrypt(
+            &test_key(),
+            &chunking::build_chunk_nonce(TRAILER_INDEX),
+            trailer,
+            &chunking::build_chunk_aad(&test_transfer_id(), TRAILER_INDEX),
+        )
+        .unwrap();
+        let mut trailer: StreamTrailer = postcard::from_bytes(&plaintext).unwrap();
+
+        // The root matches a batch tree over the encrypted chunks
+        let leaves: Vec<[u8; 32]> = messages
+            .iter()
+            .filter_map(|m| match m {
+                Message::Chunk { data, .. } => Some(blake3::hash(data).into()),
+                _ => None,
+            })
+            .collect();
+        let tree = tallow_crypto::hash::MerkleTree::build(leaves);
+        assert_eq!(trailer.merkle_root, tree.root());
+        trailer.verify_signature(&test_transfer_id()).unwrap();
+
+        // Any change to the signed fields breaks the signature
+        trailer.merkle_root[0] ^= 1;
+        assert!(trailer.verify_signature(&test_transfer_id()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream_rejected() {
+        let data = vec![0x11u8; 30_000];
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (manifest, mut messages) = stream_out(&mut pipeline, &signer, &data).await;
+
+        // Drop the last data chunk but keep the trailer
+        let end = messages.pop().unwrap();
+        messages.pop();
+        messages.push(end);
+
+        let (_, result) = stream_in(&manifest, &messages, None).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("truncated"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_signer_rejected() {
+        let signer = HybridSigner::keygen().unwrap();
+        let other = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (manifest, messages) = stream_out(&mut pipeline, &signer, b"hello stream").await;
+
+        let (_, result) = stream_in(&manifest, &messages, Some(&other.public_key())).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_chunk_rejected() {
+        let signer = HybridSigner::keygen().unwrap();
+        let mut pipeline = sender();
+        let (manifest, messages) = stream_out(&mut pipeline, &signer, &[9u8; 40_000]).await;
+
+        let mut out = Vec::new();
+        let mut receiver =
+            StreamReceiver::new(test_transfer_id(), test_key(), &manifest, &mut out).unwrap();
+        let Message::Chunk { data, .. } = &messages[1] else {
+            panic!("Expected Chunk");
+        };
+        assert!(receiver.process_chunk(1, data).await.is_err());
+    }
+}
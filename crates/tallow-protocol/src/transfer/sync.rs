@@ -2,7 +2,8 @@
 //!
 //! Compares a local directory manifest against a remote manifest and produces
 //! a diff of new, changed, and deleted files. This is used by the `send --sync`
-//! command to transfer only the files that have changed.
+//! command to transfer only the files that have changed. Within a changed
+//! file, [`delta`](super::delta) narrows the transfer to the changed blocks.
 
 use crate::transfer::manifest::{FileEntry, FileManifest};
 use std::collections::{HashMap, HashSet};
@@ -118,6 +119,10 @@ mod tests {
             manifest_hash: None,
             transfer_type: Default::default(),
             per_chunk_compression: true,
+            adaptive_compression: false,
+            holes: Default::default(),
+            hash_algorithm: Default::default(),
+            skipped: Vec::new(),
         }
     }
 
@@ -0,0 +1,376 @@
+//! Split output volumes
+//!
+//! A received file larger than the configured volume size is written as
+//! numbered parts (`file.part001`, `file.part002`, ...) next to a small
+//! JSON manifest (`file.volumes.json`), so it fits on media with a file size
+//! limit such as FAT32. [`join`] reassembles the parts and checks the result
+//! against the sender's hash recorded in the manifest.
+
+use crate::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tallow_crypto::hash::HashAlgorithm;
+
+/// Suffix of the manifest written beside the parts
+pub const VOLUME_MANIFEST_SUFFIX: &str = ".volumes.json";
+
+/// Smallest accepted volume size
+pub const MIN_VOLUME_SIZE: u64 = 1024;
+
+/// Read buffer size when joining parts
+const JOIN_BUF_SIZE: usize = 64 * 1024;
+
+/// How to reassemble a file written as volumes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeManifest {
+    /// File name of the original, without directory
+    pub file_name: String,
+    /// Size of the original in bytes
+    pub size: u64,
+    /// Maximum size of each part
+    pub volume_size: u64,
+    /// Hash of the original under `hash_algorithm`, hex encoded
+    #[serde(alias = "blake3")]
+    pub hash: String,
+    /// Part file names in order, relative to the manifest's directory
+    pub parts: Vec<String>,
+    /// Algorithm of `hash`; manifests without it are BLAKE3
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+}
+
+impl VolumeManifest {
+    /// Read a manifest written by [`VolumeWriter::finish`]
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(|e| {
+            ProtocolError::TransferFailed(format!(
+                "invalid volume manifest {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Number of parts a file of `size` bytes is split into
+pub fn volume_count(size: u64, volume_size: u64) -> u64 {
+    size.div_ceil(volume_size).max(1)
+}
+
+/// Path of the 1-based part `n` of `path`
+pub fn part_path(path: &Path, n: usize) -> PathBuf {
+    append_suffix(path, &format!(".part{:03}", n))
+}
+
+/// Path of the volume manifest for `path`
+pub fn manifest_path(path: &Path) -> PathBuf {
+    append_suffix(path, VOLUME_MANIFEST_SUFFIX)
+}
+
+fn append_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Sequential writer that rolls over to a new part every `volume_size` bytes
+#[derive(Debug)]
+pub struct VolumeWriter {
+    path: PathBuf,
+    volume_size: u64,
+    current: Option<BufWriter<File>>,
+    /// Bytes in the current part
+    current_len: u64,
+    parts: Vec<PathBuf>,
+    written: u64,
+}
+
+impl VolumeWriter {
+    /// Start writing `path` as volumes of at most `volume_size` bytes
+    ///
+    /// Nothing is written at `path` itself; the parts and manifest go
+    /// beside it.
+    pub fn create(path: &Path, volume_size: u64) -> io::Result<Self> {
+        if volume_size < MIN_VOLUME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("volume size must be at least {} bytes", MIN_VOLUME_SIZE),
+            ));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            volume_size,
+            current: None,
+            current_len: 0,
+            parts: Vec::new(),
+            written: 0,
+        })
+    }
+
+    /// Finish the last part and write the manifest
+    ///
+    /// `hash` is the whole file's hash under `algorithm`, which [`join`]
+    /// checks. Returns the manifest's path.
+    pub fn finish(mut self, hash: &[u8; 32], algorithm: HashAlgorithm) -> io::Result<PathBuf> {
+        if self.parts.is_empty() {
+            // An empty file is still one (empty) part
+            self.next_part()?;
+        }
+        if let Some(mut part) = self.current.take() {
+            part.flush()?;
+            part.get_ref().sync_all()?;
+        }
+
+        let manifest = VolumeManifest {
+            file_name: file_name(&self.path),
+            size: self.written,
+            volume_size: self.volume_size,
+            hash: hex::encode(hash),
+            parts: self.parts.iter().map(|p| file_name(p)).collect(),
+            hash_algorithm: algorithm,
+        };
+        let json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::other)?;
+        let path = manifest_path(&self.path);
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    fn next_part(&mut self) -> io::Result<()> {
+        if let Some(mut part) = self.current.take() {
+            part.flush()?;
+        }
+        let path = part_path(&self.path, self.parts.len() + 1);
+        self.current = Some(BufWriter::new(File::create(&path)?));
+        self.parts.push(path);
+        self.current_len = 0;
+        Ok(())
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.current.is_none() || self.current_len == self.volume_size {
+            self.next_part()?;
+        }
+        let room = (self.volume_size - self.current_len) as usize;
+        let n = buf.len().min(room);
+        let n = self
+            .current
+            .as_mut()
+            .expect("part opened above")
+            .write(&buf[..n])?;
+        self.current_len += n as u64;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.current {
+            Some(ref mut part) => part.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Reassemble the volumes described by the manifest at `manifest` into
+/// `output`, verifying the original hash
+///
+/// Every part is checked for before anything is written, so a missing
+/// part is reported by name. On a hash mismatch `output` is removed.
+pub fn join(manifest: &Path, output: &Path) -> Result<VolumeManifest> {
+    let volumes = VolumeManifest::load(manifest)?;
+    let dir = manifest.parent().unwrap_or(Path::new("."));
+
+    let mut parts = Vec::with_capacity(volumes.parts.len());
+    for name in &volumes.parts {
+        // Part names come from the manifest file; keep them in its directory
+        if Path::new(name).file_name() != Some(std::ffi::OsStr::new(name)) {
+            return Err(ProtocolError::TransferFailed(format!(
+                "invalid part name '{}' in volume manifest",
+                name
+            )));
+        }
+        let path = dir.join(name);
+        if !path.is_file() {
+            return Err(ProtocolError::TransferFailed(format!(
+                "missing part {}",
+                path.display()
+            )));
+        }
+        parts.push(path);
+    }
+
+    let mut out = BufWriter::new(File::create(output)?);
+    let mut hasher = volumes.hash_algorithm.hasher();
+    let mut total: u64 = 0;
+    let mut buf = vec![0u8; JOIN_BUF_SIZE];
+    for path in &parts {
+        let mut part = File::open(path)?;
+        loop {
+            let n = part.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            out.write_all(&buf[..n])?;
+            total += n as u64;
+        }
+    }
+    out.flush()?;
+    drop(out);
+
+    let expected = hex::decode(&volumes.hash).unwrap_or_default();
+    let actual = hasher.finalize();
+    if total != volumes.size || !tallow_crypto::mem::constant_time::ct_eq(&actual, &expected) {
+        let _ = std::fs::remove_file(output);
+        return Err(ProtocolError::TransferFailed(format!(
+            "joined {} does not match the original (hash mismatch)",
+            volumes.file_name
+        )));
+    }
+    Ok(volumes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VOLUME: u64 = 4096;
+
+    /// Write `data` as volumes of `VOLUME` bytes, in pieces of `step` bytes
+    fn write_volumes(path: &Path, data: &[u8], step: usize) -> PathBuf {
+        let mut writer = VolumeWriter::create(path, VOLUME).unwrap();
+        for piece in data.chunks(step) {
+            writer.write_all(piece).unwrap();
+        }
+        writer
+            .finish(blake3::hash(data).as_bytes(), HashAlgorithm::Blake3)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_split_produces_expected_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.img");
+        let data: Vec<u8> = (0..3 * VOLUME as usize + 100)
+            .map(|i| (i % 253) as u8)
+            .collect();
+
+        let manifest_file = write_volumes(&path, &data, 1000);
+        assert_eq!(manifest_file, dir.path().join("disk.img.volumes.json"));
+        assert!(!path.exists());
+
+        let manifest = VolumeManifest::load(&manifest_file).unwrap();
+        assert_eq!(
+            manifest.parts.len() as u64,
+            volume_count(data.len() as u64, VOLUME)
+        );
+        assert_eq!(
+            manifest.parts,
+            [
+                "disk.img.part001",
+                "disk.img.part002",
+                "disk.img.part003",
+                "disk.img.part004"
+            ]
+        );
+        for (n, name) in manifest.parts.iter().enumerate() {
+            let len = std::fs::metadata(dir.path().join(name)).unwrap().len();
+            let expected = if n < 3 { VOLUME } else { 100 };
+            assert_eq!(len, expected, "{}", name);
+        }
+
+        // Exactly one volume's worth is still a single part
+        let exact = dir.path().join("exact.bin");
+        let manifest =
+            VolumeManifest::load(&write_volumes(&exact, &data[..VOLUME as usize], 512)).unwrap();
+        assert_eq!(manifest.parts, ["exact.bin.part001"]);
+    }
+
+    #[test]
+    fn test_join_reconstructs_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..2 * VOLUME as usize + 7)
+            .map(|i| (i * 31 % 256) as u8)
+            .collect();
+        let manifest_file = write_volumes(&dir.path().join("video.mkv"), &data, 3000);
+
+        let output = dir.path().join("joined.mkv");
+        let manifest = join(&manifest_file, &output).unwrap();
+        assert_eq!(manifest.file_name, "video.mkv");
+        assert_eq!(std::fs::read(&output).unwrap(), data);
+
+        // A corrupted part fails verification and leaves no output
+        let part = dir.path().join("video.mkv.part002");
+        let mut bytes = std::fs::read(&part).unwrap();
+        bytes[0] ^= 0xff;
+        std::fs::write(&part, bytes).unwrap();
+        assert!(join(&manifest_file, &output).is_err());
+        assert!(!output.exists());
+    }
+
+    #[tokio::test]
+    async fn test_receiver_writes_large_file_as_volumes() {
+        use crate::transfer::{ReceivePipeline, SendPipeline};
+        use crate::wire::Message;
+
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 241) as u8).collect();
+        let src = dir.path().join("big.bin");
+        std::fs::write(&src, &data).unwrap();
+        let small = dir.path().join("small.txt");
+        std::fs::write(&small, b"fits in one volume").unwrap();
+
+        let mut sender = SendPipeline::new([3; 16], [9; 32]);
+        sender.prepare(&[src, small]).await.unwrap();
+        let out = dir.path().join("out");
+        let mut receiver = ReceivePipeline::new([3; 16], &out, [9; 32]).with_split_size(VOLUME);
+        receiver
+            .process_offer(&sender.manifest().to_bytes().unwrap())
+            .unwrap();
+        for msg in sender.chunk_accepted().await.unwrap() {
+            let Message::Chunk {
+                index, total, data, ..
+            } = msg
+            else {
+                unreachable!();
+            };
+            receiver.process_chunk(index, &data, total).unwrap();
+        }
+
+        let written = receiver.finalize().await.unwrap();
+        assert_eq!(written[0], out.join("big.bin.volumes.json"));
+        assert!(!out.join("big.bin").exists());
+        assert!(out.join("big.bin.part003").exists());
+        assert_eq!(written[1], out.join("small.txt"));
+
+        join(&written[0], &dir.path().join("joined.bin")).unwrap();
+        assert_eq!(std::fs::read(dir.path().join("joined.bin")).unwrap(), data);
+    }
+
+    #[test]
+    fn test_missing_part_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![0x5a; 3 * VOLUME as usize];
+        let manifest_file = write_volumes(&dir.path().join("backup.tar"), &data, 4096);
+        std::fs::remove_file(dir.path().join("backup.tar.part002")).unwrap();
+
+        let output = dir.path().join("backup.tar");
+        let err = join(&manifest_file, &output).unwrap_err();
+        assert!(err.to_string().contains("backup.tar.part002"), "{}", err);
+        assert!(!output.exists());
+    }
+}
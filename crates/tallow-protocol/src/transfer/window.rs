@@ -0,0 +1,193 @@
+//! Bounded read-ahead between chunk production and the network
+//!
+//! Reading a file is usually faster than the network drains it. Without a
+//! bound, chunks read ahead pile up in memory for the whole transfer. A
+//! send window hands out one semaphore permit per chunk: the producer waits
+//! for a permit before queueing, and the permit travels with the chunk
+//! until the consumer drops it (typically once the chunk is acknowledged).
+//! Memory stays proportional to window × chunk size whatever the file size.
+
+use crate::{ProtocolError, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+/// Chunks currently held in the window, and the most ever held at once
+#[derive(Debug, Default)]
+struct WindowStats {
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+/// Create a send window holding at most `capacity` chunks (minimum 1)
+pub fn send_window<T>(capacity: usize) -> (WindowSender<T>, WindowReceiver<T>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let stats = Arc::new(WindowStats::default());
+    (
+        WindowSender {
+            tx,
+            permits: Arc::new(Semaphore::new(capacity.max(1))),
+            stats: stats.clone(),
+        },
+        WindowReceiver { rx, stats },
+    )
+}
+
+/// Producer half of a send window
+#[derive(Debug)]
+pub struct WindowSender<T> {
+    tx: mpsc::UnboundedSender<InFlight<T>>,
+    permits: Arc<Semaphore>,
+    stats: Arc<WindowStats>,
+}
+
+impl<T> WindowSender<T> {
+    /// Queue `item`, waiting while the window is full
+    ///
+    /// Fails once the receiver has been dropped.
+    pub async fn send(&self, item: T) -> Result<()> {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ProtocolError::TransferFailed("send window closed".to_string()))?;
+
+        let held = self.stats.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.stats.peak.fetch_max(held, Ordering::SeqCst);
+
+        self.tx
+            .send(InFlight {
+                item,
+                _permit: permit,
+                stats: self.stats.clone(),
+            })
+            .map_err(|_| ProtocolError::TransferFailed("send window closed".to_string()))
+    }
+}
+
+/// Consumer half of a send window
+#[derive(Debug)]
+pub struct WindowReceiver<T> {
+    rx: mpsc::UnboundedReceiver<InFlight<T>>,
+    stats: Arc<WindowStats>,
+}
+
+impl<T> WindowReceiver<T> {
+    /// Next queued chunk, or `None` once every sender is gone
+    pub async fn recv(&mut self) -> Option<InFlight<T>> {
+        self.rx.recv().await
+    }
+
+    /// Chunks queued or held by the consumer right now
+    pub fn in_flight(&self) -> usize {
+        self.stats.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Most chunks ever in flight at once
+    pub fn peak(&self) -> usize {
+        self.stats.peak.load(Ordering::SeqCst)
+    }
+}
+
+/// A chunk occupying one window slot until dropped
+#[derive(Debug)]
+pub struct InFlight<T> {
+    item: T,
+    _permit: OwnedSemaphorePermit,
+    stats: Arc<WindowStats>,
+}
+
+impl<T> std::ops::Deref for InFlight<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+impl<T> Drop for InFlight<T> {
+    fn drop(&mut self) {
+        self.stats.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    const CHUNK: usize = 64 * 1024;
+
+    #[tokio::test]
+    async fn test_peak_buffered_never_exceeds_window() {
+        const WINDOW: usize = 4;
+        const CHUNKS: usize = 500;
+
+        let (tx, mut rx) = send_window::<Vec<u8>>(WINDOW);
+        let producer = tokio::spawn(async move {
+            for i in 0..CHUNKS {
+                tx.send(vec![i as u8; CHUNK]).await.unwrap();
+            }
+        });
+
+        // Hold a batch until it is "acknowledged", like the send loop does
+        let mut received = 0;
+        let mut batch = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            assert_eq!(chunk[0], received as u8);
+            received += 1;
+            batch.push(chunk);
+            assert!(rx.in_flight() <= WINDOW);
+            if batch.len() == WINDOW {
+                tokio::task::yield_now().await;
+                batch.clear();
+            }
+        }
+        producer.await.unwrap();
+
+        assert_eq!(received, CHUNKS);
+        assert!(rx.peak() <= WINDOW, "peak {}", rx.peak());
+        assert_eq!(rx.peak(), WINDOW);
+        assert_eq!(rx.in_flight(), batch.len());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_read_ahead_overlaps_slow_network() {
+        const CHUNKS: u32 = 200;
+        let step = Duration::from_millis(1);
+
+        // Reading and sending each take one tick per chunk; with a window
+        // the two overlap and the transfer takes about one tick per chunk
+        // rather than two.
+        let (tx, mut rx) = send_window::<Vec<u8>>(8);
+        let start = Instant::now();
+        let producer = tokio::spawn(async move {
+            for _ in 0..CHUNKS {
+                tokio::time::sleep(step).await;
+                tx.send(vec![0u8; CHUNK]).await.unwrap();
+            }
+        });
+
+        let mut sent = 0u32;
+        while let Some(chunk) = rx.recv().await {
+            tokio::time::sleep(step).await;
+            drop(chunk);
+            sent += 1;
+        }
+        producer.await.unwrap();
+
+        assert_eq!(sent, CHUNKS);
+        let elapsed = start.elapsed();
+        assert!(elapsed < step * (CHUNKS + 10), "took {:?}", elapsed);
+        assert!(rx.peak() <= 8);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_after_receiver_dropped() {
+        let (tx, rx) = send_window::<u32>(2);
+        drop(rx);
+        assert!(tx.send(1).await.is_err());
+    }
+}
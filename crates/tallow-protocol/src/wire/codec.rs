@@ -1,14 +1,32 @@
 //! Codec for encoding/decoding Tallow protocol messages
 //!
 //! Framing: 4-byte big-endian length prefix + postcard-serialized payload.
-//! Maximum message size: 16 MiB (prevents OOM from malicious peers).
+//!
+//! The encoding does not depend on the host: the prefix is big-endian on
+//! every architecture, postcard writes integers as little-endian base-128
+//! varints, and neither side ever reads a frame in place, so alignment
+//! never matters. Golden-vector tests below pin the exact bytes.
+//!
+//! Frame lengths are capped (16 MiB by default, less for chat) and checked
+//! against the claimed length before any payload is buffered, so a peer
+//! cannot make us wait for or allocate a 4 GiB frame.
 
 use super::Message;
 use crate::{ProtocolError, Result};
 use bytes::{Buf, BufMut, BytesMut};
+use std::collections::HashMap;
+
+/// Default maximum frame payload length (16 MiB)
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Default cap for `ChatText` frames (chat plaintext is limited to 64 KiB)
+pub const DEFAULT_MAX_CHAT_FRAME_LEN: usize = 256 * 1024;
 
-/// Maximum allowed message size (16 MiB)
-const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+/// Wire tag (postcard variant index) of `Message::Chunk`
+pub const TAG_CHUNK: u8 = 11;
+
+/// Wire tag (postcard variant index) of `Message::ChatText`
+pub const TAG_CHAT_TEXT: u8 = 25;
 
 /// Length prefix size (4 bytes, big-endian u32)
 const LENGTH_PREFIX_SIZE: usize = 4;
@@ -17,13 +35,63 @@ const LENGTH_PREFIX_SIZE: usize = 4;
 ///
 /// Encodes/decodes `Message` values using postcard serialization
 /// with a 4-byte big-endian length prefix for framing.
-#[derive(Debug, Default)]
-pub struct TallowCodec;
+#[derive(Debug, Clone)]
+pub struct TallowCodec {
+    /// Cap for message types without their own limit
+    max_frame_len: usize,
+    /// Per-type caps, keyed by wire tag; may be above or below the default
+    type_limits: HashMap<u8, usize>,
+}
+
+impl Default for TallowCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl TallowCodec {
-    /// Create a new codec
+    /// Create a new codec with the default frame limits
     pub fn new() -> Self {
-        Self
+        let mut type_limits = HashMap::new();
+        type_limits.insert(TAG_CHAT_TEXT, DEFAULT_MAX_CHAT_FRAME_LEN);
+        Self {
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            type_limits,
+        }
+    }
+
+    /// Set the cap for message types without their own limit
+    pub fn with_max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    /// Set the cap for one message type, identified by its wire tag
+    /// (e.g. [`TAG_CHUNK`])
+    pub fn with_type_limit(mut self, tag: u8, max: usize) -> Self {
+        self.type_limits.insert(tag, max);
+        self
+    }
+
+    /// Cap for message types without their own limit
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Cap that applies to frames with the given wire tag
+    pub fn limit_for(&self, tag: u8) -> usize {
+        self.type_limits
+            .get(&tag)
+            .copied()
+            .unwrap_or(self.max_frame_len)
+    }
+
+    /// Largest frame any message type may have
+    fn largest_limit(&self) -> usize {
+        self.type_limits
+            .values()
+            .copied()
+            .fold(self.max_frame_len, usize::max)
     }
 
     /// Encode a message into a buffer
@@ -33,12 +101,13 @@ impl TallowCodec {
         let payload = postcard::to_stdvec(msg)
             .map_err(|e| ProtocolError::EncodingError(format!("postcard encode failed: {}", e)))?;
 
-        if payload.len() > MAX_MESSAGE_SIZE {
-            return Err(ProtocolError::EncodingError(format!(
-                "message too large: {} bytes (max {})",
-                payload.len(),
-                MAX_MESSAGE_SIZE
-            )));
+        // Variant indices are below 128, so the tag is the first byte
+        let max = self.limit_for(payload[0]).min(u32::MAX as usize);
+        if payload.len() > max {
+            return Err(ProtocolError::FrameTooLarge {
+                len: payload.len(),
+                max,
+            });
         }
 
         buf.reserve(LENGTH_PREFIX_SIZE + payload.len());
@@ -61,11 +130,21 @@ impl TallowCodec {
         // Peek at the length (don't consume yet)
         let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
 
-        if len > MAX_MESSAGE_SIZE {
-            return Err(ProtocolError::DecodingError(format!(
-                "message too large: {} bytes (max {})",
-                len, MAX_MESSAGE_SIZE
-            )));
+        // Reject before waiting for (or buffering) the claimed payload
+        let largest = self.largest_limit();
+        if len > largest {
+            return Err(ProtocolError::FrameTooLarge { len, max: largest });
+        }
+
+        if len > 0 {
+            // The tag byte arrives right after the prefix
+            let Some(&tag) = buf.get(LENGTH_PREFIX_SIZE) else {
+                return Ok(None);
+            };
+            let max = self.limit_for(tag);
+            if len > max {
+                return Err(ProtocolError::FrameTooLarge { len, max });
+            }
         }
 
         // Check if we have the full payload
@@ -103,6 +182,19 @@ impl tokio_util::codec::Decoder for TallowCodec {
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>> {
         self.decode_msg(src)
     }
+
+    /// At end of stream, a partial prefix or payload is an error rather
+    /// than a frame still on its way
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Message>> {
+        match self.decode_msg(src)? {
+            Some(msg) => Ok(Some(msg)),
+            None if src.is_empty() => Ok(None),
+            None => Err(ProtocolError::DecodingError(format!(
+                "stream ended inside a frame ({} trailing bytes)",
+                src.len()
+            ))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -176,7 +268,7 @@ mod tests {
         let mut buf = BytesMut::new();
 
         // Simulate a message claiming to be too large
-        buf.put_u32((MAX_MESSAGE_SIZE + 1) as u32);
+        buf.put_u32((DEFAULT_MAX_FRAME_LEN + 1) as u32);
 
         let result = codec.decode_msg(&mut buf);
         assert!(result.is_err());
@@ -207,4 +299,502 @@ mod tests {
         let decoded = codec.decode_msg(&mut buf).unwrap();
         assert_eq!(decoded, Some(msg));
     }
+
+    #[test]
+    fn test_codec_tags_match_wire_encoding() {
+        let chunk = Message::Chunk {
+            transfer_id: [0u8; 16],
+            index: 0,
+            total: None,
+            data: Vec::new(),
+        };
+        let chat = Message::ChatText {
+            message_id: [0u8; 16],
+            sequence: 0,
+            ciphertext: Vec::new(),
+            nonce: [0u8; 12],
+        };
+        assert_eq!(postcard::to_stdvec(&chunk).unwrap()[0], TAG_CHUNK);
+        assert_eq!(postcard::to_stdvec(&chat).unwrap()[0], TAG_CHAT_TEXT);
+    }
+
+    #[test]
+    fn test_codec_rejects_huge_claim_before_buffering() {
+        let mut codec = TallowCodec::new();
+        let mut buf = BytesMut::new();
+
+        // 4 GiB claim with only the header present: must fail now, not
+        // return Ok(None) and wait for the payload
+        buf.put_u32(u32::MAX);
+        buf.put_u8(TAG_CHUNK);
+
+        match codec.decode_msg(&mut buf) {
+            Err(ProtocolError::FrameTooLarge { len, max }) => {
+                assert_eq!(len, u32::MAX as usize);
+                assert_eq!(max, DEFAULT_MAX_FRAME_LEN);
+            }
+            other => panic!("expected FrameTooLarge, got {:?}", other),
+        }
+        assert!(buf.capacity() < 1024);
+    }
+
+    #[test]
+    fn test_codec_never_decodes_keepalive_frame() {
+        use tallow_net::transport::keepalive::{KEEPALIVE_FRAME_LEN, KEEPALIVE_MAGIC};
+
+        // Keepalives share the channel with codec frames; their magic must
+        // always read as an oversized length so they can't be misparsed
+        let mut codec = TallowCodec::new();
+        let mut buf = BytesMut::new();
+        buf.put_slice(&KEEPALIVE_MAGIC);
+        buf.put_bytes(0, KEEPALIVE_FRAME_LEN - KEEPALIVE_MAGIC.len());
+
+        assert!(matches!(
+            codec.decode_msg(&mut buf),
+            Err(ProtocolError::FrameTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_codec_per_type_limit() {
+        let mut codec = TallowCodec::new().with_type_limit(TAG_CHAT_TEXT, 1024);
+        let mut buf = BytesMut::new();
+
+        // A chat frame over its cap is rejected even though it is far
+        // below the default limit
+        buf.put_u32(4096);
+        buf.put_u8(TAG_CHAT_TEXT);
+        assert!(matches!(
+            codec.decode_msg(&mut buf),
+            Err(ProtocolError::FrameTooLarge { max: 1024, .. })
+        ));
+
+        // The same length is fine for a chunk
+        let msg = Message::Chunk {
+            transfer_id: [1u8; 16],
+            index: 0,
+            total: None,
+            data: vec![0x55; 4000],
+        };
+        let mut buf = BytesMut::new();
+        codec.encode_msg(&msg, &mut buf).unwrap();
+        assert_eq!(codec.decode_msg(&mut buf).unwrap(), Some(msg));
+
+        // Oversized chat is refused on the encode side too
+        let chat = Message::ChatText {
+            message_id: [2u8; 16],
+            sequence: 1,
+            ciphertext: vec![0u8; 2048],
+            nonce: [0u8; 12],
+        };
+        assert!(matches!(
+            codec.encode_msg(&chat, &mut BytesMut::new()),
+            Err(ProtocolError::FrameTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_codec_large_frame_under_cap() {
+        // Chunks may be raised above the default cap
+        let mut codec = TallowCodec::new()
+            .with_max_frame_len(64 * 1024)
+            .with_type_limit(TAG_CHUNK, 8 * 1024 * 1024);
+        let mut buf = BytesMut::new();
+
+        let msg = Message::Chunk {
+            transfer_id: [3u8; 16],
+            index: 7,
+            total: Some(8),
+            data: vec![0xEE; 4 * 1024 * 1024],
+        };
+        codec.encode_msg(&msg, &mut buf).unwrap();
+        assert_eq!(codec.decode_msg(&mut buf).unwrap(), Some(msg));
+
+        // ...while other types keep the lower default
+        assert_eq!(codec.limit_for(TAG_CHAT_TEXT), DEFAULT_MAX_CHAT_FRAME_LEN);
+        assert_eq!(codec.limit_for(0), 64 * 1024);
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use futures::StreamExt;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// Wire tag of every variant
+    ///
+    /// The match is exhaustive on purpose: a new variant does not compile
+    /// until it is given a tag here and a strategy in `message()`.
+    fn wire_tag(msg: &Message) -> u8 {
+        match msg {
+            Message::VersionRequest { .. } => 0,
+            Message::VersionResponse { .. } => 1,
+            Message::VersionReject { .. } => 2,
+            Message::RoomJoin { .. } => 3,
+            Message::RoomJoined { .. } => 4,
+            Message::RoomLeave => 5,
+            Message::PeerArrived => 6,
+            Message::PeerDeparted => 7,
+            Message::FileOffer { .. } => 8,
+            Message::FileAccept { .. } => 9,
+            Message::FileReject { .. } => 10,
+            Message::Chunk { .. } => 11,
+            Message::Ack { .. } => 12,
+            Message::TransferComplete { .. } => 13,
+            Message::TransferError { .. } => 14,
+            Message::ManifestExchange { .. } => 15,
+            Message::SyncDeleteList { .. } => 16,
+            Message::Ping => 17,
+            Message::Pong => 18,
+            Message::HandshakeInit { .. } => 19,
+            Message::HandshakeResponse { .. } => 20,
+            Message::HandshakeKem { .. } => 21,
+            Message::HandshakeComplete { .. } => 22,
+            Message::ResumeInfo { .. } => 23,
+            Message::HandshakeFailed { .. } => 24,
+            Message::ChatText { .. } => 25,
+            Message::TypingIndicator { .. } => 26,
+            Message::ReadReceipt { .. } => 27,
+            Message::ChatEnd => 28,
+            Message::RoomJoinMulti { .. } => 29,
+            Message::RoomJoinedMulti { .. } => 30,
+            Message::PeerJoinedRoom { .. } => 31,
+            Message::PeerLeftRoom { .. } => 32,
+            Message::Targeted { .. } => 33,
+            Message::RoomPeerCount { .. } => 34,
+            Message::CandidateOffer { .. } => 35,
+            Message::CandidatesDone => 36,
+            Message::DirectConnected => 37,
+            Message::DirectFailed => 38,
+            Message::FileSelection { .. } => 39,
+            Message::StreamEnd { .. } => 40,
+            Message::RoomJoinToken { .. } => 41,
+            Message::RoomResume { .. } => 42,
+            Message::Capabilities { .. } => 43,
+            Message::FileChunk { .. } => 44,
+            Message::ResendChunks { .. } => 45,
+            Message::CumulativeAck { .. } => 46,
+            Message::ManifestStart { .. } => 47,
+            Message::ManifestChunk { .. } => 48,
+            Message::ManifestEnd { .. } => 49,
+            Message::ProbeData { .. } => 50,
+            Message::ProbeAck { .. } => 51,
+        }
+    }
+
+    fn bytes() -> impl Strategy<Value = Vec<u8>> {
+        vec(any::<u8>(), 0..64)
+    }
+
+    fn text() -> impl Strategy<Value = String> {
+        "\\PC{0,32}"
+    }
+
+    fn id() -> impl Strategy<Value = [u8; 16]> {
+        any::<[u8; 16]>()
+    }
+
+    fn hash() -> impl Strategy<Value = [u8; 32]> {
+        any::<[u8; 32]>()
+    }
+
+    /// Every message variant, with arbitrary field values
+    fn message() -> impl Strategy<Value = Message> {
+        prop_oneof![
+            vec(any::<u32>(), 0..8)
+                .prop_map(|supported_versions| Message::VersionRequest { supported_versions }),
+            any::<u32>().prop_map(|selected_version| Message::VersionResponse { selected_version }),
+            text().prop_map(|reason| Message::VersionReject { reason }),
+            (bytes(), proptest::option::of(bytes())).prop_map(|(room_id, password_hash)| {
+                Message::RoomJoin {
+                    room_id,
+                    password_hash,
+                }
+            }),
+            any::<bool>().prop_map(|peer_present| Message::RoomJoined { peer_present }),
+            Just(Message::RoomLeave),
+            Just(Message::PeerArrived),
+            Just(Message::PeerDeparted),
+            (id(), bytes()).prop_map(|(transfer_id, manifest)| Message::FileOffer {
+                transfer_id,
+                manifest
+            }),
+            id().prop_map(|transfer_id| Message::FileAccept { transfer_id }),
+            (id(), text()).prop_map(|(transfer_id, reason)| Message::FileReject {
+                transfer_id,
+                reason
+            }),
+            (id(), any::<u64>(), any::<Option<u64>>(), bytes()).prop_map(
+                |(transfer_id, index, total, data)| Message::Chunk {
+                    transfer_id,
+                    index,
+                    total,
+                    data,
+                }
+            ),
+            (id(), any::<u64>())
+                .prop_map(|(transfer_id, index)| Message::Ack { transfer_id, index }),
+            (id(), hash(), proptest::option::of(hash())).prop_map(
+                |(transfer_id, hash, merkle_root)| Message::TransferComplete {
+                    transfer_id,
+                    hash,
+                    merkle_root,
+                }
+            ),
+            (id(), text())
+                .prop_map(|(transfer_id, error)| Message::TransferError { transfer_id, error }),
+            (id(), bytes()).prop_map(|(transfer_id, manifest)| Message::ManifestExchange {
+                transfer_id,
+                manifest
+            }),
+            (id(), vec(text(), 0..4)).prop_map(|(transfer_id, paths)| {
+                Message::SyncDeleteList { transfer_id, paths }
+            }),
+            Just(Message::Ping),
+            Just(Message::Pong),
+            (any::<u32>(), bytes(), hash(), id()).prop_map(
+                |(protocol_version, kem_capabilities, cpace_public, nonce)| {
+                    Message::HandshakeInit {
+                        protocol_version,
+                        kem_capabilities,
+                        cpace_public,
+                        nonce,
+                    }
+                }
+            ),
+            (any::<u8>(), hash(), bytes(), id()).prop_map(
+                |(selected_kem, cpace_public, kem_public_key, nonce)| {
+                    Message::HandshakeResponse {
+                        selected_kem,
+                        cpace_public,
+                        kem_public_key,
+                        nonce,
+                    }
+                }
+            ),
+            (bytes(), hash()).prop_map(|(kem_ciphertext, confirmation)| {
+                Message::HandshakeKem {
+                    kem_ciphertext,
+                    confirmation,
+                }
+            }),
+            hash().prop_map(|confirmation| Message::HandshakeComplete { confirmation }),
+            (id(), hash(), vec(any::<u64>(), 0..8)).prop_map(
+                |(transfer_id, manifest_hash, verified_chunks)| Message::ResumeInfo {
+                    transfer_id,
+                    manifest_hash,
+                    verified_chunks,
+                }
+            ),
+            text().prop_map(|reason| Message::HandshakeFailed { reason }),
+            (id(), any::<u64>(), bytes(), any::<[u8; 12]>()).prop_map(
+                |(message_id, sequence, ciphertext, nonce)| Message::ChatText {
+                    message_id,
+                    sequence,
+                    ciphertext,
+                    nonce,
+                }
+            ),
+            any::<bool>().prop_map(|typing| Message::TypingIndicator { typing }),
+            vec(id(), 0..4).prop_map(|message_ids| Message::ReadReceipt { message_ids }),
+            Just(Message::ChatEnd),
+            (bytes(), proptest::option::of(bytes()), any::<u8>()).prop_map(
+                |(room_id, password_hash, requested_capacity)| Message::RoomJoinMulti {
+                    room_id,
+                    password_hash,
+                    requested_capacity,
+                }
+            ),
+            (any::<u8>(), bytes()).prop_map(|(peer_id, existing_peers)| {
+                Message::RoomJoinedMulti {
+                    peer_id,
+                    existing_peers,
+                }
+            }),
+            any::<u8>().prop_map(|peer_id| Message::PeerJoinedRoom { peer_id }),
+            any::<u8>().prop_map(|peer_id| Message::PeerLeftRoom { peer_id }),
+            (any::<u8>(), any::<u8>(), bytes()).prop_map(|(from_peer, to_peer, payload)| {
+                Message::Targeted {
+                    from_peer,
+                    to_peer,
+                    payload,
+                }
+            }),
+            (any::<u8>(), any::<u8>())
+                .prop_map(|(count, capacity)| Message::RoomPeerCount { count, capacity }),
+            (any::<u8>(), bytes(), any::<u32>()).prop_map(|(candidate_type, addr, priority)| {
+                Message::CandidateOffer {
+                    candidate_type,
+                    addr,
+                    priority,
+                }
+            }),
+            Just(Message::CandidatesDone),
+            Just(Message::DirectConnected),
+            Just(Message::DirectFailed),
+            (id(), vec(any::<u32>(), 0..8)).prop_map(|(transfer_id, selected_indices)| {
+                Message::FileSelection {
+                    transfer_id,
+                    selected_indices,
+                }
+            }),
+            (id(), bytes()).prop_map(|(transfer_id, trailer)| Message::StreamEnd {
+                transfer_id,
+                trailer
+            }),
+            (bytes(), bytes(), any::<Option<u8>>()).prop_map(
+                |(room_id, token, requested_capacity)| Message::RoomJoinToken {
+                    room_id,
+                    token,
+                    requested_capacity,
+                }
+            ),
+            (bytes(), bytes()).prop_map(|(room_id, session_token)| Message::RoomResume {
+                room_id,
+                session_token
+            }),
+            any::<u64>().prop_map(|features| Message::Capabilities { features }),
+            (id(), any::<u32>(), any::<u64>(), bytes()).prop_map(
+                |(transfer_id, file_id, index, data)| Message::FileChunk {
+                    transfer_id,
+                    file_id,
+                    index,
+                    data,
+                }
+            ),
+            (id(), vec(any::<u64>(), 0..8)).prop_map(|(transfer_id, indices)| {
+                Message::ResendChunks {
+                    transfer_id,
+                    indices,
+                }
+            }),
+            (id(), any::<u64>()).prop_map(|(transfer_id, received)| {
+                Message::CumulativeAck {
+                    transfer_id,
+                    received,
+                }
+            }),
+            (id(), bytes()).prop_map(|(transfer_id, header)| Message::ManifestStart {
+                transfer_id,
+                header,
+            }),
+            (id(), any::<u64>(), bytes()).prop_map(|(transfer_id, seq, entries)| {
+                Message::ManifestChunk {
+                    transfer_id,
+                    seq,
+                    entries,
+                }
+            }),
+            (id(), bytes()).prop_map(|(transfer_id, summary)| Message::ManifestEnd {
+                transfer_id,
+                summary,
+            }),
+            (any::<u32>(), any::<bool>(), any::<u16>(), bytes()).prop_map(
+                |(seq, last, ratio_permille, payload)| Message::ProbeData {
+                    seq,
+                    last,
+                    ratio_permille,
+                    payload,
+                }
+            ),
+            any::<u64>().prop_map(|bytes| Message::ProbeAck { bytes }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn codec_roundtrip_every_variant(msg in message()) {
+            let mut codec = TallowCodec::new();
+            let mut buf = BytesMut::new();
+            codec.encode_msg(&msg, &mut buf).unwrap();
+
+            // Big-endian prefix, then the variant's ordinal as the first byte
+            let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+            prop_assert_eq!(len, buf.len() - LENGTH_PREFIX_SIZE);
+            prop_assert_eq!(buf[LENGTH_PREFIX_SIZE], wire_tag(&msg));
+
+            let decoded = codec.decode_msg(&mut buf).unwrap();
+            prop_assert_eq!(decoded, Some(msg));
+            prop_assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_golden_chunk_frame() {
+        let msg = Message::Chunk {
+            transfer_id: [0x11; 16],
+            index: 300,
+            total: Some(2),
+            data: vec![0xde, 0xad],
+        };
+        let mut buf = BytesMut::new();
+        TallowCodec::new().encode_msg(&msg, &mut buf).unwrap();
+
+        let mut expected = vec![0x00, 0x00, 0x00, 0x18]; // BE length 24
+        expected.push(0x0b); // variant 11
+        expected.extend_from_slice(&[0x11; 16]); // fixed array, no length
+        expected.extend_from_slice(&[0xac, 0x02]); // varint 300
+        expected.extend_from_slice(&[0x01, 0x02]); // Some(varint 2)
+        expected.extend_from_slice(&[0x02, 0xde, 0xad]); // length-prefixed bytes
+        assert_eq!(&buf[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_golden_candidate_offer_frame() {
+        // Multi-byte integers are LEB128 varints, not host-order words
+        let msg = Message::CandidateOffer {
+            candidate_type: 1,
+            addr: vec![127, 0, 0, 1],
+            priority: 0x0102_0304,
+        };
+        let mut buf = BytesMut::new();
+        TallowCodec::new().encode_msg(&msg, &mut buf).unwrap();
+
+        let expected = [
+            0x00, 0x00, 0x00, 0x0b, // BE length 11
+            0x23, // variant 35
+            0x01, // candidate_type
+            0x04, 127, 0, 0, 1, // addr
+            0x84, 0x86, 0x88, 0x08, // varint 0x01020304
+        ];
+        assert_eq!(&buf[..], &expected[..]);
+
+        let decoded = TallowCodec::new().decode_msg(&mut buf).unwrap();
+        assert_eq!(decoded, Some(msg));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_prefix_errors_at_eof() {
+        let mut frames =
+            tokio_util::codec::FramedRead::new(&[0x00u8, 0x00][..], TallowCodec::new());
+        let result = frames.next().await.expect("an item, not a clean end");
+        assert!(matches!(result, Err(ProtocolError::DecodingError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_payload_errors_at_eof() {
+        let mut buf = BytesMut::new();
+        TallowCodec::new()
+            .encode_msg(&Message::Ping, &mut buf)
+            .unwrap();
+        TallowCodec::new()
+            .encode_msg(
+                &Message::FileAccept {
+                    transfer_id: [7; 16],
+                },
+                &mut buf,
+            )
+            .unwrap();
+        buf.truncate(buf.len() - 3);
+
+        let mut frames = tokio_util::codec::FramedRead::new(&buf[..], TallowCodec::new());
+        assert!(matches!(frames.next().await, Some(Ok(Message::Ping))));
+        assert!(matches!(
+            frames.next().await,
+            Some(Err(ProtocolError::DecodingError(_)))
+        ));
+        assert!(frames.next().await.is_none());
+    }
 }
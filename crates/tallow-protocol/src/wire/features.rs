@@ -0,0 +1,215 @@
+//! Peer capability advertisement and feature negotiation
+//!
+//! Each side advertises the optional features it supports in a
+//! [`Message::Capabilities`] message during the handshake. A transfer only
+//! uses features present in the intersection of both advertisements.
+
+use super::Message;
+use crate::compression::CompressionAlgorithm;
+
+/// Bitmask of optional protocol features
+///
+/// Unknown bits from newer peers are preserved on decode and simply drop
+/// out of the negotiated set, so new flags never break older builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FeatureSet(u64);
+
+impl FeatureSet {
+    /// Zstandard compression
+    pub const COMPRESS_ZSTD: Self = Self(1 << 0);
+    /// Brotli compression
+    pub const COMPRESS_BROTLI: Self = Self(1 << 1);
+    /// LZ4 compression
+    pub const COMPRESS_LZ4: Self = Self(1 << 2);
+    /// LZMA/XZ compression
+    pub const COMPRESS_LZMA: Self = Self(1 << 3);
+    /// Per-chunk tagged frames that let the sender stop compressing mid-stream
+    pub const ADAPTIVE_COMPRESSION: Self = Self(1 << 4);
+    /// `FileChunk` messages interleaving several files on one connection
+    pub const MULTIPLEXED_FILES: Self = Self(1 << 5);
+    /// `ResendChunks` requests for chunks that fail authentication
+    pub const CHUNK_RETRANSMIT: Self = Self(1 << 6);
+    /// Periodic `CumulativeAck` messages confirming received chunks
+    pub const CUMULATIVE_ACK: Self = Self(1 << 7);
+    /// Forward error correction on chunk streams (reserved)
+    pub const FEC: Self = Self(1 << 8);
+    /// Content-defined chunk deduplication (reserved)
+    pub const DEDUP: Self = Self(1 << 9);
+    /// CRC32C trailer on chunk payloads, checked before decryption
+    ///
+    /// Every build can verify it, but a sender advertises it only when the
+    /// user asked for checksums, so it is negotiated exactly when chunks
+    /// will carry one.
+    pub const CHUNK_CHECKSUM: Self = Self(1 << 10);
+    /// Manifest sent as `ManifestStart`/`ManifestChunk`/`ManifestEnd`
+    /// batches so neither side holds the whole file list (reserved until
+    /// the transfer pipelines adopt it)
+    pub const STREAMED_MANIFEST: Self = Self(1 << 11);
+    /// Hole lists for sparse files in the manifest, with only the bytes
+    /// outside the holes sent as chunks
+    pub const SPARSE_FILES: Self = Self(1 << 12);
+    /// SHA3-256 file hashes in place of BLAKE3
+    pub const HASH_SHA3: Self = Self(1 << 13);
+    /// Adaptive frames naming their own algorithm, chosen chunk by chunk
+    pub const PER_CHUNK_ALGORITHM: Self = Self(1 << 14);
+    /// `ProbeData`/`ProbeAck` throughput probe ahead of the file offer
+    pub const BANDWIDTH_PROBE: Self = Self(1 << 15);
+
+    /// All compression flags
+    const ALL_COMPRESSION: Self = Self(
+        Self::COMPRESS_ZSTD.0
+            | Self::COMPRESS_BROTLI.0
+            | Self::COMPRESS_LZ4.0
+            | Self::COMPRESS_LZMA.0,
+    );
+
+    /// The empty set
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Build a set from raw bits received on the wire
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Raw bits for the wire
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Features implemented by this build
+    pub const fn local() -> Self {
+        Self::ALL_COMPRESSION
+            .union(Self::ADAPTIVE_COMPRESSION)
+            .union(Self::MULTIPLEXED_FILES)
+            .union(Self::CHUNK_RETRANSMIT)
+            .union(Self::CUMULATIVE_ACK)
+            .union(Self::CHUNK_CHECKSUM)
+            .union(Self::SPARSE_FILES)
+            .union(Self::HASH_SHA3)
+            .union(Self::PER_CHUNK_ALGORITHM)
+            .union(Self::BANDWIDTH_PROBE)
+    }
+
+    /// Features assumed for a peer that never advertised capabilities
+    ///
+    /// Builds predating capability advertisement decode every compression
+    /// algorithm and nothing else, and do not understand adaptive frames.
+    pub const fn legacy() -> Self {
+        Self::ALL_COMPRESSION
+    }
+
+    /// Whether every flag in `other` is set in `self`
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Flags set in both `self` and `other`
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Flags set in either `self` or `other`
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Remove the flags in `other` from `self`
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Whether the peer can decode chunks compressed with `algorithm`
+    pub fn supports_compression(self, algorithm: CompressionAlgorithm) -> bool {
+        match algorithm {
+            CompressionAlgorithm::None => true,
+            CompressionAlgorithm::Zstd => self.contains(Self::COMPRESS_ZSTD),
+            CompressionAlgorithm::Brotli => self.contains(Self::COMPRESS_BROTLI),
+            CompressionAlgorithm::Lz4 => self.contains(Self::COMPRESS_LZ4),
+            CompressionAlgorithm::Lzma => self.contains(Self::COMPRESS_LZMA),
+        }
+    }
+
+    /// Pick the compression algorithm to use under this negotiated set
+    ///
+    /// Keeps `preferred` when supported, otherwise falls back to zstd and
+    /// finally to no compression.
+    pub fn select_compression(self, preferred: CompressionAlgorithm) -> CompressionAlgorithm {
+        if self.supports_compression(preferred) {
+            preferred
+        } else if self.supports_compression(CompressionAlgorithm::Zstd) {
+            CompressionAlgorithm::Zstd
+        } else {
+            CompressionAlgorithm::None
+        }
+    }
+}
+
+/// Compute the features both peers support
+pub fn negotiate_features(local: FeatureSet, remote: FeatureSet) -> FeatureSet {
+    local.intersection(remote)
+}
+
+/// Create a capabilities message advertising `features`
+pub fn capabilities_message(features: FeatureSet) -> Message {
+    Message::Capabilities {
+        features: features.bits(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_returns_intersection() {
+        let local = FeatureSet::COMPRESS_ZSTD
+            .union(FeatureSet::COMPRESS_BROTLI)
+            .union(FeatureSet::FEC);
+        let remote = FeatureSet::COMPRESS_ZSTD
+            .union(FeatureSet::FEC)
+            .union(FeatureSet::DEDUP);
+
+        let negotiated = negotiate_features(local, remote);
+        assert_eq!(negotiated, FeatureSet::COMPRESS_ZSTD.union(FeatureSet::FEC));
+        assert_eq!(negotiated, negotiate_features(remote, local));
+    }
+
+    #[test]
+    fn test_one_sided_feature_disabled() {
+        let local = FeatureSet::local();
+        let remote = FeatureSet::local().difference(FeatureSet::COMPRESS_BROTLI);
+
+        let negotiated = negotiate_features(local, remote);
+        assert!(!negotiated.contains(FeatureSet::COMPRESS_BROTLI));
+        assert!(!negotiated.supports_compression(CompressionAlgorithm::Brotli));
+        assert_eq!(
+            negotiated.select_compression(CompressionAlgorithm::Brotli),
+            CompressionAlgorithm::Zstd
+        );
+    }
+
+    #[test]
+    fn test_unknown_bits_drop_out() {
+        let remote = FeatureSet::from_bits(FeatureSet::local().bits() | (1 << 63));
+        assert_eq!(
+            negotiate_features(FeatureSet::local(), remote),
+            FeatureSet::local()
+        );
+    }
+
+    #[test]
+    fn test_select_compression_falls_back_to_none() {
+        let negotiated = FeatureSet::COMPRESS_LZ4;
+        assert_eq!(
+            negotiated.select_compression(CompressionAlgorithm::Lz4),
+            CompressionAlgorithm::Lz4
+        );
+        assert_eq!(
+            negotiated.select_compression(CompressionAlgorithm::Lzma),
+            CompressionAlgorithm::None
+        );
+        assert!(FeatureSet::empty().supports_compression(CompressionAlgorithm::None));
+    }
+}
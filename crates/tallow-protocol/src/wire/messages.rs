@@ -274,6 +274,142 @@ pub enum Message {
         /// Indices of files from the manifest that the receiver wants (0-based)
         selected_indices: Vec<u32>,
     },
+
+    // --- Stream transfers (DO NOT reorder; postcard ordinal) ---
+    /// End of an unknown-length stream (sender -> receiver)
+    ///
+    /// Sent after the last `Chunk` of a `TransferType::Stream` transfer.
+    /// The trailer is encrypted with the session key and carries the final
+    /// chunk/byte counts, content hash, Merkle root and sender signature.
+    StreamEnd {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Encrypted, serialized stream trailer
+        trailer: Vec<u8>,
+    },
+
+    // --- Relay token auth (DO NOT reorder; postcard ordinal) ---
+    /// Room join authenticated with a relay bearer token
+    RoomJoinToken {
+        /// Room ID (BLAKE3 hash of code phrase, 32 bytes)
+        room_id: Vec<u8>,
+        /// Serialized, Ed25519-signed relay token
+        token: Vec<u8>,
+        /// `None` = 2-peer room; `Some(n)` = multi-peer room (0 = server default)
+        requested_capacity: Option<u8>,
+    },
+
+    // --- Relay session resumption (DO NOT reorder; postcard ordinal) ---
+    /// Rebind a 2-peer room slot to this connection after a network change
+    RoomResume {
+        /// Room ID (BLAKE3 hash of code phrase, 32 bytes)
+        room_id: Vec<u8>,
+        /// Session token issued by the relay in its `RoomJoined` reply
+        session_token: Vec<u8>,
+    },
+
+    // --- Capability advertisement (DO NOT reorder; postcard ordinal) ---
+    /// Optional feature flags supported by the sending peer
+    ///
+    /// Exchanged before the KEM handshake completes and bound into the
+    /// handshake transcript, so a stripped or altered advertisement fails
+    /// key confirmation.
+    Capabilities {
+        /// Bitmask of `wire::features::FeatureSet` flags
+        features: u64,
+    },
+    /// Data chunk tagged with the file it belongs to
+    ///
+    /// Lets chunks of several files interleave on one connection. Only sent
+    /// when both peers advertise `FeatureSet::MULTIPLEXED_FILES`. Encryption
+    /// still uses the transfer-wide chunk index derived from the manifest,
+    /// so a chunk routed to the wrong file fails authentication.
+    FileChunk {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Index of the file in the manifest
+        file_id: u32,
+        /// Chunk index within the file (0-based)
+        index: u64,
+        /// Encrypted chunk data
+        data: Vec<u8>,
+    },
+    /// Ask the sender to send specific chunks again
+    ///
+    /// Sent by the receiver instead of an `Ack` when a chunk fails
+    /// authentication (e.g. a bit flip the transport did not catch). The
+    /// sender repeats exactly those chunks and the rest of the transfer
+    /// carries on. Only sent when both peers advertise
+    /// `FeatureSet::CHUNK_RETRANSMIT`.
+    ResendChunks {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Transfer-wide indices of the chunks to resend
+        indices: Vec<u64>,
+    },
+    /// Receiver's running total of chunks received and verified
+    ///
+    /// Every chunk with a transfer-wide index below `received` is stored
+    /// and authenticated. Sent periodically, so the sender can report how
+    /// much of the transfer the receiver actually has rather than how much
+    /// was written to the socket. Only sent when both peers advertise
+    /// `FeatureSet::CUMULATIVE_ACK`.
+    CumulativeAck {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Number of contiguous chunks held, counting from chunk 0
+        received: u64,
+    },
+
+    // --- Streamed manifests (DO NOT reorder; postcard ordinal) ---
+    /// Start of a manifest sent in batches instead of one `FileOffer`
+    ///
+    /// Followed by any number of `ManifestChunk` messages and one
+    /// `ManifestEnd`. Only sent when both peers advertise
+    /// `FeatureSet::STREAMED_MANIFEST`.
+    ManifestStart {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Serialized `ManifestHeader` (every manifest field but the files)
+        header: Vec<u8>,
+    },
+    /// A batch of manifest entries, in manifest order
+    ManifestChunk {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Batch sequence number (0-based, no gaps)
+        seq: u64,
+        /// Serialized `Vec<FileEntry>`
+        entries: Vec<u8>,
+    },
+    /// End of a streamed manifest
+    ManifestEnd {
+        /// Transfer ID
+        transfer_id: [u8; 16],
+        /// Serialized, signed `ManifestSummary` covering every entry
+        summary: Vec<u8>,
+    },
+
+    // --- Bandwidth probe (DO NOT reorder; postcard ordinal) ---
+    /// Random filler timed to estimate transfer duration; never file data
+    ///
+    /// Sent before the `FileOffer` only when both peers advertise
+    /// `FeatureSet::BANDWIDTH_PROBE`.
+    ProbeData {
+        /// Frame sequence number (0-based)
+        seq: u32,
+        /// Whether this is the final probe frame
+        last: bool,
+        /// Sender's estimate of compressed/original size, in thousandths
+        ratio_permille: u16,
+        /// Filler bytes
+        payload: Vec<u8>,
+    },
+    /// Reply to the final `ProbeData`, ending the probe
+    ProbeAck {
+        /// Probe bytes received
+        bytes: u64,
+    },
 }
 
 #[cfg(test)]
@@ -810,4 +946,149 @@ mod tests {
             );
         }
     }
+
+    // --- Stream transfer tests ---
+
+    #[test]
+    fn test_stream_end_roundtrip_and_discriminant() {
+        let msg = Message::StreamEnd {
+            transfer_id: [0xEE; 16],
+            trailer: vec![1, 2, 3, 4],
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+        assert_eq!(bytes[0], 40, "StreamEnd discriminant must be 40");
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_room_join_token_wire_layout() {
+        // tallow-net hand-encodes this message; pin the exact layout
+        let msg = Message::RoomJoinToken {
+            room_id: vec![0x11; 32],
+            token: vec![0x22; 200],
+            requested_capacity: None,
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+
+        let mut expected = vec![41, 32];
+        expected.extend_from_slice(&[0x11; 32]);
+        expected.extend_from_slice(&[0xC8, 0x01]); // varint(200)
+        expected.extend_from_slice(&[0x22; 200]);
+        expected.push(0x00);
+        assert_eq!(bytes, expected);
+
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_room_resume_wire_layout() {
+        // tallow-net hand-encodes this message; pin the exact layout
+        let msg = Message::RoomResume {
+            room_id: vec![0x11; 32],
+            session_token: vec![0x33; 32],
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+
+        let mut expected = vec![42, 32];
+        expected.extend_from_slice(&[0x11; 32]);
+        expected.push(32);
+        expected.extend_from_slice(&[0x33; 32]);
+        assert_eq!(bytes, expected);
+        assert_eq!(postcard::from_bytes::<Message>(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_capabilities_roundtrip() {
+        let msg = Message::Capabilities {
+            features: 0x0001_0203,
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+        assert_eq!(bytes[0], 43, "Capabilities discriminant must be 43");
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_file_chunk_roundtrip() {
+        let msg = Message::FileChunk {
+            transfer_id: [3u8; 16],
+            file_id: 7,
+            index: 12,
+            data: vec![1, 2, 3],
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+        assert_eq!(bytes[0], 44, "FileChunk discriminant must be 44");
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_resend_chunks_roundtrip() {
+        let msg = Message::ResendChunks {
+            transfer_id: [4u8; 16],
+            indices: vec![3, 17],
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+        assert_eq!(bytes[0], 45, "ResendChunks discriminant must be 45");
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_cumulative_ack_roundtrip() {
+        let msg = Message::CumulativeAck {
+            transfer_id: [5u8; 16],
+            received: 1024,
+        };
+        let bytes = postcard::to_stdvec(&msg).unwrap();
+        assert_eq!(bytes[0], 46, "CumulativeAck discriminant must be 46");
+        let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_streamed_manifest_discriminants() {
+        let messages = [
+            Message::ManifestStart {
+                transfer_id: [6u8; 16],
+                header: vec![1, 2],
+            },
+            Message::ManifestChunk {
+                transfer_id: [6u8; 16],
+                seq: 3,
+                entries: vec![4, 5, 6],
+            },
+            Message::ManifestEnd {
+                transfer_id: [6u8; 16],
+                summary: vec![7],
+            },
+        ];
+        for (msg, tag) in messages.into_iter().zip(47u8..) {
+            let bytes = postcard::to_stdvec(&msg).unwrap();
+            assert_eq!(bytes[0], tag, "{:?}", msg);
+            let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    #[test]
+    fn test_probe_discriminants() {
+        let messages = [
+            Message::ProbeData {
+                seq: 2,
+                last: true,
+                ratio_permille: 650,
+                payload: vec![0xA5; 8],
+            },
+            Message::ProbeAck { bytes: 8 },
+        ];
+        for (msg, tag) in messages.into_iter().zip(50u8..) {
+            let bytes = postcard::to_stdvec(&msg).unwrap();
+            assert_eq!(bytes[0], tag, "{:?}", msg);
+            let decoded: Message = postcard::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
 }
@@ -4,12 +4,16 @@
 
 #[cfg(feature = "full")]
 pub mod codec;
+#[cfg(feature = "full")]
+pub mod features;
 pub mod messages;
 #[cfg(feature = "full")]
 pub mod version;
 
 #[cfg(feature = "full")]
 pub use codec::TallowCodec;
+#[cfg(feature = "full")]
+pub use features::{capabilities_message, negotiate_features, FeatureSet};
 pub use messages::Message;
 #[cfg(feature = "full")]
 pub use version::{
@@ -1,10 +1,132 @@
-//! Relay password authentication
+//! Relay password and bearer token authentication
 //!
 //! BLAKE3-based password verification for relay access control.
 //! Passwords are never stored or transmitted in plaintext — only
 //! their BLAKE3 hashes are compared using constant-time operations.
+//!
+//! Bearer tokens are Ed25519-signed claims (key id, subject, expiry).
+//! A token is accepted if its `key_id` names a key in the relay's
+//! `authorized_keys` and the signature verifies; it is revoked by
+//! expiry or by removing that key from the configuration.
 
+use crate::config::AuthorizedKey;
+use serde::{Deserialize, Serialize};
 use subtle::ConstantTimeEq;
+use tallow_crypto::clock::Clock;
+use tallow_crypto::hash::domain::DOMAIN_RELAY_TOKEN;
+use tallow_crypto::sig::Ed25519Signer;
+
+/// Maximum serialized token size accepted from a client
+pub const MAX_TOKEN_LEN: usize = 512;
+
+/// Claims carried by a relay bearer token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenClaims {
+    /// Identifier of the signing key in `authorized_keys`
+    pub key_id: String,
+    /// Who the token was issued to (informational, logged on use)
+    pub subject: String,
+    /// Expiry as seconds since the Unix epoch
+    pub expires_at: u64,
+}
+
+/// Signed relay bearer token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayToken {
+    /// Signed claims
+    pub claims: TokenClaims,
+    /// Ed25519 signature over the claims digest
+    pub signature: Vec<u8>,
+}
+
+impl RelayToken {
+    /// Sign `claims` with `signer`
+    pub fn mint(claims: TokenClaims, signer: &Ed25519Signer) -> anyhow::Result<Self> {
+        let digest = claims_digest(&claims)?;
+        let signature = signer.sign(&digest).to_vec();
+        Ok(Self { claims, signature })
+    }
+
+    /// Serialize for the wire
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(postcard::to_stdvec(self)?)
+    }
+
+    /// Deserialize from the wire
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() > MAX_TOKEN_LEN {
+            anyhow::bail!("token too large: {} bytes", bytes.len());
+        }
+        Ok(postcard::from_bytes(bytes)?)
+    }
+}
+
+/// Digest of the claims that the token signature covers
+fn claims_digest(claims: &TokenClaims) -> anyhow::Result<[u8; 32]> {
+    let encoded = postcard::to_stdvec(claims)?;
+    Ok(blake3::derive_key(DOMAIN_RELAY_TOKEN, &encoded))
+}
+
+/// Verify a serialized bearer token against the authorized keys at time `now`
+///
+/// Returns the token's claims if it is well-formed, unexpired, names an
+/// authorized key, and carries a valid signature from that key.
+pub fn verify_relay_token(
+    token: &[u8],
+    authorized_keys: &[AuthorizedKey],
+    now: u64,
+) -> Option<TokenClaims> {
+    let token = RelayToken::from_bytes(token).ok()?;
+    if token.claims.expires_at <= now {
+        return None;
+    }
+
+    let key = authorized_keys
+        .iter()
+        .find(|k| k.key_id == token.claims.key_id)?;
+    let public_key = key.public_key_bytes()?;
+    let signature: [u8; 64] = token.signature.as_slice().try_into().ok()?;
+    let digest = claims_digest(&token.claims).ok()?;
+
+    tallow_crypto::sig::ed25519::verify(&public_key, &digest, &signature).ok()?;
+    Some(token.claims)
+}
+
+/// Decide whether a client may join, given its password hash and/or token
+///
+/// The relay is open only when neither a password nor any authorized
+/// token keys are configured. Otherwise a correct password or a valid
+/// token is required. Token expiry is judged against `clock`.
+pub fn verify_relay_access(
+    client_hash: Option<&[u8; 32]>,
+    token: Option<&[u8]>,
+    relay_password: &str,
+    authorized_keys: &[AuthorizedKey],
+    clock: &dyn Clock,
+) -> bool {
+    if relay_password.is_empty() && authorized_keys.is_empty() {
+        return true;
+    }
+
+    if !relay_password.is_empty() && verify_relay_password(client_hash, relay_password) {
+        return true;
+    }
+
+    let Some(token) = token else {
+        return false;
+    };
+    match verify_relay_token(token, authorized_keys, clock.unix_secs()) {
+        Some(claims) => {
+            tracing::info!(
+                "token auth accepted (key_id={}, subject={})",
+                claims.key_id,
+                claims.subject
+            );
+            true
+        }
+        None => false,
+    }
+}
 
 /// Verify a client-provided password hash against the relay's configured password
 ///
@@ -37,6 +159,8 @@ pub fn hash_relay_password(password: &str) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
+    use tallow_crypto::clock::MockClock;
 
     #[test]
     fn test_open_relay_allows_all() {
@@ -79,4 +203,116 @@ mod tests {
         let b = hash_relay_password("password2");
         assert_ne!(a, b);
     }
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn authorized(key_id: &str, signer: &Ed25519Signer) -> AuthorizedKey {
+        AuthorizedKey {
+            key_id: key_id.to_string(),
+            public_key: hex::encode(signer.verifying_key_bytes()),
+        }
+    }
+
+    fn mint(key_id: &str, signer: &Ed25519Signer, expires_at: u64) -> Vec<u8> {
+        let claims = TokenClaims {
+            key_id: key_id.to_string(),
+            subject: "alice".to_string(),
+            expires_at,
+        };
+        RelayToken::mint(claims, signer)
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_valid_token_accepted() {
+        let signer = Ed25519Signer::keygen();
+        let keys = vec![authorized("team", &signer)];
+        let token = mint("team", &signer, NOW + 3600);
+
+        let claims = verify_relay_token(&token, &keys, NOW).expect("token must verify");
+        assert_eq!(claims.subject, "alice");
+    }
+
+    #[test]
+    fn test_expired_token_rejected() {
+        let signer = Ed25519Signer::keygen();
+        let keys = vec![authorized("team", &signer)];
+        let token = mint("team", &signer, NOW - 1);
+
+        assert!(verify_relay_token(&token, &keys, NOW).is_none());
+    }
+
+    #[test]
+    fn test_removing_key_revokes_token() {
+        let signer = Ed25519Signer::keygen();
+        let other = Ed25519Signer::keygen();
+        let token = mint("team", &signer, NOW + 3600);
+
+        let mut keys = vec![authorized("team", &signer), authorized("ops", &other)];
+        assert!(verify_relay_token(&token, &keys, NOW).is_some());
+
+        keys.retain(|k| k.key_id != "team");
+        assert!(verify_relay_token(&token, &keys, NOW).is_none());
+    }
+
+    #[test]
+    fn test_token_signed_by_wrong_key_rejected() {
+        let signer = Ed25519Signer::keygen();
+        let forger = Ed25519Signer::keygen();
+        let keys = vec![authorized("team", &signer)];
+        let token = mint("team", &forger, NOW + 3600);
+
+        assert!(verify_relay_token(&token, &keys, NOW).is_none());
+    }
+
+    #[test]
+    fn test_access_with_password_or_token() {
+        let signer = Ed25519Signer::keygen();
+        let keys = vec![authorized("team", &signer)];
+        let clock = MockClock::new(NOW);
+        let token = mint("team", &signer, NOW + 3600);
+        let good_pw = hash_relay_password("secretpass");
+        let bad_pw = hash_relay_password("wrong");
+
+        assert!(verify_relay_access(
+            Some(&good_pw),
+            None,
+            "secretpass",
+            &keys,
+            &clock
+        ));
+        assert!(verify_relay_access(
+            Some(&bad_pw),
+            Some(&token),
+            "secretpass",
+            &keys,
+            &clock
+        ));
+        assert!(!verify_relay_access(
+            Some(&bad_pw),
+            None,
+            "secretpass",
+            &keys,
+            &clock
+        ));
+        // Token-only relay: no password configured, but not open
+        assert!(verify_relay_access(None, Some(&token), "", &keys, &clock));
+        assert!(!verify_relay_access(None, None, "", &keys, &clock));
+        assert!(verify_relay_access(None, None, "", &[], &clock));
+    }
+
+    #[test]
+    fn test_access_token_expires_on_clock() {
+        let signer = Ed25519Signer::keygen();
+        let keys = vec![authorized("team", &signer)];
+        let token = mint("team", &signer, NOW + 60);
+        let clock = MockClock::new(NOW);
+
+        clock.advance(Duration::from_secs(59));
+        assert!(verify_relay_access(None, Some(&token), "", &keys, &clock));
+        clock.advance(Duration::from_secs(1));
+        assert!(!verify_relay_access(None, Some(&token), "", &keys, &clock));
+    }
 }
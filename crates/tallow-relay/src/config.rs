@@ -29,6 +29,27 @@ pub struct RelayConfig {
     /// Set to empty string to disable WebSocket listener
     #[serde(default = "default_ws_bind_addr")]
     pub ws_bind_addr: String,
+    /// Public keys allowed to sign bearer tokens (empty = tokens disabled)
+    #[serde(default)]
+    pub authorized_keys: Vec<AuthorizedKey>,
+}
+
+/// A token-signing key trusted by the relay
+///
+/// Removing an entry revokes every token signed with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizedKey {
+    /// Key identifier, matched against the token's `key_id` claim
+    pub key_id: String,
+    /// Hex-encoded Ed25519 public key
+    pub public_key: String,
+}
+
+impl AuthorizedKey {
+    /// Decode the Ed25519 public key, if well-formed
+    pub fn public_key_bytes(&self) -> Option<[u8; 32]> {
+        hex::decode(&self.public_key).ok()?.try_into().ok()
+    }
 }
 
 fn default_max_peers_per_room() -> u8 {
@@ -60,6 +81,17 @@ impl RelayConfig {
             );
             self.ws_bind_addr.clear();
         }
+
+        self.authorized_keys.retain(|key| {
+            let valid = key.public_key_bytes().is_some();
+            if !valid {
+                tracing::warn!(
+                    "ignoring authorized key '{}': public key is not 32 hex-encoded bytes",
+                    key.key_id
+                );
+            }
+            valid
+        });
     }
 }
 
@@ -76,6 +108,7 @@ impl Default for RelayConfig {
             max_peers_per_room: 10,
             password: String::new(),
             ws_bind_addr: "0.0.0.0:4434".to_string(),
+            authorized_keys: Vec::new(),
         }
     }
 }
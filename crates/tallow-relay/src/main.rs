@@ -57,6 +57,39 @@ enum Commands {
         #[arg(long, default_value = "0.0.0.0:4434")]
         ws_addr: String,
     },
+    /// Manage bearer tokens for per-user relay access
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommands {
+    /// Generate a token-signing keypair
+    Keygen {
+        /// Key identifier to list under `authorized_keys`
+        #[arg(long)]
+        key_id: String,
+    },
+    /// Mint a bearer token signed with a key from `token keygen`
+    Mint {
+        /// Key identifier (must match an `authorized_keys` entry)
+        #[arg(long)]
+        key_id: String,
+
+        /// Hex-encoded signing key secret (use TALLOW_RELAY_TOKEN_SECRET env var)
+        #[arg(long, env = "TALLOW_RELAY_TOKEN_SECRET", hide_env_values = true)]
+        secret: String,
+
+        /// Who the token is issued to
+        #[arg(long)]
+        subject: String,
+
+        /// Token lifetime in hours
+        #[arg(long, default_value = "720")]
+        ttl_hours: u64,
+    },
 }
 
 #[tokio::main]
@@ -97,7 +130,10 @@ async fn main() -> anyhow::Result<()> {
             relay_config.max_peers_per_room = max_peers_per_room.min(20);
 
             // Warn if running as open relay
-            if pass.is_none() && relay_config.password.is_empty() {
+            if pass.is_none()
+                && relay_config.password.is_empty()
+                && relay_config.authorized_keys.is_empty()
+            {
                 warn!(
                     "No relay password configured — running as OPEN relay. \
                      Set --pass or TALLOW_RELAY_PASS to require authentication."
@@ -111,7 +147,55 @@ async fn main() -> anyhow::Result<()> {
             let server = RelayServer::new(relay_config);
             server.start().await?;
         }
+        Commands::Token { command } => run_token_command(command)?,
     }
 
     Ok(())
 }
+
+/// Generate signing keys and mint bearer tokens
+fn run_token_command(command: TokenCommands) -> anyhow::Result<()> {
+    match command {
+        TokenCommands::Keygen { key_id } => {
+            let seed = zeroize::Zeroizing::new(rand::random::<[u8; 32]>());
+            let signer = tallow_crypto::sig::Ed25519Signer::from_seed(*seed);
+
+            println!("# Add to the relay config:");
+            println!("[[authorized_keys]]");
+            println!("key_id = \"{}\"", key_id);
+            println!(
+                "public_key = \"{}\"",
+                hex::encode(signer.verifying_key_bytes())
+            );
+            println!();
+            println!("# Keep secret; pass to `token mint` via TALLOW_RELAY_TOKEN_SECRET:");
+            println!("{}", hex::encode(seed.as_slice()));
+        }
+        TokenCommands::Mint {
+            key_id,
+            secret,
+            subject,
+            ttl_hours,
+        } => {
+            let seed: zeroize::Zeroizing<[u8; 32]> = zeroize::Zeroizing::new(
+                hex::decode(secret.trim())
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| anyhow::anyhow!("secret must be 32 hex-encoded bytes"))?,
+            );
+            let signer = tallow_crypto::sig::Ed25519Signer::from_seed(*seed);
+
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs();
+            let claims = auth::TokenClaims {
+                key_id,
+                subject,
+                expires_at: now.saturating_add(ttl_hours.saturating_mul(3600)),
+            };
+            let token = auth::RelayToken::mint(claims, &signer)?;
+            println!("{}", hex::encode(token.to_bytes()?));
+        }
+    }
+    Ok(())
+}
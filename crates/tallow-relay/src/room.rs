@@ -2,10 +2,20 @@
 //!
 //! Rooms pair sender and receiver by room code hash (BLAKE3).
 //! The relay forwards encrypted bytes without inspection.
+//!
+//! Peers in 2-peer rooms joined over QUIC receive a session token. If their
+//! connection drops (e.g. a phone moving from WiFi to cellular), they can
+//! reconnect from any address and present the token to rebind their slot
+//! instead of exchanging a new code. Tokens are single-use: each successful
+//! resume consumes the token and issues a fresh one. A token expires
+//! [`SESSION_RESUME_WINDOW`] after its connection is lost.
 
 use dashmap::DashMap;
+use rand::RngCore;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tallow_crypto::clock::{Clock, SystemClock};
 use tokio::sync::mpsc;
 
 /// Unique room identifier (BLAKE3 hash of code phrase)
@@ -17,10 +27,35 @@ pub type PeerSender = mpsc::Sender<Vec<u8>>;
 /// Channel for receiving raw bytes from a peer
 pub type PeerReceiver = mpsc::Receiver<Vec<u8>>;
 
+/// How long a session token stays valid after its connection drops
+pub const SESSION_RESUME_WINDOW: Duration = Duration::from_secs(120);
+
+/// Length of a session token in bytes
+pub const SESSION_TOKEN_LEN: usize = 32;
+
+/// Opaque token that lets a 2-peer room member rebind to a new connection
+pub type SessionToken = [u8; SESSION_TOKEN_LEN];
+
 /// A peer waiting in or connected to a room
 pub struct RoomPeer {
     /// Channel to send data to this peer
     pub sender: PeerSender,
+    /// Connection generation; a superseded connection's teardown is ignored
+    generation: u64,
+}
+
+/// Outstanding session token for one room slot
+struct SessionTicket {
+    /// BLAKE3 hash of the token (the token itself is never stored)
+    token_hash: blake3::Hash,
+    /// `None` while the peer is connected; set when its connection drops
+    expires_at: Option<Instant>,
+}
+
+impl SessionTicket {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|t| now < t)
+    }
 }
 
 /// A relay room that pairs two peers
@@ -31,6 +66,20 @@ pub struct Room {
     pub peer_b: Option<RoomPeer>,
     /// Last time any activity occurred (join, data forwarded)
     pub last_activity: Instant,
+    /// Session tickets for peer A and peer B
+    tickets: [Option<SessionTicket>; 2],
+}
+
+/// A 2-peer room slot rebound to a new connection by [`RoomManager::resume_session`]
+pub struct ResumedSession {
+    /// Channel delivering data from the other peer
+    pub receiver: PeerReceiver,
+    /// Whether the resumed slot is peer A
+    pub is_peer_a: bool,
+    /// Generation of the new connection
+    pub generation: u64,
+    /// Replacement token (the presented one is consumed)
+    pub token: SessionToken,
 }
 
 /// A peer in a multi-peer room
@@ -55,12 +104,12 @@ pub struct MultiRoom {
 
 impl MultiRoom {
     /// Create a new multi-peer room with the given capacity
-    pub fn new(capacity: u8) -> Self {
+    pub fn new(capacity: u8, now: Instant) -> Self {
         Self {
             peers: Vec::with_capacity(capacity as usize),
             capacity,
             next_id: 0,
-            last_activity: Instant::now(),
+            last_activity: now,
         }
     }
 
@@ -83,7 +132,6 @@ impl MultiRoom {
             self.peers.push(None);
         }
         self.peers[peer_id as usize] = Some(MultiRoomPeer { sender, peer_id });
-        self.last_activity = Instant::now();
 
         Ok((peer_id, existing))
     }
@@ -107,8 +155,8 @@ impl MultiRoom {
     }
 
     /// Update last activity timestamp
-    pub fn touch(&mut self) {
-        self.last_activity = Instant::now();
+    pub fn touch(&mut self, now: Instant) {
+        self.last_activity = now;
     }
 
     /// Fan-out: send data to all peers except the sender
@@ -132,18 +180,31 @@ impl MultiRoom {
 
 impl Room {
     /// Create a new room with the first peer
-    pub fn new(peer: RoomPeer) -> Self {
-        let now = Instant::now();
+    pub fn new(peer: RoomPeer, now: Instant) -> Self {
         Self {
             peer_a: Some(peer),
             peer_b: None,
             last_activity: now,
+            tickets: [None, None],
+        }
+    }
+
+    fn slot_mut(&mut self, is_peer_a: bool) -> &mut Option<RoomPeer> {
+        if is_peer_a {
+            &mut self.peer_a
+        } else {
+            &mut self.peer_b
         }
     }
 
+    /// Whether a disconnected peer can still resume into this room
+    fn has_live_ticket(&self, now: Instant) -> bool {
+        self.tickets.iter().flatten().any(|t| t.is_live(now))
+    }
+
     /// Update last activity timestamp (call on data forwarding)
-    pub fn touch(&mut self) {
-        self.last_activity = Instant::now();
+    pub fn touch(&mut self, now: Instant) {
+        self.last_activity = now;
     }
 
     /// Check if the room is empty (both peers left)
@@ -164,6 +225,12 @@ pub struct RoomManager {
     ip_room_counts: Arc<DashMap<std::net::IpAddr, usize>>,
     /// Maximum rooms per IP
     max_rooms_per_ip: usize,
+    /// Source of connection generations for 2-peer room slots
+    next_generation: AtomicU64,
+    /// How long a dropped peer may resume with its session token
+    resume_window: Duration,
+    /// Time source for activity, idle and resume-window checks
+    clock: Arc<dyn Clock>,
 }
 
 impl RoomManager {
@@ -177,6 +244,9 @@ impl RoomManager {
             max_peers_per_room: 10,
             ip_room_counts: Arc::new(DashMap::new()),
             max_rooms_per_ip: 50,
+            next_generation: AtomicU64::new(0),
+            resume_window: SESSION_RESUME_WINDOW,
+            clock: Arc::new(SystemClock),
         }
     }
 
@@ -189,9 +259,23 @@ impl RoomManager {
             max_peers_per_room,
             ip_room_counts: Arc::new(DashMap::new()),
             max_rooms_per_ip: 50,
+            next_generation: AtomicU64::new(0),
+            resume_window: SESSION_RESUME_WINDOW,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The time source used for expiry decisions
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
     /// Join a room with per-IP tracking.
     ///
     /// If the room doesn't exist, creates it and the peer waits.
@@ -226,7 +310,10 @@ impl RoomManager {
 
         // Create channels for this peer
         let (tx, rx) = mpsc::channel(256);
-        let peer = RoomPeer { sender: tx.clone() };
+        let peer = RoomPeer {
+            sender: tx.clone(),
+            generation: self.next_generation(),
+        };
 
         match self.rooms.entry(room_id) {
             Entry::Occupied(mut entry) => {
@@ -241,12 +328,15 @@ impl RoomManager {
                     .map(|p| p.sender.clone())
                     .ok_or(RoomError::RoomFull)?;
 
+                // A fresh join takes over a dropped peer's slot; its old
+                // session can no longer resume
                 room.peer_b = Some(peer);
-                room.touch();
+                room.tickets[1] = None;
+                room.touch(self.clock.instant());
                 Ok((rx, peer_a_sender, true))
             }
             Entry::Vacant(entry) => {
-                entry.insert(Room::new(peer));
+                entry.insert(Room::new(peer, self.clock.instant()));
 
                 // Track per-IP room creation
                 if let Some(ip) = client_ip {
@@ -279,10 +369,117 @@ impl RoomManager {
     /// Update last activity timestamp for a room (call during data forwarding)
     pub fn touch_room(&self, room_id: &RoomId) {
         if let Some(mut room) = self.rooms.get_mut(room_id) {
-            room.touch();
+            room.touch(self.clock.instant());
         }
     }
 
+    /// Issue a session token for a peer that just joined a 2-peer room.
+    ///
+    /// Returns the token and the connection generation to pass to
+    /// [`RoomManager::session_peer_disconnected`], or `None` if the slot is empty.
+    pub fn issue_session_token(
+        &self,
+        room_id: &RoomId,
+        is_peer_a: bool,
+    ) -> Option<(SessionToken, u64)> {
+        let mut room = self.rooms.get_mut(room_id)?;
+        let generation = room.slot_mut(is_peer_a).as_ref()?.generation;
+        let token = new_session_token();
+        room.tickets[slot_index(is_peer_a)] = Some(SessionTicket {
+            token_hash: blake3::hash(&token),
+            expires_at: None,
+        });
+        Some((token, generation))
+    }
+
+    /// Rebind a 2-peer room slot to a new connection using a session token.
+    ///
+    /// The token is consumed and a replacement issued. If the slot's old
+    /// connection is still open it is superseded: its channel closes and its
+    /// eventual teardown is ignored. Unknown rooms, forged, used and expired
+    /// tokens are all rejected with the same error.
+    pub fn resume_session(
+        &self,
+        room_id: &RoomId,
+        token: &[u8],
+    ) -> Result<ResumedSession, RoomError> {
+        let now = self.clock.instant();
+        let presented = blake3::hash(token);
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or(RoomError::InvalidSessionToken)?;
+
+        // blake3::Hash equality is constant-time
+        let is_peer_a = match &room.tickets {
+            [Some(t), _] if t.is_live(now) && t.token_hash == presented => true,
+            [_, Some(t)] if t.is_live(now) && t.token_hash == presented => false,
+            _ => return Err(RoomError::InvalidSessionToken),
+        };
+
+        let (tx, rx) = mpsc::channel(256);
+        let generation = self.next_generation();
+        *room.slot_mut(is_peer_a) = Some(RoomPeer {
+            sender: tx,
+            generation,
+        });
+
+        let token = new_session_token();
+        room.tickets[slot_index(is_peer_a)] = Some(SessionTicket {
+            token_hash: blake3::hash(&token),
+            expires_at: None,
+        });
+        room.touch(now);
+
+        Ok(ResumedSession {
+            receiver: rx,
+            is_peer_a,
+            generation,
+            token,
+        })
+    }
+
+    /// Notify that a session-holding peer's connection ended.
+    ///
+    /// Ignored (apart from per-IP bookkeeping) if a resumed connection has
+    /// since taken the slot. Otherwise the slot is emptied and its session
+    /// token starts its [`SESSION_RESUME_WINDOW`]; the room is kept while
+    /// any token can still resume.
+    pub fn session_peer_disconnected(
+        &self,
+        room_id: &RoomId,
+        is_peer_a: bool,
+        generation: u64,
+        client_ip: Option<std::net::IpAddr>,
+    ) {
+        let now = self.clock.instant();
+        let should_remove = if let Some(mut room) = self.rooms.get_mut(room_id) {
+            let slot = room.slot_mut(is_peer_a);
+            if slot.as_ref().is_some_and(|p| p.generation == generation) {
+                *slot = None;
+                let paired = room.tickets[slot_index(!is_peer_a)].is_some();
+                let ticket = &mut room.tickets[slot_index(is_peer_a)];
+                if paired {
+                    if let Some(ticket) = ticket.as_mut() {
+                        ticket.expires_at = Some(now + self.resume_window);
+                    }
+                } else {
+                    // Never paired: nothing to resume into
+                    *ticket = None;
+                }
+            }
+            room.is_empty() && !room.has_live_ticket(now)
+        } else {
+            false
+        };
+
+        if should_remove {
+            self.rooms.remove(room_id);
+        }
+
+        self.release_ip(client_ip);
+    }
+
     /// Notify that a peer from a given IP has disconnected from a room.
     /// Decrements the per-IP room count.
     pub fn peer_disconnected(&self, room_id: &RoomId, client_ip: Option<std::net::IpAddr>) {
@@ -304,6 +501,18 @@ impl RoomManager {
         }
 
         // Decrement per-IP counter
+        self.release_ip(client_ip);
+    }
+
+    /// Count a resumed connection against its (possibly new) client IP
+    pub fn track_ip(&self, client_ip: Option<std::net::IpAddr>) {
+        if let Some(ip) = client_ip {
+            *self.ip_room_counts.entry(ip).or_insert(0) += 1;
+        }
+    }
+
+    /// Decrement the per-IP room count for a departing connection
+    fn release_ip(&self, client_ip: Option<std::net::IpAddr>) {
         if let Some(ip) = client_ip {
             if let Some(mut count) = self.ip_room_counts.get_mut(&ip) {
                 *count = count.saturating_sub(1);
@@ -315,18 +524,25 @@ impl RoomManager {
         }
     }
 
-    /// Clean up stale rooms that have been idle longer than the given duration
+    fn next_generation(&self) -> u64 {
+        self.next_generation.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Clean up stale rooms that have been idle for `max_idle_secs` or more
     ///
     /// Uses `last_activity` (not `created_at`) so that active transfers
     /// are not interrupted while completed/abandoned rooms are cleaned up.
     /// Also prunes stale per-IP counters.
     pub fn cleanup_stale(&self, max_idle_secs: u64) -> usize {
-        let now = Instant::now();
+        let now = self.clock.instant();
+        let max_idle = Duration::from_secs(max_idle_secs);
         let mut removed = 0;
 
         self.rooms.retain(|_id, room| {
-            let idle_secs = now.duration_since(room.last_activity).as_secs();
-            if idle_secs > max_idle_secs {
+            let idle = now.saturating_duration_since(room.last_activity);
+            // Rooms kept only for resumption go once every token has expired
+            let abandoned = room.is_empty() && !room.has_live_ticket(now);
+            if idle >= max_idle || abandoned {
                 removed += 1;
                 false
             } else {
@@ -335,8 +551,7 @@ impl RoomManager {
         });
 
         self.multi_rooms.retain(|_id, room| {
-            let idle_secs = now.duration_since(room.last_activity).as_secs();
-            if idle_secs > max_idle_secs {
+            if now.saturating_duration_since(room.last_activity) >= max_idle {
                 removed += 1;
                 false
             } else {
@@ -380,6 +595,7 @@ impl RoomManager {
             Entry::Occupied(mut entry) => {
                 let room = entry.get_mut();
                 let (peer_id, existing) = room.add_peer(tx)?;
+                room.touch(self.clock.instant());
                 Ok((rx, peer_id, existing))
             }
             Entry::Vacant(entry) => {
@@ -388,7 +604,7 @@ impl RoomManager {
                 } else {
                     requested_capacity.min(self.max_peers_per_room)
                 };
-                let mut room = MultiRoom::new(capacity);
+                let mut room = MultiRoom::new(capacity, self.clock.instant());
                 let (peer_id, existing) = room.add_peer(tx)?;
                 entry.insert(room);
 
@@ -442,7 +658,7 @@ impl RoomManager {
     /// Touch multi-room activity timestamp
     pub fn touch_multi_room(&self, room_id: &RoomId) {
         if let Some(mut room) = self.multi_rooms.get_mut(room_id) {
-            room.touch();
+            room.touch(self.clock.instant());
         }
     }
 
@@ -451,6 +667,27 @@ impl RoomManager {
     pub fn room_count(&self) -> usize {
         self.rooms.len()
     }
+
+    /// Override the session resume window (tests)
+    #[cfg(test)]
+    fn with_resume_window(mut self, window: Duration) -> Self {
+        self.resume_window = window;
+        self
+    }
+}
+
+fn slot_index(is_peer_a: bool) -> usize {
+    if is_peer_a {
+        0
+    } else {
+        1
+    }
+}
+
+fn new_session_token() -> SessionToken {
+    let mut token = [0u8; SESSION_TOKEN_LEN];
+    rand::thread_rng().fill_bytes(&mut token);
+    token
 }
 
 /// Errors from room operations
@@ -462,6 +699,8 @@ pub enum RoomError {
     TooManyRooms,
     /// Too many rooms from a single IP address
     TooManyRoomsPerIp,
+    /// Session token unknown, already used or expired
+    InvalidSessionToken,
 }
 
 impl std::fmt::Display for RoomError {
@@ -470,6 +709,7 @@ impl std::fmt::Display for RoomError {
             Self::RoomFull => write!(f, "room is full"),
             Self::TooManyRooms => write!(f, "server at room capacity"),
             Self::TooManyRoomsPerIp => write!(f, "too many rooms from this IP"),
+            Self::InvalidSessionToken => write!(f, "invalid or expired session token"),
         }
     }
 }
@@ -545,7 +785,7 @@ mod tests {
 
     #[test]
     fn test_multi_room_add_peers() {
-        let mut room = MultiRoom::new(3);
+        let mut room = MultiRoom::new(3, Instant::now());
         let (tx1, _rx1) = mpsc::channel(32);
         let (tx2, _rx2) = mpsc::channel(32);
         let (tx3, _rx3) = mpsc::channel(32);
@@ -567,7 +807,7 @@ mod tests {
 
     #[test]
     fn test_multi_room_full() {
-        let mut room = MultiRoom::new(2);
+        let mut room = MultiRoom::new(2, Instant::now());
         let (tx1, _rx1) = mpsc::channel(32);
         let (tx2, _rx2) = mpsc::channel(32);
         let (tx3, _rx3) = mpsc::channel(32);
@@ -579,7 +819,7 @@ mod tests {
 
     #[test]
     fn test_multi_room_remove_peer() {
-        let mut room = MultiRoom::new(10);
+        let mut room = MultiRoom::new(10, Instant::now());
         let (tx1, _rx1) = mpsc::channel(32);
         let (tx2, _rx2) = mpsc::channel(32);
 
@@ -636,4 +876,120 @@ mod tests {
         // 6th peer should fail
         assert!(manager.join_multi(room_id, 20, None).is_err());
     }
+
+    #[test]
+    fn test_session_token_rebinds_slot() {
+        let manager = RoomManager::new(100);
+        let room_id = [7u8; 32];
+        let old_ip: std::net::IpAddr = "10.0.0.1".parse().unwrap();
+
+        let (_rx_a, _, _) = manager.join_with_ip(room_id, None).unwrap();
+        manager.issue_session_token(&room_id, true).unwrap();
+        let (_rx_b, _, _) = manager.join_with_ip(room_id, Some(old_ip)).unwrap();
+        let (token_b, gen_b) = manager.issue_session_token(&room_id, false).unwrap();
+
+        // Peer B's network drops; the room survives for resumption
+        manager.session_peer_disconnected(&room_id, false, gen_b, Some(old_ip));
+        assert_eq!(manager.room_count(), 1);
+        assert!(manager.get_peer_sender(&room_id, true).is_none());
+
+        // Peer B resumes and is rebound to its original slot
+        let mut resumed = manager.resume_session(&room_id, &token_b).unwrap();
+        assert!(!resumed.is_peer_a);
+        assert_ne!(resumed.token, token_b);
+
+        let to_b = manager.get_peer_sender(&room_id, true).unwrap();
+        to_b.try_send(vec![1, 2, 3]).unwrap();
+        assert_eq!(resumed.receiver.try_recv().unwrap(), vec![1, 2, 3]);
+
+        // Tokens are single-use
+        assert!(matches!(
+            manager.resume_session(&room_id, &token_b),
+            Err(RoomError::InvalidSessionToken)
+        ));
+        // Peer A's connection was never disturbed
+        assert!(manager.get_peer_sender(&room_id, false).is_some());
+    }
+
+    #[test]
+    fn test_session_token_forged_or_expired_rejected() {
+        let manager = RoomManager::new(100).with_resume_window(Duration::ZERO);
+        let room_id = [8u8; 32];
+
+        manager.join_with_ip(room_id, None).unwrap();
+        manager.issue_session_token(&room_id, true).unwrap();
+        manager.join_with_ip(room_id, None).unwrap();
+        let (token_b, gen_b) = manager.issue_session_token(&room_id, false).unwrap();
+
+        // Forged token, and a real token for the wrong room
+        assert!(matches!(
+            manager.resume_session(&room_id, &[0u8; 32]),
+            Err(RoomError::InvalidSessionToken)
+        ));
+        assert!(matches!(
+            manager.resume_session(&[9u8; 32], &token_b),
+            Err(RoomError::InvalidSessionToken)
+        ));
+
+        // With a zero resume window, the token is dead as soon as B drops
+        manager.session_peer_disconnected(&room_id, false, gen_b, None);
+        assert!(matches!(
+            manager.resume_session(&room_id, &token_b),
+            Err(RoomError::InvalidSessionToken)
+        ));
+    }
+
+    #[test]
+    fn test_superseded_connection_teardown_ignored() {
+        let manager = RoomManager::new(100);
+        let room_id = [10u8; 32];
+
+        manager.join_with_ip(room_id, None).unwrap();
+        let (token_a, gen_a) = manager.issue_session_token(&room_id, true).unwrap();
+        manager.join_with_ip(room_id, None).unwrap();
+        manager.issue_session_token(&room_id, false).unwrap();
+
+        // Peer A resumes before the relay notices its old connection died
+        let resumed = manager.resume_session(&room_id, &token_a).unwrap();
+        assert!(resumed.is_peer_a);
+        manager.session_peer_disconnected(&room_id, true, gen_a, None);
+
+        // The resumed connection still owns slot A
+        assert!(manager.get_peer_sender(&room_id, false).is_some());
+    }
+
+    #[test]
+    fn test_unpaired_disconnect_removes_room() {
+        let manager = RoomManager::new(100);
+        let room_id = [11u8; 32];
+
+        manager.join_with_ip(room_id, None).unwrap();
+        let (token_a, gen_a) = manager.issue_session_token(&room_id, true).unwrap();
+        manager.session_peer_disconnected(&room_id, true, gen_a, None);
+
+        assert_eq!(manager.room_count(), 0);
+        assert!(manager.resume_session(&room_id, &token_a).is_err());
+    }
+
+    #[test]
+    fn test_idle_rooms_time_out_at_configured_duration() {
+        let clock = Arc::new(tallow_crypto::clock::MockClock::new(0));
+        let manager = RoomManager::new(100).with_clock(clock.clone());
+        manager.join_with_ip([1u8; 32], None).unwrap();
+        let _multi = manager.join_multi([2u8; 32], 3, None).unwrap();
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(manager.cleanup_stale(60), 0);
+
+        // Forwarding data restarts the 2-peer room's idle timer
+        manager.touch_room(&[1u8; 32]);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(manager.cleanup_stale(60), 1);
+        assert!(manager.get_multi_room(&[2u8; 32]).is_none());
+        assert_eq!(manager.room_count(), 1);
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(manager.cleanup_stale(60), 1);
+        assert_eq!(manager.room_count(), 0);
+    }
 }
@@ -4,9 +4,9 @@
 //! encrypted bytes bidirectionally without inspection.
 
 use crate::auth;
-use crate::config::RelayConfig;
+use crate::config::{AuthorizedKey, RelayConfig};
 use crate::rate_limit::RateLimiter;
-use crate::room::{RoomId, RoomManager};
+use crate::room::{PeerReceiver, RoomId, RoomManager, SessionToken, SESSION_TOKEN_LEN};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -65,6 +65,14 @@ impl RelayServer {
         let endpoint = quinn::Endpoint::server(server_config, addr)?;
         info!("relay server listening on {} (QUIC)", addr);
 
+        let authorized_keys = Arc::new(self.config.authorized_keys.clone());
+        if !authorized_keys.is_empty() {
+            info!(
+                "bearer token auth enabled ({} authorized key(s))",
+                authorized_keys.len()
+            );
+        }
+
         // Start WebSocket listener if configured
         if !self.config.ws_bind_addr.is_empty() {
             let ws_addr: SocketAddr = self
@@ -75,6 +83,7 @@ impl RelayServer {
             let ws_state = Arc::new(crate::websocket::WsState {
                 room_manager: Arc::clone(&self.room_manager),
                 password: zeroize::Zeroizing::new(self.config.password.clone()),
+                authorized_keys: Arc::clone(&authorized_keys),
             });
             let app = crate::websocket::ws_router(ws_state);
             let listener = tokio::net::TcpListener::bind(ws_addr).await?;
@@ -139,14 +148,20 @@ impl RelayServer {
 
             let room_manager = Arc::clone(&self.room_manager);
             let password = self.config.password.clone();
+            let authorized_keys = Arc::clone(&authorized_keys);
 
             tokio::spawn(async move {
                 match incoming.await {
                     Ok(connection) => {
                         tracing::debug!("accepted connection from {}", remote_addr);
-                        if let Err(e) =
-                            handle_connection(connection, room_manager, password, remote_addr.ip())
-                                .await
+                        if let Err(e) = handle_connection(
+                            connection,
+                            room_manager,
+                            password,
+                            &authorized_keys,
+                            remote_addr.ip(),
+                        )
+                        .await
                         {
                             warn!("connection handler error: {}", e);
                         }
@@ -163,10 +178,19 @@ impl RelayServer {
     }
 }
 
-/// Parsed room join — either legacy 2-peer or multi-peer
+/// Parsed room join — legacy 2-peer, multi-peer, or a 2-peer session resume
 enum ParsedRoomJoin {
     Legacy(RoomJoinParsed),
     Multi(MultiRoomJoinParsed),
+    Resume(RoomResumeParsed),
+}
+
+/// Parsed fields from a RoomResume message
+struct RoomResumeParsed {
+    /// Room identifier (32-byte BLAKE3 hash)
+    room_id: RoomId,
+    /// Session token issued when the peer first joined
+    session_token: Vec<u8>,
 }
 
 /// Parsed fields from a multi-peer room join
@@ -175,6 +199,8 @@ struct MultiRoomJoinParsed {
     room_id: RoomId,
     /// Optional BLAKE3 hash of relay password
     password_hash: Option<[u8; 32]>,
+    /// Optional serialized bearer token
+    token: Option<Vec<u8>>,
     /// Requested room capacity
     requested_capacity: u8,
 }
@@ -187,6 +213,7 @@ async fn handle_connection(
     connection: quinn::Connection,
     room_manager: Arc<RoomManager>,
     mut password: String,
+    authorized_keys: &[AuthorizedKey],
     client_ip: std::net::IpAddr,
 ) -> anyhow::Result<()> {
     // Accept bidirectional stream from client with handshake timeout.
@@ -218,14 +245,27 @@ async fn handle_connection(
     .await
     .map_err(|_| anyhow::anyhow!("handshake timeout ({}s)", HANDSHAKE_TIMEOUT.as_secs()))??;
 
-    // Extract password hash for auth check
-    let pw_hash = match &join {
-        ParsedRoomJoin::Legacy(j) => j.password_hash.as_ref(),
-        ParsedRoomJoin::Multi(j) => j.password_hash.as_ref(),
+    // Verify password or bearer token authentication. A session token is
+    // itself a capability: it was only handed out after the original join
+    // passed this check.
+    let authenticated = match &join {
+        ParsedRoomJoin::Legacy(j) => auth::verify_relay_access(
+            j.password_hash.as_ref(),
+            j.token.as_deref(),
+            &password,
+            authorized_keys,
+            room_manager.clock(),
+        ),
+        ParsedRoomJoin::Multi(j) => auth::verify_relay_access(
+            j.password_hash.as_ref(),
+            j.token.as_deref(),
+            &password,
+            authorized_keys,
+            room_manager.clock(),
+        ),
+        ParsedRoomJoin::Resume(_) => true,
     };
-
-    // Verify password authentication
-    if !auth::verify_relay_password(pw_hash, &password) {
+    if !authenticated {
         warn!("authentication failed");
         let reject = encode_auth_rejection();
         let _ = send.write_all(&reject).await;
@@ -242,13 +282,16 @@ async fn handle_connection(
         ParsedRoomJoin::Multi(multi_join) => {
             handle_multi_connection(send, recv, room_manager, multi_join, client_ip).await
         }
+        ParsedRoomJoin::Resume(resume) => {
+            handle_resume_connection(send, recv, room_manager, resume, client_ip).await
+        }
     }
 }
 
 /// Handle a legacy 2-peer room connection (existing behavior)
 async fn handle_legacy_connection(
     mut send: quinn::SendStream,
-    mut recv: quinn::RecvStream,
+    recv: quinn::RecvStream,
     room_manager: Arc<RoomManager>,
     join: RoomJoinParsed,
     client_ip: std::net::IpAddr,
@@ -258,14 +301,19 @@ async fn handle_legacy_connection(
     tracing::debug!("peer joining room (legacy 2-peer)");
 
     // Join the room with per-IP tracking
-    let (mut peer_rx, _peer_tx, peer_present) = room_manager
+    let (peer_rx, _peer_tx, peer_present) = room_manager
         .join_with_ip(room_id, Some(client_ip))
         .map_err(|e| anyhow::anyhow!("room join failed: {}", e))?;
 
     let is_peer_a = !peer_present;
 
+    // Issue a session token so the peer can resume after a network change
+    let (session_token, generation) = room_manager
+        .issue_session_token(&room_id, is_peer_a)
+        .ok_or_else(|| anyhow::anyhow!("room vanished during join"))?;
+
     // Send RoomJoined response
-    let joined_msg = encode_room_joined(peer_present)?;
+    let joined_msg = encode_room_joined_with_token(peer_present, &session_token);
     send.write_all(&joined_msg)
         .await
         .map_err(|e| anyhow::anyhow!("send room joined failed: {}", e))?;
@@ -281,10 +329,80 @@ async fn handle_legacy_connection(
         tracing::debug!("waiting for peer in room");
     }
 
-    // Forward data bidirectionally
-    let room_id_fwd = room_id;
-    let room_manager_fwd = Arc::clone(&room_manager);
+    forward_legacy(send, recv, peer_rx, &room_manager, room_id, is_peer_a).await;
 
+    tracing::debug!("peer disconnected from room");
+    room_manager.session_peer_disconnected(&room_id, is_peer_a, generation, Some(client_ip));
+
+    Ok(())
+}
+
+/// Handle a 2-peer session resume from a (possibly new) client address
+///
+/// On success the peer's room slot is rebound to this connection and a fresh
+/// session token is returned; the presented token cannot be used again.
+async fn handle_resume_connection(
+    mut send: quinn::SendStream,
+    recv: quinn::RecvStream,
+    room_manager: Arc<RoomManager>,
+    resume: RoomResumeParsed,
+    client_ip: std::net::IpAddr,
+) -> anyhow::Result<()> {
+    let room_id = resume.room_id;
+
+    let resumed = match room_manager.resume_session(&room_id, &resume.session_token) {
+        Ok(resumed) => resumed,
+        Err(e) => {
+            warn!("session resume rejected: {}", e);
+            let reject = encode_auth_rejection();
+            let _ = send.write_all(&reject).await;
+            return Ok(());
+        }
+    };
+
+    tracing::debug!("peer resumed session in room");
+
+    // The superseded connection already released its per-IP count (or will
+    // when it notices the drop), so count this one afresh
+    room_manager.track_ip(Some(client_ip));
+
+    let resumed_msg = encode_session_resumed(&resumed.token);
+    send.write_all(&resumed_msg)
+        .await
+        .map_err(|e| anyhow::anyhow!("send session resumed failed: {}", e))?;
+
+    forward_legacy(
+        send,
+        recv,
+        resumed.receiver,
+        &room_manager,
+        room_id,
+        resumed.is_peer_a,
+    )
+    .await;
+
+    tracing::debug!("resumed peer disconnected from room");
+    room_manager.session_peer_disconnected(
+        &room_id,
+        resumed.is_peer_a,
+        resumed.generation,
+        Some(client_ip),
+    );
+
+    Ok(())
+}
+
+/// Forward data bidirectionally between a 2-peer room slot and its QUIC stream
+///
+/// Returns when either direction closes.
+async fn forward_legacy(
+    mut send: quinn::SendStream,
+    mut recv: quinn::RecvStream,
+    mut peer_rx: PeerReceiver,
+    room_manager: &RoomManager,
+    room_id: RoomId,
+    is_peer_a: bool,
+) {
     // Task 1: Read from QUIC stream → send to paired peer via channel
     let forward_to_peer = async {
         loop {
@@ -306,11 +424,11 @@ async fn handle_legacy_connection(
                 Err(_) => break,
             }
 
-            if let Some(tx) = room_manager_fwd.get_peer_sender(&room_id_fwd, is_peer_a) {
+            if let Some(tx) = room_manager.get_peer_sender(&room_id, is_peer_a) {
                 if tx.send(data).await.is_err() {
                     break;
                 }
-                room_manager_fwd.touch_room(&room_id_fwd);
+                room_manager.touch_room(&room_id);
             }
         }
     };
@@ -328,11 +446,6 @@ async fn handle_legacy_connection(
         _ = forward_to_peer => {}
         _ = forward_from_peer => {}
     }
-
-    tracing::debug!("peer disconnected from room");
-    room_manager.peer_disconnected(&room_id, Some(client_ip));
-
-    Ok(())
 }
 
 /// Handle a multi-peer room connection (Phase 19)
@@ -488,6 +601,8 @@ struct RoomJoinParsed {
     room_id: RoomId,
     /// Optional BLAKE3 hash of relay password
     password_hash: Option<[u8; 32]>,
+    /// Optional serialized bearer token
+    token: Option<Vec<u8>>,
 }
 
 /// Parse a room join message, dispatching to legacy or multi-peer path.
@@ -524,6 +639,7 @@ fn parse_room_join_dispatch(data: &[u8]) -> anyhow::Result<ParsedRoomJoin> {
                 return Ok(ParsedRoomJoin::Multi(MultiRoomJoinParsed {
                     room_id: rid,
                     password_hash: pw,
+                    token: None,
                     requested_capacity,
                 }));
             }
@@ -548,9 +664,54 @@ fn parse_room_join_dispatch(data: &[u8]) -> anyhow::Result<ParsedRoomJoin> {
                 return Ok(ParsedRoomJoin::Legacy(RoomJoinParsed {
                     room_id: rid,
                     password_hash: pw,
+                    token: None,
+                }));
+            }
+            tallow_protocol::wire::Message::RoomJoinToken {
+                room_id,
+                token,
+                requested_capacity,
+            } => {
+                if room_id.len() != 32 {
+                    anyhow::bail!("invalid room_id length: {}", room_id.len());
+                }
+                if token.len() > auth::MAX_TOKEN_LEN {
+                    anyhow::bail!("token too large: {} bytes", token.len());
+                }
+                let mut rid = [0u8; 32];
+                rid.copy_from_slice(&room_id);
+                return Ok(match requested_capacity {
+                    Some(requested_capacity) => ParsedRoomJoin::Multi(MultiRoomJoinParsed {
+                        room_id: rid,
+                        password_hash: None,
+                        token: Some(token),
+                        requested_capacity,
+                    }),
+                    None => ParsedRoomJoin::Legacy(RoomJoinParsed {
+                        room_id: rid,
+                        password_hash: None,
+                        token: Some(token),
+                    }),
+                });
+            }
+            tallow_protocol::wire::Message::RoomResume {
+                room_id,
+                session_token,
+            } => {
+                if room_id.len() != 32 {
+                    anyhow::bail!("invalid room_id length: {}", room_id.len());
+                }
+                if session_token.len() != SESSION_TOKEN_LEN {
+                    anyhow::bail!("invalid session token length: {}", session_token.len());
+                }
+                let mut rid = [0u8; 32];
+                rid.copy_from_slice(&room_id);
+                return Ok(ParsedRoomJoin::Resume(RoomResumeParsed {
+                    room_id: rid,
+                    session_token,
                 }));
             }
-            _ => anyhow::bail!("expected RoomJoin or RoomJoinMulti"),
+            _ => anyhow::bail!("expected RoomJoin, RoomJoinMulti, RoomJoinToken or RoomResume"),
         }
     }
 
@@ -597,6 +758,7 @@ fn parse_room_join_legacy(data: &[u8]) -> anyhow::Result<RoomJoinParsed> {
         Ok(RoomJoinParsed {
             room_id,
             password_hash,
+            token: None,
         })
     } else if data.len() >= 34 {
         let mut room_id = [0u8; 32];
@@ -604,6 +766,7 @@ fn parse_room_join_legacy(data: &[u8]) -> anyhow::Result<RoomJoinParsed> {
         Ok(RoomJoinParsed {
             room_id,
             password_hash: None,
+            token: None,
         })
     } else if data.len() == 32 {
         let mut room_id = [0u8; 32];
@@ -611,6 +774,7 @@ fn parse_room_join_legacy(data: &[u8]) -> anyhow::Result<RoomJoinParsed> {
         Ok(RoomJoinParsed {
             room_id,
             password_hash: None,
+            token: None,
         })
     } else {
         anyhow::bail!("invalid room join message length: {}", data.len());
@@ -628,14 +792,31 @@ fn encode_auth_rejection() -> Vec<u8> {
     msg
 }
 
-/// Encode a RoomJoined response as length-prefixed bytes
-fn encode_room_joined(peer_present: bool) -> anyhow::Result<Vec<u8>> {
-    // Simple response: 1 byte for peer_present flag
-    let payload = vec![if peer_present { 1u8 } else { 0u8 }];
+/// Encode a RoomJoined response carrying a session token
+///
+/// Payload is the peer_present flag followed by the 32-byte session token.
+/// Older clients only read the first byte, so the token is ignored by them.
+fn encode_room_joined_with_token(peer_present: bool, token: &SessionToken) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + token.len());
+    payload.push(if peer_present { 1u8 } else { 0u8 });
+    payload.extend_from_slice(token);
     let mut msg = Vec::with_capacity(4 + payload.len());
     msg.extend_from_slice(&(payload.len() as u32).to_be_bytes());
     msg.extend_from_slice(&payload);
-    Ok(msg)
+    msg
+}
+
+/// Encode a SessionResumed response as length-prefixed bytes
+///
+/// Payload is `3` followed by the replacement 32-byte session token.
+fn encode_session_resumed(token: &SessionToken) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + token.len());
+    payload.push(3u8); // SessionResumed indicator
+    payload.extend_from_slice(token);
+    let mut msg = Vec::with_capacity(4 + payload.len());
+    msg.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    msg.extend_from_slice(&payload);
+    msg
 }
 
 /// Encode a PeerArrived notification as length-prefixed bytes
@@ -653,9 +834,73 @@ mod tests {
 
     #[test]
     fn test_encode_room_joined() {
-        let msg = encode_room_joined(true).unwrap();
-        assert_eq!(msg.len(), 5); // 4 byte length + 1 byte payload
-        assert_eq!(u32::from_be_bytes([msg[0], msg[1], msg[2], msg[3]]), 1);
+        let msg = encode_room_joined_with_token(true, &[0xAB; 32]);
+        assert_eq!(msg.len(), 37); // 4 byte length + 1 byte flag + 32 byte token
+        assert_eq!(u32::from_be_bytes([msg[0], msg[1], msg[2], msg[3]]), 33);
         assert_eq!(msg[4], 1);
+        assert_eq!(&msg[5..], &[0xAB; 32]);
+    }
+
+    #[test]
+    fn test_encode_session_resumed() {
+        let msg = encode_session_resumed(&[0xCD; 32]);
+        assert_eq!(u32::from_be_bytes([msg[0], msg[1], msg[2], msg[3]]), 33);
+        assert_eq!(msg[4], 3);
+        assert_eq!(&msg[5..], &[0xCD; 32]);
+    }
+
+    #[test]
+    fn test_parse_room_resume() {
+        let data = postcard::to_stdvec(&tallow_protocol::wire::Message::RoomResume {
+            room_id: vec![7u8; 32],
+            session_token: vec![9u8; 32],
+        })
+        .unwrap();
+        match parse_room_join_dispatch(&data).unwrap() {
+            ParsedRoomJoin::Resume(r) => {
+                assert_eq!(r.room_id, [7u8; 32]);
+                assert_eq!(r.session_token, vec![9u8; 32]);
+            }
+            _ => panic!("expected resume"),
+        }
+
+        let short = postcard::to_stdvec(&tallow_protocol::wire::Message::RoomResume {
+            room_id: vec![7u8; 32],
+            session_token: vec![9u8; 16],
+        })
+        .unwrap();
+        assert!(parse_room_join_dispatch(&short).is_err());
+    }
+
+    #[test]
+    fn test_parse_room_join_token() {
+        let legacy = postcard::to_stdvec(&tallow_protocol::wire::Message::RoomJoinToken {
+            room_id: vec![7u8; 32],
+            token: vec![1, 2, 3],
+            requested_capacity: None,
+        })
+        .unwrap();
+        match parse_room_join_dispatch(&legacy).unwrap() {
+            ParsedRoomJoin::Legacy(j) => {
+                assert_eq!(j.room_id, [7u8; 32]);
+                assert_eq!(j.token.as_deref(), Some(&[1u8, 2, 3][..]));
+                assert!(j.password_hash.is_none());
+            }
+            ParsedRoomJoin::Multi(_) => panic!("expected legacy join"),
+        }
+
+        let multi = postcard::to_stdvec(&tallow_protocol::wire::Message::RoomJoinToken {
+            room_id: vec![7u8; 32],
+            token: vec![1, 2, 3],
+            requested_capacity: Some(4),
+        })
+        .unwrap();
+        match parse_room_join_dispatch(&multi).unwrap() {
+            ParsedRoomJoin::Multi(j) => {
+                assert_eq!(j.requested_capacity, 4);
+                assert!(j.token.is_some());
+            }
+            ParsedRoomJoin::Legacy(_) => panic!("expected multi-peer join"),
+        }
     }
 }
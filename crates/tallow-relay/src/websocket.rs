@@ -27,6 +27,7 @@ use tower_http::cors::{Any, CorsLayer};
 use tracing::{debug, info, warn};
 
 use crate::auth;
+use crate::config::AuthorizedKey;
 use crate::room::RoomManager;
 
 /// Timeout for the initial WebSocket room join message
@@ -42,6 +43,8 @@ pub struct WsState {
     /// Relay password (empty = open relay, no authentication required).
     /// Wrapped in `Zeroizing` so the password is wiped from memory on drop.
     pub password: zeroize::Zeroizing<String>,
+    /// Keys allowed to sign bearer tokens (shared with QUIC path)
+    pub authorized_keys: Arc<Vec<AuthorizedKey>>,
 }
 
 /// Create the axum Router for WebSocket connections
@@ -83,8 +86,9 @@ async fn ws_handler(
 
 /// Handle a single WebSocket client connection
 ///
-/// 1. Read first message (RoomJoin or RoomJoinMulti, postcard-encoded, no length prefix)
-/// 2. Authenticate if password required
+/// 1. Read first message (RoomJoin, RoomJoinMulti or RoomJoinToken, postcard-encoded,
+///    no length prefix)
+/// 2. Authenticate if a password or token keys are configured
 /// 3. Join room via RoomManager
 /// 4. Send room joined response
 /// 5. Bridge bidirectionally between WebSocket and room channels
@@ -132,6 +136,7 @@ async fn handle_ws_client_inner(
                 ws_stream,
                 &room_id,
                 password_hash.as_deref(),
+                None,
             )
             .await
         }
@@ -146,12 +151,42 @@ async fn handle_ws_client_inner(
                 ws_stream,
                 &room_id,
                 password_hash.as_deref(),
+                None,
                 requested_capacity,
             )
             .await
         }
+        tallow_protocol::wire::Message::RoomJoinToken {
+            room_id,
+            token,
+            requested_capacity,
+        } => match requested_capacity {
+            Some(requested_capacity) => {
+                handle_ws_multi_join(
+                    &state,
+                    &mut ws_sink,
+                    ws_stream,
+                    &room_id,
+                    None,
+                    Some(token.as_slice()),
+                    requested_capacity,
+                )
+                .await
+            }
+            None => {
+                handle_ws_legacy_join(
+                    &state,
+                    &mut ws_sink,
+                    ws_stream,
+                    &room_id,
+                    None,
+                    Some(token.as_slice()),
+                )
+                .await
+            }
+        },
         _ => Err(WsError::InvalidMessage(
-            "expected RoomJoin or RoomJoinMulti".to_string(),
+            "expected RoomJoin, RoomJoinMulti or RoomJoinToken".to_string(),
         )),
     }
 }
@@ -163,6 +198,7 @@ async fn handle_ws_legacy_join(
     ws_stream: futures::stream::SplitStream<WebSocket>,
     room_id_bytes: &[u8],
     password_hash: Option<&[u8]>,
+    token: Option<&[u8]>,
 ) -> Result<(), WsError> {
     // Validate room_id
     if room_id_bytes.len() != 32 {
@@ -176,7 +212,13 @@ async fn handle_ws_legacy_join(
 
     // Auth check
     let pw_hash: Option<[u8; 32]> = extract_password_hash(password_hash);
-    if !auth::verify_relay_password(pw_hash.as_ref(), &state.password) {
+    if !auth::verify_relay_access(
+        pw_hash.as_ref(),
+        token,
+        &state.password,
+        &state.authorized_keys,
+        state.room_manager.clock(),
+    ) {
         warn!("WebSocket auth failed");
         let reject_payload = postcard::to_stdvec(
             &tallow_protocol::wire::Message::HandshakeFailed {
@@ -240,6 +282,7 @@ async fn handle_ws_multi_join(
     ws_stream: futures::stream::SplitStream<WebSocket>,
     room_id_bytes: &[u8],
     password_hash: Option<&[u8]>,
+    token: Option<&[u8]>,
     requested_capacity: u8,
 ) -> Result<(), WsError> {
     // Validate room_id
@@ -254,7 +297,13 @@ async fn handle_ws_multi_join(
 
     // Auth check
     let pw_hash: Option<[u8; 32]> = extract_password_hash(password_hash);
-    if !auth::verify_relay_password(pw_hash.as_ref(), &state.password) {
+    if !auth::verify_relay_access(
+        pw_hash.as_ref(),
+        token,
+        &state.password,
+        &state.authorized_keys,
+        state.room_manager.clock(),
+    ) {
         warn!("WebSocket auth failed (multi)");
         let reject_payload = postcard::to_stdvec(
             &tallow_protocol::wire::Message::HandshakeFailed {
@@ -593,6 +642,7 @@ mod tests {
         let state = Arc::new(WsState {
             room_manager,
             password: String::new().into(),
+            authorized_keys: Arc::new(Vec::new()),
         });
         // Should not panic
         let _router = ws_router(state);
@@ -608,6 +658,7 @@ mod tests {
         let state = Arc::new(WsState {
             room_manager,
             password: String::new().into(),
+            authorized_keys: Arc::new(Vec::new()),
         });
         let app = ws_router(state);
 
@@ -680,6 +731,7 @@ mod tests {
         let state = Arc::new(WsState {
             room_manager: Arc::clone(&room_manager),
             password: String::new().into(),
+            authorized_keys: Arc::new(Vec::new()),
         });
         // Verify same Arc instance
         assert_eq!(Arc::as_ptr(&state.room_manager), rm_ptr);
@@ -695,6 +747,7 @@ mod tests {
         let state = Arc::new(WsState {
             room_manager,
             password: String::new().into(),
+            authorized_keys: Arc::new(Vec::new()),
         });
         let app = ws_router(state);
 
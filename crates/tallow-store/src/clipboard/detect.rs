@@ -85,6 +85,94 @@ pub fn detect_image_format(bytes: &[u8]) -> ImageFormat {
     ImageFormat::Unknown
 }
 
+/// Read pixel dimensions (width, height) from an image header
+///
+/// Only the header is parsed; no pixel data is decoded. Supports PNG, JPEG
+/// (SOF marker scan), GIF, BMP, WebP (VP8, VP8L, VP8X) and ICO (first
+/// entry). Returns `None` for truncated headers and for formats without a
+/// fixed raster size (SVG) or with a complex layout (TIFF).
+pub fn image_dimensions(bytes: &[u8], format: &ImageFormat) -> Option<(u32, u32)> {
+    let le16 = |o: usize| Some(u16::from_le_bytes(bytes.get(o..o + 2)?.try_into().ok()?) as u32);
+    let be32 = |o: usize| Some(u32::from_be_bytes(bytes.get(o..o + 4)?.try_into().ok()?));
+    let le32 = |o: usize| Some(u32::from_le_bytes(bytes.get(o..o + 4)?.try_into().ok()?));
+    let le24 = |o: usize| {
+        let b = bytes.get(o..o + 3)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+    };
+
+    match format {
+        ImageFormat::Png => {
+            if bytes.get(12..16)? != b"IHDR" {
+                return None;
+            }
+            Some((be32(16)?, be32(20)?))
+        }
+        ImageFormat::Gif => Some((le16(6)?, le16(8)?)),
+        ImageFormat::Bmp => {
+            // OS/2 core header uses 16-bit sizes; later headers use signed
+            // 32-bit sizes (negative height = top-down rows)
+            if le32(14)? == 12 {
+                Some((le16(18)?, le16(20)?))
+            } else {
+                let width = le32(18)? as i32;
+                let height = le32(22)? as i32;
+                Some((width.unsigned_abs(), height.unsigned_abs()))
+            }
+        }
+        ImageFormat::Jpeg => jpeg_dimensions(bytes),
+        ImageFormat::Webp => match bytes.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF)),
+            b"VP8L" => {
+                let bits = le32(21)?;
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        },
+        ImageFormat::Ico => {
+            // A stored 0 means 256 pixels
+            let side = |b: u8| if b == 0 { 256 } else { b as u32 };
+            Some((side(*bytes.get(6)?), side(*bytes.get(7)?)))
+        }
+        ImageFormat::Svg | ImageFormat::Tiff | ImageFormat::Unknown => None,
+    }
+}
+
+/// Walk JPEG segments up to the first start-of-frame marker
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2; // skip SOI
+    loop {
+        // Markers are 0xFF followed by a non-0xFF code (0xFF bytes are fill)
+        if *bytes.get(i)? != 0xFF {
+            return None;
+        }
+        while *bytes.get(i)? == 0xFF {
+            i += 1;
+        }
+        let marker = *bytes.get(i)?;
+        match marker {
+            // Standalone markers carry no length
+            0x01 | 0xD0..=0xD7 => {
+                i += 1;
+                continue;
+            }
+            // End of image / start of scan before any frame header
+            0xD9 | 0xDA => return None,
+            // SOF0-SOF15, excluding DHT (C4), JPG (C8) and DAC (CC)
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let seg = bytes.get(i + 4..i + 8)?;
+                let height = u16::from_be_bytes([seg[0], seg[1]]) as u32;
+                let width = u16::from_be_bytes([seg[2], seg[3]]) as u32;
+                return Some((width, height));
+            }
+            _ => {
+                let len = bytes.get(i + 1..i + 3)?;
+                i += 1 + u16::from_be_bytes([len[0], len[1]]) as usize;
+            }
+        }
+    }
+}
+
 /// Check if text looks like a URL
 fn is_url(text: &str) -> bool {
     // Multi-line text is not a URL
@@ -251,6 +339,33 @@ mod tests {
         assert_eq!(detect_image_format(bytes), ImageFormat::Webp);
     }
 
+    #[test]
+    fn test_image_dimensions_png_and_gif() {
+        let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png, &ImageFormat::Png), Some((640, 480)));
+        assert_eq!(image_dimensions(&png[..18], &ImageFormat::Png), None);
+
+        let gif = b"GIF89a\x20\x03\x58\x02";
+        assert_eq!(image_dimensions(gif, &ImageFormat::Gif), Some((800, 600)));
+    }
+
+    #[test]
+    fn test_image_dimensions_jpeg_skips_segments() {
+        let jpeg = [
+            0xFF, 0xD8, // SOI
+            0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46, // APP0, 2 bytes payload
+            0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80, // SOF0: 480 x 640
+        ];
+        assert_eq!(
+            image_dimensions(&jpeg, &ImageFormat::Jpeg),
+            Some((640, 480))
+        );
+        assert_eq!(image_dimensions(&jpeg[..10], &ImageFormat::Jpeg), None);
+    }
+
     #[test]
     fn test_detect_tiff_le() {
         let bytes = [0x49, 0x49, 0x2A, 0x00, 0x08, 0x00];
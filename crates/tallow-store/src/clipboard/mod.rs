@@ -6,11 +6,13 @@
 pub mod detect;
 pub mod preview;
 
+use crate::config::ClipboardConfig;
 use crate::persistence::paths;
 use crate::Result;
 use crate::StoreError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Content type of a clipboard entry
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -123,12 +125,92 @@ pub struct ClipboardEntry {
     pub image_path: Option<PathBuf>,
     /// Full text content (only for text entries, not images)
     pub text_content: Option<String>,
+    /// Pinned entries are never evicted
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Largest payload `tallow receive --to-clipboard` will place on the clipboard
+pub const MAX_CLIPBOARD_RECEIVE_SIZE: u64 = 1024 * 1024;
+
+/// Why received content cannot go to the clipboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardRefusal {
+    /// Content is larger than the clipboard cap
+    TooLarge {
+        /// Content size in bytes
+        size: u64,
+        /// Cap in bytes
+        max: u64,
+    },
+    /// Content is binary or a file transfer rather than text
+    NotText,
+}
+
+impl std::fmt::Display for ClipboardRefusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { size, max } => write!(
+                f,
+                "content is {} bytes, over the {} byte clipboard limit; receive it to a file instead (omit --to-clipboard)",
+                size, max
+            ),
+            Self::NotText => write!(
+                f,
+                "only text can be received to the clipboard; receive it to a file instead (omit --to-clipboard)"
+            ),
+        }
+    }
+}
+
+/// Check a payload size against the clipboard cap before receiving it
+pub fn check_clipboard_size(size: u64, max: u64) -> std::result::Result<(), ClipboardRefusal> {
+    if size > max {
+        return Err(ClipboardRefusal::TooLarge { size, max });
+    }
+    Ok(())
+}
+
+impl ClipboardEntry {
+    /// Build a history entry for text received from a peer
+    ///
+    /// Refuses content over `max` bytes and content that is not UTF-8 text.
+    pub fn from_received_text(
+        data: &[u8],
+        max: u64,
+    ) -> std::result::Result<Self, ClipboardRefusal> {
+        check_clipboard_size(data.len() as u64, max)?;
+        let text = std::str::from_utf8(data).map_err(|_| ClipboardRefusal::NotText)?;
+        if text.contains('\0') {
+            return Err(ClipboardRefusal::NotText);
+        }
+
+        let hash = blake3::hash(data).to_hex().to_string();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Ok(Self {
+            id: hash.clone(),
+            content_type: detect::detect_content_type(text),
+            preview: preview::generate_preview(text, 80),
+            size: data.len() as u64,
+            timestamp,
+            blake3_hash: hash,
+            image_path: None,
+            text_content: Some(text.to_string()),
+            pinned: false,
+        })
+    }
 }
 
 /// Clipboard history log with JSON file persistence
 ///
-/// Entries are unlimited in count. Image data is stored as separate files
-/// in `data_dir()/clipboard_images/` to keep the JSON file small.
+/// Entries are unlimited unless limits are set with
+/// [`with_limits`](Self::with_limits); appending then evicts the oldest
+/// unpinned entries and their image files. Image data is stored as separate
+/// files in `data_dir()/clipboard_images/` to keep the JSON file small.
 ///
 /// **Security note**: Text content is stored in plaintext JSON on disk.
 /// File permissions are restricted to owner-only (0o600 on Unix), but the
@@ -139,6 +221,9 @@ pub struct ClipboardEntry {
 pub struct ClipboardHistory {
     entries: Vec<ClipboardEntry>,
     path: Option<PathBuf>,
+    images_dir: PathBuf,
+    max_entries: Option<usize>,
+    max_age: Option<Duration>,
 }
 
 impl ClipboardHistory {
@@ -147,6 +232,9 @@ impl ClipboardHistory {
         Self {
             entries: Vec::new(),
             path: None,
+            images_dir: paths::clipboard_images_dir(),
+            max_entries: None,
+            max_age: None,
         }
     }
 
@@ -155,11 +243,18 @@ impl ClipboardHistory {
         Self::open_at(paths::clipboard_history_file())
     }
 
+    /// Open persistent clipboard history at the default path, with the
+    /// retention limits from the user's `[clipboard]` config
+    pub fn open_with_config() -> Result<Self> {
+        let config = crate::config::load_config().unwrap_or_default();
+        Ok(Self::open()?.with_limits(&config.clipboard))
+    }
+
     /// Open persistent clipboard history at a custom path
     pub fn open_at(path: PathBuf) -> Result<Self> {
         let mut history = Self {
-            entries: Vec::new(),
             path: Some(path),
+            ..Self::new()
         };
 
         if let Some(ref p) = history.path {
@@ -177,12 +272,84 @@ impl ClipboardHistory {
         Ok(history)
     }
 
-    /// Append an entry and persist
+    /// Evict by `config` on every append; zero disables a limit
+    pub fn with_limits(mut self, config: &ClipboardConfig) -> Self {
+        self.max_entries = (config.max_entries > 0).then_some(config.max_entries);
+        self.max_age = (config.max_age_days > 0)
+            .then(|| Duration::from_secs(config.max_age_days.saturating_mul(24 * 60 * 60)));
+        self
+    }
+
+    /// Keep at most `max` unpinned entries
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
+    }
+
+    /// Evict unpinned entries older than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Directory image entries' files live in
+    pub fn with_images_dir(mut self, dir: PathBuf) -> Self {
+        self.images_dir = dir;
+        self
+    }
+
+    /// Append an entry, evict past the limits, and persist
     pub fn append(&mut self, entry: ClipboardEntry) -> Result<()> {
         self.entries.push(entry);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.evict(now);
         self.save()
     }
 
+    /// Pin or unpin the entry with `id` and persist
+    ///
+    /// Returns `false` if no entry has that ID.
+    pub fn set_pinned(&mut self, id: &str, pinned: bool) -> Result<bool> {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) else {
+            return Ok(false);
+        };
+        entry.pinned = pinned;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Drop unpinned entries older than the age limit, then the oldest
+    /// unpinned entries beyond the count limit, deleting their images
+    fn evict(&mut self, now: u64) {
+        let cutoff = self.max_age.map(|age| now.saturating_sub(age.as_secs()));
+        let unpinned = self.entries.iter().filter(|e| !e.pinned).count();
+        let mut excess = self
+            .max_entries
+            .map_or(0, |max| unpinned.saturating_sub(max));
+
+        let mut evicted = Vec::new();
+        // Entries are kept in append order, so the first unpinned ones are the oldest
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+            let expired = cutoff.is_some_and(|cutoff| entry.timestamp < cutoff);
+            if expired || excess > 0 {
+                excess = excess.saturating_sub(1);
+                evicted.push(entry.image_path.clone());
+                return false;
+            }
+            true
+        });
+
+        for image in evicted.into_iter().flatten() {
+            remove_image(&self.images_dir, &image);
+        }
+    }
+
     /// Get all entries
     pub fn query(&self) -> &[ClipboardEntry] {
         &self.entries
@@ -211,9 +378,9 @@ impl ClipboardHistory {
     /// Clear all entries and persist
     pub fn clear(&mut self) -> Result<()> {
         // Also remove image files
-        let images_dir = paths::clipboard_images_dir();
+        let images_dir = &self.images_dir;
         if images_dir.exists() {
-            if let Err(e) = std::fs::remove_dir_all(&images_dir) {
+            if let Err(e) = std::fs::remove_dir_all(images_dir) {
                 tracing::warn!("Failed to remove clipboard images: {}", e);
             }
         }
@@ -268,6 +435,21 @@ impl Default for ClipboardHistory {
     }
 }
 
+/// Delete an evicted entry's image file
+///
+/// Only the file name of the stored path is used, so a tampered history
+/// file cannot point eviction outside the images directory.
+fn remove_image(images_dir: &Path, image: &Path) {
+    let Some(name) = image.file_name() else {
+        return;
+    };
+    match std::fs::remove_file(images_dir.join(name)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to remove clipboard image {:?}: {}", name, e),
+    }
+}
+
 /// Save image data to the clipboard images directory, returning the relative path.
 ///
 /// The `hash` parameter must be a hex-encoded string (alphanumeric only).
@@ -305,9 +487,17 @@ mod tests {
             blake3_hash: "abc123".to_string(),
             image_path: None,
             text_content: Some("Hello world".to_string()),
+            pinned: false,
         }
     }
 
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
     #[test]
     fn test_append_and_query() {
         let mut history = ClipboardHistory::new();
@@ -381,6 +571,139 @@ mod tests {
         assert!(!history.contains_hash("xyz789"));
     }
 
+    #[test]
+    fn test_received_text_populates_history() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("clipboard_history.json");
+        let text = b"https://example.com/snippet";
+
+        let entry = ClipboardEntry::from_received_text(text, MAX_CLIPBOARD_RECEIVE_SIZE).unwrap();
+        ClipboardHistory::open_at(path.clone())
+            .unwrap()
+            .append(entry)
+            .unwrap();
+
+        let history = ClipboardHistory::open_at(path).unwrap();
+        let entry = &history.query()[0];
+        assert_eq!(entry.content_type, ContentType::Url);
+        assert_eq!(entry.size, text.len() as u64);
+        assert_eq!(
+            entry.text_content.as_deref(),
+            Some("https://example.com/snippet")
+        );
+        assert!(history.contains_hash(&blake3::hash(text).to_hex()));
+    }
+
+    #[test]
+    fn test_received_oversized_or_binary_refused() {
+        let big = vec![b'a'; 2048];
+        assert_eq!(
+            ClipboardEntry::from_received_text(&big, 1024).unwrap_err(),
+            ClipboardRefusal::TooLarge {
+                size: 2048,
+                max: 1024
+            }
+        );
+        assert!(check_clipboard_size(2048, 1024).is_err());
+        assert!(check_clipboard_size(1024, 1024).is_ok());
+
+        let binary = [0x89, b'P', b'N', b'G', 0x00, 0xFF];
+        assert_eq!(
+            ClipboardEntry::from_received_text(&binary, 1024).unwrap_err(),
+            ClipboardRefusal::NotText
+        );
+        assert!(ClipboardRefusal::NotText
+            .to_string()
+            .contains("--to-clipboard"));
+    }
+
+    #[test]
+    fn test_max_entries_evicts_oldest_and_its_image() {
+        let dir = TempDir::new().unwrap();
+        let images = dir.path().join("clipboard_images");
+        std::fs::create_dir_all(&images).unwrap();
+        std::fs::write(images.join("e0.png"), b"png").unwrap();
+
+        let mut history = ClipboardHistory::open_at(dir.path().join("clipboard_history.json"))
+            .unwrap()
+            .with_images_dir(images.clone())
+            .with_max_entries(3);
+        for i in 0..4 {
+            let mut entry = test_entry(&format!("e{}", i));
+            entry.timestamp = now();
+            if i == 0 {
+                entry.content_type = ContentType::Image {
+                    format: ImageFormat::Png,
+                };
+                entry.image_path = Some(PathBuf::from("e0.png"));
+            }
+            history.append(entry).unwrap();
+        }
+
+        let ids: Vec<_> = history.query().iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["e1", "e2", "e3"]);
+        assert!(!images.join("e0.png").exists());
+
+        // Eviction is persisted
+        let reopened =
+            ClipboardHistory::open_at(dir.path().join("clipboard_history.json")).unwrap();
+        assert_eq!(reopened.len(), 3);
+    }
+
+    #[test]
+    fn test_aged_out_entries_removed() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::new()
+            .with_images_dir(dir.path().to_path_buf())
+            .with_max_age(Duration::from_secs(3600));
+
+        let mut old = test_entry("old");
+        old.timestamp = now() - 7200;
+        history.entries.push(old);
+
+        let mut fresh = test_entry("fresh");
+        fresh.timestamp = now();
+        history.append(fresh).unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.query()[0].id, "fresh");
+    }
+
+    #[test]
+    fn test_pinned_entries_survive_eviction() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::new()
+            .with_images_dir(dir.path().to_path_buf())
+            .with_limits(&ClipboardConfig {
+                max_entries: 2,
+                max_age_days: 1,
+            });
+
+        let mut pinned = test_entry("pinned");
+        pinned.timestamp = now() - 10 * 24 * 60 * 60;
+        history.entries.push(pinned);
+        assert!(history.set_pinned("pinned", true).unwrap());
+        assert!(!history.set_pinned("missing", true).unwrap());
+
+        for i in 0..5 {
+            let mut entry = test_entry(&format!("e{}", i));
+            entry.timestamp = now();
+            history.append(entry).unwrap();
+        }
+
+        // Too old and past the count, but pinned; the limit counts unpinned only
+        let ids: Vec<_> = history.query().iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["pinned", "e3", "e4"]);
+    }
+
+    #[test]
+    fn test_unpinned_field_defaults_for_old_files() {
+        let json = r#"[{"id":"e1","content_type":"PlainText","preview":"hi","size":2,
+            "timestamp":1,"blake3_hash":"ab","image_path":null,"text_content":"hi"}]"#;
+        let entries: Vec<ClipboardEntry> = serde_json::from_str(json).unwrap();
+        assert!(!entries[0].pinned);
+    }
+
     #[test]
     fn test_content_type_display() {
         assert_eq!(format!("{}", ContentType::PlainText), "Text");
@@ -11,6 +11,10 @@ impl Default for NetworkConfig {
             relay_servers: vec!["129.146.114.5:4433".to_string()],
             stun_servers: vec!["stun.l.google.com:19302".to_string()],
             turn_servers: Vec::new(),
+            tls_min_version: "1.3".to_string(),
+            tls_cipher_suites: Vec::new(),
+            mdns_announce_interval_secs: 30,
+            mdns_record_ttl_secs: 120,
         }
     }
 }
@@ -21,11 +25,19 @@ impl Default for TransferConfig {
             download_dir: PathBuf::from("~/Downloads"),
             auto_accept_trusted: false,
             enable_compression: true,
+            compression_skip_add: Vec::new(),
+            compression_skip_remove: Vec::new(),
             chunk_size: 256 * 1024, // 256 KB
             default_throttle: String::new(),
+            bandwidth_schedule: Vec::new(),
             default_words: 4,
             default_exclude: String::new(),
             default_gitignore: false,
+            direct_io: false,
+            chunk_checksum: false,
+            on_complete: String::new(),
+            room_fairness: "independent".to_string(),
+            room_max_lag_chunks: 64,
         }
     }
 }
@@ -38,6 +50,17 @@ impl Default for PrivacyConfig {
             enable_onion_routing: false,
             use_doh: false,
             default_proxy: String::new(),
+            tor: false,
+            encrypt_history: false,
+        }
+    }
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 200,
+            max_age_days: 30,
         }
     }
 }
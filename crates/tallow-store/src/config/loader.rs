@@ -1,7 +1,8 @@
 //! Configuration file loading and saving
 
+use super::validate::{validate_config_str, ConfigIssue};
 use super::TallowConfig;
-use crate::persistence::paths;
+use crate::persistence::{paths, write_atomic};
 use crate::Result;
 use crate::StoreError;
 use std::path::PathBuf;
@@ -32,19 +33,18 @@ pub fn load_config() -> Result<TallowConfig> {
 
 /// Save configuration to file
 pub fn save_config(config: &TallowConfig) -> Result<()> {
-    let path = config_path();
-
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
+    save_config_to(config, &config_path())
+}
 
+/// Save configuration to a specific path
+///
+/// The file is replaced atomically, so a reader never sees it half written.
+pub fn save_config_to(config: &TallowConfig, path: &std::path::Path) -> Result<()> {
     let content = toml::to_string_pretty(config).map_err(|e| {
         StoreError::SerializationError(format!("Failed to serialize config: {}", e))
     })?;
 
-    std::fs::write(&path, content)?;
-    Ok(())
+    write_atomic(path, content.as_bytes())
 }
 
 /// Get configuration file path
@@ -68,6 +68,21 @@ pub fn load_config_from(path: &std::path::Path) -> Result<TallowConfig> {
     Ok(config)
 }
 
+/// Validate the config file at `path`, collecting every problem
+///
+/// Unlike [`load_config_from`], which stops at the first error, this
+/// reports all problems found. Only a failure to read the file is an `Err`.
+pub fn validate_config_file(path: &std::path::Path) -> Result<Vec<ConfigIssue>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        StoreError::ConfigError(format!(
+            "Failed to read config at {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(validate_config_str(&content))
+}
+
 /// Get a config value by dotted key path (e.g., "network.enable_mdns")
 pub fn get_config_value(config: &TallowConfig, key: &str) -> Result<String> {
     // Serialize to toml::Value and navigate by key path
@@ -101,7 +116,11 @@ pub fn set_config_value(config: &mut TallowConfig, key: &str, value: &str) -> Re
             let table = current.as_table_mut().ok_or_else(|| {
                 StoreError::ConfigError(format!("Config path is not a table: {}", key))
             })?;
-            let parsed = parse_toml_value(value);
+            // A string setting stays a string even if the text looks numeric
+            let parsed = match table.get(*part) {
+                Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+                _ => parse_toml_value(value),
+            };
             table.insert(part.to_string(), parsed);
         } else {
             current = current
@@ -177,6 +196,32 @@ mod tests {
         assert!(!config.network.enable_mdns);
     }
 
+    #[test]
+    fn test_set_string_value_keeps_type() {
+        let mut config = TallowConfig::default();
+        set_config_value(&mut config, "network.tls_min_version", "1.2").unwrap();
+        assert_eq!(config.network.tls_min_version, "1.2");
+        set_config_value(&mut config, "transfer.default_throttle", "100").unwrap();
+        assert_eq!(config.transfer.default_throttle, "100");
+    }
+
+    #[test]
+    fn test_save_config_to_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+        let mut config = TallowConfig::default();
+        config.ui.theme = "light".to_string();
+        save_config_to(&config, &path).unwrap();
+        config.ui.theme = "dark".to_string();
+        save_config_to(&config, &path).unwrap();
+
+        assert_eq!(load_config_from(&path).unwrap().ui.theme, "dark");
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
     #[test]
     fn test_get_nonexistent_key() {
         let config = TallowConfig::default();
@@ -4,8 +4,14 @@ pub mod aliases;
 pub mod defaults;
 pub mod loader;
 pub mod schema;
+pub mod validate;
 
-pub use loader::{config_path, get_config_value, load_config, save_config, set_config_value};
+pub use loader::{
+    config_path, get_config_value, load_config, load_config_from, save_config, save_config_to,
+    set_config_value, validate_config_file,
+};
 pub use schema::{
-    HookConfig, NetworkConfig, PrivacyConfig, TallowConfig, TransferConfig, UiConfig,
+    ClipboardConfig, HookConfig, NetworkConfig, PrivacyConfig, TallowConfig, TransferConfig,
+    UiConfig,
 };
+pub use validate::{validate_config, validate_config_str, ConfigIssue, ConfigIssueKind};
@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use tallow_crypto::clock::TimeRange;
 
 /// Main Tallow configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -18,6 +19,9 @@ pub struct TallowConfig {
     /// Hook commands to run before/after transfers
     #[serde(default)]
     pub hooks: HookConfig,
+    /// Clipboard history retention
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
     /// Path aliases for quick directory access
     #[serde(default)]
     pub aliases: HashMap<String, PathBuf>,
@@ -36,6 +40,35 @@ pub struct NetworkConfig {
     pub stun_servers: Vec<String>,
     /// TURN servers
     pub turn_servers: Vec<String>,
+    /// Lowest TLS version for the TCP+TLS fallback ("1.2" or "1.3")
+    #[serde(default = "default_tls_min_version")]
+    pub tls_min_version: String,
+    /// Cipher suites allowed on the TCP+TLS fallback, by rustls name
+    /// (e.g. `TLS13_AES_256_GCM_SHA384`); empty allows all
+    #[serde(default)]
+    pub tls_cipher_suites: Vec<String>,
+    /// Seconds between LAN discovery re-announcements (minimum 1)
+    #[serde(default = "default_mdns_announce_interval")]
+    pub mdns_announce_interval_secs: u64,
+    /// Seconds a LAN discovery announcement stays valid (at least the
+    /// announce interval)
+    #[serde(default = "default_mdns_record_ttl")]
+    pub mdns_record_ttl_secs: u64,
+}
+
+/// TLS 1.3 only unless configured otherwise
+fn default_tls_min_version() -> String {
+    "1.3".to_string()
+}
+
+/// Default LAN re-announce interval, in seconds
+fn default_mdns_announce_interval() -> u64 {
+    30
+}
+
+/// Default LAN announcement lifetime, in seconds
+fn default_mdns_record_ttl() -> u64 {
+    120
 }
 
 /// Default number of words in a generated code phrase
@@ -52,11 +85,25 @@ pub struct TransferConfig {
     pub auto_accept_trusted: bool,
     /// Enable compression
     pub enable_compression: bool,
+    /// Extensions to store uncompressed on top of the built-in archive,
+    /// image, audio and video types (e.g. `["parquet", "npz"]`)
+    #[serde(default)]
+    pub compression_skip_add: Vec<String>,
+    /// Built-in types (`"zip"`, `"png"`) or single extensions to compress
+    /// after all
+    #[serde(default)]
+    pub compression_skip_remove: Vec<String>,
     /// Chunk size in bytes
     pub chunk_size: usize,
     /// Default bandwidth throttle (e.g., "10MB", empty = unlimited)
     #[serde(default)]
     pub default_throttle: String,
+    /// Time-of-day rate caps in bytes/sec, e.g. `[["09:00-17:00", 1048576]]`
+    ///
+    /// Applies when no fixed throttle is given. The first matching window
+    /// wins and uncovered times are unlimited.
+    #[serde(default)]
+    pub bandwidth_schedule: Vec<(TimeRange, u64)>,
     /// Default number of words in code phrase (3-8)
     #[serde(default = "default_word_count")]
     pub default_words: u8,
@@ -66,6 +113,33 @@ pub struct TransferConfig {
     /// Respect .gitignore by default when sending directories
     #[serde(default)]
     pub default_gitignore: bool,
+    /// Write received files with O_DIRECT (bypass the page cache) where supported
+    #[serde(default)]
+    pub direct_io: bool,
+    /// Ask receivers to check a CRC32C on every chunk before decrypting it
+    #[serde(default)]
+    pub chunk_checksum: bool,
+    /// Program (run without a shell) or http(s) webhook URL notified when a
+    /// transfer finishes, successfully or not
+    #[serde(default)]
+    pub on_complete: String,
+    /// Multi-peer pacing: "independent" (drop receivers that fall too far
+    /// behind) or "lockstep" (everyone moves at the slowest receiver's pace)
+    #[serde(default = "default_room_fairness")]
+    pub room_fairness: String,
+    /// Chunks a multi-peer send buffers for its slowest receiver
+    #[serde(default = "default_room_max_lag")]
+    pub room_max_lag_chunks: usize,
+}
+
+/// Default multi-peer pacing policy
+fn default_room_fairness() -> String {
+    "independent".to_string()
+}
+
+/// Default multi-peer buffer bound, in chunks
+fn default_room_max_lag() -> usize {
+    64
 }
 
 /// Privacy configuration
@@ -82,6 +156,12 @@ pub struct PrivacyConfig {
     /// Default SOCKS5 proxy address (e.g., "socks5://127.0.0.1:9050")
     #[serde(default)]
     pub default_proxy: String,
+    /// Route all connections through the local Tor SOCKS5 port
+    #[serde(default)]
+    pub tor: bool,
+    /// Keep transfer history encrypted at rest with an identity-derived key
+    #[serde(default)]
+    pub encrypt_history: bool,
 }
 
 /// UI configuration
@@ -95,6 +175,20 @@ pub struct UiConfig {
     pub language: String,
 }
 
+/// Clipboard history retention
+///
+/// When an entry is appended, unpinned entries beyond `max_entries` or
+/// older than `max_age_days` are evicted along with their image files.
+/// Zero disables a limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    /// Unpinned entries to keep (0 = unlimited)
+    pub max_entries: usize,
+    /// Days an unpinned entry is kept (0 = forever)
+    pub max_age_days: u64,
+}
+
 /// Hook configuration for pre/post transfer commands
 ///
 /// Shell commands that run at various points during the transfer lifecycle.
@@ -0,0 +1,572 @@
+//! Configuration validation
+//!
+//! Checks a config file against the schema and reports every problem found
+//! instead of stopping at the first one: syntax errors, unknown keys, values
+//! of the wrong type or out of range, and options that conflict.
+
+use super::TallowConfig;
+use serde::Serialize;
+
+/// Smallest accepted `transfer.chunk_size` (matches the protocol minimum)
+const MIN_CHUNK_SIZE: i64 = 16 * 1024;
+
+/// Largest accepted `transfer.chunk_size` (matches the protocol maximum)
+const MAX_CHUNK_SIZE: i64 = 4 * 1024 * 1024;
+
+/// Kind of configuration problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigIssueKind {
+    /// The file is not valid TOML
+    Syntax,
+    /// A key the schema does not define
+    UnknownKey,
+    /// A required key is absent
+    MissingKey,
+    /// A value of the wrong type
+    InvalidType,
+    /// A value outside its allowed range or set
+    OutOfRange,
+    /// Options that cannot be used together
+    Conflict,
+}
+
+/// A single problem found in a config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    /// Kind of problem
+    pub kind: ConfigIssueKind,
+    /// Dotted key path the problem refers to, if any
+    pub key: Option<String>,
+    /// 1-based line number, where it can be determined
+    pub line: Option<usize>,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(ref key) = self.key {
+            write!(f, "{}: ", key)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validate config file contents, returning every problem found
+///
+/// An empty result means the contents load as a [`TallowConfig`] and pass
+/// all range and consistency checks.
+pub fn validate_config_str(content: &str) -> Vec<ConfigIssue> {
+    let table = match content.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(e) => {
+            return vec![ConfigIssue {
+                kind: ConfigIssueKind::Syntax,
+                key: None,
+                line: e.span().map(|span| line_of_offset(content, span.start)),
+                message: e.message().to_string(),
+            }];
+        }
+    };
+
+    let mut issues = Vec::new();
+    let schema = match toml::Value::try_from(TallowConfig::default()) {
+        Ok(toml::Value::Table(schema)) => schema,
+        _ => return issues,
+    };
+
+    check_table(content, "", &table, &schema, &mut issues);
+    check_ranges(content, &table, &mut issues);
+    check_conflicts(content, &table, &mut issues);
+
+    // Anything the structural checks missed (e.g. a missing required key)
+    // still surfaces through the real deserializer
+    let structural = issues
+        .iter()
+        .any(|i| matches!(i.kind, ConfigIssueKind::InvalidType));
+    if !structural {
+        if let Err(e) = toml::Value::Table(table).try_into::<TallowConfig>() {
+            let message = e.message().to_string();
+            let missing = message.starts_with("missing field");
+            issues.push(ConfigIssue {
+                kind: if missing {
+                    ConfigIssueKind::MissingKey
+                } else {
+                    ConfigIssueKind::InvalidType
+                },
+                key: None,
+                line: None,
+                message,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Validate an in-memory config, as if it had been saved and reloaded
+///
+/// Types always match here, so only range and consistency problems can
+/// show up. Issues carry line numbers within the saved form.
+pub fn validate_config(config: &TallowConfig) -> Vec<ConfigIssue> {
+    match toml::to_string_pretty(config) {
+        Ok(content) => validate_config_str(&content),
+        Err(e) => vec![ConfigIssue {
+            kind: ConfigIssueKind::InvalidType,
+            key: None,
+            line: None,
+            message: e.to_string(),
+        }],
+    }
+}
+
+/// Compare `table` against the keys and value types of `schema`
+fn check_table(
+    content: &str,
+    prefix: &str,
+    table: &toml::Table,
+    schema: &toml::Table,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (key, value) in table {
+        let path = join_key(prefix, key);
+        let Some(expected) = schema.get(key) else {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::UnknownKey,
+                &path,
+                "unknown key".to_string(),
+            ));
+            continue;
+        };
+
+        match (expected, value) {
+            // Aliases are free-form names mapping to paths
+            (toml::Value::Table(_), toml::Value::Table(aliases)) if path == "aliases" => {
+                for (name, target) in aliases {
+                    if !target.is_str() {
+                        issues.push(issue(
+                            content,
+                            ConfigIssueKind::InvalidType,
+                            &join_key(&path, name),
+                            format!("expected a path string, found {}", target.type_str()),
+                        ));
+                    }
+                }
+            }
+            (toml::Value::Table(expected), toml::Value::Table(actual)) => {
+                check_table(content, &path, actual, expected, issues);
+            }
+            // Schedule entries are `[time range, bytes per second]` pairs
+            (toml::Value::Array(_), toml::Value::Array(windows))
+                if path == "transfer.bandwidth_schedule" =>
+            {
+                if let Some(message) = windows.iter().find_map(check_schedule_window) {
+                    issues.push(issue(content, ConfigIssueKind::InvalidType, &path, message));
+                }
+            }
+            (toml::Value::Array(_), toml::Value::Array(items)) => {
+                if let Some(bad) = items.iter().find(|v| !v.is_str()) {
+                    issues.push(issue(
+                        content,
+                        ConfigIssueKind::InvalidType,
+                        &path,
+                        format!("expected a list of strings, found {}", bad.type_str()),
+                    ));
+                }
+            }
+            (expected, actual) if expected.same_type(actual) => {}
+            (expected, actual) => issues.push(issue(
+                content,
+                ConfigIssueKind::InvalidType,
+                &path,
+                format!(
+                    "expected {}, found {}",
+                    expected.type_str(),
+                    actual.type_str()
+                ),
+            )),
+        }
+    }
+}
+
+/// Problem with one `transfer.bandwidth_schedule` entry, if any
+///
+/// Every problem here also stops the entry deserializing, so all are
+/// reported as [`ConfigIssueKind::InvalidType`].
+fn check_schedule_window(window: &toml::Value) -> Option<String> {
+    let pair = match window.as_array().map(Vec::as_slice) {
+        Some([range, rate]) => range.as_str().zip(rate.as_integer()),
+        _ => None,
+    };
+    let Some((range, rate)) = pair else {
+        return Some(format!(
+            "expected [\"HH:MM-HH:MM\", bytes_per_sec], found {}",
+            window
+        ));
+    };
+    if let Err(e) = range.parse::<tallow_crypto::clock::TimeRange>() {
+        return Some(e.to_string());
+    }
+    (rate < 0).then(|| format!("rate {} must not be negative", rate))
+}
+
+/// Check values that parse but fall outside what the program accepts
+fn check_ranges(content: &str, table: &toml::Table, issues: &mut Vec<ConfigIssue>) {
+    if let Some(size) = lookup(table, "transfer.chunk_size").and_then(toml::Value::as_integer) {
+        if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&size) {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "transfer.chunk_size",
+                format!(
+                    "{} is outside the allowed range {}..={} bytes",
+                    size, MIN_CHUNK_SIZE, MAX_CHUNK_SIZE
+                ),
+            ));
+        }
+    }
+
+    if let Some(words) = lookup(table, "transfer.default_words").and_then(toml::Value::as_integer) {
+        if !(3..=8).contains(&words) {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "transfer.default_words",
+                format!("{} is outside the allowed range 3..=8", words),
+            ));
+        }
+    }
+
+    if let Some(mode) = lookup(table, "transfer.room_fairness").and_then(toml::Value::as_str) {
+        if !matches!(mode, "independent" | "lockstep") {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "transfer.room_fairness",
+                format!("'{}' is not one of independent, lockstep", mode),
+            ));
+        }
+    }
+
+    if let Some(lag) =
+        lookup(table, "transfer.room_max_lag_chunks").and_then(toml::Value::as_integer)
+    {
+        if lag < 1 {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "transfer.room_max_lag_chunks",
+                format!("{} must be at least 1", lag),
+            ));
+        }
+    }
+
+    if let Some(version) = lookup(table, "network.tls_min_version").and_then(toml::Value::as_str) {
+        if !matches!(version, "1.2" | "1.3") {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "network.tls_min_version",
+                format!("'{}' is not one of 1.2, 1.3", version),
+            ));
+        }
+    }
+
+    if let Some(interval) =
+        lookup(table, "network.mdns_announce_interval_secs").and_then(toml::Value::as_integer)
+    {
+        if interval < 1 {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "network.mdns_announce_interval_secs",
+                format!("{} must be at least 1", interval),
+            ));
+        }
+    }
+
+    if let Some(throttle) = lookup(table, "transfer.default_throttle").and_then(toml::Value::as_str)
+    {
+        if !throttle.is_empty() {
+            if let Err(e) = throttle.parse::<bytesize::ByteSize>() {
+                issues.push(issue(
+                    content,
+                    ConfigIssueKind::OutOfRange,
+                    "transfer.default_throttle",
+                    format!("'{}' is not a rate such as 10MB or 500KB: {}", throttle, e),
+                ));
+            }
+        }
+    }
+
+    if let Some(theme) = lookup(table, "ui.theme").and_then(toml::Value::as_str) {
+        if !matches!(theme, "dark" | "light" | "auto") {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::OutOfRange,
+                "ui.theme",
+                format!("'{}' is not one of dark, light, auto", theme),
+            ));
+        }
+    }
+}
+
+/// Check for options that contradict each other
+fn check_conflicts(content: &str, table: &toml::Table, issues: &mut Vec<ConfigIssue>) {
+    let enabled = |key: &str| {
+        lookup(table, key)
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false)
+    };
+
+    if enabled("privacy.tor") && enabled("network.enable_mdns") {
+        issues.push(issue(
+            content,
+            ConfigIssueKind::Conflict,
+            "network.enable_mdns",
+            "mDNS announces this device on the local network; disable it when privacy.tor is set"
+                .to_string(),
+        ));
+    }
+
+    let seconds = |key: &str| lookup(table, key).and_then(toml::Value::as_integer);
+    if let (Some(interval), Some(ttl)) = (
+        seconds("network.mdns_announce_interval_secs"),
+        seconds("network.mdns_record_ttl_secs"),
+    ) {
+        if ttl < interval {
+            issues.push(issue(
+                content,
+                ConfigIssueKind::Conflict,
+                "network.mdns_record_ttl_secs",
+                format!(
+                    "{}s is shorter than the {}s announce interval, so announcements \
+                     would expire before being refreshed",
+                    ttl, interval
+                ),
+            ));
+        }
+    }
+
+    let proxy = lookup(table, "privacy.default_proxy")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("");
+    if enabled("privacy.tor") && !proxy.is_empty() {
+        issues.push(issue(
+            content,
+            ConfigIssueKind::Conflict,
+            "privacy.default_proxy",
+            "ignored because privacy.tor routes through the local Tor proxy".to_string(),
+        ));
+    }
+}
+
+/// Build an issue for `key`, locating its line in `content`
+fn issue(content: &str, kind: ConfigIssueKind, key: &str, message: String) -> ConfigIssue {
+    ConfigIssue {
+        kind,
+        key: Some(key.to_string()),
+        line: find_key_line(content, key),
+        message,
+    }
+}
+
+/// Look up a dotted key path in a parsed table
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut current = table.get(parts.next()?)?;
+    for part in parts {
+        current = current.get(part)?;
+    }
+    Some(current)
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// 1-based line containing byte `offset`
+fn line_of_offset(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Best-effort line lookup for a dotted key
+///
+/// Handles `[section]` headers with plain `key = value` lines, which covers
+/// files written by `tallow config`. Dotted or inline-table forms yield `None`.
+fn find_key_line(content: &str, key: &str) -> Option<usize> {
+    let (section, name) = key.rsplit_once('.').unwrap_or(("", key));
+    let mut current = "";
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = header.trim();
+            if current == key {
+                return Some(i + 1);
+            }
+            continue;
+        }
+        if current != section {
+            continue;
+        }
+        if let Some((lhs, _)) = line.split_once('=') {
+            let lhs = lhs.trim().trim_matches('"');
+            if lhs == name {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_toml() -> String {
+        toml::to_string_pretty(&TallowConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(validate_config_str(&default_toml()).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let content = default_toml()
+            .replace("chunk_size = 262144", "chunk_size = 1024")
+            .replace("theme = \"auto\"", "theme = \"neon\"")
+            .replace("tor = false", "tor = true")
+            .replace("use_doh = false", "use_doh = \"yes\"")
+            .replace("tls_min_version = \"1.3\"", "tls_min_version = \"1.0\"")
+            .replace(
+                "room_fairness = \"independent\"",
+                "room_fairness = \"fast\"",
+            )
+            .replace("[ui]", "[ui]\ncolour = \"blue\"");
+
+        let issues = validate_config_str(&content);
+        let find = |key: &str| {
+            issues
+                .iter()
+                .find(|i| i.key.as_deref() == Some(key))
+                .unwrap_or_else(|| panic!("no issue for {}: {:?}", key, issues))
+        };
+
+        assert_eq!(
+            find("transfer.chunk_size").kind,
+            ConfigIssueKind::OutOfRange
+        );
+        assert_eq!(find("ui.theme").kind, ConfigIssueKind::OutOfRange);
+        assert_eq!(find("privacy.use_doh").kind, ConfigIssueKind::InvalidType);
+        assert_eq!(find("ui.colour").kind, ConfigIssueKind::UnknownKey);
+        assert_eq!(find("network.enable_mdns").kind, ConfigIssueKind::Conflict);
+        assert_eq!(
+            find("network.tls_min_version").kind,
+            ConfigIssueKind::OutOfRange
+        );
+        assert_eq!(
+            find("transfer.room_fairness").kind,
+            ConfigIssueKind::OutOfRange
+        );
+        assert_eq!(issues.len(), 7, "{:?}", issues);
+
+        // Line numbers point at the offending lines
+        let lines: Vec<&str> = content.lines().collect();
+        for issue in &issues {
+            let line = issue.line.expect("line number");
+            let name = issue.key.as_deref().unwrap().rsplit('.').next().unwrap();
+            assert!(lines[line - 1].contains(name), "{:?}", issue);
+        }
+    }
+
+    #[test]
+    fn test_syntax_error_has_line() {
+        let content = "[network]\nenable_mdns = true\nenable_relay = \n";
+        let issues = validate_config_str(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ConfigIssueKind::Syntax);
+        assert_eq!(issues[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_missing_section_reported() {
+        let content = default_toml().replace("[ui]", "[ui_old]");
+        let issues = validate_config_str(&content);
+        assert!(issues
+            .iter()
+            .any(|i| i.kind == ConfigIssueKind::UnknownKey && i.key.as_deref() == Some("ui_old")));
+        assert!(issues.iter().any(|i| i.kind == ConfigIssueKind::MissingKey));
+    }
+
+    #[test]
+    fn test_mdns_timing_checked() {
+        let issues = validate_config_str(&default_toml().replace(
+            "mdns_announce_interval_secs = 30",
+            "mdns_announce_interval_secs = 0",
+        ));
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].kind, ConfigIssueKind::OutOfRange);
+
+        let issues = validate_config_str(
+            &default_toml().replace("mdns_record_ttl_secs = 120", "mdns_record_ttl_secs = 10"),
+        );
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].kind, ConfigIssueKind::Conflict);
+        assert_eq!(
+            issues[0].key.as_deref(),
+            Some("network.mdns_record_ttl_secs")
+        );
+    }
+
+    #[test]
+    fn test_in_memory_config_checked() {
+        let mut config = TallowConfig::default();
+        assert!(validate_config(&config).is_empty());
+
+        config.transfer.default_throttle = "10MB".to_string();
+        assert!(validate_config(&config).is_empty());
+        config.transfer.default_throttle = "fast".to_string();
+        let issues = validate_config(&config);
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].key.as_deref(), Some("transfer.default_throttle"));
+    }
+
+    #[test]
+    fn test_bandwidth_schedule_checked() {
+        let good = default_toml().replace(
+            "bandwidth_schedule = []",
+            "bandwidth_schedule = [[\"09:00-17:00\", 1048576], [\"22:00-06:00\", 0]]",
+        );
+        assert!(validate_config_str(&good).is_empty());
+
+        for window in [
+            "[\"9am-5pm\", 1024]",
+            "[\"09:00-17:00\", -1]",
+            "[\"09:00-17:00\"]",
+            "\"09:00-17:00\"",
+        ] {
+            let content = default_toml().replace(
+                "bandwidth_schedule = []",
+                &format!("bandwidth_schedule = [{}]", window),
+            );
+            let issues = validate_config_str(&content);
+            assert_eq!(issues.len(), 1, "{}: {:?}", window, issues);
+            assert_eq!(issues[0].kind, ConfigIssueKind::InvalidType);
+            assert_eq!(
+                issues[0].key.as_deref(),
+                Some("transfer.bandwidth_schedule")
+            );
+        }
+    }
+}
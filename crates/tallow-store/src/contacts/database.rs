@@ -10,6 +10,9 @@ pub struct Contact {
     pub id: String,
     /// Display name
     pub name: String,
+    /// Short name to address the contact by (e.g. `tallow send --to al`)
+    #[serde(default)]
+    pub alias: Option<String>,
     /// Public key
     pub public_key: Vec<u8>,
     /// Groups
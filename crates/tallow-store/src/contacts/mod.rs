@@ -2,6 +2,8 @@
 
 pub mod database;
 pub mod groups;
+pub mod resolve;
 
 pub use database::{Contact, ContactDatabase};
 pub use groups::ContactGroup;
+pub use resolve::{ContactMatch, MatchKind};
@@ -0,0 +1,247 @@
+//! Resolving a user-typed query to contacts
+//!
+//! A query can be an alias or name (exact or prefix), a fingerprint prefix,
+//! or a name with one typo. Matches are ranked so that exact hits always
+//! beat looser ones, and [`ContactDatabase::resolve_one`] only picks a
+//! contact when the best-ranked tier holds exactly one.
+
+use super::{Contact, ContactDatabase};
+use crate::identity::fingerprint_hex;
+use crate::{Result, StoreError};
+
+/// Shortest fingerprint prefix accepted, in hex digits
+pub const MIN_FINGERPRINT_PREFIX: usize = 4;
+
+/// Shortest query considered for typo-tolerant matching
+const MIN_FUZZY_LEN: usize = 3;
+
+/// How a contact matched a query, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// Query equals the alias or name
+    Exact,
+    /// Query is a prefix of the alias or name
+    Prefix,
+    /// Query is a prefix of the key fingerprint
+    Fingerprint,
+    /// Query is one edit away from the alias or name
+    Fuzzy,
+}
+
+/// A contact matched by [`ContactDatabase::resolve`]
+#[derive(Debug, Clone, Copy)]
+pub struct ContactMatch<'a> {
+    /// The matched contact
+    pub contact: &'a Contact,
+    /// Strongest way it matched
+    pub kind: MatchKind,
+}
+
+impl ContactDatabase {
+    /// All contacts matching `query`, best matches first
+    ///
+    /// Name and alias comparisons ignore case. Ties keep insertion order.
+    pub fn resolve(&self, query: &str) -> Vec<ContactMatch<'_>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<ContactMatch<'_>> = self
+            .list()
+            .iter()
+            .filter_map(|contact| {
+                match_kind(contact, &query).map(|kind| ContactMatch { contact, kind })
+            })
+            .collect();
+        matches.sort_by_key(|m| m.kind);
+        matches
+    }
+
+    /// The single contact `query` refers to
+    ///
+    /// Fails when nothing matches, or when the best-ranked matches are tied;
+    /// the error lists the candidates so the user can be more specific.
+    pub fn resolve_one(&self, query: &str) -> Result<&Contact> {
+        let matches = self.resolve(query);
+        let Some(best) = matches.first().map(|m| m.kind) else {
+            return Err(StoreError::ConfigError(format!(
+                "No contact matches '{}'",
+                query
+            )));
+        };
+
+        let top: Vec<&Contact> = matches
+            .iter()
+            .take_while(|m| m.kind == best)
+            .map(|m| m.contact)
+            .collect();
+        match top.as_slice() {
+            [only] => Ok(*only),
+            candidates => Err(StoreError::ConfigError(format!(
+                "'{}' is ambiguous; it matches {}. Use a longer name or a fingerprint prefix",
+                query,
+                candidates
+                    .iter()
+                    .map(|&c| describe(c))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+/// `name (alias, fingerprint)` for listing candidates
+pub fn describe(contact: &Contact) -> String {
+    let fp = fingerprint_hex(&contact.public_key);
+    match &contact.alias {
+        Some(alias) => format!("{} ({}, {})", contact.name, alias, &fp[..11]),
+        None => format!("{} ({})", contact.name, &fp[..11]),
+    }
+}
+
+/// Strongest way `contact` matches an already-lowercased `query`
+fn match_kind(contact: &Contact, query: &str) -> Option<MatchKind> {
+    let names: Vec<String> = contact
+        .alias
+        .iter()
+        .chain(std::iter::once(&contact.name))
+        .map(|n| n.to_lowercase())
+        .collect();
+
+    if names.iter().any(|n| n == query) {
+        return Some(MatchKind::Exact);
+    }
+    if names.iter().any(|n| n.starts_with(query)) {
+        return Some(MatchKind::Prefix);
+    }
+
+    let hex_query: String = query.chars().filter(|&c| c != ':').collect();
+    if hex_query.len() >= MIN_FINGERPRINT_PREFIX && hex_query.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        let fp: String = fingerprint_hex(&contact.public_key)
+            .chars()
+            .filter(|&c| c != ':')
+            .collect();
+        if fp.starts_with(&hex_query) {
+            return Some(MatchKind::Fingerprint);
+        }
+    }
+
+    if query.chars().count() >= MIN_FUZZY_LEN && names.iter().any(|n| edit_distance(n, query) <= 1)
+    {
+        return Some(MatchKind::Fuzzy);
+    }
+    None
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and adjacent transpositions each cost one
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        d[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, alias: Option<&str>, key: u8) -> Contact {
+        Contact {
+            id: name.to_string(),
+            name: name.to_string(),
+            alias: alias.map(str::to_string),
+            public_key: vec![key; 32],
+            groups: Vec::new(),
+        }
+    }
+
+    fn db() -> ContactDatabase {
+        let mut db = ContactDatabase::new();
+        db.add(contact("Alice", Some("al"), 1)).unwrap();
+        db.add(contact("Albert", None, 2)).unwrap();
+        db.add(contact("Bob", Some("bobby"), 3)).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_alias_prefix_matching() {
+        let db = db();
+        // "al" is Alice's alias exactly, beating Albert's name prefix
+        assert_eq!(db.resolve_one("al").unwrap().name, "Alice");
+        assert_eq!(db.resolve_one("bob").unwrap().name, "Bob");
+        assert_eq!(db.resolve_one("BOBB").unwrap().name, "Bob");
+
+        let matches = db.resolve("al");
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].kind, MatchKind::Exact);
+        assert_eq!(matches[1].kind, MatchKind::Prefix);
+    }
+
+    #[test]
+    fn test_fingerprint_prefix_matching() {
+        let db = db();
+        let fp = fingerprint_hex(&[3u8; 32]);
+
+        let found = db.resolve_one(&fp[..5]).unwrap();
+        assert_eq!(found.name, "Bob");
+        // Colon-separated and colon-free prefixes both work
+        let bare: String = fp.chars().filter(|&c| c != ':').collect();
+        assert_eq!(db.resolve_one(&bare[..8]).unwrap().name, "Bob");
+
+        // Too short to be treated as a fingerprint
+        assert!(db
+            .resolve(&bare[..MIN_FINGERPRINT_PREFIX - 1])
+            .iter()
+            .all(|m| m.kind != MatchKind::Fingerprint));
+    }
+
+    #[test]
+    fn test_fuzzy_tolerates_one_typo() {
+        let db = db();
+        assert_eq!(db.resolve_one("alcie").unwrap().name, "Alice");
+        assert_eq!(db.resolve_one("alise").unwrap().name, "Alice");
+        assert_eq!(db.resolve_one("bbo").unwrap().name, "Bob");
+        assert_eq!(db.resolve("alcie")[0].kind, MatchKind::Fuzzy);
+
+        // Two typos is too far
+        assert!(db.resolve("axlcie").is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_query_lists_candidates() {
+        let mut db = db();
+        db.add(contact("Alberta", None, 5)).unwrap();
+
+        let err = db.resolve_one("alber").unwrap_err().to_string();
+        assert!(err.contains("ambiguous"), "{err}");
+        assert!(
+            err.contains("Albert (") && err.contains("Alberta ("),
+            "{err}"
+        );
+
+        // An exact name still wins over a longer name it prefixes
+        assert_eq!(db.resolve_one("albert").unwrap().name, "Albert");
+
+        assert!(db.resolve_one("zed").is_err());
+    }
+}
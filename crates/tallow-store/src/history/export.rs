@@ -0,0 +1,256 @@
+//! Exporting the transfer log for analysis
+//!
+//! One row per transfer with a fixed set of columns, written either as CSV
+//! (for spreadsheets) or JSON lines (for scripts). Peer identifiers can be
+//! cut down to a short prefix so an export can be shared without handing
+//! out the full fingerprints of everyone the user has talked to.
+
+use super::log::{TransferDirection, TransferEntry, TransferLog, TransferStatus};
+use crate::{Result, StoreError};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Characters of the peer identifier kept when redacting
+pub const REDACTED_PEER_LEN: usize = 8;
+
+/// CSV header, in column order
+const CSV_HEADER: &str = "timestamp,direction,peer,bytes,duration_ms,status,cipher";
+
+/// Output format for [`TransferLog::export`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = StoreError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "json-lines" | "ndjson" => Ok(Self::JsonLines),
+            other => Err(StoreError::ConfigError(format!(
+                "unknown export format '{}' (expected csv or jsonl)",
+                other
+            ))),
+        }
+    }
+}
+
+/// One exported transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Sent or received
+    pub direction: TransferDirection,
+    /// Peer fingerprint or identifier, possibly redacted to a prefix
+    pub peer: String,
+    /// Total bytes transferred
+    pub bytes: u64,
+    /// Wall-clock duration, if recorded
+    pub duration_ms: Option<u64>,
+    /// Outcome
+    pub status: TransferStatus,
+    /// Cipher protecting the transfer, if recorded
+    pub cipher: Option<String>,
+}
+
+impl ExportRecord {
+    /// Build the exported row for `entry`
+    pub fn from_entry(entry: &TransferEntry, redact: bool) -> Self {
+        let peer = if redact {
+            entry.peer_id.chars().take(REDACTED_PEER_LEN).collect()
+        } else {
+            entry.peer_id.clone()
+        };
+        Self {
+            timestamp: entry.timestamp,
+            direction: entry.direction,
+            peer,
+            bytes: entry.total_bytes,
+            duration_ms: entry.duration_ms,
+            status: entry.status,
+            cipher: entry.cipher.clone(),
+        }
+    }
+
+    fn csv_row(&self) -> String {
+        let direction = match self.direction {
+            TransferDirection::Sent => "Sent",
+            TransferDirection::Received => "Received",
+        };
+        let status = match self.status {
+            TransferStatus::Completed => "Completed",
+            TransferStatus::Failed => "Failed",
+            TransferStatus::Cancelled => "Cancelled",
+        };
+        [
+            self.timestamp.to_string(),
+            direction.to_string(),
+            csv_field(&self.peer),
+            self.bytes.to_string(),
+            self.duration_ms.map(|d| d.to_string()).unwrap_or_default(),
+            status.to_string(),
+            csv_field(self.cipher.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    }
+}
+
+/// Quote a free-text CSV field when needed
+///
+/// Fields with separators, quotes or line breaks are wrapped in quotes with
+/// inner quotes doubled (RFC 4180). A leading `=`, `+`, `-` or `@` gets a
+/// `'` prefix so spreadsheets don't evaluate it as a formula.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+impl TransferLog {
+    /// Write every entry to `writer` in `format`
+    ///
+    /// With `redact`, peer identifiers are cut to their first
+    /// [`REDACTED_PEER_LEN`] characters. Returns the number of rows written.
+    pub fn export<W: Write>(
+        &self,
+        format: ExportFormat,
+        redact: bool,
+        mut writer: W,
+    ) -> Result<usize> {
+        let records = self
+            .query()
+            .iter()
+            .map(|entry| ExportRecord::from_entry(entry, redact));
+
+        match format {
+            ExportFormat::Csv => {
+                writeln!(writer, "{}", CSV_HEADER)?;
+                for record in records {
+                    writeln!(writer, "{}", record.csv_row())?;
+                }
+            }
+            ExportFormat::JsonLines => {
+                for record in records {
+                    serde_json::to_writer(&mut writer, &record).map_err(|e| {
+                        StoreError::SerializationError(format!("Failed to export history: {}", e))
+                    })?;
+                    writeln!(writer)?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(self.query().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(peer: &str, cipher: Option<&str>) -> TransferEntry {
+        TransferEntry {
+            id: "t1".to_string(),
+            peer_id: peer.to_string(),
+            direction: TransferDirection::Received,
+            file_count: 1,
+            total_bytes: 4096,
+            timestamp: 1_708_300_000,
+            status: TransferStatus::Completed,
+            filenames: vec!["a.txt".to_string()],
+            context: None,
+            duration_ms: Some(1500),
+            cipher: cipher.map(str::to_string),
+        }
+    }
+
+    fn log(entries: Vec<TransferEntry>) -> TransferLog {
+        let mut log = TransferLog::new();
+        for e in entries {
+            log.append(e).unwrap();
+        }
+        log
+    }
+
+    #[test]
+    fn test_csv_header_and_escaping() {
+        let log = log(vec![
+            entry("ab12cd34ef56", Some("AES-256-GCM")),
+            entry("Smith, \"Bob\"", None),
+            entry("=HYPERLINK(\"x\")", None),
+        ]);
+        let mut out = Vec::new();
+        assert_eq!(log.export(ExportFormat::Csv, false, &mut out).unwrap(), 3);
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "1708300000,Received,ab12cd34ef56,4096,1500,Completed,AES-256-GCM"
+        );
+        assert_eq!(
+            lines[2],
+            "1708300000,Received,\"Smith, \"\"Bob\"\"\",4096,1500,Completed,"
+        );
+        assert_eq!(
+            lines[3],
+            "1708300000,Received,\"'=HYPERLINK(\"\"x\"\")\",4096,1500,Completed,"
+        );
+    }
+
+    #[test]
+    fn test_json_lines_roundtrip() {
+        let mut legacy = entry("peer-xyz", None);
+        legacy.duration_ms = None;
+        let entries = vec![entry("ab12cd34ef56", Some("AES-256-GCM")), legacy];
+        let log = log(entries.clone());
+
+        let mut out = Vec::new();
+        log.export(ExportFormat::JsonLines, false, &mut out)
+            .unwrap();
+        let parsed: Vec<ExportRecord> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let expected: Vec<ExportRecord> = entries
+            .iter()
+            .map(|e| ExportRecord::from_entry(e, false))
+            .collect();
+        assert_eq!(parsed, expected);
+        assert_eq!(parsed[0].peer, "ab12cd34ef56");
+    }
+
+    #[test]
+    fn test_redaction_keeps_prefix() {
+        let log = log(vec![entry("ab12cd34ef56ab78", None)]);
+        let mut out = Vec::new();
+        log.export(ExportFormat::JsonLines, true, &mut out).unwrap();
+        let record: ExportRecord = serde_json::from_slice(&out).unwrap();
+        assert_eq!(record.peer, "ab12cd34");
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("CSV".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert_eq!(
+            "jsonl".parse::<ExportFormat>().unwrap(),
+            ExportFormat::JsonLines
+        );
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
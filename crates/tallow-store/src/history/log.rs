@@ -1,10 +1,19 @@
 //! Transfer history log with file persistence
+//!
+//! History is plaintext JSON by default. With `privacy.encrypt_history`
+//! enabled it lives in an [`EncryptedKv`] under a key derived from the
+//! identity keypair, and any plaintext history is migrated on first open.
 
-use crate::persistence::paths;
+use crate::persistence::{paths, EncryptedKv};
 use crate::Result;
 use crate::StoreError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tallow_crypto::keys::IdentityKeyPair;
+use zeroize::Zeroize;
+
+/// Key under which the entry list is stored in the encrypted store
+const HISTORY_KV_KEY: &str = "transfer_history";
 
 /// Transfer log entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,10 +34,19 @@ pub struct TransferEntry {
     pub status: TransferStatus,
     /// File names transferred
     pub filenames: Vec<String>,
+    /// Context label the transfer key was bound to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Wall-clock duration in milliseconds, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Cipher protecting the transfer, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cipher: Option<String>,
 }
 
 /// Transfer direction
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransferDirection {
     /// Sent to peer
     Sent,
@@ -37,7 +55,7 @@ pub enum TransferDirection {
 }
 
 /// Transfer status
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransferStatus {
     /// Completed successfully
     Completed,
@@ -47,11 +65,35 @@ pub enum TransferStatus {
     Cancelled,
 }
 
+/// Where a transfer log is persisted
+#[derive(Debug)]
+enum Storage {
+    /// Not persisted
+    Memory,
+    /// Plaintext JSON file
+    Plain(PathBuf),
+    /// Encrypted key-value store
+    Encrypted(EncryptedKv),
+}
+
 /// Transfer history log with optional file persistence
 #[derive(Debug)]
 pub struct TransferLog {
     entries: Vec<TransferEntry>,
-    path: Option<PathBuf>,
+    storage: Storage,
+}
+
+/// Derive the history encryption key from an identity keypair
+///
+/// The key is bound to the secret key material, so only the holder of the
+/// identity can read the history.
+pub fn history_key(identity: &IdentityKeyPair) -> Result<[u8; 32]> {
+    let mut secret = identity
+        .to_bytes()
+        .map_err(|e| StoreError::IdentityError(format!("Failed to serialize keypair: {}", e)))?;
+    let key = tallow_crypto::hash::derive_key(tallow_crypto::hash::DOMAIN_HISTORY, &secret);
+    secret.zeroize();
+    Ok(key)
 }
 
 impl TransferLog {
@@ -59,32 +101,112 @@ impl TransferLog {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
-            path: None,
+            storage: Storage::Memory,
         }
     }
 
-    /// Open a persistent transfer log at the default path
-    pub fn open() -> Result<Self> {
-        Self::open_at(paths::history_file())
+    /// Open the persistent transfer log at the default location
+    ///
+    /// Honors `privacy.encrypt_history`: when set, the history key is
+    /// derived from `identity`, which the caller must already have
+    /// unlocked, the encrypted log is opened, and a plaintext log left from
+    /// before is migrated into it. Without encryption `identity` is unused.
+    pub fn open(identity: Option<&IdentityKeyPair>) -> Result<Self> {
+        let config = crate::config::load_config()?;
+        Self::open_in(
+            &paths::StorePaths::current(),
+            config.privacy.encrypt_history,
+            identity,
+        )
+    }
+
+    /// Open the transfer log of the profile at `store_paths`
+    ///
+    /// Like [`TransferLog::open`], with the encryption setting passed in.
+    pub fn open_in(
+        store_paths: &paths::StorePaths,
+        encrypt: bool,
+        identity: Option<&IdentityKeyPair>,
+    ) -> Result<Self> {
+        if !encrypt {
+            return Self::open_at(store_paths.history_file());
+        }
+
+        let keypair = identity.ok_or_else(|| {
+            StoreError::IdentityError("Encrypted history requires an unlocked identity".to_string())
+        })?;
+        let mut key = history_key(keypair)?;
+        let log = Self::open_encrypted_at(store_paths.encrypted_history_file(), &key);
+        key.zeroize();
+
+        let mut log = log?;
+        log.migrate_plaintext(&store_paths.history_file())?;
+        Ok(log)
     }
 
-    /// Open a persistent transfer log at a custom path
+    /// Open a plaintext persistent transfer log at a custom path
     pub fn open_at(path: PathBuf) -> Result<Self> {
-        let mut log = Self {
-            entries: Vec::new(),
-            path: Some(path),
+        let entries = if path.exists() {
+            let data = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&data).map_err(|e| {
+                StoreError::SerializationError(format!("Failed to parse history: {}", e))
+            })?
+        } else {
+            Vec::new()
         };
 
-        if let Some(ref p) = log.path {
-            if p.exists() {
-                let data = std::fs::read_to_string(p)?;
-                log.entries = serde_json::from_str(&data).map_err(|e| {
+        Ok(Self {
+            entries,
+            storage: Storage::Plain(path),
+        })
+    }
+
+    /// Open an encrypted transfer log at a custom path
+    ///
+    /// Fails if the store was written with a different key.
+    pub fn open_encrypted_at(path: PathBuf, key: &[u8; 32]) -> Result<Self> {
+        let kv = EncryptedKv::open_with_key(path, key)?;
+        let entries = match kv.get(HISTORY_KV_KEY)? {
+            Some(mut data) => {
+                let parsed = serde_json::from_slice(&data);
+                data.zeroize();
+                parsed.map_err(|e| {
                     StoreError::SerializationError(format!("Failed to parse history: {}", e))
-                })?;
+                })?
             }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            entries,
+            storage: Storage::Encrypted(kv),
+        })
+    }
+
+    /// Move a plaintext history file into this (encrypted) log
+    ///
+    /// Plaintext entries are older, so they go first. The plaintext file is
+    /// removed only after the encrypted copy is written. Returns whether
+    /// anything was migrated.
+    pub fn migrate_plaintext(&mut self, plain_path: &Path) -> Result<bool> {
+        if !matches!(self.storage, Storage::Encrypted(_)) || !plain_path.exists() {
+            return Ok(false);
         }
 
-        Ok(log)
+        let plain = Self::open_at(plain_path.to_path_buf())?;
+        let mut entries = plain.entries;
+        entries.append(&mut self.entries);
+        self.entries = entries;
+        self.save()?;
+
+        std::fs::remove_file(plain_path)?;
+        tracing::info!("Migrated plaintext transfer history to encrypted storage");
+        Ok(true)
+    }
+
+    /// Whether this log is encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.storage, Storage::Encrypted(_))
     }
 
     /// Append an entry and persist
@@ -125,22 +247,33 @@ impl TransferLog {
     }
 
     /// Save to disk if persistent
-    fn save(&self) -> Result<()> {
-        if let Some(ref path) = self.path {
-            if let Some(parent) = path.parent() {
-                std::fs::create_dir_all(parent)?;
+    fn save(&mut self) -> Result<()> {
+        match self.storage {
+            Storage::Memory => {}
+            Storage::Plain(ref path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let data = serde_json::to_string_pretty(&self.entries).map_err(|e| {
+                    StoreError::SerializationError(format!("Failed to serialize history: {}", e))
+                })?;
+                std::fs::write(path, &data)?;
+
+                // Restrict file permissions to owner-only on Unix (0o600)
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let perms = std::fs::Permissions::from_mode(0o600);
+                    let _ = std::fs::set_permissions(path, perms);
+                }
             }
-            let data = serde_json::to_string_pretty(&self.entries).map_err(|e| {
-                StoreError::SerializationError(format!("Failed to serialize history: {}", e))
-            })?;
-            std::fs::write(path, &data)?;
-
-            // Restrict file permissions to owner-only on Unix (0o600)
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let perms = std::fs::Permissions::from_mode(0o600);
-                let _ = std::fs::set_permissions(path, perms);
+            Storage::Encrypted(ref mut kv) => {
+                let mut data = serde_json::to_vec(&self.entries).map_err(|e| {
+                    StoreError::SerializationError(format!("Failed to serialize history: {}", e))
+                })?;
+                let result = kv.set(HISTORY_KV_KEY, &data);
+                data.zeroize();
+                result?;
             }
         }
         Ok(())
@@ -168,9 +301,27 @@ mod tests {
             timestamp: 1708300000,
             status: TransferStatus::Completed,
             filenames: vec!["test.txt".to_string()],
+            context: None,
+            duration_ms: None,
+            cipher: None,
         }
     }
 
+    #[test]
+    fn test_context_label_recorded() {
+        let mut entry = test_entry();
+        entry.context = Some("backup".to_string());
+        let json = serde_json::to_string(&entry).unwrap();
+        let parsed: TransferEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.context.as_deref(), Some("backup"));
+
+        // Entries written before labels existed still load
+        let legacy = serde_json::to_string(&test_entry()).unwrap();
+        assert!(!legacy.contains("context"));
+        let parsed: TransferEntry = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(parsed.context, None);
+    }
+
     #[test]
     fn test_append_and_query() {
         let mut log = TransferLog::new();
@@ -195,6 +346,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.enc");
+        let key = [0x42u8; 32];
+
+        {
+            let mut log = TransferLog::open_encrypted_at(path.clone(), &key).unwrap();
+            assert!(log.is_encrypted());
+            log.append(test_entry()).unwrap();
+        }
+
+        // Nothing identifying is stored in the clear
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(8).any(|w| w == b"peer-abc"));
+
+        let log = TransferLog::open_encrypted_at(path, &key).unwrap();
+        assert_eq!(log.query().len(), 1);
+        assert_eq!(log.query()[0].peer_id, "peer-abc");
+    }
+
+    #[test]
+    fn test_migration_encrypts_and_removes_plaintext() {
+        let dir = TempDir::new().unwrap();
+        let plain_path = dir.path().join("history.json");
+        let enc_path = dir.path().join("history.enc");
+        let key = [0x17u8; 32];
+
+        {
+            let mut plain = TransferLog::open_at(plain_path.clone()).unwrap();
+            plain.append(test_entry()).unwrap();
+        }
+
+        let mut log = TransferLog::open_encrypted_at(enc_path.clone(), &key).unwrap();
+        assert!(log.migrate_plaintext(&plain_path).unwrap());
+        assert!(!plain_path.exists());
+        assert_eq!(log.query()[0].id, "test-001");
+
+        // Second run: nothing left to migrate, entries persist encrypted
+        assert!(!log.migrate_plaintext(&plain_path).unwrap());
+        drop(log);
+        let log = TransferLog::open_encrypted_at(enc_path, &key).unwrap();
+        assert_eq!(log.query().len(), 1);
+    }
+
+    #[test]
+    fn test_encrypted_wrong_key_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.enc");
+
+        let mut log = TransferLog::open_encrypted_at(path.clone(), &[1u8; 32]).unwrap();
+        log.append(test_entry()).unwrap();
+        drop(log);
+
+        assert!(TransferLog::open_encrypted_at(path, &[2u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_open_in_uses_caller_identity() {
+        let dir = TempDir::new().unwrap();
+        let store_paths = paths::StorePaths::under(dir.path(), None);
+
+        // A passphrase-protected identity, unlocked by the caller
+        let mut identity = crate::identity::IdentityStore::with_path(store_paths.identity_file());
+        identity.generate("correct horse").unwrap();
+        let keypair = identity.keypair();
+
+        {
+            let mut plain = TransferLog::open_in(&store_paths, false, None).unwrap();
+            plain.append(test_entry()).unwrap();
+        }
+
+        let log = TransferLog::open_in(&store_paths, true, keypair).unwrap();
+        assert!(log.is_encrypted());
+        assert_eq!(log.query()[0].id, "test-001");
+        assert!(!store_paths.history_file().exists());
+        drop(log);
+
+        let mut reloaded = crate::identity::IdentityStore::with_path(store_paths.identity_file());
+        reloaded.load("correct horse").unwrap();
+        let log = TransferLog::open_in(&store_paths, true, reloaded.keypair()).unwrap();
+        assert_eq!(log.query().len(), 1);
+
+        // Without an unlocked identity there is no key to open it with
+        assert!(matches!(
+            TransferLog::open_in(&store_paths, true, None),
+            Err(StoreError::IdentityError(_))
+        ));
+    }
+
     #[test]
     fn test_recent() {
         let mut log = TransferLog::new();
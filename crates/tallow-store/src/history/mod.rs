@@ -1,7 +1,9 @@
 //! Transfer and chat history logging
 
 pub mod chat;
+pub mod export;
 pub mod log;
 
 pub use chat::{ChatHistoryEntry, ChatLog, StoredChatMessage};
-pub use log::{TransferDirection, TransferEntry, TransferLog, TransferStatus};
+pub use export::{ExportFormat, ExportRecord};
+pub use log::{history_key, TransferDirection, TransferEntry, TransferLog, TransferStatus};
@@ -48,7 +48,20 @@ impl IdentityStore {
     ///
     /// Uses the provided passphrase to encrypt the keypair.
     /// If passphrase is empty, the keypair is still encrypted (Argon2id salt provides uniqueness).
+    ///
+    /// The OS RNG is health-checked first; generation is refused if its
+    /// output looks degenerate, since a weak identity key can't be fixed
+    /// after it has been shared.
     pub fn generate(&mut self, passphrase: &str) -> Result<()> {
+        let report = tallow_crypto::entropy::check_health().map_err(|e| {
+            StoreError::IdentityError(format!("Refusing to generate keypair: {}", e))
+        })?;
+        if report.kernel_pool_ready == Some(false) {
+            tracing::warn!(
+                "Kernel entropy pool was not seeded yet; waited before generating the identity key"
+            );
+        }
+
         let keypair = tallow_crypto::keys::IdentityKeyPair::generate()
             .map_err(|e| StoreError::IdentityError(format!("Failed to generate keypair: {}", e)))?;
 
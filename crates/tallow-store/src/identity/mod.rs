@@ -2,6 +2,10 @@
 
 pub mod fingerprint;
 pub mod keypair;
+pub mod offline;
+pub mod prekeys;
 
 pub use fingerprint::{fingerprint_emoji, fingerprint_hex, fingerprint_short};
 pub use keypair::IdentityStore;
+pub use offline::OfflineBundle;
+pub use prekeys::PreKeyStore;
@@ -0,0 +1,165 @@
+//! Signed public identity bundles for offline key exchange
+//!
+//! An [`OfflineBundle`] carries everything a peer needs to start a session
+//! with us without a relay: the identity fingerprint, the hybrid signing
+//! key, and a pre-key bundle holding KEM public keys. The whole bundle is
+//! signed by the identity key and then sealed with a password, so a bundle
+//! shown on screen can't be read by a bystander's camera or silently swapped
+//! for another one.
+
+use crate::{Result, StoreError};
+use serde::{Deserialize, Serialize};
+use tallow_crypto::keys::{decrypt_keyring, encrypt_keyring, IdentityKeyPair, PreKeyBundle};
+use tallow_crypto::sig::hybrid::{self, HybridPublicKey, HybridSignature};
+
+/// Domain separator for the bundle signature
+const BUNDLE_DOMAIN: &[u8] = b"tallow-offline-bundle-v1:";
+
+/// Public identity bundle exchanged by scanning QR codes
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OfflineBundle {
+    /// BLAKE3 hash of the serialized identity key
+    pub fingerprint: [u8; 32],
+    /// Signed and one-time pre-keys; also carries the identity key
+    pub prekeys: PreKeyBundle,
+    /// Identity signature over the fingerprint and pre-keys
+    pub signature: HybridSignature,
+}
+
+impl std::fmt::Debug for OfflineBundle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OfflineBundle")
+            .field("fingerprint", &super::fingerprint_short(&self.fingerprint))
+            .field("onetime_prekeys", &self.prekeys.onetime_count())
+            .finish()
+    }
+}
+
+impl OfflineBundle {
+    /// Sign `prekeys` with `identity`
+    ///
+    /// The pre-keys must belong to `identity`.
+    pub fn new(identity: &IdentityKeyPair, prekeys: PreKeyBundle) -> Result<Self> {
+        let fingerprint = *identity.id();
+        if identity_fingerprint(&prekeys.identity_key)? != fingerprint {
+            return Err(StoreError::IdentityError(
+                "pre-keys belong to a different identity".to_string(),
+            ));
+        }
+        let message = signed_message(&fingerprint, &prekeys)?;
+        let signature = identity
+            .signer()
+            .sign(&message)
+            .map_err(|e| StoreError::IdentityError(format!("Failed to sign bundle: {}", e)))?;
+        Ok(Self {
+            fingerprint,
+            prekeys,
+            signature,
+        })
+    }
+
+    /// Check that the fingerprint matches the identity key and that the
+    /// bundle and every pre-key carry valid identity signatures
+    pub fn verify(&self) -> Result<()> {
+        let invalid = |e: String| StoreError::IdentityError(format!("Invalid bundle: {}", e));
+        if identity_fingerprint(&self.prekeys.identity_key)? != self.fingerprint {
+            return Err(invalid(
+                "fingerprint does not match identity key".to_string(),
+            ));
+        }
+        let message = signed_message(&self.fingerprint, &self.prekeys)?;
+        hybrid::verify(&self.prekeys.identity_key, &message, &self.signature)
+            .map_err(|e| invalid(e.to_string()))?;
+        self.prekeys.verify().map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Serialize and encrypt the bundle with `password`
+    pub fn seal(&self, password: &str) -> Result<Vec<u8>> {
+        let plain = bincode::serialize(self).map_err(|e| {
+            StoreError::SerializationError(format!("Failed to serialize bundle: {}", e))
+        })?;
+        let sealed = encrypt_keyring(password, &plain)
+            .map_err(|e| StoreError::IdentityError(format!("Failed to encrypt bundle: {}", e)))?;
+        bincode::serialize(&sealed).map_err(|e| {
+            StoreError::SerializationError(format!("Failed to serialize bundle: {}", e))
+        })
+    }
+
+    /// Decrypt a sealed bundle and verify it
+    ///
+    /// A wrong password and a tampered bundle both fail here; a bundle is
+    /// never returned unverified.
+    pub fn open(sealed: &[u8], password: &str) -> Result<Self> {
+        let keyring = bincode::deserialize(sealed).map_err(|e| {
+            StoreError::SerializationError(format!("Malformed sealed bundle: {}", e))
+        })?;
+        let plain = decrypt_keyring(password, &keyring).map_err(|_| {
+            StoreError::IdentityError("Failed to decrypt bundle (wrong password?)".to_string())
+        })?;
+        let bundle: Self = bincode::deserialize(&plain)
+            .map_err(|e| StoreError::SerializationError(format!("Malformed bundle: {}", e)))?;
+        bundle.verify()?;
+        Ok(bundle)
+    }
+}
+
+/// Fingerprint of a hybrid identity key, as computed by [`IdentityKeyPair`]
+fn identity_fingerprint(key: &HybridPublicKey) -> Result<[u8; 32]> {
+    let bytes = bincode::serialize(key).map_err(|e| {
+        StoreError::SerializationError(format!("Failed to serialize identity key: {}", e))
+    })?;
+    Ok(tallow_crypto::hash::blake3::hash(&bytes))
+}
+
+fn signed_message(fingerprint: &[u8; 32], prekeys: &PreKeyBundle) -> Result<Vec<u8>> {
+    let prekey_bytes = bincode::serialize(prekeys).map_err(|e| {
+        StoreError::SerializationError(format!("Failed to serialize pre-keys: {}", e))
+    })?;
+    let mut message = Vec::with_capacity(BUNDLE_DOMAIN.len() + 32 + prekey_bytes.len());
+    message.extend_from_slice(BUNDLE_DOMAIN);
+    message.extend_from_slice(fingerprint);
+    message.extend_from_slice(&prekey_bytes);
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(identity: &IdentityKeyPair) -> OfflineBundle {
+        let prekeys = PreKeyBundle::generate(identity.signer(), 1, 1).unwrap();
+        OfflineBundle::new(identity, prekeys).unwrap()
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let identity = IdentityKeyPair::generate().unwrap();
+        let sealed = bundle(&identity).seal("correct horse").unwrap();
+
+        let opened = OfflineBundle::open(&sealed, "correct horse").unwrap();
+        assert_eq!(&opened.fingerprint, identity.id());
+        assert_eq!(opened.prekeys.onetime_count(), 1);
+
+        assert!(OfflineBundle::open(&sealed, "wrong horse").is_err());
+    }
+
+    #[test]
+    fn test_tampered_bundle_fails_verification() {
+        let identity = IdentityKeyPair::generate().unwrap();
+        let other = IdentityKeyPair::generate().unwrap();
+
+        // Someone else's pre-keys under our fingerprint
+        let mut forged = bundle(&identity);
+        forged.prekeys = PreKeyBundle::generate(other.signer(), 1, 0).unwrap();
+        assert!(forged.verify().is_err());
+
+        // A signature from a different identity
+        let mut resigned = bundle(&identity);
+        resigned.signature = bundle(&other).signature;
+        assert!(resigned.verify().is_err());
+
+        // Mismatched identity at construction
+        let prekeys = PreKeyBundle::generate(other.signer(), 1, 0).unwrap();
+        assert!(OfflineBundle::new(&identity, prekeys).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Persistent pre-key pool with automatic one-time pre-key replenishment
+
+use crate::persistence::paths;
+use crate::Result;
+use crate::StoreError;
+use std::path::PathBuf;
+use tallow_crypto::keys::{OneTimePreKey, PreKeyBundle};
+use tallow_crypto::sig::HybridSigner;
+
+/// Default number of one-time pre-keys kept in the pool
+pub const DEFAULT_ONETIME_TARGET: usize = 100;
+
+/// Default pool size below which the pool is topped back up
+pub const DEFAULT_ONETIME_THRESHOLD: usize = 25;
+
+/// Pre-key store for asynchronous (offline recipient) sessions
+///
+/// Every initiation consumes one one-time pre-key. The store checks the
+/// pool after each consumption and whenever [`PreKeyStore::maintain`] runs,
+/// regenerating keys once it drops below the threshold.
+pub struct PreKeyStore {
+    /// The pre-key bundle, once generated or loaded
+    bundle: Option<PreKeyBundle>,
+    /// Path for persistence
+    path: Option<PathBuf>,
+    /// Replenish when the pool holds fewer keys than this
+    threshold: usize,
+    /// Pool size restored by replenishment
+    target: usize,
+}
+
+impl std::fmt::Debug for PreKeyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreKeyStore")
+            .field("onetime_count", &self.onetime_count())
+            .field("path", &self.path)
+            .field("threshold", &self.threshold)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl PreKeyStore {
+    /// Create a new in-memory pre-key store
+    pub fn new() -> Self {
+        Self {
+            bundle: None,
+            path: None,
+            threshold: DEFAULT_ONETIME_THRESHOLD,
+            target: DEFAULT_ONETIME_TARGET,
+        }
+    }
+
+    /// Open a persistent pre-key store at the default path
+    pub fn open() -> Result<Self> {
+        Self::open_at(paths::prekeys_file())
+    }
+
+    /// Open a persistent pre-key store at a custom path
+    pub fn open_at(path: PathBuf) -> Result<Self> {
+        let mut store = Self {
+            path: Some(path),
+            ..Self::new()
+        };
+
+        if let Some(ref p) = store.path {
+            if p.exists() {
+                let data = std::fs::read_to_string(p)?;
+                let bundle = serde_json::from_str(&data).map_err(|e| {
+                    StoreError::IdentityError(format!("Failed to parse pre-key store: {}", e))
+                })?;
+                store.bundle = Some(bundle);
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Set the replenishment threshold and target pool size
+    ///
+    /// `target` is raised to `threshold` if it is smaller.
+    pub fn with_limits(mut self, threshold: usize, target: usize) -> Self {
+        self.threshold = threshold;
+        self.target = target.max(threshold);
+        self
+    }
+
+    /// The current bundle, if one has been generated
+    pub fn bundle(&self) -> Option<&PreKeyBundle> {
+        self.bundle.as_ref()
+    }
+
+    /// Number of unused one-time pre-keys
+    pub fn onetime_count(&self) -> usize {
+        self.bundle.as_ref().map_or(0, PreKeyBundle::onetime_count)
+    }
+
+    /// Whether the pool has dropped below the replenishment threshold
+    pub fn needs_replenish(&self) -> bool {
+        self.onetime_count() < self.threshold
+    }
+
+    /// Load the bundle or generate one with a full one-time pre-key pool
+    pub fn load_or_generate(&mut self, identity: &HybridSigner) -> Result<()> {
+        if self.bundle.is_none() {
+            let bundle = PreKeyBundle::generate(identity, 1, self.target).map_err(|e| {
+                StoreError::IdentityError(format!("Failed to generate pre-keys: {}", e))
+            })?;
+            self.bundle = Some(bundle);
+            self.save()?;
+        }
+        self.maintain(identity)?;
+        Ok(())
+    }
+
+    /// Consume the oldest one-time pre-key, replenishing the pool if needed
+    ///
+    /// The consumed key is removed and persisted before it is returned, so
+    /// it can never be handed out twice.
+    pub fn take_onetime_prekey(
+        &mut self,
+        identity: &HybridSigner,
+    ) -> Result<Option<OneTimePreKey>> {
+        let Some(bundle) = self.bundle.as_mut() else {
+            return Ok(None);
+        };
+        let prekey = bundle.take_onetime_prekey();
+        self.save()?;
+        self.maintain(identity)?;
+        Ok(prekey)
+    }
+
+    /// Replenish the pool if it is below the threshold
+    ///
+    /// Intended to run periodically from long-lived processes as well as
+    /// after each consumption. Returns the number of keys generated.
+    pub fn maintain(&mut self, identity: &HybridSigner) -> Result<usize> {
+        if !self.needs_replenish() {
+            return Ok(0);
+        }
+        let Some(bundle) = self.bundle.as_mut() else {
+            return Ok(0);
+        };
+
+        let generated = bundle.replenish(self.target, identity).map_err(|e| {
+            StoreError::IdentityError(format!("Failed to replenish pre-keys: {}", e))
+        })?;
+        if generated > 0 {
+            tracing::debug!("Replenished {} one-time pre-keys", generated);
+            self.save()?;
+        }
+        Ok(generated)
+    }
+
+    /// Persist to disk (if path is set)
+    fn save(&self) -> Result<()> {
+        if let (Some(path), Some(bundle)) = (&self.path, &self.bundle) {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let data = serde_json::to_string(bundle).map_err(|e| {
+                StoreError::SerializationError(format!("Failed to serialize pre-keys: {}", e))
+            })?;
+            std::fs::write(path, &data)?;
+
+            // Restrict file permissions to owner-only on Unix (0o600)
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let perms = std::fs::Permissions::from_mode(0o600);
+                let _ = std::fs::set_permissions(path, perms);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for PreKeyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consumption_triggers_replenish() {
+        let identity = HybridSigner::keygen().unwrap();
+        let mut store = PreKeyStore::new().with_limits(2, 4);
+        store.load_or_generate(&identity).unwrap();
+        assert_eq!(store.onetime_count(), 4);
+
+        let first = store.take_onetime_prekey(&identity).unwrap().unwrap();
+        let second = store.take_onetime_prekey(&identity).unwrap().unwrap();
+        assert_eq!(store.onetime_count(), 2);
+
+        // Dropping below the threshold refills to the target
+        store.take_onetime_prekey(&identity).unwrap().unwrap();
+        assert_eq!(store.onetime_count(), 4);
+
+        let bundle = store.bundle().unwrap();
+        bundle.verify().unwrap();
+        assert!(bundle
+            .onetime_prekeys
+            .iter()
+            .all(|k| k.id != first.id && k.id != second.id));
+    }
+
+    #[test]
+    fn test_persists_consumed_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prekeys.json");
+        let identity = HybridSigner::keygen().unwrap();
+
+        let mut store = PreKeyStore::open_at(path.clone())
+            .unwrap()
+            .with_limits(1, 3);
+        store.load_or_generate(&identity).unwrap();
+        let taken = store.take_onetime_prekey(&identity).unwrap().unwrap();
+
+        let reopened = PreKeyStore::open_at(path).unwrap();
+        let bundle = reopened.bundle().unwrap();
+        assert_eq!(bundle.onetime_count(), 2);
+        assert!(bundle.onetime_prekeys.iter().all(|k| k.id != taken.id));
+    }
+}
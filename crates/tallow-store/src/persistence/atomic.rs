@@ -0,0 +1,162 @@
+//! Atomic file replacement and advisory lock files
+//!
+//! Stores that are shared between processes (the TUI and the CLI can both
+//! be running) read-modify-write their file while holding a [`FileLock`],
+//! then publish the result with [`write_atomic`] so readers never observe
+//! a half-written file.
+
+use crate::{Result, StoreError};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long [`FileLock::acquire`] waits before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Poll interval while another process holds the lock
+const LOCK_POLL: Duration = Duration::from_millis(10);
+
+/// A lock file older than this is assumed to belong to a crashed process
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// `<path>.<suffix>`, keeping the original extension intact
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Replace `path` with `data` via a temp file and rename
+///
+/// The file is created owner-only (0o600) on Unix before it becomes
+/// visible under its final name.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = sibling(path, "tmp");
+    std::fs::write(&tmp_path, data)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let perms = std::fs::Permissions::from_mode(0o600);
+        let _ = std::fs::set_permissions(&tmp_path, perms);
+    }
+
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Exclusive advisory lock held through a `<path>.lock` file
+///
+/// The lock file is created with `create_new`, so only one holder can
+/// exist at a time; it is removed when the guard is dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    lock_path: PathBuf,
+}
+
+impl FileLock {
+    /// Acquire the lock guarding `path`, waiting for other holders
+    ///
+    /// A lock left behind by a crashed process is broken once it is older
+    /// than a generous staleness bound.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let lock_path = sibling(path, "lock");
+        let deadline = SystemTime::now() + LOCK_TIMEOUT;
+
+        loop {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => return Ok(Self { lock_path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if Self::is_stale(&lock_path) {
+                        tracing::warn!("Breaking stale lock {}", lock_path.display());
+                        let _ = std::fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    if SystemTime::now() >= deadline {
+                        return Err(StoreError::PersistenceError(format!(
+                            "Timed out waiting for lock {}",
+                            lock_path.display()
+                        )));
+                    }
+                    std::thread::sleep(LOCK_POLL);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Whether the lock file was last touched long enough ago to be abandoned
+    fn is_stale(lock_path: &Path) -> bool {
+        std::fs::metadata(lock_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > STALE_LOCK_AGE)
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.lock_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("store.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("store.json");
+
+        let lock = FileLock::acquire(&path).unwrap();
+        let contender = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let _lock = FileLock::acquire(&path).unwrap();
+                std::time::Instant::now()
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(50));
+        let released = std::time::Instant::now();
+        drop(lock);
+
+        let acquired = contender.join().unwrap();
+        assert!(
+            acquired >= released,
+            "second holder ran while lock was held"
+        );
+        assert!(!sibling(&path, "lock").exists());
+    }
+}
@@ -110,6 +110,37 @@ impl EncryptedKv {
         Ok(store)
     }
 
+    /// Open a store at the given path with a ready-made 32-byte key
+    ///
+    /// For keys that are already uniformly random (e.g. derived from an
+    /// identity), so the Argon2id stretch used for passphrases is skipped.
+    pub fn open_with_key(path: PathBuf, key: &[u8; 32]) -> Result<Self> {
+        let existing_data = if path.exists() {
+            let data = std::fs::read(&path)?;
+            Some(bincode::deserialize::<StoreData>(&data).map_err(|e| {
+                StoreError::PersistenceError(format!("Failed to parse store: {}", e))
+            })?)
+        } else {
+            None
+        };
+
+        let mut store = Self {
+            cache: HashMap::new(),
+            path,
+            master_key: *key,
+            master_salt: existing_data
+                .as_ref()
+                .and_then(|d| d.master_salt)
+                .unwrap_or_else(rand::random),
+        };
+
+        if let Some(store_data) = existing_data {
+            store.decrypt_entries(store_data)?;
+        }
+
+        Ok(store)
+    }
+
     /// Get a value by key
     pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
         Ok(self.cache.get(key).cloned())
@@ -153,10 +184,6 @@ impl EncryptedKv {
 
     /// Encrypt all entries and save to disk atomically
     fn save_to_disk(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
         let mut store_data = StoreData {
             master_salt: Some(self.master_salt),
             ..Default::default()
@@ -190,20 +217,7 @@ impl EncryptedKv {
             StoreError::SerializationError(format!("Failed to serialize store: {}", e))
         })?;
 
-        // Atomic write: write to temp file then rename
-        let tmp_path = self.path.with_extension("tmp");
-        std::fs::write(&tmp_path, &data)?;
-        std::fs::rename(&tmp_path, &self.path)?;
-
-        // Restrict file permissions to owner-only on Unix (0o600)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let perms = std::fs::Permissions::from_mode(0o600);
-            let _ = std::fs::set_permissions(&self.path, perms);
-        }
-
-        Ok(())
+        super::write_atomic(&self.path, &data)
     }
 }
 
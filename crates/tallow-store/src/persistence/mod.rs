@@ -1,10 +1,12 @@
 //! Persistent storage utilities
 
+pub mod atomic;
 pub mod encrypted_kv;
 pub mod paths;
 
+pub use atomic::{write_atomic, FileLock};
 pub use encrypted_kv::EncryptedKv;
 pub use paths::{
-    cache_dir, config_dir, config_file, data_dir, ensure_dirs, history_file, identity_file,
-    trust_file,
+    cache_dir, config_dir, config_file, data_dir, encrypted_history_file, ensure_dirs,
+    history_file, identity_file, profile, set_profile, trust_file, StorePaths, DEFAULT_PROFILE,
 };
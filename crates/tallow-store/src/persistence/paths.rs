@@ -1,61 +1,236 @@
 //! Platform-specific paths using XDG conventions
+//!
+//! Everything Tallow stores lives under one config, one data and one cache
+//! directory. A named profile (`--profile work`) moves all three to a
+//! `profiles/<name>` subdirectory, so each profile has its own identity,
+//! trust store, history and config. The default profile keeps the
+//! unprofiled locations, so existing installs are unaffected.
 
-use std::path::PathBuf;
+use crate::{Result, StoreError};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Name of the profile that uses the unprofiled directories
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Subdirectory holding named profiles
+const PROFILES_DIR: &str = "profiles";
+
+/// Longest accepted profile name
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Select the profile every path below resolves to
+///
+/// Call once at startup, before anything is loaded; later calls are
+/// ignored so a process never mixes two profiles' files. Selecting
+/// [`DEFAULT_PROFILE`] is the same as never calling this.
+pub fn set_profile(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    let profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    let _ = PROFILE.set(profile);
+    Ok(())
+}
+
+/// The selected profile, or `None` for the default profile
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().and_then(|p| p.as_deref())
+}
+
+/// Check that `name` is usable as a profile name
+///
+/// Names become directory names, so only ASCII letters, digits, `-` and
+/// `_` are allowed.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(StoreError::ConfigError(format!(
+            "invalid profile name '{}': use up to {} letters, digits, '-' or '_'",
+            name, MAX_PROFILE_NAME_LEN
+        )))
+    }
+}
+
+/// Storage directories of one profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorePaths {
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+}
+
+impl StorePaths {
+    /// Directories of the selected profile
+    pub fn current() -> Self {
+        Self::for_profile(profile())
+    }
+
+    /// Directories of `profile` (`None` for the default profile) under the
+    /// platform's config, data and cache directories
+    pub fn for_profile(profile: Option<&str>) -> Self {
+        let root =
+            |base: Option<PathBuf>| base.unwrap_or_else(|| PathBuf::from(".")).join("tallow");
+        Self {
+            config: profile_dir(root(dirs::config_dir()), profile),
+            data: profile_dir(root(dirs::data_dir()), profile),
+            cache: profile_dir(root(dirs::cache_dir()), profile),
+        }
+    }
+
+    /// Directories of `profile` with `root` standing in for all three
+    /// platform directories (portable installs, tests)
+    pub fn under(root: &Path, profile: Option<&str>) -> Self {
+        Self {
+            config: profile_dir(root.join("config"), profile),
+            data: profile_dir(root.join("data"), profile),
+            cache: profile_dir(root.join("cache"), profile),
+        }
+    }
+
+    /// Configuration directory
+    pub fn config_dir(&self) -> &Path {
+        &self.config
+    }
+
+    /// Data directory
+    pub fn data_dir(&self) -> &Path {
+        &self.data
+    }
+
+    /// Cache directory
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache
+    }
+
+    /// Config file path
+    pub fn config_file(&self) -> PathBuf {
+        self.config.join("config.toml")
+    }
+
+    /// Identity file path
+    pub fn identity_file(&self) -> PathBuf {
+        self.config.join("identity.enc")
+    }
+
+    /// Pre-key pool path
+    pub fn prekeys_file(&self) -> PathBuf {
+        self.data.join("prekeys.json")
+    }
+
+    /// Trust database path
+    pub fn trust_file(&self) -> PathBuf {
+        self.data.join("trust.json")
+    }
+
+    /// Trust introductions path
+    pub fn introductions_file(&self) -> PathBuf {
+        self.data.join("introductions.json")
+    }
+
+    /// Transfer history path
+    pub fn history_file(&self) -> PathBuf {
+        self.data.join("history.json")
+    }
+
+    /// Encrypted transfer history path
+    pub fn encrypted_history_file(&self) -> PathBuf {
+        self.data.join("history.enc")
+    }
+
+    /// Chat history file path
+    pub fn chat_history_file(&self) -> PathBuf {
+        self.data.join("chat_history.json")
+    }
+
+    /// Clipboard history file path
+    pub fn clipboard_history_file(&self) -> PathBuf {
+        self.data.join("clipboard_history.json")
+    }
+
+    /// Clipboard images directory path
+    pub fn clipboard_images_dir(&self) -> PathBuf {
+        self.data.join("clipboard_images")
+    }
+}
+
+/// `root` itself for the default profile, `root/profiles/<name>` otherwise
+fn profile_dir(root: PathBuf, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => root.join(PROFILES_DIR).join(name),
+        None => root,
+    }
+}
 
 /// Get configuration directory (~/.config/tallow or platform equivalent)
 pub fn config_dir() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("tallow")
+    StorePaths::current().config
 }
 
 /// Get data directory (~/.local/share/tallow or platform equivalent)
 pub fn data_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("tallow")
+    StorePaths::current().data
 }
 
 /// Get cache directory (~/.cache/tallow or platform equivalent)
 pub fn cache_dir() -> PathBuf {
-    dirs::cache_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("tallow")
+    StorePaths::current().cache
 }
 
 /// Get the config file path
 pub fn config_file() -> PathBuf {
-    config_dir().join("config.toml")
+    StorePaths::current().config_file()
 }
 
 /// Get the identity file path
 pub fn identity_file() -> PathBuf {
-    config_dir().join("identity.enc")
+    StorePaths::current().identity_file()
+}
+
+/// Get the pre-key pool path
+pub fn prekeys_file() -> PathBuf {
+    StorePaths::current().prekeys_file()
 }
 
 /// Get the trust database path
 pub fn trust_file() -> PathBuf {
-    data_dir().join("trust.json")
+    StorePaths::current().trust_file()
+}
+
+/// Get the trust introductions path
+pub fn introductions_file() -> PathBuf {
+    StorePaths::current().introductions_file()
 }
 
 /// Get the transfer history path
 pub fn history_file() -> PathBuf {
-    data_dir().join("history.json")
+    StorePaths::current().history_file()
+}
+
+/// Get the encrypted transfer history path
+pub fn encrypted_history_file() -> PathBuf {
+    StorePaths::current().encrypted_history_file()
 }
 
 /// Get the chat history file path
 pub fn chat_history_file() -> PathBuf {
-    data_dir().join("chat_history.json")
+    StorePaths::current().chat_history_file()
 }
 
 /// Get the clipboard history file path
 pub fn clipboard_history_file() -> PathBuf {
-    data_dir().join("clipboard_history.json")
+    StorePaths::current().clipboard_history_file()
 }
 
 /// Get the clipboard images directory path
 pub fn clipboard_images_dir() -> PathBuf {
-    data_dir().join("clipboard_images")
+    StorePaths::current().clipboard_images_dir()
 }
 
 /// Ensure all required directories exist with restrictive permissions
@@ -95,4 +270,111 @@ mod tests {
         let path = identity_file();
         assert!(path.ends_with("identity.enc"));
     }
+
+    #[test]
+    fn test_default_profile_uses_unprofiled_paths() {
+        let default = StorePaths::for_profile(None);
+        assert_eq!(
+            default.config_dir(),
+            dirs::config_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("tallow")
+        );
+        assert_eq!(
+            default.data_dir(),
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("tallow")
+        );
+        assert_eq!(
+            default.identity_file(),
+            default.config_dir().join("identity.enc")
+        );
+        assert_eq!(default.trust_file(), default.data_dir().join("trust.json"));
+
+        // Nothing in this test binary selects a profile
+        assert_eq!(profile(), None);
+        assert_eq!(StorePaths::current(), default);
+    }
+
+    #[test]
+    fn test_named_profile_is_namespaced() {
+        let work = StorePaths::for_profile(Some("work"));
+        let default = StorePaths::for_profile(None);
+        assert_eq!(
+            work.config_dir(),
+            default.config_dir().join("profiles").join("work")
+        );
+        assert_eq!(
+            work.data_dir(),
+            default.data_dir().join("profiles").join("work")
+        );
+        assert_eq!(
+            work.cache_dir(),
+            default.cache_dir().join("profiles").join("work")
+        );
+    }
+
+    #[test]
+    fn test_profiles_do_not_share_stores() {
+        use crate::history::{TransferDirection, TransferEntry, TransferLog, TransferStatus};
+        use crate::identity::IdentityStore;
+        use crate::trust::{TofuStore, TrustLevel};
+
+        let root = tempfile::tempdir().unwrap();
+        let a = StorePaths::under(root.path(), Some("a"));
+        let b = StorePaths::under(root.path(), Some("b"));
+
+        let mut identity = IdentityStore::with_path(a.identity_file());
+        identity.load_or_generate("").unwrap();
+        let mut trust = TofuStore::open_at(a.trust_file()).unwrap();
+        trust
+            .record_first_contact("alice".to_string(), vec![1; 32])
+            .unwrap();
+        let mut history = TransferLog::open_at(a.history_file()).unwrap();
+        history
+            .append(TransferEntry {
+                id: "t1".to_string(),
+                peer_id: "alice".to_string(),
+                direction: TransferDirection::Sent,
+                file_count: 1,
+                total_bytes: 10,
+                timestamp: 0,
+                status: TransferStatus::Completed,
+                filenames: vec!["a.txt".to_string()],
+                context: None,
+                duration_ms: None,
+                cipher: None,
+            })
+            .unwrap();
+
+        // Profile A sees its own state after reopening
+        assert!(IdentityStore::with_path(a.identity_file()).exists());
+        let reopened = TofuStore::open_at(a.trust_file()).unwrap();
+        assert_ne!(reopened.get_trust("alice"), TrustLevel::Unknown);
+
+        // Profile B sees none of it
+        assert!(!IdentityStore::with_path(b.identity_file()).exists());
+        let other = TofuStore::open_at(b.trust_file()).unwrap();
+        assert_eq!(other.get_trust("alice"), TrustLevel::Unknown);
+        assert!(TransferLog::open_at(b.history_file())
+            .unwrap()
+            .query()
+            .is_empty());
+
+        // Nor does the default profile under the same root
+        let default = StorePaths::under(root.path(), None);
+        assert!(!default.identity_file().exists());
+        assert!(!default.trust_file().exists());
+    }
+
+    #[test]
+    fn test_profile_names_validated() {
+        for ok in ["work", "personal-2", "a_b", DEFAULT_PROFILE] {
+            assert!(validate_profile_name(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "..", "a/b", "a\\b", "name with space", &"x".repeat(65)] {
+            assert!(validate_profile_name(bad).is_err(), "{:?}", bad);
+        }
+    }
 }
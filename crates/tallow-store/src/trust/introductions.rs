@@ -0,0 +1,317 @@
+//! Signed introductions (web-of-trust hints)
+//!
+//! An introduction is a statement signed by one identity vouching for
+//! another. They are advisory: a peer introduced by a trusted contact is
+//! shown as "introduced by ..." but its own trust level is never raised.
+//! Introductions only count while the introducer is still trusted.
+
+use super::{TofuStore, TrustLevel};
+use crate::identity::fingerprint::fingerprint_hex;
+use crate::persistence::paths;
+use crate::Result;
+use crate::StoreError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tallow_crypto::sig::{HybridPublicKey, HybridSignature, HybridSigner};
+
+/// Domain separator for introduction signatures
+const INTRODUCTION_DOMAIN: &[u8] = b"tallow-introduction-v1:";
+
+/// A signed statement that `introducer` vouches for `subject`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Introduction {
+    /// Hybrid public key of the identity making the introduction
+    pub introducer: HybridPublicKey,
+    /// Fingerprint of the identity being introduced
+    pub subject: String,
+    /// Unix timestamp (seconds) when the introduction was signed
+    pub timestamp: u64,
+    /// Hybrid signature over the subject and timestamp
+    pub signature: HybridSignature,
+}
+
+impl Introduction {
+    /// Sign an introduction of `subject` (a fingerprint) as `introducer`
+    pub fn create(introducer: &HybridSigner, subject: &str) -> Result<Self> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let signature = introducer
+            .sign(&signed_message(subject, timestamp))
+            .map_err(|e| StoreError::TrustError(format!("Failed to sign introduction: {}", e)))?;
+
+        Ok(Self {
+            introducer: introducer.public_key(),
+            subject: subject.to_string(),
+            timestamp,
+            signature,
+        })
+    }
+
+    /// Verify the introducer's signature
+    pub fn verify(&self) -> Result<()> {
+        let message = signed_message(&self.subject, self.timestamp);
+        tallow_crypto::sig::hybrid::verify(&self.introducer, &message, &self.signature)
+            .map_err(|_| StoreError::TrustError("Introduction signature is invalid".to_string()))
+    }
+
+    /// Fingerprint of the introducer, in the same form as identity fingerprints
+    pub fn introducer_fingerprint(&self) -> Result<String> {
+        identity_fingerprint(&self.introducer)
+    }
+}
+
+/// An identity that has introduced a given peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Introducer {
+    /// Introducer's fingerprint
+    pub fingerprint: String,
+    /// Current trust level of the introducer
+    pub trust_level: TrustLevel,
+    /// When the introduction was signed
+    pub timestamp: u64,
+}
+
+/// Persistent store of verified introductions
+pub struct IntroductionStore {
+    /// Verified introductions, at most one per (introducer, subject)
+    records: Vec<Introduction>,
+    /// Path for persistence
+    path: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for IntroductionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntroductionStore")
+            .field("records", &self.records.len())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl IntroductionStore {
+    /// Create a new in-memory introduction store
+    pub fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Open a persistent introduction store at the default path
+    pub fn open() -> Result<Self> {
+        Self::open_at(paths::introductions_file())
+    }
+
+    /// Open a persistent introduction store at a custom path
+    pub fn open_at(path: PathBuf) -> Result<Self> {
+        let mut store = Self {
+            records: Vec::new(),
+            path: Some(path),
+        };
+
+        if let Some(ref p) = store.path {
+            if p.exists() {
+                let data = std::fs::read_to_string(p)?;
+                store.records = serde_json::from_str(&data).map_err(|e| {
+                    StoreError::TrustError(format!("Failed to parse introductions: {}", e))
+                })?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Record an introduction after verifying its signature
+    ///
+    /// A newer introduction of the same subject by the same introducer
+    /// replaces the older one; an older one is ignored.
+    pub fn add(&mut self, introduction: Introduction) -> Result<()> {
+        introduction.verify()?;
+        let introducer = introduction.introducer_fingerprint()?;
+        if introducer == introduction.subject {
+            return Err(StoreError::TrustError(
+                "An identity cannot introduce itself".to_string(),
+            ));
+        }
+
+        let existing = self.records.iter().position(|r| {
+            r.subject == introduction.subject
+                && r.introducer_fingerprint().ok().as_deref() == Some(introducer.as_str())
+        });
+        match existing {
+            Some(i) if self.records[i].timestamp >= introduction.timestamp => return Ok(()),
+            Some(i) => self.records[i] = introduction,
+            None => self.records.push(introduction),
+        }
+        self.save()
+    }
+
+    /// Identities that introduced `fingerprint` and are still trusted
+    ///
+    /// Introducers below [`TrustLevel::Trusted`] in `trust` are left out,
+    /// so revoking trust in an introducer hides everything they vouched for.
+    pub fn who_introduced(&self, fingerprint: &str, trust: &TofuStore) -> Vec<Introducer> {
+        let mut introducers: Vec<Introducer> = self
+            .records
+            .iter()
+            .filter(|r| r.subject == fingerprint)
+            .filter_map(|r| {
+                let introducer = r.introducer_fingerprint().ok()?;
+                let trust_level = trust.get_trust(&introducer);
+                trust_level.auto_accept().then_some(Introducer {
+                    fingerprint: introducer,
+                    trust_level,
+                    timestamp: r.timestamp,
+                })
+            })
+            .collect();
+        introducers.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        introducers
+    }
+
+    /// Save to disk if persistent
+    fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let data = serde_json::to_string(&self.records).map_err(|e| {
+                StoreError::SerializationError(format!("Failed to serialize introductions: {}", e))
+            })?;
+            std::fs::write(path, &data)?;
+
+            // Restrict file permissions to owner-only on Unix (0o600)
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let perms = std::fs::Permissions::from_mode(0o600);
+                let _ = std::fs::set_permissions(path, perms);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for IntroductionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fingerprint of a hybrid identity key, matching `IdentityStore::fingerprint`
+pub fn identity_fingerprint(public_key: &HybridPublicKey) -> Result<String> {
+    let pk_bytes = bincode::serialize(public_key).map_err(|e| {
+        StoreError::SerializationError(format!("Failed to serialize public key: {}", e))
+    })?;
+    Ok(fingerprint_hex(blake3::hash(&pk_bytes).as_bytes()))
+}
+
+/// Message signed by the introducer
+fn signed_message(subject: &str, timestamp: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(INTRODUCTION_DOMAIN.len() + 12 + subject.len());
+    message.extend_from_slice(INTRODUCTION_DOMAIN);
+    message.extend_from_slice(&(subject.len() as u32).to_le_bytes());
+    message.extend_from_slice(subject.as_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trusted(store: &mut TofuStore, signer: &HybridSigner) -> String {
+        let fingerprint = identity_fingerprint(&signer.public_key()).unwrap();
+        store
+            .record_first_contact(fingerprint.clone(), vec![1])
+            .unwrap();
+        store
+            .update_trust(&fingerprint, TrustLevel::Trusted)
+            .unwrap();
+        fingerprint
+    }
+
+    #[test]
+    fn test_valid_introduction_recorded_and_queryable() {
+        let alice = HybridSigner::keygen().unwrap();
+        let mut trust = TofuStore::new();
+        let alice_fp = trusted(&mut trust, &alice);
+
+        let mut store = IntroductionStore::new();
+        store
+            .add(Introduction::create(&alice, "bob-fingerprint").unwrap())
+            .unwrap();
+
+        let introducers = store.who_introduced("bob-fingerprint", &trust);
+        assert_eq!(introducers.len(), 1);
+        assert_eq!(introducers[0].fingerprint, alice_fp);
+        assert_eq!(introducers[0].trust_level, TrustLevel::Trusted);
+        assert!(store.who_introduced("carol-fingerprint", &trust).is_empty());
+
+        // Advisory only: the subject's own trust is untouched
+        assert_eq!(trust.get_trust("bob-fingerprint"), TrustLevel::Unknown);
+    }
+
+    #[test]
+    fn test_bad_signature_rejected() {
+        let alice = HybridSigner::keygen().unwrap();
+        let mut introduction = Introduction::create(&alice, "bob-fingerprint").unwrap();
+        introduction.subject = "mallory-fingerprint".to_string();
+
+        let mut store = IntroductionStore::new();
+        assert!(store.add(introduction).is_err());
+
+        let mut trust = TofuStore::new();
+        trusted(&mut trust, &alice);
+        assert!(store
+            .who_introduced("mallory-fingerprint", &trust)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_revoked_introducer_hidden() {
+        let alice = HybridSigner::keygen().unwrap();
+        let mut trust = TofuStore::new();
+        let alice_fp = trusted(&mut trust, &alice);
+
+        let mut store = IntroductionStore::new();
+        store
+            .add(Introduction::create(&alice, "bob-fingerprint").unwrap())
+            .unwrap();
+        assert_eq!(store.who_introduced("bob-fingerprint", &trust).len(), 1);
+
+        trust
+            .update_trust_force(&alice_fp, TrustLevel::Seen)
+            .unwrap();
+        assert!(store.who_introduced("bob-fingerprint", &trust).is_empty());
+    }
+
+    #[test]
+    fn test_self_introduction_rejected() {
+        let alice = HybridSigner::keygen().unwrap();
+        let alice_fp = identity_fingerprint(&alice.public_key()).unwrap();
+        let mut store = IntroductionStore::new();
+        assert!(store
+            .add(Introduction::create(&alice, &alice_fp).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("introductions.json");
+        let alice = HybridSigner::keygen().unwrap();
+        let mut trust = TofuStore::new();
+        trusted(&mut trust, &alice);
+
+        IntroductionStore::open_at(path.clone())
+            .unwrap()
+            .add(Introduction::create(&alice, "bob-fingerprint").unwrap())
+            .unwrap();
+
+        let store = IntroductionStore::open_at(path).unwrap();
+        assert_eq!(store.who_introduced("bob-fingerprint", &trust).len(), 1);
+    }
+}
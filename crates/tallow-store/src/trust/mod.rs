@@ -1,7 +1,9 @@
 //! Trust management and TOFU
 
+pub mod introductions;
 pub mod levels;
 pub mod tofu;
 
+pub use introductions::{Introducer, Introduction, IntroductionStore};
 pub use levels::TrustLevel;
 pub use tofu::TofuStore;
@@ -1,12 +1,17 @@
 //! Trust On First Use (TOFU) implementation with file persistence
+//!
+//! The trust file can be written by several processes at once (the TUI and
+//! the CLI), so saving is merge-on-write: under a lock, the on-disk version
+//! is re-read and only the peers this store touched are merged into it.
 
 use super::TrustLevel;
-use crate::persistence::paths;
+use crate::persistence::{paths, write_atomic, FileLock};
 use crate::Result;
 use crate::StoreError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Serializable TOFU record
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +19,27 @@ struct TofuRecord {
     public_key: Vec<u8>,
     trust_level: TrustLevel,
     first_seen: u64,
+    /// When the trust level was last set explicitly (0 = never)
+    #[serde(default)]
+    verified_at: u64,
+}
+
+impl TofuRecord {
+    /// Deterministic conflict order between two versions of the same peer
+    ///
+    /// The most recently verified record wins. Between records verified at
+    /// the same time (or never), the earliest first contact wins, as TOFU
+    /// would have pinned it, then the smaller key and the higher trust level
+    /// break any remaining tie so every writer reaches the same answer.
+    fn precedence(&self, other: &Self) -> Ordering {
+        self.verified_at
+            .cmp(&other.verified_at)
+            .then_with(|| other.first_seen.cmp(&self.first_seen))
+            .then_with(|| other.public_key.cmp(&self.public_key))
+            .then_with(|| {
+                TofuStore::rank(self.trust_level).cmp(&TofuStore::rank(other.trust_level))
+            })
+    }
 }
 
 /// TOFU database with optional file persistence
@@ -23,6 +49,10 @@ pub struct TofuStore {
     records: HashMap<String, TofuRecord>,
     /// Path for persistence
     path: Option<PathBuf>,
+    /// Peers added or changed since the last save
+    dirty: HashSet<String>,
+    /// Peers removed since the last save
+    removed: HashSet<String>,
 }
 
 impl TofuStore {
@@ -31,6 +61,8 @@ impl TofuStore {
         Self {
             records: HashMap::new(),
             path: None,
+            dirty: HashSet::new(),
+            removed: HashSet::new(),
         }
     }
 
@@ -41,21 +73,22 @@ impl TofuStore {
 
     /// Open a persistent TOFU store at a custom path
     pub fn open_at(path: PathBuf) -> Result<Self> {
-        let mut store = Self {
-            records: HashMap::new(),
+        Ok(Self {
+            records: Self::load(&path)?,
             path: Some(path),
-        };
+            dirty: HashSet::new(),
+            removed: HashSet::new(),
+        })
+    }
 
-        if let Some(ref p) = store.path {
-            if p.exists() {
-                let data = std::fs::read_to_string(p)?;
-                store.records = serde_json::from_str(&data).map_err(|e| {
-                    StoreError::TrustError(format!("Failed to parse trust store: {}", e))
-                })?;
-            }
+    /// Read the records currently on disk (empty if the file doesn't exist)
+    fn load(path: &Path) -> Result<HashMap<String, TofuRecord>> {
+        if !path.exists() {
+            return Ok(HashMap::new());
         }
-
-        Ok(store)
+        let data = std::fs::read_to_string(path)?;
+        serde_json::from_str(&data)
+            .map_err(|e| StoreError::TrustError(format!("Failed to parse trust store: {}", e)))
     }
 
     /// Record first contact with a peer
@@ -74,13 +107,16 @@ impl TofuStore {
             .as_secs();
 
         self.records.insert(
-            peer_id,
+            peer_id.clone(),
             TofuRecord {
                 public_key,
                 trust_level: TrustLevel::Seen,
                 first_seen: timestamp,
+                verified_at: 0,
             },
         );
+        self.removed.remove(&peer_id);
+        self.dirty.insert(peer_id);
         self.save()
     }
 
@@ -93,6 +129,33 @@ impl TofuStore {
             .map(|record| !tallow_crypto::mem::ct_eq(&record.public_key, current_key))
     }
 
+    /// Key pinned for a peer, if it has been seen
+    pub fn pinned_key(&self, peer_id: &str) -> Option<&[u8]> {
+        self.records
+            .get(peer_id)
+            .map(|record| record.public_key.as_slice())
+    }
+
+    /// Mark a peer as verified out-of-band, pinning `public_key` first if
+    /// the peer has not been seen
+    ///
+    /// Refuses when a different key is already pinned: verifying the new
+    /// key would silently replace the old pin. Remove the peer first if the
+    /// key change is expected.
+    pub fn mark_verified(&mut self, peer_id: &str, public_key: &[u8]) -> Result<()> {
+        match self.check_key_change(peer_id, public_key) {
+            Some(true) => {
+                return Err(StoreError::TrustError(format!(
+                    "Peer '{}' is pinned to a different key; remove it before verifying",
+                    peer_id
+                )))
+            }
+            Some(false) => {}
+            None => self.record_first_contact(peer_id.to_string(), public_key.to_vec())?,
+        }
+        self.update_trust(peer_id, TrustLevel::Verified)
+    }
+
     /// Update trust level for a peer
     ///
     /// Prevents accidental trust downgrades: you cannot lower trust from
@@ -118,6 +181,11 @@ impl TofuStore {
                 )));
             }
             record.trust_level = level;
+            record.verified_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            self.dirty.insert(peer_id.to_string());
             self.save()?;
         }
         Ok(())
@@ -125,15 +193,17 @@ impl TofuStore {
 
     /// Check if changing from `old` to `new` is a trust downgrade
     fn is_downgrade(old: TrustLevel, new: TrustLevel) -> bool {
-        let rank = |t: TrustLevel| -> u8 {
-            match t {
-                TrustLevel::Unknown => 0,
-                TrustLevel::Seen => 1,
-                TrustLevel::Trusted => 2,
-                TrustLevel::Verified => 3,
-            }
-        };
-        rank(new) < rank(old)
+        Self::rank(new) < Self::rank(old)
+    }
+
+    /// Ordering of trust levels from weakest to strongest
+    fn rank(level: TrustLevel) -> u8 {
+        match level {
+            TrustLevel::Unknown => 0,
+            TrustLevel::Seen => 1,
+            TrustLevel::Trusted => 2,
+            TrustLevel::Verified => 3,
+        }
     }
 
     /// Get trust level for a peer
@@ -155,28 +225,52 @@ impl TofuStore {
     /// Remove a peer from the trust store
     pub fn remove_peer(&mut self, peer_id: &str) -> Result<()> {
         self.records.remove(peer_id);
+        self.dirty.remove(peer_id);
+        self.removed.insert(peer_id.to_string());
         self.save()
     }
 
-    /// Save to disk if persistent
-    fn save(&self) -> Result<()> {
-        if let Some(ref path) = self.path {
-            if let Some(parent) = path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            let data = serde_json::to_string_pretty(&self.records).map_err(|e| {
-                StoreError::SerializationError(format!("Failed to serialize trust store: {}", e))
-            })?;
-            std::fs::write(path, &data)?;
-
-            // Restrict file permissions to owner-only on Unix (0o600)
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let perms = std::fs::Permissions::from_mode(0o600);
-                let _ = std::fs::set_permissions(path, perms);
+    /// Merge local changes into the on-disk store and save it, if persistent
+    ///
+    /// Peers this store didn't touch are taken from disk as-is, so edits made
+    /// by another process since we loaded survive. Peers changed on both
+    /// sides are resolved by [`TofuRecord::precedence`]. Afterwards the
+    /// in-memory view matches what was written.
+    fn save(&mut self) -> Result<()> {
+        let Some(path) = self.path.clone() else {
+            self.dirty.clear();
+            self.removed.clear();
+            return Ok(());
+        };
+
+        let _lock = FileLock::acquire(&path)?;
+        let mut merged = Self::load(&path)?;
+
+        for peer_id in &self.removed {
+            merged.remove(peer_id);
+        }
+        for peer_id in &self.dirty {
+            let Some(local) = self.records.get(peer_id) else {
+                continue;
+            };
+            match merged.get(peer_id) {
+                Some(on_disk) if on_disk.precedence(local) == Ordering::Greater => {
+                    tracing::debug!("Trust store: keeping newer on-disk record for {}", peer_id);
+                }
+                _ => {
+                    merged.insert(peer_id.clone(), local.clone());
+                }
             }
         }
+
+        let data = serde_json::to_string_pretty(&merged).map_err(|e| {
+            StoreError::SerializationError(format!("Failed to serialize trust store: {}", e))
+        })?;
+        write_atomic(&path, data.as_bytes())?;
+
+        self.records = merged;
+        self.dirty.clear();
+        self.removed.clear();
         Ok(())
     }
 }
@@ -290,4 +384,146 @@ mod tests {
             .unwrap();
         assert_eq!(store.get_trust("peer-1"), TrustLevel::Seen);
     }
+
+    #[test]
+    fn test_mark_verified_upgrades_pin() {
+        let mut store = TofuStore::new();
+        store
+            .record_first_contact("peer-1".to_string(), vec![1, 2, 3])
+            .unwrap();
+        store.mark_verified("peer-1", &[1, 2, 3]).unwrap();
+        assert_eq!(store.get_trust("peer-1"), TrustLevel::Verified);
+
+        // An unseen peer is pinned and verified in one step
+        store.mark_verified("peer-2", &[4, 5, 6]).unwrap();
+        assert_eq!(store.get_trust("peer-2"), TrustLevel::Verified);
+        assert_eq!(store.pinned_key("peer-2"), Some(&[4u8, 5, 6][..]));
+    }
+
+    #[test]
+    fn test_mark_verified_rejects_different_key() {
+        let mut store = TofuStore::new();
+        store
+            .record_first_contact("peer-1".to_string(), vec![1, 2, 3])
+            .unwrap();
+        assert!(store.mark_verified("peer-1", &[9, 9, 9]).is_err());
+        assert_eq!(store.get_trust("peer-1"), TrustLevel::Seen);
+        assert_eq!(store.pinned_key("peer-1"), Some(&[1u8, 2, 3][..]));
+    }
+
+    fn record(key: u8, first_seen: u64, verified_at: u64, level: TrustLevel) -> TofuRecord {
+        TofuRecord {
+            public_key: vec![key; 4],
+            trust_level: level,
+            first_seen,
+            verified_at,
+        }
+    }
+
+    /// Two stores loaded before either writes, each pinning `peer-x`
+    /// differently; returns the key that ends up on disk.
+    fn save_conflicting(path: &Path, first: TofuRecord, second: TofuRecord) -> Vec<u8> {
+        let mut a = TofuStore::open_at(path.to_path_buf()).unwrap();
+        let mut b = TofuStore::open_at(path.to_path_buf()).unwrap();
+        for (store, rec) in [(&mut a, first), (&mut b, second)] {
+            store.records.insert("peer-x".to_string(), rec);
+            store.dirty.insert("peer-x".to_string());
+        }
+        a.save().unwrap();
+        b.save().unwrap();
+
+        let reopened = TofuStore::open_at(path.to_path_buf()).unwrap();
+        assert_eq!(reopened.records.len(), 1);
+        assert_eq!(
+            b.records["peer-x"].public_key, reopened.records["peer-x"].public_key,
+            "writer's view should match disk after merge"
+        );
+        reopened.records["peer-x"].public_key.clone()
+    }
+
+    #[test]
+    fn test_concurrent_edits_both_survive() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trust.json");
+
+        let mut seed = TofuStore::open_at(path.clone()).unwrap();
+        seed.record_first_contact("peer-1".to_string(), vec![1])
+            .unwrap();
+
+        // Both processes load the same snapshot
+        let mut tui = TofuStore::open_at(path.clone()).unwrap();
+        let mut cli = TofuStore::open_at(path.clone()).unwrap();
+
+        tui.update_trust("peer-1", TrustLevel::Verified).unwrap();
+        // The CLI's copy of peer-1 is stale but untouched, so it must not
+        // clobber the TUI's verification
+        cli.record_first_contact("peer-2".to_string(), vec![2])
+            .unwrap();
+
+        let store = TofuStore::open_at(path).unwrap();
+        assert_eq!(store.get_trust("peer-1"), TrustLevel::Verified);
+        assert_eq!(store.get_trust("peer-2"), TrustLevel::Seen);
+        assert_eq!(cli.get_trust("peer-1"), TrustLevel::Verified);
+    }
+
+    #[test]
+    fn test_stale_writer_does_not_resurrect_removed_peer() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trust.json");
+
+        let mut seed = TofuStore::open_at(path.clone()).unwrap();
+        seed.record_first_contact("peer-1".to_string(), vec![1])
+            .unwrap();
+
+        let mut a = TofuStore::open_at(path.clone()).unwrap();
+        let mut b = TofuStore::open_at(path.clone()).unwrap();
+        a.remove_peer("peer-1").unwrap();
+        b.record_first_contact("peer-2".to_string(), vec![2])
+            .unwrap();
+
+        let store = TofuStore::open_at(path).unwrap();
+        assert_eq!(store.get_trust("peer-1"), TrustLevel::Unknown);
+        assert_eq!(store.get_trust("peer-2"), TrustLevel::Seen);
+    }
+
+    #[test]
+    fn test_fingerprint_conflict_prefers_most_recently_verified() {
+        let older = record(1, 100, 200, TrustLevel::Verified);
+        let newer = record(2, 150, 300, TrustLevel::Trusted);
+
+        for (first, second) in [
+            (older.clone(), newer.clone()),
+            (newer.clone(), older.clone()),
+        ] {
+            let dir = TempDir::new().unwrap();
+            let path = dir.path().join("trust.json");
+            assert_eq!(save_conflicting(&path, first, second), vec![2; 4]);
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_conflict_is_deterministic() {
+        // Neither pin was verified: the first contact wins, then the
+        // smaller key when both were seen at the same moment
+        let cases = [
+            (
+                record(7, 100, 0, TrustLevel::Seen),
+                record(3, 120, 0, TrustLevel::Seen),
+                7,
+            ),
+            (
+                record(7, 100, 0, TrustLevel::Seen),
+                record(3, 100, 0, TrustLevel::Seen),
+                3,
+            ),
+        ];
+
+        for (x, y, winner) in cases {
+            for (first, second) in [(x.clone(), y.clone()), (y.clone(), x.clone())] {
+                let dir = TempDir::new().unwrap();
+                let path = dir.path().join("trust.json");
+                assert_eq!(save_conflicting(&path, first, second), vec![winner; 4]);
+            }
+        }
+    }
 }
@@ -1,6 +1,8 @@
 //! Application state
 
 use crate::modes::TuiMode;
+use crate::overlays::sas::{SasState, SasVerification};
+use crate::security::WipeRegistry;
 use crate::widgets::spinner::Spinner;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -52,6 +54,12 @@ pub enum TransferDirection {
     Receive,
 }
 
+/// How long a discovered peer stays listed without being re-announced
+pub const PEER_TTL: Duration = Duration::from_secs(30);
+
+/// Age after which a peer is shown as stale while it waits out its TTL
+pub const PEER_STALE_AFTER: Duration = Duration::from_secs(15);
+
 /// Discovered peer for device panel
 #[derive(Debug, Clone)]
 pub struct PeerInfo {
@@ -61,6 +69,15 @@ pub struct PeerInfo {
     pub address: String,
     /// Whether verified via TOFU
     pub verified: bool,
+    /// When the peer was last announced
+    pub last_seen: Instant,
+}
+
+impl PeerInfo {
+    /// Whether the peer has gone quiet long enough to be flagged as stale
+    pub fn is_stale(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_seen) >= PEER_STALE_AFTER
+    }
 }
 
 /// Actions sent from background tasks to the TUI main loop
@@ -115,6 +132,15 @@ pub enum TuiAction {
     },
     /// A peer left the room
     PeerLeft,
+    /// A peer was announced by local discovery (new or re-announced)
+    PeerDiscovered {
+        /// Peer display name
+        name: String,
+        /// Peer address
+        address: String,
+        /// Whether verified via TOFU
+        verified: bool,
+    },
 
     /// User wants to send files (forwarded to background tasks)
     InitiateSend {
@@ -129,7 +155,24 @@ pub enum TuiAction {
         code: String,
         /// Relay address
         relay: String,
+        /// Put a small text transfer on the clipboard instead of saving it
+        to_clipboard: bool,
+    },
+
+    /// Handshake finished; the user must compare the SAS before anything
+    /// is transferred
+    SasVerificationRequired {
+        /// Session transcript the SAS is derived from
+        transcript: Vec<u8>,
     },
+    /// The peer confirmed its SAS matches
+    PeerSasConfirmed,
+    /// The peer reported a SAS mismatch
+    PeerSasDeclined,
+    /// User confirmed the SAS matches (forwarded to background tasks)
+    SasConfirmed,
+    /// User reported a SAS mismatch (forwarded to background tasks)
+    SasDeclined,
 
     /// Quit the TUI
     Quit,
@@ -149,6 +192,8 @@ pub enum Overlay {
         /// File size in bytes
         size: u64,
     },
+    /// SAS comparison; blocks until confirmed or declined
+    SasVerify,
 }
 
 /// Status of an active transfer
@@ -230,6 +275,14 @@ pub struct App {
     pub tick_count: u64,
     /// Spinner for animated status indicator
     pub spinner: Spinner,
+    /// SAS comparison for the current connection, if one was required
+    pub sas: Option<SasVerification>,
+    /// User decisions waiting to be forwarded to background tasks
+    pub outgoing: Vec<TuiAction>,
+    /// Key buffers and connections destroyed by the panic button
+    pub wipe: WipeRegistry,
+    /// Set once the panic button has fired
+    pub wiped: bool,
 }
 
 impl App {
@@ -253,6 +306,10 @@ impl App {
             active_transfers: HashMap::new(),
             tick_count: 0,
             spinner: Spinner::with_label(""),
+            sas: None,
+            outgoing: Vec::new(),
+            wipe: WipeRegistry::new(),
+            wiped: false,
         }
     }
 
@@ -261,6 +318,35 @@ impl App {
         self.running = false;
     }
 
+    /// Emergency wipe: destroy all session state and stop the app
+    ///
+    /// Registered hooks zeroize keys and tear down connections before this
+    /// returns. Everything else that could identify the session is dropped
+    /// from the display state. Triggering it again is a no-op.
+    pub fn panic_wipe(&mut self) {
+        if self.wiped {
+            return;
+        }
+        let hooks = self.wipe.wipe_all();
+        tracing::debug!("emergency wipe ran {} hooks", hooks);
+
+        self.sas = None;
+        self.outgoing.clear();
+        self.active_transfers.clear();
+        self.transfers.clear();
+        self.peers.clear();
+        self.overlays.clear();
+        self.show_help = false;
+        self.relay_addr = None;
+        self.room_code = None;
+        self.connected = false;
+        self.identity_fingerprint = None;
+        self.status_message.clear();
+
+        self.wiped = true;
+        self.running = false;
+    }
+
     /// Toggle help overlay (backward-compatible)
     pub fn toggle_help(&mut self) {
         if self.show_help {
@@ -304,10 +390,103 @@ impl App {
         self.overlays.last()
     }
 
-    /// Advance tick counter and spinner
+    /// Whether transfers must wait for (or were refused by) SAS verification
+    pub fn transfers_blocked(&self) -> bool {
+        self.sas
+            .as_ref()
+            .is_some_and(|v| v.state() != SasState::Verified)
+    }
+
+    /// Start a SAS comparison and show the verification overlay
+    pub fn begin_sas(&mut self, transcript: &[u8]) {
+        self.sas = Some(SasVerification::from_transcript(transcript));
+        self.status_message = "Compare the verification code with your peer".to_string();
+        self.push_overlay(Overlay::SasVerify);
+    }
+
+    /// Local user confirmed the codes match
+    pub fn confirm_sas(&mut self) {
+        let Some(state) = self.sas.as_mut().map(|v| v.confirm_local()) else {
+            return;
+        };
+        self.outgoing.push(TuiAction::SasConfirmed);
+        if state == SasState::Pending {
+            self.status_message = "Waiting for peer to confirm the code".to_string();
+        }
+        self.settle_sas(state);
+    }
+
+    /// Local user reported that the codes differ
+    pub fn decline_sas(&mut self) {
+        let Some(state) = self.sas.as_mut().map(|v| v.decline()) else {
+            return;
+        };
+        self.outgoing.push(TuiAction::SasDeclined);
+        if state == SasState::Aborted {
+            self.abort_sas("Verification codes did not match -- connection aborted");
+        }
+    }
+
+    /// Take the user decisions queued for background tasks
+    pub fn take_outgoing(&mut self) -> Vec<TuiAction> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    fn settle_sas(&mut self, state: SasState) {
+        match state {
+            SasState::Pending => {}
+            SasState::Verified => {
+                self.overlays.retain(|o| *o != Overlay::SasVerify);
+                self.status_message = "Connection verified".to_string();
+            }
+            SasState::Aborted => {
+                self.abort_sas("Verification codes did not match -- connection aborted");
+            }
+        }
+    }
+
+    fn abort_sas(&mut self, message: &str) {
+        self.overlays.retain(|o| *o != Overlay::SasVerify);
+        self.status_message = message.to_string();
+        for transfer in self.active_transfers.values_mut() {
+            if !matches!(transfer.status, TransferStatus::Complete { .. }) {
+                transfer.status = TransferStatus::Failed {
+                    error: "verification code mismatch".to_string(),
+                };
+            }
+        }
+        self.sync_transfer_info();
+    }
+
+    /// Advance tick counter and spinner, and drop peers past their TTL
     pub fn tick(&mut self) {
         self.tick_count += 1;
         self.spinner.tick();
+        self.evict_stale_peers(Instant::now());
+    }
+
+    /// Record a discovery announcement, resetting the peer's TTL
+    ///
+    /// Peers are keyed by name; a re-announcement updates the address.
+    pub fn peer_seen(&mut self, name: String, address: String, verified: bool, now: Instant) {
+        if let Some(peer) = self.peers.iter_mut().find(|p| p.name == name) {
+            peer.address = address;
+            peer.verified = verified;
+            peer.last_seen = now;
+        } else {
+            self.peers.push(PeerInfo {
+                name,
+                address,
+                verified,
+                last_seen: now,
+            });
+        }
+    }
+
+    /// Remove peers not announced within [`PEER_TTL`] of `now`
+    pub fn evict_stale_peers(&mut self, now: Instant) {
+        self.peers
+            .retain(|p| now.saturating_duration_since(p.last_seen) < PEER_TTL);
     }
 
     /// Process an incoming TuiAction
@@ -319,6 +498,10 @@ impl App {
                 total_bytes,
                 direction,
             } => {
+                if self.transfers_blocked() {
+                    tracing::warn!("ignoring transfer start before SAS verification");
+                    return;
+                }
                 let transfer = ActiveTransfer {
                     id,
                     filename,
@@ -373,14 +556,42 @@ impl App {
             TuiAction::PeerJoined { room_code } => {
                 self.room_code = Some(room_code);
             }
+            TuiAction::PeerDiscovered {
+                name,
+                address,
+                verified,
+            } => {
+                self.peer_seen(name, address, verified, Instant::now());
+            }
             TuiAction::PeerLeft => {
                 self.room_code = None;
             }
+            TuiAction::SasVerificationRequired { transcript } => {
+                self.begin_sas(&transcript);
+            }
+            TuiAction::PeerSasConfirmed => {
+                if let Some(state) = self.sas.as_mut().map(|v| v.confirm_peer()) {
+                    self.settle_sas(state);
+                }
+            }
+            TuiAction::PeerSasDeclined => {
+                if let Some(v) = self.sas.as_mut() {
+                    if v.decline() == SasState::Aborted {
+                        self.abort_sas(
+                            "Peer reported a verification code mismatch -- connection aborted",
+                        );
+                    }
+                }
+            }
             TuiAction::Quit => {
                 self.running = false;
             }
-            // InitiateSend / InitiateReceive are handled by spawning tasks
-            TuiAction::InitiateSend { .. } | TuiAction::InitiateReceive { .. } => {}
+            // InitiateSend / InitiateReceive are handled by spawning tasks;
+            // SasConfirmed / SasDeclined only flow outward
+            TuiAction::InitiateSend { .. }
+            | TuiAction::InitiateReceive { .. }
+            | TuiAction::SasConfirmed
+            | TuiAction::SasDeclined => {}
         }
     }
 
@@ -600,6 +811,44 @@ mod tests {
         assert!(app.room_code.is_none());
     }
 
+    #[test]
+    fn test_discovered_peer_evicted_after_ttl() {
+        let mut app = App::new();
+        let start = Instant::now();
+        app.peer_seen("laptop".into(), "192.168.1.5:4433".into(), false, start);
+
+        let peer = &app.peers[0];
+        assert!(!peer.is_stale(start));
+        assert!(peer.is_stale(start + PEER_STALE_AFTER));
+
+        // Stale but still listed until the TTL runs out
+        app.evict_stale_peers(start + PEER_TTL - Duration::from_secs(1));
+        assert_eq!(app.peers.len(), 1);
+
+        app.evict_stale_peers(start + PEER_TTL);
+        assert!(app.peers.is_empty());
+    }
+
+    #[test]
+    fn test_reannouncement_resets_ttl() {
+        let mut app = App::new();
+        let start = Instant::now();
+        app.peer_seen("laptop".into(), "192.168.1.5:4433".into(), false, start);
+
+        let later = start + Duration::from_secs(20);
+        app.peer_seen("laptop".into(), "192.168.1.9:4433".into(), true, later);
+        assert_eq!(app.peers.len(), 1);
+        assert_eq!(app.peers[0].address, "192.168.1.9:4433");
+        assert!(!app.peers[0].is_stale(later));
+
+        // Past the original deadline, but within the refreshed one
+        app.evict_stale_peers(start + PEER_TTL);
+        assert_eq!(app.peers.len(), 1);
+
+        app.evict_stale_peers(later + PEER_TTL);
+        assert!(app.peers.is_empty());
+    }
+
     #[test]
     fn test_apply_action_quit() {
         let mut app = App::new();
@@ -667,4 +916,107 @@ mod tests {
         });
         assert!(app.transfers[0].status.contains("Complete"));
     }
+
+    #[test]
+    fn test_sas_both_confirm_unblocks_transfers() {
+        let mut app = App::new();
+        app.apply_action(TuiAction::SasVerificationRequired {
+            transcript: b"transcript".to_vec(),
+        });
+        assert_eq!(app.top_overlay(), Some(&Overlay::SasVerify));
+        assert!(app.transfers_blocked());
+
+        app.confirm_sas();
+        // Still waiting on the peer
+        assert_eq!(app.top_overlay(), Some(&Overlay::SasVerify));
+        assert!(app.transfers_blocked());
+
+        app.apply_action(TuiAction::PeerSasConfirmed);
+        assert!(app.overlays.is_empty());
+        assert!(!app.transfers_blocked());
+        assert!(matches!(
+            app.take_outgoing().as_slice(),
+            [TuiAction::SasConfirmed]
+        ));
+
+        app.apply_action(TuiAction::TransferStarted {
+            id: [5u8; 16],
+            filename: "ok.bin".into(),
+            total_bytes: 10,
+            direction: TransferDirection::Send,
+        });
+        assert_eq!(app.active_transfers.len(), 1);
+    }
+
+    #[test]
+    fn test_panic_wipe_zeroizes_tracked_keys() {
+        use std::sync::{Arc, Mutex};
+        use tallow_crypto::mem::SecureBuf;
+
+        let mut app = App::new();
+        let session_key = Arc::new(Mutex::new(SecureBuf::new([0xA5u8; 32])));
+        let keyring = Arc::new(Mutex::new(vec![0x5Au8; 64]));
+        app.wipe.track(session_key.clone());
+        app.wipe.track(keyring.clone());
+        app.begin_sas(b"transcript");
+        app.apply_action(TuiAction::PeerJoined {
+            room_code: "abc-def".into(),
+        });
+
+        app.panic_wipe();
+
+        assert!(session_key
+            .lock()
+            .unwrap()
+            .expose_secret()
+            .iter()
+            .all(|&b| b == 0));
+        assert!(keyring.lock().unwrap().is_empty());
+        assert!(!app.running);
+        assert!(app.wiped);
+        assert!(app.sas.is_none());
+        assert!(app.room_code.is_none());
+        assert!(app.overlays.is_empty());
+        assert!(app.wipe.is_empty());
+    }
+
+    #[test]
+    fn test_panic_wipe_is_idempotent() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mut app = App::new();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        app.wipe.register(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        app.panic_wipe();
+        app.panic_wipe();
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(!app.running);
+        assert!(app.wiped);
+    }
+
+    #[test]
+    fn test_sas_peer_decline_aborts() {
+        let mut app = App::new();
+        app.begin_sas(b"transcript");
+        app.confirm_sas();
+        app.apply_action(TuiAction::PeerSasDeclined);
+
+        assert!(app.overlays.is_empty());
+        assert!(app.transfers_blocked());
+        assert!(app.status_message.contains("mismatch"));
+
+        app.apply_action(TuiAction::TransferStarted {
+            id: [6u8; 16],
+            filename: "blocked.bin".into(),
+            total_bytes: 10,
+            direction: TransferDirection::Receive,
+        });
+        assert!(app.active_transfers.is_empty());
+    }
 }
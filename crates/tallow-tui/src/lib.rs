@@ -9,6 +9,7 @@ pub mod overlays;
 pub mod panels;
 pub mod render;
 pub mod security;
+pub mod session;
 pub mod theme;
 pub mod widgets;
 
@@ -17,6 +18,7 @@ use crossterm::event::{KeyCode, KeyModifiers};
 use event::{Event, EventHandler};
 use futures::StreamExt;
 use modes::TuiMode;
+use session::TuiLink;
 use std::io;
 use std::time::Duration;
 
@@ -114,6 +116,19 @@ impl Drop for TerminalGuard {
 pub async fn run_async(
     identity_fingerprint: Option<String>,
     initial_mode: TuiMode,
+) -> io::Result<()> {
+    run_with_session(identity_fingerprint, initial_mode, None).await
+}
+
+/// Run the TUI attached to a connection task
+///
+/// Events from the task (including the SAS of a finished handshake) are
+/// applied to the app, and the user's decisions are sent back. Declining
+/// the SAS closes the link, which aborts the task's session.
+pub async fn run_with_session(
+    identity_fingerprint: Option<String>,
+    initial_mode: TuiMode,
+    mut link: Option<TuiLink>,
 ) -> io::Result<()> {
     // Check terminal availability
     if !io::IsTerminal::is_terminal(&io::stdin()) {
@@ -157,14 +172,12 @@ pub async fn run_async(
     // Create async event stream
     let mut event_stream = crossterm::event::EventStream::new();
 
-    // Create action channel for background tasks
-    let (_action_tx, mut action_rx) = tokio::sync::mpsc::channel::<TuiAction>(256);
-
     // Create tick timer (100ms = ~10 fps for spinner animation)
     let mut tick_interval = tokio::time::interval(Duration::from_millis(100));
 
     // Main async loop
     loop {
+        let mut link_closed = false;
         terminal.draw(|frame| {
             render::render(frame, &app);
         })?;
@@ -188,10 +201,19 @@ pub async fn run_async(
             _ = tick_interval.tick() => {
                 app.tick();
             }
-            Some(action) = action_rx.recv() => {
-                app.apply_action(action);
+            action = next_action(&mut link) => {
+                match action {
+                    Some(action) => app.apply_action(action),
+                    // The connection task finished
+                    None => link_closed = true,
+                }
             }
         }
+        if link_closed {
+            link = None;
+        }
+
+        forward_decisions(&mut app, &mut link);
 
         if !app.running {
             break;
@@ -202,6 +224,35 @@ pub async fn run_async(
     Ok(())
 }
 
+/// Next event from the connection task; never resolves without one
+async fn next_action(link: &mut Option<TuiLink>) -> Option<TuiAction> {
+    match link {
+        Some(link) => link.actions.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Hand the user's decisions to the connection task
+///
+/// A declined SAS ends the session: the link is dropped after the decision
+/// is sent, so the task stops instead of transferring anything.
+fn forward_decisions(app: &mut App, link: &mut Option<TuiLink>) {
+    for action in app.take_outgoing() {
+        let declined = matches!(action, TuiAction::SasDeclined);
+        match link.as_ref() {
+            Some(tui) => {
+                if tui.decisions.try_send(action).is_err() {
+                    tracing::warn!("connection task is not accepting decisions");
+                }
+            }
+            None => tracing::debug!("user decision with no connection task: {:?}", action),
+        }
+        if declined {
+            *link = None;
+        }
+    }
+}
+
 /// Handle a crossterm event (dispatches to key handler)
 fn handle_event(app: &mut App, event: crossterm::event::Event) {
     match event {
@@ -220,10 +271,37 @@ fn handle_event(app: &mut App, event: crossterm::event::Event) {
     }
 }
 
+/// Ctrl+Shift+W, the emergency wipe hotkey
+///
+/// Terminals disagree on whether Shift upper-cases the reported character,
+/// so both cases are accepted.
+fn is_panic_key(key: &crossterm::event::KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char('w') | KeyCode::Char('W'))
+        && key
+            .modifiers
+            .contains(KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+}
+
 /// Handle a key event, routing through overlay stack first
 fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) {
+    // The panic button bypasses every overlay, including SAS verification
+    if is_panic_key(&key) {
+        app.panic_wipe();
+        return;
+    }
+
     // If overlays are active, route to topmost overlay
     if let Some(overlay) = app.top_overlay().cloned() {
+        if overlay == Overlay::SasVerify {
+            // Cannot be dismissed: the user must confirm or decline
+            match key.code {
+                KeyCode::Char('y') => app.confirm_sas(),
+                KeyCode::Char('n') => app.decline_sas(),
+                _ => {}
+            }
+            return;
+        }
+
         match key.code {
             KeyCode::Esc => {
                 app.pop_overlay();
@@ -390,4 +468,105 @@ mod tests {
         handle_key_event(&mut app, make_key(KeyCode::Tab));
         assert_eq!(app.focused_panel, app::FocusedPanel::Transfers);
     }
+
+    #[test]
+    fn test_sas_overlay_confirm_proceeds() {
+        let mut app = App::new();
+        app.begin_sas(b"session transcript");
+
+        // Esc and q cannot skip verification
+        handle_key_event(&mut app, make_key(KeyCode::Esc));
+        handle_key_event(&mut app, make_key(KeyCode::Char('q')));
+        assert!(app.running);
+        assert_eq!(app.top_overlay(), Some(&Overlay::SasVerify));
+
+        handle_key_event(&mut app, make_key(KeyCode::Char('y')));
+        assert!(app.sas.as_ref().unwrap().local_confirmed());
+        assert!(app.transfers_blocked());
+
+        app.apply_action(TuiAction::PeerSasConfirmed);
+        assert!(app.overlays.is_empty());
+        assert!(!app.transfers_blocked());
+    }
+
+    #[test]
+    fn test_panic_hotkey_wipes_through_overlays() {
+        let mut app = App::new();
+        app.begin_sas(b"session transcript");
+
+        // Plain Ctrl+W is not the panic button
+        handle_key_event(&mut app, make_key_ctrl(KeyCode::Char('w')));
+        assert!(app.running);
+
+        let panic = crossterm::event::KeyEvent::new(
+            KeyCode::Char('W'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        handle_key_event(&mut app, panic);
+        assert!(app.wiped);
+        assert!(!app.running);
+        assert!(app.sas.is_none());
+
+        // A second press after the wipe is harmless
+        handle_key_event(&mut app, panic);
+        assert!(app.wiped);
+    }
+
+    #[tokio::test]
+    async fn test_declined_sas_aborts_connection_task() {
+        let (mut tui, mut session) = session::link();
+        let task = tokio::spawn(async move { session.verify_sas(b"session key").await });
+
+        let mut app = App::new();
+        app.apply_action(tui.actions.recv().await.unwrap());
+        assert_eq!(app.top_overlay(), Some(&Overlay::SasVerify));
+
+        handle_key_event(&mut app, make_key(KeyCode::Char('n')));
+        let mut link = Some(tui);
+        forward_decisions(&mut app, &mut link);
+        assert!(link.is_none());
+
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_sas_releases_connection_task() {
+        let (mut tui, mut session) = session::link();
+        let task = tokio::spawn(async move {
+            session.verify_sas(b"session key").await?;
+            session.send(TuiAction::PeerSasConfirmed).await
+        });
+
+        let mut app = App::new();
+        app.apply_action(tui.actions.recv().await.unwrap());
+        handle_key_event(&mut app, make_key(KeyCode::Char('y')));
+        let mut link = Some(tui);
+        forward_decisions(&mut app, &mut link);
+
+        let peer = link.as_mut().unwrap().actions.recv().await.unwrap();
+        app.apply_action(peer);
+        task.await.unwrap().unwrap();
+        assert!(!app.transfers_blocked());
+        assert!(app.overlays.is_empty());
+    }
+
+    #[test]
+    fn test_sas_overlay_decline_aborts() {
+        let mut app = App::new();
+        app.begin_sas(b"session transcript");
+
+        handle_key_event(&mut app, make_key(KeyCode::Char('n')));
+        assert!(app.overlays.is_empty());
+        assert!(app.transfers_blocked());
+        assert!(app.status_message.contains("did not match"));
+        assert!(matches!(
+            app.take_outgoing().as_slice(),
+            [TuiAction::SasDeclined]
+        ));
+
+        // A late peer confirmation cannot revive the connection
+        app.apply_action(TuiAction::PeerSasConfirmed);
+        assert!(app.transfers_blocked());
+    }
 }
@@ -51,5 +51,9 @@ pub fn entries() -> Vec<HelpEntry> {
             key: "r",
             description: "Refresh",
         },
+        HelpEntry {
+            key: "Ctrl+Shift+W",
+            description: "Emergency wipe and exit",
+        },
     ]
 }
@@ -1,3 +1,4 @@
 //! TUI overlays
 
 pub mod help;
+pub mod sas;
@@ -0,0 +1,147 @@
+//! SAS (Short Authentication String) verification overlay
+//!
+//! Both peers derive a short numeric code from the handshake transcript and
+//! compare it out of band. A man in the middle runs two separate handshakes,
+//! so the codes on each side differ. The connection only proceeds once both
+//! users confirm a match.
+//!
+//! Note: rendering is in render.rs (render_sas_overlay); this module holds
+//! the code derivation and confirmation state.
+
+use tallow_crypto::hash::{derive_key, DOMAIN_SAS};
+
+/// Number of decimal digits in the displayed code
+pub const SAS_DIGITS: usize = 6;
+
+/// Derive the displayed SAS from the session transcript
+///
+/// Returns `SAS_DIGITS` digits split into two groups (e.g. `"042 917"`).
+pub fn compute_sas(transcript: &[u8]) -> String {
+    let digest = derive_key(DOMAIN_SAS, transcript);
+    let mut value_bytes = [0u8; 8];
+    value_bytes.copy_from_slice(&digest[..8]);
+    let value = u64::from_le_bytes(value_bytes) % 10u64.pow(SAS_DIGITS as u32);
+
+    let digits = format!("{:0width$}", value, width = SAS_DIGITS);
+    let (first, second) = digits.split_at(SAS_DIGITS / 2);
+    format!("{} {}", first, second)
+}
+
+/// Outcome of a SAS comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasState {
+    /// Waiting for one or both peers to confirm
+    Pending,
+    /// Both peers confirmed the codes match
+    Verified,
+    /// A peer declined; the connection must be dropped
+    Aborted,
+}
+
+/// Confirmation state for one SAS comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SasVerification {
+    sas: String,
+    local_confirmed: bool,
+    peer_confirmed: bool,
+    state: SasState,
+}
+
+impl SasVerification {
+    /// Start a comparison for the given session transcript
+    pub fn from_transcript(transcript: &[u8]) -> Self {
+        Self {
+            sas: compute_sas(transcript),
+            local_confirmed: false,
+            peer_confirmed: false,
+            state: SasState::Pending,
+        }
+    }
+
+    /// The code to display
+    pub fn sas(&self) -> &str {
+        &self.sas
+    }
+
+    /// Current outcome
+    pub fn state(&self) -> SasState {
+        self.state
+    }
+
+    /// Whether the local user has confirmed
+    pub fn local_confirmed(&self) -> bool {
+        self.local_confirmed
+    }
+
+    /// Whether the peer has confirmed
+    pub fn peer_confirmed(&self) -> bool {
+        self.peer_confirmed
+    }
+
+    /// Record the local user's confirmation
+    pub fn confirm_local(&mut self) -> SasState {
+        if self.state == SasState::Pending {
+            self.local_confirmed = true;
+            self.settle();
+        }
+        self.state
+    }
+
+    /// Record the peer's confirmation
+    pub fn confirm_peer(&mut self) -> SasState {
+        if self.state == SasState::Pending {
+            self.peer_confirmed = true;
+            self.settle();
+        }
+        self.state
+    }
+
+    /// Record a decline from either side; final once reached
+    pub fn decline(&mut self) -> SasState {
+        if self.state == SasState::Pending {
+            self.state = SasState::Aborted;
+        }
+        self.state
+    }
+
+    fn settle(&mut self) {
+        if self.local_confirmed && self.peer_confirmed {
+            self.state = SasState::Verified;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sas_deterministic_from_transcript() {
+        let transcript = b"handshake transcript bytes";
+        assert_eq!(compute_sas(transcript), compute_sas(transcript));
+        assert_ne!(compute_sas(transcript), compute_sas(b"another transcript"));
+
+        let sas = compute_sas(transcript);
+        assert_eq!(sas.len(), SAS_DIGITS + 1);
+        assert!(sas.replace(' ', "").chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_both_confirm_verifies() {
+        let mut v = SasVerification::from_transcript(b"t");
+        assert_eq!(v.confirm_local(), SasState::Pending);
+        assert_eq!(v.confirm_peer(), SasState::Verified);
+
+        let mut v = SasVerification::from_transcript(b"t");
+        assert_eq!(v.confirm_peer(), SasState::Pending);
+        assert_eq!(v.confirm_local(), SasState::Verified);
+    }
+
+    #[test]
+    fn test_decline_is_final() {
+        let mut v = SasVerification::from_transcript(b"t");
+        v.confirm_local();
+        assert_eq!(v.decline(), SasState::Aborted);
+        assert_eq!(v.confirm_peer(), SasState::Aborted);
+    }
+}
@@ -6,6 +6,7 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
+use std::time::Instant;
 
 /// Render the devices panel
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
@@ -46,21 +47,33 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
+    let now = Instant::now();
     let mut lines = Vec::new();
     for peer in &app.peers {
-        let trust_icon = if peer.verified {
+        // Peers that stopped announcing are dimmed until they are evicted
+        let stale = peer.is_stale(now);
+        let trust_icon = if stale {
+            Span::styled(" [--] ", Style::default().fg(Color::DarkGray))
+        } else if peer.verified {
             Span::styled(" [ok] ", Style::default().fg(Color::Green))
         } else {
             Span::styled(" [??] ", Style::default().fg(Color::Yellow))
         };
 
-        lines.push(Line::from(vec![
+        let mut name_line = vec![
             trust_icon,
             Span::styled(
                 peer.name.as_str(),
                 Style::default().add_modifier(Modifier::BOLD),
             ),
-        ]));
+        ];
+        if stale {
+            name_line.push(Span::styled(
+                " (stale)",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::from(name_line));
         lines.push(Line::from(vec![
             Span::raw("       "),
             Span::styled(peer.address.as_str(), Style::default().fg(Color::DarkGray)),
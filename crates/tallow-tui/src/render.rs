@@ -35,6 +35,7 @@ pub fn render(frame: &mut Frame, app: &App) {
             Overlay::TransferConfirm { filename, size } => {
                 render_confirm_overlay(frame, overlay_area, filename, *size);
             }
+            Overlay::SasVerify => render_sas_overlay(frame, overlay_area, app),
         }
     }
 }
@@ -251,6 +252,68 @@ fn render_confirm_overlay(frame: &mut Frame, area: Rect, filename: &str, size: u
     frame.render_widget(paragraph, area);
 }
 
+/// Render SAS verification overlay
+fn render_sas_overlay(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(ref verification) = app.sas else {
+        return;
+    };
+
+    let status = |confirmed: bool| {
+        if confirmed {
+            Span::styled("confirmed", Style::default().fg(Color::Green))
+        } else {
+            Span::styled("waiting...", Style::default().fg(Color::DarkGray))
+        }
+    };
+
+    let lines = vec![
+        Line::from(Span::styled(
+            " Verify Connection ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("  Check that your peer sees the same code:"),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("      {}", verification.sas()),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  You:  ", Style::default().fg(Color::Yellow)),
+            status(verification.local_confirmed()),
+        ]),
+        Line::from(vec![
+            Span::styled("  Peer: ", Style::default().fg(Color::Yellow)),
+            status(verification.peer_confirmed()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "  Codes match? [y/n]",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "  Different codes mean a possible MITM.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" SAS ");
+
+    let paragraph = Paragraph::new(lines).block(block);
+    frame.render_widget(paragraph, area);
+}
+
 /// Render a warning when the terminal is too small
 fn render_size_warning(frame: &mut Frame) {
     let area = frame.area();
@@ -494,6 +557,25 @@ mod tests {
         assert!(buf_str.contains("[y/n]"));
     }
 
+    #[test]
+    fn test_sas_overlay_renders_code() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new();
+        app.begin_sas(b"render transcript");
+        let sas = app.sas.as_ref().unwrap().sas().to_string();
+
+        terminal
+            .draw(|frame| {
+                render(frame, &app);
+            })
+            .unwrap();
+
+        let buf_str = buffer_to_string(terminal.backend().buffer());
+        assert!(buf_str.contains(&sas));
+        assert!(buf_str.contains("[y/n]"));
+    }
+
     #[test]
     fn test_large_terminal_no_panic() {
         let backend = TestBackend::new(300, 80);
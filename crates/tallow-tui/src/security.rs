@@ -9,6 +9,77 @@
 //! 3. **Panic**: Panic hook fires and calls `restore_terminal()` + `wipe_screen()`,
 //!    then `TerminalGuard::drop()` fires again. Double-restore is safe because
 //!    `disable_raw_mode()` and `LeaveAlternateScreen` are idempotent.
+//!
+//! ## Panic button
+//!
+//! Anything holding key material or a live connection registers a hook in a
+//! [`WipeRegistry`]. The emergency wipe runs every hook synchronously on the
+//! UI thread, so secrets are zeroized before the main loop even sees that the
+//! app stopped running.
+
+use std::sync::{Arc, Mutex};
+use zeroize::Zeroize;
+
+/// Callback that destroys one piece of in-memory session state
+pub type WipeHook = Box<dyn FnMut() + Send>;
+
+/// Hooks run by the emergency wipe
+#[derive(Default)]
+pub struct WipeRegistry {
+    hooks: Vec<WipeHook>,
+}
+
+impl WipeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a hook, e.g. one that aborts a connection task
+    pub fn register(&mut self, hook: impl FnMut() + Send + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Zeroize a shared key buffer when the wipe runs
+    ///
+    /// A poisoned lock is still wiped; the secret matters more than the
+    /// panic that poisoned it.
+    pub fn track<T: Zeroize + Send + 'static>(&mut self, secret: Arc<Mutex<T>>) {
+        self.register(move || {
+            secret
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .zeroize();
+        });
+    }
+
+    /// Number of hooks waiting to run
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Whether no hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Run and discard every hook, returning how many ran
+    pub fn wipe_all(&mut self) -> usize {
+        let mut hooks = std::mem::take(&mut self.hooks);
+        for hook in hooks.iter_mut() {
+            hook();
+        }
+        hooks.len()
+    }
+}
+
+impl std::fmt::Debug for WipeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WipeRegistry")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
 
 /// Wipe the screen buffer (security feature)
 ///
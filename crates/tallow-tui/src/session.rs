@@ -0,0 +1,89 @@
+//! Link between the TUI and a connection task
+//!
+//! A connection task runs the handshake and the transfer in the background.
+//! It reports progress to the TUI as [`TuiAction`]s and gets the user's
+//! decisions back over the same link. Once its handshake finishes, the task
+//! calls [`SessionLink::verify_sas`], which opens the SAS overlay and does
+//! not return until the user has compared the codes.
+
+use crate::app::TuiAction;
+use std::io;
+use tokio::sync::mpsc;
+
+/// Capacity of each direction of the link
+const LINK_CAPACITY: usize = 256;
+
+/// TUI side of the link, passed to [`crate::run_with_session`]
+pub struct TuiLink {
+    pub(crate) actions: mpsc::Receiver<TuiAction>,
+    pub(crate) decisions: mpsc::Sender<TuiAction>,
+}
+
+/// Connection task side of the link
+pub struct SessionLink {
+    actions: mpsc::Sender<TuiAction>,
+    decisions: mpsc::Receiver<TuiAction>,
+}
+
+/// Create a connected pair of link ends
+pub fn link() -> (TuiLink, SessionLink) {
+    let (action_tx, action_rx) = mpsc::channel(LINK_CAPACITY);
+    let (decision_tx, decision_rx) = mpsc::channel(LINK_CAPACITY);
+    (
+        TuiLink {
+            actions: action_rx,
+            decisions: decision_tx,
+        },
+        SessionLink {
+            actions: action_tx,
+            decisions: decision_rx,
+        },
+    )
+}
+
+impl SessionLink {
+    /// Report an event to the TUI
+    ///
+    /// Fails once the TUI has closed the session, which the task must
+    /// treat as an abort.
+    pub async fn send(&self, action: TuiAction) -> io::Result<()> {
+        self.actions
+            .send(action)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "TUI session closed"))
+    }
+
+    /// Show the SAS for a finished handshake and wait for the local user
+    ///
+    /// `transcript` is what both peers derive the code from (the session
+    /// key). Returns `Ok` once the user confirms a match. A declined code,
+    /// or the TUI closing first, returns `PermissionDenied` and the caller
+    /// must drop the connection without transferring anything. The peer's
+    /// answer still has to be forwarded as
+    /// [`TuiAction::PeerSasConfirmed`] or [`TuiAction::PeerSasDeclined`].
+    pub async fn verify_sas(&mut self, transcript: &[u8]) -> io::Result<()> {
+        self.send(TuiAction::SasVerificationRequired {
+            transcript: transcript.to_vec(),
+        })
+        .await?;
+        loop {
+            match self.decisions.recv().await {
+                Some(TuiAction::SasConfirmed) => return Ok(()),
+                Some(TuiAction::SasDeclined) | None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "verification codes did not match -- connection aborted",
+                    ))
+                }
+                Some(other) => tracing::debug!("ignoring {:?} while awaiting SAS", other),
+            }
+        }
+    }
+
+    /// Next user decision that is not part of SAS verification
+    ///
+    /// `None` once the TUI has closed the session.
+    pub async fn next_decision(&mut self) -> Option<TuiAction> {
+        self.decisions.recv().await
+    }
+}
@@ -1,4 +1,11 @@
 //! TUI theming
+//!
+//! [`audit`] checks a palette's text colors against WCAG contrast
+//! guidelines, so a custom theme that would be hard to read is caught when
+//! it is loaded.
+
+use crate::widgets::theme_definitions::ThemePalette;
+use ratatui::style::Color;
 
 /// Theme mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -37,3 +44,137 @@ impl Default for Theme {
         Self::default_theme()
     }
 }
+
+/// Minimum WCAG AA contrast ratio for normal-size text
+pub const WCAG_AA_NORMAL: f64 = 4.5;
+
+/// A text/background pair in a palette that falls below WCAG AA
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastWarning {
+    /// Palette field used as the text color
+    pub fg: &'static str,
+    /// Palette field used as the background
+    pub bg: &'static str,
+    /// Contrast ratio between the two, from 1.0 to 21.0
+    pub ratio: f64,
+}
+
+impl std::fmt::Display for ContrastWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} on {} has contrast {:.2}:1, below the {}:1 WCAG AA minimum",
+            self.fg, self.bg, self.ratio, WCAG_AA_NORMAL
+        )
+    }
+}
+
+/// Check every text color in `palette` against the background it is drawn on
+///
+/// `muted` and `border` are left out: WCAG exempts inactive elements and
+/// decoration from the text contrast requirement. Pairs involving colors
+/// other than [`Color::Rgb`] are skipped, since their actual values are
+/// chosen by the terminal.
+pub fn audit(palette: &ThemePalette) -> Vec<ContrastWarning> {
+    let pairs = [
+        ("fg", palette.fg, "bg", palette.bg),
+        ("primary", palette.primary, "bg", palette.bg),
+        ("secondary", palette.secondary, "bg", palette.bg),
+        ("success", palette.success, "bg", palette.bg),
+        ("warning", palette.warning, "bg", palette.bg),
+        ("error", palette.error, "bg", palette.bg),
+        ("accent", palette.accent, "bg", palette.bg),
+        (
+            "selection_fg",
+            palette.selection_fg,
+            "selection_bg",
+            palette.selection_bg,
+        ),
+    ];
+    pairs
+        .into_iter()
+        .filter_map(|(fg, fg_color, bg, bg_color)| {
+            let ratio = contrast_ratio(fg_color, bg_color)?;
+            (ratio < WCAG_AA_NORMAL).then_some(ContrastWarning { fg, bg, ratio })
+        })
+        .collect()
+}
+
+/// WCAG contrast ratio between two RGB colors, or `None` for other colors
+pub fn contrast_ratio(a: Color, b: Color) -> Option<f64> {
+    let (a, b) = (relative_luminance(a)?, relative_luminance(b)?);
+    let (lighter, darker) = if a >= b { (a, b) } else { (b, a) };
+    Some((lighter + 0.05) / (darker + 0.05))
+}
+
+/// WCAG relative luminance of an sRGB color
+fn relative_luminance(color: Color) -> Option<f64> {
+    let Color::Rgb(r, g, b) = color else {
+        return None;
+    };
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::theme_definitions::{custom_palette, dark_palette, high_contrast_palette};
+
+    #[test]
+    fn test_contrast_ratio_matches_wcag_reference() {
+        let ratio = |a, b| contrast_ratio(a, b).unwrap();
+        let white = Color::Rgb(255, 255, 255);
+        assert!((ratio(white, Color::Rgb(0, 0, 0)) - 21.0).abs() < 1e-9);
+        assert!((ratio(white, white) - 1.0).abs() < 1e-9);
+        // #767676 is the lightest gray that passes AA on white; #777777 fails
+        assert!((ratio(Color::Rgb(0x76, 0x76, 0x76), white) - 4.54).abs() < 0.01);
+        assert!((ratio(white, Color::Rgb(0x77, 0x77, 0x77)) - 4.48).abs() < 0.01);
+        assert!((ratio(Color::Rgb(0, 0, 255), white) - 8.59).abs() < 0.01);
+        assert!((ratio(Color::Rgb(255, 0, 0), white) - 4.0).abs() < 0.01);
+        assert_eq!(contrast_ratio(Color::Red, white), None);
+    }
+
+    #[test]
+    fn test_audit_flags_low_contrast_pairs() {
+        // Mid-gray text and an orange accent on a slightly darker gray
+        let palette = custom_palette(
+            (90, 90, 90),
+            (140, 140, 140),
+            (255, 255, 255),
+            (255, 255, 255),
+            (255, 255, 255),
+            (255, 255, 255),
+            (255, 255, 255),
+            (255, 170, 0),
+            (100, 100, 100),
+            (100, 100, 100),
+            (0, 0, 0),
+            (255, 255, 255),
+        );
+        let warnings = audit(&palette);
+        let flagged: Vec<_> = warnings.iter().map(|w| (w.fg, w.bg)).collect();
+        assert_eq!(flagged, vec![("fg", "bg"), ("accent", "bg")]);
+        assert!(warnings.iter().all(|w| w.ratio < WCAG_AA_NORMAL));
+        assert!(warnings[0].to_string().contains("fg on bg"));
+    }
+
+    #[test]
+    fn test_audit_passes_high_contrast_palettes() {
+        assert!(audit(&high_contrast_palette()).is_empty());
+        assert!(audit(&dark_palette()).is_empty());
+
+        // Terminal-defined colors can't be measured, so are never flagged
+        let mut palette = dark_palette();
+        palette.fg = Color::DarkGray;
+        palette.bg = Color::Black;
+        assert!(audit(&palette).is_empty());
+    }
+}
@@ -5,7 +5,9 @@
 //! - System messages (centered)
 //! - Timestamps and delivery status indicators
 //! - Automatic scroll management
+//! - In-chat search with highlighted matches and `n`/`N` jumps
 
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     buffer::Buffer,
     layout::{HorizontalAlignment, Rect},
@@ -13,8 +15,18 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, StatefulWidget, Widget, Wrap},
 };
+use std::ops::Range;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Style for search matches
+const MATCH_STYLE: Style = Style::new().fg(Color::Black).bg(Color::Yellow);
+
+/// Style for matches inside the message the search is focused on
+const CURRENT_MATCH_STYLE: Style = Style::new()
+    .fg(Color::Black)
+    .bg(Color::LightRed)
+    .add_modifier(Modifier::BOLD);
+
 /// Message delivery status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessageStatus {
@@ -110,21 +122,31 @@ impl ChatMessage {
     }
 }
 
-/// Chat view state for scrolling
+/// Chat view state for scrolling and search
 #[derive(Debug, Default)]
 pub struct ChatViewState {
     /// Current scroll offset (0 = bottom, higher = scrolled up)
     pub scroll_offset: u16,
     /// Whether auto-scroll to bottom is enabled
     pub auto_scroll: bool,
+    /// Search query; empty when no search is active
+    query: String,
+    /// Whether keystrokes are being typed into the query
+    search_input: bool,
+    /// Indices of messages containing the query, oldest first
+    matches: Vec<usize>,
+    /// Position in `matches` of the focused match
+    current_match: Option<usize>,
+    /// Scroll the focused match into view on the next render
+    follow_match: bool,
 }
 
 impl ChatViewState {
     /// Create a new chat view state with auto-scroll enabled
     pub fn new() -> Self {
         Self {
-            scroll_offset: 0,
             auto_scroll: true,
+            ..Default::default()
         }
     }
 
@@ -147,6 +169,172 @@ impl ChatViewState {
         self.scroll_offset = 0;
         self.auto_scroll = true;
     }
+
+    /// Enter search mode with an empty query
+    pub fn start_search(&mut self) {
+        self.clear_search();
+        self.search_input = true;
+    }
+
+    /// Leave search mode and drop all highlights
+    pub fn clear_search(&mut self) {
+        self.query.clear();
+        self.search_input = false;
+        self.matches.clear();
+        self.current_match = None;
+        self.follow_match = false;
+    }
+
+    /// Search `messages` for `query` (case-insensitive)
+    ///
+    /// Focus lands on the newest match, since the view is anchored at the
+    /// bottom of the conversation.
+    pub fn set_query(&mut self, query: &str, messages: &[ChatMessage]) {
+        self.query = query.to_string();
+        self.matches = messages
+            .iter()
+            .enumerate()
+            .filter(|(_, msg)| !find_matches(&msg.content, query).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        self.current_match = self.matches.len().checked_sub(1);
+        self.follow_match = self.current_match.is_some();
+    }
+
+    /// Current search query
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Whether the query is being typed
+    pub fn is_searching(&self) -> bool {
+        self.search_input
+    }
+
+    /// Indices of the messages that match the query
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// Index of the message the search is focused on
+    pub fn current_match(&self) -> Option<usize> {
+        self.current_match.map(|i| self.matches[i])
+    }
+
+    /// Jump to the next older match, wrapping to the newest (`n`)
+    pub fn next_match(&mut self) {
+        if let Some(current) = self.current_match {
+            self.current_match = Some(current.checked_sub(1).unwrap_or(self.matches.len() - 1));
+            self.follow_match = true;
+        }
+    }
+
+    /// Jump to the next newer match, wrapping to the oldest (`N`)
+    pub fn prev_match(&mut self) {
+        if let Some(current) = self.current_match {
+            self.current_match = Some((current + 1) % self.matches.len());
+            self.follow_match = true;
+        }
+    }
+
+    /// Handle a search key: `/` to start, typing, `Enter`, `Esc`, `n`/`N`
+    ///
+    /// Returns true if the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent, messages: &[ChatMessage]) -> bool {
+        if self.search_input {
+            match key.code {
+                KeyCode::Char(c) => {
+                    let mut query = std::mem::take(&mut self.query);
+                    query.push(c);
+                    self.set_query(&query, messages);
+                }
+                KeyCode::Backspace => {
+                    let mut query = std::mem::take(&mut self.query);
+                    query.pop();
+                    self.set_query(&query, messages);
+                }
+                KeyCode::Enter => self.search_input = false,
+                KeyCode::Esc => self.clear_search(),
+                _ => return false,
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char('n') if !self.query.is_empty() => self.next_match(),
+            KeyCode::Char('N') if !self.query.is_empty() => self.prev_match(),
+            KeyCode::Esc if !self.query.is_empty() => self.clear_search(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Byte ranges of case-insensitive, non-overlapping matches of `query`
+///
+/// Comparison is on lowercased characters, so matches respect character
+/// boundaries even where lowercasing changes the length of the text.
+pub fn find_matches(text: &str, query: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercased text; each entry keeps the byte offset of its source
+    // character and whether it is the first char that character folds to
+    let mut folded: Vec<(char, usize, bool)> = Vec::with_capacity(text.len());
+    for (offset, c) in text.char_indices() {
+        for (i, lower) in c.to_lowercase().enumerate() {
+            folded.push((lower, offset, i == 0));
+        }
+    }
+    let offset_at = |i: usize| folded.get(i).map_or(text.len(), |f| f.1);
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= folded.len() {
+        let end = i + needle.len();
+        let aligned = folded[i].2 && folded.get(end).is_none_or(|f| f.2);
+        if aligned
+            && folded[i..end]
+                .iter()
+                .map(|f| f.0)
+                .eq(needle.iter().copied())
+        {
+            ranges.push(offset_at(i)..offset_at(end));
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// Split message content into lines with search matches highlighted
+fn highlighted_lines<'t>(
+    content: &'t str,
+    query: &str,
+    base: Style,
+    highlight: Style,
+) -> Vec<Line<'t>> {
+    content
+        .split('\n')
+        .map(|line| {
+            let mut spans = Vec::new();
+            let mut pos = 0;
+            for range in find_matches(line, query) {
+                if range.start > pos {
+                    spans.push(Span::styled(&line[pos..range.start], base));
+                }
+                spans.push(Span::styled(&line[range.clone()], highlight));
+                pos = range.end;
+            }
+            if pos < line.len() || spans.is_empty() {
+                spans.push(Span::styled(&line[pos..], base));
+            }
+            Line::from(spans)
+        })
+        .collect()
 }
 
 /// Scrollable chat message display widget
@@ -195,20 +383,18 @@ impl<'a> ChatView<'a> {
         let bubble_width = (width * self.max_bubble_width / 100).max(20);
         let content_width = bubble_width.saturating_sub(4); // Account for borders and padding
 
-        // Line for sender + timestamp
-        let mut lines = if msg.is_system { 0 } else { 1 };
-
-        // Content lines (wrapped)
-        let content_lines = (msg.content.chars().count() as u16).div_ceil(content_width);
-        lines += content_lines.max(1);
+        // Content lines (explicit line breaks, each wrapped)
+        let content_lines: u16 = msg
+            .content
+            .split('\n')
+            .map(|line| (line.chars().count() as u16).div_ceil(content_width).max(1))
+            .sum();
 
-        // Status line for own messages
-        if msg.is_mine && !msg.is_system {
-            lines += 1;
-        }
+        // Bubble borders and the sender/timestamp/status header
+        let chrome = if msg.is_system { 0 } else { 3 };
 
         // Add spacing
-        lines + 1
+        content_lines + chrome + 1
     }
 }
 
@@ -238,6 +424,9 @@ impl<'a> StatefulWidget for ChatView<'a> {
 
         // Determine which messages to render based on scroll
         let visible_height = area.height;
+        if state.follow_match {
+            self.follow_match(area.width, visible_height, state);
+        }
         let scroll = if state.auto_scroll {
             total_lines.saturating_sub(visible_height)
         } else {
@@ -250,7 +439,7 @@ impl<'a> StatefulWidget for ChatView<'a> {
         let start_line = scroll;
         let end_line = scroll + visible_height;
 
-        for msg in self.messages {
+        for (index, msg) in self.messages.iter().enumerate() {
             let msg_height = self.message_height(msg, area.width);
             let msg_start = current_line;
             let msg_end = current_line + msg_height;
@@ -260,7 +449,15 @@ impl<'a> StatefulWidget for ChatView<'a> {
                 let y_offset = msg_start.saturating_sub(start_line);
 
                 if y_offset < visible_height {
-                    self.render_message(msg, area, buf, y_offset);
+                    let height = (msg_height - 1).min(visible_height - y_offset);
+                    let highlight = if state.current_match() == Some(index) {
+                        CURRENT_MATCH_STYLE
+                    } else {
+                        MATCH_STYLE
+                    };
+                    let content =
+                        |base| highlighted_lines(&msg.content, &state.query, base, highlight);
+                    self.render_message(msg, content, area, buf, y_offset, height);
                 }
             }
 
@@ -274,26 +471,68 @@ impl<'a> StatefulWidget for ChatView<'a> {
 }
 
 impl<'a> ChatView<'a> {
+    /// Scroll so the focused search match is fully in view
+    fn follow_match(&self, width: u16, visible_height: u16, state: &mut ChatViewState) {
+        state.follow_match = false;
+        let Some(target) = state.current_match() else {
+            return;
+        };
+        let msg_start: u16 = self.messages[..target]
+            .iter()
+            .map(|msg| self.message_height(msg, width))
+            .sum();
+        let msg_end = msg_start + self.message_height(&self.messages[target], width);
+
+        let top = if state.auto_scroll {
+            let total: u16 = msg_end
+                + self.messages[target + 1..]
+                    .iter()
+                    .map(|msg| self.message_height(msg, width))
+                    .sum::<u16>();
+            total.saturating_sub(visible_height)
+        } else {
+            state.scroll_offset
+        };
+        let top = if msg_start < top {
+            msg_start
+        } else if msg_end > top + visible_height {
+            msg_end.saturating_sub(visible_height).min(msg_start)
+        } else {
+            top
+        };
+        state.scroll_offset = top;
+        state.auto_scroll = false;
+    }
+
     /// Render a single message
-    fn render_message(&self, msg: &ChatMessage, area: Rect, buf: &mut Buffer, y_offset: u16) {
+    fn render_message(
+        &self,
+        msg: &ChatMessage,
+        content: impl Fn(Style) -> Vec<Line<'a>>,
+        area: Rect,
+        buf: &mut Buffer,
+        y_offset: u16,
+        height: u16,
+    ) {
         let bubble_width = (area.width * self.max_bubble_width / 100).max(20);
 
         if msg.is_system {
             // System messages: centered, gray
-            let content = Span::styled(
-                &msg.content,
+            let content = content(
                 Style::default()
                     .fg(Color::DarkGray)
                     .add_modifier(Modifier::ITALIC),
             );
 
-            let para = Paragraph::new(Line::from(content)).alignment(HorizontalAlignment::Center);
+            let para = Paragraph::new(content)
+                .alignment(HorizontalAlignment::Center)
+                .wrap(Wrap { trim: false });
 
             let msg_area = Rect {
                 x: area.x,
                 y: area.y + y_offset,
                 width: area.width,
-                height: 1,
+                height,
             };
 
             para.render(msg_area, buf);
@@ -304,7 +543,7 @@ impl<'a> ChatView<'a> {
                 x: area.x + x_offset,
                 y: area.y + y_offset,
                 width: bubble_width,
-                height: 3,
+                height,
             };
 
             let time = msg.format_time();
@@ -315,12 +554,10 @@ impl<'a> ChatView<'a> {
                 Span::styled(status, Style::default().fg(msg.status.color())),
             ]);
 
-            let content = Line::from(Span::styled(
-                &msg.content,
-                Style::default().fg(Color::White),
-            ));
+            let mut lines = vec![header];
+            lines.extend(content(Style::default().fg(Color::White)));
 
-            let para = Paragraph::new(vec![header, content])
+            let para = Paragraph::new(lines)
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
@@ -335,7 +572,7 @@ impl<'a> ChatView<'a> {
                 x: area.x,
                 y: area.y + y_offset,
                 width: bubble_width,
-                height: 3,
+                height,
             };
 
             let header = Line::from(vec![
@@ -346,12 +583,10 @@ impl<'a> ChatView<'a> {
                 ),
             ]);
 
-            let content = Line::from(Span::styled(
-                &msg.content,
-                Style::default().fg(Color::White),
-            ));
+            let mut lines = vec![header];
+            lines.extend(content(Style::default().fg(Color::White)));
 
-            let para = Paragraph::new(vec![header, content])
+            let para = Paragraph::new(lines)
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
@@ -398,4 +633,162 @@ mod tests {
         assert_eq!(state.scroll_offset, 0);
         assert!(state.auto_scroll);
     }
+
+    fn conversation() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::new("Alice", "Did the report arrive?", false),
+            ChatMessage::new("Me", "Not yet", true),
+            ChatMessage::system("Transfer started: REPORT.pdf"),
+            ChatMessage::new("Alice", "ok\nthe report is 4 MB", false),
+            ChatMessage::new("Me", "Got it", true),
+        ]
+    }
+
+    #[test]
+    fn test_search_match_indices() {
+        let messages = conversation();
+        let mut state = ChatViewState::new();
+        state.set_query("report", &messages);
+        assert_eq!(state.matches(), &[0, 2, 3]);
+        assert_eq!(state.current_match(), Some(3));
+
+        state.set_query("nothing like this", &messages);
+        assert!(state.matches().is_empty());
+        assert_eq!(state.current_match(), None);
+        state.next_match();
+        assert_eq!(state.current_match(), None);
+    }
+
+    #[test]
+    fn test_find_matches_unicode() {
+        assert_eq!(
+            find_matches("Crème brûlée, CRÈME", "crème"),
+            vec![0..6, 17..23]
+        );
+        assert_eq!(
+            find_matches("データ転送とデータ", "データ"),
+            vec![0..9, 18..27]
+        );
+        // 'İ' lowercases to two chars; a match must not split it
+        assert_eq!(find_matches("İi", "i"), vec![2..3]);
+        assert_eq!(find_matches("İi", "i\u{307}"), vec![0..2]);
+        assert!(find_matches("abc", "").is_empty());
+    }
+
+    #[test]
+    fn test_jump_wraps_around() {
+        let messages = conversation();
+        let mut state = ChatViewState::new();
+        state.set_query("report", &messages);
+
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            state.next_match();
+            visited.push(state.current_match().unwrap());
+        }
+        assert_eq!(visited, vec![2, 0, 3, 2]);
+
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            state.prev_match();
+            visited.push(state.current_match().unwrap());
+        }
+        assert_eq!(visited, vec![3, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_search_keys() {
+        let messages = conversation();
+        let mut state = ChatViewState::new();
+        let key = |code| KeyEvent::from(code);
+
+        assert!(!state.handle_key(key(KeyCode::Char('n')), &messages));
+        assert!(state.handle_key(key(KeyCode::Char('/')), &messages));
+        for c in "got".chars() {
+            state.handle_key(key(KeyCode::Char(c)), &messages);
+        }
+        assert!(state.is_searching());
+        assert_eq!(state.matches(), &[4]);
+        state.handle_key(key(KeyCode::Backspace), &messages);
+        assert_eq!(state.query(), "go");
+        state.handle_key(key(KeyCode::Enter), &messages);
+        assert!(!state.is_searching());
+
+        assert!(state.handle_key(key(KeyCode::Char('N')), &messages));
+        assert!(state.handle_key(key(KeyCode::Esc), &messages));
+        assert!(state.query().is_empty());
+        assert_eq!(state.current_match(), None);
+    }
+
+    #[test]
+    fn test_highlight_spans_cover_text() {
+        let lines = highlighted_lines(
+            "Crème brûlée\nno match\nCRÈME",
+            "crème",
+            Style::default(),
+            MATCH_STYLE,
+        );
+        assert_eq!(lines.len(), 3);
+        let text: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        assert_eq!(text, vec!["Crème brûlée", "no match", "CRÈME"]);
+
+        let hits: Vec<&str> = lines
+            .iter()
+            .flat_map(|l| &l.spans)
+            .filter(|s| s.style == MATCH_STYLE)
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(hits, vec!["Crème", "CRÈME"]);
+    }
+
+    /// Symbols of each row's cells drawn in `style`, as strings
+    fn styled_text(buf: &Buffer, style: Style) -> Vec<String> {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| &buf[(x, y)])
+                    .filter(|cell| cell.bg == style.bg.unwrap())
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight_aligns_with_rendered_text() {
+        let messages = vec![
+            ChatMessage::new("Bob", "A crème brûlée", false),
+            ChatMessage::new("Bob", "two\nCRÈME brûlée", false),
+        ];
+        let mut state = ChatViewState::new();
+        state.set_query("crème", &messages);
+
+        let area = Rect::new(0, 0, 40, 20);
+        let mut buf = Buffer::empty(area);
+        ChatView::new(&messages).render(area, &mut buf, &mut state);
+
+        assert_eq!(styled_text(&buf, MATCH_STYLE), vec!["crème"]);
+        assert_eq!(styled_text(&buf, CURRENT_MATCH_STYLE), vec!["CRÈME"]);
+    }
+
+    #[test]
+    fn test_scroll_follows_match() {
+        let mut messages: Vec<ChatMessage> = (0..30)
+            .map(|i| ChatMessage::new("Bob", format!("message {}", i), false))
+            .collect();
+        messages[2].content = "the needle".to_string();
+        let mut state = ChatViewState::new();
+        state.set_query("needle", &messages);
+
+        let area = Rect::new(0, 0, 40, 12);
+        let mut buf = Buffer::empty(area);
+        ChatView::new(&messages).render(area, &mut buf, &mut state);
+
+        // Each message is 5 lines tall; message 2 starts at line 10
+        assert!(!state.auto_scroll);
+        assert_eq!(state.scroll_offset, 10);
+        assert_eq!(styled_text(&buf, CURRENT_MATCH_STYLE), vec!["needle"]);
+    }
 }
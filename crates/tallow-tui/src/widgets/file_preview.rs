@@ -1,7 +1,8 @@
 //! File preview widget for displaying file contents and metadata.
 //!
 //! Supports previewing text files, images (metadata), archives, and binary files.
-//! Provides intelligent preview based on file type detection.
+//! The type is detected from the file's first bytes where possible, falling
+//! back to the extension. Only a bounded prefix of the file is ever read.
 
 use ratatui::{
     buffer::Buffer,
@@ -11,6 +12,17 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
 use std::path::{Path, PathBuf};
+use tallow_store::clipboard::detect::{detect_image_format, image_dimensions};
+use tallow_store::clipboard::ImageFormat;
+
+/// Maximum number of bytes read from a file to build its preview
+pub const PREVIEW_READ_LIMIT: usize = 64 * 1024;
+
+/// Number of lines shown for text files
+pub const TEXT_PREVIEW_LINES: usize = 20;
+
+/// Number of bytes shown in the hex dump of binary files
+pub const HEX_PREVIEW_BYTES: usize = 128;
 
 /// File type categories for preview rendering.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,6 +62,28 @@ impl FileType {
         }
     }
 
+    /// Detects file type from the file's leading bytes, using the extension
+    /// as a hint.
+    ///
+    /// Image magic bytes win over the extension; an image extension without
+    /// a recognizable header is treated as binary. Files with an unknown
+    /// extension are shown as text when the prefix is NUL-free UTF-8.
+    pub fn from_content(path: &Path, prefix: &[u8]) -> Self {
+        let format = detect_image_format(prefix);
+        let is_image = format == ImageFormat::Svg
+            || (format != ImageFormat::Unknown && image_dimensions(prefix, &format).is_some());
+        if is_image {
+            return FileType::Image;
+        }
+
+        match FileType::from_path(path) {
+            FileType::Archive => FileType::Archive,
+            FileType::Text => FileType::Text,
+            FileType::Unknown if looks_like_text(prefix) => FileType::Text,
+            _ => FileType::Binary,
+        }
+    }
+
     /// Returns the icon for this file type.
     pub fn icon(&self) -> &'static str {
         match self {
@@ -91,9 +125,17 @@ impl FilePreview {
         self.path = path.clone();
 
         if let Some(ref p) = path {
-            self.file_type = FileType::from_path(p);
             self.file_size = std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
-            self.content_preview = self.generate_preview(p);
+            match read_prefix(p) {
+                Ok(prefix) => {
+                    self.file_type = FileType::from_content(p, &prefix);
+                    self.content_preview = self.generate_preview(p, &prefix);
+                }
+                Err(e) => {
+                    self.file_type = FileType::from_path(p);
+                    self.content_preview = format!("Error reading file: {}", e);
+                }
+            }
         } else {
             self.content_preview = String::from("No file selected");
             self.file_type = FileType::Unknown;
@@ -102,69 +144,50 @@ impl FilePreview {
     }
 
     /// Generates preview content based on file type.
-    fn generate_preview(&self, path: &Path) -> String {
+    fn generate_preview(&self, path: &Path, prefix: &[u8]) -> String {
         match self.file_type {
-            FileType::Text => self.preview_text(path),
-            FileType::Image => self.preview_image(path),
+            FileType::Text => self.preview_text(prefix),
+            FileType::Image => self.preview_image(prefix),
             FileType::Archive => self.preview_archive(path),
-            FileType::Binary => self.preview_binary(path),
+            FileType::Binary => self.preview_binary(prefix),
             FileType::Unknown => self.preview_unknown(path),
         }
     }
 
-    /// Previews text files (first N lines).
-    ///
-    /// Uses BufReader to avoid reading the entire file into memory.
-    fn preview_text(&self, path: &Path) -> String {
-        use std::io::BufRead;
-        match std::fs::File::open(path) {
-            Ok(file) => {
-                let reader = std::io::BufReader::new(file);
-                let mut lines = Vec::new();
-                let mut total_lines = 0;
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line) => {
-                            total_lines += 1;
-                            if lines.len() < 20 {
-                                lines.push(line);
-                            }
-                        }
-                        Err(_) => break,
-                    }
-                    // Stop counting after a reasonable limit
-                    if total_lines > 10_000 {
-                        break;
-                    }
-                }
-                let preview = lines.join("\n");
-                if total_lines > 20 {
-                    let remaining = if total_lines > 10_000 {
-                        "10,000+".to_string()
-                    } else {
-                        (total_lines - 20).to_string()
-                    };
-                    format!("{}\n\n... ({} more lines)", preview, remaining)
-                } else {
-                    preview
-                }
-            }
-            Err(e) => format!("Error reading file: {}", e),
+    /// Previews text files (first N lines of the prefix).
+    fn preview_text(&self, prefix: &[u8]) -> String {
+        let truncated = (prefix.len() as u64) < self.file_size;
+        let text = String::from_utf8_lossy(prefix);
+        let mut lines: Vec<&str> = text.lines().collect();
+        // The last line of a cut-off prefix is probably incomplete
+        if truncated {
+            lines.pop();
+        }
+
+        let shown = lines.len().min(TEXT_PREVIEW_LINES);
+        let preview = lines[..shown].join("\n");
+        if truncated {
+            format!("{}\n\n... (file continues)", preview)
+        } else if lines.len() > shown {
+            format!("{}\n\n... ({} more lines)", preview, lines.len() - shown)
+        } else {
+            preview
         }
     }
 
-    /// Previews image metadata.
-    fn preview_image(&self, path: &Path) -> String {
+    /// Previews image metadata read from the header.
+    fn preview_image(&self, prefix: &[u8]) -> String {
         let size = self.format_size();
-        let extension = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("unknown")
-            .to_uppercase();
+        let format = detect_image_format(prefix);
+        let dimensions = match image_dimensions(prefix, &format) {
+            Some((w, h)) => format!("{} x {}", w, h),
+            None if format == ImageFormat::Svg => "scalable".to_string(),
+            None => "unknown".to_string(),
+        };
 
         format!(
-            "Image File\n\nFormat: {}\nSize: {}\n\nPreview not available in terminal.\nOpen with an image viewer to see contents.",
-            extension, size
+            "Image File\n\nFormat: {}\nDimensions: {}\nSize: {}\n\nPreview not available in terminal.\nOpen with an image viewer to see contents.",
+            format, dimensions, size
         )
     }
 
@@ -192,51 +215,44 @@ impl FilePreview {
     }
 
     /// Previews binary files (hex dump header).
-    ///
-    /// Only reads the first 128 bytes instead of the entire file.
-    fn preview_binary(&self, path: &Path) -> String {
-        use std::io::Read;
-        match std::fs::File::open(path) {
-            Ok(mut file) => {
-                let size = self.format_size();
-                let mut preview_bytes = vec![0u8; 128];
-                let bytes_read = file.read(&mut preview_bytes).unwrap_or(0);
-                preview_bytes.truncate(bytes_read);
-
-                let mut hex_dump = String::from("Binary File\n\nHex Dump (first 128 bytes):\n\n");
-
-                for (i, chunk) in preview_bytes.chunks(16).enumerate() {
-                    hex_dump.push_str(&format!("{:04x}  ", i * 16));
-
-                    // Hex values
-                    for byte in chunk {
-                        hex_dump.push_str(&format!("{:02x} ", byte));
-                    }
-
-                    // Padding
-                    for _ in chunk.len()..16 {
-                        hex_dump.push_str("   ");
-                    }
-
-                    hex_dump.push_str(" |");
-
-                    // ASCII representation
-                    for byte in chunk {
-                        if byte.is_ascii_graphic() || *byte == b' ' {
-                            hex_dump.push(*byte as char);
-                        } else {
-                            hex_dump.push('.');
-                        }
-                    }
-
-                    hex_dump.push_str("|\n");
-                }
+    fn preview_binary(&self, prefix: &[u8]) -> String {
+        let size = self.format_size();
+        let preview_bytes = &prefix[..prefix.len().min(HEX_PREVIEW_BYTES)];
+
+        let mut hex_dump = format!(
+            "Binary File\n\nHex Dump (first {} bytes):\n\n",
+            preview_bytes.len()
+        );
+
+        for (i, chunk) in preview_bytes.chunks(16).enumerate() {
+            hex_dump.push_str(&format!("{:04x}  ", i * 16));
 
-                hex_dump.push_str(&format!("\nTotal Size: {}", size));
-                hex_dump
+            // Hex values
+            for byte in chunk {
+                hex_dump.push_str(&format!("{:02x} ", byte));
             }
-            Err(e) => format!("Error reading file: {}", e),
+
+            // Padding
+            for _ in chunk.len()..16 {
+                hex_dump.push_str("   ");
+            }
+
+            hex_dump.push_str(" |");
+
+            // ASCII representation
+            for byte in chunk {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    hex_dump.push(*byte as char);
+                } else {
+                    hex_dump.push('.');
+                }
+            }
+
+            hex_dump.push_str("|\n");
         }
+
+        hex_dump.push_str(&format!("\nTotal Size: {}", size));
+        hex_dump
     }
 
     /// Previews unknown file types.
@@ -298,6 +314,27 @@ impl FilePreview {
     }
 }
 
+/// Read at most `PREVIEW_READ_LIMIT` bytes from the start of a file
+fn read_prefix(path: &Path) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut prefix = Vec::new();
+    std::fs::File::open(path)?
+        .take(PREVIEW_READ_LIMIT as u64)
+        .read_to_end(&mut prefix)?;
+    Ok(prefix)
+}
+
+/// NUL-free UTF-8, allowing a character cut off at the end of the prefix
+fn looks_like_text(prefix: &[u8]) -> bool {
+    if prefix.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
 impl Default for FilePreview {
     fn default() -> Self {
         Self::new()
@@ -326,6 +363,72 @@ mod tests {
         );
     }
 
+    fn preview_of(name: &str, contents: &[u8]) -> FilePreview {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        let mut preview = FilePreview::new();
+        preview.set_path(Some(path));
+        preview
+    }
+
+    #[test]
+    fn test_text_preview_shows_first_lines() {
+        let text: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+        let preview = preview_of("notes.txt", text.as_bytes());
+
+        assert_eq!(preview.file_type, FileType::Text);
+        assert!(preview.content_preview.starts_with("line 1\nline 2\n"));
+        assert!(preview.content_preview.contains("line 20"));
+        assert!(!preview.content_preview.contains("line 21"));
+        assert!(preview.content_preview.ends_with("(10 more lines)"));
+
+        // Unknown extension, but the content is plainly text
+        let preview = preview_of("README", b"hello\nworld\n");
+        assert_eq!(preview.file_type, FileType::Text);
+        assert_eq!(preview.content_preview, "hello\nworld");
+    }
+
+    #[test]
+    fn test_large_text_preview_reads_bounded_prefix() {
+        let text = "x".repeat(100).repeat(PREVIEW_READ_LIMIT / 50) + "\n";
+        let preview = preview_of("huge.log", text.as_bytes());
+        assert_eq!(preview.file_type, FileType::Text);
+        assert!(preview.content_preview.ends_with("... (file continues)"));
+        assert!(preview.content_preview.len() < PREVIEW_READ_LIMIT);
+    }
+
+    #[test]
+    fn test_binary_preview_shows_hex_dump() {
+        let data: Vec<u8> = (0u8..=255).collect();
+        let preview = preview_of("blob", &data);
+
+        assert_eq!(preview.file_type, FileType::Binary);
+        assert!(preview
+            .content_preview
+            .contains("Hex Dump (first 128 bytes)"));
+        assert!(preview
+            .content_preview
+            .contains("0000  00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f"));
+        assert!(preview.content_preview.contains("0070  70 71"));
+        assert!(!preview.content_preview.contains("0080  "));
+    }
+
+    #[test]
+    fn test_image_preview_shows_format_and_dimensions() {
+        let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&1920u32.to_be_bytes());
+        png.extend_from_slice(&1080u32.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        // Detected from the header even with a misleading extension
+        let preview = preview_of("screenshot.dat", &png);
+        assert_eq!(preview.file_type, FileType::Image);
+        assert!(preview.content_preview.contains("Format: PNG"));
+        assert!(preview.content_preview.contains("Dimensions: 1920 x 1080"));
+    }
+
     #[test]
     fn test_file_preview_creation() {
         let preview = FilePreview::new();
@@ -13,6 +13,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Paragraph, Widget},
 };
+use tallow_net::transport::quality::{QualityRating as TransportRating, QualitySnapshot};
 
 /// Network quality metrics
 #[derive(Debug, Clone, Copy)]
@@ -23,6 +24,8 @@ pub struct NetworkMetrics {
     pub packet_loss_pct: f64,
     /// Jitter in milliseconds
     pub jitter_ms: f64,
+    /// Rating computed by the transport; overrides the local estimate
+    pub rating: Option<QualityRating>,
 }
 
 impl NetworkMetrics {
@@ -32,6 +35,15 @@ impl NetworkMetrics {
             latency_ms,
             packet_loss_pct: packet_loss_pct.clamp(0.0, 100.0),
             jitter_ms,
+            rating: None,
+        }
+    }
+
+    /// Create metrics from a live transport snapshot
+    pub fn from_snapshot(snapshot: &QualitySnapshot) -> Self {
+        Self {
+            rating: snapshot.rating.map(QualityRating::from),
+            ..Self::new(snapshot.rtt_ms, snapshot.loss_pct, snapshot.jitter_ms)
         }
     }
 
@@ -41,6 +53,7 @@ impl NetworkMetrics {
             latency_ms: 0.0,
             packet_loss_pct: 0.0,
             jitter_ms: 0.0,
+            rating: None,
         }
     }
 
@@ -83,6 +96,10 @@ impl NetworkMetrics {
 
     /// Get overall quality rating
     pub fn quality_rating(&self) -> QualityRating {
+        if let Some(rating) = self.rating {
+            return rating;
+        }
+
         // Weighted scoring
         let latency_score = if self.latency_ms < 50.0 {
             100
@@ -146,6 +163,18 @@ pub enum QualityRating {
     Critical,
 }
 
+impl From<TransportRating> for QualityRating {
+    fn from(rating: TransportRating) -> Self {
+        match rating {
+            TransportRating::Excellent => QualityRating::Excellent,
+            TransportRating::Good => QualityRating::Good,
+            TransportRating::Fair => QualityRating::Fair,
+            TransportRating::Poor => QualityRating::Poor,
+            TransportRating::Critical => QualityRating::Critical,
+        }
+    }
+}
+
 impl QualityRating {
     /// Get the display string
     pub fn as_str(&self) -> &'static str {
@@ -373,6 +402,23 @@ mod tests {
         assert_eq!(poor.quality_rating(), QualityRating::Poor);
     }
 
+    #[test]
+    fn test_metrics_from_transport_snapshot() {
+        let snapshot = QualitySnapshot {
+            rtt_ms: 20.0,
+            jitter_ms: 2.0,
+            loss_pct: 6.0,
+            throughput_bps: 0,
+            score: Some(25),
+            rating: Some(TransportRating::Critical),
+        };
+        let metrics = NetworkMetrics::from_snapshot(&snapshot);
+        assert_eq!(metrics.latency_ms, 20.0);
+        assert_eq!(metrics.packet_loss_pct, 6.0);
+        // The transport's rating wins over the local estimate
+        assert_eq!(metrics.quality_rating(), QualityRating::Critical);
+    }
+
     #[test]
     fn test_packet_loss_clamping() {
         let metrics = NetworkMetrics::new(50.0, 150.0, 5.0);
@@ -168,6 +168,12 @@ impl SettingWidget {
         self.is_modified = false;
     }
 
+    /// Replaces the value, keeping the original for comparison.
+    pub fn set_value(&mut self, value: SettingType) {
+        self.setting_type = value;
+        self.check_modified();
+    }
+
     /// Returns the current value of the setting.
     pub fn value(&self) -> &SettingType {
         &self.setting_type
@@ -2,8 +2,15 @@
 //!
 //! Provides a category list on the left and settings editor on the right.
 //! Supports keyboard navigation and category-based organization.
+//!
+//! Each setting is bound to a key of the [`TallowConfig`] being edited.
+//! An edit is checked with the same validation `tallow config validate`
+//! runs and rejected inline if it would introduce a problem; `s` writes the
+//! config back to its file atomically. Theme and rate-limit changes are
+//! also queued as [`LiveSetting`]s for the running app to apply at once.
 
 use super::setting_widget::{SettingType, SettingWidget};
+use super::settings_actions::SettingsAction;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     buffer::Buffer,
@@ -12,11 +19,13 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph, Widget},
 };
+use std::path::PathBuf;
+use tallow_store::config::{self, TallowConfig};
 
 /// Settings category enumeration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SettingsCategory {
-    /// Network configuration (ports, protocols, NAT)
+    /// Network configuration (discovery, relays, rate limit)
     Network,
     /// Privacy settings (metadata stripping, encryption)
     Privacy,
@@ -65,7 +74,7 @@ impl SettingsCategory {
     /// Returns the description for this category.
     pub fn description(&self) -> &'static str {
         match self {
-            SettingsCategory::Network => "Configure network ports, protocols, and connectivity",
+            SettingsCategory::Network => "Configure discovery, relays, and send rate",
             SettingsCategory::Privacy => "Control metadata handling and encryption settings",
             SettingsCategory::Security => "Manage authentication and security verification",
             SettingsCategory::Display => "Customize appearance and notifications",
@@ -74,6 +83,239 @@ impl SettingsCategory {
     }
 }
 
+/// How a config value is edited
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    /// `true`/`false`
+    Toggle,
+    /// Integer between a minimum and maximum
+    Number(i64, i64),
+    /// Free text
+    Text,
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+}
+
+/// A config key shown in the settings view
+#[derive(Debug)]
+struct ConfigField {
+    category: SettingsCategory,
+    /// Dotted key in [`TallowConfig`]
+    key: &'static str,
+    label: &'static str,
+    description: &'static str,
+    kind: FieldKind,
+}
+
+impl ConfigField {
+    /// Widget value for the config's value, in string form
+    fn to_setting(&self, value: &str) -> SettingType {
+        match self.kind {
+            FieldKind::Toggle => SettingType::Toggle(value == "true"),
+            FieldKind::Number(min, max) => {
+                SettingType::Number(value.parse().unwrap_or(min), min, max)
+            }
+            FieldKind::Text => SettingType::Text(value.to_string()),
+            FieldKind::Choice(options) => SettingType::Choice(
+                options.iter().map(|o| o.to_string()).collect(),
+                options.iter().position(|o| *o == value).unwrap_or(0),
+            ),
+        }
+    }
+}
+
+/// Settings shown, in display order within each category
+const FIELDS: &[ConfigField] = &[
+    ConfigField {
+        category: SettingsCategory::Network,
+        key: "network.enable_mdns",
+        label: "LAN Discovery",
+        description: "Find and announce devices on the local network (mDNS)",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Network,
+        key: "network.enable_relay",
+        label: "Relay",
+        description: "Fall back to a relay server when peers can't connect directly",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Network,
+        key: "network.mdns_announce_interval_secs",
+        label: "Announce Interval",
+        description: "Seconds between LAN announcements",
+        kind: FieldKind::Number(1, 3600),
+    },
+    ConfigField {
+        category: SettingsCategory::Network,
+        key: "transfer.default_throttle",
+        label: "Rate Limit",
+        description: "Default send rate, e.g. 10MB or 500KB (empty = unlimited)",
+        kind: FieldKind::Text,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.strip_metadata",
+        label: "Strip Metadata",
+        description: "Remove EXIF and metadata from files",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.encrypt_filenames",
+        label: "Encrypt Filenames",
+        description: "Encrypt filenames during transfer",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.enable_onion_routing",
+        label: "Onion Routing",
+        description: "Route traffic through multiple hops",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.tor",
+        label: "Tor",
+        description: "Connect through the local Tor proxy",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.use_doh",
+        label: "DNS over HTTPS",
+        description: "Resolve relay addresses over HTTPS",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Privacy,
+        key: "privacy.encrypt_history",
+        label: "Encrypt History",
+        description: "Keep transfer history encrypted at rest",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Security,
+        key: "network.tls_min_version",
+        label: "Minimum TLS Version",
+        description: "Oldest TLS version accepted from relays",
+        kind: FieldKind::Choice(&["1.2", "1.3"]),
+    },
+    ConfigField {
+        category: SettingsCategory::Security,
+        key: "transfer.auto_accept_trusted",
+        label: "Auto-Accept Trusted",
+        description: "Accept transfers from trusted contacts without asking",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Security,
+        key: "transfer.default_words",
+        label: "Code Words",
+        description: "Words in generated code phrases",
+        kind: FieldKind::Number(3, 8),
+    },
+    ConfigField {
+        category: SettingsCategory::Display,
+        key: "ui.theme",
+        label: "Theme",
+        description: "Application color theme",
+        kind: FieldKind::Choice(&["auto", "dark", "light"]),
+    },
+    ConfigField {
+        category: SettingsCategory::Display,
+        key: "ui.show_notifications",
+        label: "Notifications",
+        description: "Show desktop notifications",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Display,
+        key: "ui.language",
+        label: "Language",
+        description: "Interface language code",
+        kind: FieldKind::Text,
+    },
+    ConfigField {
+        category: SettingsCategory::Advanced,
+        key: "transfer.chunk_size",
+        label: "Chunk Size",
+        description: "Transfer chunk size in bytes",
+        kind: FieldKind::Number(16 * 1024, 4 * 1024 * 1024),
+    },
+    ConfigField {
+        category: SettingsCategory::Advanced,
+        key: "transfer.enable_compression",
+        label: "Compression",
+        description: "Compress data before encrypting it",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Advanced,
+        key: "transfer.chunk_checksum",
+        label: "Chunk Checksums",
+        description: "Add a CRC32C to each chunk to pinpoint corruption",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Advanced,
+        key: "transfer.direct_io",
+        label: "Direct I/O",
+        description: "Bypass the page cache when writing received files",
+        kind: FieldKind::Toggle,
+    },
+    ConfigField {
+        category: SettingsCategory::Advanced,
+        key: "transfer.room_fairness",
+        label: "Room Fairness",
+        description: "How multi-receiver rooms pace slow peers",
+        kind: FieldKind::Choice(&["independent", "lockstep"]),
+    },
+];
+
+/// Keys whose changes take effect without a restart
+const LIVE_KEYS: &[&str] = &["ui.theme", "transfer.default_throttle"];
+
+/// A changed setting the running app should apply at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveSetting {
+    /// New `ui.theme`
+    Theme(String),
+    /// New `transfer.default_throttle`; empty means unlimited
+    RateLimit(String),
+}
+
+impl LiveSetting {
+    fn for_key(key: &str, config: &TallowConfig) -> Option<Self> {
+        match key {
+            "ui.theme" => Some(Self::Theme(config.ui.theme.clone())),
+            "transfer.default_throttle" => {
+                Some(Self::RateLimit(config.transfer.default_throttle.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Config value in string form, as `tallow config get` prints it
+fn value_of(config: &TallowConfig, key: &str) -> String {
+    config::get_config_value(config, key).unwrap_or_default()
+}
+
+/// String form of a widget value, as `tallow config set` takes it
+fn setting_value(setting: &SettingType) -> String {
+    match setting {
+        SettingType::Toggle(on) => on.to_string(),
+        SettingType::Text(text) => text.clone(),
+        SettingType::Number(value, _, _) => value.to_string(),
+        SettingType::Choice(options, selected) => {
+            options.get(*selected).cloned().unwrap_or_default()
+        }
+    }
+}
+
 /// Categorized settings browser with navigation.
 #[derive(Debug)]
 pub struct SettingsView {
@@ -87,196 +329,221 @@ pub struct SettingsView {
     pub selected_item: usize,
     /// Scroll offset for settings list
     pub scroll_offset: usize,
+    /// Why the last edit or save failed, until the next one succeeds
+    pub error: Option<String>,
+    /// Config including unsaved edits
+    config: TallowConfig,
+    /// Config as last loaded or saved
+    saved: TallowConfig,
+    /// File [`save`](Self::save) writes; `None` keeps edits in memory
+    path: Option<PathBuf>,
+    /// Live settings changed since the app last took them
+    live_changes: Vec<LiveSetting>,
 }
 
 impl SettingsView {
-    /// Creates a new settings view.
+    /// Creates a settings view over the default config, not backed by a file.
     pub fn new() -> Self {
-        let categories = SettingsCategory::all();
-        let current_settings = Self::settings_for_category(categories[0]);
+        Self::with_config(TallowConfig::default(), None)
+    }
 
-        Self {
-            categories,
+    /// Creates a settings view editing `config`, saved to `path` if given.
+    pub fn with_config(config: TallowConfig, path: Option<PathBuf>) -> Self {
+        let mut view = Self {
+            categories: SettingsCategory::all(),
             selected_category: 0,
-            current_settings,
+            current_settings: Vec::new(),
             selected_item: 0,
             scroll_offset: 0,
+            error: None,
+            saved: config.clone(),
+            config,
+            path,
+            live_changes: Vec::new(),
+        };
+        view.load_category_settings();
+        view
+    }
+
+    /// Opens the config file at `path`, starting from defaults if it is missing.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let config = if path.exists() {
+            config::load_config_from(&path).map_err(|e| e.to_string())?
+        } else {
+            TallowConfig::default()
+        };
+        Ok(Self::with_config(config, Some(path)))
+    }
+
+    /// Returns the config, including unsaved edits.
+    pub fn config(&self) -> &TallowConfig {
+        &self.config
+    }
+
+    /// Returns whether any shown setting differs from the saved config.
+    pub fn has_unsaved_changes(&self) -> bool {
+        FIELDS
+            .iter()
+            .any(|f| value_of(&self.config, f.key) != value_of(&self.saved, f.key))
+    }
+
+    /// Takes the live settings changed since the last call, oldest first.
+    pub fn take_live_changes(&mut self) -> Vec<LiveSetting> {
+        std::mem::take(&mut self.live_changes)
+    }
+
+    /// Sets config `key` from its string form.
+    ///
+    /// Rejected, leaving the config as it was, if the value doesn't parse
+    /// or validation finds a problem the config didn't already have.
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut candidate = self.config.clone();
+        config::set_config_value(&mut candidate, key, value).map_err(|e| e.to_string())?;
+
+        let existing = config::validate_config(&self.config);
+        let introduced = config::validate_config(&candidate).into_iter().find(|new| {
+            !existing
+                .iter()
+                .any(|old| old.key == new.key && old.message == new.message)
+        });
+        if let Some(issue) = introduced {
+            return Err(issue.message);
+        }
+
+        self.replace_config(candidate);
+        Ok(())
+    }
+
+    /// Writes the config to its file, atomically.
+    pub fn save(&mut self) -> Result<(), String> {
+        let result = match self.path {
+            Some(ref path) => config::save_config_to(&self.config, path).map_err(|e| e.to_string()),
+            None => Err("no config file to save to".to_string()),
+        };
+        match result {
+            Ok(()) => {
+                self.saved = self.config.clone();
+                self.error = None;
+                self.reload_settings();
+                Ok(())
+            }
+            Err(e) => {
+                self.error = Some(format!("Save failed: {}", e));
+                Err(e)
+            }
+        }
+    }
+
+    /// Performs a save, cancel or reset against the config.
+    ///
+    /// Resets only touch the settings this view shows.
+    pub fn execute(&mut self, action: SettingsAction) -> Result<(), String> {
+        match action {
+            SettingsAction::Save => self.save(),
+            SettingsAction::Cancel => {
+                self.replace_config(self.saved.clone());
+                Ok(())
+            }
+            SettingsAction::Reset(key) => {
+                let default = config::get_config_value(&TallowConfig::default(), &key)
+                    .map_err(|e| e.to_string())?;
+                self.set_value(&key, &default)
+            }
+            SettingsAction::ResetAll => {
+                let defaults = TallowConfig::default();
+                let mut config = self.config.clone();
+                for field in FIELDS {
+                    config::set_config_value(
+                        &mut config,
+                        field.key,
+                        &value_of(&defaults, field.key),
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                self.replace_config(config);
+                Ok(())
+            }
+        }
+    }
+
+    /// Switches to `config`, queueing live changes and refreshing widgets.
+    fn replace_config(&mut self, config: TallowConfig) {
+        for key in LIVE_KEYS {
+            if value_of(&self.config, key) != value_of(&config, key) {
+                self.live_changes.extend(LiveSetting::for_key(key, &config));
+            }
         }
+        self.config = config;
+        self.error = None;
+        self.reload_settings();
+    }
+
+    /// Fields shown in `category`, in order.
+    fn fields(category: SettingsCategory) -> impl Iterator<Item = &'static ConfigField> {
+        FIELDS.iter().filter(move |f| f.category == category)
+    }
+
+    /// Field behind the selected widget.
+    fn current_field(&self) -> Option<&'static ConfigField> {
+        Self::fields(self.categories[self.selected_category]).nth(self.selected_item)
     }
 
     /// Returns settings widgets for a given category.
-    fn settings_for_category(category: SettingsCategory) -> Vec<SettingWidget> {
-        match category {
-            SettingsCategory::Network => vec![
-                SettingWidget::new(
-                    "WebRTC Port",
-                    "Port for WebRTC connections",
-                    SettingType::Number(9000, 1024, 65535),
-                ),
-                SettingWidget::new(
-                    "Signaling Port",
-                    "Port for signaling server",
-                    SettingType::Number(8080, 1024, 65535),
-                ),
-                SettingWidget::new(
-                    "Enable IPv6",
-                    "Allow IPv6 connections",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "NAT Traversal",
-                    "Enable STUN/TURN for NAT traversal",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Upload Speed Limit",
-                    "Maximum upload speed in MB/s (0 = unlimited)",
-                    SettingType::Number(0, 0, 1000),
-                ),
-                SettingWidget::new(
-                    "Download Speed Limit",
-                    "Maximum download speed in MB/s (0 = unlimited)",
-                    SettingType::Number(0, 0, 1000),
-                ),
-            ],
-            SettingsCategory::Privacy => vec![
-                SettingWidget::new(
-                    "Strip Metadata",
-                    "Remove EXIF and metadata from files",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Encrypt Filenames",
-                    "Encrypt filenames during transfer",
-                    SettingType::Toggle(false),
-                ),
-                SettingWidget::new(
-                    "Onion Routing",
-                    "Route traffic through multiple hops",
-                    SettingType::Toggle(false),
-                ),
-                SettingWidget::new(
-                    "Traffic Padding",
-                    "Add padding to prevent traffic analysis",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Auto-Delete History",
-                    "Automatically clear transfer history",
-                    SettingType::Choice(
-                        vec![
-                            "Never".into(),
-                            "After 1 day".into(),
-                            "After 7 days".into(),
-                            "After 30 days".into(),
-                        ],
-                        0,
-                    ),
-                ),
-            ],
-            SettingsCategory::Security => vec![
-                SettingWidget::new(
-                    "Require Verification",
-                    "Require SAS verification for new devices",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Post-Quantum Crypto",
-                    "Use ML-KEM for key exchange",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Biometric Auth",
-                    "Enable biometric authentication",
-                    SettingType::Toggle(false),
-                ),
-                SettingWidget::new(
-                    "Auto-Reject Unknown",
-                    "Automatically reject transfers from unknown devices",
-                    SettingType::Toggle(false),
-                ),
-                SettingWidget::new(
-                    "Session Timeout",
-                    "Timeout for idle sessions",
-                    SettingType::Choice(
-                        vec![
-                            "5 minutes".into(),
-                            "15 minutes".into(),
-                            "30 minutes".into(),
-                            "Never".into(),
-                        ],
-                        1,
-                    ),
-                ),
-            ],
-            SettingsCategory::Display => vec![
-                SettingWidget::new(
-                    "Theme",
-                    "Application color theme",
-                    SettingType::Choice(
-                        vec![
-                            "Light".into(),
-                            "Dark".into(),
-                            "Forest".into(),
-                            "Ocean".into(),
-                        ],
-                        1,
-                    ),
-                ),
-                SettingWidget::new(
-                    "Notifications",
-                    "Show desktop notifications",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Sound Effects",
-                    "Play sound on transfer complete",
-                    SettingType::Toggle(true),
-                ),
-                SettingWidget::new(
-                    "Display Name",
-                    "Your display name for other devices",
-                    SettingType::Text("My Device".into()),
-                ),
-                SettingWidget::new(
-                    "Show Transfer Animations",
-                    "Display animations during transfers",
-                    SettingType::Toggle(true),
-                ),
-            ],
-            SettingsCategory::Advanced => vec![
-                SettingWidget::new(
-                    "Debug Logging",
-                    "Enable verbose debug logs",
-                    SettingType::Toggle(false),
-                ),
-                SettingWidget::new(
-                    "Chunk Size",
-                    "Transfer chunk size in KB",
-                    SettingType::Number(256, 64, 4096),
-                ),
-                SettingWidget::new(
-                    "Max Connections",
-                    "Maximum concurrent connections",
-                    SettingType::Number(10, 1, 100),
-                ),
-                SettingWidget::new(
-                    "Compression",
-                    "Compression algorithm for transfers",
-                    SettingType::Choice(
-                        vec!["None".into(), "Brotli".into(), "Zstd".into(), "LZ4".into()],
-                        2,
-                    ),
-                ),
-                SettingWidget::new(
-                    "WebAssembly",
-                    "Use WebAssembly for crypto operations",
-                    SettingType::Toggle(true),
-                ),
-            ],
+    ///
+    /// Widgets compare against the saved config, so unsaved edits show as
+    /// modified.
+    fn settings_for_category(&self, category: SettingsCategory) -> Vec<SettingWidget> {
+        Self::fields(category)
+            .map(|field| {
+                let mut widget = SettingWidget::new(
+                    field.label,
+                    field.description,
+                    field.to_setting(&value_of(&self.saved, field.key)),
+                );
+                widget.set_value(field.to_setting(&value_of(&self.config, field.key)));
+                widget
+            })
+            .collect()
+    }
+
+    /// Rebuilds the current category's widgets from the config, keeping the
+    /// selection and focus.
+    fn reload_settings(&mut self) {
+        let focused = self
+            .current_settings
+            .get(self.selected_item)
+            .is_some_and(|s| s.is_focused);
+        self.current_settings = self.settings_for_category(self.categories[self.selected_category]);
+        if let Some(setting) = self.current_settings.get_mut(self.selected_item) {
+            setting.is_focused = focused;
         }
     }
 
+    /// Applies the selected widget's value to the config.
+    ///
+    /// A rejected value is explained in [`error`](Self::error) and the
+    /// widget goes back to the config's value.
+    fn commit_current_setting(&mut self) {
+        let (Some(field), Some(setting)) = (
+            self.current_field(),
+            self.current_settings.get(self.selected_item),
+        ) else {
+            return;
+        };
+        let value = setting_value(setting.value());
+        if let Err(reason) = self.set_value(field.key, &value) {
+            self.reload_settings();
+            self.error = Some(format!("{}: {}", field.label, reason));
+        }
+    }
+
+    /// Whether the selected widget takes typed input, applied on Enter.
+    fn current_is_typed(&self) -> bool {
+        self.current_settings
+            .get(self.selected_item)
+            .is_some_and(|s| matches!(s.value(), SettingType::Text(_) | SettingType::Number(..)))
+    }
+
     /// Navigates to the next category.
     pub fn next_category(&mut self) {
         if self.selected_category + 1 < self.categories.len() {
@@ -296,7 +563,7 @@ impl SettingsView {
     /// Loads settings for the currently selected category.
     fn load_category_settings(&mut self) {
         let category = self.categories[self.selected_category];
-        self.current_settings = Self::settings_for_category(category);
+        self.current_settings = self.settings_for_category(category);
         self.selected_item = 0;
         self.scroll_offset = 0;
     }
@@ -330,11 +597,19 @@ impl SettingsView {
     }
 
     /// Handles input for the currently focused setting.
+    ///
+    /// Toggles and choices have no invalid in-between states, so they
+    /// apply to the config at once.
     pub fn handle_setting_input(&mut self, key: KeyEvent) {
-        if let Some(setting) = self.current_settings.get_mut(self.selected_item) {
-            if setting.is_focused {
-                setting.handle_input(key);
-            }
+        let Some(setting) = self.current_settings.get_mut(self.selected_item) else {
+            return;
+        };
+        if !setting.is_focused {
+            return;
+        }
+        setting.handle_input(key);
+        if !self.current_is_typed() {
+            self.commit_current_setting();
         }
     }
 
@@ -349,15 +624,20 @@ impl SettingsView {
 
         if is_setting_focused {
             match key.code {
-                KeyCode::Esc => {
+                KeyCode::Enter if self.current_is_typed() => {
+                    self.commit_current_setting();
                     self.deactivate_current_setting();
-                    return true;
                 }
-                _ => {
-                    self.handle_setting_input(key);
-                    return true;
+                KeyCode::Esc => {
+                    // Drop a half-typed value
+                    if self.current_is_typed() {
+                        self.reload_settings();
+                    }
+                    self.deactivate_current_setting();
                 }
+                _ => self.handle_setting_input(key),
             }
+            return true;
         }
 
         match key.code {
@@ -381,6 +661,11 @@ impl SettingsView {
                 self.activate_current_setting();
                 true
             }
+            KeyCode::Char('s') => {
+                // A failure is shown through `error`
+                let _ = self.save();
+                true
+            }
             _ => false,
         }
     }
@@ -452,7 +737,18 @@ impl SettingsView {
                 .fg(Color::DarkGray)
                 .add_modifier(Modifier::ITALIC),
         ));
-        let desc_para = Paragraph::new(desc_line);
+        let status_line = match self.error {
+            Some(ref error) => Line::from(Span::styled(
+                error.as_str(),
+                Style::default().fg(Color::Red),
+            )),
+            None if self.has_unsaved_changes() => Line::from(Span::styled(
+                "Unsaved changes (s to save)",
+                Style::default().fg(Color::Yellow),
+            )),
+            None => Line::default(),
+        };
+        let desc_para = Paragraph::new(vec![desc_line, status_line]);
         let desc_area = Rect {
             x: inner.x,
             y: inner.y,
@@ -551,4 +847,76 @@ mod tests {
             assert_eq!(view.selected_item, 0);
         }
     }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    /// Moves the selection to `label` in `category`.
+    fn select(view: &mut SettingsView, category: SettingsCategory, label: &str) {
+        while view.categories[view.selected_category] != category {
+            view.next_category();
+        }
+        while view.current_settings[view.selected_item].label != label {
+            view.next_item();
+        }
+    }
+
+    #[test]
+    fn test_edit_updates_config() {
+        let mut view = SettingsView::new();
+        select(&mut view, SettingsCategory::Display, "Theme");
+        assert_eq!(view.config().ui.theme, "auto");
+
+        view.handle_key(key(KeyCode::Enter));
+        view.handle_key(key(KeyCode::Right));
+
+        assert_eq!(view.config().ui.theme, "dark");
+        assert!(view.has_unsaved_changes());
+        assert_eq!(
+            view.take_live_changes(),
+            vec![LiveSetting::Theme("dark".to_string())]
+        );
+        assert!(view.take_live_changes().is_empty());
+    }
+
+    #[test]
+    fn test_save_persists_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut view = SettingsView::open(path.clone()).unwrap();
+
+        view.set_value("transfer.default_throttle", "10MB").unwrap();
+        view.set_value("network.enable_relay", "false").unwrap();
+        view.handle_key(key(KeyCode::Char('s')));
+
+        assert!(view.error.is_none());
+        assert!(!view.has_unsaved_changes());
+        let saved = config::load_config_from(&path).unwrap();
+        assert_eq!(saved.transfer.default_throttle, "10MB");
+        assert!(!saved.network.enable_relay);
+    }
+
+    #[test]
+    fn test_invalid_value_rejected() {
+        let mut view = SettingsView::new();
+        select(&mut view, SettingsCategory::Network, "Rate Limit");
+        view.set_value("transfer.default_throttle", "5MB").unwrap();
+
+        view.handle_key(key(KeyCode::Enter));
+        for c in "fast".chars() {
+            view.handle_key(key(KeyCode::Char(c)));
+        }
+        view.handle_key(key(KeyCode::Enter));
+
+        assert!(view.error.as_deref().unwrap().starts_with("Rate Limit:"));
+        assert_eq!(view.config().transfer.default_throttle, "5MB");
+        assert_eq!(
+            view.current_settings[view.selected_item].value(),
+            &SettingType::Text("5MB".to_string())
+        );
+
+        assert!(view.set_value("ui.theme", "neon").is_err());
+        assert_eq!(view.config().ui.theme, "auto");
+    }
 }
@@ -193,7 +193,9 @@ pub fn get_palette(mode: ThemeMode) -> ThemePalette {
 
 /// Creates a custom palette from individual color values.
 ///
-/// Useful for user-defined themes or dynamic theme generation.
+/// Useful for user-defined themes or dynamic theme generation. Text colors
+/// that fall below WCAG AA contrast are logged as warnings (see
+/// [`crate::theme::audit`]) but the palette is still returned.
 ///
 /// # Arguments
 ///
@@ -233,7 +235,7 @@ pub fn custom_palette(
     selection_bg: (u8, u8, u8),
     selection_fg: (u8, u8, u8),
 ) -> ThemePalette {
-    ThemePalette {
+    let palette = ThemePalette {
         bg: Color::Rgb(bg.0, bg.1, bg.2),
         fg: Color::Rgb(fg.0, fg.1, fg.2),
         primary: Color::Rgb(primary.0, primary.1, primary.2),
@@ -246,7 +248,11 @@ pub fn custom_palette(
         border: Color::Rgb(border.0, border.1, border.2),
         selection_bg: Color::Rgb(selection_bg.0, selection_bg.1, selection_bg.2),
         selection_fg: Color::Rgb(selection_fg.0, selection_fg.1, selection_fg.2),
+    };
+    for warning in crate::theme::audit(&palette) {
+        tracing::warn!("custom theme: {}", warning);
     }
+    palette
 }
 
 #[cfg(test)]
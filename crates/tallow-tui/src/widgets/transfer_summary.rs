@@ -5,6 +5,7 @@
 
 use ratatui::prelude::*;
 use ratatui::widgets::*;
+use tallow_protocol::transfer::CompressionStats;
 
 use super::transfer_progress::{format_bytes, format_duration};
 
@@ -18,7 +19,7 @@ use super::transfer_progress::{format_bytes, format_duration};
 /// │ Total Size:  245.8 MB (compressed: 187.2 MB)│
 /// │ Elapsed:     1m 23s                         │
 /// │ Avg Speed:   17.8 MB/s                      │
-/// │ Compression: 23.8% reduction                │
+/// │ Compression: 1.31x, 58.6 MB saved (23.8%)   │
 /// └─────────────────────────────────────────────┘
 /// ```
 #[derive(Debug, Clone)]
@@ -29,8 +30,8 @@ pub struct TransferSummary {
     pub elapsed_secs: u64,
     /// Average transfer speed in bytes per second
     pub avg_speed: u64,
-    /// Compression ratio (0.0-1.0, where 0.8 means 80% of original size)
-    pub compression_ratio: f64,
+    /// Original vs compressed byte counts; `None` if compression was skipped
+    pub compression: Option<CompressionStats>,
     /// Number of files transferred
     pub files_count: usize,
 }
@@ -41,31 +42,48 @@ impl TransferSummary {
         total_bytes: u64,
         elapsed_secs: u64,
         avg_speed: u64,
-        compression_ratio: f64,
+        compression: Option<CompressionStats>,
         files_count: usize,
     ) -> Self {
         Self {
             total_bytes,
             elapsed_secs,
             avg_speed,
-            compression_ratio,
+            compression,
             files_count,
         }
     }
 
     /// Calculates compressed size in bytes.
     fn compressed_bytes(&self) -> u64 {
-        (self.total_bytes as f64 * self.compression_ratio) as u64
+        self.compression
+            .map_or(self.total_bytes, |c| c.compressed_bytes)
     }
 
-    /// Calculates compression percentage reduction.
+    /// Calculates compression percentage reduction (never negative).
     fn compression_percent(&self) -> f64 {
-        (1.0 - self.compression_ratio) * 100.0
+        self.compression.map_or(0.0, |c| c.percent_saved())
     }
 
-    /// Determines if compression was used (ratio != 1.0).
+    /// Determines if compression actually shrank the data.
     fn has_compression(&self) -> bool {
-        (self.compression_ratio - 1.0).abs() > 0.001
+        self.compression.is_some_and(|c| c.bytes_saved() > 0)
+    }
+
+    /// Text for the compression line.
+    fn compression_text(&self) -> String {
+        match self.compression {
+            None => "Compression: none".to_string(),
+            Some(c) if c.bytes_saved() == 0 => {
+                format!("Compression: {:.2}x, no savings", c.ratio())
+            }
+            Some(c) => format!(
+                "Compression: {:.2}x, {} saved ({:.1}%)",
+                c.ratio(),
+                format_bytes(c.bytes_saved()),
+                self.compression_percent()
+            ),
+        }
     }
 }
 
@@ -99,11 +117,7 @@ impl Widget for TransferSummary {
         let elapsed_line = format!("Elapsed:     {}", format_duration(self.elapsed_secs));
         let speed_line = format!("Avg Speed:   {}/s", format_bytes(self.avg_speed));
 
-        let compression_line = if self.has_compression() {
-            format!("Compression: {:.1}% reduction", self.compression_percent())
-        } else {
-            "Compression: None".to_string()
-        };
+        let compression_line = self.compression_text();
 
         // Render content lines
         let content_style = Style::default().fg(Color::White);
@@ -296,30 +310,57 @@ impl Widget for TransferSummaryWithErrors {
 mod tests {
     use super::*;
 
+    fn stats(original: u64, compressed: u64) -> Option<CompressionStats> {
+        Some(CompressionStats {
+            original_bytes: original,
+            compressed_bytes: compressed,
+        })
+    }
+
     #[test]
     fn test_compressed_bytes() {
-        let summary = TransferSummary::new(1000, 10, 100, 0.8, 5);
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 800), 5);
         assert_eq!(summary.compressed_bytes(), 800);
+
+        let summary = TransferSummary::new(1000, 10, 100, None, 5);
+        assert_eq!(summary.compressed_bytes(), 1000);
     }
 
     #[test]
     fn test_compression_percent() {
-        let summary = TransferSummary::new(1000, 10, 100, 0.8, 5);
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 800), 5);
         assert!((summary.compression_percent() - 20.0).abs() < 0.001);
 
-        let summary = TransferSummary::new(1000, 10, 100, 0.5, 5);
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 500), 5);
         assert!((summary.compression_percent() - 50.0).abs() < 0.001);
     }
 
     #[test]
     fn test_has_compression() {
-        let summary = TransferSummary::new(1000, 10, 100, 0.8, 5);
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 800), 5);
         assert!(summary.has_compression());
 
-        let summary = TransferSummary::new(1000, 10, 100, 1.0, 5);
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 1000), 5);
         assert!(!summary.has_compression());
     }
 
+    #[test]
+    fn test_compression_text() {
+        let summary = TransferSummary::new(4096, 10, 100, stats(4096, 1024), 1);
+        assert_eq!(
+            summary.compression_text(),
+            format!("Compression: 4.00x, {} saved (75.0%)", format_bytes(3072))
+        );
+
+        // Incompressible data grows slightly; no negative savings shown
+        let summary = TransferSummary::new(1000, 10, 100, stats(1000, 1004), 1);
+        assert_eq!(summary.compression_text(), "Compression: 1.00x, no savings");
+        assert_eq!(summary.compression_percent(), 0.0);
+
+        let summary = TransferSummary::new(1000, 10, 100, None, 1);
+        assert_eq!(summary.compression_text(), "Compression: none");
+    }
+
     #[test]
     fn test_compact_avg_speed() {
         let summary = TransferSummaryCompact::new(10000, 10, 5);
@@ -331,7 +372,7 @@ mod tests {
 
     #[test]
     fn test_status_color() {
-        let base = TransferSummary::new(1000, 10, 100, 1.0, 5);
+        let base = TransferSummary::new(1000, 10, 100, None, 5);
         let summary = TransferSummaryWithErrors::new(base.clone(), 0, 0);
         assert_eq!(summary.status_color(), Color::Green);
 
@@ -5,9 +5,12 @@
 //!
 //! Generic encode/decode uses serde-wasm-bindgen to bridge JsValue <-> Message.
 //! Typed convenience functions build specific Message variants from raw fields.
+//! `StreamCipher` seals and opens chunk payloads the way the CLI does.
 
+use tallow_protocol::transfer::chunking::{build_chunk_aad, build_chunk_nonce};
 use tallow_protocol::wire::Message;
 use wasm_bindgen::prelude::*;
+use zeroize::Zeroize;
 
 // ---------------------------------------------------------------------------
 // Generic encode / decode
@@ -285,6 +288,77 @@ pub fn encode_pong() -> Result<Vec<u8>, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("postcard encode Pong: {}", e)))
 }
 
+// ---------------------------------------------------------------------------
+// Streaming chunk cipher
+// ---------------------------------------------------------------------------
+
+/// AES-256-GCM cipher for the `data` of transfer chunks.
+///
+/// Seals and opens chunk payloads exactly as the CLI's send and receive
+/// pipelines do: the nonce and AAD come from the same
+/// `tallow_protocol::transfer::chunking` functions, so a browser sender and
+/// a CLI receiver (or the reverse) interoperate. Compression is not
+/// applied; compress first if the transfer negotiated it.
+///
+/// Unlike `TransferSession`, chunks may be processed in any order, as a
+/// streaming transport with retransmission delivers them.
+#[wasm_bindgen]
+pub struct StreamCipher {
+    key: [u8; 32],
+    transfer_id: [u8; 16],
+}
+
+#[wasm_bindgen]
+impl StreamCipher {
+    /// Create a cipher for one transfer.
+    ///
+    /// * `key`         - 32-byte session key from the KEM handshake
+    /// * `transfer_id` - 16-byte transfer identifier, bound into every chunk's AAD
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8], transfer_id: &[u8]) -> Result<StreamCipher, JsValue> {
+        Ok(StreamCipher {
+            key: to_array_32(key, "key")?,
+            transfer_id: to_array_16(transfer_id, "transfer_id")?,
+        })
+    }
+
+    /// Encrypt chunk `index`, returning ciphertext with the 16-byte tag.
+    #[wasm_bindgen(js_name = "encryptChunk")]
+    pub fn encrypt_chunk(&self, index: u64, data: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.seal(index, data)
+            .map_err(|_| JsValue::from_str("encryption failed"))
+    }
+
+    /// Decrypt chunk `index`, failing if it was altered, belongs to another
+    /// transfer, or was encrypted under a different index.
+    #[wasm_bindgen(js_name = "decryptChunk")]
+    pub fn decrypt_chunk(&self, index: u64, data: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.open(index, data)
+            .map_err(|_| JsValue::from_str("decryption failed"))
+    }
+}
+
+impl StreamCipher {
+    fn seal(&self, index: u64, data: &[u8]) -> tallow_crypto::Result<Vec<u8>> {
+        let aad = build_chunk_aad(&self.transfer_id, index);
+        let nonce = build_chunk_nonce(index);
+        tallow_crypto::symmetric::aes_encrypt(&self.key, &nonce, data, &aad)
+    }
+
+    fn open(&self, index: u64, data: &[u8]) -> tallow_crypto::Result<Vec<u8>> {
+        let aad = build_chunk_aad(&self.transfer_id, index);
+        let nonce = build_chunk_nonce(index);
+        tallow_crypto::symmetric::aes_decrypt(&self.key, &nonce, data, &aad)
+    }
+}
+
+/// Zeroize the key on drop to prevent key material from lingering in memory.
+impl Drop for StreamCipher {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Sanitization
 // ---------------------------------------------------------------------------
@@ -334,3 +408,71 @@ fn to_array_32(bytes: &[u8], name: &str) -> Result<[u8; 32], JsValue> {
         ))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const KEY: [u8; 32] = [0x5A; 32];
+    const TRANSFER_ID: [u8; 16] = [0x0C; 16];
+
+    /// The CLI pipelines' chunk sealing, with compression off
+    fn native_seal(index: u64, data: &[u8]) -> Vec<u8> {
+        let aad = build_chunk_aad(&TRANSFER_ID, index);
+        let nonce = build_chunk_nonce(index);
+        tallow_crypto::symmetric::aes_encrypt(&KEY, &nonce, data, &aad).unwrap()
+    }
+
+    fn native_open(index: u64, data: &[u8]) -> Vec<u8> {
+        let aad = build_chunk_aad(&TRANSFER_ID, index);
+        let nonce = build_chunk_nonce(index);
+        tallow_crypto::symmetric::aes_decrypt(&KEY, &nonce, data, &aad).unwrap()
+    }
+
+    #[test]
+    fn test_nonce_and_aad_layout() {
+        // Pinned bytes: a change here breaks every deployed peer
+        let index = 0x0102_0304_0506_0708;
+        assert_eq!(
+            build_chunk_nonce(index),
+            [0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8]
+        );
+        let aad = build_chunk_aad(&TRANSFER_ID, index);
+        assert_eq!(&aad[..16], &TRANSFER_ID);
+        assert_eq!(&aad[16..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_stream_cipher_interoperates_with_native() {
+        let cipher = StreamCipher::new(&KEY, &TRANSFER_ID).unwrap();
+        for index in [0, 1, 255, 256, u32::MAX as u64 + 1, u64::MAX] {
+            let data = format!("chunk {}", index).into_bytes();
+
+            // Browser sender, CLI receiver
+            let sealed = cipher.encrypt_chunk(index, &data).unwrap();
+            assert_eq!(sealed, native_seal(index, &data));
+            assert_eq!(native_open(index, &sealed), data);
+
+            // CLI sender, browser receiver
+            let sealed = native_seal(index, &data);
+            assert_eq!(cipher.decrypt_chunk(index, &sealed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_stream_cipher_binds_index_and_transfer() {
+        let cipher = StreamCipher::new(&KEY, &TRANSFER_ID).unwrap();
+        let sealed = cipher.seal(3, b"payload").unwrap();
+        assert!(cipher.open(4, &sealed).is_err());
+
+        let other = StreamCipher::new(&KEY, &[0x0D; 16]).unwrap();
+        assert!(other.open(3, &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 1;
+        assert!(cipher.open(3, &tampered).is_err());
+        assert_eq!(cipher.open(3, &sealed).unwrap(), b"payload");
+    }
+}
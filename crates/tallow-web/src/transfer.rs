@@ -1,11 +1,12 @@
 //! File transfer state machine for WASM
 //!
-//! Handles chunk encryption/decryption with AES-256-GCM using the SAME
-//! AAD and nonce construction as the CLI (see `tallow-protocol/src/transfer/chunking.rs`).
+//! Handles chunk encryption/decryption with AES-256-GCM using the CLI's own
+//! AAD and nonce construction (`tallow_protocol::transfer::chunking`).
 //!
 //! AAD = transfer_id (16 bytes) || chunk_index (8 bytes BE)
 //! Nonce = [0u8; 4] || chunk_index.to_be_bytes() (12 bytes total)
 
+use tallow_protocol::transfer::chunking::{build_chunk_aad, build_chunk_nonce};
 use tallow_protocol::wire::Message;
 use wasm_bindgen::prelude::*;
 use zeroize::Zeroize;
@@ -15,8 +16,8 @@ use zeroize::Zeroize;
 /// Owns the session key and transfer ID. Provides methods to prepare
 /// encrypted chunks for sending and decrypt received chunks.
 ///
-/// CRITICAL: The AAD and nonce construction here MUST match the CLI exactly.
-/// See `crates/tallow-protocol/src/transfer/chunking.rs`:
+/// The AAD and nonce come from the same functions the CLI uses
+/// (`tallow_protocol::transfer::chunking`):
 /// - `build_chunk_aad(transfer_id, chunk_index)` = transfer_id || index.to_be_bytes()
 /// - `build_chunk_nonce(chunk_index)` = [0u8; 4] || index.to_be_bytes()
 #[wasm_bindgen]
@@ -225,32 +226,3 @@ impl Drop for TransferSession {
         self.session_key.zeroize();
     }
 }
-
-// ---------------------------------------------------------------------------
-// AAD and nonce construction — MUST match CLI exactly
-// See: crates/tallow-protocol/src/transfer/chunking.rs
-// ---------------------------------------------------------------------------
-
-/// Build AAD (Additional Authenticated Data) for a chunk.
-///
-/// Format: transfer_id (16 bytes) || chunk_index (8 bytes big-endian)
-///
-/// This binds the chunk to a specific transfer and position,
-/// preventing reordering attacks.
-fn build_chunk_aad(transfer_id: &[u8; 16], chunk_index: u64) -> Vec<u8> {
-    let mut aad = Vec::with_capacity(24);
-    aad.extend_from_slice(transfer_id);
-    aad.extend_from_slice(&chunk_index.to_be_bytes());
-    aad
-}
-
-/// Build a counter-based nonce for AES-256-GCM.
-///
-/// Format: [0u8; 4] || chunk_index.to_be_bytes() (12 bytes total)
-///
-/// Guarantees uniqueness: each chunk gets a different nonce.
-fn build_chunk_nonce(chunk_index: u64) -> [u8; 12] {
-    let mut nonce = [0u8; 12];
-    nonce[4..12].copy_from_slice(&chunk_index.to_be_bytes());
-    nonce
-}
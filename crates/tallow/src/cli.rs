@@ -19,6 +19,16 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// OS sandbox mode: off (default) or strict (Linux: restrict syscalls
+    /// and file access once the transfer is set up)
+    #[arg(long, global = true, value_name = "MODE")]
+    pub sandbox: Option<String>,
+
+    /// Profile whose identity, trust store, history and config to use
+    /// (each profile is stored separately; "default" is the usual one)
+    #[arg(long, global = true, value_name = "NAME", env = "TALLOW_PROFILE")]
+    pub profile: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -65,7 +75,7 @@ pub enum Commands {
     Config(ConfigArgs),
 
     /// Run diagnostic checks
-    Doctor,
+    Doctor(DoctorArgs),
 
     /// Run performance benchmarks
     Benchmark(BenchmarkArgs),
@@ -91,6 +101,24 @@ pub enum Commands {
     /// Persistent receive mode (drop box) -- auto-accept from trusted contacts
     DropBox(DropBoxArgs),
 
+    /// Encrypt stdin to stdout as a signed bundle (no network)
+    Encrypt(EncryptArgs),
+
+    /// Decrypt a bundle from stdin to stdout (no network)
+    Decrypt(DecryptArgs),
+
+    /// Write a detached signature (<file>.tallowsig) with your identity key
+    Sign(SignArgs),
+
+    /// Check a detached signature against a contact or known key
+    Verify(VerifyArgs),
+
+    /// Compare a contact's fingerprint out of band and mark it verified
+    VerifyPeer(VerifyPeerArgs),
+
+    /// Reassemble a file received with --split-size
+    Join(JoinArgs),
+
     /// Check for updates and install the latest version
     Update(UpdateArgs),
 
@@ -132,8 +160,14 @@ pub struct ClipArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address (e.g., socks5://127.0.0.1:9050, also reads TALLOW_PROXY env var)
@@ -222,6 +256,10 @@ pub struct SendArgs {
     #[arg(long)]
     pub qr: bool,
 
+    /// Write the receive command's QR code to an image file (.png or .svg)
+    #[arg(long, value_name = "PATH")]
+    pub qr_out: Option<PathBuf>,
+
     /// Do not copy receive command to clipboard
     #[arg(long)]
     pub no_clipboard: bool,
@@ -230,7 +268,8 @@ pub struct SendArgs {
     #[arg(long)]
     pub ignore_stdin: bool,
 
-    /// Target peer ID or device name
+    /// Target contact (alias, name or fingerprint prefix), peer ID, or
+    /// device name
     #[arg(long)]
     pub to: Option<String>,
 
@@ -242,6 +281,15 @@ pub struct SendArgs {
     #[arg(short = 'x', long, default_value = "auto")]
     pub compress: String,
 
+    /// Add a CRC32C to every chunk so the receiver can pinpoint corruption
+    /// before decryption (a debugging aid; also set by transfer.chunk_checksum)
+    #[arg(long)]
+    pub chunk_checksum: bool,
+
+    /// File hash algorithm (blake3/sha3-256); the receiver must support it
+    #[arg(long, default_value = "blake3")]
+    pub hash: String,
+
     /// Strip metadata from files
     #[arg(long)]
     pub strip_metadata: bool,
@@ -254,10 +302,24 @@ pub struct SendArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
+    /// Relay bearer token, hex-encoded (also reads TALLOW_RELAY_TOKEN env var)
+    #[arg(
+        long = "relay-token",
+        env = "TALLOW_RELAY_TOKEN",
+        hide_env_values = true
+    )]
+    pub relay_token: Option<String>,
+
     /// SOCKS5 proxy address (e.g., socks5://127.0.0.1:9050, also reads TALLOW_PROXY env var)
     #[arg(long, env = "TALLOW_PROXY")]
     pub proxy: Option<String>,
@@ -282,6 +344,16 @@ pub struct SendArgs {
     #[arg(long)]
     pub throttle: Option<String>,
 
+    /// Pace chunks to the QUIC congestion window instead of filling the
+    /// send buffer (TCP+TLS falls back to socket backpressure)
+    #[arg(long)]
+    pub pace: bool,
+
+    /// Chunks in flight before waiting for acknowledgements; also bounds
+    /// how far file reads run ahead of the network
+    #[arg(long, default_value = "64")]
+    pub window: usize,
+
     /// Prompt sender for confirmation before starting transfer
     #[arg(long)]
     pub ask: bool,
@@ -309,12 +381,33 @@ pub struct SendArgs {
     pub notify: bool,
 
     /// Maximum reconnection attempts on transient network failure (0 to disable)
+    /// (exits with code 9 once they are used up)
     #[arg(long, default_value = "5")]
     pub max_retries: u32,
 
+    /// Seconds to wait for the peer to complete the key exchange once connected
+    #[arg(long, default_value = "30", value_name = "SECS")]
+    pub handshake_timeout: u64,
+
+    /// File holding a 256-bit pre-shared key as 64 hex characters (also
+    /// reads TALLOW_PSK_FILE). Both peers must use the same key; it
+    /// authenticates the key exchange instead of the code phrase, which
+    /// then only picks the room
+    #[arg(long, value_name = "PATH", env = "TALLOW_PSK_FILE")]
+    pub psk_file: Option<PathBuf>,
+
+    /// Bind the transfer key to a context label (the receiver must use the same one)
+    #[arg(long, value_name = "LABEL")]
+    pub context: Option<String>,
+
     /// Disable hook execution (skip pre_send, post_send, on_error hooks)
     #[arg(long)]
     pub no_hooks: bool,
+
+    /// Run a program (no shell) or POST to an http(s) URL when the transfer
+    /// finishes; overrides transfer.on_complete
+    #[arg(long, value_name = "COMMAND|URL")]
+    pub on_complete: Option<String>,
 }
 
 #[derive(Args)]
@@ -343,10 +436,24 @@ pub struct ReceiveArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
+    /// Relay bearer token, hex-encoded (also reads TALLOW_RELAY_TOKEN env var)
+    #[arg(
+        long = "relay-token",
+        env = "TALLOW_RELAY_TOKEN",
+        hide_env_values = true
+    )]
+    pub relay_token: Option<String>,
+
     /// SOCKS5 proxy address (also reads TALLOW_PROXY env var)
     #[arg(long, env = "TALLOW_PROXY")]
     pub proxy: Option<String>,
@@ -385,13 +492,79 @@ pub struct ReceiveArgs {
     #[arg(long)]
     pub per_file: bool,
 
+    /// Receive only offered files whose path matches GLOB (gitignore syntax,
+    /// repeatable); the sender skips the rest
+    #[arg(long, value_name = "GLOB")]
+    pub accept: Vec<String>,
+
+    /// Rename received files using placeholders: {name}, {ext}, {date},
+    /// {sender}, {n} (e.g. "{date}_{name}.{ext}")
+    #[arg(long)]
+    pub output_template: Option<String>,
+
+    /// What to do when a received file already exists (skip/overwrite/rename).
+    /// Prompts when unset
+    #[arg(long)]
+    pub on_conflict: Option<String>,
+
+    /// Recreate the sender's subdirectories instead of writing every file
+    /// directly into the output directory
+    #[arg(long)]
+    pub preserve_structure: bool,
+
     /// Maximum reconnection attempts on transient network failure (0 to disable)
+    /// (exits with code 9 once they are used up)
     #[arg(long, default_value = "5")]
     pub max_retries: u32,
 
+    /// Seconds to wait for the peer to complete the key exchange once connected
+    #[arg(long, default_value = "30", value_name = "SECS")]
+    pub handshake_timeout: u64,
+
+    /// File holding a 256-bit pre-shared key as 64 hex characters (also
+    /// reads TALLOW_PSK_FILE). Both peers must use the same key; it
+    /// authenticates the key exchange instead of the code phrase, which
+    /// then only picks the room
+    #[arg(long, value_name = "PATH", env = "TALLOW_PSK_FILE")]
+    pub psk_file: Option<PathBuf>,
+
+    /// Bind the transfer key to a context label (the sender must use the same one)
+    #[arg(long, value_name = "LABEL")]
+    pub context: Option<String>,
+
     /// Disable hook execution (skip pre_receive, post_receive, on_error hooks)
     #[arg(long)]
     pub no_hooks: bool,
+
+    /// Run a program (no shell) or POST to an http(s) URL when the transfer
+    /// finishes; overrides transfer.on_complete
+    #[arg(long, value_name = "COMMAND|URL")]
+    pub on_complete: Option<String>,
+
+    /// Put a small text transfer on the clipboard (and clipboard history)
+    /// instead of printing it. Files, binary data and text over 1 MiB are refused
+    #[arg(long, conflicts_with_all = ["output", "per_file"])]
+    pub to_clipboard: bool,
+
+    /// Verify received files against a checksum file (SHA256SUMS, SHA512SUMS
+    /// or B3SUMS format) and fail if any listed file differs
+    #[arg(long, value_name = "PATH", conflicts_with = "to_clipboard")]
+    pub checksum_file: Option<PathBuf>,
+
+    /// Write files larger than SIZE as numbered volumes (file.part001, ...)
+    /// plus a manifest for `tallow join` (e.g. "3G" for FAT32 media)
+    #[arg(long, value_name = "SIZE", conflicts_with_all = ["checksum_file", "to_clipboard"])]
+    pub split_size: Option<String>,
+
+    /// Receive exactly one transfer without prompting, then exit (for
+    /// scripts and cron). Requires a code phrase and accepts the offer as
+    /// --yes would; exits with code 10 if no sender connects in time
+    #[arg(long, conflicts_with = "per_file")]
+    pub once: bool,
+
+    /// Give up if no sender connects within SECS (default with --once: 300)
+    #[arg(long, value_name = "SECS")]
+    pub wait_timeout: Option<u64>,
 }
 
 #[derive(Args)]
@@ -423,8 +596,14 @@ pub struct SyncArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address (also reads TALLOW_PROXY env var)
@@ -470,8 +649,14 @@ pub struct WatchArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address (also reads TALLOW_PROXY env var)
@@ -505,8 +690,14 @@ pub struct ChatArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address (also reads TALLOW_PROXY env var)
@@ -650,6 +841,20 @@ pub enum IdentityCommands {
         #[arg(short, long)]
         emoji: bool,
     },
+    /// Show a password-protected public bundle as a sequence of QR codes
+    QrExport {
+        /// One-time pre-keys to include in the bundle
+        #[arg(long, default_value = "1")]
+        prekeys: usize,
+    },
+    /// Reassemble and verify a bundle from scanned QR frames
+    QrImport {
+        /// File with one scanned frame per line (reads stdin if omitted)
+        file: Option<PathBuf>,
+        /// Save the verified identity as a contact with this name
+        #[arg(long)]
+        name: Option<String>,
+    },
 }
 
 #[derive(Args)]
@@ -678,6 +883,11 @@ pub enum ConfigCommands {
     },
     /// List all configuration keys and values
     List,
+    /// Check a config file against the schema and report every problem
+    Validate {
+        /// Config file to check (defaults to the active config)
+        path: Option<PathBuf>,
+    },
     /// Reset to defaults
     Reset {
         /// Skip confirmation
@@ -709,6 +919,25 @@ pub enum AliasCommands {
     List,
 }
 
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// How much to show: pass/fail counts, every check with its hint, or
+    /// also the raw probe measurements. --json always has everything
+    #[arg(long, value_enum, default_value_t = DoctorDetail::Full)]
+    pub detail: DoctorDetail,
+}
+
+/// Output detail level for `tallow doctor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DoctorDetail {
+    /// Pass/fail counts and the names of failed checks
+    Summary,
+    /// Each check's status, message and fix
+    Full,
+    /// Everything in full, plus raw measurements
+    Debug,
+}
+
 #[derive(Args)]
 pub struct BenchmarkArgs {
     /// Benchmark type (crypto/network/compression/all)
@@ -736,6 +965,25 @@ pub struct HistoryArgs {
     /// Clear all transfer history
     #[arg(long)]
     pub clear: bool,
+
+    #[command(subcommand)]
+    pub command: Option<HistoryCommands>,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommands {
+    /// Export the transfer log for analysis
+    Export {
+        /// Output format (csv, jsonl)
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Shorten peer fingerprints to a prefix
+        #[arg(long)]
+        redact: bool,
+    },
 }
 
 #[derive(Args)]
@@ -748,8 +996,14 @@ pub struct SpeedTestArgs {
     #[arg(long, env = "TALLOW_RELAY", default_value = "129.146.114.5:4433")]
     pub relay: String,
 
-    /// Relay password
-    #[arg(long, env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long,
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address
@@ -778,12 +1032,100 @@ pub struct SshSetupArgs {
     #[arg(long, env = "TALLOW_RELAY", default_value = "129.146.114.5:4433")]
     pub relay: String,
 
-    /// Relay password
-    #[arg(long, env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long,
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 }
 
 /// Arguments for the `drop-box` persistent receive command
+#[derive(Args)]
+pub struct EncryptArgs {
+    /// Recipient's hybrid KEM public key file
+    #[arg(
+        long,
+        required_unless_present = "password",
+        conflicts_with = "password"
+    )]
+    pub recipient: Option<PathBuf>,
+
+    /// Encrypt to a password instead of a key (also reads TALLOW_PASSWORD env var)
+    #[arg(long, env = "TALLOW_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DecryptArgs {
+    /// Hybrid KEM secret key file matching the recipient public key
+    #[arg(
+        long,
+        required_unless_present = "password",
+        conflicts_with = "password"
+    )]
+    pub key: Option<PathBuf>,
+
+    /// Password the bundle was encrypted to (also reads TALLOW_PASSWORD env var)
+    #[arg(long, env = "TALLOW_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// File to sign
+    pub file: PathBuf,
+
+    /// Signature file to write (default: <file>.tallowsig)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite an existing signature file
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Signed file
+    pub file: PathBuf,
+
+    /// Detached signature (default: <file>.tallowsig)
+    pub signature: Option<PathBuf>,
+
+    /// Expected signer's public key, as hex (see `tallow identity show`)
+    #[arg(
+        long,
+        value_name = "HEX",
+        required_unless_present = "contact",
+        conflicts_with = "contact"
+    )]
+    pub key: Option<String>,
+
+    /// Expected signer, by contact name, alias or ID
+    #[arg(long)]
+    pub contact: Option<String>,
+}
+
+#[derive(Args)]
+pub struct VerifyPeerArgs {
+    /// Contact to verify, by name, alias or fingerprint prefix
+    pub contact: String,
+}
+
+#[derive(Args)]
+pub struct JoinArgs {
+    /// Volume manifest written beside the parts (<file>.volumes.json)
+    pub manifest: PathBuf,
+
+    /// Where to write the joined file (default: original name, beside the parts)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
 #[derive(Args)]
 pub struct DropBoxArgs {
     /// Fixed code phrase for persistent room
@@ -810,8 +1152,14 @@ pub struct DropBoxArgs {
     #[arg(long, default_value = "129.146.114.5:4433", env = "TALLOW_RELAY")]
     pub relay: String,
 
-    /// Relay password (also reads TALLOW_RELAY_PASS env var)
-    #[arg(long = "relay-pass", env = "TALLOW_RELAY_PASS", hide_env_values = true)]
+    /// Relay password (also reads TALLOW_RELAY_PASS env var; pass with no value to be prompted)
+    #[arg(
+        long = "relay-pass",
+        env = "TALLOW_RELAY_PASS",
+        hide_env_values = true,
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
     pub relay_pass: Option<String>,
 
     /// SOCKS5 proxy address (also reads TALLOW_PROXY env var)
@@ -29,11 +29,11 @@ async fn encode_and_send(
     encode_buf.clear();
     codec
         .encode_msg(msg, encode_buf)
-        .map_err(|e| io::Error::other(format!("encode: {e}")))?;
+        .map_err(crate::error::context("encode"))?;
     channel
         .send_message(encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("send: {e}")))?;
+        .map_err(crate::error::context("send"))?;
     Ok(())
 }
 
@@ -82,8 +82,14 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
                 "Custom code must be at least 4 characters for security",
             ));
         }
-        if custom_code.len() < 8 && !json {
-            output::color::warning("Short custom code -- security depends on code phrase entropy");
+        if !json {
+            let strength = tallow_crypto::kdf::estimate_strength(custom_code);
+            if strength.score < 3 {
+                output::color::warning(
+                    "Weak custom code -- security depends on code phrase entropy",
+                );
+                output::color::password_strength(&strength);
+            }
         }
         custom_code.clone()
     } else {
@@ -150,24 +156,14 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
-
     // Establish relay connection (chat always uses relay, no LAN mode)
     let mut channel = if let Some(ref proxy) = proxy_config {
         let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
             .await
-            .map_err(|e| io::Error::other(format!("Relay resolution failed: {e}")))?;
+            .map_err(crate::error::context("Relay resolution failed"))?;
 
         let mut relay = match resolved {
             tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -183,7 +179,7 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
         relay
             .connect(&room_id, pw_ref)
             .await
-            .map_err(|e| io::Error::other(format!("Connection failed: {e}")))?;
+            .map_err(crate::error::context("Connection failed"))?;
         if !relay.peer_present() {
             if !json {
                 output::color::info("Waiting for peer...");
@@ -191,7 +187,7 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
             relay
                 .wait_for_peer()
                 .await
-                .map_err(|e| io::Error::other(format!("Waiting for peer failed: {e}")))?;
+                .map_err(crate::error::context("Waiting for peer failed"))?;
         }
 
         tallow_net::transport::ConnectionResult::Relay(Box::new(relay))
@@ -201,7 +197,7 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
         relay
             .connect(&room_id, pw_ref)
             .await
-            .map_err(|e| io::Error::other(format!("Connection failed: {e}")))?;
+            .map_err(crate::error::context("Connection failed"))?;
         if !relay.peer_present() {
             if !json {
                 output::color::info("Waiting for peer...");
@@ -209,7 +205,7 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
             relay
                 .wait_for_peer()
                 .await
-                .map_err(|e| io::Error::other(format!("Waiting for peer failed: {e}")))?;
+                .map_err(crate::error::context("Waiting for peer failed"))?;
         }
 
         tallow_net::transport::ConnectionResult::Relay(Box::new(relay))
@@ -346,10 +342,10 @@ pub async fn execute(args: ChatArgs, json: bool) -> io::Result<()> {
                 }
             }
             recv_result = channel.receive_message(&mut recv_buf) => {
-                let n = recv_result.map_err(|e| io::Error::other(format!("recv: {e}")))?;
+                let n = recv_result.map_err(crate::error::context("recv"))?;
                 let mut decode_buf = BytesMut::from(&recv_buf[..n]);
                 let msg = codec.decode_msg(&mut decode_buf)
-                    .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+                    .map_err(crate::error::context("decode"))?;
 
                 match msg {
                     Some(Message::ChatText { ciphertext, nonce, .. }) => {
@@ -420,15 +416,15 @@ async fn sender_handshake(
     // Step 1: Send HandshakeInit
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("Handshake init failed: {e}")))?;
+        .map_err(crate::error::context("Handshake init failed"))?;
     encode_buf.clear();
     codec
         .encode_msg(&init_msg, encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode HandshakeInit: {e}")))?;
+        .map_err(crate::error::context("Encode HandshakeInit"))?;
     channel
         .send_message(encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send HandshakeInit: {e}")))?;
+        .map_err(crate::error::context("Send HandshakeInit"))?;
 
     // Step 2: Receive HandshakeResponse
     let n = tokio::time::timeout(
@@ -437,12 +433,12 @@ async fn sender_handshake(
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for response"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeResponse: {e}")))?;
+    .map_err(crate::error::context("Receive HandshakeResponse"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let resp_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeResponse: {e}")))?;
+        .map_err(crate::error::context("Decode HandshakeResponse"))?;
 
     match resp_msg {
         Some(Message::HandshakeResponse {
@@ -451,21 +447,21 @@ async fn sender_handshake(
             kem_public_key,
             nonce,
         }) => {
-            // Step 3: Process response -> HandshakeKem + session key
-            let (kem_msg, session_key) = handshake
+            // Step 3: Process response -> HandshakeKem
+            let kem_msg = handshake
                 .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake response processing failed: {e}"))
-                })?;
+                .map_err(crate::error::context(
+                    "Handshake response processing failed",
+                ))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&kem_msg, encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeKem: {e}")))?;
+                .map_err(crate::error::context("Encode HandshakeKem"))?;
             channel
                 .send_message(encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeKem: {e}")))?;
+                .map_err(crate::error::context("Send HandshakeKem"))?;
 
             // Step 4: Receive HandshakeComplete
             let n = tokio::time::timeout(
@@ -474,33 +470,31 @@ async fn sender_handshake(
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for confirmation"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeComplete: {e}")))?;
+            .map_err(crate::error::context("Receive HandshakeComplete"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let complete_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeComplete: {e}")))?;
+                .map_err(crate::error::context("Decode HandshakeComplete"))?;
 
             match complete_msg {
-                Some(Message::HandshakeComplete { confirmation }) => {
-                    handshake
-                        .verify_receiver_confirmation(&confirmation)
-                        .map_err(|e| io::Error::other(format!("Key confirmation failed: {e}")))?;
-                }
+                Some(Message::HandshakeComplete { confirmation }) => handshake
+                    .verify_receiver_confirmation(&confirmation)
+                    .map_err(crate::error::context("Key confirmation failed")),
                 other => {
                     channel.close().await;
-                    return Err(io::Error::other(format!(
+                    Err(io::Error::other(format!(
                         "Expected HandshakeComplete, got: {:?}",
                         other
-                    )));
+                    )))
                 }
             }
-
-            Ok(session_key)
         }
         Some(Message::HandshakeFailed { reason }) => {
             channel.close().await;
-            Err(io::Error::other(format!("Handshake failed: {reason}")))
+            Err(crate::error::context("Peer aborted the handshake")(
+                tallow_protocol::ProtocolError::HandshakeFailed(reason),
+            ))
         }
         other => {
             channel.close().await;
@@ -530,12 +524,12 @@ async fn receiver_handshake(
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for init"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeInit: {e}")))?;
+    .map_err(crate::error::context("Receive HandshakeInit"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let init_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeInit: {e}")))?;
+        .map_err(crate::error::context("Decode HandshakeInit"))?;
 
     match init_msg {
         Some(Message::HandshakeInit {
@@ -547,16 +541,16 @@ async fn receiver_handshake(
             // Step 2: Process init -> send HandshakeResponse
             let resp = handshake
                 .process_init(protocol_version, &kem_capabilities, &cpace_public, &nonce)
-                .map_err(|e| io::Error::other(format!("Handshake init processing failed: {e}")))?;
+                .map_err(crate::error::context("Handshake init processing failed"))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&resp, encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeResponse: {e}")))?;
+                .map_err(crate::error::context("Encode HandshakeResponse"))?;
             channel
                 .send_message(encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeResponse: {e}")))?;
+                .map_err(crate::error::context("Send HandshakeResponse"))?;
 
             // Step 3: Receive HandshakeKem
             let n = tokio::time::timeout(
@@ -565,12 +559,12 @@ async fn receiver_handshake(
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for KEM"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeKem: {e}")))?;
+            .map_err(crate::error::context("Receive HandshakeKem"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let kem_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeKem: {e}")))?;
+                .map_err(crate::error::context("Decode HandshakeKem"))?;
 
             match kem_msg {
                 Some(Message::HandshakeKem {
@@ -579,17 +573,17 @@ async fn receiver_handshake(
                 }) => {
                     let (complete_msg, session_key) = handshake
                         .process_kem(&kem_ciphertext, &confirmation)
-                        .map_err(|e| io::Error::other(format!("Handshake KEM failed: {e}")))?;
+                        .map_err(crate::error::context("Handshake KEM failed"))?;
 
                     // Step 4: Send HandshakeComplete
                     encode_buf.clear();
                     codec
                         .encode_msg(&complete_msg, encode_buf)
-                        .map_err(|e| io::Error::other(format!("Encode HandshakeComplete: {e}")))?;
+                        .map_err(crate::error::context("Encode HandshakeComplete"))?;
                     channel
                         .send_message(encode_buf)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send HandshakeComplete: {e}")))?;
+                        .map_err(crate::error::context("Send HandshakeComplete"))?;
 
                     Ok(session_key)
                 }
@@ -604,7 +598,9 @@ async fn receiver_handshake(
         }
         Some(Message::HandshakeFailed { reason }) => {
             channel.close().await;
-            Err(io::Error::other(format!("Handshake failed: {reason}")))
+            Err(crate::error::context("Peer aborted the handshake")(
+                tallow_protocol::ProtocolError::HandshakeFailed(reason),
+            ))
         }
         other => {
             channel.close().await;
@@ -634,17 +630,7 @@ async fn execute_multi(
     proxy_config: Option<tallow_net::privacy::ProxyConfig>,
 ) -> io::Result<()> {
     // Hash relay password for authentication
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
-
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
 
     // Build the RoomJoinMulti payload (postcard-serialized)
     let join_msg = Message::RoomJoinMulti {
@@ -667,7 +653,7 @@ async fn execute_multi(
 
         let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
             .await
-            .map_err(|e| io::Error::other(format!("Relay resolution failed: {e}")))?;
+            .map_err(crate::error::context("Relay resolution failed"))?;
 
         match resolved {
             tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -687,7 +673,7 @@ async fn execute_multi(
     let response_bytes = relay
         .connect_raw(&join_payload)
         .await
-        .map_err(|e| io::Error::other(format!("Connection failed: {e}")))?;
+        .map_err(crate::error::context("Connection failed"))?;
 
     // Parse RoomJoinedMulti from response
     let joined: Message = postcard::from_bytes(&response_bytes)
@@ -773,7 +759,7 @@ async fn execute_multi(
 
         sessions
             .add_session(session_key.as_bytes(), peer_id)
-            .map_err(|e| io::Error::other(format!("Key derivation failed: {e}")))?;
+            .map_err(crate::error::context("Key derivation failed"))?;
 
         if json {
             println!(
@@ -847,7 +833,7 @@ async fn execute_multi(
                                 text.as_bytes(),
                                 b"tallow-chat-v1",
                             )
-                            .map_err(|e| io::Error::other(format!("encrypt: {e}")))?;
+                            .map_err(crate::error::context("encrypt"))?;
 
                             let chat_msg = Message::ChatText {
                                 message_id,
@@ -895,10 +881,10 @@ async fn execute_multi(
                 }
             }
             recv_result = channel.receive_message(&mut recv_buf) => {
-                let n = recv_result.map_err(|e| io::Error::other(format!("recv: {e}")))?;
+                let n = recv_result.map_err(crate::error::context("recv"))?;
                 let mut decode_buf = BytesMut::from(&recv_buf[..n]);
                 let msg = codec.decode_msg(&mut decode_buf)
-                    .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+                    .map_err(crate::error::context("decode"))?;
 
                 match msg {
                     Some(Message::Targeted { from_peer, payload, .. }) => {
@@ -925,7 +911,7 @@ async fn execute_multi(
                                 &mut codec, &mut encode_buf, &mut recv_buf, &mut channel,
                             ).await?;
                             sessions.add_session(session_key.as_bytes(), peer_id)
-                                .map_err(|e| io::Error::other(format!("Key derivation: {e}")))?;
+                                .map_err(crate::error::context("Key derivation"))?;
                             if json {
                                 println!("{}", serde_json::json!({
                                     "event": "peer_session_established",
@@ -1076,7 +1062,7 @@ async fn handle_targeted_message(
             .await?;
             sessions
                 .add_session(session_key.as_bytes(), from_peer)
-                .map_err(|e| io::Error::other(format!("Key derivation: {e}")))?;
+                .map_err(crate::error::context("Key derivation"))?;
             if json {
                 println!(
                     "{}",
@@ -1117,7 +1103,7 @@ async fn multi_sender_handshake(
     // Step 1: Send HandshakeInit -> targeted to their_peer_id
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("handshake init: {e}")))?;
+        .map_err(crate::error::context("handshake init"))?;
     let init_bytes = postcard::to_stdvec(&init_msg)
         .map_err(|e| io::Error::other(format!("encode init: {e}")))?;
     let targeted = Message::Targeted {
@@ -1135,12 +1121,12 @@ async fn multi_sender_handshake(
         )
         .await
         .map_err(|_| io::Error::other("handshake timeout waiting for response"))?
-        .map_err(|e| io::Error::other(format!("recv: {e}")))?;
+        .map_err(crate::error::context("recv"))?;
 
         let mut db = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut db)
-            .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+            .map_err(crate::error::context("decode"))?;
 
         if let Some(Message::Targeted {
             from_peer, payload, ..
@@ -1162,9 +1148,9 @@ async fn multi_sender_handshake(
     };
 
     // Step 3: Process response -> send HandshakeKem
-    let (kem_msg, session_key) = handshake
+    let kem_msg = handshake
         .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-        .map_err(|e| io::Error::other(format!("handshake response: {e}")))?;
+        .map_err(crate::error::context("handshake response"))?;
     let kem_bytes =
         postcard::to_stdvec(&kem_msg).map_err(|e| io::Error::other(format!("encode kem: {e}")))?;
     let targeted = Message::Targeted {
@@ -1182,12 +1168,12 @@ async fn multi_sender_handshake(
         )
         .await
         .map_err(|_| io::Error::other("handshake timeout waiting for confirmation"))?
-        .map_err(|e| io::Error::other(format!("recv: {e}")))?;
+        .map_err(crate::error::context("recv"))?;
 
         let mut db = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut db)
-            .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+            .map_err(crate::error::context("decode"))?;
 
         if let Some(Message::Targeted {
             from_peer, payload, ..
@@ -1205,9 +1191,7 @@ async fn multi_sender_handshake(
 
     handshake
         .verify_receiver_confirmation(&confirmation)
-        .map_err(|e| io::Error::other(format!("key confirmation: {e}")))?;
-
-    Ok(session_key)
+        .map_err(crate::error::context("key confirmation"))
 }
 
 /// Perform KEM handshake as receiver, routing via Targeted messages.
@@ -1235,12 +1219,12 @@ async fn multi_receiver_handshake(
         )
         .await
         .map_err(|_| io::Error::other("handshake timeout waiting for init"))?
-        .map_err(|e| io::Error::other(format!("recv: {e}")))?;
+        .map_err(crate::error::context("recv"))?;
 
         let mut db = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut db)
-            .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+            .map_err(crate::error::context("decode"))?;
 
         if let Some(Message::Targeted {
             from_peer, payload, ..
@@ -1263,7 +1247,7 @@ async fn multi_receiver_handshake(
     // Step 2: Process init -> send HandshakeResponse
     let resp = handshake
         .process_init(protocol_version, &kem_capabilities, &cpace_public, &nonce)
-        .map_err(|e| io::Error::other(format!("handshake init processing: {e}")))?;
+        .map_err(crate::error::context("handshake init processing"))?;
     let resp_bytes = postcard::to_stdvec(&resp)
         .map_err(|e| io::Error::other(format!("encode response: {e}")))?;
     let targeted = Message::Targeted {
@@ -1281,12 +1265,12 @@ async fn multi_receiver_handshake(
         )
         .await
         .map_err(|_| io::Error::other("handshake timeout waiting for KEM"))?
-        .map_err(|e| io::Error::other(format!("recv: {e}")))?;
+        .map_err(crate::error::context("recv"))?;
 
         let mut db = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut db)
-            .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+            .map_err(crate::error::context("decode"))?;
 
         if let Some(Message::Targeted {
             from_peer, payload, ..
@@ -1307,7 +1291,7 @@ async fn multi_receiver_handshake(
     // Step 4: Process KEM -> send HandshakeComplete
     let (complete_msg, session_key) = handshake
         .process_kem(&kem_ciphertext, &confirmation)
-        .map_err(|e| io::Error::other(format!("handshake KEM: {e}")))?;
+        .map_err(crate::error::context("handshake KEM"))?;
     let complete_bytes = postcard::to_stdvec(&complete_msg)
         .map_err(|e| io::Error::other(format!("encode complete: {e}")))?;
     let targeted = Message::Targeted {
@@ -1344,7 +1328,7 @@ async fn handle_incoming_handshake(
     // Step 2: Process init (we already have the data) -> send HandshakeResponse
     let resp = handshake
         .process_init(protocol_version, kem_capabilities, cpace_public, nonce)
-        .map_err(|e| io::Error::other(format!("handshake init processing: {e}")))?;
+        .map_err(crate::error::context("handshake init processing"))?;
     let resp_bytes = postcard::to_stdvec(&resp)
         .map_err(|e| io::Error::other(format!("encode response: {e}")))?;
     let targeted = Message::Targeted {
@@ -1362,12 +1346,12 @@ async fn handle_incoming_handshake(
         )
         .await
         .map_err(|_| io::Error::other("handshake timeout waiting for KEM"))?
-        .map_err(|e| io::Error::other(format!("recv: {e}")))?;
+        .map_err(crate::error::context("recv"))?;
 
         let mut db = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut db)
-            .map_err(|e| io::Error::other(format!("decode: {e}")))?;
+            .map_err(crate::error::context("decode"))?;
 
         if let Some(Message::Targeted {
             from_peer: sender,
@@ -1390,7 +1374,7 @@ async fn handle_incoming_handshake(
     // Step 4: Process KEM -> send HandshakeComplete
     let (complete_msg, session_key) = handshake
         .process_kem(&kem_ciphertext, &confirmation)
-        .map_err(|e| io::Error::other(format!("handshake KEM: {e}")))?;
+        .map_err(crate::error::context("handshake KEM"))?;
     let complete_bytes = postcard::to_stdvec(&complete_msg)
         .map_err(|e| io::Error::other(format!("encode complete: {e}")))?;
     let targeted = Message::Targeted {
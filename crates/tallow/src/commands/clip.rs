@@ -124,6 +124,15 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
                 "Custom code must be at least 4 characters for security",
             ));
         }
+        if !json {
+            let strength = tallow_crypto::kdf::estimate_strength(custom_code);
+            if strength.score < 3 {
+                output::color::warning(
+                    "Weak custom code -- security depends on code phrase entropy",
+                );
+                output::color::password_strength(&strength);
+            }
+        }
         custom_code.clone()
     } else {
         tallow_protocol::room::code::generate_code_phrase(
@@ -166,7 +175,7 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     // Resolve relay address (proxy-aware: avoids DNS leaks)
     let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
+        .map_err(crate::error::context("Relay resolution failed"))?;
 
     let mut relay = match resolved {
         tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -190,15 +199,12 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
         output::color::info(&format!("Connecting to relay {}...", args.relay));
     }
 
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
 
     let peer_present = relay
         .connect(&room_id, password_hash.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay connection failed: {}", e)))?;
+        .map_err(crate::error::context("Relay connection failed"))?;
 
     if !peer_present {
         if !json {
@@ -207,7 +213,7 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
         relay
             .wait_for_peer()
             .await
-            .map_err(|e| io::Error::other(format!("Wait for peer failed: {}", e)))?;
+            .map_err(crate::error::context("Wait for peer failed"))?;
     }
 
     if !json {
@@ -224,15 +230,15 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     // Step 1: Send HandshakeInit
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("Handshake init failed: {}", e)))?;
+        .map_err(crate::error::context("Handshake init failed"))?;
     encode_buf.clear();
     codec
         .encode_msg(&init_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Encode HandshakeInit"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Send HandshakeInit"))?;
 
     // Step 2: Receive HandshakeResponse
     let n = tokio::time::timeout(
@@ -241,12 +247,12 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for response"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeResponse: {}", e)))?;
+    .map_err(crate::error::context("Receive HandshakeResponse"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let resp_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeResponse: {}", e)))?;
+        .map_err(crate::error::context("Decode HandshakeResponse"))?;
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -257,21 +263,21 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
             kem_public_key,
             nonce,
         }) => {
-            // Step 3: Process response -> HandshakeKem + session key
-            let (kem_msg, session_key_result) = handshake
+            // Step 3: Process response -> HandshakeKem
+            let kem_msg = handshake
                 .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake response processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context(
+                    "Handshake response processing failed",
+                ))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&kem_msg, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeKem"))?;
             relay
                 .forward(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeKem"))?;
 
             // Step 4: Receive HandshakeComplete
             let n = tokio::time::timeout(
@@ -280,19 +286,17 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for confirmation"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeComplete: {}", e)))?;
+            .map_err(crate::error::context("Receive HandshakeComplete"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let complete_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeComplete: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeComplete"))?;
 
-            match complete_msg {
-                Some(Message::HandshakeComplete { confirmation }) => {
-                    handshake
-                        .verify_receiver_confirmation(&confirmation)
-                        .map_err(|e| io::Error::other(format!("Key confirmation failed: {}", e)))?;
-                }
+            session_key = match complete_msg {
+                Some(Message::HandshakeComplete { confirmation }) => handshake
+                    .verify_receiver_confirmation(&confirmation)
+                    .map_err(crate::error::context("Key confirmation failed"))?,
                 other => {
                     relay.close().await;
                     return Err(io::Error::other(format!(
@@ -300,9 +304,7 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
                         other
                     )));
                 }
-            }
-
-            session_key = session_key_result;
+            };
         }
         Some(Message::FileOffer { .. }) => {
             relay.close().await;
@@ -341,7 +343,7 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     let offer_messages = pipeline
         .prepare_text(&payload)
         .await
-        .map_err(|e| io::Error::other(format!("Failed to prepare clipboard data: {}", e)))?;
+        .map_err(crate::error::context("Failed to prepare clipboard data"))?;
 
     let total_size = pipeline.manifest().total_size;
 
@@ -349,22 +351,22 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
         encode_buf.clear();
         codec
             .encode_msg(msg, &mut encode_buf)
-            .map_err(|e| io::Error::other(format!("Encode FileOffer failed: {}", e)))?;
+            .map_err(crate::error::context("Encode FileOffer failed"))?;
         relay
             .forward(&encode_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Send FileOffer failed: {}", e)))?;
+            .map_err(crate::error::context("Send FileOffer failed"))?;
     }
 
     let n = relay
         .receive(&mut recv_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Receive FileAccept failed: {}", e)))?;
+        .map_err(crate::error::context("Receive FileAccept failed"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let response = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode response failed: {}", e)))?;
+        .map_err(crate::error::context("Decode response failed"))?;
 
     match response {
         Some(Message::FileAccept { .. }) => {
@@ -394,27 +396,27 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     let chunk_messages = pipeline
         .chunk_data(&payload, 0)
         .await
-        .map_err(|e| io::Error::other(format!("Failed to chunk clipboard data: {}", e)))?;
+        .map_err(crate::error::context("Failed to chunk clipboard data"))?;
 
     for chunk_msg in &chunk_messages {
         encode_buf.clear();
         codec
             .encode_msg(chunk_msg, &mut encode_buf)
-            .map_err(|e| io::Error::other(format!("Encode chunk failed: {}", e)))?;
+            .map_err(crate::error::context("Encode chunk failed"))?;
         relay
             .forward(&encode_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Send chunk failed: {}", e)))?;
+            .map_err(crate::error::context("Send chunk failed"))?;
 
         let n = relay
             .receive(&mut recv_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Receive ack failed: {}", e)))?;
+            .map_err(crate::error::context("Receive ack failed"))?;
 
         let mut ack_buf = BytesMut::from(&recv_buf[..n]);
         let ack = codec
             .decode_msg(&mut ack_buf)
-            .map_err(|e| io::Error::other(format!("Decode ack failed: {}", e)))?;
+            .map_err(crate::error::context("Decode ack failed"))?;
 
         match ack {
             Some(Message::Ack { .. }) => {
@@ -451,11 +453,11 @@ async fn execute_send(args: &ClipArgs, json: bool) -> io::Result<()> {
     encode_buf.clear();
     codec
         .encode_msg(&complete_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode complete failed: {}", e)))?;
+        .map_err(crate::error::context("Encode complete failed"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send complete failed: {}", e)))?;
+        .map_err(crate::error::context("Send complete failed"))?;
 
     relay.close().await;
 
@@ -531,7 +533,7 @@ async fn execute_receive(
     // Resolve relay address (proxy-aware: avoids DNS leaks)
     let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
+        .map_err(crate::error::context("Relay resolution failed"))?;
 
     let mut relay = match resolved {
         tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -555,15 +557,12 @@ async fn execute_receive(
         output::color::info(&format!("Connecting to relay {}...", args.relay));
     }
 
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
 
     let peer_present = relay
         .connect(&room_id, password_hash.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay connection failed: {}", e)))?;
+        .map_err(crate::error::context("Relay connection failed"))?;
 
     if !peer_present {
         if !json {
@@ -572,7 +571,7 @@ async fn execute_receive(
         relay
             .wait_for_peer()
             .await
-            .map_err(|e| io::Error::other(format!("Wait for peer failed: {}", e)))?;
+            .map_err(crate::error::context("Wait for peer failed"))?;
     }
 
     if !json {
@@ -593,12 +592,12 @@ async fn execute_receive(
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for init"))?
-    .map_err(|e| io::Error::other(format!("Receive handshake: {}", e)))?;
+    .map_err(crate::error::context("Receive handshake"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let init_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode handshake: {}", e)))?;
+        .map_err(crate::error::context("Decode handshake"))?;
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -612,18 +611,16 @@ async fn execute_receive(
             // Step 2: Process init -> send HandshakeResponse
             let resp = handshake
                 .process_init(protocol_version, &kem_capabilities, &cpace_public, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake init processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context("Handshake init processing failed"))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&resp, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeResponse: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeResponse"))?;
             relay
                 .forward(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeResponse: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeResponse"))?;
 
             // Step 3: Receive HandshakeKem
             let n = tokio::time::timeout(
@@ -632,12 +629,12 @@ async fn execute_receive(
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for KEM"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeKem: {}", e)))?;
+            .map_err(crate::error::context("Receive HandshakeKem"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let kem_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeKem"))?;
 
             match kem_msg {
                 Some(Message::HandshakeKem {
@@ -646,19 +643,17 @@ async fn execute_receive(
                 }) => {
                     let (complete_msg, session_key_result) = handshake
                         .process_kem(&kem_ciphertext, &confirmation)
-                        .map_err(|e| io::Error::other(format!("Handshake KEM failed: {}", e)))?;
+                        .map_err(crate::error::context("Handshake KEM failed"))?;
 
                     // Step 4: Send HandshakeComplete
                     encode_buf.clear();
                     codec
                         .encode_msg(&complete_msg, &mut encode_buf)
-                        .map_err(|e| {
-                            io::Error::other(format!("Encode HandshakeComplete: {}", e))
-                        })?;
+                        .map_err(crate::error::context("Encode HandshakeComplete"))?;
                     relay
                         .forward(&encode_buf)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send HandshakeComplete: {}", e)))?;
+                        .map_err(crate::error::context("Send HandshakeComplete"))?;
 
                     session_key = session_key_result;
                 }
@@ -696,12 +691,12 @@ async fn execute_receive(
     let n = relay
         .receive(&mut recv_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Receive offer failed: {}", e)))?;
+        .map_err(crate::error::context("Receive offer failed"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let offer_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode offer failed: {}", e)))?;
+        .map_err(crate::error::context("Decode offer failed"))?;
 
     let (transfer_id, manifest_bytes) = match offer_msg {
         Some(Message::FileOffer {
@@ -731,7 +726,7 @@ async fn execute_receive(
 
     let manifest = pipeline
         .process_offer(&manifest_bytes)
-        .map_err(|e| io::Error::other(format!("Process offer failed: {}", e)))?;
+        .map_err(crate::error::context("Process offer failed"))?;
 
     let total_size = manifest.total_size;
 
@@ -771,11 +766,11 @@ async fn execute_receive(
         encode_buf.clear();
         codec
             .encode_msg(&reject_msg, &mut encode_buf)
-            .map_err(|e| io::Error::other(format!("Encode reject failed: {}", e)))?;
+            .map_err(crate::error::context("Encode reject failed"))?;
         relay
             .forward(&encode_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Send reject failed: {}", e)))?;
+            .map_err(crate::error::context("Send reject failed"))?;
         relay.close().await;
         if !json {
             output::color::info("Clipboard transfer declined.");
@@ -788,11 +783,11 @@ async fn execute_receive(
     encode_buf.clear();
     codec
         .encode_msg(&accept_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode accept failed: {}", e)))?;
+        .map_err(crate::error::context("Encode accept failed"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send accept failed: {}", e)))?;
+        .map_err(crate::error::context("Send accept failed"))?;
 
     // Receive chunks
     let transfer_start = std::time::Instant::now();
@@ -803,31 +798,34 @@ async fn execute_receive(
         let n = relay
             .receive(&mut recv_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Receive chunk failed: {}", e)))?;
+            .map_err(crate::error::context("Receive chunk failed"))?;
 
         let mut chunk_buf = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut chunk_buf)
-            .map_err(|e| io::Error::other(format!("Decode chunk failed: {}", e)))?;
+            .map_err(crate::error::context("Decode chunk failed"))?;
 
         match msg {
             Some(Message::Chunk {
                 index, total, data, ..
             }) => {
                 let chunk_size = data.len() as u64;
-                let ack = pipeline.process_chunk(index, &data, total).map_err(|e| {
-                    io::Error::other(format!("Process chunk {} failed: {}", index, e))
-                })?;
+                let ack =
+                    pipeline
+                        .process_chunk(index, &data, total)
+                        .map_err(crate::error::context(format!(
+                            "Process chunk {index} failed"
+                        )))?;
 
                 if let Some(ack_msg) = ack {
                     encode_buf.clear();
                     codec
                         .encode_msg(&ack_msg, &mut encode_buf)
-                        .map_err(|e| io::Error::other(format!("Encode ack failed: {}", e)))?;
+                        .map_err(crate::error::context("Encode ack failed"))?;
                     relay
                         .forward(&encode_buf)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send ack failed: {}", e)))?;
+                        .map_err(crate::error::context("Send ack failed"))?;
                 }
 
                 bytes_received += chunk_size;
@@ -858,15 +856,15 @@ async fn execute_receive(
     let _written_files = pipeline
         .finalize()
         .await
-        .map_err(|e| io::Error::other(format!("Finalize failed: {}", e)))?;
+        .map_err(crate::error::context("Finalize failed"))?;
 
     relay.close().await;
 
     // Read the received data
     let text_path = output_dir.join("_tallow_text_");
     if text_path.exists() {
-        let content = std::fs::read(&text_path)
-            .map_err(|e| io::Error::other(format!("Read received data: {}", e)))?;
+        let content =
+            std::fs::read(&text_path).map_err(crate::error::context("Read received data"))?;
 
         // Detect content type
         let img_format = detect::detect_image_format(&content);
@@ -1045,7 +1043,7 @@ async fn execute_watch(_args: &ClipArgs, debounce_secs: u64, json: bool) -> io::
 /// Show clipboard history
 fn execute_history(count: Option<usize>, search: Option<String>, json: bool) -> io::Result<()> {
     let history = ClipboardHistory::open()
-        .map_err(|e| io::Error::other(format!("Failed to open clipboard history: {}", e)))?;
+        .map_err(crate::error::context("Failed to open clipboard history"))?;
 
     if history.is_empty() {
         if json {
@@ -1104,12 +1102,12 @@ fn execute_history(count: Option<usize>, search: Option<String>, json: bool) ->
 /// Clear clipboard history
 fn execute_clear(json: bool) -> io::Result<()> {
     let mut history = ClipboardHistory::open()
-        .map_err(|e| io::Error::other(format!("Failed to open clipboard history: {}", e)))?;
+        .map_err(crate::error::context("Failed to open clipboard history"))?;
 
     let count = history.len();
     history
         .clear()
-        .map_err(|e| io::Error::other(format!("Failed to clear history: {}", e)))?;
+        .map_err(crate::error::context("Failed to clear history"))?;
 
     if json {
         println!(
@@ -1182,9 +1180,10 @@ fn log_to_history(
         blake3_hash: hash,
         image_path,
         text_content,
+        pinned: false,
     };
 
-    if let Ok(mut history) = ClipboardHistory::open() {
+    if let Ok(mut history) = ClipboardHistory::open_with_config() {
         if let Err(e) = history.append(entry) {
             tracing::warn!("Failed to log to clipboard history: {}", e);
         }
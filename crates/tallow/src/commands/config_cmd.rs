@@ -2,6 +2,7 @@
 
 use crate::cli::{AliasCommands, ConfigArgs, ConfigCommands};
 use std::io;
+use std::path::PathBuf;
 
 /// Execute config command
 pub async fn execute(args: ConfigArgs, json: bool) -> io::Result<()> {
@@ -10,6 +11,7 @@ pub async fn execute(args: ConfigArgs, json: bool) -> io::Result<()> {
         Some(ConfigCommands::Get { key }) => config_get(&key, json),
         Some(ConfigCommands::Set { key, value }) => config_set(&key, &value, json),
         Some(ConfigCommands::List) => config_list(json),
+        Some(ConfigCommands::Validate { path }) => config_validate(path, json),
         Some(ConfigCommands::Edit) => config_edit(json),
         Some(ConfigCommands::Reset { yes }) => config_reset(yes, json),
         Some(ConfigCommands::Alias { command }) => config_alias(command, json),
@@ -18,7 +20,7 @@ pub async fn execute(args: ConfigArgs, json: bool) -> io::Result<()> {
 
 fn config_show(json: bool) -> io::Result<()> {
     let config = tallow_store::config::load_config()
-        .map_err(|e| io::Error::other(format!("Failed to load config: {}", e)))?;
+        .map_err(crate::error::context("Failed to load config"))?;
 
     if json {
         let json_val = serde_json::to_value(&config)
@@ -40,8 +42,7 @@ fn config_show(json: bool) -> io::Result<()> {
 }
 
 fn config_get(key: &str, json: bool) -> io::Result<()> {
-    let config =
-        tallow_store::config::load_config().map_err(|e| io::Error::other(format!("{}", e)))?;
+    let config = tallow_store::config::load_config().map_err(crate::error::classified)?;
 
     let value = tallow_store::config::get_config_value(&config, key)
         .map_err(|e| io::Error::new(io::ErrorKind::NotFound, format!("{}", e)))?;
@@ -56,8 +57,7 @@ fn config_get(key: &str, json: bool) -> io::Result<()> {
 }
 
 fn config_set(key: &str, value: &str, json: bool) -> io::Result<()> {
-    let mut config =
-        tallow_store::config::load_config().map_err(|e| io::Error::other(format!("{}", e)))?;
+    let mut config = tallow_store::config::load_config().map_err(crate::error::classified)?;
 
     tallow_store::config::set_config_value(&mut config, key, value)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
@@ -77,8 +77,7 @@ fn config_set(key: &str, value: &str, json: bool) -> io::Result<()> {
 }
 
 fn config_list(json: bool) -> io::Result<()> {
-    let config =
-        tallow_store::config::load_config().map_err(|e| io::Error::other(format!("{}", e)))?;
+    let config = tallow_store::config::load_config().map_err(crate::error::classified)?;
 
     // List all keys by serializing to TOML and walking the structure
     let toml_val =
@@ -105,6 +104,39 @@ fn config_list(json: bool) -> io::Result<()> {
     Ok(())
 }
 
+fn config_validate(path: Option<PathBuf>, json: bool) -> io::Result<()> {
+    let path = path.unwrap_or_else(tallow_store::config::config_path);
+    let issues = tallow_store::config::validate_config_file(&path)
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, format!("{}", e)))?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "path": path.display().to_string(),
+                "valid": issues.is_empty(),
+                "problems": issues,
+            }))
+            .unwrap_or_default()
+        );
+    } else if issues.is_empty() {
+        crate::output::color::success(&format!("{} is valid", path.display()));
+    } else {
+        for issue in &issues {
+            crate::output::color::error(&format!("{}: {}", path.display(), issue));
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("config has {} problem(s)", issues.len()),
+        ))
+    }
+}
+
 fn config_edit(_json: bool) -> io::Result<()> {
     let path = tallow_store::config::config_path();
 
@@ -160,8 +192,8 @@ fn config_reset(yes: bool, json: bool) -> io::Result<()> {
 fn config_alias(command: AliasCommands, json: bool) -> io::Result<()> {
     match command {
         AliasCommands::Add { name, path } => {
-            let mut config = tallow_store::config::load_config()
-                .map_err(|e| io::Error::other(format!("{}", e)))?;
+            let mut config =
+                tallow_store::config::load_config().map_err(crate::error::classified)?;
             tallow_store::config::aliases::add_alias(&mut config.aliases, &name, &path)
                 .map_err(|e| io::Error::other(format!("{}", e)))?;
             tallow_store::config::save_config(&config)
@@ -180,8 +212,8 @@ fn config_alias(command: AliasCommands, json: bool) -> io::Result<()> {
             }
         }
         AliasCommands::Remove { name } => {
-            let mut config = tallow_store::config::load_config()
-                .map_err(|e| io::Error::other(format!("{}", e)))?;
+            let mut config =
+                tallow_store::config::load_config().map_err(crate::error::classified)?;
             if tallow_store::config::aliases::remove_alias(&mut config.aliases, &name) {
                 tallow_store::config::save_config(&config)
                     .map_err(|e| io::Error::other(format!("{}", e)))?;
@@ -203,8 +235,7 @@ fn config_alias(command: AliasCommands, json: bool) -> io::Result<()> {
             }
         }
         AliasCommands::List => {
-            let config = tallow_store::config::load_config()
-                .map_err(|e| io::Error::other(format!("{}", e)))?;
+            let config = tallow_store::config::load_config().map_err(crate::error::classified)?;
             let aliases = tallow_store::config::aliases::list_aliases(&config.aliases);
             if json {
                 let list: Vec<serde_json::Value> = aliases
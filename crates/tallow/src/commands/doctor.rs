@@ -1,9 +1,17 @@
 //! Doctor command for system diagnostics
 
+use crate::cli::{DoctorArgs, DoctorDetail};
+use std::fmt::Write as _;
 use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tallow_net::nat::{NatType, StunClient};
+
+/// How long each relay or STUN probe may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
 
 /// Execute doctor command
-pub async fn execute(json: bool) -> io::Result<()> {
+pub async fn execute(args: DoctorArgs, json: bool) -> io::Result<()> {
     let mut checks: Vec<DiagCheck> = Vec::new();
 
     // Platform info
@@ -33,18 +41,77 @@ pub async fn execute(json: bool) -> io::Result<()> {
     let dns_check = check_dns().await;
     checks.push(dns_check);
 
-    // Check 7: Relay reachability
-    let relay_check = check_relay().await;
-    checks.push(relay_check);
+    // Check 7: Network reachability (relays, STUN, NAT type)
+    let network_config = tallow_store::config::load_config()
+        .unwrap_or_default()
+        .network;
+    let network = probe_network(&network_config).await;
+    checks.extend(network.checks());
 
     // Check 8: Tor availability (optional)
     let tor_check = check_tor().await;
     checks.push(tor_check);
 
-    let all_passed = checks.iter().all(|c| c.passed);
+    let report = DoctorReport {
+        platform,
+        checks,
+        network,
+    };
+    let all_passed = report.all_passed();
+
+    print!("{}", report.format(args.detail, json));
+    if !json {
+        if all_passed {
+            crate::output::color::success("All checks passed");
+        } else {
+            crate::output::color::warning(&format!("{} check(s) failed", report.failed()));
+        }
+    }
+
+    if all_passed {
+        Ok(())
+    } else {
+        Err(io::Error::other("Some diagnostics failed"))
+    }
+}
+
+struct DiagCheck {
+    name: String,
+    passed: bool,
+    message: String,
+    fix: Option<String>,
+    /// Raw measurements behind the result, shown with `--detail debug`
+    details: Vec<(&'static str, String)>,
+}
+
+/// Everything `doctor` found
+struct DoctorReport {
+    platform: String,
+    checks: Vec<DiagCheck>,
+    network: NetworkReport,
+}
+
+impl DoctorReport {
+    fn all_passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    fn failed(&self) -> usize {
+        self.checks.iter().filter(|c| !c.passed).count()
+    }
 
-    if json {
-        let results: Vec<serde_json::Value> = checks
+    /// Report as printed: JSON ignores `detail` and always has everything
+    fn format(&self, detail: DoctorDetail, json: bool) -> String {
+        if json {
+            format!("{}\n", self.to_json())
+        } else {
+            self.render(detail)
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let results: Vec<serde_json::Value> = self
+            .checks
             .iter()
             .map(|c| {
                 serde_json::json!({
@@ -56,55 +123,77 @@ pub async fn execute(json: bool) -> io::Result<()> {
             })
             .collect();
 
-        println!(
-            "{}",
-            serde_json::json!({
-                "platform": platform,
-                "version": env!("CARGO_PKG_VERSION"),
-                "all_passed": all_passed,
-                "checks": results,
-            })
-        );
-    } else {
-        println!("Tallow System Diagnostics");
-        println!("=========================\n");
-        println!("Platform:  {}", platform);
-        println!("Version:   {}\n", env!("CARGO_PKG_VERSION"));
+        serde_json::json!({
+            "platform": self.platform,
+            "version": env!("CARGO_PKG_VERSION"),
+            "all_passed": self.all_passed(),
+            "checks": results,
+            "network": self.network.to_json(),
+        })
+    }
+
+    /// Human-readable report, without the final pass/fail line
+    fn render(&self, detail: DoctorDetail) -> String {
+        let mut out = String::new();
+        if detail == DoctorDetail::Summary {
+            let failed = self.failed();
+            let _ = writeln!(
+                out,
+                "{} of {} checks passed",
+                self.checks.len() - failed,
+                self.checks.len()
+            );
+            if failed > 0 {
+                let names: Vec<&str> = self
+                    .checks
+                    .iter()
+                    .filter(|c| !c.passed)
+                    .map(|c| c.name.as_str())
+                    .collect();
+                let _ = writeln!(out, "Failed: {}", names.join(", "));
+                let _ = writeln!(out, "Run `tallow doctor` for details");
+            }
+            return out;
+        }
+
+        let _ = writeln!(out, "Tallow System Diagnostics");
+        let _ = writeln!(out, "=========================\n");
+        let _ = writeln!(out, "Platform:  {}", self.platform);
+        let _ = writeln!(out, "Version:   {}", env!("CARGO_PKG_VERSION"));
+        if detail == DoctorDetail::Debug {
+            let _ = writeln!(
+                out,
+                "Protocol:  v{} (accepts v{} and later)",
+                tallow_protocol::wire::version::PROTOCOL_VERSION,
+                tallow_protocol::wire::version::MIN_PROTOCOL_VERSION
+            );
+        }
+        out.push('\n');
 
-        for check in &checks {
+        for check in &self.checks {
             let status = if check.passed { "OK" } else { "FAIL" };
             let icon = if check.passed { "+" } else { "!" };
-            println!("[{}] {}: {} — {}", icon, check.name, status, check.message);
+            let _ = writeln!(
+                out,
+                "[{}] {}: {} — {}",
+                icon, check.name, status, check.message
+            );
             if !check.passed {
                 if let Some(fix) = &check.fix {
-                    println!("    Fix: {}", fix);
+                    let _ = writeln!(out, "    Fix: {}", fix);
+                }
+            }
+            if detail == DoctorDetail::Debug {
+                for (key, value) in &check.details {
+                    let _ = writeln!(out, "    {}: {}", key, value);
                 }
             }
         }
-
-        println!();
-        if all_passed {
-            crate::output::color::success("All checks passed");
-        } else {
-            let failed = checks.iter().filter(|c| !c.passed).count();
-            crate::output::color::warning(&format!("{} check(s) failed", failed));
-        }
-    }
-
-    if all_passed {
-        Ok(())
-    } else {
-        Err(io::Error::other("Some diagnostics failed"))
+        out.push('\n');
+        out
     }
 }
 
-struct DiagCheck {
-    name: String,
-    passed: bool,
-    message: String,
-    fix: Option<String>,
-}
-
 fn check_identity() -> DiagCheck {
     let store = tallow_store::identity::IdentityStore::new();
     if store.exists() {
@@ -113,6 +202,7 @@ fn check_identity() -> DiagCheck {
             passed: true,
             message: "Identity keypair found".to_string(),
             fix: None,
+            details: Vec::new(),
         }
     } else {
         DiagCheck {
@@ -120,6 +210,7 @@ fn check_identity() -> DiagCheck {
             passed: false,
             message: "No identity keypair".to_string(),
             fix: Some("Run `tallow identity generate` to create one".to_string()),
+            details: Vec::new(),
         }
     }
 }
@@ -134,12 +225,14 @@ fn check_config() -> DiagCheck {
                 tallow_store::config::config_path().display()
             ),
             fix: None,
+            details: Vec::new(),
         },
         Err(e) => DiagCheck {
             name: "Config".to_string(),
             passed: false,
             message: format!("Failed to load: {}", e),
             fix: Some("Run `tallow config reset --yes` to recreate".to_string()),
+            details: Vec::new(),
         },
     }
 }
@@ -154,6 +247,7 @@ fn check_directories() -> DiagCheck {
                 tallow_store::persistence::config_dir().display()
             ),
             fix: None,
+            details: Vec::new(),
         },
         Err(e) => DiagCheck {
             name: "Storage".to_string(),
@@ -163,6 +257,7 @@ fn check_directories() -> DiagCheck {
                 "Create directory manually: mkdir -p {}",
                 tallow_store::persistence::config_dir().display()
             )),
+            details: Vec::new(),
         },
     }
 }
@@ -187,6 +282,7 @@ fn check_entropy() -> DiagCheck {
                 } else {
                     Some("Check /dev/urandom or system RNG".to_string())
                 },
+                details: Vec::new(),
             }
         }
         Err(e) => DiagCheck {
@@ -194,6 +290,7 @@ fn check_entropy() -> DiagCheck {
             passed: false,
             message: format!("Failed to get random bytes: {}", e),
             fix: Some("Ensure OS random number generator is available".to_string()),
+            details: Vec::new(),
         },
     }
 }
@@ -209,6 +306,7 @@ fn check_crypto() -> DiagCheck {
             passed: true,
             message: "BLAKE3, AES-256-GCM, ML-KEM available".to_string(),
             fix: None,
+            details: vec![("blake3 self-test", hex::encode(key))],
         }
     } else {
         DiagCheck {
@@ -216,20 +314,30 @@ fn check_crypto() -> DiagCheck {
             passed: false,
             message: "Crypto library returned unexpected output".to_string(),
             fix: Some("Reinstall tallow — crypto libraries may be corrupted".to_string()),
+            details: vec![("blake3 self-test", hex::encode(key))],
         }
     }
 }
 
 async fn check_dns() -> DiagCheck {
     // Try to resolve a well-known hostname
-    match tokio::net::lookup_host("dns.google:443").await {
-        Ok(mut addrs) => {
-            if addrs.next().is_some() {
+    let start = Instant::now();
+    let lookup = tokio::net::lookup_host("dns.google:443").await;
+    let mut details = vec![
+        ("query", "dns.google:443".to_string()),
+        ("lookup time", format_rtt(Some(start.elapsed()))),
+    ];
+    match lookup {
+        Ok(addrs) => {
+            let addrs: Vec<String> = addrs.map(|a| a.ip().to_string()).collect();
+            if !addrs.is_empty() {
+                details.push(("answers", addrs.join(", ")));
                 DiagCheck {
                     name: "DNS".to_string(),
                     passed: true,
                     message: "DNS resolution working".to_string(),
                     fix: None,
+                    details,
                 }
             } else {
                 DiagCheck {
@@ -237,15 +345,20 @@ async fn check_dns() -> DiagCheck {
                     passed: false,
                     message: "DNS returned no results".to_string(),
                     fix: Some("Check DNS configuration and network connectivity".to_string()),
+                    details,
                 }
             }
         }
-        Err(_) => DiagCheck {
-            name: "DNS".to_string(),
-            passed: false,
-            message: "DNS resolution failed".to_string(),
-            fix: Some("Check network connectivity and DNS settings".to_string()),
-        },
+        Err(e) => {
+            details.push(("error", e.to_string()));
+            DiagCheck {
+                name: "DNS".to_string(),
+                passed: false,
+                message: "DNS resolution failed".to_string(),
+                fix: Some("Check network connectivity and DNS settings".to_string()),
+                details,
+            }
+        }
     }
 }
 
@@ -256,6 +369,15 @@ async fn check_tor() -> DiagCheck {
         tokio::net::TcpStream::connect("127.0.0.1:9050"),
     )
     .await;
+    let probe = match &tor_available {
+        Ok(Ok(_)) => "connected".to_string(),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "timed out after 2 s".to_string(),
+    };
+    let details = vec![
+        ("probe", "TCP 127.0.0.1:9050".to_string()),
+        ("result", probe),
+    ];
 
     match tor_available {
         Ok(Ok(_)) => DiagCheck {
@@ -263,44 +385,527 @@ async fn check_tor() -> DiagCheck {
             passed: true,
             message: "Tor SOCKS port reachable at 127.0.0.1:9050".to_string(),
             fix: None,
+            details,
         },
         _ => DiagCheck {
             name: "Tor".to_string(),
             passed: true, // Not a failure -- Tor is optional
             message: "Tor not detected (optional -- use --proxy for custom SOCKS5)".to_string(),
             fix: None,
+            details,
         },
     }
 }
 
-async fn check_relay() -> DiagCheck {
-    // Try to connect to the default relay
-    let relay_addr = "129.146.114.5:4433";
-    match tokio::net::lookup_host(relay_addr).await {
-        Ok(mut addrs) => {
-            if addrs.next().is_some() {
+/// Reachability of one relay server
+#[derive(Debug, Clone)]
+struct RelayProbe {
+    addr: String,
+    /// Address the relay name resolved to
+    resolved: Option<SocketAddr>,
+    /// TCP connect time, if the relay accepted a connection
+    tcp_rtt: Option<Duration>,
+    /// QUIC handshake time, if the relay completed a handshake
+    quic_rtt: Option<Duration>,
+}
+
+impl RelayProbe {
+    fn reachable(&self) -> bool {
+        self.tcp_rtt.is_some() || self.quic_rtt.is_some()
+    }
+}
+
+/// Response of one STUN server
+#[derive(Debug, Clone)]
+struct StunProbe {
+    server: String,
+    /// Address the server name resolved to
+    resolved: Option<SocketAddr>,
+    rtt: Option<Duration>,
+    /// Our public address as the server saw it
+    mapped_addr: Option<SocketAddr>,
+}
+
+/// Which transports are likely to work from this network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConnectivityVerdict {
+    /// Peers on the same network can connect directly
+    direct_lan: bool,
+    /// Hole punching to peers elsewhere should succeed
+    direct_wan: bool,
+    /// At least one relay is reachable
+    relay: bool,
+}
+
+impl ConnectivityVerdict {
+    fn new(nat: NatType, has_lan: bool, relays: &[RelayProbe]) -> Self {
+        Self {
+            direct_lan: has_lan,
+            // Symmetric NATs map each destination to a new port, so the
+            // STUN-discovered address is useless to the peer
+            direct_wan: matches!(
+                nat,
+                NatType::None
+                    | NatType::FullCone
+                    | NatType::RestrictedCone
+                    | NatType::PortRestricted
+            ),
+            relay: relays.iter().any(RelayProbe::reachable),
+        }
+    }
+
+    fn summary(&self) -> &'static str {
+        match (self.direct_wan, self.relay, self.direct_lan) {
+            (true, true, _) => "direct WAN, with relay fallback",
+            (true, false, _) => "direct WAN only (no relay reachable)",
+            (false, true, _) => "relay-only (NAT prevents direct WAN connections)",
+            (false, false, true) => "direct LAN only",
+            (false, false, false) => "no transport is likely to work",
+        }
+    }
+}
+
+/// Results of the network probes
+#[derive(Debug)]
+struct NetworkReport {
+    relays: Vec<RelayProbe>,
+    stun: Vec<StunProbe>,
+    nat: NatType,
+    verdict: ConnectivityVerdict,
+}
+
+impl NetworkReport {
+    fn checks(&self) -> Vec<DiagCheck> {
+        let mut checks = Vec::new();
+        for relay in &self.relays {
+            let details = vec![
+                ("resolved", format_addr(relay.resolved)),
+                ("tcp connect", format_rtt_precise(relay.tcp_rtt)),
+                ("quic handshake", format_rtt_precise(relay.quic_rtt)),
+            ];
+            checks.push(if relay.reachable() {
                 DiagCheck {
                     name: "Relay".to_string(),
                     passed: true,
-                    message: format!("Relay {} is resolvable", relay_addr),
+                    message: format!(
+                        "{} reachable (TCP {}, QUIC {})",
+                        relay.addr,
+                        format_rtt(relay.tcp_rtt),
+                        format_rtt(relay.quic_rtt)
+                    ),
                     fix: None,
+                    details,
                 }
             } else {
                 DiagCheck {
                     name: "Relay".to_string(),
                     passed: false,
-                    message: format!("Relay {} resolved but no addresses", relay_addr),
-                    fix: Some("Try a different relay: tallow config set network.relay_servers [\"host:port\"]".to_string()),
+                    message: format!("{} unreachable over TCP and QUIC", relay.addr),
+                    fix: Some("Try a different relay: tallow config set network.relay_servers [\"host:port\"], or self-host with tallow-relay".to_string()),
+                    details,
                 }
-            }
+            });
         }
-        Err(_) => DiagCheck {
-            name: "Relay".to_string(),
-            passed: false,
-            message: format!("Cannot resolve relay {}", relay_addr),
-            fix: Some(
-                "Default relay may not be deployed yet. Self-host with: tallow-relay".to_string(),
+        for stun in &self.stun {
+            let details = vec![
+                ("resolved", format_addr(stun.resolved)),
+                ("binding response", format_rtt_precise(stun.rtt)),
+                ("mapped address", format_addr(stun.mapped_addr)),
+            ];
+            checks.push(match stun.mapped_addr {
+                Some(mapped) => DiagCheck {
+                    name: "STUN".to_string(),
+                    passed: true,
+                    message: format!(
+                        "{} answered in {} (public address {})",
+                        stun.server,
+                        format_rtt(stun.rtt),
+                        mapped
+                    ),
+                    fix: None,
+                    details,
+                },
+                None => DiagCheck {
+                    name: "STUN".to_string(),
+                    passed: false,
+                    message: format!("No response from {}", stun.server),
+                    fix: Some(
+                        "Outbound UDP may be blocked; direct connections will fall back to the relay"
+                            .to_string(),
+                    ),
+                    details,
+                },
+            });
+        }
+        checks.push(DiagCheck {
+            name: "NAT".to_string(),
+            passed: true, // Informational -- the verdict below says what it means
+            message: self.nat.to_string(),
+            fix: None,
+            details: Vec::new(),
+        });
+        let any = self.verdict.direct_lan || self.verdict.direct_wan || self.verdict.relay;
+        checks.push(DiagCheck {
+            name: "Connectivity".to_string(),
+            passed: any,
+            message: self.verdict.summary().to_string(),
+            fix: if any {
+                None
+            } else {
+                Some("Check your network connection and firewall".to_string())
+            },
+            details: vec![
+                ("direct_lan", self.verdict.direct_lan.to_string()),
+                ("direct_wan", self.verdict.direct_wan.to_string()),
+                ("relay", self.verdict.relay.to_string()),
+            ],
+        });
+        checks
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let ms = |rtt: Option<Duration>| rtt.map(|d| d.as_millis() as u64);
+        serde_json::json!({
+            "relays": self.relays.iter().map(|r| serde_json::json!({
+                "addr": r.addr,
+                "reachable": r.reachable(),
+                "tcp_rtt_ms": ms(r.tcp_rtt),
+                "quic_rtt_ms": ms(r.quic_rtt),
+            })).collect::<Vec<_>>(),
+            "stun": self.stun.iter().map(|s| serde_json::json!({
+                "server": s.server,
+                "reachable": s.mapped_addr.is_some(),
+                "rtt_ms": ms(s.rtt),
+                "mapped_addr": s.mapped_addr.map(|a| a.to_string()),
+            })).collect::<Vec<_>>(),
+            "nat_type": self.nat.to_string(),
+            "direct_lan": self.verdict.direct_lan,
+            "direct_wan": self.verdict.direct_wan,
+            "relay": self.verdict.relay,
+            "summary": self.verdict.summary(),
+        })
+    }
+}
+
+/// Round-trip time to the microsecond, for `--detail debug`
+fn format_rtt_precise(rtt: Option<Duration>) -> String {
+    match rtt {
+        Some(d) => format!("{} µs", d.as_micros()),
+        None => "no response".to_string(),
+    }
+}
+
+fn format_addr(addr: Option<SocketAddr>) -> String {
+    addr.map_or_else(|| "-".to_string(), |a| a.to_string())
+}
+
+fn format_rtt(rtt: Option<Duration>) -> String {
+    match rtt {
+        Some(d) => format!("{} ms", d.as_millis()),
+        None => "unreachable".to_string(),
+    }
+}
+
+/// Probe the configured relays and STUN servers and detect the NAT type
+async fn probe_network(config: &tallow_store::config::NetworkConfig) -> NetworkReport {
+    let relays = futures::future::join_all(
+        config
+            .relay_servers
+            .iter()
+            .map(|addr| probe_relay(addr, PROBE_TIMEOUT)),
+    );
+    let stun = futures::future::join_all(
+        config
+            .stun_servers
+            .iter()
+            .map(|server| probe_stun(server, PROBE_TIMEOUT)),
+    );
+    let nat = async {
+        tallow_net::nat::detect_cached()
+            .await
+            .unwrap_or(NatType::Unknown)
+    };
+    let (relays, stun, nat) = tokio::join!(relays, stun, nat);
+
+    let has_lan = tallow_net::nat::candidates::get_local_ip().is_ok_and(|ip| !ip.is_loopback());
+    let verdict = ConnectivityVerdict::new(nat, has_lan, &relays);
+    NetworkReport {
+        relays,
+        stun,
+        nat,
+        verdict,
+    }
+}
+
+async fn resolve(addr: &str) -> Option<SocketAddr> {
+    tokio::net::lookup_host(addr).await.ok()?.next()
+}
+
+/// Check TCP and QUIC reachability of a relay, timing each
+async fn probe_relay(addr: &str, limit: Duration) -> RelayProbe {
+    let resolved = resolve(addr).await;
+    let (tcp_rtt, quic_rtt) = match resolved {
+        Some(sock) => tokio::join!(probe_tcp(sock, limit), probe_quic(sock, limit)),
+        None => (None, None),
+    };
+    RelayProbe {
+        addr: addr.to_string(),
+        resolved,
+        tcp_rtt,
+        quic_rtt,
+    }
+}
+
+async fn probe_tcp(addr: SocketAddr, limit: Duration) -> Option<Duration> {
+    let start = Instant::now();
+    match tokio::time::timeout(limit, tokio::net::TcpStream::connect(addr)).await {
+        Ok(Ok(_)) => Some(start.elapsed()),
+        _ => None,
+    }
+}
+
+async fn probe_quic(addr: SocketAddr, limit: Duration) -> Option<Duration> {
+    use tallow_net::Transport;
+
+    let mut transport = tallow_net::transport::QuicTransport::new();
+    let start = Instant::now();
+    let connected = matches!(
+        tokio::time::timeout(limit, transport.connect(addr)).await,
+        Ok(Ok(()))
+    );
+    let rtt = start.elapsed();
+    transport.close().await;
+    connected.then_some(rtt)
+}
+
+/// Send a STUN binding request and time the response
+async fn probe_stun(server: &str, limit: Duration) -> StunProbe {
+    let mut probe = StunProbe {
+        server: server.to_string(),
+        resolved: resolve(server).await,
+        rtt: None,
+        mapped_addr: None,
+    };
+    if let Some(addr) = probe.resolved {
+        let start = Instant::now();
+        let client = StunClient::new(addr);
+        if let Ok(Ok(result)) = tokio::time::timeout(limit, client.discover_public_address()).await
+        {
+            probe.rtt = Some(start.elapsed());
+            probe.mapped_addr = Some(result.mapped_addr);
+        }
+    }
+    probe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_TIMEOUT: Duration = Duration::from_millis(300);
+
+    /// An address nothing is listening on
+    async fn closed_addr() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    /// Minimal STUN server answering one binding request with the sender's
+    /// address in XOR-MAPPED-ADDRESS
+    async fn mock_stun_server() -> SocketAddr {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 576];
+            let (n, from) = socket.recv_from(&mut buf).await.unwrap();
+            assert!(n >= 20);
+            let SocketAddr::V4(from) = from else {
+                unreachable!()
+            };
+            let cookie = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]);
+
+            let mut response = Vec::new();
+            response.extend_from_slice(&0x0101u16.to_be_bytes());
+            response.extend_from_slice(&12u16.to_be_bytes());
+            response.extend_from_slice(&buf[4..20]);
+            response.extend_from_slice(&0x0020u16.to_be_bytes());
+            response.extend_from_slice(&8u16.to_be_bytes());
+            response.extend_from_slice(&[0, 0x01]);
+            response.extend_from_slice(&(from.port() ^ (cookie >> 16) as u16).to_be_bytes());
+            response.extend_from_slice(&(u32::from(*from.ip()) ^ cookie).to_be_bytes());
+            socket.send_to(&response, from).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_relay_probe_reports_reachability() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let up = listener.local_addr().unwrap();
+        let down = closed_addr().await;
+
+        let reachable = probe_relay(&up.to_string(), TEST_TIMEOUT).await;
+        assert!(reachable.reachable());
+        assert!(reachable.tcp_rtt.is_some());
+        // Nothing speaks QUIC on the mock
+        assert!(reachable.quic_rtt.is_none());
+
+        let unreachable = probe_relay(&down.to_string(), TEST_TIMEOUT).await;
+        assert!(!unreachable.reachable());
+
+        let unresolvable = probe_relay("relay.invalid:4433", TEST_TIMEOUT).await;
+        assert!(!unresolvable.reachable());
+
+        let verdict =
+            ConnectivityVerdict::new(NatType::Symmetric, true, &[unreachable.clone(), reachable]);
+        assert!(verdict.relay);
+        let verdict = ConnectivityVerdict::new(NatType::Symmetric, true, &[unreachable]);
+        assert!(!verdict.relay);
+    }
+
+    #[tokio::test]
+    async fn test_stun_probe_reports_mapped_address() {
+        let server = mock_stun_server().await;
+        let probe = probe_stun(&server.to_string(), TEST_TIMEOUT).await;
+        let mapped = probe.mapped_addr.expect("mock STUN server answered");
+        assert_eq!(mapped.ip(), server.ip());
+        assert!(probe.rtt.is_some());
+
+        // A server that never answers
+        let silent = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let probe = probe_stun(&silent.local_addr().unwrap().to_string(), TEST_TIMEOUT).await;
+        assert!(probe.mapped_addr.is_none());
+        assert!(probe.rtt.is_none());
+    }
+
+    fn sample_report() -> DoctorReport {
+        let relay = RelayProbe {
+            addr: "relay.example:4433".to_string(),
+            resolved: Some("192.0.2.1:4433".parse().unwrap()),
+            tcp_rtt: Some(Duration::from_micros(21_500)),
+            quic_rtt: None,
+        };
+        let stun = StunProbe {
+            server: "stun.example:3478".to_string(),
+            resolved: None,
+            rtt: None,
+            mapped_addr: None,
+        };
+        let network = NetworkReport {
+            verdict: ConnectivityVerdict::new(
+                NatType::FullCone,
+                true,
+                std::slice::from_ref(&relay),
             ),
-        },
+            relays: vec![relay],
+            stun: vec![stun],
+            nat: NatType::FullCone,
+        };
+        let mut checks = vec![check_crypto()];
+        checks.extend(network.checks());
+        DoctorReport {
+            platform: "linux x86_64".to_string(),
+            checks,
+            network,
+        }
+    }
+
+    #[test]
+    fn test_detail_levels_add_information() {
+        let report = sample_report();
+        let summary = report.render(DoctorDetail::Summary);
+        let full = report.render(DoctorDetail::Full);
+        let debug = report.render(DoctorDetail::Debug);
+
+        // Counts and the failing check only
+        assert_eq!(
+            summary,
+            "4 of 5 checks passed\nFailed: STUN\nRun `tallow doctor` for details\n"
+        );
+
+        // Every check with its status, and the hint for the failure
+        for name in ["Crypto", "Relay", "STUN", "NAT", "Connectivity"] {
+            assert!(full.contains(&format!("] {}: ", name)), "{}", full);
+        }
+        assert!(full.contains("Fix: Outbound UDP may be blocked"));
+        assert!(!full.contains("µs"));
+        assert!(!full.contains("Protocol:"));
+
+        // Full output plus raw measurements
+        let full_lines: Vec<&str> = full.lines().collect();
+        let debug_lines: Vec<&str> = debug.lines().collect();
+        assert!(full_lines.iter().all(|line| debug_lines.contains(line)));
+        assert!(debug_lines.len() > full_lines.len() + 8);
+        assert!(debug.contains("tcp connect: 21500 µs"));
+        assert!(debug.contains("resolved: 192.0.2.1:4433"));
+        assert!(debug.contains("blake3 self-test: "));
+        assert!(debug.contains(&format!(
+            "Protocol:  v{}",
+            tallow_protocol::wire::version::PROTOCOL_VERSION
+        )));
+    }
+
+    #[test]
+    fn test_json_ignores_detail_level() {
+        let report = sample_report();
+        let json = report.format(DoctorDetail::Full, true);
+        for detail in [DoctorDetail::Summary, DoctorDetail::Debug] {
+            assert_eq!(report.format(detail, true), json);
+        }
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["all_passed"], false);
+        assert_eq!(value["checks"].as_array().unwrap().len(), 5);
+        assert_eq!(value["network"]["relays"][0]["tcp_rtt_ms"], 21);
+    }
+
+    #[test]
+    fn test_nat_type_maps_to_verdict() {
+        let relay = RelayProbe {
+            addr: "relay:4433".to_string(),
+            resolved: None,
+            tcp_rtt: Some(Duration::from_millis(20)),
+            quic_rtt: None,
+        };
+        let relays = [relay];
+
+        for nat in [
+            NatType::None,
+            NatType::FullCone,
+            NatType::RestrictedCone,
+            NatType::PortRestricted,
+        ] {
+            let verdict = ConnectivityVerdict::new(nat, true, &relays);
+            assert!(verdict.direct_wan, "{}", nat);
+            assert_eq!(verdict.summary(), "direct WAN, with relay fallback");
+        }
+        for nat in [NatType::Symmetric, NatType::Unknown] {
+            let verdict = ConnectivityVerdict::new(nat, true, &relays);
+            assert!(!verdict.direct_wan, "{}", nat);
+            assert_eq!(
+                verdict.summary(),
+                "relay-only (NAT prevents direct WAN connections)"
+            );
+        }
+
+        let verdict = ConnectivityVerdict::new(NatType::Symmetric, true, &[]);
+        assert_eq!(
+            verdict,
+            ConnectivityVerdict {
+                direct_lan: true,
+                direct_wan: false,
+                relay: false,
+            }
+        );
+        assert_eq!(verdict.summary(), "direct LAN only");
+        let report = NetworkReport {
+            relays: Vec::new(),
+            stun: Vec::new(),
+            nat: NatType::Unknown,
+            verdict: ConnectivityVerdict::new(NatType::Unknown, false, &[]),
+        };
+        assert!(report
+            .checks()
+            .iter()
+            .any(|c| c.name == "Connectivity" && !c.passed));
     }
 }
@@ -39,9 +39,9 @@ pub async fn execute(args: DropBoxArgs, json: bool) -> io::Result<()> {
         std::fs::create_dir_all(&output_dir)?;
     }
 
-    // Load or generate identity (needed for future trust DB lookups)
-    let mut _identity = tallow_store::identity::IdentityStore::new();
-    if let Err(e) = _identity.load_or_generate("") {
+    // Load or generate identity (keys the history log when it is encrypted)
+    let mut identity = tallow_store::identity::IdentityStore::new();
+    if let Err(e) = identity.load_or_generate("") {
         tracing::warn!("Identity initialization failed: {}", e);
     }
 
@@ -97,21 +97,14 @@ pub async fn execute(args: DropBoxArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
-
     let mut transfer_count: u64 = 0;
 
+    // Drop the cached NAT type when the machine switches networks between transfers
+    let _network_watcher = tallow_net::nat::detection::spawn_network_watcher();
+
     // Main drop box loop
     loop {
         if args.max_transfers > 0 && transfer_count >= args.max_transfers {
@@ -146,6 +139,7 @@ pub async fn execute(args: DropBoxArgs, json: bool) -> io::Result<()> {
             pw_ref,
             &proxy_config,
             &output_dir,
+            &identity,
         )
         .await
         {
@@ -185,6 +179,7 @@ pub async fn execute(args: DropBoxArgs, json: bool) -> io::Result<()> {
 }
 
 /// Handle a single transfer session within the drop box loop
+#[allow(clippy::too_many_arguments)]
 async fn handle_one_transfer(
     args: &DropBoxArgs,
     json: bool,
@@ -193,6 +188,7 @@ async fn handle_one_transfer(
     pw_ref: Option<&[u8; 32]>,
     proxy_config: &Option<tallow_net::privacy::ProxyConfig>,
     output_dir: &std::path::Path,
+    identity: &tallow_store::identity::IdentityStore,
 ) -> io::Result<()> {
     // Establish connection
     let (mut channel, mut is_direct) = if let Some(ref proxy) = proxy_config {
@@ -228,9 +224,11 @@ async fn handle_one_transfer(
         )
     } else {
         let relay_addr: std::net::SocketAddr = resolve_relay(&args.relay)?;
-        tallow_net::transport::establish_receiver_connection(room_id, relay_addr, pw_ref, false)
-            .await
-            .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?
+        tallow_net::transport::establish_receiver_connection(
+            room_id, relay_addr, pw_ref, None, false,
+        )
+        .await
+        .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?
     };
 
     if !json {
@@ -261,10 +259,38 @@ async fn handle_one_transfer(
     .map_err(|e| io::Error::other(format!("Receive handshake: {}", e)))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
-    let init_msg = codec
+    let mut init_msg = codec
         .decode_msg(&mut decode_buf)
         .map_err(|e| io::Error::other(format!("Decode handshake: {}", e)))?;
 
+    // Answer the sender's capability advertisement before its HandshakeInit
+    if let Some(Message::Capabilities { features }) = init_msg {
+        let reply = handshake
+            .process_capabilities(features, tallow_protocol::wire::FeatureSet::local())
+            .map_err(|e| io::Error::other(format!("Handshake capabilities failed: {}", e)))?;
+        encode_buf.clear();
+        codec
+            .encode_msg(&reply, &mut encode_buf)
+            .map_err(|e| io::Error::other(format!("Encode Capabilities: {}", e)))?;
+        channel
+            .send_message(&encode_buf)
+            .await
+            .map_err(|e| io::Error::other(format!("Send Capabilities: {}", e)))?;
+
+        let n = tokio::time::timeout(
+            std::time::Duration::from_secs(30),
+            channel.receive_message(&mut recv_buf),
+        )
+        .await
+        .map_err(|_| io::Error::other("Handshake timeout waiting for init"))?
+        .map_err(|e| io::Error::other(format!("Receive handshake: {}", e)))?;
+
+        let mut decode_buf = BytesMut::from(&recv_buf[..n]);
+        init_msg = codec
+            .decode_msg(&mut decode_buf)
+            .map_err(|e| io::Error::other(format!("Decode handshake: {}", e)))?;
+    }
+
     let session_key: tallow_protocol::kex::SessionKey;
 
     match init_msg {
@@ -356,11 +382,13 @@ async fn handle_one_transfer(
     }
 
     // --- P2P Direct Connection Upgrade ---
-    if !is_direct && proxy_config.is_none() && !args.no_p2p {
+    let network_policy = tallow_net::privacy::NetworkPolicy::new(proxy_config.clone());
+    let p2p_allowed = crate::commands::proxy::p2p_allowed(&network_policy, args.no_p2p);
+    if !is_direct && p2p_allowed {
         if !json {
             output::color::info("Attempting P2P direct connection...");
         }
-        let suppress_p2p = proxy_config.is_some() || args.no_p2p;
+        let suppress_p2p = !p2p_allowed;
         match tallow_net::transport::negotiate_p2p(&mut channel, false, suppress_p2p).await {
             tallow_net::transport::NegotiationResult::Direct(direct_conn) => {
                 if !json {
@@ -416,6 +444,12 @@ async fn handle_one_transfer(
         output_dir,
         *session_key.as_bytes(),
     );
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::CHUNK_CHECKSUM)
+    {
+        pipeline = pipeline.with_chunk_checksum(true);
+    }
 
     let manifest = pipeline
         .process_offer(&manifest_bytes)
@@ -532,7 +566,7 @@ async fn handle_one_transfer(
     loop {
         let n = reconnect::receive_with_retry(&mut channel, &mut recv_buf, &reconnect_config)
             .await
-            .map_err(|e| io::Error::other(format!("Receive chunk failed: {}", e)))?;
+            .map_err(|e| crate::exit_codes::network_error("Receive chunk failed", e))?;
 
         let mut chunk_buf = BytesMut::from(&recv_buf[..n]);
         let msg = codec
@@ -556,7 +590,7 @@ async fn handle_one_transfer(
                         .map_err(|e| io::Error::other(format!("Encode ack: {}", e)))?;
                     reconnect::send_with_retry(&mut channel, &encode_buf, &reconnect_config)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send ack: {}", e)))?;
+                        .map_err(|e| crate::exit_codes::network_error("Send ack", e))?;
                 }
 
                 bytes_received += chunk_size;
@@ -636,7 +670,7 @@ async fn handle_one_transfer(
     }
 
     // Log to transfer history
-    if let Ok(mut history) = tallow_store::history::TransferLog::open() {
+    if let Ok(mut history) = tallow_store::history::TransferLog::open(identity.keypair()) {
         let filenames: Vec<String> = written_files
             .iter()
             .map(|f| f.display().to_string())
@@ -653,6 +687,9 @@ async fn handle_one_transfer(
                 .as_secs(),
             status: tallow_store::history::TransferStatus::Completed,
             filenames,
+            context: None,
+            duration_ms: Some(transfer_start.elapsed().as_millis() as u64),
+            cipher: Some(tallow_protocol::transfer::chunking::CHUNK_CIPHER.to_string()),
         });
     }
 
@@ -1,16 +1,25 @@
 //! Transfer history command implementation
 
-use crate::cli::HistoryArgs;
+use crate::cli::{HistoryArgs, HistoryCommands};
 use crate::output;
 use std::io;
-use tallow_store::history::{TransferDirection, TransferLog, TransferStatus};
+use std::path::Path;
+use tallow_store::history::{ExportFormat, TransferDirection, TransferLog, TransferStatus};
 
 /// Execute the history command
 pub async fn execute(args: HistoryArgs, json: bool) -> io::Result<()> {
+    if let Some(HistoryCommands::Export {
+        format,
+        output,
+        redact,
+    }) = args.command
+    {
+        return export(&format, output.as_deref(), redact, json);
+    }
+
     // --clear: wipe all history and exit
     if args.clear {
-        let mut log = TransferLog::open()
-            .map_err(|e| io::Error::other(format!("Failed to open history: {}", e)))?;
+        let mut log = open_log()?;
         log.clear()
             .map_err(|e| io::Error::other(format!("Failed to clear history: {}", e)))?;
 
@@ -23,8 +32,7 @@ pub async fn execute(args: HistoryArgs, json: bool) -> io::Result<()> {
     }
 
     // Load history
-    let log = TransferLog::open()
-        .map_err(|e| io::Error::other(format!("Failed to open history: {}", e)))?;
+    let log = open_log()?;
 
     let entries = log.recent(args.limit);
 
@@ -84,6 +92,51 @@ pub async fn execute(args: HistoryArgs, json: bool) -> io::Result<()> {
     Ok(())
 }
 
+/// Open the history log, unlocking the identity that keys it when the
+/// log is encrypted
+fn open_log() -> io::Result<TransferLog> {
+    let mut identity = tallow_store::identity::IdentityStore::new();
+    if let Err(e) = identity.load("") {
+        tracing::debug!("Identity not loaded for history: {}", e);
+    }
+    TransferLog::open(identity.keypair())
+        .map_err(|e| io::Error::other(format!("Failed to open history: {}", e)))
+}
+
+/// Write the whole log to `dest` (or stdout) as CSV or JSON lines
+fn export(format: &str, dest: Option<&Path>, redact: bool, json: bool) -> io::Result<()> {
+    let format: ExportFormat = format
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
+    let log = open_log()?;
+
+    let rows = match dest {
+        Some(path) => {
+            let file = std::fs::File::create(path)?;
+            log.export(format, redact, io::BufWriter::new(file))
+        }
+        None => log.export(format, redact, io::stdout().lock()),
+    }
+    .map_err(|e| io::Error::other(format!("Failed to export history: {}", e)))?;
+
+    // With no file the export itself is the output
+    if let Some(path) = dest {
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "event": "history_exported",
+                    "path": path.display().to_string(),
+                    "entries": rows,
+                })
+            );
+        } else {
+            output::color::success(&format!("Exported {} entries to {}", rows, path.display()));
+        }
+    }
+    Ok(())
+}
+
 /// Convert a direction enum to a display string
 fn direction_str(dir: TransferDirection) -> &'static str {
     match dir {
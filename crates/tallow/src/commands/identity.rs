@@ -2,8 +2,11 @@
 
 use crate::cli::{
     ContactsArgs, ContactsCommands, IdentityArgs, IdentityCommands, TrustArgs, TrustCommands,
+    VerifyPeerArgs,
 };
 use std::io;
+use tallow_store::identity::{fingerprint_emoji, fingerprint_hex, fingerprint_short};
+use tallow_store::trust::{TofuStore, TrustLevel};
 
 /// Execute identity command
 pub async fn execute_identity(args: IdentityArgs, json: bool) -> io::Result<()> {
@@ -13,6 +16,10 @@ pub async fn execute_identity(args: IdentityArgs, json: bool) -> io::Result<()>
         Some(IdentityCommands::Export { output }) => identity_export(&output, json),
         Some(IdentityCommands::Import { file }) => identity_import(&file, json),
         Some(IdentityCommands::Fingerprint { emoji }) => identity_fingerprint(emoji, json),
+        Some(IdentityCommands::QrExport { prekeys }) => identity_qr_export(prekeys, json),
+        Some(IdentityCommands::QrImport { file, name }) => {
+            identity_qr_import(file.as_deref(), name, json)
+        }
         None => identity_show(json),
     }
 }
@@ -194,6 +201,146 @@ fn identity_fingerprint(emoji: bool, json: bool) -> io::Result<()> {
     Ok(())
 }
 
+fn identity_qr_export(prekeys: usize, json: bool) -> io::Result<()> {
+    use crate::output::qr::{split_frames, FRAME_PAYLOAD_BYTES};
+
+    let mut store = tallow_store::identity::IdentityStore::new();
+    store
+        .load_or_generate("")
+        .map_err(|e| io::Error::other(format!("Failed to load identity: {}", e)))?;
+    let identity = store
+        .keypair()
+        .ok_or_else(|| io::Error::other("No identity available"))?;
+
+    // Publish from the persistent pool so the pre-keys stay usable; the
+    // one-time keys shown are not consumed until a peer initiates with them
+    let mut prekey_store = tallow_store::identity::PreKeyStore::open()
+        .map_err(|e| io::Error::other(format!("Failed to open pre-key store: {}", e)))?;
+    prekey_store
+        .load_or_generate(identity.signer())
+        .map_err(|e| io::Error::other(format!("{}", e)))?;
+    let mut prekey_bundle = prekey_store
+        .bundle()
+        .cloned()
+        .ok_or_else(|| io::Error::other("No pre-keys available"))?;
+    prekey_bundle.onetime_prekeys.truncate(prekeys);
+    let bundle = tallow_store::identity::OfflineBundle::new(identity, prekey_bundle)
+        .map_err(|e| io::Error::other(format!("{}", e)))?;
+
+    let password = crate::output::prompts::password_prompt("Bundle password")?;
+    let password = std::str::from_utf8(password.as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Password is not UTF-8"))?;
+    if password.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "A bundle password is required",
+        ));
+    }
+    let sealed = bundle
+        .seal(password)
+        .map_err(|e| io::Error::other(format!("{}", e)))?;
+    let frames = split_frames(&sealed, FRAME_PAYLOAD_BYTES);
+    let fingerprint = tallow_store::identity::fingerprint_hex(&bundle.fingerprint);
+
+    if json {
+        let texts: Vec<String> = frames.iter().map(|f| f.encode()).collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "identity_qr_export",
+                "fingerprint": fingerprint,
+                "frames": texts,
+            })
+        );
+        return Ok(());
+    }
+
+    crate::output::color::info(&format!(
+        "Bundle for {} split into {} QR code(s)",
+        fingerprint,
+        frames.len()
+    ));
+    println!("Tell the scanning party the password out of band.");
+    crate::output::qr::display_frames(&frames)?;
+    crate::output::color::success("All frames shown");
+    Ok(())
+}
+
+fn identity_qr_import(
+    file: Option<&std::path::Path>,
+    name: Option<String>,
+    json: bool,
+) -> io::Result<()> {
+    use crate::output::qr::{Frame, FrameAssembler};
+    use std::io::BufRead;
+
+    let reader: Box<dyn BufRead> = match file {
+        Some(path) => Box::new(io::BufReader::new(std::fs::File::open(path)?)),
+        None => {
+            if !json {
+                println!("Paste scanned frames, one per line (Ctrl+D when done):");
+            }
+            Box::new(io::stdin().lock())
+        }
+    };
+
+    let mut assembler = FrameAssembler::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        assembler.push(Frame::decode(&line)?)?;
+        if assembler.is_complete() {
+            break;
+        }
+    }
+    let sealed = assembler.finish()?;
+
+    let password = crate::output::prompts::password_prompt("Bundle password")?;
+    let password = std::str::from_utf8(password.as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Password is not UTF-8"))?;
+    let bundle = tallow_store::identity::OfflineBundle::open(&sealed, password)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}", e)))?;
+
+    let fingerprint = tallow_store::identity::fingerprint_hex(&bundle.fingerprint);
+    if let Some(ref name) = name {
+        let mut db = tallow_store::contacts::ContactDatabase::new();
+        db.add(tallow_store::contacts::Contact {
+            id: hex::encode(blake3::hash(name.as_bytes()).as_bytes())[..16].to_string(),
+            name: name.clone(),
+            alias: None,
+            public_key: bundle.fingerprint.to_vec(),
+            groups: Vec::new(),
+        })
+        .map_err(|e| io::Error::other(format!("{}", e)))?;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "identity_qr_import",
+                "fingerprint": fingerprint,
+                "onetime_prekeys": bundle.prekeys.onetime_count(),
+                "contact": name,
+            })
+        );
+    } else {
+        crate::output::color::success("Bundle signature verified");
+        println!("Fingerprint: {}", fingerprint);
+        println!(
+            "             {}",
+            tallow_store::identity::fingerprint_emoji(&bundle.fingerprint)
+        );
+        match name {
+            Some(name) => println!("Saved as contact '{}'", name),
+            None => println!("Compare this fingerprint with the owner before trusting it."),
+        }
+    }
+    Ok(())
+}
+
 /// Execute contacts command
 pub async fn execute_contacts(args: ContactsArgs, json: bool) -> io::Result<()> {
     match args.command {
@@ -228,6 +375,7 @@ pub async fn execute_contacts(args: ContactsArgs, json: bool) -> io::Result<()>
             let contact = tallow_store::contacts::Contact {
                 id: hex::encode(blake3::hash(name.as_bytes()).as_bytes())[..16].to_string(),
                 name: name.clone(),
+                alias: None,
                 public_key: hex::decode(&key).unwrap_or_else(|_| key.as_bytes().to_vec()),
                 groups: Vec::new(),
             };
@@ -377,3 +525,171 @@ pub async fn execute_trust(args: TrustArgs, json: bool) -> io::Result<()> {
 
     Ok(())
 }
+
+/// Execute the verify-peer wizard
+///
+/// Shows the contact's fingerprint in every format `tallow identity
+/// fingerprint` can print, asks whether it matches what the peer reads out
+/// over a separate channel, and only then marks the pin as verified.
+pub fn execute_verify_peer(args: VerifyPeerArgs, json: bool) -> io::Result<()> {
+    let db = tallow_store::contacts::ContactDatabase::new();
+    let contact = db
+        .resolve_one(&args.contact)
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e.to_string()))?;
+    let peer_id = fingerprint_hex(&contact.public_key);
+
+    let mut store = TofuStore::open()
+        .map_err(|e| io::Error::other(format!("Failed to open trust store: {}", e)))?;
+    let before = store.get_trust(&peer_id);
+    let formats = verification_formats(&contact.public_key);
+
+    if !json {
+        crate::output::color::section(&format!("Verify {}", contact.name));
+        println!("  Current trust: {}", trust_label(before));
+        for (label, text) in &formats {
+            println!("  {:<8} {}", format!("{}:", label), text);
+        }
+        println!();
+        println!(
+            "Ask {} to run `tallow identity fingerprint` (or add --emoji) and read the",
+            contact.name
+        );
+        println!("result to you in person or on a call, not over the channel you send files on.");
+    }
+
+    let after = if before == TrustLevel::Verified {
+        before
+    } else {
+        let confirmed = crate::output::prompts::confirm(&format!(
+            "Does it match exactly what {} reports?",
+            contact.name
+        ))?;
+        apply_verification(&mut store, &peer_id, &contact.public_key, confirmed)?
+    };
+
+    if json {
+        let formats: serde_json::Map<String, serde_json::Value> = formats
+            .iter()
+            .map(|(label, text)| (label.to_lowercase(), serde_json::json!(text)))
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "peer_verification",
+                "contact": contact.name,
+                "peer_id": peer_id,
+                "fingerprints": formats,
+                "verified": after == TrustLevel::Verified,
+                "trust_level": format!("{:?}", after),
+            })
+        );
+    } else if before == TrustLevel::Verified {
+        crate::output::color::info(&format!("{} is already verified", contact.name));
+    } else if after == TrustLevel::Verified {
+        crate::output::color::success(&format!("{} marked as verified", contact.name));
+    } else {
+        crate::output::color::warning(&format!(
+            "Not verified; {} stays {}",
+            contact.name,
+            trust_label(after)
+        ));
+    }
+    Ok(())
+}
+
+/// Fingerprint formats shown by the verify-peer wizard, as `(label, text)`
+///
+/// All are derived from the same identity key, and each matches what the
+/// peer sees from `tallow identity fingerprint` in the corresponding mode.
+fn verification_formats(public_key: &[u8]) -> Vec<(&'static str, String)> {
+    vec![
+        ("Hex", fingerprint_hex(public_key)),
+        ("Emoji", fingerprint_emoji(public_key)),
+        ("Short", fingerprint_short(public_key)),
+    ]
+}
+
+/// Record the wizard's answer, returning the peer's resulting trust level
+///
+/// Declining changes nothing: the pin keeps whatever level it had.
+fn apply_verification(
+    store: &mut TofuStore,
+    peer_id: &str,
+    public_key: &[u8],
+    confirmed: bool,
+) -> io::Result<TrustLevel> {
+    if confirmed {
+        store
+            .mark_verified(peer_id, public_key)
+            .map_err(|e| io::Error::other(format!("{}", e)))?;
+    }
+    Ok(store.get_trust(peer_id))
+}
+
+/// How a trust level reads in the wizard
+fn trust_label(level: TrustLevel) -> &'static str {
+    match level {
+        TrustLevel::Unknown => "never seen",
+        TrustLevel::Seen => "pinned on first use, not verified",
+        TrustLevel::Trusted => "trusted, not verified",
+        TrustLevel::Verified => "verified",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn pinned_store() -> (TofuStore, String) {
+        let peer_id = fingerprint_hex(&KEY);
+        let mut store = TofuStore::new();
+        store
+            .record_first_contact(peer_id.clone(), KEY.to_vec())
+            .unwrap();
+        (store, peer_id)
+    }
+
+    #[test]
+    fn test_confirmation_upgrades_trust() {
+        let (mut store, peer_id) = pinned_store();
+        let level = apply_verification(&mut store, &peer_id, &KEY, true).unwrap();
+        assert_eq!(level, TrustLevel::Verified);
+        assert_eq!(store.get_trust(&peer_id), TrustLevel::Verified);
+    }
+
+    #[test]
+    fn test_declining_leaves_trust_unchanged() {
+        let (mut store, peer_id) = pinned_store();
+        let level = apply_verification(&mut store, &peer_id, &KEY, false).unwrap();
+        assert_eq!(level, TrustLevel::Seen);
+
+        // Declining for a peer never seen does not pin it either
+        let unseen = fingerprint_hex(&[8u8; 32]);
+        let level = apply_verification(&mut store, &unseen, &[8u8; 32], false).unwrap();
+        assert_eq!(level, TrustLevel::Unknown);
+        assert!(store.pinned_key(&unseen).is_none());
+    }
+
+    #[test]
+    fn test_verification_formats_consistent() {
+        let formats = verification_formats(&KEY);
+        let labels: Vec<&str> = formats.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, ["Hex", "Emoji", "Short"]);
+
+        // Hex is what `tallow identity fingerprint` prints for this key
+        let hex = &formats[0].1;
+        assert_eq!(hex, &fingerprint_hex(&KEY));
+        assert_eq!(formats[1].1, fingerprint_emoji(&KEY));
+        // The short form is the start of the hex form
+        assert!(hex.replace(':', "").starts_with(&formats[2].1));
+
+        // Same key, same output; a different key changes every format
+        assert_eq!(verification_formats(&KEY), formats);
+        let other = verification_formats(&[8u8; 32]);
+        for ((_, a), (_, b)) in formats.iter().zip(&other) {
+            assert_ne!(a, b);
+        }
+    }
+}
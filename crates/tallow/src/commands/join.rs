@@ -0,0 +1,60 @@
+//! Join command: reassemble a file received as split volumes
+
+use crate::cli::JoinArgs;
+use crate::output;
+use std::io;
+use tallow_protocol::transfer::volumes::{self, VolumeManifest};
+
+/// Execute the join command
+pub fn execute(args: JoinArgs, json: bool) -> io::Result<()> {
+    let manifest = VolumeManifest::load(&args.manifest)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    let output = match args.output {
+        Some(path) => path,
+        None => {
+            // The manifest's own file name, never a path it names
+            let name = std::path::Path::new(&manifest.file_name)
+                .file_name()
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Volume manifest has no file name; pass --output",
+                    )
+                })?;
+            args.manifest
+                .parent()
+                .unwrap_or(std::path::Path::new("."))
+                .join(name)
+        }
+    };
+    if output.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", output.display()),
+        ));
+    }
+
+    let manifest = volumes::join(&args.manifest, &output)
+        .map_err(|e| io::Error::other(format!("Join failed: {}", e)))?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "join_complete",
+                "output": output.display().to_string(),
+                "parts": manifest.parts.len(),
+                "total_bytes": manifest.size,
+            })
+        );
+    } else {
+        output::color::success(&format!(
+            "Joined {} part(s) into {} ({}), hash verified",
+            manifest.parts.len(),
+            output.display(),
+            output::format_size(manifest.size)
+        ));
+    }
+    Ok(())
+}
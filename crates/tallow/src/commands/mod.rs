@@ -9,10 +9,14 @@ pub mod doctor;
 pub mod drop_box;
 pub mod history;
 pub mod identity;
+pub mod join;
 pub mod man_pages;
+pub mod pipe;
 pub mod proxy;
+pub mod psk;
 pub mod receive;
 pub mod send;
+pub mod signature;
 pub mod speed_test;
 pub mod ssh_setup;
 pub mod sync;
@@ -0,0 +1,93 @@
+//! Offline encrypt/decrypt filters for scripting
+//!
+//! `tallow encrypt` seals stdin into a signed stream bundle on stdout, and
+//! `tallow decrypt` reverses it. Nothing touches the network, and neither
+//! side buffers the whole input. Status messages go to stderr so stdout
+//! carries only data.
+
+use crate::cli::{DecryptArgs, EncryptArgs};
+use std::io;
+use std::path::Path;
+use tallow_crypto::kem::hybrid;
+use tallow_protocol::transfer::bundle::{self, BundleRecipient, BundleUnlock};
+
+/// Execute the encrypt filter
+pub async fn execute_encrypt(args: EncryptArgs) -> io::Result<()> {
+    let public_key = match args.recipient {
+        Some(ref path) => Some(read_key_file::<hybrid::PublicKey>(path)?),
+        None => None,
+    };
+    let recipient = match (&public_key, args.password.as_deref()) {
+        (Some(pk), _) => BundleRecipient::PublicKey(pk),
+        (None, Some(password)) => BundleRecipient::Password(password),
+        (None, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "either --recipient or --password is required",
+            ))
+        }
+    };
+
+    // Sign with the long-term identity when available
+    let mut identity = tallow_store::identity::IdentityStore::new();
+    if let Err(e) = identity.load_or_generate("") {
+        tracing::warn!("Identity unavailable, signing with an ephemeral key: {}", e);
+    }
+    let ephemeral_signer;
+    let signer = match identity.keypair() {
+        Some(keypair) => keypair.signer(),
+        None => {
+            ephemeral_signer = tallow_crypto::sig::HybridSigner::keygen()
+                .map_err(|e| io::Error::other(format!("Signer init failed: {}", e)))?;
+            &ephemeral_signer
+        }
+    };
+
+    let bytes = bundle::seal_stream(tokio::io::stdin(), recipient, signer, tokio::io::stdout())
+        .await
+        .map_err(|e| io::Error::other(format!("Encryption failed: {}", e)))?;
+    tracing::debug!("Encrypted {} bytes from stdin", bytes);
+    Ok(())
+}
+
+/// Execute the decrypt filter
+///
+/// Plaintext is written as it is decrypted. If this returns an error, the
+/// bundle was truncated, tampered with, or not meant for us, and whatever
+/// reached stdout must be discarded.
+pub async fn execute_decrypt(args: DecryptArgs) -> io::Result<()> {
+    let secret_key = match args.key {
+        Some(ref path) => Some(read_key_file::<hybrid::SecretKey>(path)?),
+        None => None,
+    };
+    let unlock = match (&secret_key, args.password.as_deref()) {
+        (Some(sk), _) => BundleUnlock::SecretKey(sk),
+        (None, Some(password)) => BundleUnlock::Password(password),
+        (None, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "either --key or --password is required",
+            ))
+        }
+    };
+
+    let trailer = bundle::open_stream(tokio::io::stdin(), unlock, None, tokio::io::stdout())
+        .await
+        .map_err(|e| {
+            eprintln!("Decrypted output is incomplete or unauthenticated; discard it.");
+            io::Error::other(format!("Decryption failed: {}", e))
+        })?;
+    tracing::debug!("Decrypted {} bytes to stdout", trailer.total_bytes);
+    Ok(())
+}
+
+/// Read a postcard-encoded hybrid KEM key from `path`
+fn read_key_file<T: serde::de::DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let bytes = std::fs::read(path)?;
+    postcard::from_bytes(&bytes).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a valid key file: {}", path.display(), e),
+        )
+    })
+}
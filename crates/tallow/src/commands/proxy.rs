@@ -5,12 +5,13 @@
 //! receive, sync, watch, clip) to avoid duplicated proxy logic.
 
 use std::io;
-use tallow_net::privacy::ProxyConfig;
+use tallow_net::privacy::{verify_tor_proxy, NetworkPolicy, ProxyConfig};
 
 /// Build ProxyConfig from CLI flags, env vars, and config
 ///
 /// Priority (highest first):
-/// 1. `--tor` (forces `socks5://127.0.0.1:9050` + `tor_mode`)
+/// 1. `--tor` or `privacy.tor` (forces `socks5://127.0.0.1:9050` + `tor_mode`,
+///    after verifying the port is a live Tor SOCKS5 proxy)
 /// 2. `--proxy <url>`
 /// 3. `TALLOW_PROXY` env var (handled by clap `env` attribute on `--proxy`)
 /// 4. `privacy.default_proxy` in config file
@@ -20,37 +21,35 @@ pub async fn build_proxy_config(
     proxy: &Option<String>,
     json: bool,
 ) -> io::Result<Option<ProxyConfig>> {
-    // Priority 1: --tor flag
-    if tor {
-        // Verify Tor is running by probing the SOCKS5 port
-        let tor_reachable = tokio::time::timeout(
-            std::time::Duration::from_secs(2),
-            tokio::net::TcpStream::connect("127.0.0.1:9050"),
-        )
-        .await;
-
-        match tor_reachable {
-            Ok(Ok(_stream)) => {
-                return Ok(Some(ProxyConfig::tor_default()));
-            }
-            _ => {
-                let msg = "Tor is not running on 127.0.0.1:9050.\n\
-                           Install Tor and start the service:\n\
-                           - Linux/macOS: sudo systemctl start tor  (or  brew services start tor)\n\
-                           - Windows: Start Tor Browser or Tor Expert Bundle\n\
-                           Or use --proxy to specify a custom SOCKS5 proxy.";
-                if json {
-                    println!(
-                        "{}",
-                        serde_json::json!({
-                            "event": "error",
-                            "message": "Tor not running on 127.0.0.1:9050",
-                        })
-                    );
-                }
-                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, msg));
+    let cfg = tallow_store::config::load_config().ok();
+
+    // Priority 1: --tor flag or `privacy.tor = true`
+    if tor || cfg.as_ref().is_some_and(|c| c.privacy.tor) {
+        let tor_config = ProxyConfig::tor_default();
+
+        // Verify the port is reachable and actually behaves like Tor
+        if let Err(e) = verify_tor_proxy(tor_config.socks5_addr).await {
+            let msg = format!(
+                "Tor is not available on {}: {}\n\
+                 Install Tor and start the service:\n\
+                 - Linux/macOS: sudo systemctl start tor  (or  brew services start tor)\n\
+                 - Windows: Start Tor Browser or Tor Expert Bundle\n\
+                 Or use --proxy to specify a custom SOCKS5 proxy.",
+                tor_config.socks5_addr, e
+            );
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "event": "error",
+                        "message": format!("Tor not available on {}: {}", tor_config.socks5_addr, e),
+                    })
+                );
             }
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, msg));
         }
+
+        return Ok(Some(tor_config));
     }
 
     // Priority 2/3: --proxy flag (or TALLOW_PROXY env var, handled by clap)
@@ -67,7 +66,7 @@ pub async fn build_proxy_config(
     }
 
     // Priority 4: config file default_proxy
-    if let Ok(cfg) = tallow_store::config::load_config() {
+    if let Some(cfg) = cfg {
         if !cfg.privacy.default_proxy.is_empty() {
             let mut config = ProxyConfig::from_url(&cfg.privacy.default_proxy).map_err(|e| {
                 io::Error::other(format!(
@@ -89,6 +88,44 @@ pub async fn build_proxy_config(
     Ok(None)
 }
 
+/// Build the [`NetworkPolicy`] for the proxy chosen by the CLI flags
+///
+/// Send and receive ask the policy, not the proxy config, whether LAN
+/// discovery, NAT traversal and the P2P upgrade may run.
+pub async fn build_network_policy(
+    tor: bool,
+    proxy: &Option<String>,
+    json: bool,
+) -> io::Result<NetworkPolicy> {
+    Ok(NetworkPolicy::new(
+        build_proxy_config(tor, proxy, json).await?,
+    ))
+}
+
+/// Whether to try the direct P2P upgrade after the relay handshake
+///
+/// Negotiation runs STUN and hole punching, so the policy has to allow
+/// NAT traversal as well as P2P.
+pub fn p2p_allowed(policy: &NetworkPolicy, no_p2p: bool) -> bool {
+    !no_p2p && policy.allows_p2p() && policy.allows_nat_traversal()
+}
+
+/// TLS policy for proxied TCP+TLS connections, from `[network]` config
+pub fn tls_policy(
+    network: &tallow_store::config::NetworkConfig,
+) -> io::Result<tallow_net::transport::TlsPolicy> {
+    tallow_net::transport::TlsPolicy::from_config(
+        &network.tls_min_version,
+        &network.tls_cipher_suites,
+    )
+    .map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid TLS policy: {}", e),
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +163,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_proxy_flag_disables_leaky_features() {
+        let proxy_url = Some("socks5://127.0.0.1:1080".to_string());
+        let policy = build_network_policy(false, &proxy_url, false)
+            .await
+            .unwrap();
+        assert!(policy.is_proxied());
+        assert!(!policy.allows_lan_discovery());
+        assert!(!p2p_allowed(&policy, false));
+    }
+
+    #[tokio::test]
+    async fn test_direct_policy_allows_p2p_unless_disabled() {
+        let policy = build_network_policy(false, &None, false).await.unwrap();
+        assert!(policy.allows_lan_discovery());
+        assert!(p2p_allowed(&policy, false));
+        assert!(!p2p_allowed(&policy, true));
+    }
+
     #[tokio::test]
     async fn test_invalid_proxy_url() {
         let proxy_url = Some("not-a-valid-url".to_string());
@@ -0,0 +1,176 @@
+//! Pre-shared key mode for `send` and `receive`
+//!
+//! With `--psk-file`, both peers authenticate the key exchange with a
+//! 256-bit key provisioned out of band instead of running CPace over the
+//! code phrase. The code phrase still names the room.
+
+use std::io;
+use std::path::Path;
+use tallow_protocol::kex::{ReceiverHandshake, SenderHandshake};
+use zeroize::Zeroizing;
+
+/// Read a pre-shared key file: 64 hex characters, surrounding whitespace
+/// ignored
+pub fn load(path: &Path) -> io::Result<Zeroizing<[u8; 32]>> {
+    let text = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Cannot read PSK file {}: {}", path.display(), e),
+        )
+    })?);
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "PSK file {} must hold exactly 64 hex characters (256 bits)",
+                path.display()
+            ),
+        )
+    };
+    let bytes = Zeroizing::new(hex::decode(text.trim()).map_err(|_| invalid())?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    if bytes.len() != key.len() {
+        return Err(invalid());
+    }
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+/// Load the key named by `--psk-file`, if one was given
+pub fn from_args(psk_file: Option<&Path>) -> io::Result<Option<Zeroizing<[u8; 32]>>> {
+    psk_file.map(load).transpose()
+}
+
+/// Sender handshake authenticated by `psk`, or by the code phrase without one
+pub fn sender_handshake(
+    code_phrase: &str,
+    room_id: &[u8; 32],
+    psk: Option<&[u8; 32]>,
+) -> SenderHandshake {
+    match psk {
+        Some(psk) => SenderHandshake::with_psk(psk, room_id),
+        None => SenderHandshake::new(code_phrase, room_id),
+    }
+}
+
+/// Receiver handshake authenticated by `psk`, or by the code phrase without one
+pub fn receiver_handshake(
+    code_phrase: &str,
+    room_id: &[u8; 32],
+    psk: Option<&[u8; 32]>,
+) -> ReceiverHandshake {
+    match psk {
+        Some(psk) => ReceiverHandshake::with_psk(psk, room_id),
+        None => ReceiverHandshake::new(code_phrase, room_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tallow_protocol::wire::Message;
+
+    fn write_key(dir: &Path, name: &str, byte: u8) -> std::path::PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("{}\n", hex::encode([byte; 32]))).unwrap();
+        path
+    }
+
+    /// Run the four handshake messages between the two sides
+    fn exchange(
+        mut sender: SenderHandshake,
+        mut receiver: ReceiverHandshake,
+    ) -> tallow_protocol::Result<([u8; 32], [u8; 32])> {
+        let Message::HandshakeInit {
+            protocol_version,
+            kem_capabilities,
+            cpace_public,
+            nonce,
+        } = sender.init()?
+        else {
+            panic!("expected HandshakeInit");
+        };
+        let Message::HandshakeResponse {
+            selected_kem,
+            cpace_public,
+            kem_public_key,
+            nonce,
+        } = receiver.process_init(protocol_version, &kem_capabilities, &cpace_public, &nonce)?
+        else {
+            panic!("expected HandshakeResponse");
+        };
+        let Message::HandshakeKem {
+            kem_ciphertext,
+            confirmation,
+        } = sender.process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)?
+        else {
+            panic!("expected HandshakeKem");
+        };
+        let (complete, receiver_key) = receiver.process_kem(&kem_ciphertext, &confirmation)?;
+        let Message::HandshakeComplete { confirmation } = complete else {
+            panic!("expected HandshakeComplete");
+        };
+        let sender_key = sender.verify_receiver_confirmation(&confirmation)?;
+        Ok((*sender_key.as_bytes(), *receiver_key.as_bytes()))
+    }
+
+    /// `--psk-file` from a parsed command line, loaded the way the commands do
+    fn key_from_cli(argv: &[&str]) -> Option<Zeroizing<[u8; 32]>> {
+        let psk_file = match crate::cli::Cli::try_parse_from(argv).unwrap().command {
+            crate::cli::Commands::Send(args) => args.psk_file,
+            crate::cli::Commands::Receive(args) => args.psk_file,
+            _ => panic!("expected send or receive"),
+        };
+        from_args(psk_file.as_deref()).unwrap()
+    }
+
+    #[test]
+    fn test_psk_file_drives_handshake() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = write_key(dir.path(), "shared.key", 0x42);
+        let other = write_key(dir.path(), "other.key", 0x24);
+        let shared = shared.to_str().unwrap();
+        let room_id = [7u8; 32];
+
+        let send_key = key_from_cli(&["tallow", "send", "--psk-file", shared, "a.txt"]);
+        let recv_key = key_from_cli(&["tallow", "receive", "--psk-file", shared, "1-a-b"]);
+        let (sender_key, receiver_key) = exchange(
+            sender_handshake("1-a-b", &room_id, send_key.as_deref()),
+            receiver_handshake("1-a-b", &room_id, recv_key.as_deref()),
+        )
+        .unwrap();
+        assert_eq!(sender_key, receiver_key);
+
+        // A different key on one side fails key confirmation
+        let wrong = key_from_cli(&["tallow", "receive", "--psk-file", other.to_str().unwrap()]);
+        let err = exchange(
+            sender_handshake("1-a-b", &room_id, send_key.as_deref()),
+            receiver_handshake("1-a-b", &room_id, wrong.as_deref()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            crate::exit_codes::for_error(&crate::error::classified(err)),
+            crate::exit_codes::AUTH_FAILURE
+        );
+
+        // Without the flag the code phrase authenticates as before
+        assert!(key_from_cli(&["tallow", "receive", "1-a-b"]).is_none());
+    }
+
+    #[test]
+    fn test_load_rejects_malformed_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents) in [
+            ("short.key", "abcd".to_string()),
+            ("long.key", hex::encode([1u8; 33])),
+            ("text.key", "z".repeat(64)),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            let err = load(&path).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", name);
+        }
+        assert!(load(&dir.path().join("missing.key")).is_err());
+    }
+}
@@ -7,31 +7,62 @@ use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
 use tallow_net::transport::reconnect::{self, ReconnectConfig};
 use tallow_net::transport::PeerChannel;
+use tallow_protocol::transfer::checksums::ChecksumList;
 use tallow_protocol::transfer::manifest::TransferType;
+use tallow_protocol::transfer::naming::{
+    self, ConflictPolicy, DirectoryLayout, OutputName, OutputTemplate,
+};
+use tallow_protocol::transfer::AcceptanceMask;
 use tallow_protocol::wire::{codec::TallowCodec, Message};
 
 /// Maximum receive buffer size (256 KB)
 const RECV_BUF_SIZE: usize = 256 * 1024;
 
+/// Chunks between cumulative acks, when the sender supports them
+const CUMULATIVE_ACK_INTERVAL: u64 = 16;
+
+/// How long `--once` waits for a sender when `--wait-timeout` isn't given
+const DEFAULT_ONCE_WAIT: std::time::Duration = std::time::Duration::from_secs(300);
+
 /// Execute receive command
 pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     // Load config for hooks
     let config = tallow_store::config::load_config().unwrap_or_default();
+    let on_complete = crate::hooks::CompletionHook::resolve(
+        args.on_complete.as_deref(),
+        &config.transfer.on_complete,
+        !args.no_hooks,
+    )?;
+
+    let started = std::time::Instant::now();
+    let mut completion = crate::hooks::Completion::new("receive");
+    let result = receive(args, json, config, &mut completion).await;
+    completion.finish(&result, started.elapsed());
+    on_complete.notify(&completion).await;
+    result
+}
+
+async fn receive(
+    args: ReceiveArgs,
+    json: bool,
+    config: tallow_store::config::TallowConfig,
+    completion: &mut crate::hooks::Completion,
+) -> io::Result<()> {
     let hook_runner = crate::hooks::HookRunner::from_config(&config.hooks, !args.no_hooks);
 
-    // Build proxy config from CLI flags
-    let proxy_config =
-        crate::commands::proxy::build_proxy_config(args.tor, &args.proxy, json).await?;
+    // Build the network policy from the proxy flags
+    let network_policy =
+        crate::commands::proxy::build_network_policy(args.tor, &args.proxy, json).await?;
 
     // Suppress LAN advertise when proxy is active (broadcasts local IP)
-    if proxy_config.is_some() && args.advertise && !json {
+    if !network_policy.allows_lan_discovery() && args.advertise && !json {
         output::color::warning(
             "LAN advertise disabled: --advertise leaks local IP when using a proxy",
         );
     }
 
     // Log proxy usage
-    if let Some(ref proxy) = proxy_config {
+    if let Some(proxy) = network_policy.proxy() {
         if !json {
             if proxy.tor_mode {
                 output::color::info("Routing through Tor...");
@@ -43,7 +74,7 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
 
     // LAN advertise via mDNS (backward compat -- --local subsumes this)
     let mut _mdns_discovery = None;
-    if args.advertise && !args.local && proxy_config.is_none() {
+    if args.advertise && !args.local && network_policy.allows_lan_discovery() {
         if !json {
             output::color::info("Advertising on LAN for peer discovery...");
         }
@@ -60,7 +91,7 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     let code_phrase = match &args.code {
         Some(code) => code.clone(),
         None => {
-            if json {
+            if json || args.once {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
                     "Code phrase required. Usage: tallow receive <code-phrase>",
@@ -81,6 +112,49 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         ));
     }
 
+    // Read the pre-shared key now so a bad file fails before connecting
+    let psk = crate::commands::psk::from_args(args.psk_file.as_deref())?;
+
+    // --once runs unattended, so it takes the offer as --yes would
+    let auto_accept = args.yes || args.auto_accept || args.once;
+
+    // Parse naming options up front so a typo fails before connecting
+    let output_template = args
+        .output_template
+        .as_deref()
+        .map(OutputTemplate::parse)
+        .transpose()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let conflict_policy = match args.on_conflict.as_deref() {
+        Some(policy) => Some(
+            policy
+                .parse::<ConflictPolicy>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?,
+        ),
+        None if args.overwrite => Some(ConflictPolicy::Overwrite),
+        None => None,
+    };
+
+    // Load the checksum file now so a bad listing fails before connecting
+    let checksums = args
+        .checksum_file
+        .as_deref()
+        .map(ChecksumList::load)
+        .transpose()
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid checksum file: {}", e),
+            )
+        })?;
+
+    // Parse --split-size up front for the same reason
+    let split_size = args
+        .split_size
+        .as_deref()
+        .map(parse_split_size)
+        .transpose()?;
+
     // Determine output directory
     let output_dir = args.output.unwrap_or_else(|| PathBuf::from("."));
     if !output_dir.exists() {
@@ -125,61 +199,71 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
+    // Decode relay bearer token (hex, as printed by `tallow-relay token mint`)
+    let relay_token: Option<Vec<u8>> = args
+        .relay_token
+        .as_deref()
+        .map(|t| hex::decode(t.trim()))
+        .transpose()
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Relay token must be hex-encoded",
+            )
+        })?;
 
-    // Establish connection: proxy-aware relay or direct LAN with fallback
-    let (mut channel, mut is_direct) = if let Some(ref proxy) = proxy_config {
-        // Proxy active: resolve via DoH/hostname, skip LAN discovery entirely
-        let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
-            .await
-            .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
+    // --once never lingers: without an explicit window it still gives up
+    let wait = args
+        .wait_timeout
+        .map(std::time::Duration::from_secs)
+        .or(args.once.then_some(DEFAULT_ONCE_WAIT));
 
-        let mut relay = match resolved {
-            tallow_net::relay::ResolvedRelay::Addr(addr) => {
-                let mut client = tallow_net::relay::RelayClient::new(addr);
-                client.set_proxy(proxy.clone());
-                client
-            }
-            tallow_net::relay::ResolvedRelay::Hostname { ref host, port } => {
-                tallow_net::relay::RelayClient::new_with_proxy(host, port, proxy.clone())
+    // Establish connection: proxy-aware relay or direct LAN with fallback
+    let connect = async {
+        if network_policy.is_proxied() {
+            // Proxy active: resolve via DoH/hostname, skip LAN discovery entirely
+            let mut relay = network_policy
+                .relay_client(&args.relay)
+                .await
+                .map_err(crate::error::context("Relay resolution failed"))?;
+            if let Some(ref token) = relay_token {
+                relay.set_auth_token(token.clone());
             }
-        };
+            relay.set_tls_policy(crate::commands::proxy::tls_policy(&config.network)?);
 
-        relay
-            .connect(&room_id, pw_ref)
-            .await
-            .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?;
-        if !relay.peer_present() {
             relay
-                .wait_for_peer()
+                .connect(&room_id, pw_ref)
                 .await
-                .map_err(|e| io::Error::other(format!("Waiting for peer failed: {}", e)))?;
-        }
+                .map_err(crate::error::context("Connection failed"))?;
+            if !relay.peer_present() {
+                relay
+                    .wait_for_peer()
+                    .await
+                    .map_err(crate::error::context("Waiting for peer failed"))?;
+            }
 
-        (
-            tallow_net::transport::ConnectionResult::Relay(Box::new(relay)),
-            false,
-        )
-    } else {
-        // No proxy: use direct LAN / relay fallback strategy
-        let relay_addr: std::net::SocketAddr = resolve_relay(&args.relay)?;
-        tallow_net::transport::establish_receiver_connection(
-            &room_id, relay_addr, pw_ref, args.local,
-        )
-        .await
-        .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?
+            Ok::<_, io::Error>((
+                tallow_net::transport::ConnectionResult::Relay(Box::new(relay)),
+                false,
+            ))
+        } else {
+            // No proxy: use direct LAN / relay fallback strategy
+            let relay_addr: std::net::SocketAddr = resolve_relay(&args.relay)?;
+            tallow_net::transport::establish_receiver_connection(
+                &room_id,
+                relay_addr,
+                pw_ref,
+                relay_token.as_deref(),
+                args.local,
+            )
+            .await
+            .map_err(crate::error::context("Connection failed"))
+        }
     };
+    let (mut channel, mut is_direct) = wait_for_sender(connect, wait).await?;
 
     if is_direct {
         if json {
@@ -216,21 +300,49 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     };
 
     // --- KEM Handshake ---
-    let mut handshake = tallow_protocol::kex::ReceiverHandshake::new(&code_phrase, &room_id);
+    let handshake_deadline = tallow_net::transport::HandshakeDeadline::start(
+        std::time::Duration::from_secs(args.handshake_timeout),
+    );
+    let mut handshake =
+        crate::commands::psk::receiver_handshake(&code_phrase, &room_id, psk.as_deref());
 
     // Step 1: Receive HandshakeInit (or detect old protocol)
-    let n = tokio::time::timeout(
-        std::time::Duration::from_secs(30),
-        channel.receive_message(&mut recv_buf),
-    )
-    .await
-    .map_err(|_| io::Error::other("Handshake timeout waiting for init"))?
-    .map_err(|e| io::Error::other(format!("Receive handshake: {}", e)))?;
+    let n = handshake_deadline
+        .step(channel.receive_message(&mut recv_buf))
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::TimedOut, e))?
+        .map_err(crate::error::context("Receive handshake"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
-    let init_msg = codec
+    let mut init_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode handshake: {}", e)))?;
+        .map_err(crate::error::context("Decode handshake"))?;
+
+    // Answer the sender's capability advertisement before its HandshakeInit
+    if let Some(Message::Capabilities { features }) = init_msg {
+        let reply = handshake
+            .process_capabilities(features, tallow_protocol::wire::FeatureSet::local())
+            .map_err(crate::error::context("Handshake capabilities failed"))?;
+        encode_buf.clear();
+        codec
+            .encode_msg(&reply, &mut encode_buf)
+            .map_err(crate::error::context("Encode Capabilities"))?;
+        channel
+            .send_message(&encode_buf)
+            .await
+            .map_err(crate::error::context("Send Capabilities"))?;
+
+        let n = handshake_deadline
+            .step(channel.receive_message(&mut recv_buf))
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::TimedOut, e))?
+            .map_err(crate::error::context("Receive handshake"))?;
+
+        let mut decode_buf = BytesMut::from(&recv_buf[..n]);
+        init_msg = codec
+            .decode_msg(&mut decode_buf)
+            .map_err(crate::error::context("Decode handshake"))?;
+    }
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -244,53 +356,64 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
             // Step 2: Process init -> send HandshakeResponse
             let resp = handshake
                 .process_init(protocol_version, &kem_capabilities, &cpace_public, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake init processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context("Handshake init processing failed"))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&resp, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeResponse: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeResponse"))?;
             channel
                 .send_message(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeResponse: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeResponse"))?;
 
             // Step 3: Receive HandshakeKem
-            let n = tokio::time::timeout(
-                std::time::Duration::from_secs(30),
-                channel.receive_message(&mut recv_buf),
-            )
-            .await
-            .map_err(|_| io::Error::other("Handshake timeout waiting for KEM"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeKem: {}", e)))?;
+            let n = handshake_deadline
+                .step(channel.receive_message(&mut recv_buf))
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::TimedOut, e))?
+                .map_err(crate::error::context("Receive HandshakeKem"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let kem_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeKem"))?;
 
             match kem_msg {
                 Some(Message::HandshakeKem {
                     kem_ciphertext,
                     confirmation,
                 }) => {
-                    let (complete_msg, session_key_result) = handshake
-                        .process_kem(&kem_ciphertext, &confirmation)
-                        .map_err(|e| io::Error::other(format!("Handshake KEM failed: {}", e)))?;
+                    // Tell the sender right away rather than leaving it to
+                    // time out waiting for HandshakeComplete
+                    let (complete_msg, session_key_result) =
+                        match handshake.process_kem(&kem_ciphertext, &confirmation) {
+                            Ok(result) => result,
+                            Err(e) => {
+                                encode_buf.clear();
+                                if codec
+                                    .encode_msg(
+                                        &tallow_protocol::kex::handshake_failed_message(),
+                                        &mut encode_buf,
+                                    )
+                                    .is_ok()
+                                {
+                                    let _ = channel.send_message(&encode_buf).await;
+                                }
+                                channel.close().await;
+                                return Err(crate::error::context("Handshake KEM failed")(e));
+                            }
+                        };
 
                     // Step 4: Send HandshakeComplete
                     encode_buf.clear();
                     codec
                         .encode_msg(&complete_msg, &mut encode_buf)
-                        .map_err(|e| {
-                            io::Error::other(format!("Encode HandshakeComplete: {}", e))
-                        })?;
+                        .map_err(crate::error::context("Encode HandshakeComplete"))?;
                     channel
                         .send_message(&encode_buf)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send HandshakeComplete: {}", e)))?;
+                        .map_err(crate::error::context("Send HandshakeComplete"))?;
 
                     session_key = session_key_result;
                 }
@@ -336,14 +459,15 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     // --- P2P Direct Connection Upgrade ---
     // Attempt to upgrade from relay to direct P2P QUIC after handshake.
     // Skip when: proxy active, --no-p2p set, already direct (LAN)
-    if !is_direct && proxy_config.is_none() && !args.no_p2p {
+    let p2p_allowed = crate::commands::proxy::p2p_allowed(&network_policy, args.no_p2p);
+    if !is_direct && p2p_allowed {
         if !json {
             output::color::info("Attempting P2P direct connection...");
         }
 
         // Receiver = responder (QUIC server role)
         // Pass the combined suppression flag as defense-in-depth guard.
-        let suppress_p2p = proxy_config.is_some() || args.no_p2p;
+        let suppress_p2p = !p2p_allowed;
         match tallow_net::transport::negotiate_p2p(&mut channel, false, suppress_p2p).await {
             tallow_net::transport::NegotiationResult::Direct(direct_conn) => {
                 if json {
@@ -381,31 +505,60 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 }
             }
         }
-    } else if proxy_config.is_some() || args.no_p2p {
+    } else if !p2p_allowed {
         tracing::debug!(
             "P2P disabled: proxy={}, no_p2p={}",
-            proxy_config.is_some(),
+            network_policy.is_proxied(),
             args.no_p2p
         );
     }
     // --- End P2P Upgrade ---
 
-    // Receive FileOffer
-    let n = channel
-        .receive_message(&mut recv_buf)
-        .await
-        .map_err(|e| io::Error::other(format!("Receive FileOffer failed: {}", e)))?;
+    // Receive FileOffer, answering a bandwidth probe if the sender runs one
+    let mut probe = tallow_protocol::transfer::ProbeReceiver::new();
+    let mut probed = false;
+    let offer_msg = loop {
+        let n = channel
+            .receive_message(&mut recv_buf)
+            .await
+            .map_err(|e| io::Error::other(format!("Receive FileOffer failed: {}", e)))?;
 
-    let mut decode_buf = BytesMut::from(&recv_buf[..n]);
-    let offer_msg = codec
-        .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode FileOffer failed: {}", e)))?;
+        let mut decode_buf = BytesMut::from(&recv_buf[..n]);
+        let msg = codec
+            .decode_msg(&mut decode_buf)
+            .map_err(|e| io::Error::other(format!("Decode FileOffer failed: {}", e)))?;
+        let Some(msg @ Message::ProbeData { .. }) = msg else {
+            break msg;
+        };
+        if !probed && !json {
+            output::color::info(
+                "Sender is probing bandwidth (test data, not part of the transfer)...",
+            );
+        }
+        probed = true;
+        if let Some(ack) = probe.record(&msg) {
+            encode_buf.clear();
+            codec
+                .encode_msg(&ack, &mut encode_buf)
+                .map_err(|e| io::Error::other(format!("Encode probe ack failed: {}", e)))?;
+            channel
+                .send_message(&encode_buf)
+                .await
+                .map_err(|e| io::Error::other(format!("Send probe ack failed: {}", e)))?;
+        }
+    };
 
     let (transfer_id, manifest_bytes) = match offer_msg {
         Some(Message::FileOffer {
             transfer_id,
             manifest,
         }) => (transfer_id, manifest),
+        Some(Message::HandshakeFailed { reason }) => {
+            channel.close().await;
+            return Err(crate::error::context("Sender aborted the handshake")(
+                tallow_protocol::ProtocolError::HandshakeFailed(reason),
+            ));
+        }
         other => {
             let msg = format!("Expected FileOffer, got: {:?}", other);
             channel.close().await;
@@ -413,12 +566,52 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         }
     };
 
-    // Initialize receive pipeline with handshake-derived session key
+    // Handshake-derived session key, bound to the caller's context label
+    // if one was given
+    let transfer_key = match args.context {
+        Some(ref label) => *session_key
+            .for_context(label)
+            .map_err(|e| io::Error::other(format!("Derive transfer key: {}", e)))?
+            .as_bytes(),
+        None => *session_key.as_bytes(),
+    };
+
+    // Initialize receive pipeline with the transfer key
     let mut pipeline = tallow_protocol::transfer::ReceivePipeline::new(
         transfer_id,
         output_dir.clone(),
-        *session_key.as_bytes(),
+        transfer_key,
+    )
+    .with_write_config(tallow_protocol::transfer::WriteConfig {
+        preallocate: true,
+        direct_io: config.transfer.direct_io,
+    })
+    .with_checkpoint_path(
+        tallow_store::persistence::data_dir()
+            .join("checkpoints")
+            .join(format!("{}.checkpoint", hex::encode(transfer_id))),
     );
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::CHUNK_RETRANSMIT)
+    {
+        pipeline = pipeline.with_chunk_retransmit(
+            tallow_protocol::transfer::retransmit::DEFAULT_MAX_CHUNK_RETRIES,
+        );
+    }
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::CUMULATIVE_ACK)
+    {
+        pipeline = pipeline.with_cumulative_ack(CUMULATIVE_ACK_INTERVAL);
+    }
+    // Negotiated only when the sender asked for chunk checksums
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::CHUNK_CHECKSUM)
+    {
+        pipeline = pipeline.with_chunk_checksum(true);
+    }
 
     // Check for resume from a previous interrupted transfer
     if let Some(ref resume_id) = args.resume_id {
@@ -464,7 +657,32 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     // Process the offer
     let manifest = pipeline
         .process_offer(&manifest_bytes)
-        .map_err(|e| io::Error::other(format!("Failed to process offer: {}", e)))?;
+        .map_err(|e| io::Error::other(format!("Failed to process offer: {}", e)))?
+        .clone();
+
+    // A sender that names paths outside the output directory is either
+    // broken or hostile; refuse instead of guessing where files belong
+    if let Some(path) = pipeline.unsafe_paths().first() {
+        let safe_path =
+            tallow_protocol::transfer::sanitize::sanitize_display(&path.to_string_lossy());
+        let reject_msg = Message::FileReject {
+            transfer_id,
+            reason: "manifest path escapes the output directory".to_string(),
+        };
+        encode_buf.clear();
+        codec
+            .encode_msg(&reject_msg, &mut encode_buf)
+            .map_err(|e| io::Error::other(format!("Encode FileReject failed: {}", e)))?;
+        channel
+            .send_message(&encode_buf)
+            .await
+            .map_err(|e| io::Error::other(format!("Send FileReject failed: {}", e)))?;
+        channel.close().await;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Transfer rejected: unsafe path '{}' in manifest", safe_path),
+        ));
+    }
 
     let total_size = manifest.total_size;
     let total_chunks = manifest.total_chunks;
@@ -476,6 +694,13 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         .collect();
 
     let is_text_transfer = manifest.transfer_type == TransferType::Text;
+    let is_stream_transfer = manifest.transfer_type == TransferType::Stream;
+    // Text is shown rather than saved, so it is never split
+    if let (Some(size), false) = (split_size, is_text_transfer) {
+        pipeline = pipeline.with_split_size(size);
+    }
+    // Streams go straight to stdout when it is piped
+    let stream_to_stdout = is_stream_transfer && !json && !std::io::stdout().is_terminal();
 
     if json {
         println!(
@@ -487,7 +712,16 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 "total_bytes": total_size,
                 "total_chunks": total_chunks,
                 "files": filenames,
+                "skipped": manifest
+                    .skipped
+                    .iter()
+                    .map(|s| serde_json::json!({
+                        "path": s.path.display().to_string(),
+                        "reason": s.reason,
+                    }))
+                    .collect::<Vec<_>>(),
                 "text_transfer": is_text_transfer,
+                "stream_transfer": is_stream_transfer,
             })
         );
     } else {
@@ -497,6 +731,8 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 "Incoming text transfer ({})",
                 output::format_size(total_size)
             ));
+        } else if is_stream_transfer {
+            output::color::info("Incoming stream (size unknown until it ends)");
         } else {
             output::color::section("Incoming transfer:");
             for entry in manifest.files.iter() {
@@ -506,46 +742,169 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 output::color::file_entry(&safe_name, entry.size);
             }
             output::color::transfer_summary(file_count, total_size);
+            if !manifest.skipped.is_empty() {
+                output::color::warning(&format!(
+                    "The sender couldn't read {} file(s), which won't arrive:",
+                    manifest.skipped.len()
+                ));
+                for skipped in &manifest.skipped {
+                    eprintln!(
+                        "   {}",
+                        tallow_protocol::transfer::sanitize::sanitize_display(&skipped.to_string())
+                    );
+                }
+            }
         }
         println!();
     }
 
-    // Check for existing files (overwrite protection)
-    if !is_text_transfer && !args.overwrite {
-        let mut conflicts = Vec::new();
-        for entry in manifest.files.iter() {
-            let target = output_dir.join(&entry.path);
-            if target.exists() {
-                conflicts.push(target);
-            }
+    // --to-clipboard only takes small text payloads; refuse anything else
+    // before a byte is transferred
+    if args.to_clipboard {
+        let refusal = if is_text_transfer {
+            tallow_store::clipboard::check_clipboard_size(
+                total_size,
+                tallow_store::clipboard::MAX_CLIPBOARD_RECEIVE_SIZE,
+            )
+            .err()
+        } else {
+            Some(tallow_store::clipboard::ClipboardRefusal::NotText)
+        };
+        if let Some(refusal) = refusal {
+            let reject_msg = Message::FileReject {
+                transfer_id,
+                reason: "receiver only accepts small text for the clipboard".to_string(),
+            };
+            encode_buf.clear();
+            codec
+                .encode_msg(&reject_msg, &mut encode_buf)
+                .map_err(|e| io::Error::other(format!("Encode FileReject failed: {}", e)))?;
+            channel
+                .send_message(&encode_buf)
+                .await
+                .map_err(|e| io::Error::other(format!("Send FileReject failed: {}", e)))?;
+            channel.close().await;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Transfer declined: {}", refusal),
+            ));
         }
-        if !conflicts.is_empty() && !json {
-            output::color::warning("The following files already exist:");
-            for path in &conflicts {
-                println!("  {}", path.display());
+    }
+
+    // Resolve output names (template + conflict policy) and check for
+    // existing files (overwrite protection)
+    let mut output_names: Option<Vec<OutputName>> = None;
+    if !is_text_transfer && !stream_to_stdout {
+        let layout = if args.preserve_structure {
+            DirectoryLayout::Preserve
+        } else {
+            DirectoryLayout::Flatten
+        };
+        let originals: Vec<PathBuf> = manifest
+            .files
+            .iter()
+            .map(|f| layout.apply(&f.path))
+            .collect();
+        let ctx = naming::TemplateContext {
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            // Peers are anonymous; the first verification group identifies
+            // this session on both ends
+            sender: output::verify::numeric_verification(session_key.as_bytes())
+                .split(' ')
+                .next()
+                .unwrap_or("peer")
+                .to_string(),
+        };
+        // Without a policy, resolve as if overwriting and ask below
+        let names = naming::resolve_output_names(
+            &originals,
+            output_template.as_ref(),
+            &ctx,
+            conflict_policy.unwrap_or(ConflictPolicy::Overwrite),
+            |p| output_dir.join(p).exists(),
+        )
+        .map_err(|e| io::Error::other(format!("Failed to resolve output names: {}", e)))?;
+
+        if conflict_policy.is_none() {
+            let conflicts: Vec<PathBuf> = names
+                .iter()
+                .filter_map(|n| match n {
+                    OutputName::Write(p) => Some(output_dir.join(p)),
+                    OutputName::Skip => None,
+                })
+                .filter(|p| p.exists())
+                .collect();
+            if !conflicts.is_empty() && !json {
+                output::color::warning("The following files already exist:");
+                for path in &conflicts {
+                    println!("  {}", path.display());
+                }
+                if !auto_accept {
+                    let overwrite =
+                        output::prompts::confirm_with_default("Overwrite existing files?", false)?;
+                    if !overwrite {
+                        let reject_msg = Message::FileReject {
+                            transfer_id,
+                            reason: "file conflict -- receiver declined overwrite".to_string(),
+                        };
+                        encode_buf.clear();
+                        codec
+                            .encode_msg(&reject_msg, &mut encode_buf)
+                            .map_err(|e| io::Error::other(format!("Encode reject: {}", e)))?;
+                        channel
+                            .send_message(&encode_buf)
+                            .await
+                            .map_err(|e| io::Error::other(format!("Send reject: {}", e)))?;
+                        channel.close().await;
+                        output::color::info("Transfer declined due to file conflicts.");
+                        return Ok(());
+                    }
+                }
             }
-            if !args.yes && !args.auto_accept {
-                let overwrite =
-                    output::prompts::confirm_with_default("Overwrite existing files?", false)?;
-                if !overwrite {
-                    let reject_msg = Message::FileReject {
-                        transfer_id,
-                        reason: "file conflict -- receiver declined overwrite".to_string(),
-                    };
-                    encode_buf.clear();
-                    codec
-                        .encode_msg(&reject_msg, &mut encode_buf)
-                        .map_err(|e| io::Error::other(format!("Encode reject: {}", e)))?;
-                    channel
-                        .send_message(&encode_buf)
-                        .await
-                        .map_err(|e| io::Error::other(format!("Send reject: {}", e)))?;
-                    channel.close().await;
-                    output::color::info("Transfer declined due to file conflicts.");
-                    return Ok(());
+        } else if !json {
+            for (original, name) in originals.iter().zip(&names) {
+                let safe_name = tallow_protocol::transfer::sanitize::sanitize_display(
+                    &original.display().to_string(),
+                );
+                match name {
+                    OutputName::Skip => {
+                        output::color::info(&format!("Skipping {} (already exists)", safe_name))
+                    }
+                    OutputName::Write(p) if p != original => {
+                        output::color::info(&format!("Saving {} as {}", safe_name, p.display()))
+                    }
+                    OutputName::Write(_) => {}
                 }
             }
         }
+        output_names = Some(names);
+    }
+    let skipped: Vec<u32> = output_names
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, n)| **n == OutputName::Skip)
+        .map(|(i, _)| i as u32)
+        .collect();
+
+    if !skipped.is_empty() && skipped.len() == file_count {
+        let reject_msg = Message::FileReject {
+            transfer_id,
+            reason: "all files already exist on the receiver".to_string(),
+        };
+        encode_buf.clear();
+        codec
+            .encode_msg(&reject_msg, &mut encode_buf)
+            .map_err(|e| io::Error::other(format!("Encode FileReject failed: {}", e)))?;
+        channel
+            .send_message(&encode_buf)
+            .await
+            .map_err(|e| io::Error::other(format!("Send FileReject failed: {}", e)))?;
+        channel.close().await;
+        if !json {
+            output::color::info("All files already exist. Transfer declined.");
+        }
+        return Ok(());
     }
 
     // --- Per-file selection or whole-transfer accept/reject ---
@@ -605,12 +964,74 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 ));
                 Some(indices)
             }
+        } else if !args.accept.is_empty() && !is_text_transfer && file_count > 1 {
+            // Non-interactive selection by --accept patterns
+            let mask = AcceptanceMask::from_globs(&manifest, &args.accept)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+            if mask.is_empty() {
+                let reject_msg = Message::FileReject {
+                    transfer_id,
+                    reason: "receiver selected no files".to_string(),
+                };
+                encode_buf.clear();
+                codec
+                    .encode_msg(&reject_msg, &mut encode_buf)
+                    .map_err(|e| io::Error::other(format!("Encode FileReject failed: {}", e)))?;
+                channel
+                    .send_message(&encode_buf)
+                    .await
+                    .map_err(|e| io::Error::other(format!("Send FileReject failed: {}", e)))?;
+                channel.close().await;
+                if !json {
+                    output::color::info("No files matched --accept. Transfer declined.");
+                }
+                return Ok(());
+            }
+
+            if mask.is_all() {
+                None
+            } else {
+                if !json {
+                    output::color::info(&format!(
+                        "Accepting {}/{} file(s) matching --accept",
+                        mask.len(),
+                        file_count
+                    ));
+                }
+                Some(mask.indices())
+            }
         } else {
             None
         };
 
-    // Prompt for confirmation unless --yes or --auto-accept
-    let accepted = if args.yes || args.auto_accept {
+    if probed {
+        let estimate = tallow_protocol::transfer::estimate::estimate(
+            total_size,
+            probe.ratio_hint(),
+            &probe.sample(),
+        );
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "event": "transfer_estimate",
+                    "probe_bytes": probe.sample().bytes(),
+                    "bytes_per_sec": estimate.bytes_per_sec,
+                    "estimated_wire_bytes": estimate.wire_bytes,
+                    "estimated_seconds": estimate.duration.map(|d| d.as_secs_f64()),
+                })
+            );
+        } else {
+            output::color::info(&format!(
+                "Estimated transfer time: {}",
+                output::format_estimate(&estimate)
+            ));
+        }
+    }
+
+    // Prompt for confirmation unless --yes, --auto-accept or --once
+    let accepted = if auto_accept {
         true
     } else if json {
         // JSON mode cannot prompt interactively; require --yes
@@ -618,7 +1039,7 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
             io::ErrorKind::InvalidInput,
             "JSON mode requires --yes flag to accept transfers",
         ));
-    } else if selected_indices.is_some() {
+    } else if args.per_file && selected_indices.is_some() {
         // Per-file mode: user already chose files, skip extra confirmation
         true
     } else {
@@ -627,6 +1048,8 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 "Accept {} ({})?",
                 if is_text_transfer {
                     "text transfer".to_string()
+                } else if is_stream_transfer {
+                    "stream".to_string()
                 } else {
                     format!("{} file(s)", file_count)
                 },
@@ -657,8 +1080,27 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         return Ok(());
     }
 
+    // Files skipped by --on-conflict are left out of the selection
+    let selected_indices = if skipped.is_empty() {
+        selected_indices
+    } else {
+        Some(
+            selected_indices
+                .unwrap_or_else(|| (0..file_count as u32).collect())
+                .into_iter()
+                .filter(|i| !skipped.contains(i))
+                .collect(),
+        )
+    };
+
     // Send FileSelection if the receiver chose a subset, then FileAccept
     if let Some(ref indices) = selected_indices {
+        // Chunks will be numbered over the selected files only
+        let mask = AcceptanceMask::from_indices(&manifest, indices)
+            .map_err(|e| io::Error::other(format!("Invalid file selection: {}", e)))?;
+        pipeline
+            .accept_files(mask)
+            .map_err(|e| io::Error::other(format!("Failed to apply file selection: {}", e)))?;
         let selection_msg = Message::FileSelection {
             transfer_id,
             selected_indices: indices.clone(),
@@ -692,6 +1134,106 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         }
     }
 
+    // --sandbox strict: setup is done, confine the rest of the transfer
+    crate::sandbox::lockdown(&crate::sandbox::SandboxConfig::for_transfer(&output_dir))
+        .map_err(|e| io::Error::other(format!("Sandbox lockdown failed: {}", e)))?;
+
+    if is_stream_transfer {
+        let stream_path = if stream_to_stdout {
+            None
+        } else {
+            let name = match output_names.as_ref().and_then(|n| n.first()) {
+                Some(OutputName::Write(name)) => name.clone(),
+                _ => manifest
+                    .files
+                    .first()
+                    .map(|f| f.path.clone())
+                    .unwrap_or_else(|| PathBuf::from("stdin")),
+            };
+            Some(output_dir.join(name))
+        };
+        let manifest = manifest.clone();
+        let transfer_start = std::time::Instant::now();
+
+        let result = receive_stream(
+            &mut channel,
+            &mut codec,
+            &mut encode_buf,
+            &mut recv_buf,
+            &reconnect_config,
+            transfer_id,
+            transfer_key,
+            &manifest,
+            stream_path.as_deref(),
+        )
+        .await;
+        channel.close().await;
+
+        let result = match (result, &checksums, &stream_path) {
+            (Ok(bytes), Some(list), Some(path)) => {
+                verify_checksums(list, &output_dir, std::slice::from_ref(path), json)
+                    .map(|()| bytes)
+            }
+            (result, _, _) => result,
+        };
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if args.notify && !json {
+                    output::notifications::notify_transfer_failed(&e.to_string());
+                }
+                return Err(e);
+            }
+        };
+
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "event": "transfer_complete",
+                    "total_bytes": bytes,
+                    "files": stream_path.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+                })
+            );
+        } else if let Some(ref path) = stream_path {
+            output::color::transfer_complete(bytes, transfer_start.elapsed());
+            println!("  Saved: {}", path.display());
+        }
+
+        if let Ok(mut history) = tallow_store::history::TransferLog::open(identity.keypair()) {
+            let _ = history.append(tallow_store::history::TransferEntry {
+                id: hex::encode(transfer_id),
+                peer_id: "unknown".to_string(),
+                direction: tallow_store::history::TransferDirection::Received,
+                file_count,
+                total_bytes: bytes,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                status: tallow_store::history::TransferStatus::Completed,
+                filenames: filenames.clone(),
+                context: args.context.clone(),
+                duration_ms: Some(transfer_start.elapsed().as_millis() as u64),
+                cipher: Some(tallow_protocol::transfer::chunking::CHUNK_CIPHER.to_string()),
+            });
+        }
+        completion.succeeded(
+            stream_path
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect(),
+            bytes,
+        );
+        return Ok(());
+    }
+
+    if let Some(names) = output_names {
+        pipeline
+            .set_output_names(names)
+            .map_err(|e| io::Error::other(format!("Failed to set output names: {}", e)))?;
+    }
+
     // Create progress bar
     let transfer_start = std::time::Instant::now();
     let progress = output::TransferProgressBar::new(total_size);
@@ -701,13 +1243,35 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     loop {
         let n = reconnect::receive_with_retry(&mut channel, &mut recv_buf, &reconnect_config)
             .await
-            .map_err(|e| io::Error::other(format!("Receive chunk failed: {}", e)))?;
+            .map_err(|e| crate::exit_codes::network_error("Receive chunk failed", e))?;
 
         let mut chunk_buf = BytesMut::from(&recv_buf[..n]);
         let msg = codec
             .decode_msg(&mut chunk_buf)
             .map_err(|e| io::Error::other(format!("Decode chunk failed: {}", e)))?;
 
+        // Multiplexed senders address chunks per file; map them to the
+        // transfer-wide index so the rest of the loop handles both alike
+        let msg = match msg {
+            Some(Message::FileChunk {
+                transfer_id,
+                file_id,
+                index,
+                data,
+            }) => {
+                let index = pipeline
+                    .file_chunk_index(file_id, index)
+                    .map_err(|e| pipeline_error(format!("Route chunk of file {}", file_id), e))?;
+                Some(Message::Chunk {
+                    transfer_id,
+                    index,
+                    total: None,
+                    data,
+                })
+            }
+            other => other,
+        };
+
         match msg {
             Some(Message::Chunk {
                 index, total, data, ..
@@ -715,9 +1279,12 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 let chunk_size = data.len() as u64;
 
                 // Process the chunk (decrypt, store)
-                let ack = pipeline.process_chunk(index, &data, total).map_err(|e| {
-                    io::Error::other(format!("Process chunk {} failed: {}", index, e))
-                })?;
+                let ack = pipeline
+                    .process_chunk(index, &data, total)
+                    .map_err(|e| pipeline_error(format!("Process chunk {} failed", index), e))?;
+
+                // A chunk that failed authentication is re-requested, not counted
+                let resend_requested = matches!(ack, Some(Message::ResendChunks { .. }));
 
                 // Send acknowledgment (with retry)
                 if let Some(ack_msg) = ack {
@@ -727,9 +1294,21 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                         .map_err(|e| io::Error::other(format!("Encode ack failed: {}", e)))?;
                     reconnect::send_with_retry(&mut channel, &encode_buf, &reconnect_config)
                         .await
-                        .map_err(|e| io::Error::other(format!("Send ack failed: {}", e)))?;
+                        .map_err(|e| crate::exit_codes::network_error("Send ack failed", e))?;
+                }
+                if let Some(cumulative) = pipeline.cumulative_ack() {
+                    encode_buf.clear();
+                    codec
+                        .encode_msg(&cumulative, &mut encode_buf)
+                        .map_err(|e| io::Error::other(format!("Encode ack failed: {}", e)))?;
+                    reconnect::send_with_retry(&mut channel, &encode_buf, &reconnect_config)
+                        .await
+                        .map_err(|e| crate::exit_codes::network_error("Send ack failed", e))?;
                 }
 
+                if resend_requested {
+                    continue;
+                }
                 bytes_received += chunk_size;
                 progress.update(bytes_received.min(total_size));
 
@@ -800,7 +1379,21 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     let written_files = pipeline
         .finalize()
         .await
-        .map_err(|e| io::Error::other(format!("Finalize failed: {}", e)))?;
+        .map_err(|e| pipeline_error("Finalize failed".to_string(), e))?;
+
+    if let Some(ref list) = checksums {
+        if is_text_transfer {
+            if !json {
+                output::color::warning("Text transfer: --checksum-file not applied");
+            }
+        } else if let Err(e) = verify_checksums(list, &output_dir, &written_files, json) {
+            if args.notify && !json {
+                output::notifications::notify_transfer_failed(&e.to_string());
+            }
+            channel.close().await;
+            return Err(e);
+        }
+    }
 
     // Clean up checkpoint on success
     let checkpoint_path = tallow_store::persistence::data_dir()
@@ -814,7 +1407,14 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     // Handle text transfers vs file transfers
     let is_stdout_pipe = !std::io::stdout().is_terminal();
 
-    if is_text_transfer {
+    if is_text_transfer && args.to_clipboard {
+        let text_path = output_dir.join("_tallow_text_");
+        let content = tokio::fs::read(&text_path)
+            .await
+            .map_err(|e| io::Error::other(format!("Read text content: {}", e)))?;
+        let _ = tokio::fs::remove_file(&text_path).await;
+        receive_to_clipboard(&content, json)?;
+    } else if is_text_transfer {
         // Text transfer: read the virtual file and output to terminal/stdout
         let text_path = output_dir.join("_tallow_text_");
         if text_path.exists() {
@@ -873,6 +1473,12 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
         for f in &written_files {
             println!("  Saved: {}", f.display());
         }
+        if written_files.iter().any(|f| {
+            f.to_string_lossy()
+                .ends_with(tallow_protocol::transfer::volumes::VOLUME_MANIFEST_SUFFIX)
+        }) {
+            output::color::info("Reassemble split files with: tallow join <file>.volumes.json");
+        }
     }
 
     // Desktop notification (opt-in via --notify, suppressed in JSON mode)
@@ -885,7 +1491,7 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     }
 
     // Log to transfer history
-    if let Ok(mut history) = tallow_store::history::TransferLog::open() {
+    if let Ok(mut history) = tallow_store::history::TransferLog::open(identity.keypair()) {
         let _ = history.append(tallow_store::history::TransferEntry {
             id: hex::encode(transfer_id),
             peer_id: "unknown".to_string(),
@@ -898,6 +1504,9 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
                 .as_secs(),
             status: tallow_store::history::TransferStatus::Completed,
             filenames: filenames.clone(),
+            context: args.context.clone(),
+            duration_ms: Some(transfer_start.elapsed().as_millis() as u64),
+            cipher: Some(tallow_protocol::transfer::chunking::CHUNK_CIPHER.to_string()),
         });
     }
 
@@ -913,6 +1522,7 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
             code_phrase: Some(code_phrase.clone()),
             ..Default::default()
         };
+        completion.succeeded(hook_env.files.clone(), total_size);
         hook_runner
             .run_hook(crate::hooks::HookType::PostReceive, &hook_env)
             .await?;
@@ -921,6 +1531,231 @@ pub async fn execute(args: ReceiveArgs, json: bool) -> io::Result<()> {
     Ok(())
 }
 
+/// Wait for `connect` to reach a sender, giving up after `wait` if set
+///
+/// Running out of time fails with [`TallowError::NoSender`], which exits
+/// with [`crate::exit_codes::NO_SENDER`] rather than a network error.
+///
+/// [`TallowError::NoSender`]: crate::error::TallowError::NoSender
+async fn wait_for_sender<T>(
+    connect: impl std::future::Future<Output = io::Result<T>>,
+    wait: Option<std::time::Duration>,
+) -> io::Result<T> {
+    match wait {
+        Some(wait) => tokio::time::timeout(wait, connect)
+            .await
+            .map_err(|_| crate::error::classified(crate::error::TallowError::NoSender(wait)))?,
+        None => connect.await,
+    }
+}
+
+/// Check received files against an external checksum file
+///
+/// Files the listing does not mention are left unchecked. On a mismatch
+/// the files stay on disk but the transfer is reported as failed.
+fn verify_checksums(
+    list: &ChecksumList,
+    output_dir: &std::path::Path,
+    files: &[PathBuf],
+    json: bool,
+) -> io::Result<()> {
+    let verified = list.verify_files(output_dir, files).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Checksum verification failed: {}", e),
+        )
+    })?;
+    if !json {
+        output::color::success(&format!(
+            "{} of {} file(s) match the checksum file",
+            verified,
+            files.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Place received text on the clipboard and record it in clipboard history
+fn receive_to_clipboard(content: &[u8], json: bool) -> io::Result<()> {
+    use tallow_store::clipboard::{ClipboardEntry, ClipboardHistory, MAX_CLIPBOARD_RECEIVE_SIZE};
+
+    let entry = ClipboardEntry::from_received_text(content, MAX_CLIPBOARD_RECEIVE_SIZE)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}", e)))?;
+    if let Some(ref text) = entry.text_content {
+        output::clipboard::copy_to_clipboard(text);
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "clipboard_received",
+                "type": format!("{}", entry.content_type),
+                "size": entry.size,
+                "preview": entry.preview,
+            })
+        );
+    } else {
+        output::color::success("Text copied to clipboard");
+        output::color::section(&format!(
+            "  {}",
+            tallow_protocol::transfer::sanitize::sanitize_display(&entry.preview)
+        ));
+    }
+
+    match ClipboardHistory::open_with_config() {
+        Ok(mut history) => {
+            if let Err(e) = history.append(entry) {
+                tracing::warn!("Failed to log to clipboard history: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to open clipboard history: {}", e),
+    }
+    Ok(())
+}
+
+/// Receive a stream transfer into `path`, or stdout when `path` is `None`
+///
+/// Returns the number of bytes written. A file left behind by a failed or
+/// unverified stream is removed.
+#[allow(clippy::too_many_arguments)]
+async fn receive_stream(
+    channel: &mut tallow_net::transport::ConnectionResult,
+    codec: &mut TallowCodec,
+    encode_buf: &mut BytesMut,
+    recv_buf: &mut [u8],
+    retry_config: &ReconnectConfig,
+    transfer_id: [u8; 16],
+    session_key: [u8; 32],
+    manifest: &tallow_protocol::transfer::FileManifest,
+    path: Option<&std::path::Path>,
+) -> io::Result<u64> {
+    let writer: Box<dyn tokio::io::AsyncWrite + Unpin + Send> = match path {
+        Some(path) => Box::new(tokio::io::BufWriter::new(
+            tokio::fs::File::create(path).await?,
+        )),
+        None => Box::new(tokio::io::stdout()),
+    };
+
+    let result = match tallow_protocol::transfer::StreamReceiver::new(
+        transfer_id,
+        session_key,
+        manifest,
+        writer,
+    ) {
+        Ok(mut receiver) => {
+            stream_chunks(
+                channel,
+                codec,
+                encode_buf,
+                recv_buf,
+                retry_config,
+                &mut receiver,
+            )
+            .await
+        }
+        Err(e) => Err(io::Error::other(format!("Stream setup failed: {}", e))),
+    };
+
+    if result.is_err() {
+        if let Some(path) = path {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+    result
+}
+
+/// Feed stream chunks into `receiver` until the sender completes
+///
+/// Fails unless a valid `StreamEnd` trailer arrived before `TransferComplete`.
+async fn stream_chunks<W: tokio::io::AsyncWrite + Unpin>(
+    channel: &mut tallow_net::transport::ConnectionResult,
+    codec: &mut TallowCodec,
+    encode_buf: &mut BytesMut,
+    recv_buf: &mut [u8],
+    retry_config: &ReconnectConfig,
+    receiver: &mut tallow_protocol::transfer::StreamReceiver<W>,
+) -> io::Result<u64> {
+    let mut verified = false;
+
+    loop {
+        let n = reconnect::receive_with_retry(channel, recv_buf, retry_config)
+            .await
+            .map_err(|e| crate::exit_codes::network_error("Receive chunk failed", e))?;
+
+        let mut chunk_buf = BytesMut::from(&recv_buf[..n]);
+        let msg = codec
+            .decode_msg(&mut chunk_buf)
+            .map_err(|e| io::Error::other(format!("Decode chunk failed: {}", e)))?;
+
+        match msg {
+            Some(Message::Chunk { index, data, .. }) => {
+                let ack = receiver.process_chunk(index, &data).await.map_err(|e| {
+                    io::Error::other(format!("Process chunk {} failed: {}", index, e))
+                })?;
+                encode_buf.clear();
+                codec
+                    .encode_msg(&ack, encode_buf)
+                    .map_err(|e| io::Error::other(format!("Encode ack failed: {}", e)))?;
+                reconnect::send_with_retry(channel, encode_buf, retry_config)
+                    .await
+                    .map_err(|e| crate::exit_codes::network_error("Send ack failed", e))?;
+            }
+            Some(Message::StreamEnd { trailer, .. }) => {
+                receiver
+                    .finish(&trailer, None)
+                    .await
+                    .map_err(|e| io::Error::other(format!("Stream verification failed: {}", e)))?;
+                verified = true;
+            }
+            Some(Message::TransferComplete { .. }) => break,
+            Some(Message::TransferError { error, .. }) => {
+                let safe_error = tallow_protocol::transfer::sanitize::sanitize_display(&error);
+                return Err(io::Error::other(format!(
+                    "Transfer error from sender: {}",
+                    safe_error
+                )));
+            }
+            other => {
+                tracing::warn!("Unexpected message during stream: {:?}", other);
+            }
+        }
+    }
+
+    if !verified {
+        return Err(io::Error::other(
+            "Stream ended without a signed trailer; output is unverified",
+        ));
+    }
+    Ok(receiver.bytes_written())
+}
+
+/// Parse a `--split-size` value (e.g. "3G", "700MB") into bytes
+fn parse_split_size(s: &str) -> io::Result<u64> {
+    let size: bytesize::ByteSize = s.parse().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid split size '{}': {}. Examples: '3G', '700MB'", s, e),
+        )
+    })?;
+    let min = tallow_protocol::transfer::volumes::MIN_VOLUME_SIZE;
+    if size.as_u64() < min {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Split size must be at least {} bytes", min),
+        ));
+    }
+    Ok(size.as_u64())
+}
+
+/// Convert a receive pipeline error, keeping a full disk distinguishable
+///
+/// Disk-full errors keep their `StorageFull` kind so the CLI exits with
+/// [`crate::exit_codes::DISK_FULL`] and the user is told how to resume.
+fn pipeline_error(context: String, e: tallow_protocol::ProtocolError) -> io::Error {
+    crate::error::context(context)(e)
+}
+
 /// Resolve a relay address string to a SocketAddr
 fn resolve_relay(relay: &str) -> io::Result<std::net::SocketAddr> {
     // Try parsing as a direct SocketAddr first
@@ -941,3 +1776,68 @@ fn resolve_relay(relay: &str) -> io::Result<std::net::SocketAddr> {
         .next()
         .ok_or_else(|| io::Error::other(format!("No addresses found for relay '{}'", relay)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exit_codes;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_for_sender_times_out() {
+        let err = wait_for_sender(
+            std::future::pending::<io::Result<()>>(),
+            Some(Duration::from_millis(20)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(exit_codes::for_error(&err), exit_codes::NO_SENDER);
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(
+            exit_codes::classification(exit_codes::NO_SENDER),
+            "no_sender"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sender_passes_through() {
+        let connected = wait_for_sender(async { Ok(7) }, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(connected, 7);
+
+        // A failed connection keeps its own classification
+        let err = wait_for_sender::<()>(
+            async { Err(io::Error::from(io::ErrorKind::ConnectionRefused)) },
+            Some(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(exit_codes::for_error(&err), exit_codes::NETWORK_ERROR);
+
+        // Without a window the receiver waits as long as it takes
+        assert!(wait_for_sender(async { Ok(()) }, None).await.is_ok());
+    }
+
+    #[test]
+    fn test_once_flags() {
+        use clap::Parser;
+        let cli = crate::cli::Cli::try_parse_from(["tallow", "receive", "--once", "1-alpha-beta"])
+            .unwrap();
+        let crate::cli::Commands::Receive(args) = cli.command else {
+            panic!("expected receive");
+        };
+        assert!(args.once);
+        assert_eq!(args.wait_timeout, None);
+
+        // Per-file selection prompts, which --once can't do
+        assert!(crate::cli::Cli::try_parse_from([
+            "tallow",
+            "receive",
+            "--once",
+            "--per-file",
+            "1-alpha-beta"
+        ])
+        .is_err());
+    }
+}
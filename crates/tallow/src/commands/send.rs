@@ -5,8 +5,11 @@ use crate::output;
 use bytes::BytesMut;
 use std::io::{self, IsTerminal, Read};
 use std::path::PathBuf;
+use tallow_net::transport::bandwidth::{BandwidthLimiter, BandwidthSchedule};
 use tallow_net::transport::reconnect::{self, ReconnectConfig};
+use tallow_net::transport::CongestionPacer;
 use tallow_net::transport::PeerChannel;
+use tallow_protocol::transfer::{send_window, SkippedFile};
 use tallow_protocol::wire::{codec::TallowCodec, Message};
 
 /// Maximum receive buffer size (256 KB)
@@ -18,10 +21,80 @@ enum SendSource {
     Files(Vec<PathBuf>),
     /// Text from --text flag or stdin pipe
     Text(Vec<u8>),
+    /// Unbounded stdin pipe sent as a stream (`tallow stream`)
+    Stream,
+}
+
+/// Bytes of the first source file read to guess the compression ratio
+const RATIO_SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Guess the compressed/original size ratio from the start of the first file
+///
+/// Sources without a readable file (text, streams) count as incompressible,
+/// which only makes the estimate pessimistic.
+fn compression_ratio_hint(paths: &[PathBuf], compression: Option<&str>) -> f64 {
+    let mut sample = Vec::new();
+    if let Some(path) = paths.first() {
+        if let Ok(file) = std::fs::File::open(path) {
+            let _ = file.take(RATIO_SAMPLE_BYTES).read_to_end(&mut sample);
+        }
+    }
+    if sample.is_empty() {
+        return 1.0;
+    }
+    tallow_protocol::transfer::estimate::compression_ratio_hint(&sample, compression)
+}
+
+/// Send the probe frames and time the receiver's `ProbeAck`
+async fn run_bandwidth_probe(
+    channel: &mut tallow_net::transport::ConnectionResult,
+    codec: &mut TallowCodec,
+    encode_buf: &mut BytesMut,
+    recv_buf: &mut [u8],
+    ratio: f64,
+) -> io::Result<tallow_protocol::transfer::ThroughputSample> {
+    let started = std::time::Instant::now();
+    let mut sent = 0u64;
+    for frame in tallow_protocol::transfer::estimate::probe_frames(ratio) {
+        if let Message::ProbeData { ref payload, .. } = frame {
+            sent += payload.len() as u64;
+        }
+        encode_buf.clear();
+        codec
+            .encode_msg(&frame, encode_buf)
+            .map_err(|e| io::Error::other(format!("Encode probe failed: {}", e)))?;
+        channel
+            .send_message(&encode_buf[..])
+            .await
+            .map_err(|e| io::Error::other(format!("Send probe failed: {}", e)))?;
+    }
+
+    let n = channel
+        .receive_message(recv_buf)
+        .await
+        .map_err(|e| io::Error::other(format!("Receive probe ack failed: {}", e)))?;
+    let elapsed = started.elapsed();
+    let mut decode_buf = BytesMut::from(&recv_buf[..n]);
+    match codec
+        .decode_msg(&mut decode_buf)
+        .map_err(|e| io::Error::other(format!("Decode probe ack failed: {}", e)))?
+    {
+        Some(Message::ProbeAck { bytes }) => Ok(tallow_protocol::transfer::ThroughputSample::new(
+            bytes.min(sent),
+            elapsed,
+        )),
+        other => Err(io::Error::other(format!(
+            "Expected probe ack, got: {:?}",
+            other
+        ))),
+    }
 }
 
 /// Determine what to send based on CLI args and stdin state
-fn determine_source(args: &SendArgs) -> io::Result<SendSource> {
+///
+/// In stream mode, piped stdin is sent as it is read instead of being
+/// buffered in memory, so there is no size cap.
+fn determine_source(args: &SendArgs, stream: bool) -> io::Result<SendSource> {
     // --text flag takes highest priority
     if let Some(ref text) = args.text {
         let ct = tallow_store::clipboard::detect::detect_content_type(text);
@@ -29,6 +102,10 @@ fn determine_source(args: &SendArgs) -> io::Result<SendSource> {
         return Ok(SendSource::Text(text.as_bytes().to_vec()));
     }
 
+    if stream && args.files.is_empty() && !args.ignore_stdin && !std::io::stdin().is_terminal() {
+        return Ok(SendSource::Stream);
+    }
+
     // Check for piped stdin (not a terminal) when no files given
     if args.files.is_empty() && !args.ignore_stdin && !std::io::stdin().is_terminal() {
         // Cap stdin reads at 256 MiB to prevent OOM from unbounded input
@@ -70,23 +147,64 @@ fn determine_source(args: &SendArgs) -> io::Result<SendSource> {
 
 /// Execute send command
 pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
+    run(args, json, false).await
+}
+
+/// Execute stream command: like send, but piped stdin is streamed
+pub async fn execute_stream(args: SendArgs, json: bool) -> io::Result<()> {
+    run(args, json, true).await
+}
+
+async fn run(args: SendArgs, json: bool, stream: bool) -> io::Result<()> {
     // Load config for hooks
     let config = tallow_store::config::load_config().unwrap_or_default();
+    let on_complete = crate::hooks::CompletionHook::resolve(
+        args.on_complete.as_deref(),
+        &config.transfer.on_complete,
+        !args.no_hooks,
+    )?;
+
+    let started = std::time::Instant::now();
+    let mut completion = crate::hooks::Completion::new("send");
+    let result = transfer(args, json, stream, config, &mut completion).await;
+    completion.finish(&result, started.elapsed());
+    on_complete.notify(&completion).await;
+    result
+}
+
+async fn transfer(
+    args: SendArgs,
+    json: bool,
+    stream: bool,
+    config: tallow_store::config::TallowConfig,
+    completion: &mut crate::hooks::Completion,
+) -> io::Result<()> {
     let hook_runner = crate::hooks::HookRunner::from_config(&config.hooks, !args.no_hooks);
 
-    // Build proxy config from CLI flags
-    let proxy_config =
-        crate::commands::proxy::build_proxy_config(args.tor, &args.proxy, json).await?;
+    if let Some(ref query) = args.to {
+        if let Some(contact) = resolve_recipient(query, json)? {
+            if !json {
+                output::color::info(&format!(
+                    "Sending to {}",
+                    tallow_store::contacts::resolve::describe(&contact)
+                ));
+            }
+        }
+    }
+
+    // Build the network policy from the proxy flags
+    let network_policy =
+        crate::commands::proxy::build_network_policy(args.tor, &args.proxy, json).await?;
 
     // Suppress LAN discovery when proxy is active (broadcasts local IP)
-    if proxy_config.is_some() && args.discover && !json {
+    if !network_policy.allows_lan_discovery() && args.discover && !json {
         output::color::warning(
             "LAN discovery disabled: --discover leaks local IP when using a proxy",
         );
     }
 
     // Log proxy usage
-    if let Some(ref proxy) = proxy_config {
+    if let Some(proxy) = network_policy.proxy() {
         if !json {
             if proxy.tor_mode {
                 output::color::info("Routing through Tor...");
@@ -97,7 +215,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     }
 
     // LAN peer discovery via mDNS (skip when proxy is active)
-    if args.discover && proxy_config.is_none() {
+    if args.discover && network_policy.allows_lan_discovery() {
         if !json {
             output::color::info("Discovering peers on LAN...");
         }
@@ -146,7 +264,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     }
 
     // Determine what we're sending (text, stdin pipe, or files)
-    let source = determine_source(&args)?;
+    let source = determine_source(&args, stream)?;
 
     // Validate files exist (only for file mode)
     if let SendSource::Files(ref files) = source {
@@ -168,6 +286,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         let hook_files: Vec<String> = match &source {
             SendSource::Files(files) => files.iter().map(|f| f.display().to_string()).collect(),
             SendSource::Text(_) => vec!["<text>".to_string()],
+            SendSource::Stream => vec!["<stdin>".to_string()],
         };
         let hook_env = crate::hooks::HookEnv {
             files: hook_files,
@@ -185,6 +304,9 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         tracing::warn!("Identity initialization failed: {}", e);
     }
 
+    // Read the pre-shared key now so a bad file fails before connecting
+    let psk = crate::commands::psk::from_args(args.psk_file.as_deref())?;
+
     // Generate code phrase for the room
     let code_phrase = if let Some(ref custom_code) = args.custom_code {
         // Validate minimum length for security
@@ -194,8 +316,14 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 "Custom code must be at least 4 characters for security",
             ));
         }
-        if custom_code.len() < 8 && !json {
-            output::color::warning("Short custom code -- security depends on code phrase entropy");
+        if !json {
+            let strength = tallow_crypto::kdf::estimate_strength(custom_code);
+            if strength.score < 3 {
+                output::color::warning(
+                    "Weak custom code -- security depends on code phrase entropy",
+                );
+                output::color::password_strength(&strength);
+            }
         }
         custom_code.clone()
     } else if let Some(room) = &args.room {
@@ -211,6 +339,24 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     // Derive room ID from code phrase
     let room_id = tallow_protocol::room::code::derive_room_id(&code_phrase);
 
+    if let Some(ref path) = args.qr_out {
+        let format = output::qr::QrFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--qr-out must end in .png or .svg: {}", path.display()),
+            )
+        })?;
+        output::qr::render_to_file(
+            &format!("tallow receive {}", code_phrase),
+            path,
+            format,
+            output::qr::QrImageOptions::default(),
+        )?;
+        if !json {
+            output::color::info(&format!("QR code written to {}", path.display()));
+        }
+    }
+
     if json {
         println!(
             "{}",
@@ -261,21 +407,40 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         _ => tallow_protocol::compression::CompressionAlgorithm::Zstd, // "auto" and unrecognized default to zstd
     };
 
+    let hash_algorithm =
+        tallow_crypto::hash::HashAlgorithm::parse(&args.hash).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown hash algorithm '{}' (use blake3 or sha3-256)",
+                    args.hash
+                ),
+            )
+        })?;
+
     // Build exclusion config from --exclude and --git flags
     let exclusion = tallow_protocol::transfer::ExclusionConfig::from_exclude_str(
         args.exclude.as_deref(),
         args.git,
     );
 
-    // Parse bandwidth throttle
-    let throttle_bps = parse_throttle(&args.throttle)?;
+    // Bandwidth throttle: a fixed --throttle wins over the configured schedule
+    let mut throttle = build_throttle(&args.throttle, &config.transfer)?;
+    let mut pacer = args.pace.then(CongestionPacer::new);
 
     let mut pipeline = tallow_protocol::transfer::SendPipeline::new(transfer_id, placeholder_key)
         .with_compression(compression)
+        .with_compression_skiplist(
+            tallow_protocol::compression::SkipList::default().with_overrides(
+                &config.transfer.compression_skip_add,
+                &config.transfer.compression_skip_remove,
+            ),
+        )
+        .with_hash_algorithm(hash_algorithm)
         .with_exclusion(exclusion);
 
     // Prepare transfer based on source — with content type hint
-    let (offer_messages, source_files) = match &source {
+    let (mut offer_messages, source_files) = match &source {
         SendSource::Text(data) => {
             // Show content type hint for text transfers
             if !json {
@@ -310,7 +475,14 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 .prepare(files)
                 .await
                 .map_err(|e| io::Error::other(format!("Failed to prepare transfer: {}", e)))?;
-            (msgs, files.clone())
+            // Manifest order, which is sorted and need not match the order given
+            (msgs, pipeline.source_paths().to_vec())
+        }
+        SendSource::Stream => {
+            let msgs = pipeline
+                .prepare_stream("stdin")
+                .map_err(|e| io::Error::other(format!("Failed to prepare stream: {}", e)))?;
+            (msgs, Vec::new())
         }
     };
 
@@ -327,15 +499,25 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 "total_files": file_count,
                 "total_bytes": total_size,
                 "total_chunks": total_chunks,
+                "skipped": skipped_json(&manifest.skipped),
             })
         );
     } else {
+        for skipped in &manifest.skipped {
+            output::color::warning(&format!("Skipping {}", skipped));
+        }
         output::color::transfer_summary(file_count, total_size);
     }
 
     // --dry-run: display file summary and exit without connecting
     if args.dry_run {
         let compression_name = manifest.compression.as_deref().unwrap_or("none");
+        let wire_bytes = tallow_protocol::transfer::estimate::estimate(
+            total_size,
+            compression_ratio_hint(pipeline.source_paths(), manifest.compression.as_deref()),
+            &tallow_protocol::transfer::ThroughputSample::new(0, std::time::Duration::ZERO),
+        )
+        .wire_bytes;
 
         if json {
             let file_list: Vec<serde_json::Value> = manifest
@@ -358,7 +540,10 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                     "total_chunks": total_chunks,
                     "compression": compression_name,
                     "chunk_size": manifest.chunk_size,
+                    "estimated_wire_bytes": wire_bytes,
+                    "estimated_seconds": serde_json::Value::Null,
                     "files": file_list,
+                    "skipped": skipped_json(&manifest.skipped),
                 })
             );
         } else {
@@ -375,6 +560,11 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 output::format_size(manifest.chunk_size as u64)
             ));
             output::color::info(&format!("Compression: {}", compression_name));
+            output::color::info(&format!(
+                "Estimated size on the wire: {}",
+                output::format_size(wire_bytes)
+            ));
+            output::color::info("Estimated time: unknown (a dry run has no peer to probe)");
         }
         return Ok(());
     }
@@ -396,47 +586,44 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
+    // Decode relay bearer token (hex, as printed by `tallow-relay token mint`)
+    let relay_token: Option<Vec<u8>> = args
+        .relay_token
+        .as_deref()
+        .map(|t| hex::decode(t.trim()))
+        .transpose()
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Relay token must be hex-encoded",
+            )
+        })?;
 
     // Establish connection: proxy-aware relay or direct LAN with fallback
     let fingerprint_prefix = identity.fingerprint_prefix(8);
-    let (mut channel, mut is_direct) = if let Some(ref proxy) = proxy_config {
+    let (mut channel, mut is_direct) = if network_policy.is_proxied() {
         // Proxy active: resolve via DoH/hostname, skip LAN discovery entirely
-        let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
+        let mut relay = network_policy
+            .relay_client(&args.relay)
             .await
-            .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
-
-        let mut relay = match resolved {
-            tallow_net::relay::ResolvedRelay::Addr(addr) => {
-                let mut client = tallow_net::relay::RelayClient::new(addr);
-                client.set_proxy(proxy.clone());
-                client
-            }
-            tallow_net::relay::ResolvedRelay::Hostname { ref host, port } => {
-                tallow_net::relay::RelayClient::new_with_proxy(host, port, proxy.clone())
-            }
-        };
+            .map_err(crate::error::context("Relay resolution failed"))?;
+        if let Some(ref token) = relay_token {
+            relay.set_auth_token(token.clone());
+        }
+        relay.set_tls_policy(crate::commands::proxy::tls_policy(&config.network)?);
 
         relay
             .connect(&room_id, pw_ref)
             .await
-            .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?;
+            .map_err(crate::error::context("Connection failed"))?;
         if !relay.peer_present() {
             relay
                 .wait_for_peer()
                 .await
-                .map_err(|e| io::Error::other(format!("Waiting for peer failed: {}", e)))?;
+                .map_err(crate::error::context("Waiting for peer failed"))?;
         }
 
         (
@@ -451,10 +638,17 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
             &fingerprint_prefix,
             relay_addr,
             pw_ref,
+            relay_token.as_deref(),
             args.local,
+            tallow_net::discovery::AdvertiseOptions {
+                interval: std::time::Duration::from_secs(
+                    config.network.mdns_announce_interval_secs,
+                ),
+                ttl: std::time::Duration::from_secs(config.network.mdns_record_ttl_secs),
+            },
         )
         .await
-        .map_err(|e| io::Error::other(format!("Connection failed: {}", e)))?
+        .map_err(crate::error::context("Connection failed"))?
     };
 
     if is_direct {
@@ -492,34 +686,59 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     };
 
     // --- KEM Handshake ---
-    let mut handshake = tallow_protocol::kex::SenderHandshake::new(&code_phrase, &room_id);
+    let handshake_deadline = tallow_net::transport::HandshakeDeadline::start(
+        std::time::Duration::from_secs(args.handshake_timeout),
+    );
+    let mut handshake =
+        crate::commands::psk::sender_handshake(&code_phrase, &room_id, psk.as_deref());
+
+    // Chunk checksums are opt-in: only offer them when asked, so the
+    // negotiated set says whether chunks will carry one
+    let mut advertised = tallow_protocol::wire::FeatureSet::local();
+    if !(args.chunk_checksum || config.transfer.chunk_checksum) {
+        advertised = advertised.difference(tallow_protocol::wire::FeatureSet::CHUNK_CHECKSUM);
+    }
 
-    // Step 1: Send HandshakeInit
+    // Step 0: Advertise capabilities, then Step 1: Send HandshakeInit
+    let caps_msg = handshake
+        .advertise(advertised)
+        .map_err(crate::error::context("Handshake advertise failed"))?;
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("Handshake init failed: {}", e)))?;
-    encode_buf.clear();
-    codec
-        .encode_msg(&init_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode HandshakeInit: {}", e)))?;
-    channel
-        .send_message(&encode_buf)
-        .await
-        .map_err(|e| io::Error::other(format!("Send HandshakeInit: {}", e)))?;
-
-    // Step 2: Receive HandshakeResponse
-    let n = tokio::time::timeout(
-        std::time::Duration::from_secs(30),
-        channel.receive_message(&mut recv_buf),
-    )
-    .await
-    .map_err(|_| io::Error::other("Handshake timeout waiting for response"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeResponse: {}", e)))?;
+        .map_err(crate::error::context("Handshake init failed"))?;
+    for msg in [&caps_msg, &init_msg] {
+        encode_buf.clear();
+        codec
+            .encode_msg(msg, &mut encode_buf)
+            .map_err(crate::error::context("Encode HandshakeInit"))?;
+        channel
+            .send_message(&encode_buf)
+            .await
+            .map_err(crate::error::context("Send HandshakeInit"))?;
+    }
 
-    let mut decode_buf = BytesMut::from(&recv_buf[..n]);
-    let resp_msg = codec
-        .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeResponse: {}", e)))?;
+    // Step 2: Receive HandshakeResponse, preceded by the receiver's
+    // Capabilities when it supports them
+    let resp_msg = loop {
+        let n = handshake_deadline
+            .step(channel.receive_message(&mut recv_buf))
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::TimedOut, e))?
+            .map_err(crate::error::context("Receive HandshakeResponse"))?;
+
+        let mut decode_buf = BytesMut::from(&recv_buf[..n]);
+        match codec
+            .decode_msg(&mut decode_buf)
+            .map_err(crate::error::context("Decode HandshakeResponse"))?
+        {
+            Some(Message::Capabilities { features }) => {
+                handshake
+                    .process_capabilities(features)
+                    .map_err(crate::error::context("Handshake capabilities failed"))?;
+            }
+            other => break other,
+        }
+    };
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -530,41 +749,61 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
             kem_public_key,
             nonce,
         }) => {
-            // Step 3: Process response -> HandshakeKem + session key
-            let (kem_msg, session_key_result) = handshake
+            // Step 3: Process response -> HandshakeKem
+            let kem_msg = handshake
                 .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake response processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context(
+                    "Handshake response processing failed",
+                ))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&kem_msg, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeKem"))?;
             channel
                 .send_message(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeKem"))?;
 
             // Step 4: Receive HandshakeComplete
-            let n = tokio::time::timeout(
-                std::time::Duration::from_secs(30),
-                channel.receive_message(&mut recv_buf),
-            )
-            .await
-            .map_err(|_| io::Error::other("Handshake timeout waiting for confirmation"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeComplete: {}", e)))?;
+            let n = handshake_deadline
+                .step(channel.receive_message(&mut recv_buf))
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::TimedOut, e))?
+                .map_err(crate::error::context("Receive HandshakeComplete"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let complete_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeComplete: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeComplete"))?;
 
-            match complete_msg {
+            // The session key is only released once the receiver proves it
+            // derived the same one; nothing is sent under it before that
+            session_key = match complete_msg {
                 Some(Message::HandshakeComplete { confirmation }) => {
-                    handshake
-                        .verify_receiver_confirmation(&confirmation)
-                        .map_err(|e| io::Error::other(format!("Key confirmation failed: {}", e)))?;
+                    match handshake.verify_receiver_confirmation(&confirmation) {
+                        Ok(key) => key,
+                        Err(e) => {
+                            encode_buf.clear();
+                            if codec
+                                .encode_msg(
+                                    &tallow_protocol::kex::handshake_failed_message(),
+                                    &mut encode_buf,
+                                )
+                                .is_ok()
+                            {
+                                let _ = channel.send_message(&encode_buf).await;
+                            }
+                            channel.close().await;
+                            return Err(crate::error::context("Key confirmation failed")(e));
+                        }
+                    }
+                }
+                Some(Message::HandshakeFailed { reason }) => {
+                    channel.close().await;
+                    return Err(crate::error::context("Receiver aborted the handshake")(
+                        tallow_protocol::ProtocolError::HandshakeFailed(reason),
+                    ));
                 }
                 other => {
                     channel.close().await;
@@ -573,9 +812,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                         other
                     )));
                 }
-            }
-
-            session_key = session_key_result;
+            };
         }
         Some(Message::FileOffer { .. }) => {
             channel.close().await;
@@ -598,8 +835,47 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     }
     // --- End handshake ---
 
-    // Set the real session key derived from KEM handshake
-    pipeline.set_session_key(*session_key.as_bytes());
+    // Set the real session key derived from KEM handshake, bound to the
+    // caller's context label if one was given
+    let transfer_key = match args.context {
+        Some(ref label) => *session_key
+            .for_context(label)
+            .map_err(|e| io::Error::other(format!("Derive transfer key: {}", e)))?
+            .as_bytes(),
+        None => *session_key.as_bytes(),
+    };
+    pipeline.set_session_key(transfer_key);
+
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::CHUNK_CHECKSUM)
+    {
+        pipeline = pipeline.with_chunk_checksum(true);
+    } else if args.chunk_checksum && !json {
+        output::color::warning("Peer does not support chunk checksums; sending without them");
+    }
+
+    // Only use features the receiver advertised (or that every build supports)
+    pipeline
+        .check_hash_algorithm(handshake.negotiated_features())
+        .map_err(|e| io::Error::new(io::ErrorKind::Unsupported, e.to_string()))?;
+    let compression_changed = pipeline.restrict_compression(handshake.negotiated_features());
+    let sparse = pipeline
+        .enable_sparse(handshake.negotiated_features())
+        .map_err(|e| io::Error::other(format!("Failed to map sparse files: {}", e)))?;
+    if compression_changed || sparse {
+        let offer = pipeline
+            .file_offer()
+            .map_err(|e| io::Error::other(format!("Failed to rebuild offer: {}", e)))?;
+        offer_messages = vec![offer];
+    }
+    if compression_changed && !json {
+        let compression_name = pipeline.manifest().compression.as_deref().unwrap_or("none");
+        output::color::info(&format!(
+            "Peer lacks the requested compression; using {}",
+            compression_name
+        ));
+    }
 
     // Display verification string for MITM detection (opt-in via --verify)
     if args.verify {
@@ -613,14 +889,15 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     // --- P2P Direct Connection Upgrade ---
     // Attempt to upgrade from relay to direct P2P QUIC after handshake.
     // Skip when: proxy active, --no-p2p set, already direct (LAN)
-    if !is_direct && proxy_config.is_none() && !args.no_p2p {
+    let p2p_allowed = crate::commands::proxy::p2p_allowed(&network_policy, args.no_p2p);
+    if !is_direct && p2p_allowed {
         if !json {
             output::color::info("Attempting P2P direct connection...");
         }
 
         // Sender = initiator (QUIC client role)
         // Pass the combined suppression flag as defense-in-depth guard.
-        let suppress_p2p = proxy_config.is_some() || args.no_p2p;
+        let suppress_p2p = !p2p_allowed;
         match tallow_net::transport::negotiate_p2p(&mut channel, true, suppress_p2p).await {
             tallow_net::transport::NegotiationResult::Direct(direct_conn) => {
                 if json {
@@ -658,15 +935,59 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 }
             }
         }
-    } else if proxy_config.is_some() || args.no_p2p {
+    } else if !p2p_allowed {
         tracing::debug!(
             "P2P disabled: proxy={}, no_p2p={}",
-            proxy_config.is_some(),
+            network_policy.is_proxied(),
             args.no_p2p
         );
     }
     // --- End P2P Upgrade ---
 
+    // Time a short probe so both sides can show an ETA before the offer
+    if handshake
+        .negotiated_features()
+        .contains(tallow_protocol::wire::FeatureSet::BANDWIDTH_PROBE)
+        && total_size >= tallow_protocol::transfer::estimate::MIN_PROBED_SIZE
+    {
+        let ratio = compression_ratio_hint(
+            pipeline.source_paths(),
+            pipeline.manifest().compression.as_deref(),
+        );
+        if !json {
+            output::color::info(&format!(
+                "Probing bandwidth ({} of test data, not part of the transfer)...",
+                output::format_size(tallow_protocol::transfer::estimate::PROBE_BYTES as u64)
+            ));
+        }
+        let sample = run_bandwidth_probe(
+            &mut channel,
+            &mut codec,
+            &mut encode_buf,
+            &mut recv_buf,
+            ratio,
+        )
+        .await?;
+        let estimate = tallow_protocol::transfer::estimate::estimate(total_size, ratio, &sample);
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "event": "transfer_estimate",
+                    "probe_bytes": sample.bytes(),
+                    "bytes_per_sec": estimate.bytes_per_sec,
+                    "estimated_wire_bytes": estimate.wire_bytes,
+                    "estimated_seconds": estimate.duration.map(|d| d.as_secs_f64()),
+                })
+            );
+        } else {
+            output::color::info(&format!(
+                "Estimated transfer time: {}",
+                output::format_estimate(&estimate)
+            ));
+        }
+    }
+
     // Send FileOffer
     for msg in &offer_messages {
         encode_buf.clear();
@@ -696,24 +1017,22 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         Some(Message::FileSelection {
             selected_indices, ..
         }) => {
-            // Validate indices are within range and non-empty
-            if selected_indices.is_empty() {
-                return Err(io::Error::other(
-                    "Receiver sent empty file selection — treating as rejection",
-                ));
-            }
-            if selected_indices.iter().any(|&i| i >= file_count as u32) {
-                return Err(io::Error::other(format!(
-                    "Receiver sent invalid file selection index (max: {})",
-                    file_count - 1
-                )));
-            }
             // Cap selection size to prevent memory abuse
             if selected_indices.len() > file_count {
                 return Err(io::Error::other(
                     "Receiver sent more selections than files in manifest",
                 ));
             }
+            // Validate indices are within range and non-empty
+            let mask = tallow_protocol::transfer::AcceptanceMask::from_indices(
+                &manifest,
+                &selected_indices,
+            )
+            .map_err(|e| {
+                io::Error::other(format!("Receiver sent invalid file selection: {}", e))
+            })?;
+            // Chunks are numbered over the accepted files only
+            pipeline.accept_files(mask);
 
             // Receiver sent per-file selection -- store indices, then wait for FileAccept
             tracing::info!(
@@ -799,52 +1118,29 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         }
     }
 
-    // Filter source files based on per-file selection (if any)
-    let effective_source_files: Vec<PathBuf> = if let Some(ref indices) = selected_file_indices {
-        // Only send the files at the selected indices
-        let selected_set: std::collections::HashSet<u32> = indices.iter().copied().collect();
-        source_files
+    // Filter source files and totals based on per-file selection (if any).
+    // Sparse files may have shrunk the chunk counts since the offer was shown.
+    let manifest = pipeline.manifest().clone();
+    let acceptance = pipeline.acceptance();
+    let effective_source_files: Vec<PathBuf> = if selected_file_indices.is_some() {
+        acceptance
+            .indices()
             .iter()
-            .enumerate()
-            .filter(|(i, _)| selected_set.contains(&(*i as u32)))
-            .map(|(_, f)| f.clone())
+            .map(|&i| source_files[i as usize].clone())
             .collect()
     } else {
         source_files.clone()
     };
+    let effective_total_size = acceptance.total_size(&manifest);
+    let effective_total_chunks = acceptance.total_chunks(&manifest);
 
-    // Recalculate totals for progress if selection reduced the file set
-    let effective_total_size: u64 = if selected_file_indices.is_some() {
-        let selected_set: std::collections::HashSet<u32> = selected_file_indices
-            .as_ref()
-            .map(|v| v.iter().copied().collect())
-            .unwrap_or_default();
-        manifest
-            .files
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| selected_set.contains(&(*i as u32)))
-            .map(|(_, f)| f.size)
-            .sum()
-    } else {
-        total_size
-    };
-
-    let effective_total_chunks: u64 = if selected_file_indices.is_some() {
-        let selected_set: std::collections::HashSet<u32> = selected_file_indices
-            .as_ref()
-            .map(|v| v.iter().copied().collect())
-            .unwrap_or_default();
-        manifest
-            .files
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| selected_set.contains(&(*i as u32)))
-            .map(|(_, f)| f.chunk_count)
-            .sum()
-    } else {
-        total_chunks
-    };
+    // --sandbox strict: setup is done, confine the rest of the transfer
+    let sandbox_sources: Vec<&std::path::Path> = effective_source_files
+        .iter()
+        .map(PathBuf::as_path)
+        .collect();
+    crate::sandbox::lockdown(&crate::sandbox::SandboxConfig::for_send(&sandbox_sources))
+        .map_err(|e| io::Error::other(format!("Sandbox lockdown failed: {}", e)))?;
 
     // Create progress bar and send chunks with sliding window
     let transfer_start = std::time::Instant::now();
@@ -852,10 +1148,10 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     let mut total_sent: u64 = 0;
     let mut chunk_index: u64 = 0;
 
-    /// Sliding window size: send up to N chunks before draining acks.
-    /// At 256 KB chunks and ~80ms RTT, 64-chunk windows yield ~200 MB/s ceiling.
-    const WINDOW_SIZE: usize = 64;
-    const _: () = assert!(WINDOW_SIZE > 0, "WINDOW_SIZE must be > 0");
+    // Sliding window: send up to N chunks before draining acks, and never
+    // read more than N chunks ahead of the last acknowledged batch.
+    // At 256 KB chunks and ~80ms RTT, 64-chunk windows yield ~200 MB/s ceiling.
+    let window_size = args.window.max(1);
 
     // Collect BLAKE3 hashes of encrypted chunks for Merkle tree
     let mut chunk_hashes: Vec<[u8; 32]> = Vec::new();
@@ -873,20 +1169,16 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         progress: &output::TransferProgressBar,
         total_sent: &mut u64,
         total_size: u64,
-        throttle_bps: u64,
+        throttle: &mut BandwidthLimiter,
+        pacer: &mut Option<CongestionPacer>,
         chunk_hashes: &mut Vec<[u8; 32]>,
         retry_config: &ReconnectConfig,
     ) -> io::Result<()> {
-        // Phase 1: Send up to WINDOW_SIZE chunks
+        // Phase 1: Send the whole window
         for chunk_msg in batch {
             // Apply bandwidth throttle if configured
-            if throttle_bps > 0 {
-                if let Message::Chunk { ref data, .. } = chunk_msg {
-                    let delay_ms = (data.len() as u64 * 1000) / throttle_bps;
-                    if delay_ms > 0 {
-                        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                    }
-                }
+            if let Message::Chunk { ref data, .. } = chunk_msg {
+                throttle.wait_if_needed(data.len()).await;
             }
 
             // Record chunk hash for Merkle tree
@@ -898,16 +1190,26 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
             codec
                 .encode_msg(chunk_msg, encode_buf)
                 .map_err(|e| io::Error::other(format!("Encode chunk failed: {}", e)))?;
+            if let Some(pacer) = pacer.as_mut() {
+                pacer.pace(encode_buf.len(), channel.congestion()).await;
+            }
             reconnect::send_with_retry(channel, encode_buf, retry_config)
                 .await
-                .map_err(|e| io::Error::other(format!("Send chunk failed: {}", e)))?;
+                .map_err(|e| crate::exit_codes::network_error("Send chunk failed", e))?;
         }
 
-        // Phase 2: Drain all acks
-        for _ in 0..batch.len() {
+        // Phase 2: Drain all acks, resending any chunk the receiver could not
+        // authenticate
+        let mut unacked = tallow_protocol::transfer::SentChunks::new();
+        for chunk_msg in batch {
+            if let Message::Chunk { index, .. } = chunk_msg {
+                unacked.record(*index, chunk_msg);
+            }
+        }
+        while !unacked.is_empty() {
             let n = reconnect::receive_with_retry(channel, recv_buf, retry_config)
                 .await
-                .map_err(|e| io::Error::other(format!("Receive ack failed: {}", e)))?;
+                .map_err(|e| crate::exit_codes::network_error("Receive ack failed", e))?;
 
             let mut ack_buf = BytesMut::from(&recv_buf[..n]);
             let ack = codec
@@ -915,8 +1217,29 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 .map_err(|e| io::Error::other(format!("Decode ack failed: {}", e)))?;
 
             match ack {
-                Some(Message::Ack { .. }) => {
+                Some(Message::Ack { index, .. }) => {
                     // Count acked bytes (approximate from chunk data sizes)
+                    unacked.ack(index);
+                }
+                Some(Message::ResendChunks { indices, .. }) => {
+                    let resend = unacked
+                        .resend(&indices)
+                        .map_err(|e| io::Error::other(format!("Invalid resend request: {}", e)))?;
+                    tracing::debug!("Resending chunks {:?}", indices);
+                    for chunk_msg in resend {
+                        encode_buf.clear();
+                        codec
+                            .encode_msg(chunk_msg, encode_buf)
+                            .map_err(|e| io::Error::other(format!("Encode chunk failed: {}", e)))?;
+                        reconnect::send_with_retry(channel, encode_buf, retry_config)
+                            .await
+                            .map_err(|e| {
+                                crate::exit_codes::network_error("Resend chunk failed", e)
+                            })?;
+                    }
+                }
+                Some(Message::CumulativeAck { .. }) => {
+                    // The bar already advances only on per-chunk acks
                 }
                 Some(Message::TransferError { error, .. }) => {
                     progress.finish();
@@ -949,6 +1272,9 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
         Ok(())
     }
 
+    // Stream mode learns its size only once stdin is exhausted
+    let mut streamed_bytes: Option<u64> = None;
+
     match &source {
         SendSource::Text(data) => {
             // Text/stdin: small data, use in-memory chunking
@@ -958,7 +1284,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 .map_err(|e| io::Error::other(format!("Failed to chunk text: {}", e)))?;
 
             // Send in sliding window batches
-            for batch in chunk_messages.chunks(WINDOW_SIZE) {
+            for batch in chunk_messages.chunks(window_size) {
                 send_batch_and_drain(
                     batch,
                     &mut channel,
@@ -968,7 +1294,8 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                     &progress,
                     &mut total_sent,
                     effective_total_size,
-                    throttle_bps,
+                    &mut throttle,
+                    &mut pacer,
                     &mut chunk_hashes,
                     &reconnect_config,
                 )
@@ -982,6 +1309,24 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                     io::Error::other(format!("Failed to open {}: {}", file.display(), e))
                 })?;
 
+                // Read ahead on a separate task, at most one window of chunks
+                // beyond what the receiver has acknowledged
+                let (window_tx, mut window_rx) = send_window(window_size);
+                let read_ahead = tokio::spawn(async move {
+                    loop {
+                        let next = reader.next_chunk().await;
+                        let done = !matches!(next, Ok(Some(_)));
+                        if let Some(chunk) = next.transpose() {
+                            if window_tx.send(chunk).await.is_err() {
+                                break;
+                            }
+                        }
+                        if done {
+                            break;
+                        }
+                    }
+                });
+
                 // Find this file's chunk count from the manifest
                 let file_name = file
                     .file_name()
@@ -994,16 +1339,26 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                     .find(|f| f.path.to_string_lossy() == file_name)
                     .map(|f| f.chunk_count)
                     .unwrap_or(1);
-                let mut batch: Vec<Message> = Vec::with_capacity(WINDOW_SIZE);
-
-                while let Some(raw_chunk) = reader.next_chunk().await.map_err(|e| {
-                    io::Error::other(format!("Read chunk from {}: {}", file.display(), e))
-                })? {
+                let mut batch: Vec<Message> = Vec::with_capacity(window_size);
+                // Window slots of the batch, released once it is acknowledged
+                let mut slots = Vec::with_capacity(window_size);
+
+                while let Some(slot) = window_rx.recv().await {
+                    let raw_chunk = match &*slot {
+                        Ok(chunk) => chunk,
+                        Err(e) => {
+                            return Err(io::Error::other(format!(
+                                "Read chunk from {}: {}",
+                                file.display(),
+                                e
+                            )))
+                        }
+                    };
                     let is_last_chunk_overall = chunk_index + 1 == effective_total_chunks;
 
                     let msg = pipeline
                         .encrypt_chunk(
-                            &raw_chunk,
+                            raw_chunk,
                             chunk_index,
                             effective_total_chunks,
                             is_last_chunk_overall,
@@ -1011,10 +1366,11 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                         .map_err(|e| io::Error::other(format!("Encrypt chunk failed: {}", e)))?;
 
                     batch.push(msg);
+                    slots.push(slot);
                     chunk_index += 1;
 
                     // Send batch when window is full
-                    if batch.len() >= WINDOW_SIZE {
+                    if batch.len() >= window_size {
                         send_batch_and_drain(
                             &batch,
                             &mut channel,
@@ -1024,12 +1380,14 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                             &progress,
                             &mut total_sent,
                             effective_total_size,
-                            throttle_bps,
+                            &mut throttle,
+                            &mut pacer,
                             &mut chunk_hashes,
                             &reconnect_config,
                         )
                         .await?;
                         batch.clear();
+                        slots.clear();
                     }
                 }
 
@@ -1044,18 +1402,105 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                         &progress,
                         &mut total_sent,
                         effective_total_size,
-                        throttle_bps,
+                        &mut throttle,
+                        &mut pacer,
+                        &mut chunk_hashes,
+                        &reconnect_config,
+                    )
+                    .await?;
+                }
+                drop(slots);
+                read_ahead
+                    .await
+                    .map_err(|e| io::Error::other(format!("Read-ahead task failed: {}", e)))?;
+            }
+        }
+        SendSource::Stream => {
+            // Sign the trailer with the long-term identity when available
+            let ephemeral_signer;
+            let signer = match identity.keypair() {
+                Some(keypair) => keypair.signer(),
+                None => {
+                    ephemeral_signer = tallow_crypto::sig::HybridSigner::keygen()
+                        .map_err(|e| io::Error::other(format!("Signer init failed: {}", e)))?;
+                    &ephemeral_signer
+                }
+            };
+
+            let mut chunker = pipeline.stream_chunks(tokio::io::stdin(), signer);
+            let mut batch: Vec<Message> = Vec::with_capacity(window_size);
+
+            while let Some(msg) = chunker
+                .next_message()
+                .await
+                .map_err(|e| io::Error::other(format!("Read stdin failed: {}", e)))?
+            {
+                if let Message::StreamEnd { .. } = msg {
+                    if !batch.is_empty() {
+                        send_batch_and_drain(
+                            &batch,
+                            &mut channel,
+                            &mut codec,
+                            &mut encode_buf,
+                            &mut recv_buf,
+                            &progress,
+                            &mut total_sent,
+                            u64::MAX,
+                            &mut throttle,
+                            &mut pacer,
+                            &mut chunk_hashes,
+                            &reconnect_config,
+                        )
+                        .await?;
+                        batch.clear();
+                    }
+
+                    // The trailer is not acked; TransferComplete follows
+                    encode_buf.clear();
+                    codec
+                        .encode_msg(&msg, &mut encode_buf)
+                        .map_err(|e| io::Error::other(format!("Encode trailer failed: {}", e)))?;
+                    reconnect::send_with_retry(&mut channel, &encode_buf, &reconnect_config)
+                        .await
+                        .map_err(|e| crate::exit_codes::network_error("Send trailer failed", e))?;
+                    continue;
+                }
+
+                batch.push(msg);
+                if batch.len() >= window_size {
+                    send_batch_and_drain(
+                        &batch,
+                        &mut channel,
+                        &mut codec,
+                        &mut encode_buf,
+                        &mut recv_buf,
+                        &progress,
+                        &mut total_sent,
+                        u64::MAX,
+                        &mut throttle,
+                        &mut pacer,
                         &mut chunk_hashes,
                         &reconnect_config,
                     )
                     .await?;
+                    batch.clear();
                 }
             }
+
+            streamed_bytes = Some(chunker.bytes_read());
+            chunk_index = chunker.chunks_sent();
         }
     }
 
     progress.finish();
 
+    let effective_total_size = streamed_bytes.unwrap_or(effective_total_size);
+    let effective_total_chunks = if streamed_bytes.is_some() {
+        chunk_index
+    } else {
+        effective_total_chunks
+    };
+
     // Build Merkle tree from chunk hashes for integrity verification
     let merkle_root = if !chunk_hashes.is_empty() {
         let tree = tallow_crypto::hash::MerkleTree::build(chunk_hashes);
@@ -1087,6 +1532,8 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     channel.close().await;
 
     let effective_file_count = effective_source_files.len();
+    let compression_stats = pipeline.compression_stats();
+    let skipped = pipeline.manifest().skipped.clone();
 
     if json {
         println!(
@@ -1096,10 +1543,27 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 "total_bytes": effective_total_size,
                 "total_chunks": effective_total_chunks,
                 "files_sent": effective_file_count,
+                "files_skipped": skipped_json(&skipped),
+                "compression_stats": compression_stats.map(|c| serde_json::json!({
+                    "original_bytes": c.original_bytes,
+                    "compressed_bytes": c.compressed_bytes,
+                    "ratio": c.ratio(),
+                    "bytes_saved": c.bytes_saved(),
+                })),
             })
         );
     } else {
         output::color::transfer_complete(effective_total_size, transfer_start.elapsed());
+        output::color::compression_summary(compression_stats.as_ref());
+        if !skipped.is_empty() {
+            output::color::section("File outcomes:");
+            for file in &effective_source_files {
+                output::color::file_outcome(&file.display().to_string(), None);
+            }
+            for file in &skipped {
+                output::color::file_outcome(&file.path.display().to_string(), Some(&file.reason));
+            }
+        }
     }
 
     // Desktop notification (opt-in via --notify, suppressed in JSON mode)
@@ -1112,7 +1576,7 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
     }
 
     // Log to transfer history
-    if let Ok(mut history) = tallow_store::history::TransferLog::open() {
+    if let Ok(mut history) = tallow_store::history::TransferLog::open(identity.keypair()) {
         let _ = history.append(tallow_store::history::TransferEntry {
             id: hex::encode(transfer_id),
             peer_id: "unknown".to_string(),
@@ -1128,6 +1592,9 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
                 .iter()
                 .map(|f| f.display().to_string())
                 .collect(),
+            context: args.context.clone(),
+            duration_ms: Some(transfer_start.elapsed().as_millis() as u64),
+            cipher: Some(tallow_protocol::transfer::chunking::CHUNK_CIPHER.to_string()),
         });
     }
 
@@ -1142,12 +1609,82 @@ pub async fn execute(args: SendArgs, json: bool) -> io::Result<()> {
             direction: "send".to_string(),
             ..Default::default()
         };
+        completion.succeeded(hook_env.files.clone(), effective_total_size);
         hook_runner
             .run_hook(crate::hooks::HookType::PostSend, &hook_env)
             .await?;
     }
 
-    Ok(())
+    partial_success(&skipped, effective_file_count)
+}
+
+/// `--json` form of the files left out of a transfer
+fn skipped_json(skipped: &[SkippedFile]) -> Vec<serde_json::Value> {
+    skipped
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "path": s.path.display().to_string(),
+                "reason": s.reason,
+            })
+        })
+        .collect()
+}
+
+/// Fail with [`TallowError::PartialSuccess`] if any files were left out
+///
+/// [`TallowError::PartialSuccess`]: crate::error::TallowError::PartialSuccess
+fn partial_success(skipped: &[SkippedFile], sent: usize) -> io::Result<()> {
+    if skipped.is_empty() {
+        return Ok(());
+    }
+    Err(crate::error::classified(
+        crate::error::TallowError::PartialSuccess {
+            skipped: skipped.len(),
+            total: sent + skipped.len(),
+        },
+    ))
+}
+
+/// Resolve `--to` against saved contacts
+///
+/// With no contacts saved the value is left alone as a raw peer ID or
+/// device name. A tie between equally good matches is offered as a choice
+/// on a terminal and is an error otherwise.
+fn resolve_recipient(
+    query: &str,
+    json: bool,
+) -> io::Result<Option<tallow_store::contacts::Contact>> {
+    let db = tallow_store::contacts::ContactDatabase::new();
+    if db.list().is_empty() {
+        return Ok(None);
+    }
+
+    let err = match db.resolve_one(query) {
+        Ok(contact) => return Ok(Some(contact.clone())),
+        Err(e) => e,
+    };
+
+    let matches = db.resolve(query);
+    let tied: Vec<&tallow_store::contacts::Contact> = match matches.first() {
+        Some(best) => matches
+            .iter()
+            .take_while(|m| m.kind == best.kind)
+            .map(|m| m.contact)
+            .collect(),
+        None => Vec::new(),
+    };
+    if tied.len() > 1 && !json && io::stdin().is_terminal() {
+        let labels: Vec<String> = tied
+            .iter()
+            .map(|&c| tallow_store::contacts::resolve::describe(c))
+            .collect();
+        let choice =
+            output::prompts::select(&format!("'{}' matches several contacts", query), &labels)?;
+        return Ok(Some(tied[choice].clone()));
+    }
+
+    Err(io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))
 }
 
 /// Parse a throttle string (e.g., "10MB", "500KB") into bytes per second
@@ -1168,6 +1705,26 @@ fn parse_throttle(throttle: &Option<String>) -> io::Result<u64> {
     }
 }
 
+/// Build the send rate limiter
+///
+/// A fixed `--throttle` takes precedence. Without one, the configured
+/// `transfer.bandwidth_schedule` applies, with its windows in local time.
+fn build_throttle(
+    throttle: &Option<String>,
+    transfer: &tallow_store::config::TransferConfig,
+) -> io::Result<BandwidthLimiter> {
+    let bps = parse_throttle(throttle)?;
+    if bps > 0 || transfer.bandwidth_schedule.is_empty() {
+        return Ok(BandwidthLimiter::new(bps));
+    }
+    let schedule = BandwidthSchedule::new(transfer.bandwidth_schedule.clone())
+        .with_utc_offset(chrono::Local::now().offset().local_minus_utc());
+    Ok(BandwidthLimiter::scheduled(
+        schedule,
+        std::sync::Arc::new(tallow_crypto::clock::SystemClock),
+    ))
+}
+
 /// Public throttle parser for use by sync and watch commands
 pub fn parse_throttle_pub(throttle: &Option<String>) -> io::Result<u64> {
     parse_throttle(throttle)
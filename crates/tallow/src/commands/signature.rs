@@ -0,0 +1,212 @@
+//! Detached file signatures
+//!
+//! `tallow sign` writes `<file>.tallowsig` next to a file being published,
+//! and `tallow verify` checks it against a signer the verifier already
+//! trusts. Only the file's BLAKE3 hash is signed, so files of any size are
+//! streamed once and never held in memory.
+
+use crate::cli::{SignArgs, VerifyArgs};
+use crate::output;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tallow_crypto::keys::IdentityKeyPair;
+use tallow_crypto::sig::DetachedSignature;
+
+/// Extension appended to the signed file's name
+const SIGNATURE_EXTENSION: &str = "tallowsig";
+
+/// Execute the sign command
+pub fn execute_sign(args: SignArgs, json: bool) -> io::Result<()> {
+    let output_path = args
+        .output
+        .unwrap_or_else(|| default_signature_path(&args.file));
+    if output_path.exists() && !args.force {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists (use --force to overwrite)",
+                output_path.display()
+            ),
+        ));
+    }
+
+    let mut store = tallow_store::identity::IdentityStore::new();
+    store
+        .load_or_generate("")
+        .map_err(|e| io::Error::other(format!("Failed to load identity: {}", e)))?;
+    let identity = store
+        .keypair()
+        .ok_or_else(|| io::Error::other("No identity available"))?;
+
+    let signature = sign_file(&args.file, identity)?;
+    let bytes = signature
+        .to_bytes()
+        .map_err(|e| io::Error::other(format!("Failed to encode signature: {}", e)))?;
+    std::fs::write(&output_path, bytes)?;
+
+    let signer = hex::encode(signature.signer_fingerprint);
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "file_signed",
+                "file": args.file.display().to_string(),
+                "signature": output_path.display().to_string(),
+                "signer": signer,
+                "algorithm": format!("{:?}", signature.algorithm),
+            })
+        );
+    } else {
+        output::color::success(&format!("Signature written to {}", output_path.display()));
+        println!("  Signer key: {}", signer);
+        println!("  Verify with: tallow verify <file> --key {}", signer);
+    }
+    Ok(())
+}
+
+/// Execute the verify command
+pub fn execute_verify(args: VerifyArgs, json: bool) -> io::Result<()> {
+    let (expected, signer_label) = match (args.key.as_deref(), args.contact.as_deref()) {
+        (Some(key), _) => (parse_key(key)?, key.to_string()),
+        (None, Some(query)) => {
+            let db = tallow_store::contacts::ContactDatabase::new();
+            let contact = db
+                .resolve_one(query)
+                .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e.to_string()))?;
+            let key = <[u8; 32]>::try_from(contact.public_key.as_slice()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Contact '{}' has no identity key on file", contact.name),
+                )
+            })?;
+            (key, contact.name.clone())
+        }
+        (None, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "either --key or --contact is required",
+            ))
+        }
+    };
+
+    let signature_path = args
+        .signature
+        .unwrap_or_else(|| default_signature_path(&args.file));
+    let signature = DetachedSignature::from_bytes(&std::fs::read(&signature_path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    verify_file(&args.file, &signature, &expected)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "signature_verified",
+                "file": args.file.display().to_string(),
+                "signer": hex::encode(signature.signer_fingerprint),
+            })
+        );
+    } else {
+        output::color::success(&format!(
+            "Good signature on {} from {}",
+            args.file.display(),
+            signer_label
+        ));
+    }
+    Ok(())
+}
+
+/// `<file>.tallowsig` beside the file
+fn default_signature_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Parse a 32-byte signer key, ignoring `:`, `-` and space separators
+fn parse_key(key: &str) -> io::Result<[u8; 32]> {
+    let cleaned: String = key
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | ' '))
+        .collect();
+    hex::decode(&cleaned)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--key must be a 64-character hex public key",
+            )
+        })
+}
+
+/// Stream a file through BLAKE3
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = tallow_crypto::hash::blake3::StreamHasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize())
+}
+
+fn sign_file(path: &Path, identity: &IdentityKeyPair) -> io::Result<DetachedSignature> {
+    DetachedSignature::sign(identity.signer(), hash_file(path)?)
+        .map_err(|e| io::Error::other(format!("Signing failed: {}", e)))
+}
+
+fn verify_file(path: &Path, signature: &DetachedSignature, expected: &[u8; 32]) -> io::Result<()> {
+    signature
+        .verify(&hash_file(path)?, expected)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_signature_path() {
+        assert_eq!(
+            default_signature_path(Path::new("dist/app.tar.gz")),
+            PathBuf::from("dist/app.tar.gz.tallowsig")
+        );
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let key = [0xabu8; 32];
+        assert_eq!(parse_key(&hex::encode(key)).unwrap(), key);
+        let grouped = key.map(|b| format!("{:02x}", b)).join(":");
+        assert_eq!(parse_key(&grouped).unwrap(), key);
+        assert!(parse_key("abcd").is_err());
+        assert!(parse_key("not hex").is_err());
+    }
+
+    #[test]
+    fn test_sign_and_verify_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("release.bin");
+        std::fs::write(&path, vec![7u8; 200_000]).unwrap();
+        let identity = IdentityKeyPair::generate().unwrap();
+        let other = IdentityKeyPair::generate().unwrap();
+
+        let signature = sign_file(&path, &identity).unwrap();
+        let signature = DetachedSignature::from_bytes(&signature.to_bytes().unwrap()).unwrap();
+        verify_file(&path, &signature, identity.id()).unwrap();
+
+        let err = verify_file(&path, &signature, other.id()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("not the expected key"), "{}", err);
+
+        std::fs::write(&path, vec![8u8; 200_000]).unwrap();
+        let err = verify_file(&path, &signature, identity.id()).unwrap_err();
+        assert!(err.to_string().contains("file does not match"), "{}", err);
+    }
+}
@@ -37,10 +37,7 @@ pub async fn execute(args: SpeedTestArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
     // Generate a unique benchmark room code to avoid collisions
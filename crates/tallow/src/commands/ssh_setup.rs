@@ -74,21 +74,27 @@ async fn send_ssh_key(args: &SshSetupArgs, json: bool) -> io::Result<()> {
         custom_code: args.code.clone(),
         words: None,
         qr: false,
+        qr_out: None,
         no_clipboard: false,
         ignore_stdin: true,
         to: None,
         room: None,
         compress: "none".to_string(), // SSH keys are small, no compression needed
+        chunk_checksum: false,
+        hash: "blake3".to_string(),
         strip_metadata: false,
         encrypt_filenames: false,
         relay: args.relay.clone(),
         relay_pass: args.relay_pass.clone(),
+        relay_token: std::env::var("TALLOW_RELAY_TOKEN").ok(),
         proxy: None,
         tor: false,
         discover: false,
         exclude: None,
         git: false,
         throttle: None,
+        pace: false,
+        window: 64,
         ask: false,
         verify: true, // Always verify for SSH key exchange
         local: false,
@@ -96,7 +102,11 @@ async fn send_ssh_key(args: &SshSetupArgs, json: bool) -> io::Result<()> {
         dry_run: false,
         notify: false,
         max_retries: 5,
+        handshake_timeout: 30,
+        psk_file: None,
+        context: None,
         no_hooks: true, // No hooks for SSH key exchange
+        on_complete: None,
     };
 
     if !json {
@@ -135,6 +145,7 @@ async fn receive_ssh_key(args: &SshSetupArgs, json: bool) -> io::Result<()> {
         auto_accept: false,
         relay: args.relay.clone(),
         relay_pass: args.relay_pass.clone(),
+        relay_token: std::env::var("TALLOW_RELAY_TOKEN").ok(),
         proxy: None,
         tor: false,
         advertise: false,
@@ -144,8 +155,21 @@ async fn receive_ssh_key(args: &SshSetupArgs, json: bool) -> io::Result<()> {
         no_p2p: false,
         notify: false,
         max_retries: 5,
+        handshake_timeout: 30,
+        psk_file: None,
+        context: None,
         no_hooks: true, // No hooks for SSH key exchange
+        on_complete: None,
         per_file: false,
+        accept: Vec::new(),
+        output_template: None,
+        on_conflict: None,
+        preserve_structure: false,
+        to_clipboard: false,
+        checksum_file: None,
+        split_size: None,
+        once: false,
+        wait_timeout: None,
     };
 
     crate::commands::receive::execute(receive_args, json).await?;
@@ -84,7 +84,7 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     let _offer = pipeline
         .prepare(std::slice::from_ref(&args.dir))
         .await
-        .map_err(|e| io::Error::other(format!("Failed to scan directory: {}", e)))?;
+        .map_err(crate::error::context("Failed to scan directory"))?;
 
     let manifest = pipeline.manifest();
 
@@ -108,7 +108,7 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     // Resolve relay address (proxy-aware: avoids DNS leaks)
     let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
+        .map_err(crate::error::context("Relay resolution failed"))?;
 
     let mut relay = match resolved {
         tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -133,23 +133,13 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
-
     let peer_present = relay
         .connect(&room_id, pw_ref)
         .await
-        .map_err(|e| io::Error::other(format!("Relay connection failed: {}", e)))?;
+        .map_err(crate::error::context("Relay connection failed"))?;
 
     if !peer_present {
         if !json {
@@ -158,7 +148,7 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
         relay
             .wait_for_peer()
             .await
-            .map_err(|e| io::Error::other(format!("Wait for peer failed: {}", e)))?;
+            .map_err(crate::error::context("Wait for peer failed"))?;
     }
 
     if !json {
@@ -175,15 +165,15 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     // Step 1: Send HandshakeInit
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("Handshake init failed: {}", e)))?;
+        .map_err(crate::error::context("Handshake init failed"))?;
     encode_buf.clear();
     codec
         .encode_msg(&init_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Encode HandshakeInit"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Send HandshakeInit"))?;
 
     // Step 2: Receive HandshakeResponse
     let n = tokio::time::timeout(
@@ -192,12 +182,12 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for response"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeResponse: {}", e)))?;
+    .map_err(crate::error::context("Receive HandshakeResponse"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let resp_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeResponse: {}", e)))?;
+        .map_err(crate::error::context("Decode HandshakeResponse"))?;
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -208,20 +198,20 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
             kem_public_key,
             nonce,
         }) => {
-            let (kem_msg, session_key_result) = handshake
+            let kem_msg = handshake
                 .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake response processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context(
+                    "Handshake response processing failed",
+                ))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&kem_msg, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeKem"))?;
             relay
                 .forward(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeKem"))?;
 
             // Step 4: Receive HandshakeComplete
             let n = tokio::time::timeout(
@@ -230,19 +220,17 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for confirmation"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeComplete: {}", e)))?;
+            .map_err(crate::error::context("Receive HandshakeComplete"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let complete_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeComplete: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeComplete"))?;
 
-            match complete_msg {
-                Some(Message::HandshakeComplete { confirmation }) => {
-                    handshake
-                        .verify_receiver_confirmation(&confirmation)
-                        .map_err(|e| io::Error::other(format!("Key confirmation failed: {}", e)))?;
-                }
+            session_key = match complete_msg {
+                Some(Message::HandshakeComplete { confirmation }) => handshake
+                    .verify_receiver_confirmation(&confirmation)
+                    .map_err(crate::error::context("Key confirmation failed"))?,
                 other => {
                     relay.close().await;
                     return Err(io::Error::other(format!(
@@ -250,9 +238,7 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
                         other
                     )));
                 }
-            }
-
-            session_key = session_key_result;
+            };
         }
         Some(Message::FileOffer { .. }) => {
             relay.close().await;
@@ -279,7 +265,7 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
     // Serialize manifest before mutable borrow to avoid conflict with pipeline.manifest() ref
     let manifest_bytes = manifest
         .to_bytes()
-        .map_err(|e| io::Error::other(format!("Failed to serialize manifest: {}", e)))?;
+        .map_err(crate::error::context("Failed to serialize manifest"))?;
     pipeline.set_session_key(*session_key.as_bytes());
 
     let exchange_msg = Message::ManifestExchange {
@@ -289,22 +275,22 @@ pub async fn execute(args: SyncArgs, json: bool) -> io::Result<()> {
 
     codec
         .encode_msg(&exchange_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode manifest failed: {}", e)))?;
+        .map_err(crate::error::context("Encode manifest failed"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send manifest failed: {}", e)))?;
+        .map_err(crate::error::context("Send manifest failed"))?;
 
     // Wait for peer's manifest exchange response
     let n = relay
         .receive(&mut recv_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Receive failed: {}", e)))?;
+        .map_err(crate::error::context("Receive failed"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let response = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode failed: {}", e)))?;
+        .map_err(crate::error::context("Decode failed"))?;
 
     match response {
         Some(Message::ManifestExchange {
@@ -365,7 +351,7 @@ async fn handle_manifest_exchange(
     // Parse remote manifest
     let remote_manifest =
         tallow_protocol::transfer::FileManifest::from_bytes(&remote_manifest_bytes)
-            .map_err(|e| io::Error::other(format!("Invalid remote manifest: {}", e)))?;
+            .map_err(crate::error::context("Invalid remote manifest"))?;
 
     // Compute diff
     let diff =
@@ -438,29 +424,29 @@ async fn handle_manifest_exchange(
         let offer_messages = delta_pipeline
             .prepare(&files_to_send)
             .await
-            .map_err(|e| io::Error::other(format!("Failed to prepare delta: {}", e)))?;
+            .map_err(crate::error::context("Failed to prepare delta"))?;
 
         // Send FileOffer
         for msg in &offer_messages {
             encode_buf.clear();
             codec
                 .encode_msg(msg, encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode failed: {}", e)))?;
+                .map_err(crate::error::context("Encode failed"))?;
             relay
                 .forward(encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send failed: {}", e)))?;
+                .map_err(crate::error::context("Send failed"))?;
         }
 
         // Wait for accept
         let n = relay
             .receive(recv_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Receive failed: {}", e)))?;
+            .map_err(crate::error::context("Receive failed"))?;
         let mut accept_buf = BytesMut::from(&recv_buf[..n]);
         let accept = codec
             .decode_msg(&mut accept_buf)
-            .map_err(|e| io::Error::other(format!("Decode failed: {}", e)))?;
+            .map_err(crate::error::context("Decode failed"))?;
 
         match accept {
             Some(Message::FileAccept { .. }) => {
@@ -484,11 +470,13 @@ async fn handle_manifest_exchange(
 
         let throttle_bps = crate::commands::send::parse_throttle_pub(&args.throttle)?;
 
-        for file in &files_to_send {
+        // Manifest order (sorted), which chunk indices refer to
+        let source_paths = delta_pipeline.source_paths().to_vec();
+        for file in &source_paths {
             let chunk_messages = delta_pipeline
                 .chunk_file(file, chunk_index)
                 .await
-                .map_err(|e| io::Error::other(format!("Chunk failed: {}", e)))?;
+                .map_err(crate::error::context("Chunk failed"))?;
 
             for chunk_msg in &chunk_messages {
                 if throttle_bps > 0 {
@@ -503,20 +491,20 @@ async fn handle_manifest_exchange(
                 encode_buf.clear();
                 codec
                     .encode_msg(chunk_msg, encode_buf)
-                    .map_err(|e| io::Error::other(format!("Encode failed: {}", e)))?;
+                    .map_err(crate::error::context("Encode failed"))?;
                 relay
                     .forward(encode_buf)
                     .await
-                    .map_err(|e| io::Error::other(format!("Send failed: {}", e)))?;
+                    .map_err(crate::error::context("Send failed"))?;
 
                 let n = relay
                     .receive(recv_buf)
                     .await
-                    .map_err(|e| io::Error::other(format!("Receive ack failed: {}", e)))?;
+                    .map_err(crate::error::context("Receive ack failed"))?;
                 let mut ack_buf = BytesMut::from(&recv_buf[..n]);
                 if let Some(Message::Ack { .. }) = codec
                     .decode_msg(&mut ack_buf)
-                    .map_err(|e| io::Error::other(format!("Decode ack failed: {}", e)))?
+                    .map_err(crate::error::context("Decode ack failed"))?
                 {
                     if let Message::Chunk { ref data, .. } = chunk_msg {
                         total_sent += data.len() as u64;
@@ -544,11 +532,11 @@ async fn handle_manifest_exchange(
         encode_buf.clear();
         codec
             .encode_msg(&delete_msg, encode_buf)
-            .map_err(|e| io::Error::other(format!("Encode delete list failed: {}", e)))?;
+            .map_err(crate::error::context("Encode delete list failed"))?;
         relay
             .forward(encode_buf)
             .await
-            .map_err(|e| io::Error::other(format!("Send delete list failed: {}", e)))?;
+            .map_err(crate::error::context("Send delete list failed"))?;
     }
 
     // Send completion
@@ -560,11 +548,11 @@ async fn handle_manifest_exchange(
     encode_buf.clear();
     codec
         .encode_msg(&complete_msg, encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode complete failed: {}", e)))?;
+        .map_err(crate::error::context("Encode complete failed"))?;
     relay
         .forward(encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send complete failed: {}", e)))?;
+        .map_err(crate::error::context("Send complete failed"))?;
 
     if json {
         println!(
@@ -46,7 +46,7 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
 
     let (mut event_rx, watch_handle) =
         tallow_protocol::transfer::watch::start_watcher(watch_config)
-            .map_err(|e| io::Error::other(format!("Failed to start watcher: {}", e)))?;
+            .map_err(crate::error::context("Failed to start watcher"))?;
 
     // Generate code phrase
     let code_phrase = args
@@ -81,7 +81,7 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
     // Resolve relay address (proxy-aware: avoids DNS leaks)
     let resolved = tallow_net::relay::resolve_relay_proxy(&args.relay, proxy_config.as_ref())
         .await
-        .map_err(|e| io::Error::other(format!("Relay resolution failed: {}", e)))?;
+        .map_err(crate::error::context("Relay resolution failed"))?;
 
     let mut relay = match resolved {
         tallow_net::relay::ResolvedRelay::Addr(addr) => {
@@ -106,23 +106,13 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
     }
 
     // Hash relay password for authentication (if provided)
-    let password_hash: Option<[u8; 32]> = args
-        .relay_pass
-        .as_ref()
-        .map(|pass| blake3::hash(pass.as_bytes()).into());
+    let password_hash = crate::output::prompts::relay_password_hash(args.relay_pass.as_deref())?;
     let pw_ref = password_hash.as_ref();
 
-    if args.relay_pass.is_some() && std::env::var("TALLOW_RELAY_PASS").is_err() {
-        tracing::warn!(
-            "Relay password passed via CLI argument -- visible in process list. \
-             Use TALLOW_RELAY_PASS env var for better security."
-        );
-    }
-
     let peer_present = relay
         .connect(&room_id, pw_ref)
         .await
-        .map_err(|e| io::Error::other(format!("Relay connection failed: {}", e)))?;
+        .map_err(crate::error::context("Relay connection failed"))?;
 
     if !peer_present {
         if !json {
@@ -131,7 +121,7 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
         relay
             .wait_for_peer()
             .await
-            .map_err(|e| io::Error::other(format!("Wait for peer failed: {}", e)))?;
+            .map_err(crate::error::context("Wait for peer failed"))?;
     }
 
     if !json {
@@ -148,15 +138,15 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
     // Step 1: Send HandshakeInit
     let init_msg = handshake
         .init()
-        .map_err(|e| io::Error::other(format!("Handshake init failed: {}", e)))?;
+        .map_err(crate::error::context("Handshake init failed"))?;
     encode_buf.clear();
     codec
         .encode_msg(&init_msg, &mut encode_buf)
-        .map_err(|e| io::Error::other(format!("Encode HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Encode HandshakeInit"))?;
     relay
         .forward(&encode_buf)
         .await
-        .map_err(|e| io::Error::other(format!("Send HandshakeInit: {}", e)))?;
+        .map_err(crate::error::context("Send HandshakeInit"))?;
 
     // Step 2: Receive HandshakeResponse
     let n = tokio::time::timeout(
@@ -165,12 +155,12 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
     )
     .await
     .map_err(|_| io::Error::other("Handshake timeout waiting for response"))?
-    .map_err(|e| io::Error::other(format!("Receive HandshakeResponse: {}", e)))?;
+    .map_err(crate::error::context("Receive HandshakeResponse"))?;
 
     let mut decode_buf = BytesMut::from(&recv_buf[..n]);
     let resp_msg = codec
         .decode_msg(&mut decode_buf)
-        .map_err(|e| io::Error::other(format!("Decode HandshakeResponse: {}", e)))?;
+        .map_err(crate::error::context("Decode HandshakeResponse"))?;
 
     let session_key: tallow_protocol::kex::SessionKey;
 
@@ -181,20 +171,20 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
             kem_public_key,
             nonce,
         }) => {
-            let (kem_msg, session_key_result) = handshake
+            let kem_msg = handshake
                 .process_response(selected_kem, &cpace_public, &kem_public_key, &nonce)
-                .map_err(|e| {
-                    io::Error::other(format!("Handshake response processing failed: {}", e))
-                })?;
+                .map_err(crate::error::context(
+                    "Handshake response processing failed",
+                ))?;
 
             encode_buf.clear();
             codec
                 .encode_msg(&kem_msg, &mut encode_buf)
-                .map_err(|e| io::Error::other(format!("Encode HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Encode HandshakeKem"))?;
             relay
                 .forward(&encode_buf)
                 .await
-                .map_err(|e| io::Error::other(format!("Send HandshakeKem: {}", e)))?;
+                .map_err(crate::error::context("Send HandshakeKem"))?;
 
             // Step 4: Receive HandshakeComplete
             let n = tokio::time::timeout(
@@ -203,19 +193,17 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
             )
             .await
             .map_err(|_| io::Error::other("Handshake timeout waiting for confirmation"))?
-            .map_err(|e| io::Error::other(format!("Receive HandshakeComplete: {}", e)))?;
+            .map_err(crate::error::context("Receive HandshakeComplete"))?;
 
             let mut decode_buf = BytesMut::from(&recv_buf[..n]);
             let complete_msg = codec
                 .decode_msg(&mut decode_buf)
-                .map_err(|e| io::Error::other(format!("Decode HandshakeComplete: {}", e)))?;
+                .map_err(crate::error::context("Decode HandshakeComplete"))?;
 
-            match complete_msg {
-                Some(Message::HandshakeComplete { confirmation }) => {
-                    handshake
-                        .verify_receiver_confirmation(&confirmation)
-                        .map_err(|e| io::Error::other(format!("Key confirmation failed: {}", e)))?;
-                }
+            session_key = match complete_msg {
+                Some(Message::HandshakeComplete { confirmation }) => handshake
+                    .verify_receiver_confirmation(&confirmation)
+                    .map_err(crate::error::context("Key confirmation failed"))?,
                 other => {
                     relay.close().await;
                     return Err(io::Error::other(format!(
@@ -223,9 +211,7 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
                         other
                     )));
                 }
-            }
-
-            session_key = session_key_result;
+            };
         }
         Some(Message::FileOffer { .. }) => {
             relay.close().await;
@@ -328,7 +314,9 @@ pub async fn execute(args: WatchArgs, json: bool) -> io::Result<()> {
             Ok(Some(Message::FileAccept { .. })) => {
                 // Send chunks
                 let mut chunk_index: u64 = 0;
-                for file in &files_to_send {
+                // Manifest order (sorted), which chunk indices refer to
+                let source_paths = pipeline.source_paths().to_vec();
+                for file in &source_paths {
                     let chunk_messages = match pipeline.chunk_file(file, chunk_index).await {
                         Ok(msgs) => msgs,
                         Err(e) => {
@@ -0,0 +1,134 @@
+//! Errors from every subsystem crate, classified for exit codes
+//!
+//! [`TallowError`] and [`ErrorKind`] live in the `tallow-error` crate so
+//! the other Tallow crates can share them; [`crate::exit_codes::for_error`]
+//! maps the kind to the process exit code.
+
+pub use tallow_error::{classified, context, ErrorKind, TallowError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exit_codes;
+    use std::io;
+    use tallow_crypto::CryptoError;
+    use tallow_net::NetworkError;
+    use tallow_protocol::ProtocolError;
+    use tallow_store::StoreError;
+
+    /// Exit code for `err` after it has been through a command's `?`
+    fn code<E: Into<TallowError>>(err: E) -> i32 {
+        exit_codes::for_error(&context("Doing something")(err))
+    }
+
+    #[test]
+    fn test_crypto_errors() {
+        assert_eq!(
+            code(CryptoError::PakeFailure("x".into())),
+            exit_codes::AUTH_FAILURE
+        );
+        assert_eq!(
+            code(CryptoError::Decryption("x".into())),
+            exit_codes::AUTH_FAILURE
+        );
+        assert_eq!(
+            code(CryptoError::Serialization("auth".into())),
+            exit_codes::ERROR
+        );
+    }
+
+    #[test]
+    fn test_network_errors() {
+        assert_eq!(
+            code(NetworkError::AuthenticationFailed),
+            exit_codes::AUTH_FAILURE
+        );
+        assert_eq!(code(NetworkError::Timeout), exit_codes::NETWORK_ERROR);
+        assert_eq!(
+            code(NetworkError::RelayError("password rejected".into())),
+            exit_codes::NETWORK_ERROR
+        );
+        assert_eq!(
+            code(NetworkError::Io(io::Error::other("config"))),
+            exit_codes::NETWORK_ERROR
+        );
+        let exhausted = NetworkError::RetriesExhausted {
+            attempts: 4,
+            last: Box::new(NetworkError::Timeout),
+        };
+        let err = context("Send chunk failed")(exhausted);
+        assert_eq!(exit_codes::for_error(&err), exit_codes::RETRIES_EXHAUSTED);
+        assert_eq!(exit_codes::attempts(&err), Some(4));
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        assert_eq!(
+            code(ProtocolError::HandshakeFailed("x".into())),
+            exit_codes::AUTH_FAILURE
+        );
+        assert_eq!(
+            code(ProtocolError::KeyConfirmationFailed),
+            exit_codes::AUTH_FAILURE
+        );
+        assert_eq!(
+            code(ProtocolError::DiskFull("x".into())),
+            exit_codes::DISK_FULL
+        );
+        // Classification doesn't depend on what the message says
+        assert_eq!(
+            code(ProtocolError::TransferFailed(
+                "permission denied by config".into()
+            )),
+            exit_codes::ERROR
+        );
+    }
+
+    #[test]
+    fn test_store_errors() {
+        assert_eq!(
+            code(StoreError::ConfigError("x".into())),
+            exit_codes::CONFIG_ERROR
+        );
+        assert_eq!(
+            code(StoreError::Io(io::Error::from(
+                io::ErrorKind::PermissionDenied
+            ))),
+            exit_codes::PERMISSION_DENIED
+        );
+        assert_eq!(
+            code(StoreError::TrustError("auth".into())),
+            exit_codes::ERROR
+        );
+    }
+
+    #[test]
+    fn test_context_keeps_message_and_io_kind() {
+        let err = context("Write failed")(ProtocolError::DiskFull("no space".into()));
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        assert_eq!(err.to_string(), "Write failed: Disk full: no space");
+
+        let nested = context("Receive failed")(TallowError::from(err));
+        assert_eq!(exit_codes::for_error(&nested), exit_codes::DISK_FULL);
+        assert!(nested
+            .to_string()
+            .starts_with("Receive failed: Write failed: "));
+    }
+
+    #[test]
+    fn test_partial_success_exit_code() {
+        let err = classified(TallowError::PartialSuccess {
+            skipped: 2,
+            total: 10,
+        });
+        assert_eq!(exit_codes::for_error(&err), exit_codes::PARTIAL_SUCCESS);
+        assert_eq!(
+            exit_codes::classification(exit_codes::for_error(&err)),
+            "partial_success"
+        );
+        assert_eq!(
+            err.to_string(),
+            "2 of 10 files could not be read and were not sent"
+        );
+    }
+}
@@ -1,4 +1,14 @@
 //! Exit code constants
+//!
+//! Also maps a command's final error to its exit code, using the
+//! classification in [`crate::error`]. A network operation
+//! that failed after using up its retry budget exits with
+//! [`RETRIES_EXHAUSTED`], so scripts can tell a flaky link (worth
+//! re-running) from a failure that retrying will not fix.
+
+use crate::error::{ErrorKind, TallowError};
+use std::io;
+use tallow_net::NetworkError;
 
 /// Success exit code
 pub const SUCCESS: i32 = 0;
@@ -23,3 +33,155 @@ pub const PERMISSION_DENIED: i32 = 6;
 
 /// Invalid configuration
 pub const CONFIG_ERROR: i32 = 7;
+
+/// Output disk full (transfer can be resumed after freeing space)
+pub const DISK_FULL: i32 = 8;
+
+/// A transient network failure outlasted every retry (re-running may succeed)
+pub const RETRIES_EXHAUSTED: i32 = 9;
+
+/// `receive --wait-timeout` ran out before any sender connected
+pub const NO_SENDER: i32 = 10;
+
+/// The transfer finished, but some files couldn't be read and were left out
+pub const PARTIAL_SUCCESS: i32 = 11;
+
+/// Wrap the error of a retried network operation, prefixed with `context`
+///
+/// An exhausted retry budget stays recognizable after the conversion.
+pub fn network_error(context: &str, err: NetworkError) -> io::Error {
+    crate::error::context(context)(err)
+}
+
+/// Attempts made by the network operation behind `err`, if it was retried
+pub fn attempts(err: &io::Error) -> Option<u32> {
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<TallowError>())
+        .and_then(TallowError::attempts)
+}
+
+/// Exit code for a command that failed with `err`
+///
+/// Errors carrying a [`TallowError`] are classified by variant; anything
+/// else by its `io::ErrorKind`. The message text is never inspected.
+pub fn for_error(err: &io::Error) -> i32 {
+    for_kind(ErrorKind::of_io(err))
+}
+
+/// Exit code for a failure category
+pub fn for_kind(kind: ErrorKind) -> i32 {
+    match kind {
+        ErrorKind::Other => ERROR,
+        ErrorKind::Auth => AUTH_FAILURE,
+        ErrorKind::Cancelled => CANCELLED,
+        ErrorKind::Network => NETWORK_ERROR,
+        ErrorKind::NotFound => FILE_NOT_FOUND,
+        ErrorKind::PermissionDenied => PERMISSION_DENIED,
+        ErrorKind::Config => CONFIG_ERROR,
+        ErrorKind::DiskFull => DISK_FULL,
+        ErrorKind::RetriesExhausted => RETRIES_EXHAUSTED,
+        ErrorKind::NoSender => NO_SENDER,
+        ErrorKind::PartialSuccess => PARTIAL_SUCCESS,
+    }
+}
+
+/// Classification reported in `--json` error output for exit code `code`
+pub fn classification(code: i32) -> &'static str {
+    match code {
+        RETRIES_EXHAUSTED => "retries_exhausted",
+        CANCELLED => "cancelled",
+        NO_SENDER => "no_sender",
+        PARTIAL_SUCCESS => "partial_success",
+        _ => "unrecoverable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tallow_net::transport::reconnect::{self, ReconnectConfig};
+    use tallow_net::transport::PeerChannel;
+
+    /// Channel whose sends always fail with `fail()`
+    struct FailingChannel {
+        sends: u32,
+        fail: fn() -> NetworkError,
+    }
+
+    impl PeerChannel for FailingChannel {
+        async fn send_message(&mut self, _data: &[u8]) -> tallow_net::Result<()> {
+            self.sends += 1;
+            Err((self.fail)())
+        }
+        async fn receive_message(&mut self, _buf: &mut [u8]) -> tallow_net::Result<usize> {
+            Ok(0)
+        }
+        async fn close(&mut self) {}
+        fn transport_description(&self) -> String {
+            "test".to_string()
+        }
+    }
+
+    fn budget(max_retries: u32) -> ReconnectConfig {
+        ReconnectConfig::new(
+            max_retries,
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+            0.0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_reported() {
+        let mut channel = FailingChannel {
+            sends: 0,
+            fail: || {
+                NetworkError::Io(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                ))
+            },
+        };
+        let err = reconnect::send_with_retry(&mut channel, b"chunk", &budget(2))
+            .await
+            .map_err(|e| network_error("Send chunk failed", e))
+            .unwrap_err();
+
+        assert_eq!(channel.sends, 3);
+        assert_eq!(attempts(&err), Some(3));
+        assert_eq!(for_error(&err), RETRIES_EXHAUSTED);
+        assert_eq!(classification(for_error(&err)), "retries_exhausted");
+        assert!(err.to_string().starts_with("Send chunk failed: "));
+        assert!(err.to_string().contains("gave up after 3 attempts"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure_not_retried() {
+        let mut channel = FailingChannel {
+            sends: 0,
+            fail: || NetworkError::AuthenticationFailed,
+        };
+        let err = reconnect::send_with_retry(&mut channel, b"chunk", &budget(5))
+            .await
+            .map_err(|e| network_error("Send chunk failed", e))
+            .unwrap_err();
+
+        assert_eq!(channel.sends, 1);
+        assert_eq!(attempts(&err), None);
+        assert_eq!(for_error(&err), AUTH_FAILURE);
+        assert_eq!(classification(for_error(&err)), "unrecoverable");
+    }
+
+    #[test]
+    fn test_error_kinds_mapped() {
+        let code = |kind| for_error(&io::Error::new(kind, "x"));
+        assert_eq!(code(io::ErrorKind::NotFound), FILE_NOT_FOUND);
+        assert_eq!(code(io::ErrorKind::TimedOut), NETWORK_ERROR);
+        assert_eq!(code(io::ErrorKind::Interrupted), CANCELLED);
+        assert_eq!(code(io::ErrorKind::StorageFull), DISK_FULL);
+        // Untyped errors are not classified by their wording
+        assert_eq!(for_error(&io::Error::other("bad config key")), ERROR);
+        assert_eq!(for_error(&io::Error::other("auth denied")), ERROR);
+    }
+}
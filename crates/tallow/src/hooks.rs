@@ -10,8 +10,15 @@
 //! - Commands execute via the system shell with a 30-second timeout
 //! - Sensitive data (keys, secrets) is NOT passed in environment variables
 //! - Hook failures log a warning but do NOT abort the transfer
+//!
+//! The completion hook (`--on-complete` / `transfer.on_complete`) is
+//! stricter: its command is split into arguments and executed directly,
+//! never through a shell, so file names and error text in its metadata
+//! can't be turned into shell syntax.
 
+use serde::Serialize;
 use std::io;
+use std::time::Duration;
 use tallow_store::config::HookConfig;
 
 /// Maximum time a hook is allowed to run before being killed
@@ -174,14 +181,14 @@ async fn run_hook_command(command: &str, env: &HookEnv) -> io::Result<bool> {
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
 
-    let mut child = cmd.spawn()?;
+    let child = cmd.spawn()?;
+    wait_with_timeout(child).await
+}
 
-    // Apply timeout — use child.wait() so we retain the handle for kill-on-timeout
-    let result = tokio::time::timeout(
-        std::time::Duration::from_secs(HOOK_TIMEOUT_SECS),
-        child.wait(),
-    )
-    .await;
+/// Wait for a hook process, killing it once [`HOOK_TIMEOUT_SECS`] pass
+async fn wait_with_timeout(mut child: tokio::process::Child) -> io::Result<bool> {
+    // Use child.wait() so we retain the handle for kill-on-timeout
+    let result = tokio::time::timeout(Duration::from_secs(HOOK_TIMEOUT_SECS), child.wait()).await;
 
     match result {
         Ok(Ok(status)) => Ok(status.success()),
@@ -201,6 +208,276 @@ async fn run_hook_command(command: &str, env: &HookEnv) -> io::Result<bool> {
     }
 }
 
+/// Outcome of a finished transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionStatus {
+    /// Every file arrived and verified
+    Success,
+    /// The transfer ended with an error
+    Failure,
+    /// The transfer finished, but some files were left out
+    Partial,
+}
+
+impl CompletionStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Partial => "partial",
+        }
+    }
+}
+
+/// Transfer metadata handed to the completion hook
+///
+/// Commands see it both as `TALLOW_*` environment variables and as a JSON
+/// document on stdin; webhooks receive the JSON document as the POST body.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Completion {
+    /// Transfer direction ("send" or "receive")
+    pub direction: String,
+    /// Unset until the transfer succeeds or fails
+    pub status: Option<CompletionStatus>,
+    /// Files sent or written
+    pub files: Vec<String>,
+    /// Total transfer size in bytes
+    pub total_size: u64,
+    /// Wall-clock time of the whole command
+    pub duration_ms: u64,
+    /// Error message on failure
+    pub error: Option<String>,
+}
+
+impl Completion {
+    /// Start recording a transfer in `direction`
+    pub fn new(direction: &str) -> Self {
+        Self {
+            direction: direction.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Mark the transfer as successful
+    pub fn succeeded(&mut self, files: Vec<String>, total_size: u64) {
+        self.status = Some(CompletionStatus::Success);
+        self.files = files;
+        self.total_size = total_size;
+    }
+
+    /// Record the command's result and running time
+    ///
+    /// An error marks the transfer failed, except a partial success,
+    /// which keeps the files recorded as sent. A command that returns
+    /// `Ok` without calling [`Completion::succeeded`] (a dry run, say)
+    /// keeps no status and the hook does not fire.
+    pub fn finish(&mut self, result: &io::Result<()>, elapsed: Duration) {
+        self.duration_ms = elapsed.as_millis() as u64;
+        if let Err(e) = result {
+            self.status = Some(match crate::error::ErrorKind::of_io(e) {
+                crate::error::ErrorKind::PartialSuccess => CompletionStatus::Partial,
+                _ => CompletionStatus::Failure,
+            });
+            self.error = Some(e.to_string());
+        }
+    }
+}
+
+/// Where the completion notification goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionTarget {
+    /// A program and its arguments, executed without a shell
+    Command(Vec<String>),
+    /// An http(s) URL that receives the metadata as a JSON POST
+    Webhook(String),
+}
+
+impl CompletionTarget {
+    /// Parse a command line or webhook URL; `None` when `spec` is blank
+    pub fn parse(spec: &str) -> io::Result<Option<Self>> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Ok(None);
+        }
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Ok(Some(Self::Webhook(spec.to_string())));
+        }
+        let argv = split_command(spec)?;
+        if argv.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::Command(argv)))
+    }
+}
+
+/// Split a command line into arguments without invoking a shell
+///
+/// Whitespace separates arguments. Single quotes keep their contents
+/// verbatim; double quotes do too, except that `\"` and `\\` inside them
+/// stand for a quote and a backslash. Nothing else is special: there is no
+/// variable, glob, tilde or command substitution, and `;`, `|` and `&` are
+/// ordinary characters. Backslashes outside quotes are literal so Windows
+/// paths work unquoted.
+pub fn split_command(spec: &str) -> io::Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = spec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(unterminated_quote(spec)),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"' | '\\')) => {
+                            current.extend(chars.next());
+                        }
+                        Some(c) => current.push(c),
+                        None => return Err(unterminated_quote(spec)),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn unterminated_quote(spec: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Unterminated quote in on-complete command: {}", spec),
+    )
+}
+
+/// Notifies a command or webhook when a transfer finishes
+#[derive(Debug, Default)]
+pub struct CompletionHook {
+    target: Option<CompletionTarget>,
+}
+
+impl CompletionHook {
+    /// Pick the hook from `--on-complete`, falling back to the config value
+    ///
+    /// `--no-hooks` (`enabled == false`) only silences the configured hook;
+    /// a command given on the same command line still runs. A malformed
+    /// command is an error here, before any transfer starts.
+    pub fn resolve(cli: Option<&str>, config: &str, enabled: bool) -> io::Result<Self> {
+        let spec = match cli {
+            Some(spec) => spec,
+            None if enabled => config,
+            None => "",
+        };
+        Ok(Self {
+            target: CompletionTarget::parse(spec)?,
+        })
+    }
+
+    /// Deliver `completion` to the hook
+    ///
+    /// A no-op without a hook or without a status. Failures are logged and
+    /// never change the transfer's own result.
+    pub async fn notify(&self, completion: &Completion) {
+        let (Some(target), Some(status)) = (&self.target, completion.status) else {
+            return;
+        };
+        let result = match target {
+            CompletionTarget::Command(argv) => run_completion_command(argv, completion).await,
+            CompletionTarget::Webhook(url) => post_webhook(url, completion).await,
+        };
+        match result {
+            Ok(true) => tracing::info!("on_complete hook ran ({})", status.as_str()),
+            Ok(false) => tracing::warn!("on_complete hook reported failure"),
+            Err(e) => tracing::warn!("on_complete hook failed: {}", e),
+        }
+    }
+}
+
+/// Execute the completion command directly, metadata in env and on stdin
+async fn run_completion_command(argv: &[String], completion: &Completion) -> io::Result<bool> {
+    use tokio::io::AsyncWriteExt;
+
+    let payload = serde_json::to_vec(completion).map_err(io::Error::other)?;
+
+    let mut cmd = tokio::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    cmd.env(
+        "TALLOW_STATUS",
+        completion.status.map_or("", |s| s.as_str()),
+    );
+    cmd.env("TALLOW_DIRECTION", &completion.direction);
+    cmd.env("TALLOW_FILES", completion.files.join(","));
+    cmd.env("TALLOW_SIZE", completion.total_size.to_string());
+    cmd.env("TALLOW_DURATION_MS", completion.duration_ms.to_string());
+    if let Some(ref error) = completion.error {
+        cmd.env("TALLOW_ERROR", error);
+    }
+    cmd.stdin(std::process::Stdio::piped());
+    cmd.stdout(std::process::Stdio::null());
+    cmd.stderr(std::process::Stdio::null());
+
+    let mut child = cmd.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A program that ignores stdin may exit before reading it
+        let _ = tokio::time::timeout(
+            Duration::from_secs(HOOK_TIMEOUT_SECS),
+            stdin.write_all(&payload),
+        )
+        .await;
+    }
+    wait_with_timeout(child).await
+}
+
+/// POST the metadata as JSON to a webhook URL
+#[cfg(feature = "webhook")]
+async fn post_webhook(url: &str, completion: &Completion) -> io::Result<bool> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(HOOK_TIMEOUT_SECS))
+        .user_agent(format!("tallow/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| io::Error::other(format!("Failed to create HTTP client: {}", e)))?;
+    let response = client
+        .post(url)
+        .json(completion)
+        .send()
+        .await
+        .map_err(|e| io::Error::other(format!("Webhook request failed: {}", e)))?;
+    Ok(response.status().is_success())
+}
+
+#[cfg(not(feature = "webhook"))]
+async fn post_webhook(_url: &str, _completion: &Completion) -> io::Result<bool> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "webhook support was not compiled in (enable the `webhook` feature)",
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -460,4 +737,162 @@ on_error = "echo 'Transfer failed: $TALLOW_ERROR'"
         assert!(!parsed.hooks.post_receive.is_empty());
         assert!(!parsed.hooks.on_error.is_empty());
     }
+
+    #[test]
+    fn test_split_command_no_shell_syntax() {
+        let argv = split_command(r#"notify 'a b' "c \"d\"" $HOME;rm *  `x` C:\tmp"#).unwrap();
+        assert_eq!(
+            argv,
+            vec!["notify", "a b", "c \"d\"", "$HOME;rm", "*", "`x`", r"C:\tmp"]
+        );
+        assert_eq!(
+            split_command("say '' done").unwrap(),
+            vec!["say", "", "done"]
+        );
+        assert!(split_command("   ").unwrap().is_empty());
+        assert!(split_command("echo 'oops").is_err());
+        assert!(split_command("echo \"oops").is_err());
+    }
+
+    #[test]
+    fn test_completion_target_parse() {
+        assert_eq!(CompletionTarget::parse("  ").unwrap(), None);
+        assert_eq!(
+            CompletionTarget::parse("https://hooks.example/t").unwrap(),
+            Some(CompletionTarget::Webhook("https://hooks.example/t".into()))
+        );
+        assert_eq!(
+            CompletionTarget::parse("notify-send 'done now'").unwrap(),
+            Some(CompletionTarget::Command(vec![
+                "notify-send".into(),
+                "done now".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_completion_hook_resolve() {
+        let hook = CompletionHook::resolve(None, "from-config", false).unwrap();
+        assert!(hook.target.is_none());
+        let hook = CompletionHook::resolve(Some("from-cli"), "from-config", false).unwrap();
+        assert_eq!(
+            hook.target,
+            Some(CompletionTarget::Command(vec!["from-cli".into()]))
+        );
+        assert!(CompletionHook::resolve(Some("bad 'quote"), "", true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_completion_hook_skips_without_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let hook = CompletionHook {
+            target: Some(CompletionTarget::Command(vec![
+                "touch".into(),
+                marker.display().to_string(),
+            ])),
+        };
+        let mut completion = Completion::new("send");
+        completion.finish(&Ok(()), Duration::from_millis(5));
+        hook.notify(&completion).await;
+        assert!(!marker.exists());
+    }
+
+    /// Hook that dumps its environment and stdin into `dir`
+    #[cfg(unix)]
+    fn recording_hook(dir: &std::path::Path) -> CompletionHook {
+        let script = dir.join("record.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             printf '%s|%s|%s|%s|%s' \"$TALLOW_STATUS\" \"$TALLOW_DIRECTION\" \
+             \"$TALLOW_FILES\" \"$TALLOW_SIZE\" \"$TALLOW_ERROR\" > \"$1/env\"\n\
+             cat > \"$1/stdin.json\"\n",
+        )
+        .unwrap();
+        CompletionHook {
+            target: Some(CompletionTarget::Command(vec![
+                "sh".into(),
+                script.display().to_string(),
+                dir.display().to_string(),
+            ])),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_completion_hook_success_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = recording_hook(dir.path());
+        let mut completion = Completion::new("send");
+        completion.succeeded(vec!["a.txt".into(), "b.txt".into()], 42);
+        completion.finish(&Ok(()), Duration::from_millis(1500));
+        hook.notify(&completion).await;
+
+        let env = std::fs::read_to_string(dir.path().join("env")).unwrap();
+        assert_eq!(env, "success|send|a.txt,b.txt|42|");
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("stdin.json")).unwrap()).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["files"], serde_json::json!(["a.txt", "b.txt"]));
+        assert_eq!(json["total_size"], 42);
+        assert_eq!(json["duration_ms"], 1500);
+        assert!(json["error"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_completion_hook_failure_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = recording_hook(dir.path());
+        let mut completion = Completion::new("receive");
+        completion.finish(
+            &Err(io::Error::other("peer went away")),
+            Duration::from_millis(10),
+        );
+        hook.notify(&completion).await;
+
+        let env = std::fs::read_to_string(dir.path().join("env")).unwrap();
+        assert_eq!(env, "failure|receive||0|peer went away");
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("stdin.json")).unwrap()).unwrap();
+        assert_eq!(json["status"], "failure");
+        assert_eq!(json["direction"], "receive");
+        assert_eq!(json["error"], "peer went away");
+    }
+
+    #[test]
+    fn test_completion_partial_success_status() {
+        let mut completion = Completion::new("send");
+        completion.succeeded(vec!["a.txt".to_string()], 3);
+        completion.finish(
+            &Err(crate::error::classified(
+                crate::error::TallowError::PartialSuccess {
+                    skipped: 1,
+                    total: 2,
+                },
+            )),
+            Duration::from_millis(10),
+        );
+        assert_eq!(completion.status, Some(CompletionStatus::Partial));
+        assert_eq!(completion.files, ["a.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_completion_hook_arguments_not_shell_interpreted() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().display();
+        // A shell would substitute "x" and stop the touch at the semicolon
+        let spec = format!("touch \"{base}/$(echo x)\" {base}/a;echo");
+        let hook = CompletionHook::resolve(Some(&spec), "", true).unwrap();
+        let mut completion = Completion::new("send");
+        completion.succeeded(Vec::new(), 0);
+        hook.notify(&completion).await;
+
+        assert!(dir.path().join("$(echo x)").exists());
+        assert!(dir.path().join("a;echo").exists());
+        assert!(!dir.path().join("x").exists());
+        assert!(!dir.path().join("a").exists());
+    }
 }
@@ -3,7 +3,11 @@
 //! SAND-04: Structured logging via tracing-subscriber.
 //! SAND-05: Sensitive data (keys, passphrases, file contents) never appears in logs.
 
+use std::fmt;
 use std::io;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::{FormatFields, Writer};
 use tracing_subscriber::EnvFilter;
 
 /// Patterns that indicate sensitive data in log messages
@@ -25,6 +29,18 @@ const SENSITIVE_FIELD_PATTERNS: &[&str] = &[
     "shared_secret",
 ];
 
+/// Field names whose values are shortened to a prefix instead of hidden
+///
+/// A fingerprint prefix is enough to tell peers apart in a log, but the
+/// full value is what a user compares out of band to verify a peer.
+const PREFIX_FIELD_PATTERNS: &[&str] = &["fingerprint"];
+
+/// Characters of a prefix-redacted value kept in logs
+const REDACTED_PREFIX_LEN: usize = 8;
+
+/// Replacement for a redacted value
+const REDACTED: &str = "[REDACTED]";
+
 /// Initialize logging based on verbosity level (SAND-04)
 ///
 /// Maps CLI flags to tracing levels:
@@ -36,8 +52,9 @@ const SENSITIVE_FIELD_PATTERNS: &[&str] = &[
 /// When quiet mode is enabled, only errors are shown.
 ///
 /// SAND-05: Even at trace level, sensitive fields (keys, passphrases)
-/// are never logged. Use `tracing::debug!(key = "[REDACTED]")` or the
-/// `redact()` helper instead of logging raw key material.
+/// are never logged: every span and event is formatted through
+/// [`RedactingFields`]. Still use the `redact()` helper rather than
+/// logging raw key material under an innocuous name.
 pub fn init_logging(verbosity: u8, quiet: bool) -> io::Result<()> {
     let level = if quiet {
         "error"
@@ -58,6 +75,7 @@ pub fn init_logging(verbosity: u8, quiet: bool) -> io::Result<()> {
     });
 
     tracing_subscriber::fmt()
+        .fmt_fields(RedactingFields)
         .with_env_filter(filter)
         .with_target(false)
         .with_thread_ids(false)
@@ -104,6 +122,100 @@ pub fn is_sensitive_field(name: &str) -> bool {
         .any(|pattern| lower.contains(pattern))
 }
 
+/// How a field's value is shown in logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Redaction {
+    /// As logged
+    None,
+    /// Only the first few characters
+    Prefix,
+    /// Replaced entirely
+    Hide,
+}
+
+fn redaction(name: &str) -> Redaction {
+    let lower = name.to_lowercase();
+    if PREFIX_FIELD_PATTERNS.iter().any(|p| lower.contains(p)) {
+        Redaction::Prefix
+    } else if is_sensitive_field(name) {
+        Redaction::Hide
+    } else {
+        Redaction::None
+    }
+}
+
+fn redact_prefix(value: &str) -> String {
+    let value = value.trim_matches('"');
+    if value.chars().count() <= REDACTED_PREFIX_LEN {
+        return value.to_string();
+    }
+    let prefix: String = value.chars().take(REDACTED_PREFIX_LEN).collect();
+    format!("{}…", prefix)
+}
+
+/// Field formatter that redacts sensitive values (SAND-05)
+///
+/// Fields named like a secret ([`is_sensitive_field`]) are replaced with
+/// `[REDACTED]` and fingerprints are cut to a short prefix, at every level
+/// and for span fields as well as event fields. Other fields and the
+/// message are written as the default formatter would.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedactingFields;
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = RedactingVisitor {
+            writer,
+            first: true,
+            result: Ok(()),
+        };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct RedactingVisitor<'writer> {
+    writer: Writer<'writer>,
+    first: bool,
+    result: fmt::Result,
+}
+
+impl RedactingVisitor<'_> {
+    fn write(&mut self, field: &Field, value: fmt::Arguments<'_>) {
+        if self.result.is_err() {
+            return;
+        }
+        let sep = if self.first { "" } else { " " };
+        self.first = false;
+        self.result = if field.name() == "message" {
+            write!(self.writer, "{}{}", sep, value)
+        } else {
+            write!(self.writer, "{}{}={}", sep, field.name(), value)
+        };
+    }
+}
+
+impl Visit for RedactingVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match redaction(field.name()) {
+            Redaction::None => self.write(field, format_args!("{:?}", value)),
+            Redaction::Prefix => self.write(field, format_args!("{}", redact_prefix(value))),
+            Redaction::Hide => self.write(field, format_args!("{}", REDACTED)),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match redaction(field.name()) {
+            Redaction::None => self.write(field, format_args!("{:?}", value)),
+            Redaction::Prefix => {
+                let full = format!("{:?}", value);
+                self.write(field, format_args!("{}", redact_prefix(&full)))
+            }
+            Redaction::Hide => self.write(field, format_args!("{}", REDACTED)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +243,78 @@ mod tests {
         let data = vec![0u8; 1024];
         assert_eq!(redact_bytes(&data), "[1024 bytes]");
     }
+
+    /// Log through [`RedactingFields`] into a string
+    fn capture(log: impl FnOnce()) -> String {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(RedactingFields)
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .compact()
+            .finish();
+        tracing::subscriber::with_default(subscriber, log);
+        let bytes = buffer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_event_fields_redacted() {
+        let out = capture(|| {
+            tracing::trace!(
+                password = "hunter2",
+                session_key = ?[7u8; 32],
+                filename = "report.pdf",
+                chunk_index = 3,
+                "Sending chunk"
+            );
+        });
+        assert!(out.contains("Sending chunk"), "{}", out);
+        assert!(out.contains("password=[REDACTED]"), "{}", out);
+        assert!(out.contains("session_key=[REDACTED]"), "{}", out);
+        assert!(!out.contains("hunter2"), "{}", out);
+        assert!(!out.contains("7, 7"), "{}", out);
+        assert!(out.contains("filename=\"report.pdf\""), "{}", out);
+        assert!(out.contains("chunk_index=3"), "{}", out);
+    }
+
+    #[test]
+    fn test_span_fields_redacted() {
+        let out = capture(|| {
+            let span = tracing::info_span!("handshake", secret = "s3cr3t", peer = "alice");
+            let _guard = span.enter();
+            tracing::info!("Handshake complete");
+        });
+        assert!(out.contains("secret=[REDACTED]"), "{}", out);
+        assert!(!out.contains("s3cr3t"), "{}", out);
+        assert!(out.contains("peer=\"alice\""), "{}", out);
+    }
+
+    #[test]
+    fn test_fingerprint_shortened() {
+        let fingerprint = "a1b2c3d4e5f60718293a4b5c6d7e8f90";
+        let out = capture(|| {
+            tracing::warn!(fingerprint, "Unknown peer");
+            tracing::warn!(peer_fingerprint = ?fingerprint, "Unknown peer");
+        });
+        assert!(!out.contains(fingerprint), "{}", out);
+        assert!(out.contains("fingerprint=a1b2c3d4…"), "{}", out);
+        assert!(out.contains("peer_fingerprint=a1b2c3d4…"), "{}", out);
+    }
 }
@@ -7,6 +7,7 @@
 
 mod cli;
 mod commands;
+mod error;
 #[allow(dead_code)]
 mod exit_codes;
 pub mod hooks;
@@ -39,6 +40,32 @@ async fn main() {
     // Disable core dumps to prevent key material from being written to disk
     sandbox::disable_core_dumps();
 
+    if let Some(ref mode) = cli.sandbox {
+        match mode.parse::<sandbox::SandboxMode>() {
+            Ok(mode) => {
+                if mode == sandbox::SandboxMode::Strict && !sandbox::is_sandbox_supported() {
+                    output::color::warning(&format!(
+                        "--sandbox strict is not supported on {}; running unsandboxed",
+                        std::env::consts::OS
+                    ));
+                }
+                sandbox::set_mode(mode);
+            }
+            Err(e) => {
+                eprintln!("Invalid --sandbox: {}", e);
+                std::process::exit(exit_codes::ERROR);
+            }
+        }
+    }
+
+    // Select the profile before anything touches the store
+    if let Some(ref name) = cli.profile {
+        if let Err(e) = tallow_store::persistence::set_profile(name) {
+            eprintln!("Invalid --profile: {}", e);
+            std::process::exit(exit_codes::ERROR);
+        }
+    }
+
     // Ensure storage directories exist
     if let Err(e) = tallow_store::persistence::ensure_dirs() {
         tracing::warn!("Failed to create storage directories: {}", e);
@@ -53,7 +80,7 @@ async fn main() {
         cli::Commands::Chat(args) => commands::chat::execute(args, json_output).await,
         cli::Commands::Sync(args) => commands::sync::execute(args, json_output).await,
         cli::Commands::Watch(args) => commands::watch::execute(args, json_output).await,
-        cli::Commands::Stream(args) => commands::send::execute(args, json_output).await,
+        cli::Commands::Stream(args) => commands::send::execute_stream(args, json_output).await,
         cli::Commands::Tui(args) => commands::tui_cmd::execute(args).await,
         cli::Commands::Relay(args) => commands::send::execute(args, json_output).await,
         cli::Commands::Relays => {
@@ -73,7 +100,7 @@ async fn main() {
             commands::identity::execute_identity(args, json_output).await
         }
         cli::Commands::Config(args) => commands::config_cmd::execute(args, json_output).await,
-        cli::Commands::Doctor => commands::doctor::execute(json_output).await,
+        cli::Commands::Doctor(args) => commands::doctor::execute(args, json_output).await,
         cli::Commands::Benchmark(args) => commands::benchmark::execute(args, json_output).await,
         cli::Commands::Completions(args) => {
             commands::completions::execute(args);
@@ -86,7 +113,15 @@ async fn main() {
         cli::Commands::SpeedTest(args) => commands::speed_test::execute(args, json_output).await,
         cli::Commands::SshSetup(args) => commands::ssh_setup::execute(args, json_output).await,
         cli::Commands::DropBox(args) => commands::drop_box::execute(args, json_output).await,
+        cli::Commands::Encrypt(args) => commands::pipe::execute_encrypt(args).await,
+        cli::Commands::Decrypt(args) => commands::pipe::execute_decrypt(args).await,
         cli::Commands::History(args) => commands::history::execute(args, json_output).await,
+        cli::Commands::Sign(args) => commands::signature::execute_sign(args, json_output),
+        cli::Commands::Verify(args) => commands::signature::execute_verify(args, json_output),
+        cli::Commands::VerifyPeer(args) => {
+            commands::identity::execute_verify_peer(args, json_output)
+        }
+        cli::Commands::Join(args) => commands::join::execute(args, json_output),
         cli::Commands::Update(args) => {
             #[cfg(feature = "self-update")]
             {
@@ -132,9 +167,13 @@ fn handle_result(result: Result<(), io::Error>, json_output: bool) -> ! {
     match result {
         Ok(()) => std::process::exit(exit_codes::SUCCESS),
         Err(e) => {
+            let code = exit_codes::for_error(&e);
             if json_output {
                 let err_json = serde_json::json!({
                     "error": format!("{}", e),
+                    "classification": exit_codes::classification(code),
+                    "attempts": exit_codes::attempts(&e).unwrap_or(1),
+                    "exit_code": code,
                 });
                 eprintln!("{}", err_json);
             } else {
@@ -146,28 +185,6 @@ fn handle_result(result: Result<(), io::Error>, json_output: bool) -> ! {
                     eprintln!("{}", hint);
                 }
             }
-
-            // Map error kinds to specific exit codes
-            let code = match e.kind() {
-                io::ErrorKind::NotFound => exit_codes::FILE_NOT_FOUND,
-                io::ErrorKind::PermissionDenied => exit_codes::PERMISSION_DENIED,
-                io::ErrorKind::ConnectionRefused
-                | io::ErrorKind::ConnectionReset
-                | io::ErrorKind::ConnectionAborted
-                | io::ErrorKind::TimedOut => exit_codes::NETWORK_ERROR,
-                io::ErrorKind::Interrupted => exit_codes::CANCELLED,
-                _ => {
-                    // Check error message for further classification
-                    let msg = format!("{}", e);
-                    if msg.contains("auth") || msg.contains("password") || msg.contains("denied") {
-                        exit_codes::AUTH_FAILURE
-                    } else if msg.contains("config") {
-                        exit_codes::CONFIG_ERROR
-                    } else {
-                        exit_codes::ERROR
-                    }
-                }
-            };
             std::process::exit(code);
         }
     }
@@ -44,6 +44,37 @@ pub fn error(text: &str) {
     }
 }
 
+/// Print a password/code strength meter with its warning and suggestions to stderr
+pub fn password_strength(report: &tallow_crypto::kdf::StrengthReport) {
+    let label = match report.score {
+        0 => "very weak",
+        1 => "weak",
+        2 => "fair",
+        3 => "strong",
+        _ => "very strong",
+    };
+    let filled = report.score.min(4) as usize;
+    let meter = format!("[{}{}]", "#".repeat(filled), "-".repeat(4 - filled));
+    let style = match report.score {
+        0 | 1 => "red",
+        2 => "yellow",
+        _ => "green",
+    };
+
+    eprintln!(
+        "Strength: {} {} ({}/4)",
+        styled(&meter, style),
+        label,
+        report.score
+    );
+    if let Some(ref warning) = report.warning {
+        self::warning(warning);
+    }
+    for suggestion in &report.suggestions {
+        eprintln!("  {}", styled(&format!("- {}", suggestion), "dim"));
+    }
+}
+
 /// Print success message to stdout
 pub fn success(text: &str) {
     if color_enabled() {
@@ -87,6 +118,21 @@ pub fn file_entry(name: &str, size: u64) {
     }
 }
 
+/// Print one file's line in an end-of-transfer outcome list
+pub fn file_outcome(name: &str, skipped_reason: Option<&str>) {
+    match (skipped_reason, color_enabled()) {
+        (None, true) => println!("   {} {}", "sent".green(), name),
+        (None, false) => println!("   sent {}", name),
+        (Some(reason), true) => println!(
+            "   {} {} {}",
+            "skipped".red(),
+            name,
+            format!("({})", reason).dimmed()
+        ),
+        (Some(reason), false) => println!("   skipped {} ({})", name, reason),
+    }
+}
+
 /// Print transfer completion with speed summary
 pub fn transfer_complete(total_bytes: u64, duration: std::time::Duration) {
     let speed = super::format_speed(total_bytes, duration);
@@ -103,6 +149,16 @@ pub fn transfer_complete(total_bytes: u64, duration: std::time::Duration) {
     }
 }
 
+/// Print how much compression saved on a completed transfer
+pub fn compression_summary(stats: Option<&tallow_protocol::transfer::CompressionStats>) {
+    let text = super::format_compression(stats);
+    if color_enabled() {
+        println!("{} Compression: {}", ">>".cyan().bold(), text);
+    } else {
+        println!(">> Compression: {}", text);
+    }
+}
+
 /// Print a section separator (dimmed)
 pub fn section(text: &str) {
     if color_enabled() {
@@ -47,8 +47,9 @@ pub fn diagnose(err: &dyn std::fmt::Display) -> Option<String> {
     }
     if lower.contains("no space left on device") || lower.contains("disk full") {
         return Some(
-            "Disk full. Free up space or specify a different output directory: \
-             tallow receive -o /path/with/space <code>"
+            "Disk full. Received chunks were kept; free up space and retry with\n  \
+             tallow receive --resume-id <transfer-id> <code>\n  \
+             or specify a different output directory: tallow receive -o /path/with/space <code>"
                 .to_string(),
         );
     }
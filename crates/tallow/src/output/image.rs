@@ -226,7 +226,7 @@ pub fn write_clipboard_image(png_data: &[u8]) -> bool {
 /// This is sufficient for round-tripping images produced by [`encode_rgba_as_png`],
 /// but will return `None` for PNGs with other filter types (sub, up, average, paeth).
 /// For externally-produced PNGs, consider saving directly to disk instead.
-fn decode_png_rgba(data: &[u8]) -> Option<(usize, usize, Vec<u8>)> {
+pub(crate) fn decode_png_rgba(data: &[u8]) -> Option<(usize, usize, Vec<u8>)> {
     // Verify PNG signature
     if data.len() < 33 || !data.starts_with(&[137, 80, 78, 71, 13, 10, 26, 10]) {
         return None;
@@ -47,6 +47,19 @@ pub fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
+/// Format a transfer time estimate, or "unknown" when the probe measured
+/// no usable throughput.
+pub fn format_estimate(estimate: &tallow_protocol::transfer::TransferEstimate) -> String {
+    match (estimate.duration, estimate.bytes_per_sec) {
+        (Some(duration), Some(rate)) => format!(
+            "~{} at {}/s",
+            format_duration(duration),
+            format_size(rate as u64)
+        ),
+        _ => "unknown".to_string(),
+    }
+}
+
 /// Format transfer speed as human-readable bytes/second.
 pub fn format_speed(bytes: u64, duration: std::time::Duration) -> String {
     let secs = duration.as_secs_f64();
@@ -57,6 +70,24 @@ pub fn format_speed(bytes: u64, duration: std::time::Duration) -> String {
     format!("{}/s", format_size(bps as u64))
 }
 
+/// Format compression savings for the completion summary.
+///
+/// `None` means compression was disabled for the transfer.
+pub fn format_compression(stats: Option<&tallow_protocol::transfer::CompressionStats>) -> String {
+    match stats {
+        None => "none".to_string(),
+        Some(stats) if stats.bytes_saved() == 0 => {
+            format!("{:.2}x (no savings)", stats.ratio())
+        }
+        Some(stats) => format!(
+            "{:.2}x, saved {} ({:.1}%)",
+            stats.ratio(),
+            format_size(stats.bytes_saved()),
+            stats.percent_saved()
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +173,29 @@ mod tests {
         let speed = format_speed(1_073_741_824, std::time::Duration::from_secs(1));
         assert_eq!(speed, "1.00 GiB/s");
     }
+
+    #[test]
+    fn test_format_compression() {
+        use tallow_protocol::transfer::CompressionStats;
+
+        let stats = CompressionStats {
+            original_bytes: 4 * 1_048_576,
+            compressed_bytes: 1_048_576,
+        };
+        assert_eq!(
+            format_compression(Some(&stats)),
+            "4.00x, saved 3.00 MiB (75.0%)"
+        );
+
+        let incompressible = CompressionStats {
+            original_bytes: 1_000_000,
+            compressed_bytes: 1_000_040,
+        };
+        assert_eq!(
+            format_compression(Some(&incompressible)),
+            "1.00x (no savings)"
+        );
+
+        assert_eq!(format_compression(None), "none");
+    }
 }
@@ -1,7 +1,11 @@
-//! User prompts and input using dialoguer
+//! User prompts and input using dialoguer, plus a raw-mode password reader
 
-use std::io;
-use zeroize::Zeroizing;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io::{self, IsTerminal, Write};
+use tallow_crypto::mem::wipe::unlock_memory;
+use tallow_crypto::mem::{lock_memory, SecureBuf};
+use zeroize::Zeroize;
 
 /// Prompt for yes/no confirmation (default: no)
 pub fn confirm(message: &str) -> io::Result<bool> {
@@ -23,15 +27,195 @@ pub fn confirm_with_default(message: &str, default: bool) -> io::Result<bool> {
 
 /// Prompt for password input (hidden)
 ///
-/// Returns a `Zeroizing<String>` that automatically zeroizes
-/// the password memory when dropped, preventing it from lingering
-/// in freed heap allocations.
-pub fn password_prompt(message: &str) -> io::Result<Zeroizing<String>> {
-    let password = dialoguer::Password::new()
-        .with_prompt(message)
-        .interact()
-        .map_err(|e| io::Error::other(format!("Password prompt failed: {}", e)))?;
-    Ok(Zeroizing::new(password))
+/// Keys are read in raw mode, so nothing is echoed, and go straight into
+/// a locked [`SecretPassword`] buffer; no `String` holding the password
+/// is ever built. Backspace and Ctrl+U edit in place. Esc or Ctrl+C
+/// cancels with [`io::ErrorKind::Interrupted`].
+pub fn password_prompt(message: &str) -> io::Result<SecretPassword> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::other(
+            "Password prompt needs an interactive terminal",
+        ));
+    }
+
+    let mut stderr = io::stderr();
+    write!(stderr, "{}: ", message)?;
+    stderr.flush()?;
+
+    let mut password = SecretPassword::new();
+    let outcome = {
+        let _raw = RawModeGuard::enable()?;
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match password.apply_key(key) {
+                KeyOutcome::Continue => {}
+                done => break done,
+            }
+        }
+    };
+    writeln!(stderr)?;
+
+    match outcome {
+        KeyOutcome::Submit => Ok(password),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "Password entry cancelled",
+        )),
+    }
+}
+
+/// BLAKE3 hash of the relay password, if one was configured
+///
+/// `--relay-pass` given without a value prompts for the password, which
+/// keeps it out of both the process list and the environment. A value on
+/// the command line still works but is warned about.
+pub fn relay_password_hash(relay_pass: Option<&str>) -> io::Result<Option<[u8; 32]>> {
+    match relay_pass {
+        None => Ok(None),
+        Some("") => {
+            let password = password_prompt("Relay password")?;
+            Ok(Some(blake3::hash(password.as_bytes()).into()))
+        }
+        Some(pass) => {
+            if std::env::var("TALLOW_RELAY_PASS").is_err() {
+                tracing::warn!(
+                    "Relay password passed via CLI argument -- visible in process list. \
+                     Use TALLOW_RELAY_PASS env var or --relay-pass with no value to be prompted."
+                );
+            }
+            Ok(Some(blake3::hash(pass.as_bytes()).into()))
+        }
+    }
+}
+
+/// Longest password the prompt accepts, in bytes
+///
+/// The buffer is allocated once at this size, so editing never
+/// reallocates and leaves an old copy behind in freed heap memory.
+pub const MAX_PASSWORD_LEN: usize = 1024;
+
+/// A password read by [`password_prompt`]
+///
+/// Held in a fixed-size [`SecureBuf`] that stays `mlock`ed for its whole
+/// lifetime. Bytes removed while editing are zeroed immediately, and the
+/// whole buffer is wiped on drop before its pages are unlocked.
+pub struct SecretPassword {
+    buf: SecureBuf<Vec<u8>>,
+    len: usize,
+}
+
+/// What the prompt does after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyOutcome {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+impl SecretPassword {
+    fn new() -> Self {
+        let buf = SecureBuf::new(vec![0u8; MAX_PASSWORD_LEN]);
+        if let Err(e) = lock_memory(buf.expose_secret().as_ptr(), MAX_PASSWORD_LEN) {
+            tracing::debug!("Password buffer left swappable: {}", e);
+        }
+        Self { buf, len: 0 }
+    }
+
+    /// The password as UTF-8 bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf.expose_secret()[..self.len]
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing was typed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append `c`, returning false when the buffer is full
+    fn push(&mut self, c: char) -> bool {
+        let end = self.len + c.len_utf8();
+        if end > MAX_PASSWORD_LEN {
+            return false;
+        }
+        c.encode_utf8(&mut self.buf.expose_secret_mut()[self.len..end]);
+        self.len = end;
+        true
+    }
+
+    /// Remove the last character, zeroing its bytes
+    fn pop(&mut self) {
+        let bytes = self.buf.expose_secret_mut();
+        let mut start = self.len.saturating_sub(1);
+        while start > 0 && bytes[start] & 0xC0 == 0x80 {
+            start -= 1;
+        }
+        bytes[start..self.len].zeroize();
+        self.len = start;
+    }
+
+    /// Zero the whole buffer, keeping its allocation
+    fn wipe(&mut self) {
+        self.buf.expose_secret_mut().as_mut_slice().zeroize();
+        self.len = 0;
+    }
+
+    fn apply_key(&mut self, key: KeyEvent) -> KeyOutcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return KeyOutcome::Submit,
+            KeyCode::Esc => return KeyOutcome::Cancel,
+            KeyCode::Char('c' | 'd') if ctrl => return KeyOutcome::Cancel,
+            KeyCode::Char('u') if ctrl => self.wipe(),
+            KeyCode::Char('h') if ctrl => self.pop(),
+            KeyCode::Backspace => self.pop(),
+            KeyCode::Char(c) if !ctrl => {
+                self.push(c);
+            }
+            _ => {}
+        }
+        KeyOutcome::Continue
+    }
+}
+
+impl Drop for SecretPassword {
+    fn drop(&mut self) {
+        self.wipe();
+        let _ = unlock_memory(self.buf.expose_secret().as_ptr(), MAX_PASSWORD_LEN);
+    }
+}
+
+impl std::fmt::Debug for SecretPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretPassword")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Keeps the terminal in raw mode (no echo, no line buffering) until dropped
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
 }
 
 /// Select from a list of options
@@ -45,3 +229,89 @@ pub fn select<T: ToString>(message: &str, options: &[T]) -> io::Result<usize> {
         .interact()
         .map_err(|e| io::Error::other(format!("Select failed: {}", e)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(password: &mut SecretPassword, code: KeyCode) -> KeyOutcome {
+        password.apply_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn ctrl(password: &mut SecretPassword, c: char) -> KeyOutcome {
+        password.apply_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    fn type_str(password: &mut SecretPassword, s: &str) {
+        for c in s.chars() {
+            press(password, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_backspace_edits_in_place_without_copies() {
+        let mut password = SecretPassword::new();
+        let ptr = password.buf.expose_secret().as_ptr();
+
+        type_str(&mut password, "hunter2xyz");
+        for _ in 0..3 {
+            press(&mut password, KeyCode::Backspace);
+        }
+        type_str(&mut password, "!é");
+        assert_eq!(press(&mut password, KeyCode::Enter), KeyOutcome::Submit);
+        assert_eq!(password.as_bytes(), "hunter2!é".as_bytes());
+
+        // Multi-byte characters are removed whole
+        press(&mut password, KeyCode::Backspace);
+        assert_eq!(password.as_bytes(), b"hunter2!");
+
+        // Same allocation throughout, and nothing left past the live bytes
+        let bytes = password.buf.expose_secret();
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes.len(), MAX_PASSWORD_LEN);
+        assert!(bytes[password.len()..].iter().all(|&b| b == 0));
+
+        ctrl(&mut password, 'u');
+        assert!(password.is_empty());
+        assert!(password.buf.expose_secret().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_wipe_on_drop_path_zeroes_buffer() {
+        let mut password = SecretPassword::new();
+        type_str(&mut password, "correct horse battery staple");
+        assert!(!password.is_empty());
+
+        // Drop runs exactly this before unlocking the pages
+        password.wipe();
+        assert!(password.as_bytes().is_empty());
+        assert!(password.buf.expose_secret().iter().all(|&b| b == 0));
+        assert_eq!(password.buf.expose_secret().len(), MAX_PASSWORD_LEN);
+    }
+
+    #[test]
+    fn test_cancel_keys_and_overflow() {
+        let mut password = SecretPassword::new();
+        assert_eq!(ctrl(&mut password, 'c'), KeyOutcome::Cancel);
+        assert_eq!(press(&mut password, KeyCode::Esc), KeyOutcome::Cancel);
+
+        // Backspace on an empty buffer is harmless
+        assert_eq!(
+            press(&mut password, KeyCode::Backspace),
+            KeyOutcome::Continue
+        );
+
+        for _ in 0..MAX_PASSWORD_LEN + 10 {
+            press(&mut password, KeyCode::Char('a'));
+        }
+        assert_eq!(password.len(), MAX_PASSWORD_LEN);
+        assert!(!password.push('b'));
+    }
+
+    #[test]
+    fn test_relay_password_hash_from_value() {
+        assert_eq!(relay_password_hash(None).unwrap(), None);
+        let expected: [u8; 32] = blake3::hash(b"secret").into();
+        assert_eq!(relay_password_hash(Some("secret")).unwrap(), Some(expected));
+    }
+}
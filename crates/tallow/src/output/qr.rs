@@ -1,4 +1,9 @@
 //! QR code terminal display
+//!
+//! Codes can also be written to PNG or SVG files with [`render_to_file`],
+//! for sharing somewhere a terminal can't be scanned.
+
+use std::path::Path;
 
 /// Display a QR code in the terminal containing the receive command.
 ///
@@ -18,3 +23,516 @@ pub fn display_receive_qr(code_phrase: &str) -> std::io::Result<()> {
     qr2term::print_qr(&receive_cmd)
         .map_err(|e| std::io::Error::other(format!("QR generation failed: {e}")))
 }
+
+/// Image formats a QR code can be written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrFormat {
+    /// Raster image; each module is `module_size` pixels square
+    Png,
+    /// Vector image, crisp at any scale
+    Svg,
+}
+
+impl QrFormat {
+    /// Format implied by a file's extension (`.png` or `.svg`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("png") {
+            Some(Self::Png)
+        } else if ext.eq_ignore_ascii_case("svg") {
+            Some(Self::Svg)
+        } else {
+            None
+        }
+    }
+}
+
+/// Size of a rendered QR image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrImageOptions {
+    /// Pixels per module (the PNG's resolution, the SVG's nominal size)
+    pub module_size: u32,
+    /// Blank modules around the code; scanners want at least 4
+    pub quiet_zone: u32,
+}
+
+impl Default for QrImageOptions {
+    fn default() -> Self {
+        Self {
+            module_size: 8,
+            quiet_zone: 4,
+        }
+    }
+}
+
+/// QR modules for `data` as rows of dark (`true`) and light cells
+fn module_rows(data: &str) -> std::io::Result<Vec<Vec<bool>>> {
+    let code = qrcode::QrCode::new(data.as_bytes())
+        .map_err(|e| std::io::Error::other(format!("QR generation failed: {e}")))?;
+    let width = code.width();
+    let colors = code.to_colors();
+    Ok(colors
+        .chunks(width)
+        .map(|row| row.iter().map(|c| *c == qrcode::Color::Dark).collect())
+        .collect())
+}
+
+/// Render `data` as a QR code in PNG format
+pub fn render_png(data: &str, options: QrImageOptions) -> std::io::Result<Vec<u8>> {
+    let rows = module_rows(data)?;
+    let module = options.module_size.max(1) as usize;
+    let quiet = options.quiet_zone as usize;
+    let side = (rows.len() + 2 * quiet) * module;
+
+    let mut rgba = vec![0xFF; side * side * 4];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, dark)| **dark) {
+            for py in 0..module {
+                let start = (((y + quiet) * module + py) * side + (x + quiet) * module) * 4;
+                for pixel in rgba[start..start + module * 4].chunks_mut(4) {
+                    pixel.copy_from_slice(&[0, 0, 0, 0xFF]);
+                }
+            }
+        }
+    }
+
+    super::image::encode_rgba_as_png(side, side, &rgba).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("QR image would be {side}x{side} pixels; use a smaller module size"),
+        )
+    })
+}
+
+/// Render `data` as a QR code in SVG format
+///
+/// Drawn in module units with a `viewBox`, so it stays sharp however far
+/// it is scaled; `module_size` only sets the default display size.
+pub fn render_svg(data: &str, options: QrImageOptions) -> std::io::Result<String> {
+    let rows = module_rows(data)?;
+    let quiet = options.quiet_zone as usize;
+    let modules = rows.len() + 2 * quiet;
+    let size = modules * options.module_size.max(1) as usize;
+
+    let mut path = String::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, dark)| **dark) {
+            path.push_str(&format!("M{},{}h1v1h-1z", x + quiet, y + quiet));
+        }
+    }
+
+    Ok(format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" ",
+            "viewBox=\"0 0 {modules} {modules}\" shape-rendering=\"crispEdges\">",
+            "<rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>",
+            "<path d=\"{path}\" fill=\"#000\"/></svg>\n"
+        ),
+        size = size,
+        modules = modules,
+        path = path
+    ))
+}
+
+/// Write `data` as a QR code image to `path`
+pub fn render_to_file(
+    data: &str,
+    path: &Path,
+    format: QrFormat,
+    options: QrImageOptions,
+) -> std::io::Result<()> {
+    let bytes = match format {
+        QrFormat::Png => render_png(data, options)?,
+        QrFormat::Svg => render_svg(data, options)?.into_bytes(),
+    };
+    std::fs::write(path, bytes).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("Cannot write QR code to {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Prefix marking a Tallow bundle frame
+const FRAME_PREFIX: &str = "TQR1";
+
+/// Payload bytes carried by one frame
+///
+/// 256 bytes is 512 hex digits, which fits a version 15 QR code at
+/// medium error correction: still small enough to scan off a terminal.
+pub const FRAME_PAYLOAD_BYTES: usize = 256;
+
+/// Most frames accepted in one sequence (well above any real bundle)
+const MAX_FRAMES: usize = 1024;
+
+/// Columns needed for a version 15 code plus its quiet zone
+const FRAME_MIN_COLUMNS: u16 = 85;
+
+/// One frame of a payload split across several QR codes
+///
+/// Encoded as `TQR1:<id>:<index>/<total>:<hex>`, using only characters in
+/// the QR alphanumeric set. The id is the start of the payload's BLAKE3
+/// hash, so frames of two different bundles are never mixed and the
+/// reassembled payload can be checked against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// First four bytes of the BLAKE3 hash of the whole payload
+    pub id: [u8; 4],
+    /// Zero-based position of this frame
+    pub index: usize,
+    /// Number of frames in the sequence
+    pub total: usize,
+    /// This frame's slice of the payload
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    /// Text placed in the QR code
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}/{}:{}",
+            FRAME_PREFIX,
+            hex::encode_upper(self.id),
+            self.index + 1,
+            self.total,
+            hex::encode_upper(&self.data)
+        )
+    }
+
+    /// Parse scanned text back into a frame
+    pub fn decode(text: &str) -> std::io::Result<Self> {
+        let invalid = |msg: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Not a Tallow QR frame: {}", msg),
+            )
+        };
+
+        let mut parts = text.trim().splitn(4, ':');
+        if parts.next() != Some(FRAME_PREFIX) {
+            return Err(invalid("missing TQR1 prefix"));
+        }
+        let id = parts
+            .next()
+            .and_then(|id| hex::decode(id).ok())
+            .and_then(|id| <[u8; 4]>::try_from(id).ok())
+            .ok_or_else(|| invalid("bad id"))?;
+        let (number, total) = parts
+            .next()
+            .and_then(|pos| pos.split_once('/'))
+            .and_then(|(n, t)| Some((n.parse::<usize>().ok()?, t.parse::<usize>().ok()?)))
+            .ok_or_else(|| invalid("bad frame position"))?;
+        if number == 0 || number > total || total > MAX_FRAMES {
+            return Err(invalid("frame position out of range"));
+        }
+        let data = parts
+            .next()
+            .and_then(|data| hex::decode(data).ok())
+            .ok_or_else(|| invalid("bad payload"))?;
+
+        Ok(Self {
+            id,
+            index: number - 1,
+            total,
+            data,
+        })
+    }
+}
+
+/// Split `payload` into frames of at most `max_bytes` each, in order
+pub fn split_frames(payload: &[u8], max_bytes: usize) -> Vec<Frame> {
+    let id = frame_id(payload);
+    let chunks: Vec<&[u8]> = if payload.is_empty() {
+        vec![&[]]
+    } else {
+        payload.chunks(max_bytes.max(1)).collect()
+    };
+    let total = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| Frame {
+            id,
+            index,
+            total,
+            data: data.to_vec(),
+        })
+        .collect()
+}
+
+fn frame_id(payload: &[u8]) -> [u8; 4] {
+    let hash = blake3::hash(payload);
+    let mut id = [0u8; 4];
+    id.copy_from_slice(&hash.as_bytes()[..4]);
+    id
+}
+
+/// Collects scanned frames, in any order, until the payload is complete
+#[derive(Debug, Default)]
+pub struct FrameAssembler {
+    id: Option<[u8; 4]>,
+    frames: Vec<Option<Vec<u8>>>,
+}
+
+impl FrameAssembler {
+    /// Start with no frames
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a frame; scanning the same frame twice is harmless
+    ///
+    /// Fails if the frame belongs to a different sequence than earlier ones.
+    pub fn push(&mut self, frame: Frame) -> std::io::Result<()> {
+        match self.id {
+            None => {
+                self.id = Some(frame.id);
+                self.frames = vec![None; frame.total];
+            }
+            Some(id) if id != frame.id || self.frames.len() != frame.total => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Frame belongs to a different bundle",
+                ));
+            }
+            Some(_) => {}
+        }
+        self.frames[frame.index] = Some(frame.data);
+        Ok(())
+    }
+
+    /// One-based numbers of the frames not yet scanned
+    pub fn missing(&self) -> Vec<usize> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.is_none())
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    /// Whether every frame has been scanned
+    pub fn is_complete(&self) -> bool {
+        self.id.is_some() && self.missing().is_empty()
+    }
+
+    /// Join the frames and check the result against the sequence id
+    pub fn finish(self) -> std::io::Result<Vec<u8>> {
+        let Some(id) = self.id else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "No frames scanned",
+            ));
+        };
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Missing frame(s) {} of {}",
+                    missing
+                        .iter()
+                        .map(usize::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    self.frames.len()
+                ),
+            ));
+        }
+
+        let payload: Vec<u8> = self.frames.into_iter().flatten().flatten().collect();
+        if frame_id(&payload) != id {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Reassembled bundle is corrupt",
+            ));
+        }
+        Ok(payload)
+    }
+}
+
+/// Show each frame as a QR code, waiting for Enter between frames
+///
+/// Unlike [`display_receive_qr`] this fails on narrow terminals: skipping
+/// the codes would leave nothing to scan.
+pub fn display_frames(frames: &[Frame]) -> std::io::Result<()> {
+    let (width, _) = crossterm::terminal::size().unwrap_or((80, 24));
+    if width < FRAME_MIN_COLUMNS {
+        return Err(std::io::Error::other(format!(
+            "Terminal too narrow ({} cols) to show bundle QR codes; need at least {}",
+            width, FRAME_MIN_COLUMNS
+        )));
+    }
+
+    let stdin = std::io::stdin();
+    for frame in frames {
+        println!("Frame {} of {}", frame.index + 1, frame.total);
+        qr2term::print_qr(&frame.encode())
+            .map_err(|e| std::io::Error::other(format!("QR generation failed: {e}")))?;
+        if frame.index + 1 < frame.total {
+            println!("Press Enter once scanned...");
+            stdin.read_line(&mut String::new())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tallow_crypto::keys::{IdentityKeyPair, PreKeyBundle};
+    use tallow_store::identity::OfflineBundle;
+
+    fn sealed_bundle() -> Vec<u8> {
+        let identity = IdentityKeyPair::generate().unwrap();
+        let prekeys = PreKeyBundle::generate(identity.signer(), 1, 1).unwrap();
+        OfflineBundle::new(&identity, prekeys)
+            .unwrap()
+            .seal("pw")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_png_decodes_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code.png");
+        let data = "tallow receive 7-amber-falcon-orbit";
+        let options = QrImageOptions {
+            module_size: 6,
+            quiet_zone: 2,
+        };
+        render_to_file(data, &path, QrFormat::Png, options).unwrap();
+
+        let png = std::fs::read(&path).unwrap();
+        let (width, height, rgba) = crate::output::image::decode_png_rgba(&png).unwrap();
+        let modules = module_rows(data).unwrap().len() as u32;
+        assert_eq!(width, ((modules + 4) * 6) as usize);
+        assert_eq!(width, height);
+
+        // Every pixel matches its module (quiet zone light), so any reader
+        // sees exactly the matrix the encoder produced
+        let rows = module_rows(data).unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                let (mx, my) = ((x / 6) as isize - 2, (y / 6) as isize - 2);
+                let dark = usize::try_from(my)
+                    .ok()
+                    .zip(usize::try_from(mx).ok())
+                    .and_then(|(my, mx)| rows.get(my).and_then(|row| row.get(mx)))
+                    .copied()
+                    .unwrap_or(false);
+                let pixel = &rgba[(y * width + x) * 4..][..4];
+                let expected: &[u8] = if dark { &[0, 0, 0, 0xFF] } else { &[0xFF; 4] };
+                assert_eq!(pixel, expected, "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_is_scalable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code.svg");
+        render_to_file(
+            "tallow receive abc",
+            &path,
+            QrFormat::Svg,
+            QrImageOptions::default(),
+        )
+        .unwrap();
+
+        let svg = std::fs::read_to_string(&path).unwrap();
+        let modules = module_rows("tallow receive abc").unwrap().len() + 8;
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(&format!("viewBox=\"0 0 {modules} {modules}\"")));
+        assert!(svg.contains(&format!("width=\"{}\"", modules * 8)));
+        assert!(svg.contains("shape-rendering=\"crispEdges\""));
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(QrFormat::from_path(Path::new("a.PNG")), Some(QrFormat::Png));
+        assert_eq!(QrFormat::from_path(Path::new("a.svg")), Some(QrFormat::Svg));
+        assert_eq!(QrFormat::from_path(Path::new("a.jpg")), None);
+        assert_eq!(QrFormat::from_path(Path::new("a")), None);
+    }
+
+    #[test]
+    fn test_unwritable_path_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("no-such-dir").join("code.png");
+        let err = render_to_file("x", &path, QrFormat::Png, QrImageOptions::default()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Cannot write QR code to {}: ", path.display())));
+    }
+
+    #[test]
+    fn test_large_bundle_splits_into_ordered_frames() {
+        let sealed = sealed_bundle();
+        assert!(sealed.len() > FRAME_PAYLOAD_BYTES);
+
+        let frames = split_frames(&sealed, FRAME_PAYLOAD_BYTES);
+        assert_eq!(frames.len(), sealed.len().div_ceil(FRAME_PAYLOAD_BYTES));
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.index, i);
+            assert_eq!(frame.total, frames.len());
+            assert!(frame.data.len() <= FRAME_PAYLOAD_BYTES);
+            assert_eq!(Frame::decode(&frame.encode()).unwrap(), *frame);
+        }
+        let joined: Vec<u8> = frames.iter().flat_map(|f| f.data.clone()).collect();
+        assert_eq!(joined, sealed);
+    }
+
+    #[test]
+    fn test_reassembles_out_of_order_frames() {
+        let sealed = sealed_bundle();
+        let mut texts: Vec<String> = split_frames(&sealed, FRAME_PAYLOAD_BYTES)
+            .iter()
+            .map(Frame::encode)
+            .collect();
+        texts.reverse();
+        texts.swap(0, 1);
+
+        let mut assembler = FrameAssembler::new();
+        for text in &texts {
+            assert!(!assembler.is_complete());
+            assembler.push(Frame::decode(text).unwrap()).unwrap();
+        }
+        // A duplicate scan changes nothing
+        assembler.push(Frame::decode(&texts[2]).unwrap()).unwrap();
+        assert!(assembler.is_complete());
+
+        let payload = assembler.finish().unwrap();
+        assert_eq!(payload, sealed);
+        let bundle = OfflineBundle::open(&payload, "pw").unwrap();
+        bundle.verify().unwrap();
+    }
+
+    #[test]
+    fn test_missing_frame_is_detected() {
+        let payload: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let frames = split_frames(&payload, 100);
+        assert_eq!(frames.len(), 10);
+
+        let mut assembler = FrameAssembler::new();
+        for frame in frames.iter().filter(|f| f.index != 6) {
+            assembler.push(frame.clone()).unwrap();
+        }
+        assert!(!assembler.is_complete());
+        assert_eq!(assembler.missing(), vec![7]);
+        let err = assembler.finish().unwrap_err();
+        assert!(
+            err.to_string().contains("Missing frame(s) 7 of 10"),
+            "{err}"
+        );
+
+        // Frames from another bundle are refused
+        let mut assembler = FrameAssembler::new();
+        assembler.push(frames[0].clone()).unwrap();
+        let other = split_frames(b"something else entirely", 100);
+        assert!(assembler.push(other[0].clone()).is_err());
+    }
+}